@@ -78,6 +78,66 @@ pub trait HasMetadata {
             .get_mut::<M>()
             .ok_or_else(|| Error::key_not_found(format!("{} not found", type_name::<M>())))
     }
+
+    /// Add a metadata to the metadata map, under `key`.
+    ///
+    /// Unlike [`Self::add_metadata`], this allows to keep multiple instances of the same type.
+    #[inline]
+    fn add_metadata_with_key<M>(&mut self, key: &str, meta: M)
+    where
+        M: SerdeAny,
+    {
+        self.metadata_map_mut().insert_keyed(key, meta);
+    }
+
+    /// Remove a metadata stored under `key` from the metadata map
+    #[inline]
+    fn remove_metadata_by_key<M>(&mut self, key: &str) -> Option<Box<M>>
+    where
+        M: SerdeAny,
+    {
+        self.metadata_map_mut().remove_keyed::<M>(key)
+    }
+
+    /// Check for a metadata stored under `key`
+    #[inline]
+    fn has_metadata_by_key<M>(&self, key: &str) -> bool
+    where
+        M: SerdeAny,
+    {
+        self.metadata_map().contains_keyed::<M>(key)
+    }
+
+    /// To get metadata stored under `key`
+    #[inline]
+    fn metadata_by_key<M>(&self, key: &str) -> Result<&M, Error>
+    where
+        M: SerdeAny,
+    {
+        self.metadata_map().get_keyed::<M>(key).ok_or_else(|| {
+            Error::key_not_found(format!("{} with key {key} not found", type_name::<M>()))
+        })
+    }
+
+    /// To get mutable metadata stored under `key`
+    #[inline]
+    fn metadata_by_key_mut<M>(&mut self, key: &str) -> Result<&mut M, Error>
+    where
+        M: SerdeAny,
+    {
+        self.metadata_map_mut()
+            .get_keyed_mut::<M>(key)
+            .ok_or_else(|| {
+                Error::key_not_found(format!("{} with key {key} not found", type_name::<M>()))
+            })
+    }
+
+    /// Iterate over the type names and approximate serialized sizes of all stored metadata,
+    /// to debug bloated states. See [`SerdeAnyMap::sizes`].
+    #[inline]
+    fn metadata_sizes(&self) -> impl Iterator<Item = (&'static str, usize)> + '_ {
+        self.metadata_map().sizes()
+    }
 }
 
 /// Trait for elements offering named metadata
@@ -11,7 +11,7 @@ use crate::{
         inmemory_ondisk::InMemoryOnDiskCorpus, ondisk::OnDiskMetadataFormat, Corpus, CorpusId,
         HasTestcase, Testcase,
     },
-    inputs::Input,
+    inputs::{Input, TargetBytesConverter},
     Error,
 };
 
@@ -177,6 +177,11 @@ where
     fn store_input_from(&self, testcase: &Testcase<Self::Input>) -> Result<(), Error> {
         self.inner.store_input_from(testcase)
     }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush()
+    }
 }
 
 impl<I> HasTestcase for CachedOnDiskCorpus<I>
@@ -280,4 +285,13 @@ impl<I> CachedOnDiskCorpus<I> {
     pub fn inner(&self) -> &InMemoryOnDiskCorpus<I> {
         &self.inner
     }
+
+    /// Also write the concrete bytes of each input next to its file,
+    /// see [`InMemoryOnDiskCorpus::export_target_bytes`]
+    pub fn export_target_bytes<TC>(&mut self, converter: TC, extension: &str)
+    where
+        TC: TargetBytesConverter<Input = I> + Send + 'static,
+    {
+        self.inner.export_target_bytes(converter, extension);
+    }
 }
@@ -4,14 +4,18 @@
 //! For a lower memory footprint, consider using [`crate::corpus::CachedOnDiskCorpus`]
 //! which only stores a certain number of [`Testcase`]s and removes additional ones in a FIFO manner.
 
-use alloc::string::String;
-use core::cell::RefCell;
+use alloc::{string::String, sync::Arc};
+use core::{
+    cell::RefCell,
+    fmt::{self, Debug, Formatter},
+};
 use std::{
     fs,
     fs::{File, OpenOptions},
     io,
     io::Write,
     path::{Path, PathBuf},
+    sync::Mutex,
 };
 
 #[cfg(feature = "gzip")]
@@ -24,7 +28,7 @@ use super::{
 };
 use crate::{
     corpus::{Corpus, CorpusId, InMemoryCorpus, Testcase},
-    inputs::Input,
+    inputs::{Input, TargetBytesConverter},
     Error, HasMetadata,
 };
 
@@ -47,6 +51,63 @@ fn try_create_new<P: AsRef<Path>>(path: P) -> Result<Option<File>, io::Error> {
     }
 }
 
+/// A converter the corpus may share with other threads
+type SharedConverter<I> = Arc<Mutex<dyn TargetBytesConverter<Input = I> + Send>>;
+
+/// Writes the concrete bytes of each input next to its file, see [`InMemoryOnDiskCorpus::export_target_bytes`]
+#[derive(Serialize, Deserialize)]
+struct TargetBytesExport<I> {
+    /// `None` after the corpus was deserialized, until the converter is set again
+    #[serde(skip, default = "Option::default")]
+    converter: Option<SharedConverter<I>>,
+    extension: String,
+}
+
+impl<I> TargetBytesExport<I> {
+    /// The converter, or an error if it was not set again after the corpus was deserialized
+    fn converter(&self) -> Result<&SharedConverter<I>, Error> {
+        self.converter.as_ref().ok_or_else(|| {
+            Error::illegal_state(format!(
+                "The corpus exports .{} files, but lost its converter on restore, call export_target_bytes again",
+                self.extension
+            ))
+        })
+    }
+}
+
+impl<I> Clone for TargetBytesExport<I> {
+    fn clone(&self) -> Self {
+        Self {
+            converter: self.converter.clone(),
+            extension: self.extension.clone(),
+        }
+    }
+}
+
+impl<I> Debug for TargetBytesExport<I> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TargetBytesExport")
+            .field("extension", &self.extension)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Removes the file at `path`, if any. Files stored before an option was enabled may not exist.
+fn remove_if_exists<P: AsRef<Path>>(path: P) -> Result<(), io::Error> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+/// Renames the file at `from`, if any. Files stored before an option was enabled may not exist.
+fn rename_if_exists<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<(), io::Error> {
+    match fs::rename(from, to) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
 /// A corpus able to store [`Testcase`]s to disk, while also keeping all of them in memory.
 ///
 /// Metadata is written to a `.<filename>.metadata` file in the same folder by default.
@@ -57,6 +118,7 @@ pub struct InMemoryOnDiskCorpus<I> {
     meta_format: Option<OnDiskMetadataFormat>,
     prefix: Option<String>,
     locking: bool,
+    export: Option<TargetBytesExport<I>>,
 }
 
 impl<I> Corpus for InMemoryOnDiskCorpus<I>
@@ -207,7 +269,31 @@ where
                 "No input available for testcase. Could not store anything.",
             ));
         };
-        input.to_file(file_path)
+        input.to_file(file_path)?;
+        if let Some(export) = &self.export {
+            let mut converter = export.converter()?.lock().unwrap();
+            let bytes = converter.to_target_bytes(input);
+            fs::write(
+                self.exported_path(testcase.filename().as_ref().unwrap(), export),
+                &*bytes,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Rewrite the metadata files, the metadata of an entry may have changed after it was stored
+    fn flush(&mut self) -> Result<(), Error> {
+        if self.meta_format.is_none() {
+            return Ok(());
+        }
+        for nth in 0..self.count_all() {
+            let id = self.nth_from_all(nth);
+            let testcase = &mut self.get_from_all(id)?.borrow_mut();
+            if testcase.filename().is_some() {
+                self.save_metadata(testcase)?;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -312,9 +398,40 @@ impl<I> InMemoryOnDiskCorpus<I> {
             meta_format,
             prefix,
             locking,
+            export: None,
         })
     }
 
+    /// Also write the concrete bytes of each input, as the `converter` creates them,
+    /// to `<filename>.<extension>` next to the file of the input.
+    ///
+    /// Use this for structured inputs, such as Nautilus trees, so that external tools, e.g., triage scripts,
+    /// can use the corpus. The exported file is renamed and removed together with the input.
+    /// Only inputs stored after this call are exported.
+    /// The corpus remembers the export when it is serialized, but not the converter: after restoring a state,
+    /// call this again, or storing an input fails with an error.
+    pub fn export_target_bytes<TC>(&mut self, converter: TC, extension: &str)
+    where
+        TC: TargetBytesConverter<Input = I> + Send + 'static,
+    {
+        self.export = Some(TargetBytesExport {
+            converter: Some(Arc::new(Mutex::new(converter))),
+            extension: extension.into(),
+        });
+    }
+
+    /// The extension of the exported concrete bytes, if any, see [`InMemoryOnDiskCorpus::export_target_bytes`]
+    #[must_use]
+    pub fn exported_extension(&self) -> Option<&str> {
+        self.export.as_ref().map(|export| export.extension.as_str())
+    }
+
+    /// The path of the exported concrete bytes of the input in the file `filename`
+    fn exported_path(&self, filename: &str, export: &TargetBytesExport<I>) -> PathBuf {
+        self.dir_path
+            .join(format!("{filename}.{}", export.extension))
+    }
+
     /// Sets the filename for a [`Testcase`].
     /// If an error gets returned from the corpus (i.e., file exists), we'll have to retry with a different filename.
     #[inline]
@@ -350,6 +467,12 @@ impl<I> InMemoryOnDiskCorpus<I> {
             let new_file_path = self.dir_path.join(&new_filename);
 
             fs::rename(testcase.file_path().as_ref().unwrap(), &new_file_path)?;
+            if let Some(export) = &self.export {
+                rename_if_exists(
+                    self.exported_path(&old_filename, export),
+                    self.exported_path(&new_filename, export),
+                )?;
+            }
 
             let new_metadata_path = {
                 if let Some(old_metadata_path) = testcase.metadata_path() {
@@ -379,6 +502,10 @@ impl<I> InMemoryOnDiskCorpus<I> {
     where
         I: Input,
     {
+        // Fail before anything is written
+        if let Some(export) = &self.export {
+            export.converter()?;
+        }
         let file_name_orig = testcase.filename_mut().take().unwrap_or_else(|| {
             // TODO walk entry metadata to ask for pieces of filename (e.g. :havoc in AFL)
             testcase.input().as_ref().unwrap().generate_name(Some(id))
@@ -409,6 +536,16 @@ impl<I> InMemoryOnDiskCorpus<I> {
         }
         *testcase.filename_mut() = Some(file_name);
 
+        self.save_metadata(testcase)?;
+        self.store_input_from(testcase)?;
+        Ok(())
+    }
+
+    /// Write the metadata file of this (already named) testcase, if metadata is stored
+    fn save_metadata(&self, testcase: &mut Testcase<I>) -> Result<(), Error>
+    where
+        I: Input,
+    {
         if self.meta_format.is_some() {
             let metafile_name = format!(".{}.metadata", testcase.filename().as_ref().unwrap());
             let metafile_path = self.dir_path.join(&metafile_name);
@@ -441,8 +578,6 @@ impl<I> InMemoryOnDiskCorpus<I> {
             fs::rename(&tmpfile_path, &metafile_path)?;
             *testcase.metadata_path_mut() = Some(metafile_path);
         }
-
-        self.store_input_from(testcase)?;
         Ok(())
     }
 
@@ -452,6 +587,9 @@ impl<I> InMemoryOnDiskCorpus<I> {
             if self.meta_format.is_some() {
                 fs::remove_file(self.dir_path.join(format!(".{filename}.metadata")))?;
             }
+            if let Some(export) = &self.export {
+                remove_if_exists(self.exported_path(filename, export))?;
+            }
             // also try to remove the corresponding `.lafl_lock` file if it still exists
             // (even though it shouldn't exist anymore, at this point in time)
             drop(fs::remove_file(
@@ -470,9 +608,33 @@ impl<I> InMemoryOnDiskCorpus<I> {
 
 #[cfg(test)]
 mod tests {
-    use std::{env, fs, io::Write};
+    use alloc::vec::Vec;
+    use std::{env, fs, io::Write, process};
+
+    use libafl_bolts::ownedref::OwnedSlice;
 
     use super::{create_new, try_create_new};
+    use crate::{
+        corpus::{Corpus, InMemoryOnDiskCorpus, Testcase},
+        inputs::{EncodedInput, TargetBytesConverter},
+    };
+
+    /// Concretizes each code to a single byte
+    struct CodesToBytes;
+
+    impl TargetBytesConverter for CodesToBytes {
+        type Input = EncodedInput;
+
+        fn to_target_bytes<'a>(&mut self, input: &'a EncodedInput) -> OwnedSlice<'a, u8> {
+            OwnedSlice::from(
+                input
+                    .codes()
+                    .iter()
+                    .map(|code| *code as u8)
+                    .collect::<Vec<_>>(),
+            )
+        }
+    }
 
     #[test]
     fn test() {
@@ -490,4 +652,57 @@ mod tests {
         drop(f);
         fs::remove_file(path).unwrap();
     }
+
+    #[test]
+    fn test_export_target_bytes() {
+        let dir = env::temp_dir().join(format!("libafl_export_target_bytes_{}", process::id()));
+        let mut corpus = InMemoryOnDiskCorpus::<EncodedInput>::no_meta(&dir).unwrap();
+        corpus.export_target_bytes(CodesToBytes, "bytes");
+        assert_eq!(corpus.exported_extension(), Some("bytes"));
+
+        let exported = |corpus: &InMemoryOnDiskCorpus<EncodedInput>, id| {
+            let testcase = corpus.get_from_all(id).unwrap().borrow();
+            let filename = testcase.filename().clone().unwrap();
+            assert!(dir.join(&filename).is_file());
+            fs::read(dir.join(format!("{filename}.bytes"))).unwrap()
+        };
+
+        let id = corpus
+            .add(Testcase::new(EncodedInput::new(vec![0x41, 0x42])))
+            .unwrap();
+        assert_eq!(exported(&corpus, id), b"AB");
+
+        corpus
+            .replace(id, Testcase::new(EncodedInput::new(vec![0x43])))
+            .unwrap();
+        assert_eq!(exported(&corpus, id), b"C");
+
+        // Pruning disables an entry by moving it, both files move along
+        corpus
+            .load_input_into(&mut corpus.get(id).unwrap().borrow_mut())
+            .unwrap();
+        let mut testcase = corpus.remove(id).unwrap();
+        testcase.set_disabled(true);
+        let id = corpus.add_disabled(testcase).unwrap();
+        assert_eq!(exported(&corpus, id), b"C");
+
+        // Removing an entry removes both files, and the exported bytes of the replaced input are gone, too
+        corpus.remove(id).unwrap();
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+        // A restored corpus does not silently stop exporting
+        let mut restored: InMemoryOnDiskCorpus<EncodedInput> =
+            postcard::from_bytes(&postcard::to_allocvec(&corpus).unwrap()).unwrap();
+        assert_eq!(restored.exported_extension(), Some("bytes"));
+        assert!(restored
+            .add(Testcase::new(EncodedInput::new(vec![0x44])))
+            .is_err());
+        restored.export_target_bytes(CodesToBytes, "bytes");
+        let id = restored
+            .add(Testcase::new(EncodedInput::new(vec![0x44])))
+            .unwrap();
+        assert_eq!(exported(&restored, id), b"D");
+        restored.remove(id).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -1,7 +1,9 @@
 //! Corpuses contain the testcases, either in memory, on disk, or somewhere else.
 
 pub mod testcase;
-pub use testcase::{HasTestcase, SchedulerTestcaseMetadata, Testcase};
+pub use testcase::{
+    AddedAtMetadata, CrashSignatureMetadata, HasTestcase, SchedulerTestcaseMetadata, Testcase,
+};
 
 pub mod inmemory;
 pub use inmemory::InMemoryCorpus;
@@ -181,6 +183,12 @@ pub trait Corpus: Sized {
     /// Get the nth corpus id; considers both enabled and disabled testcases
     fn nth_from_all(&self, nth: usize) -> CorpusId;
 
+    /// Get the nth disabled corpus id, in insertion order; considers only disabled testcases
+    fn nth_disabled(&self, nth: usize) -> CorpusId {
+        // disabled testcases are indexed after all enabled ones
+        self.nth_from_all(self.count() + nth)
+    }
+
     /// Method to load the input for this [`Testcase`] from persistent storage,
     /// if necessary, and if was not already loaded (`== Some(input)`).
     /// After this call, `testcase.input()` must always return `Some(input)`.
@@ -189,6 +197,12 @@ pub trait Corpus: Sized {
     /// Method to store the input of this `Testcase` to persistent storage, if necessary.
     fn store_input_from(&self, testcase: &Testcase<Self::Input>) -> Result<(), Error>;
 
+    /// Write everything that changed since the entries were stored, such as their metadata,
+    /// to persistent storage. Called before the fuzzer exits gracefully.
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
     /// Loads the `Input` for a given [`CorpusId`] from the [`Corpus`], and returns the clone.
     fn cloned_input_for_id(&self, id: CorpusId) -> Result<Self::Input, Error>
     where
@@ -17,7 +17,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     corpus::{CachedOnDiskCorpus, Corpus, CorpusId, HasTestcase, Testcase},
-    inputs::Input,
+    inputs::{Input, TargetBytesConverter},
     Error,
 };
 
@@ -172,6 +172,11 @@ where
     fn store_input_from(&self, testcase: &Testcase<Self::Input>) -> Result<(), Error> {
         self.inner.store_input_from(testcase)
     }
+
+    #[inline]
+    fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush()
+    }
 }
 
 impl<I> HasTestcase for OnDiskCorpus<I>
@@ -275,4 +280,13 @@ impl<I> OnDiskCorpus<I> {
     pub fn dir_path(&self) -> &PathBuf {
         &self.dir_path
     }
+
+    /// Also write the concrete bytes of each input next to its file,
+    /// see [`crate::corpus::InMemoryOnDiskCorpus::export_target_bytes`]
+    pub fn export_target_bytes<TC>(&mut self, converter: TC, extension: &str)
+    where
+        TC: TargetBytesConverter<Input = I> + Send + 'static,
+    {
+        self.inner.export_target_bytes(converter, extension);
+    }
 }
@@ -11,7 +11,7 @@ use core::{
 #[cfg(feature = "std")]
 use std::path::PathBuf;
 
-use libafl_bolts::{serdeany::SerdeAnyMap, HasLen};
+use libafl_bolts::{current_time, serdeany::SerdeAnyMap, HasLen};
 use serde::{Deserialize, Serialize};
 
 use super::Corpus;
@@ -497,6 +497,47 @@ impl SchedulerTestcaseMetadata {
 
 libafl_bolts::impl_serdeany!(SchedulerTestcaseMetadata);
 
+/// The signature of the crash a solution causes, e.g., the hash of its stacktrace.
+///
+/// The [`crate::feedbacks::NewHashFeedback`] adds this to the solutions it finds,
+/// and the [`crate::stages::SolutionPruning`] tells duplicates apart by it.
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashSignatureMetadata {
+    /// The signature
+    pub signature: u64,
+}
+
+libafl_bolts::impl_serdeany!(CrashSignatureMetadata);
+
+/// When a testcase was added to the [`Corpus`], for [`crate::stages::CorpusPruning::grace_period`].
+///
+/// The main node of a [`crate::events::CentralizedEventManager`] adds this to the testcases it accepts from secondary nodes.
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddedAtMetadata {
+    /// The time when the testcase was added
+    pub time: Duration,
+}
+
+libafl_bolts::impl_serdeany!(AddedAtMetadata);
+
+impl AddedAtMetadata {
+    /// Stamp a testcase added to the corpus now
+    #[must_use]
+    pub fn now() -> Self {
+        Self {
+            time: current_time(),
+        }
+    }
+}
+
 #[cfg(feature = "std")]
 impl<I> Drop for Testcase<I> {
     fn drop(&mut self) {
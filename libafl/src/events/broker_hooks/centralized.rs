@@ -12,7 +12,10 @@ use libafl_bolts::{
 #[cfg(feature = "llmp_compression")]
 use crate::events::COMPRESS_THRESHOLD;
 use crate::{
-    events::{BrokerEventResult, Event, _LLMP_TAG_TO_MAIN},
+    events::{
+        AckedForward, BrokerEventResult, Event, _LLMP_TAG_TO_MAIN, _LLMP_TAG_TO_MAIN_ACKED,
+        _LLMP_TAG_TO_MAIN_DELTA,
+    },
     inputs::Input,
 };
 
@@ -37,7 +40,10 @@ where
         msg: &mut [u8],
         _new_msgs: &mut Vec<(Tag, Flags, Vec<u8>)>,
     ) -> Result<LlmpMsgHookResult, Error> {
-        if *msg_tag == _LLMP_TAG_TO_MAIN {
+        if *msg_tag == _LLMP_TAG_TO_MAIN
+            || *msg_tag == _LLMP_TAG_TO_MAIN_DELTA
+            || *msg_tag == _LLMP_TAG_TO_MAIN_ACKED
+        {
             #[cfg(feature = "llmp_compression")]
             let compressor = &self.compressor;
             #[cfg(not(feature = "llmp_compression"))]
@@ -51,7 +57,11 @@ where
             } else {
                 &*msg
             };
-            let event: Event<I> = postcard::from_bytes(event_bytes)?;
+            let event: Event<I> = if *msg_tag == _LLMP_TAG_TO_MAIN_ACKED {
+                postcard::from_bytes::<AckedForward<I>>(event_bytes)?.event
+            } else {
+                postcard::from_bytes(event_bytes)?
+            };
             match Self::handle_in_broker(client_id, &event)? {
                 BrokerEventResult::Forward => Ok(LlmpMsgHookResult::ForwardToClients),
                 BrokerEventResult::Handled => Ok(LlmpMsgHookResult::Handled),
@@ -95,7 +105,10 @@ where
         event: &Event<I>,
     ) -> Result<BrokerEventResult, Error> {
         match &event {
-            Event::NewTestcase { .. } | Event::Stop => Ok(BrokerEventResult::Forward),
+            Event::NewTestcase { .. } | Event::Stop | Event::ClientIdentity { .. } => {
+                Ok(BrokerEventResult::Forward)
+            }
+
             _ => Ok(BrokerEventResult::Handled),
         }
     }
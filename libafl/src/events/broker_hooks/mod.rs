@@ -13,7 +13,7 @@ use libafl_bolts::{
 #[cfg(feature = "llmp_compression")]
 use crate::events::llmp::COMPRESS_THRESHOLD;
 use crate::{
-    events::{llmp::LLMP_TAG_EVENT_TO_BOTH, BrokerEventResult, Event},
+    events::{handle_client_lifecycle, llmp::LLMP_TAG_EVENT_TO_BOTH, BrokerEventResult, Event},
     inputs::Input,
     monitors::Monitor,
     Error,
@@ -196,7 +196,9 @@ where
             }
             Event::CustomBuf { .. } => Ok(BrokerEventResult::Forward),
             Event::Stop => Ok(BrokerEventResult::Forward),
-            //_ => Ok(BrokerEventResult::Forward),
+            Event::ClientExiting { .. } | Event::ClientIdentity { .. } => {
+                Ok(handle_client_lifecycle(monitor, client_id, event))
+            } //_ => Ok(BrokerEventResult::Forward),
         }
     }
 }
@@ -0,0 +1,747 @@
+//! The builder of a [`CentralizedEventManager`]
+
+use alloc::{boxed::Box, collections::VecDeque, string::String};
+use core::{fmt::Debug, time::Duration};
+#[cfg(unix)]
+use std::path::Path;
+use std::{env, marker::PhantomData};
+
+use hashbrown::HashMap;
+#[cfg(feature = "llmp_compression")]
+use libafl_bolts::compress::GzipCompressor;
+use libafl_bolts::{
+    llmp::{LlmpClient, LlmpClientDescription},
+    serdeany::SerdeAny,
+    shmem::{NopShMemProvider, ShMemProvider},
+    tuples::Handle,
+    ClientId,
+};
+
+use super::{
+    covered_runs, AckTracker, BoxedInputHasher, Broadcasts, CentralizedEventManager,
+    CentralizedStats, ChecksumSync, CoverageConfig, CoverageSync, DedupCache, DeltaDecoder,
+    DeltaEncoder, EventHistory, ForwardingQuota, IncompatibleHandler, Keepalive, LoadShedding,
+    MapHighWater, SheddingStats, CENTRALIZED_ROLE_ENV, DEFAULT_LOW_TRUST_REEXECS,
+    DEFAULT_MAX_DEFERRED, DEFAULT_RESTART_FLUSH_TIMEOUT, DEFAULT_SERIALIZE_PERCENTAGE_THRESHOLD,
+    DEFAULT_SERIALIZE_TIME_FACTOR,
+};
+#[cfg(feature = "llmp_compression")]
+use crate::events::llmp::COMPRESS_THRESHOLD;
+use crate::{
+    events::{llmp::UnmapWait, EventManagerHooksTuple, InputHasher, NopEventManager},
+    feedbacks::MapFeedbackMetadata,
+    inputs::NopInput,
+    monitors::ClientRegistry,
+    observers::{MapObserver, TimeObserver},
+    state::{NopState, State, UsesState},
+    Error,
+};
+
+impl
+    CentralizedEventManager<
+        NopEventManager<NopState<NopInput>>,
+        (),
+        NopState<NopInput>,
+        NopShMemProvider,
+    >
+{
+    /// Creates a builder for [`CentralizedEventManager`]
+    #[must_use]
+    pub fn builder() -> CentralizedEventManagerBuilder {
+        CentralizedEventManagerBuilder::new()
+    }
+}
+
+/// The builder or `CentralizedEventManager`
+///
+/// `B` is the [`IncompatibleHandler`] set with [`CentralizedEventManagerBuilder::on_incompatible`], if any.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug)]
+pub struct CentralizedEventManagerBuilder<B = (), H = ()> {
+    is_main: Option<bool>,
+    main_client_id: Option<ClientId>,
+    import_only: bool,
+    keepalive: Option<Duration>,
+    map_high_water: Option<f64>,
+    shutdown_deadline: Option<Duration>,
+    restart_flush_timeout: Duration,
+    unmap_wait_warning: Option<Duration>,
+    reexec_timeout: Option<Duration>,
+    serialize_time_factor: u32,
+    serialize_percentage_threshold: usize,
+    forward_map_deltas: bool,
+    low_trust_reexecs: usize,
+    skip_trusted_objectives: bool,
+    lazy_observers: bool,
+    event_history: Option<usize>,
+    register_identity: bool,
+    identity_label: Option<String>,
+    client_registry: Option<ClientRegistry>,
+    dedup: Option<usize>,
+    corpus_checksum: Option<Duration>,
+    global_coverage: Option<CoverageConfig>,
+    forwarding_quota: Option<(usize, Duration)>,
+    max_deferred: usize,
+    load_shedding: Option<usize>,
+    on_incompatible: B,
+    input_hasher: H,
+}
+
+impl Default for CentralizedEventManagerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CentralizedEventManagerBuilder {
+    /// The constructor
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            is_main: None,
+            main_client_id: None,
+            import_only: false,
+            keepalive: None,
+            map_high_water: None,
+            shutdown_deadline: None,
+            restart_flush_timeout: DEFAULT_RESTART_FLUSH_TIMEOUT,
+            unmap_wait_warning: None,
+            reexec_timeout: None,
+            serialize_time_factor: DEFAULT_SERIALIZE_TIME_FACTOR,
+            serialize_percentage_threshold: DEFAULT_SERIALIZE_PERCENTAGE_THRESHOLD,
+            forward_map_deltas: false,
+            low_trust_reexecs: DEFAULT_LOW_TRUST_REEXECS,
+            skip_trusted_objectives: false,
+            lazy_observers: false,
+            event_history: None,
+            register_identity: false,
+            identity_label: None,
+            client_registry: None,
+            dedup: None,
+            corpus_checksum: None,
+            global_coverage: None,
+            forwarding_quota: None,
+            max_deferred: DEFAULT_MAX_DEFERRED,
+            load_shedding: None,
+            on_incompatible: (),
+            input_hasher: (),
+        }
+    }
+}
+
+impl<B, H> CentralizedEventManagerBuilder<B, H> {
+    /// Make this a main evaluator node
+    #[must_use]
+    pub fn is_main(self, is_main: bool) -> Self {
+        Self {
+            is_main: Some(is_main),
+            ..self
+        }
+    }
+
+    /// The [`ClientId`] the centralized broker assigned to the main node,
+    /// for [`CentralizedEventManagerBuilder::build_from_client_infer_role`] to compare the client's id with
+    #[must_use]
+    pub fn main_client_id(self, main_client_id: ClientId) -> Self {
+        Self {
+            main_client_id: Some(main_client_id),
+            ..self
+        }
+    }
+
+    /// Make a main node import the testcases of its secondaries without re-executing them.
+    ///
+    /// Each received testcase is added to the corpus as-is, passing the observers sent along, if any,
+    /// to the feedbacks. Use this when the main node only collects the corpus and does not run the target.
+    #[must_use]
+    pub fn import_only(self, import_only: bool) -> Self {
+        Self {
+            import_only,
+            ..self
+        }
+    }
+
+    /// Make a secondary node send a lightweight keepalive to the main node if nothing else was
+    /// forwarded for `interval`, independent of how often the inner manager reports its stats.
+    ///
+    /// This keeps the centralized broker from considering quiet secondaries dead.
+    #[must_use]
+    pub fn keepalive(self, interval: Duration) -> Self {
+        Self {
+            keepalive: Some(interval),
+            ..self
+        }
+    }
+
+    /// Log a warning when sending to the main node fills the current page of the centralized
+    /// LLMP map beyond `mark`, a share from `0.0` to `1.0`, see [`CentralizedEventManager::map_usage`].
+    ///
+    /// Use this to learn when to scale out before the map runs full.
+    #[must_use]
+    pub fn map_high_water(self, mark: f64) -> Self {
+        Self {
+            map_high_water: Some(mark),
+            ..self
+        }
+    }
+
+    /// Give up waiting for the brokers to map our last messages after `deadline` when shutting down or restarting,
+    /// logging a warning, instead of blocking until they do.
+    ///
+    /// Use this so that a wedged peer cannot hang the teardown. Messages sent last may get lost then.
+    /// By default, the manager waits indefinitely before a restart, and [`SHUTDOWN_UNMAP_TIMEOUT`] on shutdown.
+    /// On shutdown, the inner manager bounds its own wait.
+    #[must_use]
+    pub fn shutdown_deadline(self, deadline: Duration) -> Self {
+        Self {
+            shutdown_deadline: Some(deadline),
+            ..self
+        }
+    }
+
+    /// Give up sending the messages a secondary node still owes the main node after `timeout` when restarting.
+    ///
+    /// Before a restart, a secondary retries the messages it could not send yet, and sends a final keepalive.
+    /// Defaults to one second.
+    #[must_use]
+    pub fn restart_flush_timeout(self, timeout: Duration) -> Self {
+        Self {
+            restart_flush_timeout: timeout,
+            ..self
+        }
+    }
+
+    /// Warn whenever a single wait for the centralized broker to map our last messages, e.g., before a restart,
+    /// takes longer than `threshold`, see [`CentralizedEventManager::unmap_wait_stats`].
+    #[must_use]
+    pub fn unmap_wait_warning(self, threshold: Duration) -> Self {
+        Self {
+            unmap_wait_warning: Some(threshold),
+            ..self
+        }
+    }
+
+    /// Treat a forwarded testcase the main node runs again as a timeout if the run takes longer than `timeout`.
+    ///
+    /// For the run, the main node lowers the timeout of the executor to `timeout`, see [`crate::executors::HasTimeout`],
+    /// so a target hanging on such a testcase cannot stall it for longer. Either way, the testcase then only goes
+    /// through the objectives, never into the corpus, and the main node goes on with the next one.
+    /// Testcases run one by one instead of in batches if this is set.
+    #[must_use]
+    pub fn reexec_timeout(self, timeout: Duration) -> Self {
+        Self {
+            reexec_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Only serialize the observers of a testcase if doing so, and deserializing them in the main node,
+    /// takes less than the execution time divided by `factor`, see [`AdaptiveSerializer::serialize_observers_adaptive`].
+    ///
+    /// Defaults to [`DEFAULT_SERIALIZE_TIME_FACTOR`], use [`crate::events::benchmark_serialization`] to measure
+    /// the serialization time of the observers.
+    #[must_use]
+    pub fn serialize_time_factor(self, factor: u32) -> Self {
+        Self {
+            serialize_time_factor: factor,
+            ..self
+        }
+    }
+
+    /// Once enough testcases were forwarded, keep serializing their observers only while more than `percentage`
+    /// of them were worth it, see [`AdaptiveSerializer::serialize_observers_adaptive`].
+    ///
+    /// Defaults to [`DEFAULT_SERIALIZE_PERCENTAGE_THRESHOLD`].
+    #[must_use]
+    pub fn serialize_percentage_threshold(self, percentage: usize) -> Self {
+        Self {
+            serialize_percentage_threshold: percentage,
+            ..self
+        }
+    }
+
+    /// Make a secondary node forward only the bytes of the serialized observers that changed since
+    /// the last testcase it forwarded, instead of the full buffer.
+    ///
+    /// Coverage maps mostly stay the same between testcases, so this shrinks the centralized traffic.
+    /// The main node reconstructs the observers for its accept decision. If it cannot, e.g., because it restarted
+    /// and lost the previous buffer, it falls back to re-executing the testcase until the next full buffer arrives.
+    /// Not supported together with `multi_machine`.
+    #[must_use]
+    pub fn forward_map_deltas(self, forward_map_deltas: bool) -> Self {
+        Self {
+            forward_map_deltas,
+            ..self
+        }
+    }
+
+    /// Make a main node run each testcase of a low-trust secondary `reexecs` times before evaluating it,
+    /// see [`CentralizedEventManager::set_trust`].
+    ///
+    /// If the target exits differently than the secondary claimed in any of these runs,
+    /// the testcase is discarded. Defaults to [`DEFAULT_LOW_TRUST_REEXECS`].
+    #[must_use]
+    pub fn low_trust_reexecs(self, reexecs: usize) -> Self {
+        Self {
+            low_trust_reexecs: reexecs,
+            ..self
+        }
+    }
+
+    /// Make a main node skip the objective for testcases a trusted secondary with a matching [`EventConfig`]
+    /// sent along with its observers, if the secondary reported [`ExitKind::Ok`].
+    ///
+    /// Such testcases ran the same target on the secondary, so only the feedback is checked, see
+    /// [`ExecutionProcessor::evaluate_execution_without_objective`].
+    /// Testcases with any other exit kind still run the objective. Defaults to `false`.
+    #[must_use]
+    pub fn skip_trusted_objectives(self, skip_trusted_objectives: bool) -> Self {
+        Self {
+            skip_trusted_objectives,
+            ..self
+        }
+    }
+
+    /// Make a main node deserialize the observers sent along with a testcase one by one, and discard the
+    /// testcase as soon as one of them rules out that it is interesting, see [`LazyObserversTuple`].
+    ///
+    /// Put the coverage map first in the observers tuple, so the other observers are only deserialized for
+    /// testcases with new coverage. Only use this if the map feedbacks alone decide, e.g., not with a
+    /// [`crate::feedbacks::NewHashFeedback`] in an OR. A map only rules out testcases if all its feedbacks
+    /// maximize it, such as [`crate::feedbacks::MaxMapFeedback`]. Observers that can not tell are always
+    /// deserialized, so the decisions stay the same as with the full deserialization otherwise. Defaults to `false`.
+    #[must_use]
+    pub fn lazy_observers(self, lazy_observers: bool) -> Self {
+        Self {
+            lazy_observers,
+            ..self
+        }
+    }
+
+    /// Make a main node remember a summary of the last `n` events it handled, see
+    /// [`CentralizedEventManager::recent_events`].
+    ///
+    /// Use this to see what the main node was busy with before it crashed or hung.
+    /// Events are recorded as soon as they arrive, and the history is logged if the process panics.
+    /// Off by default.
+    #[must_use]
+    pub fn event_history(self, n: usize) -> Self {
+        Self {
+            event_history: Some(n),
+            ..self
+        }
+    }
+
+    /// Make a main node skip forwarded testcases whose input has the same hash as one of the last `capacity`
+    /// testcases it handled, before running them, see [`CentralizedEventManagerBuilder::input_hasher`].
+    ///
+    /// Use this if several secondaries tend to find the same inputs. Off by default.
+    #[must_use]
+    pub fn dedup(self, capacity: usize) -> Self {
+        Self {
+            dedup: Some(capacity),
+            ..self
+        }
+    }
+
+    /// Make a main node broadcast the [`CorpusChecksum`] of its corpus every `interval`, and secondaries
+    /// compare it to the one of their own corpus, see [`CentralizedEventManager::checksum_mismatches`].
+    ///
+    /// Use this if the testcases the main node accepted flow back to the secondaries, which should then
+    /// have the same corpus. While testcases are in flight, the checksums may differ for a little while,
+    /// only mismatches that persist hint at a divergence. Both sides must hash inputs the same way,
+    /// see [`CentralizedEventManagerBuilder::input_hasher`]. Off by default.
+    #[must_use]
+    pub fn corpus_checksum(self, interval: Duration) -> Self {
+        Self {
+            corpus_checksum: Some(interval),
+            ..self
+        }
+    }
+
+    /// Make a main node publish the map indices covered anywhere in the fleet every `interval`,
+    /// and secondaries store them as [`GlobalCoverageMetadata`], named like the map.
+    ///
+    /// The coverage is read from the history of the map feedback of the given observer,
+    /// an index is covered if its entry is not the default. It is sent as run lengths of
+    /// covered and uncovered indices, a few hundred KB at most for sparsely covered maps of
+    /// millions of entries; the `interval` keeps the traffic down. Off by default.
+    #[must_use]
+    pub fn global_coverage<C>(self, observer: &Handle<C>, interval: Duration) -> Self
+    where
+        C: MapObserver,
+        C::Entry: Default,
+        MapFeedbackMetadata<C::Entry>: SerdeAny,
+    {
+        Self {
+            global_coverage: Some(CoverageConfig {
+                name: observer.name().clone(),
+                interval,
+                runs: covered_runs::<C::Entry>,
+            }),
+            ..self
+        }
+    }
+
+    /// Make a main node handle at most `quota` testcases of each secondary per `window`, so a single
+    /// secondary flooding it can not starve the others.
+    ///
+    /// Testcases over the quota are deferred, and only handled when no other testcases arrived, see
+    /// [`CentralizedEventManager::quota_stats`]. The events that did arrive are handled round-robin
+    /// across the secondaries of the same trust level, instead of in the order they arrived in. Off by default.
+    #[must_use]
+    pub fn forwarding_quota(self, quota: usize, window: Duration) -> Self {
+        Self {
+            forwarding_quota: Some((quota, window)),
+            ..self
+        }
+    }
+
+    /// How many testcases over the [`CentralizedEventManagerBuilder::forwarding_quota`] a main node keeps,
+    /// dropping the oldest ones beyond. Defaults to [`DEFAULT_MAX_DEFERRED`].
+    #[must_use]
+    pub fn max_deferred(self, max_deferred: usize) -> Self {
+        Self {
+            max_deferred,
+            ..self
+        }
+    }
+
+    /// Keep a secondary node fuzzing when the centralized broker does not keep up with its messages,
+    /// instead of giving up once too many pages of its LLMP sender went unread.
+    ///
+    /// While the sender is saturated, stats and logs are dropped, and testcases are held back and sent along
+    /// with later messages. Beyond `max_deferred` held back testcases, the oldest ones are dropped, with a warning.
+    /// See [`CentralizedEventManager::shedding_stats`]. Off by default.
+    #[must_use]
+    pub fn load_shedding(self, max_deferred: usize) -> Self {
+        Self {
+            load_shedding: Some(max_deferred),
+            ..self
+        }
+    }
+
+    /// Register this secondary at the main node once built, with an [`Event::ClientIdentity`],
+    /// see [`CentralizedEventManager::client_registry`]
+    #[must_use]
+    pub fn register_identity(self, register_identity: bool) -> Self {
+        Self {
+            register_identity,
+            ..self
+        }
+    }
+
+    /// Label this secondary in the [`Event::ClientIdentity`] it registers with at the main node,
+    /// this implies [`CentralizedEventManagerBuilder::register_identity`]
+    #[must_use]
+    pub fn identity_label(self, label: String) -> Self {
+        Self {
+            register_identity: true,
+            identity_label: Some(label),
+            ..self
+        }
+    }
+
+    /// Keep the identities of the secondaries of a main node in `registry`, e.g., to change its capacity,
+    /// see [`CentralizedEventManager::client_registry`]
+    #[must_use]
+    pub fn client_registry(self, registry: ClientRegistry) -> Self {
+        Self {
+            client_registry: Some(registry),
+            ..self
+        }
+    }
+
+    /// Route testcases from clients whose [`EventConfig`] does not match the one of this main node
+    /// to `handler`, instead of re-executing them locally.
+    ///
+    /// Use this when such clients may fuzz a different target, so their testcases can, e.g., be passed to
+    /// another evaluator or logged. The handler gets the [`Event::NewTestcase`] and the [`ClientId`] it came from.
+    /// Testcases of, or for, an [`EventConfig::AlwaysUnique`] config are never routed to the handler,
+    /// as they are not known to come from a different target.
+    #[must_use]
+    pub fn on_incompatible<F>(self, handler: F) -> CentralizedEventManagerBuilder<F, H> {
+        CentralizedEventManagerBuilder {
+            is_main: self.is_main,
+            main_client_id: self.main_client_id,
+            import_only: self.import_only,
+            keepalive: self.keepalive,
+            map_high_water: self.map_high_water,
+            shutdown_deadline: self.shutdown_deadline,
+            restart_flush_timeout: self.restart_flush_timeout,
+            unmap_wait_warning: self.unmap_wait_warning,
+            reexec_timeout: self.reexec_timeout,
+            serialize_time_factor: self.serialize_time_factor,
+            serialize_percentage_threshold: self.serialize_percentage_threshold,
+            forward_map_deltas: self.forward_map_deltas,
+            low_trust_reexecs: self.low_trust_reexecs,
+            skip_trusted_objectives: self.skip_trusted_objectives,
+            lazy_observers: self.lazy_observers,
+            event_history: self.event_history,
+            register_identity: self.register_identity,
+            identity_label: self.identity_label,
+            client_registry: self.client_registry,
+            dedup: self.dedup,
+            corpus_checksum: self.corpus_checksum,
+            global_coverage: self.global_coverage,
+            forwarding_quota: self.forwarding_quota,
+            max_deferred: self.max_deferred,
+            load_shedding: self.load_shedding,
+            on_incompatible: handler,
+            input_hasher: self.input_hasher,
+        }
+    }
+
+    /// Hash inputs with `hasher` wherever this manager needs to recognize the same input,
+    /// e.g., for [`CentralizedEventManagerBuilder::dedup`], see [`CentralizedEventManager::input_hash`].
+    ///
+    /// Defaults to `()`, hashing the serialized input, see [`InputHasher`].
+    #[must_use]
+    pub fn input_hasher<H2>(self, hasher: H2) -> CentralizedEventManagerBuilder<B, H2> {
+        CentralizedEventManagerBuilder {
+            is_main: self.is_main,
+            main_client_id: self.main_client_id,
+            import_only: self.import_only,
+            keepalive: self.keepalive,
+            map_high_water: self.map_high_water,
+            shutdown_deadline: self.shutdown_deadline,
+            restart_flush_timeout: self.restart_flush_timeout,
+            unmap_wait_warning: self.unmap_wait_warning,
+            reexec_timeout: self.reexec_timeout,
+            serialize_time_factor: self.serialize_time_factor,
+            serialize_percentage_threshold: self.serialize_percentage_threshold,
+            forward_map_deltas: self.forward_map_deltas,
+            low_trust_reexecs: self.low_trust_reexecs,
+            skip_trusted_objectives: self.skip_trusted_objectives,
+            lazy_observers: self.lazy_observers,
+            event_history: self.event_history,
+            register_identity: self.register_identity,
+            identity_label: self.identity_label,
+            client_registry: self.client_registry,
+            dedup: self.dedup,
+            corpus_checksum: self.corpus_checksum,
+            global_coverage: self.global_coverage,
+            forwarding_quota: self.forwarding_quota,
+            max_deferred: self.max_deferred,
+            load_shedding: self.load_shedding,
+            on_incompatible: self.on_incompatible,
+            input_hasher: hasher,
+        }
+    }
+
+    /// Creates a new [`CentralizedEventManager`].
+    pub fn build_from_client<EM, EMH, S, SP>(
+        self,
+        inner: EM,
+        hooks: EMH,
+        client: LlmpClient<SP>,
+        time_obs: Option<Handle<TimeObserver>>,
+    ) -> Result<CentralizedEventManager<EM, EMH, S, SP>, Error>
+    where
+        EM: UsesState<State = S>,
+        EMH: EventManagerHooksTuple<S>,
+        S: State,
+        SP: ShMemProvider,
+        B: IncompatibleHandler<S::Input>,
+        H: InputHasher<S::Input> + 'static,
+    {
+        let mut mgr = CentralizedEventManager {
+            inner,
+            hook_order: hooks.priority_order(),
+            hooks,
+            client,
+            #[cfg(feature = "llmp_compression")]
+            compressor: GzipCompressor::with_threshold(COMPRESS_THRESHOLD),
+            time_ref: time_obs,
+            is_main: self.is_main.unwrap_or(false),
+            import_only: self.import_only,
+            stats: CentralizedStats::default(),
+            event_counts: HashMap::default(),
+            keepalive: Keepalive::new(self.keepalive),
+            map_high_water: MapHighWater::new(self.map_high_water),
+            shutdown_deadline: self.shutdown_deadline,
+            restart_flush_timeout: self.restart_flush_timeout,
+            unmap_wait: UnmapWait::new(self.unmap_wait_warning),
+            reexec_timeout: self.reexec_timeout,
+            serialize_time_factor: self.serialize_time_factor,
+            serialize_percentage_threshold: self.serialize_percentage_threshold,
+            pending: VecDeque::new(),
+            shedding: self.load_shedding.map(|max_deferred| LoadShedding {
+                max_deferred,
+                stats: SheddingStats::default(),
+            }),
+            delta_encoder: self.forward_map_deltas.then(DeltaEncoder::default),
+            delta_decoder: DeltaDecoder::default(),
+            tap: None,
+            event_log: None,
+            on_incompatible: self.on_incompatible.into_handler(),
+            input_hasher: BoxedInputHasher {
+                hasher: Box::new(self.input_hasher),
+            },
+            dedup: self.dedup.map(DedupCache::new),
+            checksum: self.corpus_checksum.map(ChecksumSync::new),
+            coverage: self.global_coverage.map(CoverageSync::new),
+            quota: self
+                .forwarding_quota
+                .map(|(quota, window)| ForwardingQuota::new(quota, window, self.max_deferred)),
+            trust: HashMap::default(),
+            low_trust_reexecs: self.low_trust_reexecs,
+            skip_trusted_objectives: self.skip_trusted_objectives,
+            lazy_observers: self.lazy_observers,
+            acks: AckTracker::default(),
+            history: self.event_history.map(EventHistory::dumped_on_panic),
+            broadcasts: Broadcasts::default(),
+            clients: self.client_registry.unwrap_or_default(),
+            phantom: PhantomData,
+        };
+        if self.register_identity {
+            mgr.register_identity(self.identity_label)?;
+        }
+        Ok(mgr)
+    }
+
+    /// Creates a new [`CentralizedEventManager`], deciding if it is the main node, in this order:
+    ///
+    /// 1. the role set explicitly with [`CentralizedEventManagerBuilder::is_main`],
+    /// 2. the [`CENTRALIZED_ROLE_ENV`] environment variable, either `main` or `secondary`,
+    /// 3. the id of the `client`, which is the main node if it is the one set with
+    ///    [`CentralizedEventManagerBuilder::main_client_id`].
+    ///
+    /// Fails if none of them decides the role, or if the environment variable holds anything else.
+    pub fn build_from_client_infer_role<EM, EMH, S, SP>(
+        self,
+        inner: EM,
+        hooks: EMH,
+        client: LlmpClient<SP>,
+        time_obs: Option<Handle<TimeObserver>>,
+    ) -> Result<CentralizedEventManager<EM, EMH, S, SP>, Error>
+    where
+        EM: UsesState<State = S>,
+        EMH: EventManagerHooksTuple<S>,
+        S: State,
+        SP: ShMemProvider,
+        B: IncompatibleHandler<S::Input>,
+        H: InputHasher<S::Input> + 'static,
+    {
+        let is_main = self.infer_role(client.sender().id())?;
+        self.is_main(is_main)
+            .build_from_client(inner, hooks, client, time_obs)
+    }
+
+    /// If the node with the given `client_id` is the main node, see [`Self::build_from_client_infer_role`]
+    fn infer_role(&self, client_id: ClientId) -> Result<bool, Error> {
+        if let Some(is_main) = self.is_main {
+            return Ok(is_main);
+        }
+        if let Ok(role) = env::var(CENTRALIZED_ROLE_ENV) {
+            return match role.as_str() {
+                "main" => Ok(true),
+                "secondary" => Ok(false),
+                _ => Err(Error::illegal_argument(format!(
+                    "{CENTRALIZED_ROLE_ENV} must be either main or secondary, not {role:?}"
+                ))),
+            };
+        }
+        match self.main_client_id {
+            Some(main_client_id) => Ok(client_id == main_client_id),
+            None => Err(Error::illegal_argument(format!(
+                "The role of {client_id:?} is ambiguous: neither is_main, {CENTRALIZED_ROLE_ENV}, nor main_client_id is set"
+            ))),
+        }
+    }
+
+    /// Create a centralized event manager on a port
+    ///
+    /// If the port is not yet bound, it will act as a broker; otherwise, it
+    /// will act as a client.
+    pub fn build_on_port<EM, EMH, S, SP>(
+        self,
+        inner: EM,
+        hooks: EMH,
+        shmem_provider: SP,
+        port: u16,
+        time_obs: Option<Handle<TimeObserver>>,
+    ) -> Result<CentralizedEventManager<EM, EMH, S, SP>, Error>
+    where
+        EM: UsesState<State = S>,
+        EMH: EventManagerHooksTuple<S>,
+        S: State,
+        SP: ShMemProvider,
+        B: IncompatibleHandler<S::Input>,
+        H: InputHasher<S::Input> + 'static,
+    {
+        let client = LlmpClient::create_attach_to_tcp(shmem_provider, port)?;
+        self.build_from_client(inner, hooks, client, time_obs)
+    }
+
+    /// Create a centralized event manager attached over the unix domain socket at `path`
+    ///
+    /// The manager always acts as a client: the centralized broker is expected to listen on
+    /// `path`, see [`libafl_bolts::llmp::LlmpBrokerInner::launch_uds_listener_on`].
+    /// If the socket does not exist yet, this waits until the broker created it.
+    /// If it does exist, access to it is governed by the filesystem permissions of the socket file.
+    #[cfg(unix)]
+    pub fn build_on_uds<EM, EMH, P, S, SP>(
+        self,
+        inner: EM,
+        hooks: EMH,
+        shmem_provider: SP,
+        path: P,
+        time_obs: Option<Handle<TimeObserver>>,
+    ) -> Result<CentralizedEventManager<EM, EMH, S, SP>, Error>
+    where
+        EM: UsesState<State = S>,
+        EMH: EventManagerHooksTuple<S>,
+        P: AsRef<Path>,
+        S: State,
+        SP: ShMemProvider,
+        B: IncompatibleHandler<S::Input>,
+        H: InputHasher<S::Input> + 'static,
+    {
+        let client = LlmpClient::create_attach_to_uds(shmem_provider, path)?;
+        self.build_from_client(inner, hooks, client, time_obs)
+    }
+
+    /// If a client respawns, it may reuse the existing connection, previously
+    /// stored by [`LlmpClient::to_env()`].
+    pub fn build_existing_client_from_env<EM, EMH, S, SP>(
+        self,
+        inner: EM,
+        hooks: EMH,
+        shmem_provider: SP,
+        env_name: &str,
+        time_obs: Option<Handle<TimeObserver>>,
+    ) -> Result<CentralizedEventManager<EM, EMH, S, SP>, Error>
+    where
+        EM: UsesState<State = S>,
+        EMH: EventManagerHooksTuple<S>,
+        S: State,
+        SP: ShMemProvider,
+        B: IncompatibleHandler<S::Input>,
+        H: InputHasher<S::Input> + 'static,
+    {
+        let client = LlmpClient::on_existing_from_env(shmem_provider, env_name)?;
+        self.build_from_client(inner, hooks, client, time_obs)
+    }
+
+    /// Create an existing client from description
+    pub fn existing_client_from_description<EM, EMH, S, SP>(
+        self,
+        inner: EM,
+        hooks: EMH,
+        shmem_provider: SP,
+        description: &LlmpClientDescription,
+        time_obs: Option<Handle<TimeObserver>>,
+    ) -> Result<CentralizedEventManager<EM, EMH, S, SP>, Error>
+    where
+        EM: UsesState<State = S>,
+        EMH: EventManagerHooksTuple<S>,
+        S: State,
+        SP: ShMemProvider,
+        B: IncompatibleHandler<S::Input>,
+        H: InputHasher<S::Input> + 'static,
+    {
+        let client = LlmpClient::existing_client_from_description(shmem_provider, description)?;
+        self.build_from_client(inner, hooks, client, time_obs)
+    }
+}
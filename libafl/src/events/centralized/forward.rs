@@ -0,0 +1,645 @@
+//! Forwarding from the secondary nodes to the main node, and keeping both in sync
+
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::time::Duration;
+use std::marker::PhantomData;
+
+use hashbrown::HashSet;
+#[cfg(feature = "llmp_compression")]
+use libafl_bolts::llmp::LLMP_FLAG_COMPRESSED;
+use libafl_bolts::{
+    current_time,
+    llmp::{Flags, Tag, LLMP_FLAG_INITIALIZED},
+    shmem::ShMemProvider,
+    DistributedError, DistributedPhase,
+};
+
+use super::{
+    Ack, CentralizedEventManager, CorpusChecksum, CoverageSummary, GlobalCoverageMetadata,
+    PendingForward, QuotaStats, SendRetry, Shed, ToMain, _LLMP_TAG_ACK_FROM_MAIN,
+    _LLMP_TAG_BROADCAST_FROM_MAIN, _LLMP_TAG_CHECKSUM_FROM_MAIN, _LLMP_TAG_COVERAGE_FROM_MAIN,
+    _LLMP_TAG_TO_MAIN, _LLMP_TAG_TO_MAIN_DELTA, CENTRALIZED_STATS_INTERVAL, MAX_PENDING_FORWARDS,
+    MAX_SEND_ATTEMPTS,
+};
+use crate::{
+    corpus::Corpus,
+    events::{AdaptiveSerializer, Event, EventFirer, EventManagerHooksTuple, HasEventManagerId},
+    inputs::Input,
+    monitors::{
+        AggregatorOps, ClientIdentity, UserStats, UserStatsValue, CENTRALIZED_ACCEPTED_STAT,
+        CENTRALIZED_BACKLOG_STAT, CENTRALIZED_CHECKSUM_MISMATCHES_STAT, CENTRALIZED_DEFERRED_STAT,
+        CENTRALIZED_DISCARDED_STAT, CENTRALIZED_DRAIN_TIME_STAT, CENTRALIZED_DROPPED_STAT,
+        CENTRALIZED_FORWARDED_STAT, CENTRALIZED_LOOP_EVENTS_STAT, CENTRALIZED_ROLE_STAT,
+        CENTRALIZED_SELF_MESSAGES_STAT, CENTRALIZED_SEND_DROPPED_STAT,
+        CENTRALIZED_SEND_ERRORS_STAT, CENTRALIZED_SHED_DEFERRED_STAT,
+        CENTRALIZED_SHED_DROPPED_STAT, CENTRALIZED_SHED_STATS_STAT,
+    },
+    state::{HasCorpus, State, Stoppable, UsesState},
+    Error, HasNamedMetadata,
+};
+
+impl<EM, EMH, S, SP> CentralizedEventManager<EM, EMH, S, SP>
+where
+    EM: UsesState<State = S>,
+    EMH: EventManagerHooksTuple<S>,
+    S: State,
+    SP: ShMemProvider,
+{
+    /// Read the acknowledgments the main node sent to this secondary node, if it awaits any,
+    /// the operator messages of the main node, if this node handles them, its corpus checksum, and its coverage.
+    ///
+    /// All other messages the centralized broker passes on to this node are skipped.
+    pub(super) fn receive_from_main(&mut self) -> Result<(), Error> {
+        if self.acks.awaiting.is_empty()
+            && self.broadcasts.handlers.is_empty()
+            && self.checksum.is_none()
+            && self.coverage.is_none()
+        {
+            return Ok(());
+        }
+        let self_id = self.client.sender().id();
+        while let Some((client_id, tag, _flags, msg)) = self.client.recv_buf_with_flags()? {
+            if tag == _LLMP_TAG_COVERAGE_FROM_MAIN {
+                if let Some(sync) = &mut self.coverage {
+                    if client_id != self_id {
+                        sync.received = Some(postcard::from_bytes(msg)?);
+                    }
+                }
+                continue;
+            }
+            if tag == _LLMP_TAG_CHECKSUM_FROM_MAIN {
+                if let Some(sync) = &mut self.checksum {
+                    if client_id != self_id {
+                        sync.received = Some(postcard::from_bytes(msg)?);
+                    }
+                }
+                continue;
+            }
+            if tag == _LLMP_TAG_BROADCAST_FROM_MAIN {
+                if client_id != self_id && !self.broadcasts.handlers.is_empty() {
+                    match postcard::from_bytes::<Event<S::Input>>(msg)? {
+                        Event::CustomBuf { tag, buf } => {
+                            self.broadcasts.received.push_back((tag, buf));
+                        }
+                        event => log::warn!(
+                            "Skipping {} from the main node, only custom buffers are broadcast",
+                            event.name()
+                        ),
+                    }
+                }
+                continue;
+            }
+            if tag != _LLMP_TAG_ACK_FROM_MAIN {
+                continue;
+            }
+            let ack: Ack = postcard::from_bytes(msg)?;
+            if ack.client_id != self_id {
+                continue;
+            }
+            for seq in ack.seqs {
+                // The same event may have been forwarded, and acknowledged, more than once
+                if self.acks.awaiting.remove(&seq) {
+                    self.acks.acknowledged.push(seq);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Register this secondary at the main node, see [`Event::ClientIdentity`]
+    pub(super) fn register_identity(&mut self, label: Option<String>) -> Result<(), Error> {
+        if !self.is_main {
+            let event: Event<S::Input> = Event::ClientIdentity {
+                identity: ClientIdentity::current(label),
+            };
+            self.forward_to_main(_LLMP_TAG_TO_MAIN, &event)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "llmp_compression")]
+    pub(super) fn forward_to_main<T>(&mut self, tag: Tag, msg: &T) -> Result<(), Error>
+    where
+        T: ToMain,
+    {
+        let serialized = postcard::to_allocvec(msg)?;
+        // Control messages are tiny, don't waste cycles on them, whatever the threshold
+        if msg.is_control() {
+            return self.send_to_main(tag, LLMP_FLAG_INITIALIZED, serialized, msg.shed());
+        }
+        match self.compressor.maybe_compress(&serialized) {
+            Some(comp_buf) => self.send_to_main(
+                tag,
+                LLMP_FLAG_INITIALIZED | LLMP_FLAG_COMPRESSED,
+                comp_buf,
+                msg.shed(),
+            ),
+            None => self.send_to_main(tag, LLMP_FLAG_INITIALIZED, serialized, msg.shed()),
+        }
+    }
+
+    #[cfg(not(feature = "llmp_compression"))]
+    pub(super) fn forward_to_main<T>(&mut self, tag: Tag, msg: &T) -> Result<(), Error>
+    where
+        T: ToMain,
+    {
+        let serialized = postcard::to_allocvec(msg)?;
+        self.send_to_main(tag, LLMP_FLAG_INITIALIZED, serialized, msg.shed())
+    }
+
+    /// Send a message to the main node, after the pending ones.
+    /// If it cannot be sent now, it stays pending, until the next message or [`Self::flush_pending`].
+    pub(super) fn send_to_main(
+        &mut self,
+        tag: Tag,
+        flags: Flags,
+        buf: Vec<u8>,
+        shed: Shed,
+    ) -> Result<(), Error> {
+        let msg = PendingForward {
+            tag,
+            flags,
+            testcase: shed == Shed::Defer,
+            buf,
+        };
+        if self.shedding.is_some() {
+            self.send_pending()?;
+            if !self.pending.is_empty() || self.client.sender().would_block(msg.buf.len()) {
+                self.shed(msg, shed);
+                return Ok(());
+            }
+        }
+        self.pending.push_back(msg);
+        self.send_pending()?;
+        self.keepalive.last_sent = (self.keepalive.clock)();
+        self.map_high_water.check(self.map_usage());
+        Ok(())
+    }
+
+    /// Hold back, or drop, `msg` while the LLMP sender is saturated, see [`CentralizedEventManagerBuilder::load_shedding`]
+    pub(super) fn shed(&mut self, msg: PendingForward, shed: Shed) {
+        let shedding = self.shedding.as_mut().unwrap();
+        match shed {
+            Shed::Drop => {
+                shedding.stats.dropped_stats += 1;
+                return;
+            }
+            Shed::Defer => shedding.stats.deferred_testcases += 1,
+            Shed::Keep => {}
+        }
+        self.pending.push_back(msg);
+        if self.pending.iter().filter(|msg| msg.testcase).count() > shedding.max_deferred {
+            let oldest = self.pending.iter().position(|msg| msg.testcase).unwrap();
+            let dropped = self.pending.remove(oldest).unwrap();
+            shedding.stats.dropped_testcases += 1;
+            log::warn!(
+                "The centralized broker does not keep up, dropped a testcase to keep at most {} held back",
+                shedding.max_deferred
+            );
+            self.dropped_forward(&dropped);
+        }
+    }
+
+    /// Keep the delta encoding in sync with the main node after `msg` was dropped instead of sent
+    fn dropped_forward(&mut self, msg: &PendingForward) {
+        if msg.tag == _LLMP_TAG_TO_MAIN_DELTA {
+            if let Some(encoder) = &mut self.delta_encoder {
+                // The main node never sees the base this message may have set, or moved
+                encoder.reset();
+            }
+        }
+    }
+
+    /// Send the pending messages to the main node, in order, until one fails,
+    /// or, with load shedding, until the LLMP sender is saturated.
+    /// Only errors if too many messages are pending.
+    pub(super) fn send_pending(&mut self) -> Result<(), Error> {
+        let now = current_time();
+        if !self.stats.send_retry.due(now) {
+            return Ok(());
+        }
+        while let Some(msg) = self.pending.front() {
+            if self.shedding.is_some() && self.client.sender().would_block(msg.buf.len()) {
+                return Ok(());
+            }
+            let Err(err) = self
+                .client
+                .send_buf_with_flags(msg.tag, msg.flags, &msg.buf)
+            else {
+                if self.stats.send_retry.attempts > 0 {
+                    log::info!(
+                        "Sent to the main node again after {} failed attempts",
+                        self.stats.send_retry.attempts
+                    );
+                }
+                self.stats.send_retry = SendRetry::default();
+                self.pending.pop_front();
+                continue;
+            };
+            self.stats.send_errors += 1;
+            self.stats.send_retry.failed(now);
+            if self.stats.send_retry.attempts == 1 {
+                log::warn!(
+                    "Could not send to the main node, {} messages pending, backing off: {err}",
+                    self.pending.len()
+                );
+            }
+            if self.stats.send_retry.attempts >= MAX_SEND_ATTEMPTS {
+                log::warn!(
+                    "Dropping a message with tag {:?} to the main node after {MAX_SEND_ATTEMPTS} failed attempts: {err}",
+                    msg.tag
+                );
+                self.stats.send_dropped += 1;
+                self.stats.send_retry = SendRetry::default();
+                let dropped = self.pending.pop_front().unwrap();
+                self.dropped_forward(&dropped);
+                continue;
+            }
+            if self.pending.len() > MAX_PENDING_FORWARDS {
+                return Err(err.in_distributed_flow(
+                    DistributedError::new(
+                        DistributedPhase::Forwarding,
+                        format!(
+                            "Could not send to the main node, {} messages pending",
+                            self.pending.len()
+                        ),
+                    )
+                    .with_tag(msg.tag),
+                ));
+            }
+            return Ok(());
+        }
+        Ok(())
+    }
+
+    /// Retry the pending messages to the main node for at most `timeout`, without backing off.
+    /// Returns `false`, with a warning, if some are still pending.
+    pub(super) fn flush_pending(&mut self, timeout: Duration) -> bool {
+        let start = current_time();
+        loop {
+            self.stats.send_retry.next_attempt = None;
+            if let Err(err) = self.send_pending() {
+                log::warn!("Could not flush the messages to the main node: {err}");
+                return false;
+            }
+            if self.pending.is_empty() {
+                return true;
+            }
+            if current_time().saturating_sub(start) >= timeout {
+                log::warn!(
+                    "Could not send {} messages to the main node within {timeout:?}, giving up",
+                    self.pending.len()
+                );
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Send a keepalive to the main node, carrying the last known executions
+    pub(super) fn send_keepalive(&mut self) -> Result<(), Error> {
+        self.forward_to_main(
+            _LLMP_TAG_TO_MAIN,
+            &Event::<S::Input>::UpdateExecStats {
+                executions: self.keepalive.executions,
+                time: (self.keepalive.clock)(),
+                phantom: PhantomData,
+            },
+        )
+    }
+
+    /// Send a keepalive to the main node, if nothing was sent for the keepalive interval
+    pub(super) fn maybe_keepalive(&mut self) -> Result<(), Error> {
+        if self.keepalive.due() {
+            log::debug!("Sending keepalive to the main node");
+            self.send_keepalive()?;
+        }
+        Ok(())
+    }
+}
+
+impl<EM, EMH, S, SP> CentralizedEventManager<EM, EMH, S, SP>
+where
+    EM: UsesState<State = S> + EventFirer + AdaptiveSerializer + HasEventManagerId,
+    EMH: EventManagerHooksTuple<S>,
+    S: State + Stoppable + HasCorpus,
+    S::Corpus: Corpus<Input = S::Input>,
+    SP: ShMemProvider,
+{
+    /// Report the forwarding stats of this node to the inner manager as user stats,
+    /// at most every [`CENTRALIZED_STATS_INTERVAL`].
+    pub(super) fn maybe_report_stats(&mut self, state: &mut S) -> Result<(), Error> {
+        let cur = current_time();
+        if let Some(last_report) = self.stats.last_report {
+            if cur.saturating_sub(last_report) < CENTRALIZED_STATS_INTERVAL {
+                return Ok(());
+            }
+        } else {
+            // first report, announce the role of this node
+            let role = if self.is_main { "main" } else { "secondary" };
+            self.fire_user_stat(
+                state,
+                CENTRALIZED_ROLE_STAT,
+                UserStats::new(
+                    UserStatsValue::String(Cow::Borrowed(role)),
+                    AggregatorOps::None,
+                ),
+            )?;
+        }
+        self.stats.last_report = Some(cur);
+
+        let stats = if self.is_main {
+            vec![
+                (
+                    CENTRALIZED_ACCEPTED_STAT,
+                    self.stats.accepted,
+                    AggregatorOps::Sum,
+                ),
+                (
+                    CENTRALIZED_DISCARDED_STAT,
+                    self.stats.discarded,
+                    AggregatorOps::Sum,
+                ),
+                (
+                    CENTRALIZED_BACKLOG_STAT,
+                    self.stats.backlog,
+                    AggregatorOps::Max,
+                ),
+                (
+                    CENTRALIZED_SELF_MESSAGES_STAT,
+                    self.stats.self_messages,
+                    AggregatorOps::Sum,
+                ),
+            ]
+            .into_iter()
+            .chain(
+                (self.stats.main_loop.iterations > 0)
+                    .then_some([
+                        (
+                            CENTRALIZED_LOOP_EVENTS_STAT,
+                            self.stats.main_loop.last_events,
+                            AggregatorOps::Max,
+                        ),
+                        (
+                            CENTRALIZED_DRAIN_TIME_STAT,
+                            self.stats.main_loop.last_drain_time.as_millis() as u64,
+                            AggregatorOps::Max,
+                        ),
+                    ])
+                    .into_iter()
+                    .flatten(),
+            )
+            .collect()
+        } else {
+            let mut stats = vec![
+                (
+                    CENTRALIZED_FORWARDED_STAT,
+                    self.stats.forwarded,
+                    AggregatorOps::Sum,
+                ),
+                (
+                    CENTRALIZED_SEND_ERRORS_STAT,
+                    self.stats.send_errors,
+                    AggregatorOps::Sum,
+                ),
+                (
+                    CENTRALIZED_SEND_DROPPED_STAT,
+                    self.stats.send_dropped,
+                    AggregatorOps::Sum,
+                ),
+            ];
+            if let Some(sync) = &self.checksum {
+                stats.push((
+                    CENTRALIZED_CHECKSUM_MISMATCHES_STAT,
+                    sync.mismatches,
+                    AggregatorOps::Sum,
+                ));
+            }
+            if let Some(shedding) = &self.shedding {
+                stats.extend([
+                    (
+                        CENTRALIZED_SHED_STATS_STAT,
+                        shedding.stats.dropped_stats,
+                        AggregatorOps::Sum,
+                    ),
+                    (
+                        CENTRALIZED_SHED_DEFERRED_STAT,
+                        shedding.stats.deferred_testcases,
+                        AggregatorOps::Sum,
+                    ),
+                    (
+                        CENTRALIZED_SHED_DROPPED_STAT,
+                        shedding.stats.dropped_testcases,
+                        AggregatorOps::Sum,
+                    ),
+                ]);
+            }
+            stats
+        };
+        for (name, value, aggregator_op) in stats {
+            self.fire_user_stat(
+                state,
+                name,
+                UserStats::new(UserStatsValue::Number(value), aggregator_op),
+            )?;
+        }
+
+        // One stat per offending secondary, so it can be told apart in the monitor
+        let over_quota = self
+            .quota
+            .iter()
+            .flat_map(|quota| quota.clients.iter())
+            .filter(|(_, client)| client.stats != QuotaStats::default())
+            .map(|(client_id, client)| {
+                let who = self.clients.get(*client_id).map_or_else(
+                    || format!("client {}", client_id.0),
+                    |client| client.identity.to_string(),
+                );
+                (who, client.stats)
+            })
+            .collect::<Vec<_>>();
+        for (who, stats) in over_quota {
+            for (name, value) in [
+                (CENTRALIZED_DEFERRED_STAT, stats.deferred),
+                (CENTRALIZED_DROPPED_STAT, stats.dropped),
+            ] {
+                self.fire_user_stat(
+                    state,
+                    format!("{name} {who}"),
+                    UserStats::new(UserStatsValue::Number(value), AggregatorOps::None),
+                )?;
+            }
+        }
+        self.inner.report_serializer_stats(state)
+    }
+
+    /// Update the [`CorpusChecksum`] of the local corpus, then broadcast it, if this is the main node and
+    /// the interval passed, or compare it to the one of the main node, if this secondary received one
+    pub(super) fn sync_checksum(&mut self, state: &mut S) -> Result<(), Error> {
+        let Some(sync) = &self.checksum else {
+            return Ok(());
+        };
+        if self.is_main {
+            let cur = current_time();
+            if sync
+                .last_sent
+                .is_some_and(|last_sent| cur.saturating_sub(last_sent) < sync.interval)
+            {
+                return Ok(());
+            }
+            let checksum = self.update_checksum(state)?;
+            self.client.send_buf_with_flags(
+                _LLMP_TAG_CHECKSUM_FROM_MAIN,
+                LLMP_FLAG_INITIALIZED,
+                &postcard::to_allocvec(&checksum)?,
+            )?;
+            if let Some(sync) = &mut self.checksum {
+                sync.last_sent = Some(cur);
+            }
+        } else if let Some(main) = sync.received {
+            let local = self.update_checksum(state)?;
+            let Some(sync) = &mut self.checksum else {
+                return Ok(());
+            };
+            sync.received = None;
+            if local != main {
+                sync.mismatches += 1;
+                log::warn!(
+                    "The corpus of this secondary diverged from the main node: {} entries with checksum {:#x}, expected {} entries with checksum {:#x}",
+                    local.count,
+                    local.sum,
+                    main.count,
+                    main.sum
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Publish the coverage, if this is the main node and the interval passed,
+    /// or store the coverage of the main node as [`GlobalCoverageMetadata`], if this secondary received it
+    pub(super) fn sync_coverage(&mut self, state: &mut S) -> Result<(), Error>
+    where
+        S: HasNamedMetadata,
+    {
+        let Some(sync) = &mut self.coverage else {
+            return Ok(());
+        };
+        if !self.is_main {
+            if let Some(summary) = sync.received.take() {
+                let coverage = GlobalCoverageMetadata::from_runs(
+                    summary.len,
+                    &summary.runs,
+                    summary.published,
+                )?;
+                state.add_named_metadata(&summary.name, coverage);
+            }
+            return Ok(());
+        }
+        let cur = current_time();
+        if sync
+            .last_sent
+            .is_some_and(|last_sent| cur.saturating_sub(last_sent) < sync.config.interval)
+        {
+            return Ok(());
+        }
+        sync.last_sent = Some(cur);
+        let Some((len, runs)) = (sync.config.runs)(state.named_metadata_map(), &sync.config.name)
+        else {
+            return Ok(());
+        };
+        let summary = CoverageSummary {
+            name: sync.config.name.clone(),
+            len,
+            runs,
+            published: cur,
+        };
+        self.client.send_buf_with_flags(
+            _LLMP_TAG_COVERAGE_FROM_MAIN,
+            LLMP_FLAG_INITIALIZED,
+            &postcard::to_allocvec(&summary)?,
+        )
+    }
+
+    /// Bring the [`CorpusChecksum`] up to date with the corpus, hashing the inputs of new entries only
+    fn update_checksum(&mut self, state: &S) -> Result<CorpusChecksum, Error> {
+        let Some(sync) = &mut self.checksum else {
+            return Ok(CorpusChecksum::default());
+        };
+        let corpus = state.corpus();
+        let mut removed = sync.hashes.keys().copied().collect::<HashSet<_>>();
+        for id in corpus.ids() {
+            if removed.remove(&id) {
+                continue;
+            }
+            let hash = self
+                .input_hasher
+                .hasher
+                .hash_input(&corpus.cloned_input_for_id(id)?)?;
+            sync.hashes.insert(id, hash);
+            sync.checksum.add(hash);
+        }
+        for id in removed {
+            if let Some(hash) = sync.hashes.remove(&id) {
+                sync.checksum.remove(hash);
+            }
+        }
+        Ok(sync.checksum)
+    }
+
+    pub(super) fn fire_user_stat(
+        &mut self,
+        state: &mut S,
+        name: impl Into<Cow<'static, str>>,
+        value: UserStats,
+    ) -> Result<(), Error> {
+        self.inner.fire(
+            state,
+            Event::UpdateUserStats {
+                name: name.into(),
+                value,
+                phantom: PhantomData,
+            },
+        )
+    }
+
+    /// Forward a testcase to the main node, delta-encoding its observers if enabled.
+    ///
+    /// While load shedding holds messages back, the observers are sent in full, so that
+    /// dropping a held back testcase cannot leave the later ones without their base.
+    pub(super) fn forward_testcase_to_main<I>(&mut self, mut event: Event<I>) -> Result<(), Error>
+    where
+        I: Input,
+    {
+        if self.delta_encoder.is_some() && self.shedding.is_some() {
+            if let Event::NewTestcase {
+                observers_buf: Some(observers_buf),
+                ..
+            } = &event
+            {
+                self.send_pending()?;
+                if !self.pending.is_empty() || self.client.sender().would_block(observers_buf.len())
+                {
+                    self.delta_encoder.as_mut().unwrap().reset();
+                }
+            }
+        }
+        if let (
+            Some(encoder),
+            Event::NewTestcase {
+                observers_buf: observers_buf @ Some(_),
+                ..
+            },
+        ) = (&mut self.delta_encoder, &mut event)
+        {
+            let payload = encoder.encode(observers_buf.take().unwrap());
+            *observers_buf = Some(postcard::to_allocvec(&payload)?);
+            return self.forward_to_main(_LLMP_TAG_TO_MAIN_DELTA, &event);
+        }
+        self.forward_to_main(_LLMP_TAG_TO_MAIN, &event)
+    }
+}
@@ -0,0 +1,887 @@
+//! Receiving and evaluating the testcases of the secondary nodes on the main node
+
+use alloc::vec::Vec;
+use core::{cmp::Reverse, mem, time::Duration};
+use std::{io::Read, process};
+
+use hashbrown::HashMap;
+#[cfg(feature = "llmp_compression")]
+use libafl_bolts::llmp::LLMP_FLAG_COMPRESSED;
+use libafl_bolts::{
+    current_time, llmp::LLMP_FLAG_INITIALIZED, shmem::ShMemProvider, ClientId, DistributedError,
+    DistributedPhase,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Ack, AckedForward, CentralizedEventManager, EventOutcome, MainEvaluation, ObserversPayload,
+    PendingInMain, _LLMP_TAG_ACK_FROM_MAIN, _LLMP_TAG_BROADCAST_FROM_MAIN,
+    _LLMP_TAG_CHECKSUM_FROM_MAIN, _LLMP_TAG_COVERAGE_FROM_MAIN, _LLMP_TAG_TO_MAIN,
+    _LLMP_TAG_TO_MAIN_ACKED, _LLMP_TAG_TO_MAIN_DELTA, DEFAULT_TRUST, MAX_RECEIVED_PER_PROCESS,
+    SELF_MESSAGE_WARN_THRESHOLD,
+};
+use crate::{
+    corpus::{AddedAtMetadata, Corpus, CorpusId, Testcase},
+    events::{
+        framing::{self, ReadRecord},
+        AdaptiveSerializer, Event, EventConfig, EventFirer, EventManagerHooksTuple,
+        HasEventManagerId, ProvenanceMetadata,
+    },
+    executors::{Executor, ExitKind, HasObservers, HasTimeout},
+    fuzzer::{
+        EvaluatorObservers, ExecuteInputResult, ExecutionProcessor, ForcedInputMetadata,
+        HasScheduler,
+    },
+    inputs::UsesInput,
+    observers::{LazyObserversTuple, ObserversTuple},
+    schedulers::Scheduler,
+    stages::CalibrationHint,
+    state::{HasCorpus, HasExecutions, State, Stoppable, UsesState},
+    Error, HasMetadata,
+};
+
+impl<EM, EMH, S, SP> CentralizedEventManager<EM, EMH, S, SP>
+where
+    EM: UsesState<State = S> + EventFirer + AdaptiveSerializer + HasEventManagerId,
+    EMH: EventManagerHooksTuple<S>,
+    S: State + Stoppable + HasCorpus,
+    S::Corpus: Corpus<Input = S::Input>,
+    SP: ShMemProvider,
+{
+    /// Reconstruct the observers of a testcase forwarded by `client_id` with delta-encoding.
+    ///
+    /// If this is not possible, the observers are dropped, so the testcase gets re-executed.
+    pub(super) fn decode_delta_event(
+        &mut self,
+        client_id: ClientId,
+        mut event: Event<S::Input>,
+    ) -> Result<Event<S::Input>, Error> {
+        if let Event::NewTestcase { observers_buf, .. } = &mut event {
+            if let Some(buf) = observers_buf.take() {
+                let payload: ObserversPayload = postcard::from_bytes(&buf)?;
+                *observers_buf = self.delta_decoder.decode(client_id, payload);
+                if observers_buf.is_none() {
+                    log::debug!(
+                        "No base for the observer delta of {client_id:?}, re-executing the testcase"
+                    );
+                }
+            }
+        }
+        Ok(event)
+    }
+
+    pub(super) fn receive_from_secondary<E, Z>(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut <Self as UsesState>::State,
+        executor: &mut E,
+    ) -> Result<usize, Error>
+    where
+        E: Executor<Self, Z, State = <Self as UsesState>::State> + HasObservers + HasTimeout,
+        E::Observers:
+            ObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State> + Serialize,
+        <Self as UsesState>::State: UsesInput + HasExecutions + HasMetadata,
+        E::Observers: LazyObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State>,
+        Z: EvaluatorObservers<E, Self, <S::Corpus as Corpus>::Input, S>
+            + ExecutionProcessor<Self, <S::Corpus as Corpus>::Input, E::Observers, S>
+            + HasScheduler<<S::Corpus as Corpus>::Input, S>,
+    {
+        let mut received = Vec::new();
+        let mut acks = HashMap::new();
+        // The rest stays in the LLMP map until the next call
+        while received.len() < MAX_RECEIVED_PER_PROCESS {
+            let Some(next) = self.recv_from_secondary(&mut acks)? else {
+                break;
+            };
+            received.extend(next);
+        }
+        if let Some(quota) = &mut self.quota {
+            let idle = quota.quota;
+            received = quota.admit(received, current_time(), idle);
+        }
+        let count = self.handle_by_trust(fuzzer, executor, state, received)?;
+        self.send_acks(acks)?;
+        Ok(count)
+    }
+
+    /// Like [`Self::receive_from_secondary`], but handle at most one event, returning if there was one
+    pub(super) fn receive_one_from_secondary<E, Z>(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut <Self as UsesState>::State,
+        executor: &mut E,
+    ) -> Result<bool, Error>
+    where
+        E: Executor<Self, Z, State = <Self as UsesState>::State> + HasObservers + HasTimeout,
+        E::Observers:
+            ObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State> + Serialize,
+        <Self as UsesState>::State: UsesInput + HasExecutions + HasMetadata,
+        E::Observers: LazyObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State>,
+        Z: EvaluatorObservers<E, Self, <S::Corpus as Corpus>::Input, S>
+            + ExecutionProcessor<Self, <S::Corpus as Corpus>::Input, E::Observers, S>
+            + HasScheduler<<S::Corpus as Corpus>::Input, S>,
+    {
+        let mut acks = HashMap::new();
+        let mut received = Vec::new();
+        while let Some(next) = self.recv_from_secondary(&mut acks)? {
+            if let Some(next) = next {
+                received.push(next);
+                break;
+            }
+        }
+        if let Some(quota) = &mut self.quota {
+            received = quota.admit(received, current_time(), 1);
+        }
+        let count = self.handle_by_trust(fuzzer, executor, state, received)?;
+        self.send_acks(acks)?;
+        Ok(count > 0)
+    }
+
+    /// Receive the next message from a secondary, `None` if there is none.
+    ///
+    /// Returns the event to handle, if the message carried one, and collects the sequence numbers
+    /// to acknowledge once it is handled in `acks`, by secondary.
+    #[allow(clippy::type_complexity)]
+    fn recv_from_secondary(
+        &mut self,
+        acks: &mut HashMap<ClientId, Vec<u64>>,
+    ) -> Result<
+        Option<
+            Option<(
+                ClientId,
+                Event<<<Self as UsesState>::State as UsesInput>::Input>,
+            )>,
+        >,
+        Error,
+    > {
+        // TODO: Get around local event copy by moving handle_in_client
+        let self_id = self.client.sender().id();
+        let Some((client_id, tag, _flags, msg)) = self.client.recv_buf_with_flags()? else {
+            return Ok(None);
+        };
+        if tag == _LLMP_TAG_ACK_FROM_MAIN
+            || tag == _LLMP_TAG_BROADCAST_FROM_MAIN
+            || tag == _LLMP_TAG_CHECKSUM_FROM_MAIN
+            || tag == _LLMP_TAG_COVERAGE_FROM_MAIN
+        {
+            // Our own acknowledgments and operator messages, passed on to every node
+            return Ok(Some(None));
+        }
+        assert!(
+            tag == _LLMP_TAG_TO_MAIN
+                || tag == _LLMP_TAG_TO_MAIN_DELTA
+                || tag == _LLMP_TAG_TO_MAIN_ACKED,
+            "Only _LLMP_TAG_TO_MAIN parcel should have arrived in the main node!"
+        );
+
+        if client_id == self_id {
+            self.skip_self_message();
+            return Ok(Some(None));
+        }
+        let decoded = (|| -> Result<_, Error> {
+            #[cfg(not(feature = "llmp_compression"))]
+            let event_bytes = msg;
+            #[cfg(feature = "llmp_compression")]
+            let compressed;
+            #[cfg(feature = "llmp_compression")]
+            let event_bytes = if _flags & LLMP_FLAG_COMPRESSED == LLMP_FLAG_COMPRESSED {
+                compressed = self.compressor.decompress(msg)?;
+                &compressed
+            } else {
+                msg
+            };
+            if tag == _LLMP_TAG_TO_MAIN_ACKED {
+                let acked: AckedForward<_> = postcard::from_bytes(event_bytes)?;
+                Ok((Some(acked.seq), acked.event))
+            } else {
+                Ok((None, postcard::from_bytes(event_bytes)?))
+            }
+        })();
+        let decoded = match decoded {
+            Ok((seq, event)) if tag == _LLMP_TAG_TO_MAIN_DELTA => self
+                .decode_delta_event(client_id, event)
+                .map(|event| (seq, event)),
+            decoded => decoded,
+        };
+        let decoded = decoded.map_err(|err| {
+            err.in_distributed_flow(
+                DistributedError::new(
+                    DistributedPhase::Deserializing,
+                    "Could not decode a message from a secondary node",
+                )
+                .from_client(client_id)
+                .with_tag(tag),
+            )
+        });
+        let (seq, event): (
+            Option<u64>,
+            Event<<<Self as UsesState>::State as UsesInput>::Input>,
+        ) = match decoded {
+            Ok(decoded) => decoded,
+            Err(err) => {
+                // A single bad message must not take the main node down with it
+                log::warn!("Skipping a message that could not be decoded: {err}");
+                self.stats.malformed += 1;
+                return Ok(Some(None));
+            }
+        };
+        if let Some(seq) = seq {
+            acks.entry(client_id).or_default().push(seq);
+        }
+        log::debug!("Processor received message {}", event.name_detailed());
+        if let Event::ClientIdentity { identity } = event {
+            log::info!("Secondary {} is {identity}", client_id.0);
+            self.clients.register(client_id, identity, current_time());
+            return Ok(Some(None));
+        }
+        self.clients.seen(client_id, current_time());
+        Ok(Some(Some((client_id, event))))
+    }
+
+    /// Acknowledge the handled events of `acks`, with one message to each secondary
+    fn send_acks(&mut self, acks: HashMap<ClientId, Vec<u64>>) -> Result<(), Error> {
+        for (client_id, seqs) in acks {
+            let ack = Ack { client_id, seqs };
+            self.client.send_buf_with_flags(
+                _LLMP_TAG_ACK_FROM_MAIN,
+                LLMP_FLAG_INITIALIZED,
+                &postcard::to_allocvec(&ack)?,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Count a message this main node received from itself,
+    /// warning once if there are more than [`SELF_MESSAGE_WARN_THRESHOLD`]
+    fn skip_self_message(&mut self) {
+        self.stats.self_messages += 1;
+        if self.stats.self_messages == SELF_MESSAGE_WARN_THRESHOLD + 1 {
+            log::warn!(
+                "The main node received more than {SELF_MESSAGE_WARN_THRESHOLD} messages from itself, \
+                 is it also forwarding as a secondary node?"
+            );
+        }
+    }
+
+    /// Handle the `received` events, those of more trusted secondaries first,
+    /// the others in the order they arrived in, or round-robin across the secondaries with a
+    /// [`CentralizedEventManagerBuilder::forwarding_quota`]
+    pub(super) fn handle_by_trust<E, Z>(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut <Self as UsesState>::State,
+        mut received: Vec<(
+            ClientId,
+            Event<<<Self as UsesState>::State as UsesInput>::Input>,
+        )>,
+    ) -> Result<usize, Error>
+    where
+        E: Executor<Self, Z, State = <Self as UsesState>::State> + HasObservers + HasTimeout,
+        E::Observers:
+            ObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State> + Serialize,
+        <Self as UsesState>::State: UsesInput + HasExecutions + HasMetadata,
+        E::Observers: LazyObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State>,
+        Z: EvaluatorObservers<E, Self, <S::Corpus as Corpus>::Input, S>
+            + ExecutionProcessor<Self, <S::Corpus as Corpus>::Input, E::Observers, S>
+            + HasScheduler<<S::Corpus as Corpus>::Input, S>,
+    {
+        if self.quota.is_some() {
+            // The n-th event of each client goes in the n-th round, stable, so the rounds keep the arrival order
+            let mut rounds = HashMap::<ClientId, usize>::new();
+            let mut keyed = received
+                .into_iter()
+                .map(|(client_id, event)| {
+                    let round = rounds.entry(client_id).or_default();
+                    *round += 1;
+                    ((Reverse(self.trust(client_id)), *round), (client_id, event))
+                })
+                .collect::<Vec<_>>();
+            keyed.sort_by_key(|(key, _)| *key);
+            received = keyed.into_iter().map(|(_, received)| received).collect();
+        } else if !self.trust.is_empty() {
+            // Stable, so the events of each client stay in order
+            received.sort_by_key(|(client_id, _)| Reverse(self.trust(*client_id)));
+        }
+        let count = received.len();
+        // Consecutive testcases that have to be run again are evaluated together
+        let mut batch = Vec::new();
+        for (client_id, event) in received {
+            let Some(pending) = self.prepare_in_main(fuzzer, executor, state, client_id, event)?
+            else {
+                continue;
+            };
+            if let MainEvaluation::Execute = pending.evaluation {
+                batch.push(pending);
+                continue;
+            }
+            self.evaluate_batch_in_main(fuzzer, executor, state, mem::take(&mut batch))?;
+            let (res, item) = self.evaluate_in_main(fuzzer, executor, state, &pending)?;
+            self.finish_in_main(state, pending, &res, item)?;
+        }
+        self.evaluate_batch_in_main(fuzzer, executor, state, batch)?;
+        Ok(count)
+    }
+
+    /// Run a testcase of the low-trust secondary `client_id` again, see [`CentralizedEventManagerBuilder::low_trust_reexecs`].
+    ///
+    /// The input runs like any other, see [`EvaluatorObservers::run_input_unevaluated`].
+    /// Returns `false` if the target exited differently than the secondary claimed,
+    /// or `None` if the input filter of the `fuzzer` skips the input.
+    fn recheck_low_trust<E, Z>(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut <Self as UsesState>::State,
+        client_id: ClientId,
+        input: &<<Self as UsesState>::State as UsesInput>::Input,
+        exit_kind: ExitKind,
+    ) -> Result<Option<bool>, Error>
+    where
+        Z: EvaluatorObservers<E, Self, <S::Corpus as Corpus>::Input, S>,
+    {
+        for _ in 0..self.low_trust_reexecs {
+            let Some(actual) =
+                fuzzer.run_input_unevaluated(state, executor, self, input.clone())?
+            else {
+                log::debug!(
+                    "Skipping a testcase of low-trust {client_id:?}, the input filter rejects it"
+                );
+                return Ok(None);
+            };
+            if actual != exit_kind {
+                log::info!(
+                    "A testcase of low-trust {client_id:?} exited with {actual:?}, not {exit_kind:?} as claimed"
+                );
+                return Ok(Some(false));
+            }
+        }
+        Ok(Some(true))
+    }
+
+    /// Replay the events recorded by an [`EventTap`] from `reader`, handling each of them
+    /// as if it had just been received from a secondary node.
+    ///
+    /// Returns the number of replayed events.
+    pub fn replay_from<E, R, Z>(
+        &mut self,
+        mut reader: R,
+        fuzzer: &mut Z,
+        state: &mut <Self as UsesState>::State,
+        executor: &mut E,
+    ) -> Result<usize, Error>
+    where
+        E: Executor<Self, Z, State = <Self as UsesState>::State> + HasObservers + HasTimeout,
+        E::Observers:
+            ObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State> + Serialize,
+        <Self as UsesState>::State: UsesInput + HasExecutions + HasMetadata,
+        E::Observers: LazyObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State>,
+        R: Read,
+        Z: EvaluatorObservers<E, Self, <S::Corpus as Corpus>::Input, S>
+            + ExecutionProcessor<Self, <S::Corpus as Corpus>::Input, E::Observers, S>
+            + HasScheduler<<S::Corpus as Corpus>::Input, S>,
+    {
+        let mut count = 0;
+        loop {
+            let (client_id, event): (ClientId, Event<_>) = match framing::read_record(&mut reader)?
+            {
+                ReadRecord::Record(record) => record,
+                ReadRecord::End => break,
+                ReadRecord::Truncated(why) => {
+                    log::warn!("Skipping the end of the replay at record {count}, as {why}");
+                    break;
+                }
+            };
+            log::debug!("Replaying message {}", event.name_detailed());
+            self.handle_in_main(fuzzer, executor, state, client_id, event)?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Handle `event` as if this main node had just received it from the secondary `client_id`,
+    /// without going through LLMP.
+    ///
+    /// Use this to test monitors and hooks without real secondaries, or to inject faults,
+    /// e.g., events that secondaries never forward. Those are rejected with an error, like received ones.
+    pub fn inject_event<E, Z>(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut <Self as UsesState>::State,
+        executor: &mut E,
+        client_id: ClientId,
+        event: Event<<<Self as UsesState>::State as UsesInput>::Input>,
+    ) -> Result<(), Error>
+    where
+        E: Executor<Self, Z, State = <Self as UsesState>::State> + HasObservers + HasTimeout,
+        E::Observers:
+            ObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State> + Serialize,
+        <Self as UsesState>::State: UsesInput + HasExecutions + HasMetadata,
+        E::Observers: LazyObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State>,
+        Z: EvaluatorObservers<E, Self, <S::Corpus as Corpus>::Input, S>
+            + ExecutionProcessor<Self, <S::Corpus as Corpus>::Input, E::Observers, S>
+            + HasScheduler<<S::Corpus as Corpus>::Input, S>,
+    {
+        log::debug!("Injecting message {}", event.name_detailed());
+        self.handle_in_main(fuzzer, executor, state, client_id, event)
+    }
+
+    // Handle arriving events in the main node
+    pub(super) fn handle_in_main<E, Z>(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut <Self as UsesState>::State,
+        client_id: ClientId,
+        event: Event<<<Self as UsesState>::State as UsesInput>::Input>,
+    ) -> Result<(), Error>
+    where
+        E: Executor<Self, Z, State = <Self as UsesState>::State> + HasObservers + HasTimeout,
+        E::Observers:
+            ObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State> + Serialize,
+        <Self as UsesState>::State: UsesInput + HasExecutions + HasMetadata,
+        for<'a> E::Observers: Deserialize<'a> + Serialize,
+        E::Observers: LazyObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State>,
+        Z: EvaluatorObservers<E, Self, <S::Corpus as Corpus>::Input, S>
+            + ExecutionProcessor<Self, <S::Corpus as Corpus>::Input, E::Observers, S>
+            + HasScheduler<<S::Corpus as Corpus>::Input, S>,
+    {
+        if let Some(pending) = self.prepare_in_main(fuzzer, executor, state, client_id, event)? {
+            let (res, item) = self.evaluate_in_main(fuzzer, executor, state, &pending)?;
+            self.finish_in_main(state, pending, &res, item)?;
+        }
+        Ok(())
+    }
+
+    /// Check an event received from the secondary `client_id`,
+    /// returning the testcase to evaluate, if it carries one that should be evaluated
+    #[allow(clippy::type_complexity)]
+    fn prepare_in_main<E, Z>(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut <Self as UsesState>::State,
+        client_id: ClientId,
+        event: Event<<<Self as UsesState>::State as UsesInput>::Input>,
+    ) -> Result<
+        Option<PendingInMain<<<Self as UsesState>::State as UsesInput>::Input, E::Observers>>,
+        Error,
+    >
+    where
+        E: Executor<Self, Z, State = <Self as UsesState>::State> + HasObservers,
+        E::Observers:
+            ObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State> + Serialize,
+        <Self as UsesState>::State: UsesInput + HasExecutions + HasMetadata,
+        for<'a> E::Observers: Deserialize<'a> + Serialize,
+        E::Observers: LazyObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State>,
+        Z: EvaluatorObservers<E, Self, <S::Corpus as Corpus>::Input, S>
+            + ExecutionProcessor<Self, <S::Corpus as Corpus>::Input, E::Observers, S>
+            + HasScheduler<<S::Corpus as Corpus>::Input, S>,
+    {
+        log::debug!("handle_in_main!");
+
+        if let Some(tap) = &mut self.tap {
+            tap.record(client_id, &event)?;
+        }
+
+        *self.event_counts.entry(event.name()).or_default() += 1;
+        self.record_received(client_id, event.name());
+        let event_name = event.name_detailed();
+
+        let (input, exit_kind, compatible) = match &event {
+            Event::NewTestcase {
+                input,
+                client_config,
+                exit_kind,
+                forward_id,
+                ..
+            } => {
+                log::debug!(
+                    "Received {} from {client_id:?} ({client_config:?}, forward {forward_id:?})",
+                    event_name
+                );
+                (
+                    input.clone(),
+                    *exit_kind,
+                    client_config.match_with(&self.configuration()),
+                )
+            }
+            Event::Stop => {
+                state.request_stop();
+                self.record_outcome(client_id, event.name(), EventOutcome::Handled);
+                return Ok(None);
+            }
+            _ => {
+                self.record_outcome(client_id, event.name(), EventOutcome::Rejected);
+                return Err(Error::distributed(
+                    DistributedError::new(
+                        DistributedPhase::Receiving,
+                        "Received illegal message that message should not have arrived",
+                    )
+                    .from_client(client_id)
+                    .with_event(event_name),
+                ));
+            }
+        };
+
+        // Only a testcase of a fuzzer that is known to run a different config is incompatible,
+        // one of an `AlwaysUnique` config may still be run here
+        let incompatible = match &event {
+            Event::NewTestcase { client_config, .. } => {
+                let config = self.configuration();
+                !matches!(client_config, EventConfig::AlwaysUnique)
+                    && !matches!(config, EventConfig::AlwaysUnique)
+                    && !client_config.match_with(&config)
+            }
+            _ => false,
+        };
+        if incompatible {
+            if let Some(bounce) = &mut self.on_incompatible {
+                log::debug!("Bouncing {event_name} from incompatible {client_id:?}");
+                let name = event.name();
+                (bounce.handler)(event, client_id);
+                self.record_outcome(client_id, name, EventOutcome::Bounced);
+                return Ok(None);
+            }
+        }
+
+        if let Some(dedup) = &mut self.dedup {
+            if !dedup.insert(self.input_hasher.hasher.hash_input(&input)?) {
+                log::debug!("Skipping duplicate {event_name} from {client_id:?}");
+                self.stats.duplicates += 1;
+                self.record_outcome(client_id, event.name(), EventOutcome::Discarded);
+                return Ok(None);
+            }
+        }
+
+        let low_trust = self.trust(client_id) < DEFAULT_TRUST;
+        if low_trust && !self.import_only {
+            match self.recheck_low_trust(fuzzer, executor, state, client_id, &input, exit_kind)? {
+                Some(true) => (),
+                Some(false) => {
+                    self.stats.discarded += 1;
+                    self.record_outcome(client_id, event.name(), EventOutcome::Discarded);
+                    return Ok(None);
+                }
+                None => {
+                    self.record_outcome(client_id, event.name(), EventOutcome::Skipped);
+                    return Ok(None);
+                }
+            }
+        }
+
+        let observers_buf = match &event {
+            Event::NewTestcase { observers_buf, .. } => observers_buf.as_deref(),
+            _ => None,
+        };
+        let deserializing = |err: postcard::Error| {
+            Error::from(err).in_distributed_flow(
+                DistributedError::new(
+                    DistributedPhase::Deserializing,
+                    "Could not deserialize the observers of a testcase",
+                )
+                .from_client(client_id)
+                .with_event(event_name.clone()),
+            )
+        };
+        let evaluation = match observers_buf {
+            Some(observers_buf) if self.import_only => MainEvaluation::Import(Some(
+                postcard::from_bytes(observers_buf).map_err(deserializing)?,
+            )),
+            None if self.import_only => MainEvaluation::Import(None),
+            Some(observers_buf) if compatible && !low_trust && self.lazy_observers => {
+                let Some((observers, _)) = E::Observers::take_lazy(observers_buf, state)? else {
+                    log::debug!(
+                        "[{}] {} ruled out by its observers",
+                        process::id(),
+                        event_name
+                    );
+                    self.stats.discarded += 1;
+                    self.record_outcome(client_id, event.name(), EventOutcome::Discarded);
+                    return Ok(None);
+                };
+                MainEvaluation::Observers(observers)
+            }
+            Some(observers_buf) if compatible && !low_trust => MainEvaluation::Observers(
+                postcard::from_bytes(observers_buf).map_err(deserializing)?,
+            ),
+            _ => MainEvaluation::Execute,
+        };
+
+        Ok(Some(PendingInMain {
+            client_id,
+            event_name,
+            input,
+            exit_kind,
+            evaluation,
+            event,
+        }))
+    }
+
+    /// Evaluate a testcase that passed [`CentralizedEventManager::prepare_in_main`]
+    fn evaluate_in_main<E, Z>(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut <Self as UsesState>::State,
+        pending: &PendingInMain<<<Self as UsesState>::State as UsesInput>::Input, E::Observers>,
+    ) -> Result<(ExecuteInputResult, Option<CorpusId>), Error>
+    where
+        E: Executor<Self, Z, State = <Self as UsesState>::State> + HasObservers + HasTimeout,
+        E::Observers:
+            ObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State> + Serialize,
+        <Self as UsesState>::State: UsesInput + HasExecutions + HasMetadata,
+        for<'a> E::Observers: Deserialize<'a> + Serialize,
+        E::Observers: LazyObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State>,
+        Z: EvaluatorObservers<E, Self, <S::Corpus as Corpus>::Input, S>
+            + ExecutionProcessor<Self, <S::Corpus as Corpus>::Input, E::Observers, S>
+            + HasScheduler<<S::Corpus as Corpus>::Input, S>,
+    {
+        let event_name = &pending.event_name;
+        match &pending.evaluation {
+            MainEvaluation::Import(observers) => {
+                log::debug!("[{}] Importing event {}", process::id(), event_name);
+                if let Some(observers) = observers {
+                    let id = fuzzer.process_execution(
+                        state,
+                        self,
+                        &pending.input,
+                        &ExecuteInputResult::Corpus,
+                        observers,
+                    )?;
+                    Ok((ExecuteInputResult::Corpus, id))
+                } else {
+                    let id = state
+                        .corpus_mut()
+                        .add(Testcase::from(pending.input.clone()))?;
+                    fuzzer.scheduler_mut().on_add(state, id)?;
+                    Ok((ExecuteInputResult::Corpus, Some(id)))
+                }
+            }
+            MainEvaluation::Observers(observers) => {
+                #[cfg(feature = "scalability_introspection")]
+                {
+                    state.scalability_monitor_mut().testcase_with_observers += 1;
+                }
+                log::debug!(
+                    "[{}] Running fuzzer with event {}",
+                    process::id(),
+                    event_name
+                );
+                if self.skip_trusted_objectives && pending.exit_kind == ExitKind::Ok {
+                    fuzzer.evaluate_execution_without_objective(
+                        state,
+                        self,
+                        pending.input.clone(),
+                        observers,
+                        &pending.exit_kind,
+                        false,
+                    )
+                } else {
+                    fuzzer.evaluate_execution(
+                        state,
+                        self,
+                        pending.input.clone(),
+                        observers,
+                        &pending.exit_kind,
+                        false,
+                    )
+                }
+            }
+            MainEvaluation::Execute => {
+                #[cfg(feature = "scalability_introspection")]
+                {
+                    state.scalability_monitor_mut().testcase_without_observers += 1;
+                }
+                log::debug!(
+                    "[{}] Running fuzzer with event {}",
+                    process::id(),
+                    event_name
+                );
+                if let Some(timeout) = self.reexec_timeout {
+                    return self.execute_with_watchdog(fuzzer, executor, state, pending, timeout);
+                }
+                fuzzer.evaluate_input_with_observers(
+                    state,
+                    executor,
+                    self,
+                    pending.input.clone(),
+                    false,
+                )
+            }
+        }
+    }
+
+    /// Run a forwarded testcase again, as a timeout if the executor reports one or the run takes longer than `timeout`.
+    ///
+    /// The executor stops a hanging target after at most `timeout`, its own timeout is restored afterwards.
+    /// Timeouts only go through the objectives, so a hanging testcase never enters the corpus.
+    fn execute_with_watchdog<E, Z>(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut <Self as UsesState>::State,
+        pending: &PendingInMain<<<Self as UsesState>::State as UsesInput>::Input, E::Observers>,
+        timeout: Duration,
+    ) -> Result<(ExecuteInputResult, Option<CorpusId>), Error>
+    where
+        E: Executor<Self, Z, State = <Self as UsesState>::State> + HasObservers + HasTimeout,
+        E::Observers: ObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State>,
+        Z: EvaluatorObservers<E, Self, <S::Corpus as Corpus>::Input, S>
+            + ExecutionProcessor<Self, <S::Corpus as Corpus>::Input, E::Observers, S>,
+    {
+        let input = &pending.input;
+        let exec_timeout = executor.timeout();
+        executor.set_timeout(timeout.min(exec_timeout));
+        let start = current_time();
+        let run = fuzzer.run_input_unevaluated(state, executor, self, input.clone());
+        executor.set_timeout(exec_timeout);
+        let Some(mut exit_kind) = run? else {
+            return Ok((ExecuteInputResult::Skipped, None));
+        };
+        let elapsed = current_time().saturating_sub(start);
+        if exit_kind == ExitKind::Ok && elapsed > timeout {
+            exit_kind = ExitKind::Timeout;
+        }
+
+        let observers = executor.observers();
+        if exit_kind != ExitKind::Timeout {
+            return fuzzer.evaluate_execution(
+                state,
+                self,
+                input.clone(),
+                &*observers,
+                &exit_kind,
+                false,
+            );
+        }
+
+        self.stats.reexec_timeouts += 1;
+        log::warn!(
+            "{} from {:?} timed out after {elapsed:?} in the main node",
+            pending.event_name,
+            pending.client_id
+        );
+        let exec_res = match fuzzer.check_results(state, self, input, &*observers, &exit_kind)? {
+            ExecuteInputResult::Solution => ExecuteInputResult::Solution,
+            _ => ExecuteInputResult::None,
+        };
+        fuzzer.process_execution(state, self, input, &exec_res, &*observers)?;
+        Ok((exec_res, None))
+    }
+
+    /// Run the testcases of `batch`, all to be executed again, with one call to
+    /// [`EvaluatorObservers::evaluate_inputs_with_observers_batch`], and finish each of them
+    fn evaluate_batch_in_main<E, Z>(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut <Self as UsesState>::State,
+        batch: Vec<PendingInMain<<<Self as UsesState>::State as UsesInput>::Input, E::Observers>>,
+    ) -> Result<(), Error>
+    where
+        E: Executor<Self, Z, State = <Self as UsesState>::State> + HasObservers + HasTimeout,
+        E::Observers:
+            ObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State> + Serialize,
+        <Self as UsesState>::State: UsesInput + HasExecutions + HasMetadata,
+        for<'a> E::Observers: Deserialize<'a> + Serialize,
+        E::Observers: LazyObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State>,
+        Z: EvaluatorObservers<E, Self, <S::Corpus as Corpus>::Input, S>
+            + ExecutionProcessor<Self, <S::Corpus as Corpus>::Input, E::Observers, S>
+            + HasScheduler<<S::Corpus as Corpus>::Input, S>,
+    {
+        if batch.len() <= 1 || self.reexec_timeout.is_some() {
+            for pending in batch {
+                let (res, item) = self.evaluate_in_main(fuzzer, executor, state, &pending)?;
+                self.finish_in_main(state, pending, &res, item)?;
+            }
+            return Ok(());
+        }
+
+        #[cfg(feature = "scalability_introspection")]
+        {
+            state.scalability_monitor_mut().testcase_without_observers += batch.len();
+        }
+        log::debug!(
+            "[{}] Running fuzzer with a batch of {} events",
+            process::id(),
+            batch.len()
+        );
+        let inputs = batch.iter().map(|pending| pending.input.clone()).collect();
+        let results =
+            fuzzer.evaluate_inputs_with_observers_batch(state, executor, self, inputs, false)?;
+        for (pending, (res, item)) in batch.into_iter().zip(results) {
+            self.finish_in_main(state, pending, &res, item)?;
+        }
+        Ok(())
+    }
+
+    /// Pass an evaluated testcase on to the inner manager, if it was added as `item`
+    fn finish_in_main<OT>(
+        &mut self,
+        state: &mut <Self as UsesState>::State,
+        pending: PendingInMain<<<Self as UsesState>::State as UsesInput>::Input, OT>,
+        res: &ExecuteInputResult,
+        item: Option<CorpusId>,
+    ) -> Result<(), Error>
+    where
+        <Self as UsesState>::State: HasExecutions + HasMetadata,
+    {
+        let PendingInMain {
+            client_id,
+            event_name,
+            mut event,
+            ..
+        } = pending;
+        if *res == ExecuteInputResult::Skipped {
+            log::debug!(
+                "[{}] {} was skipped by the input filter",
+                process::id(),
+                event_name
+            );
+            self.record_outcome(client_id, event.name(), EventOutcome::Skipped);
+            return Ok(());
+        }
+        let Some(item) = item else {
+            log::debug!("[{}] {} was discarded...)", process::id(), event_name);
+            self.stats.discarded += 1;
+            self.record_outcome(client_id, event.name(), EventOutcome::Discarded);
+            return Ok(());
+        };
+
+        let provenance = ProvenanceMetadata::from_event(client_id, &event);
+        let added_at = AddedAtMetadata::now();
+        let mut testcase = state.corpus().get(item)?.borrow_mut();
+        if let Some(provenance) = provenance {
+            testcase.add_metadata(provenance);
+        }
+        testcase.add_metadata(added_at);
+        if matches!(event, Event::NewTestcase { forced: true, .. }) {
+            testcase.add_metadata(ForcedInputMetadata);
+        }
+        drop(testcase);
+        if let Some(event_log) = &mut self.event_log {
+            event_log.record(state, client_id, item)?;
+        }
+
+        for index in &self.hook_order {
+            self.hooks.on_fire_nth(*index, state, client_id, &event)?;
+        }
+
+        log::debug!(
+            "[{}] Adding received Testcase {} as item #{item}...",
+            process::id(),
+            event_name
+        );
+
+        // Let the receivers skip most of the calibration, see `CalibrationStage::forwarded_runs`
+        if let Event::NewTestcase { calibration, .. } = &mut event {
+            *calibration = CalibrationHint::of(state, item)?;
+        }
+        let name = event.name();
+        self.inner.fire(state, event)?;
+        self.stats.accepted += 1;
+        self.record_outcome(client_id, name, EventOutcome::Accepted);
+        Ok(())
+    }
+}
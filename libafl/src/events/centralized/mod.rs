@@ -0,0 +1,2057 @@
+//! Centralized event manager is a special event manager that will be used to achieve a more efficient message passing architecture.
+
+// Some technical details..
+// A very standard multi-process fuzzing using centralized event manager will consist of 4 components
+// 1. The "fuzzer clients", the fuzzer that will do the "normal" fuzzing
+// 2. The "centralized broker, the broker that gathers all the testcases from all the fuzzer clients
+// 3. The "main evaluator", the evaluator node that will evaluate all the testcases pass by the centralized event manager to see if the testcases are worth propagating
+// 4. The "main broker", the gathers the stats from the fuzzer clients and broadcast the newly found testcases from the main evaluator.
+
+use alloc::{
+    borrow::Cow,
+    boxed::Box,
+    collections::{BTreeSet, VecDeque},
+    string::String,
+    vec::Vec,
+};
+use core::{fmt::Debug, mem, time::Duration};
+use std::{
+    io::Write,
+    marker::PhantomData,
+    panic,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use hashbrown::{HashMap, HashSet};
+#[cfg(feature = "llmp_compression")]
+use libafl_bolts::compress::GzipCompressor;
+use libafl_bolts::{
+    current_time,
+    llmp::{Flags, LlmpClient, LlmpClientDescription, Tag, LLMP_FLAG_INITIALIZED},
+    serdeany::{NamedSerdeAnyMap, SerdeAny},
+    shmem::ShMemProvider,
+    tuples::Handle,
+    ClientId,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    corpus::{Corpus, CorpusId},
+    events::{
+        framing, llmp::UnmapWait, AdaptiveSerializer, AdaptiveSerializerStats, CentralizedMetrics,
+        CustomBufEventResult, CustomBufHandlerFn, Event, EventConfig, EventFirer, EventLogWriter,
+        EventManager, EventManagerHooksTuple, EventManagerId, EventProcessor, EventRestarter,
+        HasCentralizedMetrics, HasCustomBufHandlers, HasEventManagerId, HasPendingEvents,
+        InputHasher, LogSeverity, ProgressReporter, UnmapWaitStats, SHUTDOWN_UNMAP_TIMEOUT,
+    },
+    executors::{Executor, ExitKind, HasObservers, HasTimeout},
+    feedbacks::MapFeedbackMetadata,
+    fuzzer::{
+        BudgetKind, CampaignBudget, EvaluatorObservers, ExecutionProcessor, HasScheduler,
+        STATS_TIMEOUT_DEFAULT,
+    },
+    inputs::{Input, UsesInput},
+    monitors::{AggregatorOps, ClientRegistry, UserStats, UserStatsValue, CAMPAIGN_BUDGET_STAT},
+    observers::{LazyObserversTuple, ObserversTuple, TimeObserver},
+    stages::ReattachableEventManager,
+    state::{
+        HasCorpus, HasExecutions, HasLastReportTime, HasSolutions, HasStartTime, State, Stoppable,
+        UsesState,
+    },
+    Error, HasMetadata, HasNamedMetadata,
+};
+
+pub mod builder;
+pub use builder::*;
+
+mod forward;
+mod main_node;
+
+pub mod multi;
+pub use multi::*;
+
+#[cfg(test)]
+mod tests;
+
+pub(crate) const _LLMP_TAG_TO_MAIN: Tag = Tag(0x3453453);
+/// A [`Event::NewTestcase`] to the main node, carrying delta-encoded observers
+pub(crate) const _LLMP_TAG_TO_MAIN_DELTA: Tag = Tag(0x3453454);
+/// An event to the main node that the main node acknowledges, see [`CentralizedEventManager::forward_to_main_acked`]
+pub(crate) const _LLMP_TAG_TO_MAIN_ACKED: Tag = Tag(0x3453455);
+/// The acknowledgment of an [`_LLMP_TAG_TO_MAIN_ACKED`] message, from the main node to the secondary that sent it
+pub(crate) const _LLMP_TAG_ACK_FROM_MAIN: Tag = Tag(0x3453456);
+/// An operator message from the main node to all secondaries, see [`CentralizedEventManager::broadcast_custom`]
+pub(crate) const _LLMP_TAG_BROADCAST_FROM_MAIN: Tag = Tag(0x3453457);
+/// The [`CorpusChecksum`] of the main node, to all secondaries, see [`CentralizedEventManagerBuilder::corpus_checksum`]
+pub(crate) const _LLMP_TAG_CHECKSUM_FROM_MAIN: Tag = Tag(0x3453458);
+/// The coverage of the main node, to all secondaries, see [`CentralizedEventManagerBuilder::global_coverage`]
+pub(crate) const _LLMP_TAG_COVERAGE_FROM_MAIN: Tag = Tag(0x3453459);
+
+/// A wrapper manager to implement a main-secondary architecture with another broker
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug)]
+pub struct CentralizedEventManager<EM, EMH, S, SP>
+where
+    EM: UsesState<State = S>,
+    EMH: EventManagerHooksTuple<S>,
+    S: State,
+    SP: ShMemProvider,
+{
+    inner: EM,
+    /// The centralized LLMP client for inter process communication
+    client: LlmpClient<SP>,
+    #[cfg(feature = "llmp_compression")]
+    compressor: GzipCompressor,
+    time_ref: Option<Handle<TimeObserver>>,
+    hooks: EMH,
+    /// The order the hooks run in, see [`crate::events::EventManagerHook::priority`]
+    hook_order: Vec<usize>,
+    is_main: bool,
+    import_only: bool,
+    stats: CentralizedStats,
+    /// How many events of each kind this node forwarded to, or received as, the main node
+    event_counts: HashMap<&'static str, u64>,
+    keepalive: Keepalive,
+    map_high_water: MapHighWater,
+    shutdown_deadline: Option<Duration>,
+    restart_flush_timeout: Duration,
+    /// See [`CentralizedEventManager::unmap_wait_stats`]
+    unmap_wait: UnmapWait,
+    /// See [`CentralizedEventManagerBuilder::reexec_timeout`]
+    reexec_timeout: Option<Duration>,
+    /// See [`CentralizedEventManagerBuilder::serialize_time_factor`]
+    serialize_time_factor: u32,
+    /// See [`CentralizedEventManagerBuilder::serialize_percentage_threshold`]
+    serialize_percentage_threshold: usize,
+    /// Messages to the main node that could not be sent yet, oldest first
+    pending: VecDeque<PendingForward>,
+    /// See [`CentralizedEventManagerBuilder::load_shedding`]
+    shedding: Option<LoadShedding>,
+    delta_encoder: Option<DeltaEncoder>,
+    delta_decoder: DeltaDecoder,
+    tap: Option<EventTap>,
+    /// See [`CentralizedEventManager::set_event_log`]
+    event_log: Option<EventLogWriter>,
+    on_incompatible: Option<BounceHandler<S::Input>>,
+    /// See [`CentralizedEventManagerBuilder::input_hasher`]
+    input_hasher: BoxedInputHasher<S::Input>,
+    /// See [`CentralizedEventManagerBuilder::dedup`]
+    dedup: Option<DedupCache>,
+    /// See [`CentralizedEventManagerBuilder::corpus_checksum`]
+    checksum: Option<ChecksumSync>,
+    /// See [`CentralizedEventManagerBuilder::global_coverage`]
+    coverage: Option<CoverageSync>,
+    /// See [`CentralizedEventManagerBuilder::forwarding_quota`]
+    quota: Option<ForwardingQuota<S::Input>>,
+    /// The trust levels of secondaries, see [`CentralizedEventManager::set_trust`]
+    trust: HashMap<ClientId, i32>,
+    low_trust_reexecs: usize,
+    skip_trusted_objectives: bool,
+    lazy_observers: bool,
+    acks: AckTracker,
+    /// The events this main node handled last, see [`CentralizedEventManagerBuilder::event_history`]
+    history: Option<Arc<Mutex<EventHistory>>>,
+    broadcasts: Broadcasts<S>,
+    /// The identities of the secondaries, see [`CentralizedEventManager::client_registry`]
+    clients: ClientRegistry,
+    phantom: PhantomData<S>,
+}
+
+/// Receives the testcases the main node got from clients with an incompatible [`EventConfig`],
+/// see [`CentralizedEventManagerBuilder::on_incompatible`].
+///
+/// Implemented for any `FnMut(Event<I>, ClientId)`, and for `()`, meaning no handler.
+pub trait IncompatibleHandler<I>
+where
+    I: Input,
+{
+    /// Box this handler up for the [`CentralizedEventManager`], `None` if there is no handler
+    fn into_handler(self) -> Option<BounceHandler<I>>;
+}
+
+impl<I> IncompatibleHandler<I> for ()
+where
+    I: Input,
+{
+    fn into_handler(self) -> Option<BounceHandler<I>> {
+        None
+    }
+}
+
+impl<F, I> IncompatibleHandler<I> for F
+where
+    F: FnMut(Event<I>, ClientId) + 'static,
+    I: Input,
+{
+    fn into_handler(self) -> Option<BounceHandler<I>> {
+        Some(BounceHandler {
+            handler: Box::new(self),
+        })
+    }
+}
+
+/// A boxed [`IncompatibleHandler`], as stored in the [`CentralizedEventManager`]
+pub struct BounceHandler<I>
+where
+    I: Input,
+{
+    handler: Box<dyn FnMut(Event<I>, ClientId)>,
+}
+
+impl<I> Debug for BounceHandler<I>
+where
+    I: Input,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BounceHandler").finish_non_exhaustive()
+    }
+}
+
+/// A boxed [`InputHasher`], as stored in the [`CentralizedEventManager`]
+pub struct BoxedInputHasher<I> {
+    hasher: Box<dyn InputHasher<I>>,
+}
+
+impl<I> Debug for BoxedInputHasher<I> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BoxedInputHasher").finish_non_exhaustive()
+    }
+}
+
+/// What the forwarding quota of a main node did to the testcases of one secondary,
+/// see [`CentralizedEventManagerBuilder::forwarding_quota`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaStats {
+    /// The testcases that arrived while the secondary was over its quota
+    pub deferred: u64,
+    /// The deferred testcases that were dropped to make room for newer ones
+    pub dropped: u64,
+}
+
+/// The recent testcases of one secondary, see [`ForwardingQuota`]
+#[derive(Debug, Default, Clone)]
+struct ClientQuota {
+    /// When the testcases in the current window arrived, oldest first
+    received: VecDeque<Duration>,
+    stats: QuotaStats,
+    /// When this main node last warned about the secondary
+    warned: Option<Duration>,
+}
+
+/// Limits how many testcases of each secondary a main node handles per window,
+/// see [`CentralizedEventManagerBuilder::forwarding_quota`]
+#[derive(Debug)]
+struct ForwardingQuota<I>
+where
+    I: Input,
+{
+    quota: usize,
+    window: Duration,
+    max_deferred: usize,
+    clients: HashMap<ClientId, ClientQuota>,
+    /// The testcases over the quota, oldest first
+    deferred: VecDeque<(ClientId, Event<I>)>,
+}
+
+impl<I> ForwardingQuota<I>
+where
+    I: Input,
+{
+    fn new(quota: usize, window: Duration, max_deferred: usize) -> Self {
+        Self {
+            quota: quota.max(1),
+            window,
+            max_deferred: max_deferred.max(1),
+            clients: HashMap::default(),
+            deferred: VecDeque::new(),
+        }
+    }
+
+    /// The events of `received` to handle now, deferring the testcases of secondaries over their quota.
+    /// If there is nothing else to handle, up to `idle` deferred testcases are handled instead.
+    fn admit(
+        &mut self,
+        received: Vec<(ClientId, Event<I>)>,
+        now: Duration,
+        idle: usize,
+    ) -> Vec<(ClientId, Event<I>)> {
+        let mut admitted = Vec::with_capacity(received.len());
+        for (client_id, event) in received {
+            if !matches!(event, Event::NewTestcase { .. }) {
+                admitted.push((client_id, event));
+                continue;
+            }
+            let client = self.clients.entry(client_id).or_default();
+            while client
+                .received
+                .front()
+                .is_some_and(|time| now.saturating_sub(*time) >= self.window)
+            {
+                client.received.pop_front();
+            }
+            if client.received.len() < self.quota {
+                client.received.push_back(now);
+                admitted.push((client_id, event));
+                continue;
+            }
+
+            client.stats.deferred += 1;
+            if client
+                .warned
+                .is_none_or(|warned| now.saturating_sub(warned) >= self.window)
+            {
+                client.warned = Some(now);
+                log::warn!(
+                    "Secondary {} sent more than {} testcases in {:?}, deferring the rest",
+                    client_id.0,
+                    self.quota,
+                    self.window
+                );
+            }
+            if self.deferred.len() >= self.max_deferred {
+                if let Some((dropped, _)) = self.deferred.pop_front() {
+                    self.clients.entry(dropped).or_default().stats.dropped += 1;
+                }
+            }
+            self.deferred.push_back((client_id, event));
+        }
+        if admitted.is_empty() {
+            let idle = idle.min(self.deferred.len());
+            admitted.extend(self.deferred.drain(..idle));
+        }
+        admitted
+    }
+}
+
+/// The hashes of the last testcases a main node handled, see [`CentralizedEventManagerBuilder::dedup`]
+#[derive(Debug, Clone)]
+struct DedupCache {
+    capacity: usize,
+    hashes: HashSet<u64>,
+    /// The hashes in `hashes`, oldest first
+    order: VecDeque<u64>,
+}
+
+impl DedupCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            hashes: HashSet::with_capacity(capacity),
+            order: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Remember `hash`, evicting the oldest one if full. Returns `false` if it is already known.
+    fn insert(&mut self, hash: u64) -> bool {
+        if self.capacity == 0 {
+            return true;
+        }
+        if !self.hashes.insert(hash) {
+            return false;
+        }
+        if self.order.len() == self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.hashes.remove(&oldest);
+            }
+        }
+        self.order.push_back(hash);
+        true
+    }
+}
+
+/// A checksum of the inputs of a corpus, which does not depend on the order of its entries,
+/// see [`CentralizedEventManagerBuilder::corpus_checksum`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CorpusChecksum {
+    /// The entries of the corpus
+    pub count: u64,
+    /// The wrapping sum of the hashes of their inputs.
+    /// Like a hash of the sorted hashes, it does not depend on the order, but is cheap to update.
+    pub sum: u64,
+}
+
+impl CorpusChecksum {
+    fn add(&mut self, hash: u64) {
+        self.count += 1;
+        self.sum = self.sum.wrapping_add(hash);
+    }
+
+    fn remove(&mut self, hash: u64) {
+        self.count -= 1;
+        self.sum = self.sum.wrapping_sub(hash);
+    }
+}
+
+/// Keeps the [`CorpusChecksum`] of the local corpus up to date, hashing only the entries added since,
+/// and the checksum of the main node to compare it to
+#[derive(Debug, Clone)]
+struct ChecksumSync {
+    /// How often a main node broadcasts its checksum
+    interval: Duration,
+    last_sent: Option<Duration>,
+    /// The hashes of the inputs of the corpus entries, as of the last update
+    hashes: HashMap<CorpusId, u64>,
+    checksum: CorpusChecksum,
+    /// The checksum a secondary received from the main node, and did not compare yet
+    received: Option<CorpusChecksum>,
+    mismatches: u64,
+}
+
+impl ChecksumSync {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_sent: None,
+            hashes: HashMap::default(),
+            checksum: CorpusChecksum::default(),
+            received: None,
+            mismatches: 0,
+        }
+    }
+}
+
+/// The map indices covered anywhere in the fleet, as the main node of a [`CentralizedEventManager`]
+/// last published them, see [`CentralizedEventManagerBuilder::global_coverage`].
+///
+/// A secondary node stores it as named metadata of its state, by the name of the map.
+/// Schedulers and mutators can use it to focus on the regions that are rarely covered fleet-wide,
+/// instead of those rarely covered by this node.
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlobalCoverageMetadata {
+    /// One bit per map index, set if covered
+    bits: Vec<u64>,
+    len: usize,
+    covered: usize,
+    /// When the main node published the coverage
+    published: Duration,
+}
+
+libafl_bolts::impl_serdeany!(GlobalCoverageMetadata);
+
+impl GlobalCoverageMetadata {
+    /// Unpack the run lengths of a [`CoverageSummary`]
+    fn from_runs(len: usize, runs: &[u64], published: Duration) -> Result<Self, Error> {
+        let mut bits = vec![0_u64; len.div_ceil(64)];
+        let (mut idx, mut covered) = (0_usize, 0);
+        for (nth, run) in runs.iter().enumerate() {
+            let run = usize::try_from(*run)
+                .ok()
+                .filter(|run| idx + run <= len)
+                .ok_or_else(|| {
+                    Error::illegal_argument("The runs of the global coverage exceed the map")
+                })?;
+            if nth % 2 == 1 {
+                for set in idx..idx + run {
+                    bits[set / 64] |= 1 << (set % 64);
+                }
+                covered += run;
+            }
+            idx += run;
+        }
+        Ok(Self {
+            bits,
+            len,
+            covered,
+            published,
+        })
+    }
+
+    /// If the map index `idx` is covered anywhere in the fleet
+    #[must_use]
+    pub fn is_covered(&self, idx: usize) -> bool {
+        idx < self.len && self.bits[idx / 64] & (1 << (idx % 64)) != 0
+    }
+
+    /// The number of map indices covered anywhere in the fleet
+    #[must_use]
+    pub fn covered(&self) -> usize {
+        self.covered
+    }
+
+    /// The size of the map
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// If the map is empty
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// When the main node published the coverage
+    #[must_use]
+    pub fn published(&self) -> Duration {
+        self.published
+    }
+}
+
+/// The run lengths of the covered indices of the history of a map feedback, see [`covered_runs`]
+type CoveredRunsFn = fn(&NamedSerdeAnyMap, &str) -> Option<(usize, Vec<u64>)>;
+
+/// The run lengths of the indices of the map feedback history `name` that are covered, i.e., not the default,
+/// alternating between uncovered and covered runs, starting with an uncovered one.
+///
+/// This keeps the message small for large, sparsely covered maps.
+fn covered_runs<T>(metadata: &NamedSerdeAnyMap, name: &str) -> Option<(usize, Vec<u64>)>
+where
+    T: PartialEq + Default,
+    MapFeedbackMetadata<T>: SerdeAny,
+{
+    let history = &metadata.get::<MapFeedbackMetadata<T>>(name)?.history_map;
+    let initial = T::default();
+    let mut runs = Vec::new();
+    let (mut covered, mut run) = (false, 0_u64);
+    for entry in history {
+        if (*entry != initial) != covered {
+            runs.push(run);
+            covered = !covered;
+            run = 0;
+        }
+        run += 1;
+    }
+    runs.push(run);
+    Some((history.len(), runs))
+}
+
+/// What a main node publishes about its coverage, see [`CentralizedEventManagerBuilder::global_coverage`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CoverageSummary {
+    name: Cow<'static, str>,
+    len: usize,
+    runs: Vec<u64>,
+    published: Duration,
+}
+
+/// How a main node publishes its coverage, see [`CentralizedEventManagerBuilder::global_coverage`]
+#[derive(Debug, Clone)]
+struct CoverageConfig {
+    name: Cow<'static, str>,
+    interval: Duration,
+    runs: CoveredRunsFn,
+}
+
+/// The state of publishing the coverage on a main node, and of receiving it on a secondary
+#[derive(Debug, Clone)]
+struct CoverageSync {
+    config: CoverageConfig,
+    last_sent: Option<Duration>,
+    received: Option<CoverageSummary>,
+}
+
+impl CoverageSync {
+    fn new(config: CoverageConfig) -> Self {
+        Self {
+            config,
+            last_sent: None,
+            received: None,
+        }
+    }
+}
+
+/// Records the events arriving in a main node, so they can be replayed
+/// with [`CentralizedEventManager::replay_from`] later.
+///
+/// Each record is the little-endian `u32` length of the record, followed by the
+/// `postcard`-serialized tuple of the sending [`ClientId`] and the [`Event`].
+pub struct EventTap {
+    writer: Box<dyn Write>,
+}
+
+impl Debug for EventTap {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EventTap").finish_non_exhaustive()
+    }
+}
+
+impl EventTap {
+    /// Create a new [`EventTap`], writing all records to `writer`
+    pub fn new<W>(writer: W) -> Self
+    where
+        W: Write + 'static,
+    {
+        Self {
+            writer: Box::new(writer),
+        }
+    }
+
+    /// Append a record for an event received from `client_id`
+    fn record<I>(&mut self, client_id: ClientId, event: &Event<I>) -> Result<(), Error>
+    where
+        I: Input,
+    {
+        framing::write_record(&mut self.writer, &(client_id, event))
+    }
+}
+
+/// The trust level of secondaries without one, see [`CentralizedEventManager::set_trust`]
+pub const DEFAULT_TRUST: i32 = 0;
+
+/// How often a main node re-runs testcases of low-trust secondaries by default,
+/// see [`CentralizedEventManagerBuilder::low_trust_reexecs`]
+pub const DEFAULT_LOW_TRUST_REEXECS: usize = 2;
+
+/// How many testcases over their quota a main node keeps by default,
+/// see [`CentralizedEventManagerBuilder::max_deferred`]
+pub const DEFAULT_MAX_DEFERRED: usize = 4096;
+
+/// The default time factor of the adaptive observer serialization of a [`CentralizedEventManager`],
+/// twice the one of the [`LlmpEventManager`] since the observers are serialized twice
+pub const DEFAULT_SERIALIZE_TIME_FACTOR: u32 = 4;
+
+/// The default percentage of executions for which the observers of a [`CentralizedEventManager`] must have been
+/// serialized before the adaptive serialization may skip them
+pub const DEFAULT_SERIALIZE_PERCENTAGE_THRESHOLD: usize = 80;
+
+/// The environment variable [`CentralizedEventManagerBuilder::build_from_client_infer_role`] reads the role from,
+/// either `main` or `secondary`
+pub const CENTRALIZED_ROLE_ENV: &str = "LIBAFL_CENTRALIZED_ROLE";
+
+/// How many messages a main node may receive from itself before it warns about a likely misconfiguration,
+/// such as a node that is both main and secondary forwarding to itself
+const SELF_MESSAGE_WARN_THRESHOLD: u64 = 16;
+
+/// How often a [`CentralizedEventManager`] reports its forwarding stats
+const CENTRALIZED_STATS_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The forwarding stats of a [`CentralizedEventManager`], reported as user stats
+#[derive(Debug, Default, Clone, Copy)]
+struct CentralizedStats {
+    /// Testcases this secondary node forwarded to the main node
+    forwarded: u64,
+    /// Forwarded testcases this main node accepted
+    accepted: u64,
+    /// Forwarded testcases this main node discarded
+    discarded: u64,
+    /// Forwarded messages this main node handled in the last `process` call
+    backlog: u64,
+    /// Messages this main node received from itself, and skipped
+    self_messages: u64,
+    /// Messages from secondaries this main node could not decode, and skipped
+    malformed: u64,
+    /// Forwarded testcases that timed out when this main node ran them again
+    reexec_timeouts: u64,
+    /// Forwarded testcases this main node skipped as duplicates, see [`CentralizedEventManagerBuilder::dedup`]
+    duplicates: u64,
+    /// Failed attempts of this secondary node to send a message to the main node
+    send_errors: u64,
+    /// Messages this secondary node dropped, as they could not be sent after [`MAX_SEND_ATTEMPTS`] attempts
+    send_dropped: u64,
+    /// The retries of the oldest pending message to the main node
+    send_retry: SendRetry,
+    /// See [`CentralizedEventManager::run_main_loop`]
+    main_loop: MainLoopStats,
+    /// The last time the stats were reported, `None` if they were never reported
+    last_report: Option<Duration>,
+}
+
+/// Statistics of [`CentralizedEventManager::run_main_loop`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MainLoopStats {
+    /// How often the loop polled for events
+    pub iterations: u64,
+    /// The events the loop handled in total
+    pub events: u64,
+    /// The events handled in the last iteration that handled any
+    pub last_events: u64,
+    /// How long draining the events took in the last iteration that handled any
+    pub last_drain_time: Duration,
+}
+
+/// Why [`CentralizedEventManager::run_main_loop`] returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MainLoopExit {
+    /// A stop was requested, see [`Stoppable`]
+    Stopped,
+    /// A budget of the campaign ran out
+    BudgetExhausted(BudgetKind),
+    /// The broker is shutting down
+    ShuttingDown,
+}
+
+/// Makes sure a secondary node sends something to the main node at least every `interval`,
+/// so the centralized broker does not consider it dead.
+#[derive(Debug, Clone, Copy)]
+struct Keepalive {
+    /// The maximum time between two messages to the main node, `None` to not send keepalives
+    interval: Option<Duration>,
+    /// The last time a message went to the main node
+    last_sent: Duration,
+    /// The last known executions of this node, sent along with keepalives
+    executions: u64,
+    /// The source of the current time
+    clock: fn() -> Duration,
+}
+
+impl Keepalive {
+    fn new(interval: Option<Duration>) -> Self {
+        let clock = current_time;
+        Self {
+            interval,
+            last_sent: clock(),
+            executions: 0,
+            clock,
+        }
+    }
+
+    /// Checks if a keepalive needs to be sent now
+    fn due(&self) -> bool {
+        self.interval
+            .is_some_and(|interval| (self.clock)().saturating_sub(self.last_sent) >= interval)
+    }
+}
+
+/// How much of the current page of the centralized LLMP map is in use,
+/// see [`CentralizedEventManager::map_usage`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MapUsage {
+    /// The bytes in use
+    pub used: usize,
+    /// The bytes the page can hold
+    pub capacity: usize,
+}
+
+impl MapUsage {
+    /// The share of the capacity in use, from `0.0` to `1.0`
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn ratio(&self) -> f64 {
+        if self.capacity == 0 {
+            0.0
+        } else {
+            self.used as f64 / self.capacity as f64
+        }
+    }
+}
+
+/// What the main node did with an event it received, see [`EventSummary`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventOutcome {
+    /// The testcase was added to the corpus, and passed on to the inner manager
+    Accepted,
+    /// The testcase was not interesting, or did not behave as claimed
+    Discarded,
+    /// The input filter of the fuzzer skipped the testcase, so it was not run or evaluated
+    Skipped,
+    /// The testcase came from an incompatible client, and went to the handler set with
+    /// [`CentralizedEventManagerBuilder::on_incompatible`]
+    Bounced,
+    /// The event carried no testcase, e.g., [`Event::Stop`], and was handled
+    Handled,
+    /// The event should never have reached the main node, and was rejected with an error
+    Rejected,
+    /// The main node is still handling the event, or crashed while doing so
+    Pending,
+}
+
+/// A summary of an event the main node handled, see [`CentralizedEventManager::recent_events`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventSummary {
+    /// The kind of the event, see [`Event::name`]
+    pub event: &'static str,
+    /// The secondary the event came from
+    pub client_id: ClientId,
+    /// What the main node did with the event
+    pub outcome: EventOutcome,
+    /// When the main node was done with the event, or received it, if it is [`EventOutcome::Pending`]
+    pub time: Duration,
+}
+
+/// The most recent events a main node handled, oldest first, at most `capacity` of them
+#[derive(Debug, Clone)]
+struct EventHistory {
+    capacity: usize,
+    events: VecDeque<EventSummary>,
+}
+
+impl EventHistory {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            events: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// A new history, logged by a panic hook if the process panics while it is alive
+    fn dumped_on_panic(capacity: usize) -> Arc<Mutex<Self>> {
+        let history = Arc::new(Mutex::new(Self::new(capacity)));
+        let weak = Arc::downgrade(&history);
+        let old_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |panic_info| {
+            if let Some(history) = weak.upgrade() {
+                // The panic may have struck while the history was locked
+                if let Ok(history) = history.try_lock() {
+                    history.dump();
+                }
+            }
+            old_hook(panic_info);
+        }));
+        history
+    }
+
+    /// Add `summary`, evicting the oldest one if full
+    fn push(&mut self, summary: EventSummary) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back(summary);
+    }
+
+    /// Set the outcome of the oldest [`EventOutcome::Pending`] `event` of `client_id`,
+    /// or add it, if it was evicted since
+    fn finish(&mut self, summary: EventSummary) {
+        let pending = self.events.iter_mut().find(|pending| {
+            pending.outcome == EventOutcome::Pending
+                && pending.client_id == summary.client_id
+                && pending.event == summary.event
+        });
+        match pending {
+            Some(pending) => *pending = summary,
+            None => self.push(summary),
+        }
+    }
+
+    /// Log all summaries, oldest first
+    fn dump(&self) {
+        log::error!("The last {} events of the main node:", self.events.len());
+        for summary in &self.events {
+            log::error!(
+                "{:?}: {} from {:?}, {:?}",
+                summary.time,
+                summary.event,
+                summary.client_id,
+                summary.outcome
+            );
+        }
+    }
+}
+
+/// The generation of a node, i.e., how often it restarted.
+/// Kept in the state, so it survives restarts, and sent along with forwarded testcases.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct GenerationMetadata {
+    /// The number of restarts so far
+    pub generation: u64,
+}
+
+libafl_bolts::impl_serdeany!(GenerationMetadata);
+
+/// Warns when the usage of the centralized LLMP map crosses a high-water mark
+#[derive(Debug, Clone, Copy)]
+struct MapHighWater {
+    /// The share of the map, `None` to never warn
+    mark: Option<f64>,
+    /// If the usage was above the mark at the last check
+    above: bool,
+}
+
+impl MapHighWater {
+    fn new(mark: Option<f64>) -> Self {
+        Self { mark, above: false }
+    }
+
+    /// Check the usage, warning once each time it crosses the mark
+    fn check(&mut self, usage: MapUsage) {
+        let Some(mark) = self.mark else {
+            return;
+        };
+        let above = usage.ratio() >= mark;
+        if above && !self.above {
+            log::warn!(
+                "The centralized LLMP map is {:.1}% full ({} of {} bytes), above the high-water mark of {:.1}%",
+                usage.ratio() * 100.0,
+                usage.used,
+                usage.capacity,
+                mark * 100.0
+            );
+        }
+        self.above = above;
+    }
+}
+
+/// Wait for the centralized broker to map the last messages of `client` for at most `timeout`.
+/// Returns `false`, with a warning, if it did not.
+fn await_client_safe<SP>(client: &LlmpClient<SP>, timeout: Duration) -> bool
+where
+    SP: ShMemProvider,
+{
+    let safe = client.await_safe_to_unmap_timeout(timeout);
+    if !safe {
+        log::warn!(
+            "The centralized broker did not map our last messages within {timeout:?}, giving up"
+        );
+    }
+    safe
+}
+
+/// How long a secondary node tries to send its pending messages before a restart by default,
+/// see [`CentralizedEventManagerBuilder::restart_flush_timeout`]
+const DEFAULT_RESTART_FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The most messages a secondary node queues for the main node before sending fails for good
+const MAX_PENDING_FORWARDS: usize = 1024;
+
+/// The most events a main node receives from the secondary nodes in one `process` call,
+/// so a flood of them does not pile up in memory
+const MAX_RECEIVED_PER_PROCESS: usize = 4096;
+
+/// How often a secondary node tries to send the oldest pending message to the main node before it drops it,
+/// so a message LLMP keeps rejecting does not hold back the ones after it
+const MAX_SEND_ATTEMPTS: u32 = 8;
+
+/// The delay before the first retry of a message to the main node, doubled on every failed attempt
+const SEND_RETRY_BACKOFF: Duration = Duration::from_millis(10);
+
+/// The longest delay between two retries of a message to the main node
+const MAX_SEND_RETRY_BACKOFF: Duration = Duration::from_secs(5);
+
+/// The failed attempts to send the oldest pending message to the main node
+#[derive(Debug, Default, Clone, Copy)]
+struct SendRetry {
+    /// The failed attempts so far
+    attempts: u32,
+    /// When to try again, `None` to try right away
+    next_attempt: Option<Duration>,
+}
+
+impl SendRetry {
+    /// Record a failed attempt, and back off exponentially before the next one
+    fn failed(&mut self, now: Duration) {
+        self.attempts += 1;
+        let backoff = SEND_RETRY_BACKOFF
+            .saturating_mul(1 << self.attempts.min(16))
+            .min(MAX_SEND_RETRY_BACKOFF);
+        self.next_attempt = Some(now + backoff);
+    }
+
+    /// If the backoff of the last failed attempt is over
+    fn due(&self, now: Duration) -> bool {
+        self.next_attempt
+            .is_none_or(|next_attempt| now >= next_attempt)
+    }
+}
+
+/// A message to the main node that could not be sent yet
+#[derive(Debug, Clone)]
+struct PendingForward {
+    tag: Tag,
+    flags: Flags,
+    buf: Vec<u8>,
+    /// If this carries a testcase, which [`LoadShedding`] may drop
+    testcase: bool,
+}
+
+/// What load shedding does with a message to the main node while the LLMP sender is saturated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shed {
+    /// Stats and logs, the next ones replace them
+    Drop,
+    /// Testcases, held back within [`LoadShedding::max_deferred`]
+    Defer,
+    /// Control messages, always held back
+    Keep,
+}
+
+/// What the load shedding of a secondary node did to its messages to the main node,
+/// see [`CentralizedEventManagerBuilder::load_shedding`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SheddingStats {
+    /// The stats and logs dropped while the LLMP sender was saturated
+    pub dropped_stats: u64,
+    /// The testcases held back while the LLMP sender was saturated
+    pub deferred_testcases: u64,
+    /// The held back testcases that were dropped to make room for newer ones
+    pub dropped_testcases: u64,
+}
+
+/// See [`CentralizedEventManagerBuilder::load_shedding`]
+#[derive(Debug, Clone, Copy)]
+struct LoadShedding {
+    max_deferred: usize,
+    stats: SheddingStats,
+}
+
+/// An event a secondary node wants the main node to acknowledge, see [`CentralizedEventManager::forward_to_main_acked`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "I: serde::de::DeserializeOwned")]
+pub(crate) struct AckedForward<I>
+where
+    I: Input,
+{
+    /// The sequence number the main node acknowledges
+    pub(crate) seq: u64,
+    pub(crate) event: Event<I>,
+}
+
+/// A message a secondary node forwards to the main node
+trait ToMain: Serialize {
+    /// If this is a small control message, never worth compressing
+    #[cfg_attr(not(feature = "llmp_compression"), allow(dead_code))]
+    fn is_control(&self) -> bool;
+
+    /// What load shedding does with this message, see [`CentralizedEventManagerBuilder::load_shedding`]
+    fn shed(&self) -> Shed;
+}
+
+impl<I> ToMain for Event<I>
+where
+    I: Input,
+{
+    fn is_control(&self) -> bool {
+        matches!(
+            self,
+            Event::UpdateExecStats { .. }
+                | Event::UpdateUserStats { .. }
+                | Event::Stop
+                | Event::ClientExiting { .. }
+                | Event::ClientIdentity { .. }
+        )
+    }
+
+    fn shed(&self) -> Shed {
+        match self {
+            Event::NewTestcase { .. } | Event::Objective { .. } => Shed::Defer,
+            Event::UpdateExecStats { .. } | Event::UpdateUserStats { .. } | Event::Log { .. } => {
+                Shed::Drop
+            }
+            #[cfg(feature = "introspection")]
+            Event::UpdatePerfMonitor { .. } => Shed::Drop,
+            _ => Shed::Keep,
+        }
+    }
+}
+
+impl<I> ToMain for AckedForward<I>
+where
+    I: Input,
+{
+    fn is_control(&self) -> bool {
+        self.event.is_control()
+    }
+
+    fn shed(&self) -> Shed {
+        // The sender waits for the acknowledgment, so never drop it silently
+        Shed::Keep
+    }
+}
+
+/// The acknowledgment of the [`AckedForward`]s of one secondary by the main node.
+///
+/// LLMP passes it on to every node, the other secondaries skip it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Ack {
+    /// The secondary that sent the acknowledged events
+    client_id: ClientId,
+    seqs: Vec<u64>,
+}
+
+/// The operator messages a secondary node received, and the handlers for them
+struct Broadcasts<S> {
+    handlers: Vec<Box<CustomBufHandlerFn<S>>>,
+    /// The tags and buffers of the [`Event::CustomBuf`]s received, but not handled yet, oldest first
+    received: VecDeque<(String, Vec<u8>)>,
+}
+
+impl<S> Default for Broadcasts<S> {
+    fn default() -> Self {
+        Self {
+            handlers: Vec::new(),
+            received: VecDeque::new(),
+        }
+    }
+}
+
+impl<S> Debug for Broadcasts<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Broadcasts")
+            .field("handlers", &self.handlers.len())
+            .field("received", &self.received)
+            .finish()
+    }
+}
+
+/// The events a secondary node forwarded with an acknowledgment request
+#[derive(Debug, Default)]
+struct AckTracker {
+    next_seq: u64,
+    /// Forwarded, but not acknowledged yet
+    awaiting: BTreeSet<u64>,
+    /// Acknowledged since the last [`CentralizedEventManager::take_acknowledged`]
+    acknowledged: Vec<u64>,
+}
+
+/// How the main node evaluates a testcase received from a secondary node
+enum MainEvaluation<OT> {
+    /// Add it as is, processing the observers the secondary sent along, if any
+    Import(Option<OT>),
+    /// Evaluate the observers the secondary sent along, without running it
+    Observers(OT),
+    /// Run it again
+    Execute,
+}
+
+/// A testcase received from a secondary node that passed the checks of the main node, but was not evaluated yet
+struct PendingInMain<I, OT>
+where
+    I: Input,
+{
+    client_id: ClientId,
+    /// The detailed name of the event, for logging
+    event_name: Cow<'static, str>,
+    /// A copy of the input to evaluate
+    input: I,
+    exit_kind: ExitKind,
+    evaluation: MainEvaluation<OT>,
+    /// The received event, passed on to the inner manager if the testcase is added
+    event: Event<I>,
+}
+
+/// Send a full observers buffer to the main node at least every this many delta-encoded testcases,
+/// so a main node that lost its base, e.g., after a restart, can pick up again
+const DELTA_KEYFRAME_INTERVAL: usize = 64;
+
+/// The observers of a testcase forwarded with [`CentralizedEventManagerBuilder::forward_map_deltas`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+enum ObserversPayload {
+    /// The full serialized observers, the new base of this secondary
+    Full(Vec<u8>),
+    /// The bytes of the serialized observers that differ from the last base
+    Delta {
+        /// The length of the serialized observers
+        len: usize,
+        /// The changed runs, each one starting at an offset into the serialized observers
+        runs: Vec<(usize, Vec<u8>)>,
+    },
+}
+
+/// Delta-encodes the serialized observers a secondary forwards to the main node
+#[derive(Debug, Default)]
+struct DeltaEncoder {
+    /// The last full buffer, as known to the main node
+    base: Option<Vec<u8>>,
+    /// The deltas sent since the last full buffer
+    since_keyframe: usize,
+}
+
+impl DeltaEncoder {
+    /// Encode `observers_buf` against the base, falling back to a full buffer if the layout changed
+    fn encode(&mut self, observers_buf: Vec<u8>) -> ObserversPayload {
+        let base = match &mut self.base {
+            Some(base)
+                if base.len() == observers_buf.len()
+                    && self.since_keyframe < DELTA_KEYFRAME_INTERVAL =>
+            {
+                base
+            }
+            _ => {
+                self.base = Some(observers_buf.clone());
+                self.since_keyframe = 0;
+                return ObserversPayload::Full(observers_buf);
+            }
+        };
+
+        let mut runs: Vec<(usize, Vec<u8>)> = vec![];
+        for (idx, (new, old)) in observers_buf.iter().zip(base.iter_mut()).enumerate() {
+            if new == old {
+                continue;
+            }
+            *old = *new;
+            match runs.last_mut() {
+                Some((start, bytes)) if *start + bytes.len() == idx => bytes.push(*new),
+                _ => runs.push((idx, vec![*new])),
+            }
+        }
+        self.since_keyframe += 1;
+        ObserversPayload::Delta {
+            len: observers_buf.len(),
+            runs,
+        }
+    }
+
+    /// Forget the base, e.g., if the main node will never receive it, so the next buffer is sent in full
+    fn reset(&mut self) {
+        self.base = None;
+    }
+}
+
+/// Reconstructs the delta-encoded observers received from each secondary
+#[derive(Debug, Default)]
+struct DeltaDecoder {
+    /// The last full buffer per secondary
+    bases: HashMap<ClientId, Vec<u8>>,
+}
+
+impl DeltaDecoder {
+    /// Decode a payload from `client_id`.
+    ///
+    /// Returns `None` if there is no matching base for a delta, so that the testcase has to be re-executed.
+    fn decode(&mut self, client_id: ClientId, payload: ObserversPayload) -> Option<Vec<u8>> {
+        match payload {
+            ObserversPayload::Full(observers_buf) => {
+                self.bases.insert(client_id, observers_buf.clone());
+                Some(observers_buf)
+            }
+            ObserversPayload::Delta { len, runs } => {
+                let base = self.bases.get_mut(&client_id)?;
+                if base.len() != len
+                    || runs
+                        .iter()
+                        .any(|(start, bytes)| start.saturating_add(bytes.len()) > len)
+                {
+                    // Out of sync, wait for the next full buffer
+                    self.bases.remove(&client_id);
+                    return None;
+                }
+                for (start, bytes) in runs {
+                    base[start..start + bytes.len()].copy_from_slice(&bytes);
+                }
+                Some(base.clone())
+            }
+        }
+    }
+}
+
+impl<EM, EMH, S, SP> UsesState for CentralizedEventManager<EM, EMH, S, SP>
+where
+    EM: UsesState<State = S>,
+    EMH: EventManagerHooksTuple<S>,
+    S: State,
+    SP: ShMemProvider,
+{
+    type State = EM::State;
+}
+
+impl<EM, EMH, S, SP> AdaptiveSerializer for CentralizedEventManager<EM, EMH, S, SP>
+where
+    EM: AdaptiveSerializer + UsesState<State = S>,
+    EMH: EventManagerHooksTuple<S>,
+    S: State,
+    SP: ShMemProvider,
+{
+    fn serialization_time(&self) -> Duration {
+        self.inner.serialization_time()
+    }
+    fn deserialization_time(&self) -> Duration {
+        self.inner.deserialization_time()
+    }
+    fn serializations_cnt(&self) -> usize {
+        self.inner.serializations_cnt()
+    }
+    fn should_serialize_cnt(&self) -> usize {
+        self.inner.should_serialize_cnt()
+    }
+
+    fn serialization_time_mut(&mut self) -> &mut Duration {
+        self.inner.serialization_time_mut()
+    }
+    fn deserialization_time_mut(&mut self) -> &mut Duration {
+        self.inner.deserialization_time_mut()
+    }
+    fn serializations_cnt_mut(&mut self) -> &mut usize {
+        self.inner.serializations_cnt_mut()
+    }
+    fn should_serialize_cnt_mut(&mut self) -> &mut usize {
+        self.inner.should_serialize_cnt_mut()
+    }
+
+    fn serializer_stats(&self) -> &AdaptiveSerializerStats {
+        self.inner.serializer_stats()
+    }
+    fn serializer_stats_mut(&mut self) -> &mut AdaptiveSerializerStats {
+        self.inner.serializer_stats_mut()
+    }
+
+    fn time_ref(&self) -> &Option<Handle<TimeObserver>> {
+        &self.time_ref
+    }
+}
+
+impl<EM, EMH, S, SP> EventFirer for CentralizedEventManager<EM, EMH, S, SP>
+where
+    EM: AdaptiveSerializer + EventFirer<State = S> + HasEventManagerId,
+    EMH: EventManagerHooksTuple<S>,
+    S: State + HasCorpus + HasMetadata,
+    S::Corpus: Corpus<Input = S::Input>,
+    SP: ShMemProvider,
+{
+    fn should_send(&self) -> bool {
+        self.inner.should_send()
+    }
+
+    #[allow(clippy::match_same_arms)]
+    fn fire(
+        &mut self,
+        state: &mut Self::State,
+        mut event: Event<<Self::State as UsesInput>::Input>,
+    ) -> Result<(), Error> {
+        if !self.is_main {
+            // secondary node
+            let mut is_tc = false;
+            // Forward to main only if new tc or heartbeat
+            let should_be_forwarded = match &mut event {
+                Event::NewTestcase { .. } => {
+                    self.stamp_testcase(state, &mut event);
+                    is_tc = true;
+                    true
+                }
+                Event::UpdateExecStats { executions, .. } => {
+                    // send it but this guy won't be handled. the only purpose is to keep this client alive else the broker thinks it is dead and will dc it
+                    self.keepalive.executions = *executions;
+                    true
+                }
+                Event::Stop => true,
+                _ => false,
+            };
+
+            if should_be_forwarded {
+                *self.event_counts.entry(event.name()).or_default() += 1;
+            }
+            if is_tc {
+                // early return here because we only send it to centralized not main broker.
+                return self.forward_testcase_to_main(event);
+            } else if should_be_forwarded {
+                self.forward_to_main(_LLMP_TAG_TO_MAIN, &event)?;
+            } else {
+                self.maybe_keepalive()?;
+            }
+        }
+
+        // now inner llmp manager will process it if it's not a new testcase from a secondary node.
+        self.inner.fire(state, event)
+    }
+
+    fn log(
+        &mut self,
+        state: &mut Self::State,
+        severity_level: LogSeverity,
+        message: String,
+    ) -> Result<(), Error> {
+        self.inner.log(state, severity_level, message)
+    }
+
+    fn serialize_observers<OT>(&mut self, observers: &OT) -> Result<Option<Vec<u8>>, Error>
+    where
+        OT: ObserversTuple<Self::Input, Self::State> + Serialize,
+    {
+        self.inner.serialize_observers_adaptive(
+            observers,
+            self.serialize_time_factor,
+            self.serialize_percentage_threshold,
+        )
+    }
+
+    fn configuration(&self) -> EventConfig {
+        self.inner.configuration()
+    }
+}
+
+impl<EM, EMH, S, SP> EventRestarter for CentralizedEventManager<EM, EMH, S, SP>
+where
+    EM: EventRestarter<State = S>,
+    EMH: EventManagerHooksTuple<S>,
+    S: State + HasExecutions + HasMetadata,
+    SP: ShMemProvider,
+{
+    #[inline]
+    fn on_restart(&mut self, state: &mut Self::State) -> Result<(), Error> {
+        // Bump the generation before the inner manager serializes the state for the next run
+        state
+            .metadata_or_insert_with(GenerationMetadata::default)
+            .generation += 1;
+        if !self.is_main {
+            // Whatever we still owe the main node goes out before we wait for it to be mapped
+            self.keepalive.executions = *state.executions();
+            if let Err(err) = self.send_keepalive() {
+                log::warn!("Could not send a final keepalive to the main node: {err}");
+            }
+            self.flush_pending(self.restart_flush_timeout);
+        }
+        if let Some(deadline) = self.shutdown_deadline {
+            self.unmap_wait
+                .measure(|| await_client_safe(&self.client, deadline));
+        } else {
+            self.unmap_wait
+                .measure(|| self.client.await_safe_to_unmap_blocking());
+        }
+        self.inner.on_restart(state)?;
+        Ok(())
+    }
+
+    fn send_exiting(&mut self) -> Result<(), Error> {
+        self.client.sender_mut().send_exiting()?;
+        self.inner.send_exiting()
+    }
+
+    #[inline]
+    fn await_restart_safe(&mut self) {
+        if let Some(deadline) = self.shutdown_deadline {
+            self.await_restart_safe_for(deadline);
+        } else {
+            self.unmap_wait
+                .measure(|| self.client.await_safe_to_unmap_blocking());
+            self.inner.await_restart_safe();
+        }
+    }
+
+    fn await_restart_safe_for(&mut self, timeout: Duration) -> bool {
+        let start = current_time();
+        let client_safe = self
+            .unmap_wait
+            .measure(|| await_client_safe(&self.client, timeout));
+        let remaining = timeout.saturating_sub(current_time().saturating_sub(start));
+        let inner_safe = self.inner.await_restart_safe_for(remaining);
+        if !inner_safe {
+            log::warn!(
+                "The inner event manager was not safe to exit within {timeout:?}, giving up"
+            );
+        }
+        client_safe && inner_safe
+    }
+}
+
+impl<E, EM, EMH, S, SP, Z> EventProcessor<E, Z> for CentralizedEventManager<EM, EMH, S, SP>
+where
+    EM: AdaptiveSerializer
+        + EventProcessor<E, Z>
+        + EventFirer<State = S>
+        + EventRestarter
+        + HasEventManagerId,
+    EMH: EventManagerHooksTuple<S>,
+    E: HasObservers + HasTimeout + Executor<Self, Z, State = Self::State>,
+    E::Observers:
+        ObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State> + Serialize,
+    E::Observers: LazyObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State>,
+    S: State + HasCorpus,
+    S::Corpus: Corpus<Input = S::Input>,
+    Self::State: HasExecutions + HasMetadata + HasNamedMetadata,
+    SP: ShMemProvider,
+    Z: EvaluatorObservers<E, Self, <S::Corpus as Corpus>::Input, S>
+        + ExecutionProcessor<Self, <S::Corpus as Corpus>::Input, E::Observers, S>
+        + HasScheduler<<S::Corpus as Corpus>::Input, S>,
+{
+    fn process(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut Self::State,
+        executor: &mut E,
+    ) -> Result<usize, Error> {
+        let count = if self.is_main {
+            // main node
+            let count = self.receive_from_secondary(fuzzer, state, executor)?;
+            self.stats.backlog = count as u64;
+            // The broker passes on the fetches of testcases the main node announced by hash,
+            // see `LlmpEventManagerBuilder::hash_first`
+            count + self.inner.process(fuzzer, state, executor)?
+        } else {
+            // The main node does not process incoming events from the broker ATM
+            let count = self.inner.process(fuzzer, state, executor)?;
+            self.receive_from_main()?;
+            count + self.handle_broadcasts(state)?
+        };
+        self.sync_checksum(state)?;
+        self.sync_coverage(state)?;
+        self.maybe_report_stats(state)?;
+        Ok(count)
+    }
+
+    fn process_one(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut Self::State,
+        executor: &mut E,
+    ) -> Result<bool, Error> {
+        let processed = if self.is_main {
+            self.receive_one_from_secondary(fuzzer, state, executor)?
+                || self.inner.process_one(fuzzer, state, executor)?
+        } else if self.inner.process_one(fuzzer, state, executor)? {
+            true
+        } else {
+            if self.broadcasts.received.is_empty() {
+                self.receive_from_main()?;
+            }
+            self.handle_one_broadcast(state)?
+        };
+        self.sync_checksum(state)?;
+        self.sync_coverage(state)?;
+        self.maybe_report_stats(state)?;
+        Ok(processed)
+    }
+
+    fn on_shutdown(&mut self) -> Result<(), Error> {
+        // The inner manager says goodbye to its broker first, bounding its own wait
+        self.inner.on_shutdown()?;
+        self.client.sender_mut().send_exiting()?;
+        // Give the centralized broker a chance to get our last messages before we unmap
+        let timeout = self.shutdown_deadline.unwrap_or(SHUTDOWN_UNMAP_TIMEOUT);
+        self.unmap_wait
+            .measure(|| await_client_safe(&self.client, timeout));
+        Ok(())
+    }
+}
+
+impl<EM, EMH, S, SP> HasPendingEvents for CentralizedEventManager<EM, EMH, S, SP>
+where
+    EM: HasPendingEvents + UsesState<State = S>,
+    EMH: EventManagerHooksTuple<S>,
+    S: State,
+    SP: ShMemProvider,
+{
+    fn pending_events(&self) -> bool {
+        if self.is_main {
+            self.client.has_pending()
+                || self
+                    .quota
+                    .as_ref()
+                    .is_some_and(|quota| !quota.deferred.is_empty())
+                || self.inner.pending_events()
+        } else {
+            // Secondaries only read the messages of the main node if they await any
+            self.inner.pending_events()
+                || !self.broadcasts.received.is_empty()
+                || ((!self.acks.awaiting.is_empty()
+                    || !self.broadcasts.handlers.is_empty()
+                    || self.checksum.is_some()
+                    || self.coverage.is_some())
+                    && self.client.has_pending())
+        }
+    }
+}
+
+impl<E, EM, EMH, S, SP, Z> EventManager<E, Z> for CentralizedEventManager<EM, EMH, S, SP>
+where
+    E: HasObservers + HasTimeout + Executor<Self, Z, State = Self::State>,
+    E::Observers:
+        ObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State> + Serialize,
+    E::Observers: LazyObserversTuple<<Self as UsesInput>::Input, <Self as UsesState>::State>,
+    EM: AdaptiveSerializer + EventManager<E, Z, State = S>,
+    EM::State: HasExecutions + HasMetadata + HasNamedMetadata + HasLastReportTime,
+    EMH: EventManagerHooksTuple<S>,
+    S: State + HasCorpus,
+    S::Corpus: Corpus<Input = S::Input>,
+    SP: ShMemProvider,
+    Z: EvaluatorObservers<E, Self, <S::Corpus as Corpus>::Input, S>
+        + ExecutionProcessor<Self, <S::Corpus as Corpus>::Input, E::Observers, S>
+        + HasScheduler<<S::Corpus as Corpus>::Input, S>,
+{
+}
+
+impl<EM, EMH, S, SP> CentralizedEventManager<EM, EMH, S, SP>
+where
+    EM: UsesState<State = S>
+        + EventFirer
+        + AdaptiveSerializer
+        + HasEventManagerId
+        + HasPendingEvents,
+    EMH: EventManagerHooksTuple<S>,
+    S: State
+        + HasCorpus
+        + HasSolutions
+        + HasStartTime
+        + Stoppable
+        + HasMetadata
+        + HasExecutions
+        + HasLastReportTime,
+    S::Corpus: Corpus<Input = S::Input>,
+    SP: ShMemProvider,
+{
+    /// Run this main node without a fuzzing loop of its own, only evaluating the testcases
+    /// the secondaries forward, polling for them every `poll_interval` while there are none.
+    ///
+    /// Reports the progress and the [`MainLoopStats`] like the fuzzing loop would, and returns
+    /// cleanly once a stop is requested, a budget of `budget` ran out, or the broker shuts down.
+    /// Pass the [`crate::fuzzer::StdFuzzer::budget`] of the fuzzer to honor the budgets set on it.
+    pub fn run_main_loop<E, Z>(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut S,
+        executor: &mut E,
+        poll_interval: Duration,
+        budget: &CampaignBudget,
+    ) -> Result<MainLoopExit, Error>
+    where
+        Self: EventManager<E, Z, State = S>,
+    {
+        if !self.is_main {
+            return Err(Error::illegal_state(
+                "Only the main node runs the main loop",
+            ));
+        }
+        let exit = loop {
+            if state.stop_requested() {
+                break MainLoopExit::Stopped;
+            }
+            if let Some(kind) = budget.exhausted(state) {
+                log::info!(
+                    "The {} budget of this campaign ran out, stopping",
+                    kind.name()
+                );
+                self.fire_user_stat(
+                    state,
+                    CAMPAIGN_BUDGET_STAT,
+                    UserStats::new(
+                        UserStatsValue::String(Cow::Borrowed(kind.name())),
+                        AggregatorOps::None,
+                    ),
+                )?;
+                break MainLoopExit::BudgetExhausted(kind);
+            }
+
+            let start = current_time();
+            let events = match self.process(fuzzer, state, executor) {
+                Ok(events) => events as u64,
+                Err(Error::ShuttingDown) => break MainLoopExit::ShuttingDown,
+                Err(err) => return Err(err),
+            };
+            let main_loop = &mut self.stats.main_loop;
+            main_loop.iterations += 1;
+            main_loop.events += events;
+            if events > 0 {
+                main_loop.last_events = events;
+                main_loop.last_drain_time = current_time().saturating_sub(start);
+            }
+            self.maybe_report_progress(state, STATS_TIMEOUT_DEFAULT)?;
+
+            if events == 0 && !self.pending_events() {
+                std::thread::sleep(poll_interval);
+            }
+        };
+        log::info!("The main loop exits: {exit:?}");
+        if exit != MainLoopExit::ShuttingDown {
+            self.report_progress(state)?;
+            self.on_shutdown()?;
+        }
+        Ok(exit)
+    }
+}
+
+impl<EM, EMH, S, SP> HasCustomBufHandlers for CentralizedEventManager<EM, EMH, S, SP>
+where
+    EM: HasCustomBufHandlers<State = S>,
+    EMH: EventManagerHooksTuple<S>,
+    S: State,
+    SP: ShMemProvider,
+{
+    /// Adds a custom buffer handler that will run for each incoming `CustomBuf` event.
+    fn add_custom_buf_handler(
+        &mut self,
+        handler: Box<
+            dyn FnMut(&mut Self::State, &str, &[u8]) -> Result<CustomBufEventResult, Error>,
+        >,
+    ) {
+        self.inner.add_custom_buf_handler(handler);
+    }
+}
+
+impl<EM, EMH, S, SP> ProgressReporter for CentralizedEventManager<EM, EMH, S, SP>
+where
+    EM: AdaptiveSerializer + ProgressReporter<State = S> + HasEventManagerId,
+    EM::State: HasMetadata + HasExecutions + HasLastReportTime,
+    EMH: EventManagerHooksTuple<S>,
+    S: State + HasCorpus,
+    S::Corpus: Corpus<Input = S::Input>,
+    SP: ShMemProvider,
+{
+    fn on_progress_check(&mut self, state: &mut Self::State) -> Result<(), Error> {
+        self.keepalive.executions = *state.executions();
+        if !self.is_main {
+            self.maybe_keepalive()?;
+        }
+        Ok(())
+    }
+}
+
+impl<EM, EMH, S, SP> HasEventManagerId for CentralizedEventManager<EM, EMH, S, SP>
+where
+    EM: HasEventManagerId + UsesState<State = S>,
+    EMH: EventManagerHooksTuple<S>,
+    S: State + HasCorpus,
+    S::Corpus: Corpus<Input = S::Input>,
+    SP: ShMemProvider,
+{
+    fn mgr_id(&self) -> EventManagerId {
+        self.inner.mgr_id()
+    }
+}
+
+impl<EM, EMH, S, SP> ReattachableEventManager for CentralizedEventManager<EM, EMH, S, SP>
+where
+    EM: UsesState<State = S>,
+    EMH: EventManagerHooksTuple<S>,
+    S: State,
+    SP: ShMemProvider,
+{
+    fn to_env(&self, env_name: &str) {
+        self.to_env(env_name);
+    }
+}
+
+impl<EM, EMH, S, SP> HasCentralizedMetrics for CentralizedEventManager<EM, EMH, S, SP>
+where
+    EM: UsesState<State = S>,
+    EMH: EventManagerHooksTuple<S>,
+    S: State,
+    SP: ShMemProvider,
+{
+    fn centralized_metrics(&self) -> CentralizedMetrics {
+        let map_usage = self.map_usage();
+        CentralizedMetrics {
+            is_main: self.is_main,
+            forwarded: self.stats.forwarded,
+            accepted: self.stats.accepted,
+            discarded: self.stats.discarded,
+            backlog: self.stats.backlog,
+            self_messages: self.stats.self_messages,
+            pending: self.pending.len(),
+            map_used: map_usage.used,
+            map_capacity: map_usage.capacity,
+            event_counts: self
+                .event_counts
+                .iter()
+                .map(|(name, count)| ((*name).into(), *count))
+                .collect(),
+        }
+    }
+}
+
+impl<EM, EMH, S, SP> CentralizedEventManager<EM, EMH, S, SP>
+where
+    EM: UsesState<State = S>,
+    EMH: EventManagerHooksTuple<S>,
+    S: State,
+    SP: ShMemProvider,
+{
+    /// The identities of the secondaries of this main node, by the [`ClientId`] they forward with
+    pub fn client_registry(&self) -> &ClientRegistry {
+        &self.clients
+    }
+
+    /// Describe the client event manager's LLMP parts in a restorable fashion
+    pub fn describe(&self) -> Result<LlmpClientDescription, Error> {
+        self.client.describe()
+    }
+
+    /// Write the config for a client [`EventManager`] to env vars, a new
+    /// client can reattach using [`CentralizedEventManagerBuilder::build_existing_client_from_env()`].
+    pub fn to_env(&self, env_name: &str) {
+        self.client.to_env(env_name).unwrap();
+    }
+
+    /// The messages this main node received from itself, and skipped.
+    ///
+    /// More than a few of them hint at a node that forwards to itself.
+    pub fn self_messages(&self) -> u64 {
+        self.stats.self_messages
+    }
+
+    /// The messages from secondaries this main node could not decode, and skipped
+    pub fn malformed_messages(&self) -> u64 {
+        self.stats.malformed
+    }
+
+    /// The forwarded testcases that timed out when this main node ran them again,
+    /// see [`CentralizedEventManagerBuilder::reexec_timeout`]
+    pub fn reexec_timeouts(&self) -> u64 {
+        self.stats.reexec_timeouts
+    }
+
+    /// The forwarded testcases this main node skipped as duplicates,
+    /// see [`CentralizedEventManagerBuilder::dedup`]
+    pub fn duplicates(&self) -> u64 {
+        self.stats.duplicates
+    }
+
+    /// The statistics of [`CentralizedEventManager::run_main_loop`] so far
+    pub fn main_loop_stats(&self) -> MainLoopStats {
+        self.stats.main_loop
+    }
+
+    /// How long this node was blocked waiting for the centralized broker to map its last messages,
+    /// before restarting or exiting, see [`CentralizedEventManagerBuilder::unmap_wait_warning`].
+    ///
+    /// The waits of the inner manager are measured by the inner manager.
+    #[must_use]
+    pub fn unmap_wait_stats(&self) -> &UnmapWaitStats {
+        self.unmap_wait.stats()
+    }
+
+    /// The [`CorpusChecksum`] of the local corpus, as of the last broadcast or comparison,
+    /// `None` without [`CentralizedEventManagerBuilder::corpus_checksum`]
+    pub fn corpus_checksum(&self) -> Option<CorpusChecksum> {
+        self.checksum.as_ref().map(|sync| sync.checksum)
+    }
+
+    /// How often the corpus of this secondary did not match the checksum the main node broadcast,
+    /// see [`CentralizedEventManagerBuilder::corpus_checksum`]
+    pub fn checksum_mismatches(&self) -> u64 {
+        self.checksum.as_ref().map_or(0, |sync| sync.mismatches)
+    }
+
+    /// What the [`CentralizedEventManagerBuilder::forwarding_quota`] did to the testcases of `client_id`,
+    /// `None` if they never went over it, or there is no quota
+    pub fn quota_stats(&self, client_id: ClientId) -> Option<QuotaStats> {
+        self.quota
+            .as_ref()?
+            .clients
+            .get(&client_id)
+            .map(|client| client.stats)
+            .filter(|stats| *stats != QuotaStats::default())
+    }
+
+    /// What the [`CentralizedEventManagerBuilder::load_shedding`] of this secondary node did so far,
+    /// `None` if it is off
+    pub fn shedding_stats(&self) -> Option<SheddingStats> {
+        self.shedding.map(|shedding| shedding.stats)
+    }
+
+    /// The hash of `input`, as computed by the [`CentralizedEventManagerBuilder::input_hasher`]
+    pub fn input_hash(&self, input: &S::Input) -> Result<u64, Error> {
+        self.input_hasher.hasher.hash_input(input)
+    }
+
+    /// Know if this instance is main or secondary
+    pub fn is_main(&self) -> bool {
+        self.is_main
+    }
+
+    /// How much of the current page of the centralized LLMP map this node sends on is in use
+    pub fn map_usage(&self) -> MapUsage {
+        let (used, capacity) = self.client.sender().page_usage();
+        MapUsage { used, capacity }
+    }
+
+    /// How many events of each kind, by [`Event::name`], this node forwarded to the main node,
+    /// or, for the main node, received from the secondaries
+    pub fn event_counts(&self) -> &HashMap<&'static str, u64> {
+        &self.event_counts
+    }
+
+    /// Summaries of the last events this main node handled, oldest first,
+    /// see [`CentralizedEventManagerBuilder::event_history`].
+    ///
+    /// Empty if the history is off.
+    pub fn recent_events(&self) -> Vec<EventSummary> {
+        self.history
+            .as_ref()
+            .map(|history| {
+                let history = history.lock().unwrap_or_else(PoisonError::into_inner);
+                history.events.iter().copied().collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Remember that this main node started to handle an event, if the history is on
+    fn record_received(&mut self, client_id: ClientId, event: &'static str) {
+        if let Some(history) = &self.history {
+            let mut history = history.lock().unwrap_or_else(PoisonError::into_inner);
+            history.push(EventSummary {
+                event,
+                client_id,
+                outcome: EventOutcome::Pending,
+                time: current_time(),
+            });
+        }
+    }
+
+    /// Remember what this main node did with an event, if the history is on
+    fn record_outcome(&mut self, client_id: ClientId, event: &'static str, outcome: EventOutcome) {
+        if let Some(history) = &self.history {
+            let mut history = history.lock().unwrap_or_else(PoisonError::into_inner);
+            history.finish(EventSummary {
+                event,
+                client_id,
+                outcome,
+                time: current_time(),
+            });
+        }
+    }
+
+    /// Record all events arriving in this main node with the given [`EventTap`],
+    /// or stop recording with `None`.
+    pub fn set_event_tap(&mut self, tap: Option<EventTap>) {
+        self.tap = tap;
+    }
+
+    /// Log the testcases this main node accepts with the given [`EventLogWriter`],
+    /// or stop logging with `None`.
+    ///
+    /// Unlike an [`EventTap`], the log only holds the accepted testcases, as they were added to the corpus,
+    /// to rebuild the corpus offline with an [`crate::events::EventLogReader`].
+    pub fn set_event_log(&mut self, event_log: Option<EventLogWriter>) {
+        self.event_log = event_log;
+    }
+
+    /// Set the trust level of the secondary `client_id`, for a main node.
+    ///
+    /// Within one `process` call, the main node handles the testcases of more trusted secondaries first,
+    /// so they win races for the same coverage. Testcases of secondaries trusted less than [`DEFAULT_TRUST`]
+    /// are always re-executed, ignoring the observers sent along,
+    /// and checked more often, see [`CentralizedEventManagerBuilder::low_trust_reexecs`].
+    pub fn set_trust(&mut self, client_id: ClientId, trust: i32) {
+        self.trust.insert(client_id, trust);
+    }
+
+    /// Forget the trust level of the secondary `client_id`, so it has the [`DEFAULT_TRUST`] again
+    pub fn reset_trust(&mut self, client_id: ClientId) {
+        self.trust.remove(&client_id);
+    }
+
+    /// The trust level of the secondary `client_id`, see [`CentralizedEventManager::set_trust`]
+    pub fn trust(&self, client_id: ClientId) -> i32 {
+        self.trust.get(&client_id).copied().unwrap_or(DEFAULT_TRUST)
+    }
+
+    /// Broadcast an operator message, e.g., a command to pause fuzzing, from this main node to all secondaries,
+    /// as an [`Event::CustomBuf`] tagged with the `topic`.
+    ///
+    /// Secondaries pass it to their handlers, see [`CentralizedEventManager::add_broadcast_handler`],
+    /// during their next `process`. Only the main node can broadcast, and it does not handle its own
+    /// messages, so a handler cannot start a broadcast loop.
+    pub fn broadcast_custom(&mut self, topic: &str, payload: Vec<u8>) -> Result<(), Error> {
+        if !self.is_main {
+            return Err(Error::illegal_state(
+                "Only the main node broadcasts to the secondary nodes",
+            ));
+        }
+        let event: Event<S::Input> = Event::CustomBuf {
+            buf: payload,
+            tag: topic.into(),
+        };
+        self.client.send_buf_with_flags(
+            _LLMP_TAG_BROADCAST_FROM_MAIN,
+            LLMP_FLAG_INITIALIZED,
+            &postcard::to_allocvec(&event)?,
+        )
+    }
+
+    /// Add a handler for the operator messages of the main node, see [`CentralizedEventManager::broadcast_custom`].
+    ///
+    /// The handlers get the `topic` and the `payload` of each message, in the order they were added,
+    /// until one returns [`CustomBufEventResult::Handled`].
+    /// Secondaries without handlers skip the messages.
+    pub fn add_broadcast_handler(&mut self, handler: Box<CustomBufHandlerFn<S>>) {
+        self.broadcasts.handlers.push(handler);
+    }
+
+    /// Pass the operator messages received from the main node to the handlers, returning how many there were
+    fn handle_broadcasts(&mut self, state: &mut S) -> Result<usize, Error> {
+        let mut count = 0;
+        while self.handle_one_broadcast(state)? {
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Pass the oldest operator message received from the main node to the handlers, returning if there was one
+    fn handle_one_broadcast(&mut self, state: &mut S) -> Result<bool, Error> {
+        let Some((tag, buf)) = self.broadcasts.received.pop_front() else {
+            return Ok(false);
+        };
+        log::debug!("Received operator message {tag} from main");
+        for handler in &mut self.broadcasts.handlers {
+            if handler(state, &tag, &buf)? == CustomBufEventResult::Handled {
+                break;
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<EM, EMH, S, SP> CentralizedEventManager<EM, EMH, S, SP>
+where
+    EM: UsesState<State = S> + HasEventManagerId,
+    EMH: EventManagerHooksTuple<S>,
+    S: State + HasMetadata,
+    SP: ShMemProvider,
+{
+    /// Forward `event` to the main node, and have the main node acknowledge it, e.g., for critical testcases.
+    ///
+    /// Returns the sequence number of the event. Once the main node received and handled the event,
+    /// it sends back an acknowledgment, which [`CentralizedEventManager::take_acknowledged`] reports.
+    /// The event is forwarded as is, without delta-encoding, and only once: if it stays in
+    /// [`CentralizedEventManager::awaiting_ack`], e.g., because the main node restarted meanwhile,
+    /// forward it again for at-least-once delivery.
+    ///
+    /// Only [`Event::NewTestcase`] and [`Event::Stop`] reach the main node, other events are rejected.
+    pub fn forward_to_main_acked(
+        &mut self,
+        state: &mut S,
+        mut event: Event<S::Input>,
+    ) -> Result<u64, Error> {
+        if self.is_main {
+            return Err(Error::illegal_state(
+                "Only secondary nodes forward events to the main node",
+            ));
+        }
+        if !matches!(event, Event::NewTestcase { .. } | Event::Stop) {
+            // The centralized broker would drop it, and it would never be acknowledged
+            return Err(Error::illegal_argument(format!(
+                "The main node does not handle {} events",
+                event.name()
+            )));
+        }
+        self.stamp_testcase(state, &mut event);
+        *self.event_counts.entry(event.name()).or_default() += 1;
+
+        let seq = self.acks.next_seq;
+        self.acks.next_seq += 1;
+        self.forward_to_main(_LLMP_TAG_TO_MAIN_ACKED, &AckedForward { seq, event })?;
+        self.acks.awaiting.insert(seq);
+        Ok(seq)
+    }
+
+    /// The sequence numbers of the events the main node acknowledged since the last call,
+    /// see [`CentralizedEventManager::forward_to_main_acked`].
+    ///
+    /// The acknowledgments are also picked up on each `process` of this secondary node.
+    pub fn take_acknowledged(&mut self) -> Result<Vec<u64>, Error> {
+        self.receive_from_main()?;
+        Ok(mem::take(&mut self.acks.acknowledged))
+    }
+
+    /// The sequence numbers of the events forwarded with [`CentralizedEventManager::forward_to_main_acked`]
+    /// the main node did not acknowledge yet, oldest first
+    pub fn awaiting_ack(&self) -> impl Iterator<Item = u64> + '_ {
+        self.acks.awaiting.iter().copied()
+    }
+
+    /// Stamp a testcase this secondary node forwards with the id of this node and the generation, and count it
+    fn stamp_testcase(&mut self, state: &S, event: &mut Event<S::Input>) {
+        if let Event::NewTestcase {
+            forward_id,
+            generation,
+            ..
+        } = event
+        {
+            *forward_id = Some(ClientId(self.inner.mgr_id().0 as u32));
+            *generation = Some(
+                state
+                    .metadata::<GenerationMetadata>()
+                    .map_or(0, |meta| meta.generation),
+            );
+            self.stats.forwarded += 1;
+        }
+    }
+}
+
+/*
+impl<EM, SP> Drop for CentralizedEventManager<EM, SP>
+where
+    EM: UsesState,    SP: ShMemProvider + 'static,
+{
+    /// LLMP clients will have to wait until their pages are mapped by somebody.
+    fn drop(&mut self) {
+        self.await_restart_safe();
+    }
+}*/
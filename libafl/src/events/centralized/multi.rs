@@ -0,0 +1,453 @@
+//! Several inner event managers sharing one state, see [`MultiInner`]
+
+use alloc::vec::Vec;
+use core::{fmt::Debug, time::Duration};
+
+use libafl_bolts::{
+    current_time,
+    tuples::{Handle, HasConstLen},
+};
+use serde::Serialize;
+
+use crate::{
+    events::{
+        AdaptiveSerializer, AdaptiveSerializerStats, Event, EventConfig, EventFirer, EventManager,
+        EventManagerId, EventProcessor, EventRestarter, HasEventManagerId, HasPendingEvents,
+        ProgressReporter,
+    },
+    inputs::UsesInput,
+    observers::{ObserversTuple, TimeObserver},
+    state::{HasExecutions, HasLastReportTime, State, UsesState},
+    Error, HasMetadata,
+};
+
+/// The inner event managers of a [`MultiInner`] after its primary one, as a tuple list of managers sharing one state
+pub trait InnerManagersTuple<S>: HasConstLen
+where
+    S: UsesInput,
+{
+    /// Fire the `event` to the manager at index `idx` of this tuple
+    fn fire_nth(&mut self, idx: usize, state: &mut S, event: Event<S::Input>) -> Result<(), Error>;
+
+    /// Call [`EventRestarter::on_restart`] on all managers
+    fn on_restart_all(&mut self, state: &mut S) -> Result<(), Error>;
+
+    /// Call [`EventRestarter::send_exiting`] on all managers
+    fn send_exiting_all(&mut self) -> Result<(), Error>;
+
+    /// Call [`EventRestarter::await_restart_safe`] on all managers
+    fn await_restart_safe_all(&mut self);
+
+    /// Call [`EventRestarter::await_restart_safe_for`] on all managers, within the same `timeout` started at `start`
+    fn await_restart_safe_for_all(&mut self, timeout: Duration, start: Duration) -> bool;
+
+    /// If any of the managers has pending events
+    fn pending_events_any(&self) -> bool;
+}
+
+impl<S> InnerManagersTuple<S> for ()
+where
+    S: UsesInput,
+{
+    fn fire_nth(
+        &mut self,
+        idx: usize,
+        _state: &mut S,
+        event: Event<S::Input>,
+    ) -> Result<(), Error> {
+        Err(Error::illegal_argument(format!(
+            "No inner manager left to fire {} to, {idx} past the last one",
+            event.name()
+        )))
+    }
+
+    fn on_restart_all(&mut self, _state: &mut S) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn send_exiting_all(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn await_restart_safe_all(&mut self) {}
+
+    fn await_restart_safe_for_all(&mut self, _timeout: Duration, _start: Duration) -> bool {
+        true
+    }
+
+    fn pending_events_any(&self) -> bool {
+        false
+    }
+}
+
+impl<Head, Tail, S> InnerManagersTuple<S> for (Head, Tail)
+where
+    Head: EventFirer<State = S> + EventRestarter + HasPendingEvents,
+    Tail: InnerManagersTuple<S>,
+    S: State,
+{
+    fn fire_nth(&mut self, idx: usize, state: &mut S, event: Event<S::Input>) -> Result<(), Error> {
+        if idx == 0 {
+            self.0.fire(state, event)
+        } else {
+            self.1.fire_nth(idx - 1, state, event)
+        }
+    }
+
+    fn on_restart_all(&mut self, state: &mut S) -> Result<(), Error> {
+        self.0.on_restart(state)?;
+        self.1.on_restart_all(state)
+    }
+
+    fn send_exiting_all(&mut self) -> Result<(), Error> {
+        self.0.send_exiting()?;
+        self.1.send_exiting_all()
+    }
+
+    fn await_restart_safe_all(&mut self) {
+        self.0.await_restart_safe();
+        self.1.await_restart_safe_all();
+    }
+
+    fn await_restart_safe_for_all(&mut self, timeout: Duration, start: Duration) -> bool {
+        let remaining = timeout.saturating_sub(current_time().saturating_sub(start));
+        let safe = self.0.await_restart_safe_for(remaining);
+        self.1.await_restart_safe_for_all(timeout, start) && safe
+    }
+
+    fn pending_events_any(&self) -> bool {
+        self.0.pending_events() || self.1.pending_events_any()
+    }
+}
+
+/// The event processing of the inner event managers of a [`MultiInner`] after its primary one
+pub trait InnerProcessorsTuple<E, S, Z> {
+    /// Call [`EventProcessor::process`] on all managers, returning the number of processed events
+    fn process_all(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut S,
+        executor: &mut E,
+    ) -> Result<usize, Error>;
+
+    /// Call [`EventProcessor::process_one`] on the managers until one of them processed an event
+    fn process_one_any(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut S,
+        executor: &mut E,
+    ) -> Result<bool, Error>;
+
+    /// Call [`EventProcessor::on_shutdown`] on all managers
+    fn on_shutdown_all(&mut self) -> Result<(), Error>;
+}
+
+impl<E, S, Z> InnerProcessorsTuple<E, S, Z> for () {
+    fn process_all(
+        &mut self,
+        _fuzzer: &mut Z,
+        _state: &mut S,
+        _executor: &mut E,
+    ) -> Result<usize, Error> {
+        Ok(0)
+    }
+
+    fn process_one_any(
+        &mut self,
+        _fuzzer: &mut Z,
+        _state: &mut S,
+        _executor: &mut E,
+    ) -> Result<bool, Error> {
+        Ok(false)
+    }
+
+    fn on_shutdown_all(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<E, Head, Tail, S, Z> InnerProcessorsTuple<E, S, Z> for (Head, Tail)
+where
+    Head: EventProcessor<E, Z, State = S>,
+    Tail: InnerProcessorsTuple<E, S, Z>,
+    S: State,
+{
+    fn process_all(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut S,
+        executor: &mut E,
+    ) -> Result<usize, Error> {
+        let count = self.0.process(fuzzer, state, executor)?;
+        Ok(count + self.1.process_all(fuzzer, state, executor)?)
+    }
+
+    fn process_one_any(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut S,
+        executor: &mut E,
+    ) -> Result<bool, Error> {
+        Ok(self.0.process_one(fuzzer, state, executor)?
+            || self.1.process_one_any(fuzzer, state, executor)?)
+    }
+
+    fn on_shutdown_all(&mut self) -> Result<(), Error> {
+        self.0.on_shutdown()?;
+        self.1.on_shutdown_all()
+    }
+}
+
+/// Multiplexes several inner event managers behind a single one,
+/// so that one [`CentralizedEventManager`] can feed, e.g., one manager per objective.
+///
+/// The managers are a tuple list, such as `tuple_list!(primary, second, third)`.
+/// Each fired [`Event`] (including logs and progress reports) is routed to exactly one of the
+/// managers, the one at the index returned by the `selector`.
+/// Incoming events are processed, and restarts and shutdowns are propagated, for all managers.
+/// The first manager is the primary one: its id, configuration, and serialization stats
+/// are used for the [`MultiInner`] as a whole.
+///
+/// A single inner manager does not need this wrapper, [`CentralizedEventManager`] can hold it directly.
+pub struct MultiInner<EM, EMT, F> {
+    primary: EM,
+    others: EMT,
+    selector: F,
+}
+
+impl<EM, EMT, F> Debug for MultiInner<EM, EMT, F>
+where
+    EM: Debug,
+    EMT: Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("MultiInner")
+            .field("primary", &self.primary)
+            .field("others", &self.others)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<EM, EMT, F> MultiInner<EM, EMT, F>
+where
+    EM: UsesState,
+    EMT: InnerManagersTuple<EM::State>,
+    F: FnMut(&Event<<EM::State as UsesInput>::Input>) -> usize,
+{
+    /// Create a new [`MultiInner`], routing fired events to the manager at index `selector(&event)` of `managers`.
+    pub fn new(managers: (EM, EMT), selector: F) -> Self {
+        let (primary, others) = managers;
+        Self {
+            primary,
+            others,
+            selector,
+        }
+    }
+}
+
+impl<EM, EMT, F> MultiInner<EM, EMT, F> {
+    /// The primary manager, i.e., the first one
+    #[must_use]
+    pub fn primary(&self) -> &EM {
+        &self.primary
+    }
+
+    /// The primary manager, i.e., the first one (mutable)
+    pub fn primary_mut(&mut self) -> &mut EM {
+        &mut self.primary
+    }
+
+    /// The managers after the primary one
+    #[must_use]
+    pub fn others(&self) -> &EMT {
+        &self.others
+    }
+
+    /// The managers after the primary one (mutable)
+    pub fn others_mut(&mut self) -> &mut EMT {
+        &mut self.others
+    }
+}
+
+impl<EM, EMT, F> UsesState for MultiInner<EM, EMT, F>
+where
+    EM: UsesState,
+{
+    type State = EM::State;
+}
+
+impl<EM, EMT, F> EventFirer for MultiInner<EM, EMT, F>
+where
+    EM: EventFirer,
+    EMT: InnerManagersTuple<EM::State>,
+    F: FnMut(&Event<<EM::State as UsesInput>::Input>) -> usize,
+{
+    fn fire(
+        &mut self,
+        state: &mut Self::State,
+        event: Event<<Self::State as UsesInput>::Input>,
+    ) -> Result<(), Error> {
+        let idx = (self.selector)(&event);
+        match idx {
+            0 => self.primary.fire(state, event),
+            idx if idx <= EMT::LEN => self.others.fire_nth(idx - 1, state, event),
+            idx => Err(Error::illegal_argument(format!(
+                "Selector routed {} to inner manager {idx}, but there are only {}",
+                event.name(),
+                EMT::LEN + 1
+            ))),
+        }
+    }
+
+    fn serialize_observers<OT>(&mut self, observers: &OT) -> Result<Option<Vec<u8>>, Error>
+    where
+        OT: ObserversTuple<Self::Input, Self::State> + Serialize,
+    {
+        self.primary.serialize_observers(observers)
+    }
+
+    fn configuration(&self) -> EventConfig {
+        self.primary.configuration()
+    }
+
+    fn should_send(&self) -> bool {
+        self.primary.should_send()
+    }
+}
+
+impl<EM, EMT, F> EventRestarter for MultiInner<EM, EMT, F>
+where
+    EM: EventRestarter,
+    EMT: InnerManagersTuple<EM::State>,
+{
+    fn on_restart(&mut self, state: &mut Self::State) -> Result<(), Error> {
+        self.primary.on_restart(state)?;
+        self.others.on_restart_all(state)
+    }
+
+    fn send_exiting(&mut self) -> Result<(), Error> {
+        self.primary.send_exiting()?;
+        self.others.send_exiting_all()
+    }
+
+    fn await_restart_safe(&mut self) {
+        self.primary.await_restart_safe();
+        self.others.await_restart_safe_all();
+    }
+
+    fn await_restart_safe_for(&mut self, timeout: Duration) -> bool {
+        let start = current_time();
+        let safe = self.primary.await_restart_safe_for(timeout);
+        self.others.await_restart_safe_for_all(timeout, start) && safe
+    }
+}
+
+impl<E, EM, EMT, F, Z> EventProcessor<E, Z> for MultiInner<EM, EMT, F>
+where
+    EM: EventProcessor<E, Z>,
+    EMT: InnerProcessorsTuple<E, EM::State, Z>,
+{
+    fn process(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut Self::State,
+        executor: &mut E,
+    ) -> Result<usize, Error> {
+        let count = self.primary.process(fuzzer, state, executor)?;
+        Ok(count + self.others.process_all(fuzzer, state, executor)?)
+    }
+
+    fn process_one(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut Self::State,
+        executor: &mut E,
+    ) -> Result<bool, Error> {
+        Ok(self.primary.process_one(fuzzer, state, executor)?
+            || self.others.process_one_any(fuzzer, state, executor)?)
+    }
+
+    fn on_shutdown(&mut self) -> Result<(), Error> {
+        self.primary.on_shutdown()?;
+        self.others.on_shutdown_all()
+    }
+}
+
+impl<EM, EMT, F> HasPendingEvents for MultiInner<EM, EMT, F>
+where
+    EM: HasPendingEvents + UsesState,
+    EMT: InnerManagersTuple<EM::State>,
+{
+    fn pending_events(&self) -> bool {
+        self.primary.pending_events() || self.others.pending_events_any()
+    }
+}
+
+impl<E, EM, EMT, F, Z> EventManager<E, Z> for MultiInner<EM, EMT, F>
+where
+    EM: EventManager<E, Z>,
+    EM::State: HasMetadata + HasExecutions + HasLastReportTime,
+    EMT: InnerManagersTuple<EM::State> + InnerProcessorsTuple<E, EM::State, Z>,
+    F: FnMut(&Event<<EM::State as UsesInput>::Input>) -> usize,
+{
+}
+
+impl<EM, EMT, F> ProgressReporter for MultiInner<EM, EMT, F>
+where
+    EM: ProgressReporter,
+    EM::State: HasMetadata + HasExecutions + HasLastReportTime,
+    EMT: InnerManagersTuple<EM::State>,
+    F: FnMut(&Event<<EM::State as UsesInput>::Input>) -> usize,
+{
+}
+
+impl<EM, EMT, F> HasEventManagerId for MultiInner<EM, EMT, F>
+where
+    EM: HasEventManagerId,
+{
+    fn mgr_id(&self) -> EventManagerId {
+        self.primary.mgr_id()
+    }
+}
+
+impl<EM, EMT, F> AdaptiveSerializer for MultiInner<EM, EMT, F>
+where
+    EM: AdaptiveSerializer,
+{
+    fn serialization_time(&self) -> Duration {
+        self.primary.serialization_time()
+    }
+    fn deserialization_time(&self) -> Duration {
+        self.primary.deserialization_time()
+    }
+    fn serializations_cnt(&self) -> usize {
+        self.primary.serializations_cnt()
+    }
+    fn should_serialize_cnt(&self) -> usize {
+        self.primary.should_serialize_cnt()
+    }
+
+    fn serialization_time_mut(&mut self) -> &mut Duration {
+        self.primary.serialization_time_mut()
+    }
+    fn deserialization_time_mut(&mut self) -> &mut Duration {
+        self.primary.deserialization_time_mut()
+    }
+    fn serializations_cnt_mut(&mut self) -> &mut usize {
+        self.primary.serializations_cnt_mut()
+    }
+    fn should_serialize_cnt_mut(&mut self) -> &mut usize {
+        self.primary.should_serialize_cnt_mut()
+    }
+
+    fn serializer_stats(&self) -> &AdaptiveSerializerStats {
+        self.primary.serializer_stats()
+    }
+    fn serializer_stats_mut(&mut self) -> &mut AdaptiveSerializerStats {
+        self.primary.serializer_stats_mut()
+    }
+
+    fn time_ref(&self) -> &Option<Handle<TimeObserver>> {
+        self.primary.time_ref()
+    }
+}
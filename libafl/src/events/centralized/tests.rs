@@ -0,0 +1,2993 @@
+use alloc::{borrow::Cow, boxed::Box, rc::Rc, string::String, vec::Vec};
+use core::{
+    cell::RefCell,
+    marker::PhantomData,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use std::{env, fs, fs::File, process, thread, time::Instant};
+
+#[cfg(unix)]
+use libafl_bolts::os::{fork, ForkResult};
+use libafl_bolts::{
+    hash_std,
+    llmp::{
+        LlmpBroker, LlmpClient, LlmpReceiver, LlmpSender, LlmpSharedMap, Tag, LLMP_FLAG_INITIALIZED,
+    },
+    rands::{Rand, StdRand},
+    serdeany::SerdeAnyMap,
+    shmem::{ShMemProvider, StdShMemProvider},
+    tuples::{tuple_list, Handled},
+    ClientId, DistributedPhase, Named,
+};
+use serial_test::serial;
+
+use crate::{
+    corpus::{AddedAtMetadata, Corpus, CorpusId, Testcase},
+    events::{
+        centralized::{
+            covered_runs, CentralizedEventManagerBuilder, CoverageSummary, DeltaDecoder,
+            DeltaEncoder, EventOutcome, EventTap, ForwardingQuota, GenerationMetadata,
+            GlobalCoverageMetadata, IncompatibleHandler, MainLoopExit, MainLoopStats, MapHighWater,
+            MultiInner, ObserversPayload, PendingForward, QuotaStats, Shed, SheddingStats,
+            _LLMP_TAG_TO_MAIN, _LLMP_TAG_TO_MAIN_DELTA, CENTRALIZED_ROLE_ENV,
+            DEFAULT_LOW_TRUST_REEXECS, MAX_SEND_ATTEMPTS, SELF_MESSAGE_WARN_THRESHOLD,
+        },
+        CentralizedEventManager, CentralizedLlmpHook, CustomBufEventResult, Event, EventConfig,
+        EventFirer, EventManagerHook, EventProcessor, EventRestarter, HasCentralizedMetrics,
+        HasPendingEvents, InputHasher, LlmpEventManager, LogSeverity, NopEventManager,
+        ProgressReporter, ProvenanceMetadata, SHUTDOWN_UNMAP_TIMEOUT,
+    },
+    executors::{Executor, ExitKind, HasTimeout, InProcessExecutor, WithObservers},
+    feedbacks::{
+        ConstFeedback, Feedback, MapFeedbackMetadata, MaxMapFeedback, StateInitializer,
+        TimeoutFeedback,
+    },
+    fuzzer::{ForcedInputMetadata, SkippedInputsMetadata},
+    inputs::{BytesInput, HasMutatorBytes, NopInput},
+    monitors::{
+        UserStatsValue, CENTRALIZED_SEND_DROPPED_STAT, CENTRALIZED_SEND_ERRORS_STAT,
+        CENTRALIZED_SHED_DEFERRED_STAT, CENTRALIZED_SHED_DROPPED_STAT, CENTRALIZED_SHED_STATS_STAT,
+    },
+    observers::{MapObserver, StdMapObserver},
+    schedulers::QueueScheduler,
+    stages::{CorpusPruning, Stage},
+    state::{
+        HasCorpus, HasExecutions, HasSolutions, NopState, State, StdState, Stoppable, UsesState,
+    },
+    testing::{bytes_state, BytesState, RecordingEventManager},
+    BudgetKind, Error, HasMetadata, HasNamedMetadata, StdFuzzer,
+};
+
+/// Interesting if the first byte of the input, modulo 4, was not seen before
+#[derive(Debug, Default)]
+struct FirstByteFeedback {
+    seen: [bool; 4],
+    #[cfg(feature = "track_hit_feedbacks")]
+    last_result: Option<bool>,
+}
+
+impl Named for FirstByteFeedback {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("FirstByteFeedback");
+        &NAME
+    }
+}
+
+impl<S> StateInitializer<S> for FirstByteFeedback {}
+
+impl<EM, OT, S> Feedback<EM, BytesInput, OT, S> for FirstByteFeedback {
+    fn is_interesting(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        input: &BytesInput,
+        _observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        let idx = usize::from(input.as_ref()[0]) % self.seen.len();
+        let interesting = !self.seen[idx];
+        self.seen[idx] = true;
+        #[cfg(feature = "track_hit_feedbacks")]
+        {
+            self.last_result = Some(interesting);
+        }
+        Ok(interesting)
+    }
+
+    #[cfg(feature = "track_hit_feedbacks")]
+    fn last_result(&self) -> Result<bool, Error> {
+        self.last_result
+            .ok_or(Error::illegal_state("No last result set"))
+    }
+}
+
+/// The observers of [`NewEdgeFeedback`]
+type EdgeObservers = (StdMapObserver<'static, u8, false>, ());
+
+/// Interesting if the map of the observer hits an entry not seen before
+#[derive(Debug, Default)]
+struct NewEdgeFeedback {
+    seen: [bool; 16],
+    #[cfg(feature = "track_hit_feedbacks")]
+    last_result: Option<bool>,
+}
+
+impl Named for NewEdgeFeedback {
+    fn name(&self) -> &Cow<'static, str> {
+        static NAME: Cow<'static, str> = Cow::Borrowed("NewEdgeFeedback");
+        &NAME
+    }
+}
+
+impl<S> StateInitializer<S> for NewEdgeFeedback {}
+
+impl<EM, S> Feedback<EM, BytesInput, EdgeObservers, S> for NewEdgeFeedback {
+    fn is_interesting(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _input: &BytesInput,
+        observers: &EdgeObservers,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        let mut interesting = false;
+        for (seen, hits) in self.seen.iter_mut().zip(observers.0.to_vec()) {
+            if hits > 0 && !*seen {
+                *seen = true;
+                interesting = true;
+            }
+        }
+        #[cfg(feature = "track_hit_feedbacks")]
+        {
+            self.last_result = Some(interesting);
+        }
+        Ok(interesting)
+    }
+
+    #[cfg(feature = "track_hit_feedbacks")]
+    fn last_result(&self) -> Result<bool, Error> {
+        self.last_result
+            .ok_or(Error::illegal_state("No last result set"))
+    }
+}
+
+/// An LLMP client with the given id, on a map no broker ever maps
+fn unbrokered_client(
+    shmem_provider: &mut StdShMemProvider,
+    id: ClientId,
+) -> LlmpClient<StdShMemProvider> {
+    let mut client = LlmpClient::new(
+        shmem_provider.clone(),
+        LlmpSharedMap::new(id, shmem_provider.new_shmem(1024).unwrap()),
+        id,
+    )
+    .unwrap();
+    // A little hack for CI. Don't do that in a real-world scenario.
+    unsafe {
+        client.mark_safe_to_unmap();
+    }
+    client
+}
+
+/// The inner [`LlmpEventManager`] of a node, on client 0, and the client of its centralized manager,
+/// with the given id, neither of them connected to a broker
+fn client_pair<S>(
+    centralized_id: ClientId,
+) -> (
+    LlmpEventManager<(), S, StdShMemProvider>,
+    LlmpClient<StdShMemProvider>,
+)
+where
+    S: State,
+{
+    let mut shmem_provider = StdShMemProvider::new().unwrap();
+    let client = unbrokered_client(&mut shmem_provider, ClientId(0));
+    let centralized_client = unbrokered_client(&mut shmem_provider, centralized_id);
+    let inner = LlmpEventManager::builder()
+        .build_from_client(client, "fuzzer".into(), None)
+        .unwrap();
+    (inner, centralized_client)
+}
+
+#[test]
+fn test_multi_inner_routing() {
+    let mut mgr = MultiInner::new(
+        tuple_list!(
+            RecordingEventManager::<NopState<NopInput>>::new(),
+            RecordingEventManager::<NopState<NopInput>>::new()
+        ),
+        |event: &Event<NopInput>| usize::from(matches!(event, Event::Log { .. })),
+    );
+    let mut state = NopState::new();
+
+    mgr.fire(
+        &mut state,
+        Event::UpdateExecStats {
+            time: Duration::ZERO,
+            executions: 0,
+            phantom: PhantomData,
+        },
+    )
+    .unwrap();
+    mgr.log(&mut state, LogSeverity::Info, "hello".into())
+        .unwrap();
+
+    assert_eq!(mgr.primary().fired, ["Client Heartbeat"]);
+    assert_eq!(mgr.others().0.fired, ["Log"]);
+}
+
+#[test]
+fn test_multi_inner_bad_route() {
+    let mut mgr = MultiInner::new(
+        tuple_list!(RecordingEventManager::<NopState<NopInput>>::new()),
+        |_: &Event<NopInput>| 1,
+    );
+    // The closure selector is skipped, so the manager is still `Debug`
+    assert!(format!("{mgr:?}").starts_with("MultiInner"));
+    assert!(mgr
+        .log(&mut NopState::new(), LogSeverity::Info, "hello".into())
+        .is_err());
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_multi_inner_in_main_node() {
+    let mut feedback = FirstByteFeedback::default();
+    let mut objective = ConstFeedback::new(false);
+    let mut state = bytes_state(&mut feedback, &mut objective);
+    let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+
+    let (primary, centralized_client) = client_pair(ClientId(1));
+    let inner = MultiInner::new(
+        tuple_list!(primary, RecordingEventManager::new()),
+        // Testcases go to the second manager, everything else to the primary one
+        |event: &Event<BytesInput>| usize::from(matches!(event, Event::NewTestcase { .. })),
+    );
+    let mut mgr = CentralizedEventManager::builder()
+        .is_main(true)
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+
+    let mut harness = |_: &BytesInput| ExitKind::Ok;
+    let mut executor = InProcessExecutor::new(
+        &mut harness,
+        tuple_list!(),
+        &mut fuzzer,
+        &mut state,
+        &mut mgr,
+    )
+    .unwrap();
+
+    mgr.inject_event(
+        &mut fuzzer,
+        &mut state,
+        &mut executor,
+        ClientId(2),
+        Event::NewTestcase {
+            input: BytesInput::new(vec![0]),
+            observers_buf: None,
+            exit_kind: ExitKind::Ok,
+            corpus_size: 0,
+            client_config: EventConfig::AlwaysUnique,
+            time: Duration::ZERO,
+            forward_id: Some(ClientId(2)),
+            generation: None,
+            calibration: None,
+            forced: false,
+            #[cfg(feature = "multi_machine")]
+            node_id: None,
+        },
+    )
+    .unwrap();
+    mgr.log(&mut state, LogSeverity::Info, "hello".into())
+        .unwrap();
+
+    // The accepted testcase is announced through the second manager, the log is not
+    assert_eq!(state.corpus().count(), 1);
+    assert_eq!(mgr.inner.others().0.fired, ["Testcase"]);
+}
+
+/// Let a fresh main node handle `events`, or replay them from `replay`.
+/// Returns the accepted and discarded testcases, the final corpus size, and the target executions.
+fn run_main_node<B, H>(
+    builder: CentralizedEventManagerBuilder<B, H>,
+    events: &[Event<BytesInput>],
+    tap: Option<EventTap>,
+    replay: Option<&[u8]>,
+) -> (u64, u64, usize, u64)
+where
+    B: IncompatibleHandler<BytesInput>,
+    H: InputHasher<BytesInput> + 'static,
+{
+    run_main_node_with_state(builder, events, tap, replay).0
+}
+
+/// Like [`run_main_node`], but also returns the state of the main node
+#[allow(clippy::type_complexity)]
+fn run_main_node_with_state<B, H>(
+    builder: CentralizedEventManagerBuilder<B, H>,
+    events: &[Event<BytesInput>],
+    tap: Option<EventTap>,
+    replay: Option<&[u8]>,
+) -> ((u64, u64, usize, u64), BytesState)
+where
+    B: IncompatibleHandler<BytesInput>,
+    H: InputHasher<BytesInput> + 'static,
+{
+    let mut feedback = FirstByteFeedback::default();
+    let mut objective = ConstFeedback::new(false);
+    let mut state = bytes_state(&mut feedback, &mut objective);
+    let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+
+    let (inner, centralized_client) = client_pair(ClientId(1));
+    let mut mgr = builder
+        .is_main(true)
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+    mgr.set_event_tap(tap);
+
+    let mut harness = |_: &BytesInput| ExitKind::Ok;
+    let mut executor = InProcessExecutor::new(
+        &mut harness,
+        tuple_list!(),
+        &mut fuzzer,
+        &mut state,
+        &mut mgr,
+    )
+    .unwrap();
+
+    if let Some(replay) = replay {
+        let count = mgr
+            .replay_from(replay, &mut fuzzer, &mut state, &mut executor)
+            .unwrap();
+        assert_eq!(count, events.len());
+    } else {
+        for event in events {
+            mgr.inject_event(
+                &mut fuzzer,
+                &mut state,
+                &mut executor,
+                ClientId(2),
+                event.clone(),
+            )
+            .unwrap();
+        }
+    }
+    let stats = (
+        mgr.stats.accepted,
+        mgr.stats.discarded,
+        state.corpus().count(),
+        *state.executions(),
+    );
+    (stats, state)
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_tap_replay() {
+    let events = [0, 1, 0, 2, 1, 5]
+        .into_iter()
+        .map(|byte| Event::NewTestcase {
+            input: BytesInput::new(vec![byte]),
+            observers_buf: None,
+            exit_kind: ExitKind::Ok,
+            corpus_size: 0,
+            client_config: EventConfig::AlwaysUnique,
+            time: Duration::ZERO,
+            forward_id: Some(ClientId(2)),
+            generation: None,
+            calibration: None,
+            forced: false,
+            #[cfg(feature = "multi_machine")]
+            node_id: None,
+        })
+        .collect::<Vec<_>>();
+
+    let tap_path = env::temp_dir().join(format!("libafl_event_tap_{}", process::id()));
+    let tap = EventTap::new(File::create(&tap_path).unwrap());
+    let live = run_main_node(CentralizedEventManager::builder(), &events, Some(tap), None);
+    let recorded = fs::read(&tap_path).unwrap();
+    fs::remove_file(&tap_path).unwrap();
+
+    // 0, 1, 2 are new, the rest maps to known entries
+    assert_eq!(live, (3, 3, 3, 6));
+    let replayed = run_main_node(
+        CentralizedEventManager::builder(),
+        &events,
+        None,
+        Some(&recorded),
+    );
+    assert_eq!(live, replayed);
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_on_incompatible() {
+    let events = [
+        (Some("fuzzer"), 0),
+        (Some("other target"), 1),
+        (Some("other target"), 2),
+        (None, 3),
+    ]
+    .into_iter()
+    .map(|(config, byte)| Event::NewTestcase {
+        input: BytesInput::new(vec![byte]),
+        observers_buf: None,
+        exit_kind: ExitKind::Ok,
+        corpus_size: 0,
+        client_config: config.map_or(EventConfig::AlwaysUnique, EventConfig::from_name),
+        time: Duration::ZERO,
+        forward_id: Some(ClientId(2)),
+        generation: None,
+        calibration: None,
+        forced: false,
+        #[cfg(feature = "multi_machine")]
+        node_id: None,
+    })
+    .collect::<Vec<_>>();
+
+    let bounced = Rc::new(RefCell::new(vec![]));
+    let bounced_clone = bounced.clone();
+    let builder = CentralizedEventManager::builder().on_incompatible(
+        move |event: Event<BytesInput>, client_id| {
+            let Event::NewTestcase { input, .. } = event else {
+                panic!("Unexpected event {}", event.name());
+            };
+            bounced_clone
+                .borrow_mut()
+                .push((input.as_ref()[0], client_id));
+        },
+    );
+
+    // The compatible testcase and the one of an `AlwaysUnique` config were executed,
+    // the ones of a different config went to the handler
+    assert_eq!(run_main_node(builder, &events, None, None), (2, 0, 2, 2));
+    assert_eq!(*bounced.borrow(), [(1, ClientId(2)), (2, ClientId(2))]);
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_dedup_input_hasher() {
+    let events = [&b"ab"[..], b"AB", b"ab", b"cd"]
+        .into_iter()
+        .map(|bytes| Event::NewTestcase {
+            input: BytesInput::new(bytes.to_vec()),
+            observers_buf: None,
+            exit_kind: ExitKind::Ok,
+            corpus_size: 0,
+            client_config: EventConfig::AlwaysUnique,
+            time: Duration::ZERO,
+            forward_id: Some(ClientId(2)),
+            generation: None,
+            calibration: None,
+            forced: false,
+            #[cfg(feature = "multi_machine")]
+            node_id: None,
+        })
+        .collect::<Vec<_>>();
+
+    // Only the exact repeat is skipped, `AB` runs and maps to the entry of `ab`
+    let builder = CentralizedEventManager::builder().dedup(16);
+    assert_eq!(run_main_node(builder, &events, None, None), (2, 1, 2, 3));
+
+    // Ignoring the case, `AB` is a repeat as well
+    let builder = CentralizedEventManager::builder()
+        .dedup(16)
+        .input_hasher(|input: &BytesInput| hash_std(&input.bytes().to_ascii_lowercase()));
+    assert_eq!(run_main_node(builder, &events, None, None), (2, 0, 2, 2));
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_import_only() {
+    let events = [0, 1, 0]
+        .into_iter()
+        .map(|byte| Event::NewTestcase {
+            input: BytesInput::new(vec![byte]),
+            observers_buf: None,
+            exit_kind: ExitKind::Ok,
+            corpus_size: 0,
+            client_config: EventConfig::AlwaysUnique,
+            time: Duration::ZERO,
+            forward_id: Some(ClientId(2)),
+            generation: None,
+            calibration: None,
+            forced: false,
+            #[cfg(feature = "multi_machine")]
+            node_id: None,
+        })
+        .collect::<Vec<_>>();
+
+    // Every testcase is imported, even the duplicate, and the target never runs
+    let builder = CentralizedEventManager::builder().import_only(true);
+    assert_eq!(run_main_node(builder, &events, None, None), (3, 0, 3, 0));
+}
+
+/// Let a fresh main node handle testcases with the given coverage `maps`, forwarded with full
+/// or delta-encoded observers. Returns if each of them was accepted, and the target executions.
+fn run_map_main_node(maps: &[[u8; 16]], deltas: bool) -> (Vec<bool>, u64) {
+    let observer = StdMapObserver::owned("map", vec![0_u8; 16]);
+    let mut feedback = NewEdgeFeedback::default();
+    let mut objective = ConstFeedback::new(false);
+    let mut state = bytes_state(&mut feedback, &mut objective);
+    let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+
+    let (inner, centralized_client) = client_pair(ClientId(1));
+    let mut mgr = CentralizedEventManager::builder()
+        .is_main(true)
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+
+    let mut harness = |_: &BytesInput| ExitKind::Ok;
+    let mut executor = InProcessExecutor::new(
+        &mut harness,
+        tuple_list!(observer),
+        &mut fuzzer,
+        &mut state,
+        &mut mgr,
+    )
+    .unwrap();
+
+    let mut encoder = DeltaEncoder::default();
+    let mut accepted = vec![];
+    for (nth, map) in maps.iter().enumerate() {
+        let observers = tuple_list!(StdMapObserver::owned("map", map.to_vec()));
+        let full = postcard::to_allocvec(&observers).unwrap();
+        let observers_buf = if deltas {
+            let payload = encoder.encode(full.clone());
+            if nth > 0 {
+                let ObserversPayload::Delta { runs, .. } = &payload else {
+                    panic!("Expected a delta, got {payload:?}");
+                };
+                assert!(runs.iter().map(|(_, bytes)| bytes.len()).sum::<usize>() < full.len());
+            }
+            postcard::to_allocvec(&payload).unwrap()
+        } else {
+            full
+        };
+
+        let mut event = Event::NewTestcase {
+            input: BytesInput::new(vec![nth as u8]),
+            observers_buf: Some(observers_buf),
+            exit_kind: ExitKind::Ok,
+            corpus_size: 0,
+            client_config: EventConfig::from_name("fuzzer"),
+            time: Duration::ZERO,
+            forward_id: Some(ClientId(2)),
+            generation: None,
+            calibration: None,
+            forced: false,
+            #[cfg(feature = "multi_machine")]
+            node_id: None,
+        };
+        if deltas {
+            event = mgr.decode_delta_event(ClientId(2), event).unwrap();
+        }
+        let before = mgr.stats.accepted;
+        mgr.handle_in_main(&mut fuzzer, &mut executor, &mut state, ClientId(2), event)
+            .unwrap();
+        accepted.push(mgr.stats.accepted > before);
+    }
+    (accepted, *state.executions())
+}
+
+/// Let a fresh main node, whose objective considers every checked testcase a solution, handle
+/// a trusted `Ok` and a trusted crashing testcase with observers. Returns the corpus and solutions count.
+fn run_skip_objective_main_node(skip_trusted_objectives: bool) -> (usize, usize) {
+    let mut feedback = FirstByteFeedback::default();
+    let mut objective = ConstFeedback::new(true);
+    let mut state = bytes_state(&mut feedback, &mut objective);
+    let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+
+    let (inner, centralized_client) = client_pair(ClientId(1));
+    let mut mgr = CentralizedEventManager::builder()
+        .is_main(true)
+        .skip_trusted_objectives(skip_trusted_objectives)
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+    let mut harness = |_: &BytesInput| ExitKind::Ok;
+    let mut executor = InProcessExecutor::new(
+        &mut harness,
+        tuple_list!(),
+        &mut fuzzer,
+        &mut state,
+        &mut mgr,
+    )
+    .unwrap();
+
+    for (byte, exit_kind) in [(0, ExitKind::Ok), (1, ExitKind::Crash)] {
+        let event = Event::NewTestcase {
+            input: BytesInput::new(vec![byte]),
+            observers_buf: Some(postcard::to_allocvec(&tuple_list!()).unwrap()),
+            exit_kind,
+            corpus_size: 0,
+            client_config: EventConfig::from_name("fuzzer"),
+            time: Duration::ZERO,
+            forward_id: Some(ClientId(2)),
+            generation: None,
+            calibration: None,
+            forced: false,
+            #[cfg(feature = "multi_machine")]
+            node_id: None,
+        };
+        mgr.handle_in_main(&mut fuzzer, &mut executor, &mut state, ClientId(2), event)
+            .unwrap();
+    }
+    assert_eq!(*state.executions(), 0);
+    (state.corpus().count(), state.solutions().count())
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_skip_trusted_objectives() {
+    // By default, the objective checks every testcase
+    assert_eq!(run_skip_objective_main_node(false), (0, 2));
+    // The `Ok` testcase only goes through the feedback, the crash still hits the objective
+    assert_eq!(run_skip_objective_main_node(true), (1, 1));
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_forward_map_deltas() {
+    let mut maps = [[0_u8; 16]; 5];
+    maps[0][1] = 1;
+    maps[1][1] = 1;
+    maps[1][7] = 2;
+    maps[2][7] = 1;
+    maps[3][1] = 1;
+    maps[3][7] = 2;
+    maps[4][15] = 1;
+
+    let full = run_map_main_node(&maps, false);
+    assert_eq!(full, (vec![true, true, false, false, true], 0));
+    assert_eq!(run_map_main_node(&maps, true), full);
+}
+
+/// Let a fresh main node with a [`MaxMapFeedback`] on a small `map` observer handle testcases with the given
+/// serialized observers, a small coverage map first, and a large map the feedback ignores.
+/// Returns if each of them was accepted, or `None` if handling it failed.
+fn run_history_main_node(observers_bufs: &[Vec<u8>], lazy: bool) -> Vec<Option<bool>> {
+    let map: StdMapObserver<'static, u8, false> = StdMapObserver::owned("map", vec![0_u8; 16]);
+    // Named unlike the map, so its history is found through the map
+    let mut feedback = MaxMapFeedback::with_name("coverage", &map);
+    let mut objective = ConstFeedback::new(false);
+    let mut state = bytes_state(&mut feedback, &mut objective);
+    let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+
+    let (inner, centralized_client) = client_pair(ClientId(1));
+    let mut mgr = CentralizedEventManager::builder()
+        .is_main(true)
+        .lazy_observers(lazy)
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+
+    let mut harness = |_: &BytesInput| ExitKind::Ok;
+    let mut executor = InProcessExecutor::new(
+        &mut harness,
+        tuple_list!(map, StdMapObserver::owned("big", vec![0_u8; 4096])),
+        &mut fuzzer,
+        &mut state,
+        &mut mgr,
+    )
+    .unwrap();
+
+    let mut accepted = vec![];
+    for (nth, observers_buf) in observers_bufs.iter().enumerate() {
+        let event = Event::NewTestcase {
+            input: BytesInput::new(vec![nth as u8]),
+            observers_buf: Some(observers_buf.clone()),
+            exit_kind: ExitKind::Ok,
+            corpus_size: 0,
+            client_config: EventConfig::from_name("fuzzer"),
+            time: Duration::ZERO,
+            forward_id: Some(ClientId(2)),
+            generation: None,
+            calibration: None,
+            forced: false,
+            #[cfg(feature = "multi_machine")]
+            node_id: None,
+        };
+        let before = mgr.stats.accepted;
+        accepted.push(
+            mgr.handle_in_main(&mut fuzzer, &mut executor, &mut state, ClientId(2), event)
+                .ok()
+                .map(|()| mgr.stats.accepted > before),
+        );
+    }
+    assert_eq!(*state.executions(), 0);
+    accepted
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_lazy_observers() {
+    let mut maps = [[0_u8; 16]; 6];
+    maps[0][1] = 1;
+    maps[1][1] = 1;
+    maps[2][1] = 2;
+    maps[2][7] = 1;
+    maps[3][7] = 1;
+    maps[4][15] = 3;
+    let mut bufs = maps
+        .iter()
+        .map(|map| {
+            let observers = tuple_list!(
+                StdMapObserver::owned("map", map.to_vec()),
+                StdMapObserver::owned("big", vec![1_u8; 4096])
+            );
+            postcard::to_allocvec(&observers).unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    // The decisions match the full deserialization
+    let eager = run_history_main_node(&bufs, false);
+    assert_eq!(
+        eager,
+        [true, false, true, false, true, false].map(Some).to_vec()
+    );
+    assert_eq!(run_history_main_node(&bufs, true), eager);
+
+    // Only the coverage map of a testcase without new coverage is deserialized
+    let map_len = postcard::to_allocvec(&StdMapObserver::owned("map", maps[5].to_vec()))
+        .unwrap()
+        .len();
+    bufs[5].truncate(map_len);
+    assert_eq!(run_history_main_node(&bufs, false)[5], None);
+    assert_eq!(run_history_main_node(&bufs, true)[5], Some(false));
+}
+
+#[test]
+fn test_delta_decoder_fallback() {
+    let mut encoder = DeltaEncoder::default();
+    let mut decoder = DeltaDecoder::default();
+    let first = encoder.encode(vec![0, 1, 2, 3]);
+    let second = encoder.encode(vec![0, 4, 5, 3]);
+    assert_eq!(
+        second,
+        ObserversPayload::Delta {
+            len: 4,
+            runs: vec![(1, vec![4, 5])]
+        }
+    );
+
+    // No base for this client yet, the testcase has to be re-executed
+    assert_eq!(decoder.decode(ClientId(2), second.clone()), None);
+    assert_eq!(decoder.decode(ClientId(2), first), Some(vec![0, 1, 2, 3]));
+    assert_eq!(decoder.decode(ClientId(2), second), Some(vec![0, 4, 5, 3]));
+    // A new layout always goes out in full
+    assert_eq!(encoder.encode(vec![1]), ObserversPayload::Full(vec![1]));
+}
+
+/// The current time of [`fake_clock`], in milliseconds
+static FAKE_TIME_MS: AtomicU64 = AtomicU64::new(0);
+
+fn fake_clock() -> Duration {
+    Duration::from_millis(FAKE_TIME_MS.load(Ordering::Relaxed))
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_keepalive() {
+    let (inner, centralized_client) = client_pair(ClientId(1));
+    let mut mgr = CentralizedEventManager::builder()
+        .keepalive(Duration::from_secs(10))
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+    FAKE_TIME_MS.store(1_000, Ordering::Relaxed);
+    mgr.keepalive.clock = fake_clock;
+    mgr.keepalive.last_sent = fake_clock();
+
+    let mut state = StdState::nop::<BytesInput>().unwrap();
+    let log = |mgr: &mut CentralizedEventManager<_, _, _, _>, state: &mut _| {
+        mgr.fire(
+            state,
+            Event::Log {
+                severity_level: LogSeverity::Info,
+                message: String::from("not forwarded"),
+                phantom: PhantomData,
+            },
+        )
+        .unwrap();
+    };
+
+    // Not due yet
+    FAKE_TIME_MS.store(10_999, Ordering::Relaxed);
+    log(&mut mgr, &mut state);
+    assert_eq!(mgr.keepalive.last_sent, Duration::from_secs(1));
+
+    // Due, fire sends a keepalive
+    FAKE_TIME_MS.store(11_000, Ordering::Relaxed);
+    log(&mut mgr, &mut state);
+    assert_eq!(mgr.keepalive.last_sent, Duration::from_secs(11));
+
+    // Forwarded testcases reset the timer
+    FAKE_TIME_MS.store(15_000, Ordering::Relaxed);
+    mgr.fire(
+        &mut state,
+        Event::NewTestcase {
+            input: BytesInput::new(vec![0]),
+            observers_buf: None,
+            exit_kind: ExitKind::Ok,
+            corpus_size: 1,
+            client_config: EventConfig::AlwaysUnique,
+            time: Duration::ZERO,
+            forward_id: None,
+            generation: None,
+            calibration: None,
+            forced: false,
+            #[cfg(feature = "multi_machine")]
+            node_id: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(mgr.keepalive.last_sent, Duration::from_secs(15));
+
+    // Due again, maybe_report_progress sends a keepalive carrying the executions
+    FAKE_TIME_MS.store(25_000, Ordering::Relaxed);
+    *state.executions_mut() = 1234;
+    mgr.maybe_report_progress(&mut state, Duration::from_secs(3600))
+        .unwrap();
+    assert_eq!(mgr.keepalive.last_sent, Duration::from_secs(25));
+    assert_eq!(mgr.keepalive.executions, 1234);
+}
+
+#[test]
+#[serial]
+#[cfg(feature = "llmp_compression")]
+#[cfg_attr(miri, ignore)]
+fn test_compress_control() {
+    use libafl_bolts::{compress::GzipCompressor, llmp::LLMP_FLAG_COMPRESSED};
+
+    let (inner, centralized_client) = client_pair(ClientId(1));
+    // Read what the secondary sends, like the centralized broker would
+    let mut to_main = LlmpReceiver::on_existing_from_description(
+        StdShMemProvider::new().unwrap(),
+        &centralized_client.sender().describe().unwrap(),
+    )
+    .unwrap();
+    let mut mgr = CentralizedEventManager::builder()
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+    // Everything above the threshold
+    mgr.compressor = GzipCompressor::with_threshold(0);
+
+    let mut state = StdState::nop::<BytesInput>().unwrap();
+    mgr.send_keepalive().unwrap();
+    mgr.fire(
+        &mut state,
+        Event::NewTestcase {
+            input: BytesInput::new(vec![0; 64]),
+            observers_buf: None,
+            exit_kind: ExitKind::Ok,
+            corpus_size: 1,
+            client_config: EventConfig::AlwaysUnique,
+            time: Duration::ZERO,
+            forward_id: None,
+            generation: None,
+            calibration: None,
+            forced: false,
+            #[cfg(feature = "multi_machine")]
+            node_id: None,
+        },
+    )
+    .unwrap();
+
+    // The keepalive goes out as is, the testcase compressed
+    let mut compressed = vec![];
+    while let Some((_, tag, flags, _)) = to_main.recv_buf_with_flags().unwrap() {
+        assert_eq!(tag, _LLMP_TAG_TO_MAIN);
+        compressed.push(flags & LLMP_FLAG_COMPRESSED == LLMP_FLAG_COMPRESSED);
+    }
+    assert_eq!(compressed, [false, true]);
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_map_high_water() {
+    const INPUT_LEN: usize = 64 * 1024;
+
+    let (inner, centralized_client) = client_pair(ClientId(1));
+    let mut mgr = CentralizedEventManager::builder()
+        .map_high_water(0.9)
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+    assert_eq!(mgr.map_high_water.mark, Some(0.9));
+
+    let usage = mgr.map_usage();
+    assert!(usage.capacity > 0);
+    assert!(usage.ratio() < 0.9);
+
+    // Pretend the map is small: `INPUT_LEN` more bytes make it cross the mark
+    #[allow(clippy::cast_precision_loss)]
+    let mark = (usage.used + INPUT_LEN) as f64 / usage.capacity as f64;
+    mgr.map_high_water = MapHighWater::new(Some(mark));
+
+    // Random bytes, so compression does not shrink the input
+    let mut rand = StdRand::with_seed(0);
+    let input = (0..INPUT_LEN)
+        .map(|_| rand.next().to_le_bytes()[0])
+        .collect::<Vec<_>>();
+    let mut state = StdState::nop::<BytesInput>().unwrap();
+    mgr.fire(
+        &mut state,
+        Event::NewTestcase {
+            input: BytesInput::new(input),
+            observers_buf: None,
+            exit_kind: ExitKind::Ok,
+            corpus_size: 1,
+            client_config: EventConfig::AlwaysUnique,
+            time: Duration::ZERO,
+            forward_id: None,
+            generation: None,
+            calibration: None,
+            forced: false,
+            #[cfg(feature = "multi_machine")]
+            node_id: None,
+        },
+    )
+    .unwrap();
+
+    assert!(mgr.map_usage().used >= usage.used + INPUT_LEN);
+    assert!(mgr.map_high_water.above);
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_shutdown_deadline() {
+    /// Shut down through the [`EventProcessor`] of the given executor and fuzzer
+    fn shutdown<E, EM, Z>(mgr: &mut EM, _executor: &E, _fuzzer: &Z) -> Result<(), Error>
+    where
+        EM: EventProcessor<E, Z>,
+    {
+        mgr.on_shutdown()
+    }
+
+    let mut feedback = ConstFeedback::new(false);
+    let mut objective = ConstFeedback::new(false);
+    let mut state = bytes_state(&mut feedback, &mut objective);
+    let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+
+    let mut shmem_provider = StdShMemProvider::new().unwrap();
+    let client = unbrokered_client(&mut shmem_provider, ClientId(0));
+    // Read what the inner manager sends to its broker
+    let mut broker = LlmpReceiver::on_existing_from_description(
+        shmem_provider.clone(),
+        &client.sender().describe().unwrap(),
+    )
+    .unwrap();
+    // The centralized broker never maps our messages
+    let centralized_client = LlmpClient::new(
+        shmem_provider.clone(),
+        LlmpSharedMap::new(ClientId(1), shmem_provider.new_shmem(1024).unwrap()),
+        ClientId(1),
+    )
+    .unwrap();
+    let inner = LlmpEventManager::builder()
+        .build_from_client(client, "fuzzer".into(), None)
+        .unwrap();
+    let mut mgr = CentralizedEventManager::builder()
+        .shutdown_deadline(Duration::from_millis(50))
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+
+    let mut harness = |_: &BytesInput| ExitKind::Ok;
+    let executor = InProcessExecutor::new(
+        &mut harness,
+        tuple_list!(),
+        &mut fuzzer,
+        &mut state,
+        &mut mgr,
+    )
+    .unwrap();
+
+    let start = Instant::now();
+    shutdown(&mut mgr, &executor, &fuzzer).unwrap();
+    assert!(start.elapsed() < SHUTDOWN_UNMAP_TIMEOUT);
+
+    // The inner manager still told its broker that it is exiting
+    let exited = loop {
+        match broker.recv_buf() {
+            Ok(Some(_)) => {}
+            Ok(None) => break false,
+            Err(err) => break matches!(err, Error::ShuttingDown),
+        }
+    };
+    assert!(exited);
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_event_counts() {
+    let (inner, centralized_client) = client_pair(ClientId(1));
+    let mut mgr = CentralizedEventManager::builder()
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+
+    let mut state = StdState::nop::<BytesInput>().unwrap();
+    let testcase = || Event::NewTestcase {
+        input: BytesInput::new(vec![0]),
+        observers_buf: None,
+        exit_kind: ExitKind::Ok,
+        corpus_size: 1,
+        client_config: EventConfig::AlwaysUnique,
+        time: Duration::ZERO,
+        forward_id: None,
+        generation: None,
+        calibration: None,
+        forced: false,
+        #[cfg(feature = "multi_machine")]
+        node_id: None,
+    };
+    let events = [
+        testcase(),
+        Event::UpdateExecStats {
+            time: Duration::ZERO,
+            executions: 10,
+            phantom: PhantomData,
+        },
+        testcase(),
+        Event::Log {
+            severity_level: LogSeverity::Info,
+            message: String::from("not forwarded"),
+            phantom: PhantomData,
+        },
+        Event::Stop,
+    ];
+    for event in events {
+        mgr.fire(&mut state, event).unwrap();
+    }
+
+    let counts = mgr.event_counts();
+    assert_eq!(counts.get("Testcase"), Some(&2));
+    assert_eq!(counts.get("Client Heartbeat"), Some(&1));
+    assert_eq!(counts.get("Stop"), Some(&1));
+    assert_eq!(counts.get("Log"), None);
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_generation() {
+    let (inner, centralized_client) = client_pair(ClientId(1));
+    let mut mgr = CentralizedEventManager::builder()
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+
+    // Each restart of the secondary bumps the generation in its state
+    let mut state = StdState::nop::<BytesInput>().unwrap();
+    mgr.on_restart(&mut state).unwrap();
+    let restored: SerdeAnyMap =
+        postcard::from_bytes(&postcard::to_allocvec(state.metadata_map()).unwrap()).unwrap();
+    *state.metadata_map_mut() = restored;
+    mgr.on_restart(&mut state).unwrap();
+    let generation = state.metadata::<GenerationMetadata>().unwrap().generation;
+    assert_eq!(generation, 2);
+
+    // The main node records the generation of accepted testcases
+    let events = [Event::NewTestcase {
+        input: BytesInput::new(vec![0]),
+        observers_buf: None,
+        exit_kind: ExitKind::Ok,
+        corpus_size: 0,
+        client_config: EventConfig::AlwaysUnique,
+        time: Duration::from_secs(1),
+        forward_id: Some(ClientId(2)),
+        generation: Some(generation),
+        calibration: None,
+        forced: false,
+        #[cfg(feature = "multi_machine")]
+        node_id: None,
+    }];
+    let (_, state) =
+        run_main_node_with_state(CentralizedEventManager::builder(), &events, None, None);
+    let testcase = state.corpus().get(CorpusId(0)).unwrap().borrow();
+    assert!(ProvenanceMetadata::is_imported(&testcase));
+    let provenance = ProvenanceMetadata::of(&testcase).unwrap();
+    assert_eq!(
+        (provenance.client_id, provenance.generation),
+        (ClientId(2), Some(2))
+    );
+    assert_eq!(provenance.original_found_time, Duration::from_secs(1));
+    assert!(provenance.received_time > provenance.original_found_time);
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_grace_period() {
+    let events = [Event::NewTestcase {
+        input: BytesInput::new(vec![0]),
+        observers_buf: None,
+        exit_kind: ExitKind::Ok,
+        corpus_size: 0,
+        client_config: EventConfig::AlwaysUnique,
+        time: Duration::ZERO,
+        forward_id: Some(ClientId(2)),
+        generation: None,
+        calibration: None,
+        forced: false,
+        #[cfg(feature = "multi_machine")]
+        node_id: None,
+    }];
+    let (_, mut state) =
+        run_main_node_with_state(CentralizedEventManager::builder(), &events, None, None);
+    let forwarded = CorpusId(0);
+    assert!(state
+        .corpus()
+        .get(forwarded)
+        .unwrap()
+        .borrow()
+        .has_metadata::<AddedAtMetadata>());
+    for input in [vec![1], vec![2], vec![3]] {
+        state
+            .corpus_mut()
+            .add(Testcase::new(BytesInput::new(input)))
+            .unwrap();
+    }
+
+    // Everything else is disabled, but the new entry survives the next prune
+    let mut pruning = CorpusPruning::try_new(1.0)
+        .unwrap()
+        .grace_period(Duration::from_secs(3600));
+    pruning
+        .perform(&mut (), &mut (), &mut state, &mut ())
+        .unwrap();
+    assert!(state.corpus().get(forwarded).is_ok());
+    assert_eq!(state.corpus().count(), 1);
+
+    // Until its grace period is over
+    state
+        .corpus_mut()
+        .add(Testcase::new(BytesInput::new(vec![4])))
+        .unwrap();
+    state
+        .corpus()
+        .get(forwarded)
+        .unwrap()
+        .borrow_mut()
+        .metadata_mut::<AddedAtMetadata>()
+        .unwrap()
+        .time = Duration::ZERO;
+    pruning
+        .perform(&mut (), &mut (), &mut state, &mut ())
+        .unwrap();
+    assert_eq!(state.corpus().count(), 1);
+    assert_eq!(state.corpus().count_disabled(), 4);
+}
+
+/// Runs no target, but hangs on inputs starting with `0xff`,
+/// like a target stuck until the timeout of its executor stops it
+#[derive(Debug)]
+struct HangingExecutor<S> {
+    timeout: Duration,
+    /// The timeouts that stopped a hang
+    stopped_after: Rc<RefCell<Vec<Duration>>>,
+    phantom: PhantomData<S>,
+}
+
+impl<S> UsesState for HangingExecutor<S>
+where
+    S: State,
+{
+    type State = S;
+}
+
+impl<EM, S, Z> Executor<EM, Z> for HangingExecutor<S>
+where
+    EM: UsesState<State = S>,
+    S: State<Input = BytesInput> + HasExecutions,
+{
+    fn run_target(
+        &mut self,
+        _fuzzer: &mut Z,
+        state: &mut S,
+        _mgr: &mut EM,
+        input: &BytesInput,
+    ) -> Result<ExitKind, Error> {
+        *state.executions_mut() += 1;
+        if input.as_ref()[0] == 0xff {
+            thread::sleep(self.timeout);
+            self.stopped_after.borrow_mut().push(self.timeout);
+            return Ok(ExitKind::Timeout);
+        }
+        Ok(ExitKind::Ok)
+    }
+}
+
+impl<S> HasTimeout for HangingExecutor<S> {
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_reexec_timeout() {
+    let mut feedback = FirstByteFeedback::default();
+    let mut objective = TimeoutFeedback::new();
+    let mut state = bytes_state(&mut feedback, &mut objective);
+    let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+
+    let (inner, centralized_client) = client_pair(ClientId(1));
+    let mut mgr = CentralizedEventManager::builder()
+        .is_main(true)
+        .reexec_timeout(Duration::from_millis(20))
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+    let stopped_after = Rc::new(RefCell::new(Vec::new()));
+    let mut executor = WithObservers::new(
+        HangingExecutor {
+            timeout: Duration::from_secs(60),
+            stopped_after: stopped_after.clone(),
+            phantom: PhantomData,
+        },
+        tuple_list!(),
+    );
+
+    let testcase = |byte| Event::NewTestcase {
+        input: BytesInput::new(vec![byte]),
+        observers_buf: None,
+        exit_kind: ExitKind::Ok,
+        corpus_size: 1,
+        client_config: EventConfig::AlwaysUnique,
+        time: Duration::ZERO,
+        forward_id: Some(ClientId(7)),
+        generation: None,
+        calibration: None,
+        forced: false,
+        #[cfg(feature = "multi_machine")]
+        node_id: None,
+    };
+    for byte in [0xff, 1] {
+        mgr.inject_event(
+            &mut fuzzer,
+            &mut state,
+            &mut executor,
+            ClientId(7),
+            testcase(byte),
+        )
+        .unwrap();
+    }
+
+    // The executor stopped the hang after the timeout of the main node, then got its own back
+    assert_eq!(*stopped_after.borrow(), vec![Duration::from_millis(20)]);
+    assert_eq!(executor.timeout(), Duration::from_secs(60));
+    // The hang became an objective, and the next testcase was handled as usual
+    assert_eq!(mgr.reexec_timeouts(), 1);
+    assert_eq!(state.solutions().count(), 1);
+    assert_eq!(state.corpus().count(), 1);
+    assert_eq!(mgr.stats.accepted, 1);
+    assert_eq!(mgr.stats.discarded, 1);
+    assert_eq!(*state.executions(), 2);
+}
+
+/// Records its name when an event is accepted
+struct NamedHook {
+    name: &'static str,
+    priority: i32,
+    fired: Rc<RefCell<Vec<&'static str>>>,
+}
+
+impl<S> EventManagerHook<S> for NamedHook
+where
+    S: State,
+{
+    fn pre_exec(
+        &mut self,
+        _state: &mut S,
+        _client_id: ClientId,
+        _event: &Event<S::Input>,
+    ) -> Result<bool, Error> {
+        Ok(true)
+    }
+
+    fn on_fire(
+        &mut self,
+        _state: &mut S,
+        _client_id: ClientId,
+        _event: &Event<S::Input>,
+    ) -> Result<(), Error> {
+        self.fired.borrow_mut().push(self.name);
+        Ok(())
+    }
+
+    fn priority(&self) -> i32 {
+        self.priority
+    }
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_hook_priority() {
+    let mut feedback = FirstByteFeedback::default();
+    let mut objective = ConstFeedback::new(false);
+    let mut state = bytes_state(&mut feedback, &mut objective);
+    let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+
+    let (inner, centralized_client) = client_pair(ClientId(1));
+    // The metrics come first in the tuple, but have to see what the dedup decided
+    let fired = Rc::new(RefCell::new(Vec::new()));
+    let hook = |name, priority| NamedHook {
+        name,
+        priority,
+        fired: fired.clone(),
+    };
+    let hooks = tuple_list!(hook("metrics", 0), hook("dedup", 10), hook("log", 0));
+    let mut mgr = CentralizedEventManager::builder()
+        .is_main(true)
+        .build_from_client(inner, hooks, centralized_client, None)
+        .unwrap();
+    let mut harness = |_: &BytesInput| ExitKind::Ok;
+    let mut executor = InProcessExecutor::new(
+        &mut harness,
+        tuple_list!(),
+        &mut fuzzer,
+        &mut state,
+        &mut mgr,
+    )
+    .unwrap();
+
+    mgr.inject_event(
+        &mut fuzzer,
+        &mut state,
+        &mut executor,
+        ClientId(7),
+        Event::NewTestcase {
+            input: BytesInput::new(vec![1]),
+            observers_buf: None,
+            exit_kind: ExitKind::Ok,
+            corpus_size: 1,
+            client_config: EventConfig::AlwaysUnique,
+            time: Duration::ZERO,
+            forward_id: Some(ClientId(7)),
+            generation: None,
+            calibration: None,
+            forced: false,
+            #[cfg(feature = "multi_machine")]
+            node_id: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(*fired.borrow(), ["dedup", "metrics", "log"]);
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_inject_event() {
+    let mut feedback = FirstByteFeedback::default();
+    let mut objective = ConstFeedback::new(false);
+    let mut state = bytes_state(&mut feedback, &mut objective);
+    let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+
+    let (inner, centralized_client) = client_pair(ClientId(1));
+    let mut mgr = CentralizedEventManager::builder()
+        .is_main(true)
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+    let mut harness = |_: &BytesInput| ExitKind::Ok;
+    let mut executor = InProcessExecutor::new(
+        &mut harness,
+        tuple_list!(),
+        &mut fuzzer,
+        &mut state,
+        &mut mgr,
+    )
+    .unwrap();
+
+    mgr.inject_event(
+        &mut fuzzer,
+        &mut state,
+        &mut executor,
+        ClientId(7),
+        Event::NewTestcase {
+            input: BytesInput::new(vec![1]),
+            observers_buf: None,
+            exit_kind: ExitKind::Ok,
+            corpus_size: 1,
+            client_config: EventConfig::AlwaysUnique,
+            time: Duration::ZERO,
+            forward_id: Some(ClientId(7)),
+            generation: None,
+            calibration: None,
+            forced: false,
+            #[cfg(feature = "multi_machine")]
+            node_id: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(state.corpus().count(), 1);
+    assert_eq!(mgr.stats.accepted, 1);
+    let testcase = state.corpus().get(CorpusId(0)).unwrap().borrow();
+    assert_eq!(
+        testcase.metadata::<ProvenanceMetadata>().unwrap().client_id,
+        ClientId(7)
+    );
+    drop(testcase);
+
+    mgr.inject_event(
+        &mut fuzzer,
+        &mut state,
+        &mut executor,
+        ClientId(7),
+        Event::Stop,
+    )
+    .unwrap();
+    assert!(state.stop_requested());
+
+    // Secondaries never forward logs
+    let res = mgr.inject_event(
+        &mut fuzzer,
+        &mut state,
+        &mut executor,
+        ClientId(7),
+        Event::Log {
+            severity_level: LogSeverity::Info,
+            message: String::from("illegal"),
+            phantom: PhantomData,
+        },
+    );
+    let Err(Error::Distributed(err, _)) = res else {
+        panic!("Expected a distributed error, got {res:?}");
+    };
+    assert_eq!(err.phase, DistributedPhase::Receiving);
+    assert_eq!(err.client_id, Some(ClientId(7)));
+    assert_eq!(err.event.as_deref(), Some("Log"));
+    assert!(err.is_recoverable());
+    assert_eq!(state.corpus().count(), 1);
+    assert_eq!(mgr.event_counts()["Log"], 1);
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_event_history() {
+    let mut feedback = FirstByteFeedback::default();
+    let mut objective = ConstFeedback::new(false);
+    let mut state = bytes_state(&mut feedback, &mut objective);
+    let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+
+    let (inner, centralized_client) = client_pair(ClientId(1));
+    let mut mgr = CentralizedEventManager::builder()
+        .is_main(true)
+        .event_history(3)
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+    let mut harness = |_: &BytesInput| ExitKind::Ok;
+    let mut executor = InProcessExecutor::new(
+        &mut harness,
+        tuple_list!(),
+        &mut fuzzer,
+        &mut state,
+        &mut mgr,
+    )
+    .unwrap();
+    assert!(mgr.recent_events().is_empty());
+
+    let testcase = |byte| Event::NewTestcase {
+        input: BytesInput::new(vec![byte]),
+        observers_buf: None,
+        exit_kind: ExitKind::Ok,
+        corpus_size: 1,
+        client_config: EventConfig::AlwaysUnique,
+        time: Duration::ZERO,
+        forward_id: None,
+        generation: None,
+        calibration: None,
+        forced: false,
+        #[cfg(feature = "multi_machine")]
+        node_id: None,
+    };
+    // The second testcase has the same first byte, modulo 4, so it is not interesting
+    let received = [
+        (ClientId(7), testcase(0)),
+        (ClientId(7), testcase(4)),
+        (ClientId(8), Event::Stop),
+        (ClientId(9), testcase(1)),
+    ];
+    for (client_id, event) in received {
+        mgr.inject_event(&mut fuzzer, &mut state, &mut executor, client_id, event)
+            .unwrap();
+    }
+    let outcomes = mgr
+        .recent_events()
+        .iter()
+        .map(|summary| (summary.event, summary.client_id, summary.outcome))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        outcomes,
+        [
+            ("Testcase", ClientId(7), EventOutcome::Discarded),
+            ("Stop", ClientId(8), EventOutcome::Handled),
+            ("Testcase", ClientId(9), EventOutcome::Accepted),
+        ]
+    );
+
+    // The oldest summary makes room for the newest, even if the event is rejected
+    let res = mgr.inject_event(
+        &mut fuzzer,
+        &mut state,
+        &mut executor,
+        ClientId(9),
+        Event::Log {
+            severity_level: LogSeverity::Info,
+            message: String::from("illegal"),
+            phantom: PhantomData,
+        },
+    );
+    assert!(res.is_err());
+    let recent = mgr.recent_events();
+    assert_eq!(recent.len(), 3);
+    assert_eq!(recent[0].outcome, EventOutcome::Handled);
+    assert_eq!(recent[2].event, "Log");
+    assert_eq!(recent[2].outcome, EventOutcome::Rejected);
+    assert!(recent.windows(2).all(|pair| pair[0].time <= pair[1].time));
+}
+
+/// Let a fresh main node handle testcases with the given first byte and claimed exit kind,
+/// received from secondaries with the given `trust` levels within one `process` call.
+/// Returns the accepted and discarded testcases, the target executions,
+/// and the first byte and provenance of each corpus entry.
+fn run_trusted_main_node(
+    builder: CentralizedEventManagerBuilder,
+    trust: &[(ClientId, i32)],
+    received: &[(ClientId, u8, ExitKind)],
+) -> (u64, u64, u64, Vec<(u8, ClientId)>) {
+    let mut feedback = FirstByteFeedback::default();
+    let mut objective = ConstFeedback::new(false);
+    let mut state = bytes_state(&mut feedback, &mut objective);
+    let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+
+    let (inner, centralized_client) = client_pair(ClientId(1));
+    let mut mgr = builder
+        .is_main(true)
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+    for (client_id, level) in trust {
+        mgr.set_trust(*client_id, *level);
+    }
+
+    let mut harness = |_: &BytesInput| ExitKind::Ok;
+    let mut executor = InProcessExecutor::new(
+        &mut harness,
+        tuple_list!(),
+        &mut fuzzer,
+        &mut state,
+        &mut mgr,
+    )
+    .unwrap();
+
+    let received = received
+        .iter()
+        .map(|(client_id, byte, exit_kind)| {
+            let event = Event::NewTestcase {
+                input: BytesInput::new(vec![*byte]),
+                observers_buf: None,
+                exit_kind: *exit_kind,
+                corpus_size: 0,
+                client_config: EventConfig::AlwaysUnique,
+                time: Duration::ZERO,
+                forward_id: Some(*client_id),
+                generation: None,
+                calibration: None,
+                forced: false,
+                #[cfg(feature = "multi_machine")]
+                node_id: None,
+            };
+            (*client_id, event)
+        })
+        .collect();
+    let count = mgr
+        .handle_by_trust(&mut fuzzer, &mut executor, &mut state, received)
+        .unwrap();
+    assert_eq!(count as u64, mgr.stats.accepted + mgr.stats.discarded);
+
+    let corpus = state
+        .corpus()
+        .ids()
+        .map(|id| {
+            let testcase = state.corpus().get(id).unwrap().borrow();
+            (
+                testcase.input().as_ref().unwrap().as_ref()[0],
+                testcase.metadata::<ProvenanceMetadata>().unwrap().client_id,
+            )
+        })
+        .collect();
+    (
+        mgr.stats.accepted,
+        mgr.stats.discarded,
+        *state.executions(),
+        corpus,
+    )
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_self_messages() {
+    let mut feedback = FirstByteFeedback::default();
+    let mut objective = ConstFeedback::new(false);
+    let mut state = bytes_state(&mut feedback, &mut objective);
+    let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+
+    let mut shmem_provider = StdShMemProvider::new().unwrap();
+    let client = unbrokered_client(&mut shmem_provider, ClientId(0));
+    // The node receives what it sends itself
+    let mut centralized_client = LlmpClient::new_p2p(shmem_provider.clone(), ClientId(1)).unwrap();
+    // A little hack for CI. Don't do that in a real-world scenario.
+    unsafe {
+        centralized_client.mark_safe_to_unmap();
+    }
+    let inner = LlmpEventManager::builder()
+        .build_from_client(client, "fuzzer".into(), None)
+        .unwrap();
+    let mut mgr = CentralizedEventManager::builder()
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+
+    let mut harness = |_: &BytesInput| ExitKind::Ok;
+    let mut executor = InProcessExecutor::new(
+        &mut harness,
+        tuple_list!(),
+        &mut fuzzer,
+        &mut state,
+        &mut mgr,
+    )
+    .unwrap();
+
+    // Forward as a secondary node, then receive as the main node
+    let sent = SELF_MESSAGE_WARN_THRESHOLD + 4;
+    for byte in 0..sent {
+        mgr.fire(
+            &mut state,
+            Event::NewTestcase {
+                input: BytesInput::new(vec![byte as u8]),
+                observers_buf: None,
+                exit_kind: ExitKind::Ok,
+                corpus_size: 0,
+                client_config: EventConfig::AlwaysUnique,
+                time: Duration::ZERO,
+                forward_id: None,
+                generation: None,
+                calibration: None,
+                forced: false,
+                #[cfg(feature = "multi_machine")]
+                node_id: None,
+            },
+        )
+        .unwrap();
+    }
+    assert_eq!(mgr.stats.forwarded, sent);
+
+    mgr.is_main = true;
+    let handled = mgr
+        .receive_from_secondary(&mut fuzzer, &mut state, &mut executor)
+        .unwrap();
+    assert_eq!(handled, 0);
+    assert_eq!(mgr.self_messages(), sent);
+    assert_eq!(mgr.centralized_metrics().self_messages, sent);
+    assert_eq!(state.corpus().count(), 0);
+    assert_eq!(mgr.stats.accepted + mgr.stats.discarded, 0);
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_process_one() {
+    let mut feedback = FirstByteFeedback::default();
+    let mut objective = ConstFeedback::new(false);
+    let mut state = bytes_state(&mut feedback, &mut objective);
+    let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+
+    let mut shmem_provider = StdShMemProvider::new().unwrap();
+    let client = unbrokered_client(&mut shmem_provider, ClientId(0));
+    let mut centralized_client = LlmpClient::new(
+        shmem_provider.clone(),
+        LlmpSharedMap::new(ClientId(2), shmem_provider.new_shmem(1024).unwrap()),
+        ClientId(1),
+    )
+    .unwrap();
+    // Write to the main node, like the centralized broker would for secondary 2
+    let mut secondary = LlmpSender::on_existing_from_description(
+        shmem_provider.clone(),
+        &centralized_client.receiver().describe().unwrap(),
+    )
+    .unwrap();
+    // A little hack for CI. Don't do that in a real-world scenario.
+    unsafe {
+        centralized_client.mark_safe_to_unmap();
+        secondary.mark_safe_to_unmap();
+    }
+    let inner = LlmpEventManager::builder()
+        .build_from_client(client, "fuzzer".into(), None)
+        .unwrap();
+    let mut mgr = CentralizedEventManager::builder()
+        .is_main(true)
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+
+    let mut harness = |_: &BytesInput| ExitKind::Ok;
+    let mut executor = InProcessExecutor::new(
+        &mut harness,
+        tuple_list!(),
+        &mut fuzzer,
+        &mut state,
+        &mut mgr,
+    )
+    .unwrap();
+
+    assert!(!mgr.pending_events());
+    assert!(!mgr
+        .process_one(&mut fuzzer, &mut state, &mut executor)
+        .unwrap());
+
+    for byte in 0..2 {
+        let event = Event::NewTestcase {
+            input: BytesInput::new(vec![byte]),
+            observers_buf: None,
+            exit_kind: ExitKind::Ok,
+            corpus_size: 0,
+            client_config: EventConfig::AlwaysUnique,
+            time: Duration::ZERO,
+            forward_id: Some(ClientId(2)),
+            generation: None,
+            calibration: None,
+            forced: false,
+            #[cfg(feature = "multi_machine")]
+            node_id: None,
+        };
+        secondary
+            .send_buf(_LLMP_TAG_TO_MAIN, &postcard::to_allocvec(&event).unwrap())
+            .unwrap();
+    }
+
+    // One testcase at a time, without blocking once they are all handled
+    for handled in 1..=2 {
+        assert!(mgr.pending_events());
+        assert!(mgr
+            .process_one(&mut fuzzer, &mut state, &mut executor)
+            .unwrap());
+        assert_eq!(state.corpus().count(), handled);
+    }
+    assert!(!mgr.pending_events());
+    assert!(!mgr
+        .process_one(&mut fuzzer, &mut state, &mut executor)
+        .unwrap());
+    assert_eq!(mgr.stats.accepted, 2);
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_malformed_from_secondary() {
+    let mut feedback = FirstByteFeedback::default();
+    let mut objective = ConstFeedback::new(false);
+    let mut state = bytes_state(&mut feedback, &mut objective);
+    let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+
+    let mut shmem_provider = StdShMemProvider::new().unwrap();
+    let client = unbrokered_client(&mut shmem_provider, ClientId(0));
+    let mut centralized_client = LlmpClient::new(
+        shmem_provider.clone(),
+        LlmpSharedMap::new(ClientId(2), shmem_provider.new_shmem(1024).unwrap()),
+        ClientId(1),
+    )
+    .unwrap();
+    // Write to the main node, like the centralized broker would for secondary 2
+    let mut secondary = LlmpSender::on_existing_from_description(
+        shmem_provider.clone(),
+        &centralized_client.receiver().describe().unwrap(),
+    )
+    .unwrap();
+    // A little hack for CI. Don't do that in a real-world scenario.
+    unsafe {
+        centralized_client.mark_safe_to_unmap();
+        secondary.mark_safe_to_unmap();
+    }
+    let inner = LlmpEventManager::builder()
+        .build_from_client(client, "fuzzer".into(), None)
+        .unwrap();
+    let mut mgr = CentralizedEventManager::builder()
+        .is_main(true)
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+
+    let mut harness = |_: &BytesInput| ExitKind::Ok;
+    let mut executor = InProcessExecutor::new(
+        &mut harness,
+        tuple_list!(),
+        &mut fuzzer,
+        &mut state,
+        &mut mgr,
+    )
+    .unwrap();
+
+    let event = Event::NewTestcase {
+        input: BytesInput::new(vec![1]),
+        observers_buf: None,
+        exit_kind: ExitKind::Ok,
+        corpus_size: 0,
+        client_config: EventConfig::AlwaysUnique,
+        time: Duration::ZERO,
+        forward_id: Some(ClientId(2)),
+        generation: None,
+        calibration: None,
+        forced: false,
+        #[cfg(feature = "multi_machine")]
+        node_id: None,
+    };
+    secondary.send_buf(_LLMP_TAG_TO_MAIN, &[0xff; 8]).unwrap();
+    secondary
+        .send_buf(_LLMP_TAG_TO_MAIN, &postcard::to_allocvec(&event).unwrap())
+        .unwrap();
+
+    // The undecodable message is skipped, the testcase after it still handled
+    let handled = mgr
+        .receive_from_secondary(&mut fuzzer, &mut state, &mut executor)
+        .unwrap();
+    assert_eq!(handled, 1);
+    assert_eq!(mgr.malformed_messages(), 1);
+    assert_eq!(state.corpus().count(), 1);
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_run_main_loop() {
+    let mut feedback = FirstByteFeedback::default();
+    let mut objective = ConstFeedback::new(false);
+    let mut state = bytes_state(&mut feedback, &mut objective);
+    let mut fuzzer =
+        StdFuzzer::new(QueueScheduler::new(), feedback, objective).stop_after_executions(3);
+    let budget = *fuzzer.budget();
+
+    let mut shmem_provider = StdShMemProvider::new().unwrap();
+    let client = unbrokered_client(&mut shmem_provider, ClientId(0));
+    let mut centralized_client = LlmpClient::new(
+        shmem_provider.clone(),
+        LlmpSharedMap::new(ClientId(2), shmem_provider.new_shmem(1024).unwrap()),
+        ClientId(1),
+    )
+    .unwrap();
+    // Write to the main node, like the centralized broker would for secondary 2
+    let mut secondary = LlmpSender::on_existing_from_description(
+        shmem_provider.clone(),
+        &centralized_client.receiver().describe().unwrap(),
+    )
+    .unwrap();
+    // A little hack for CI. Don't do that in a real-world scenario.
+    unsafe {
+        centralized_client.mark_safe_to_unmap();
+        secondary.mark_safe_to_unmap();
+    }
+    let inner = LlmpEventManager::builder()
+        .build_from_client(client, "fuzzer".into(), None)
+        .unwrap();
+    let mut mgr = CentralizedEventManager::builder()
+        .is_main(true)
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+
+    let mut harness = |_: &BytesInput| ExitKind::Ok;
+    let mut executor = InProcessExecutor::new(
+        &mut harness,
+        tuple_list!(),
+        &mut fuzzer,
+        &mut state,
+        &mut mgr,
+    )
+    .unwrap();
+
+    let mut send = |bytes: &[u8]| {
+        for byte in bytes {
+            let event = Event::NewTestcase {
+                input: BytesInput::new(vec![*byte]),
+                observers_buf: None,
+                exit_kind: ExitKind::Ok,
+                corpus_size: 0,
+                client_config: EventConfig::AlwaysUnique,
+                time: Duration::ZERO,
+                forward_id: Some(ClientId(2)),
+                generation: None,
+                calibration: None,
+                forced: false,
+                #[cfg(feature = "multi_machine")]
+                node_id: None,
+            };
+            secondary
+                .send_buf(_LLMP_TAG_TO_MAIN, &postcard::to_allocvec(&event).unwrap())
+                .unwrap();
+        }
+    };
+    let poll_interval = Duration::from_millis(1);
+
+    // A stop ends the loop right away
+    send(&[0, 1]);
+    state.request_stop();
+    assert_eq!(
+        mgr.run_main_loop(
+            &mut fuzzer,
+            &mut state,
+            &mut executor,
+            poll_interval,
+            &budget
+        )
+        .unwrap(),
+        MainLoopExit::Stopped
+    );
+    assert_eq!(mgr.main_loop_stats(), MainLoopStats::default());
+    state.discard_stop_request();
+
+    // The testcases run until the executions budget runs out
+    send(&[2]);
+    assert_eq!(
+        mgr.run_main_loop(
+            &mut fuzzer,
+            &mut state,
+            &mut executor,
+            poll_interval,
+            &budget
+        )
+        .unwrap(),
+        MainLoopExit::BudgetExhausted(BudgetKind::Executions)
+    );
+    assert_eq!(*state.executions(), 3);
+    assert_eq!(state.corpus().count(), 3);
+    let stats = mgr.main_loop_stats();
+    assert_eq!(
+        (stats.iterations, stats.events, stats.last_events),
+        (1, 3, 3)
+    );
+
+    mgr.is_main = false;
+    assert!(mgr
+        .run_main_loop(
+            &mut fuzzer,
+            &mut state,
+            &mut executor,
+            poll_interval,
+            &budget
+        )
+        .is_err());
+}
+
+#[test]
+#[serial]
+#[cfg(unix)]
+#[cfg_attr(miri, ignore)]
+fn test_forward_to_main_acked() {
+    let path = env::temp_dir().join(format!("libafl_centralized_acked_{}.sock", process::id()));
+    let _ = fs::remove_file(&path);
+
+    let mut feedback = FirstByteFeedback::default();
+    let mut objective = ConstFeedback::new(false);
+    let mut state = bytes_state(&mut feedback, &mut objective);
+    let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+
+    // Two secondaries and the main node, attached to a centralized broker
+    let mut shmem_provider = StdShMemProvider::new().unwrap();
+    let mut broker = LlmpBroker::new(
+        shmem_provider.clone(),
+        tuple_list!(CentralizedLlmpHook::<BytesInput>::new().unwrap()),
+    )
+    .unwrap();
+    broker.inner_mut().launch_uds_listener_on(&path).unwrap();
+    let mut secondaries = (0..2)
+        .map(|_| {
+            let client = unbrokered_client(&mut shmem_provider, ClientId(0));
+            let inner = LlmpEventManager::builder()
+                .build_from_client(client, "fuzzer".into(), None)
+                .unwrap();
+            CentralizedEventManager::builder()
+                .build_on_uds(inner, tuple_list!(), shmem_provider.clone(), &path, None)
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let client = unbrokered_client(&mut shmem_provider, ClientId(0));
+    let inner = LlmpEventManager::builder()
+        .build_from_client(client, "fuzzer".into(), None)
+        .unwrap();
+    let mut main = CentralizedEventManager::builder()
+        .is_main(true)
+        .build_on_uds(inner, tuple_list!(), shmem_provider.clone(), &path, None)
+        .unwrap();
+    let mut pump = || {
+        for _ in 0..3 {
+            broker.broker_once().unwrap();
+        }
+    };
+
+    let mut harness = |_: &BytesInput| ExitKind::Ok;
+    let mut executor = InProcessExecutor::new(
+        &mut harness,
+        tuple_list!(),
+        &mut fuzzer,
+        &mut state,
+        &mut main,
+    )
+    .unwrap();
+
+    let testcase = |byte| Event::NewTestcase {
+        input: BytesInput::new(vec![byte]),
+        observers_buf: None,
+        exit_kind: ExitKind::Ok,
+        corpus_size: 0,
+        client_config: EventConfig::AlwaysUnique,
+        time: Duration::ZERO,
+        forward_id: None,
+        generation: None,
+        calibration: None,
+        forced: byte == 3,
+        #[cfg(feature = "multi_machine")]
+        node_id: None,
+    };
+    let mut secondary_state = StdState::nop::<BytesInput>().unwrap();
+    let [first, second] = &mut secondaries[..] else {
+        unreachable!()
+    };
+    assert_eq!(
+        first
+            .forward_to_main_acked(&mut secondary_state, testcase(0))
+            .unwrap(),
+        0
+    );
+    first.fire(&mut secondary_state, testcase(1)).unwrap();
+    assert_eq!(
+        first
+            .forward_to_main_acked(&mut secondary_state, testcase(2))
+            .unwrap(),
+        1
+    );
+    // Both secondaries count their own sequence numbers
+    assert_eq!(
+        second
+            .forward_to_main_acked(&mut secondary_state, testcase(3))
+            .unwrap(),
+        0
+    );
+    assert_eq!(first.stats.forwarded, 3);
+    assert_eq!(first.awaiting_ack().collect::<Vec<_>>(), [0, 1]);
+    assert!(first.take_acknowledged().unwrap().is_empty());
+
+    // The centralized broker drops everything else, it would never be acknowledged
+    let log = Event::Log {
+        severity_level: LogSeverity::Info,
+        message: "not for the main node".into(),
+        phantom: PhantomData,
+    };
+    assert!(first
+        .forward_to_main_acked(&mut secondary_state, log)
+        .is_err());
+    // The main node has nobody to forward to
+    assert!(main.forward_to_main_acked(&mut state, testcase(4)).is_err());
+
+    pump();
+    let handled = main
+        .receive_from_secondary(&mut fuzzer, &mut state, &mut executor)
+        .unwrap();
+    assert_eq!(handled, 4);
+    assert_eq!(state.corpus().count(), 4);
+    // The forced testcase stays marked on the main node
+    let forced = state
+        .corpus()
+        .ids()
+        .filter(|id| {
+            let testcase = state.corpus().get(*id).unwrap().borrow();
+            testcase.has_metadata::<ForcedInputMetadata>()
+        })
+        .map(|id| state.corpus().cloned_input_for_id(id).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(forced, [BytesInput::new(vec![3])]);
+
+    // Each secondary got the acknowledgments of its own acked testcases only
+    pump();
+    assert_eq!(first.take_acknowledged().unwrap(), [0, 1]);
+    assert_eq!(first.awaiting_ack().count(), 0);
+    assert!(first.take_acknowledged().unwrap().is_empty());
+    assert_eq!(second.take_acknowledged().unwrap(), [0]);
+    assert_eq!(second.awaiting_ack().count(), 0);
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[serial]
+#[cfg(unix)]
+#[cfg_attr(miri, ignore)]
+fn test_register_identity() {
+    let path = env::temp_dir().join(format!(
+        "libafl_centralized_identity_{}.sock",
+        process::id()
+    ));
+    let _ = fs::remove_file(&path);
+
+    let mut feedback = FirstByteFeedback::default();
+    let mut objective = ConstFeedback::new(false);
+    let mut state = bytes_state(&mut feedback, &mut objective);
+    let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+
+    // A labeled secondary, an unregistered one, and the main node, attached to a centralized broker
+    let mut shmem_provider = StdShMemProvider::new().unwrap();
+    let mut broker = LlmpBroker::new(
+        shmem_provider.clone(),
+        tuple_list!(CentralizedLlmpHook::<BytesInput>::new().unwrap()),
+    )
+    .unwrap();
+    broker.inner_mut().launch_uds_listener_on(&path).unwrap();
+    let secondaries = [Some("asan"), None]
+        .into_iter()
+        .map(|label| {
+            let client = unbrokered_client(&mut shmem_provider, ClientId(0));
+            let inner = LlmpEventManager::builder()
+                .build_from_client::<BytesState, _>(client, "fuzzer".into(), None)
+                .unwrap();
+            let builder = CentralizedEventManager::builder();
+            let builder = match label {
+                Some(label) => builder.identity_label(label.into()),
+                None => builder,
+            };
+            builder
+                .build_on_uds(inner, tuple_list!(), shmem_provider.clone(), &path, None)
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let client = unbrokered_client(&mut shmem_provider, ClientId(0));
+    let inner = LlmpEventManager::builder()
+        .build_from_client(client, "fuzzer".into(), None)
+        .unwrap();
+    let mut main = CentralizedEventManager::builder()
+        .is_main(true)
+        .build_on_uds(inner, tuple_list!(), shmem_provider.clone(), &path, None)
+        .unwrap();
+
+    let mut harness = |_: &BytesInput| ExitKind::Ok;
+    let mut executor = InProcessExecutor::new(
+        &mut harness,
+        tuple_list!(),
+        &mut fuzzer,
+        &mut state,
+        &mut main,
+    )
+    .unwrap();
+
+    for _ in 0..3 {
+        broker.broker_once().unwrap();
+    }
+    // The registration is no event for the fuzzer
+    let handled = main
+        .receive_from_secondary(&mut fuzzer, &mut state, &mut executor)
+        .unwrap();
+    assert_eq!(handled, 0);
+
+    // Only the labeled secondary registered on build
+    assert_eq!(main.client_registry().len(), 1);
+    let registered = main
+        .client_registry()
+        .get(secondaries[0].client.sender().id())
+        .unwrap();
+    assert_eq!(registered.identity.pid, process::id());
+    assert_eq!(registered.identity.label.as_deref(), Some("asan"));
+    assert_eq!(registered.registrations, 1);
+    assert!(main
+        .client_registry()
+        .get(secondaries[1].client.sender().id())
+        .is_none());
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[serial]
+#[cfg(unix)]
+#[cfg_attr(miri, ignore)]
+fn test_broadcast_custom() {
+    let path = env::temp_dir().join(format!("libafl_centralized_custom_{}.sock", process::id()));
+    let _ = fs::remove_file(&path);
+
+    // Two secondaries and the main node, attached to a centralized broker
+    let mut shmem_provider = StdShMemProvider::new().unwrap();
+    let mut broker = LlmpBroker::new(
+        shmem_provider.clone(),
+        tuple_list!(CentralizedLlmpHook::<BytesInput>::new().unwrap()),
+    )
+    .unwrap();
+    broker.inner_mut().launch_uds_listener_on(&path).unwrap();
+
+    let received = Rc::new(RefCell::new(Vec::new()));
+    let mut secondaries = Vec::new();
+    for name in ["first", "second"] {
+        let client = unbrokered_client(&mut shmem_provider, ClientId(0));
+        let inner = LlmpEventManager::builder()
+            .build_from_client(client, "fuzzer".into(), None)
+            .unwrap();
+        let mut secondary = CentralizedEventManager::builder()
+            .build_on_uds(inner, tuple_list!(), shmem_provider.clone(), &path, None)
+            .unwrap();
+        let received = received.clone();
+        secondary.add_broadcast_handler(Box::new(move |_state, topic, payload| {
+            received
+                .borrow_mut()
+                .push((name, String::from(topic), payload.to_vec()));
+            Ok(CustomBufEventResult::Handled)
+        }));
+        // Never called, the first handler already handled the message
+        secondary.add_broadcast_handler(Box::new(|_state, _topic, _payload| {
+            panic!("The message was already handled")
+        }));
+        secondaries.push(secondary);
+    }
+    let client = unbrokered_client(&mut shmem_provider, ClientId(0));
+    let inner = LlmpEventManager::builder()
+        .build_from_client(client, "fuzzer".into(), None)
+        .unwrap();
+    let mut main = CentralizedEventManager::builder()
+        .is_main(true)
+        .build_on_uds(inner, tuple_list!(), shmem_provider.clone(), &path, None)
+        .unwrap();
+
+    main.broadcast_custom("pause", vec![1, 2, 3]).unwrap();
+    for _ in 0..3 {
+        broker.broker_once().unwrap();
+    }
+    let mut state = StdState::nop::<BytesInput>().unwrap();
+    for secondary in &mut secondaries {
+        secondary.receive_from_main().unwrap();
+        assert_eq!(secondary.handle_broadcasts(&mut state).unwrap(), 1);
+        // Every message is handled once
+        secondary.receive_from_main().unwrap();
+        assert_eq!(secondary.handle_broadcasts(&mut state).unwrap(), 0);
+    }
+    assert_eq!(
+        *received.borrow(),
+        [
+            ("first", "pause".into(), vec![1, 2, 3]),
+            ("second", "pause".into(), vec![1, 2, 3])
+        ]
+    );
+
+    // Only the main node broadcasts, and it skips its own messages, so they can't loop
+    assert!(secondaries[0].broadcast_custom("pause", vec![]).is_err());
+    main.receive_from_main().unwrap();
+    assert_eq!(main.handle_broadcasts(&mut state).unwrap(), 0);
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_corpus_checksum() {
+    // Both secondaries listen to the main node directly, without a broker in between
+    let mut shmem_provider = StdShMemProvider::new().unwrap();
+    let mut clients = (0..6)
+        .map(|id| unbrokered_client(&mut shmem_provider, ClientId(id)))
+        .collect::<Vec<_>>();
+    let from_main = clients[5].sender().describe().unwrap();
+    for id in [1, 3] {
+        *clients[id].receiver_mut() =
+            LlmpReceiver::on_existing_from_description(shmem_provider.clone(), &from_main).unwrap();
+    }
+    let mut clients = clients.into_iter();
+    let mut next_client = || clients.next().unwrap();
+    let mut build = |is_main| {
+        let inner = LlmpEventManager::builder()
+            .build_from_client(next_client(), "fuzzer".into(), None)
+            .unwrap();
+        CentralizedEventManager::builder()
+            .is_main(is_main)
+            .corpus_checksum(Duration::ZERO)
+            .build_from_client(inner, tuple_list!(), next_client(), None)
+            .unwrap()
+    };
+    let mut secondaries = [build(false), build(false)];
+    let mut main = build(true);
+
+    let state_with = |inputs: &[u8]| {
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        for input in inputs {
+            state
+                .corpus_mut()
+                .add(Testcase::new(BytesInput::new(vec![*input])))
+                .unwrap();
+        }
+        state
+    };
+    let mut main_state = state_with(&[1, 2]);
+    // The same corpus in another order, and a diverged one
+    let mut states = [state_with(&[2, 1]), state_with(&[1, 3])];
+
+    let sync = |main: &mut CentralizedEventManager<_, _, _, _>,
+                main_state: &mut _,
+                secondaries: &mut [CentralizedEventManager<_, _, _, _>],
+                states: &mut [_]| {
+        main.sync_checksum(main_state).unwrap();
+        for (secondary, state) in secondaries.iter_mut().zip(states.iter_mut()) {
+            secondary.receive_from_main().unwrap();
+            secondary.sync_checksum(state).unwrap();
+        }
+    };
+    sync(&mut main, &mut main_state, &mut secondaries, &mut states);
+    let checksum = main.corpus_checksum().unwrap();
+    assert_eq!(checksum.count, 2);
+    assert_eq!(secondaries[0].corpus_checksum(), Some(checksum));
+    assert_eq!(secondaries[0].checksum_mismatches(), 0);
+    assert_ne!(secondaries[1].corpus_checksum(), Some(checksum));
+    assert_eq!(secondaries[1].checksum_mismatches(), 1);
+
+    // Catching up with the main node, only the new entries are hashed
+    let diverged = states[1].corpus().last().unwrap();
+    states[1].corpus_mut().remove(diverged).unwrap();
+    states[1]
+        .corpus_mut()
+        .add(Testcase::new(BytesInput::new(vec![2])))
+        .unwrap();
+    sync(&mut main, &mut main_state, &mut secondaries, &mut states);
+    assert_eq!(secondaries[1].corpus_checksum(), Some(checksum));
+    assert_eq!(secondaries[1].checksum_mismatches(), 1);
+    assert_eq!(secondaries[1].checksum.as_ref().unwrap().hashes.len(), 2);
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_global_coverage() {
+    let mut shmem_provider = StdShMemProvider::new().unwrap();
+    let mut clients = (0..6)
+        .map(|id| unbrokered_client(&mut shmem_provider, ClientId(id)))
+        .collect::<Vec<_>>();
+    let from_main = clients[5].sender().describe().unwrap();
+    for id in [1, 3] {
+        *clients[id].receiver_mut() =
+            LlmpReceiver::on_existing_from_description(shmem_provider.clone(), &from_main).unwrap();
+    }
+    let observer = StdMapObserver::owned("edges", vec![0_u8; 16]).handle();
+    let mut clients = clients.into_iter();
+    let mut next_client = || clients.next().unwrap();
+    let mut build = |is_main| {
+        let inner = LlmpEventManager::builder()
+            .build_from_client(next_client(), "fuzzer".into(), None)
+            .unwrap();
+        CentralizedEventManager::builder()
+            .is_main(is_main)
+            .global_coverage(&observer, Duration::ZERO)
+            .build_from_client(inner, tuple_list!(), next_client(), None)
+            .unwrap()
+    };
+    let mut secondaries = [build(false), build(false)];
+    let mut main = build(true);
+
+    let mut main_state = StdState::nop::<BytesInput>().unwrap();
+    let history = MapFeedbackMetadata::with_history_map(vec![0_u8, 1, 1, 0, 0, 0, 4, 0, 0, 0], 0);
+    main_state.add_named_metadata("edges", history);
+    let mut states = [
+        StdState::nop::<BytesInput>().unwrap(),
+        StdState::nop::<BytesInput>().unwrap(),
+    ];
+
+    main.sync_coverage(&mut main_state).unwrap();
+    for (secondary, state) in secondaries.iter_mut().zip(states.iter_mut()) {
+        secondary.receive_from_main().unwrap();
+        secondary.sync_coverage(state).unwrap();
+        let coverage = state
+            .named_metadata::<GlobalCoverageMetadata>("edges")
+            .unwrap();
+        assert_eq!((coverage.len(), coverage.covered()), (10, 3));
+        let covered = (0..12)
+            .filter(|idx| coverage.is_covered(*idx))
+            .collect::<Vec<_>>();
+        assert_eq!(covered, [1, 2, 6]);
+    }
+
+    // A large map stays small on the wire, and is unpacked as it was
+    let len = 8 << 20;
+    let mut history_map = vec![0_u8; len];
+    for idx in (0..len).step_by(97).take(50_000) {
+        history_map[idx] = 1;
+    }
+    let history = MapFeedbackMetadata::with_history_map(history_map, 0);
+    main_state.add_named_metadata("edges", history);
+    let (runs_len, runs) = covered_runs::<u8>(main_state.named_metadata_map(), "edges").unwrap();
+    let summary = CoverageSummary {
+        name: "edges".into(),
+        len: runs_len,
+        runs,
+        published: Duration::ZERO,
+    };
+    let serialized = postcard::to_allocvec(&summary).unwrap();
+    assert!(serialized.len() < 300 << 10, "{} bytes", serialized.len());
+    let coverage =
+        GlobalCoverageMetadata::from_runs(summary.len, &summary.runs, Duration::ZERO).unwrap();
+    assert_eq!((coverage.len(), coverage.covered()), (len, 50_000));
+    assert!(coverage.is_covered(97) && !coverage.is_covered(98));
+    assert!(GlobalCoverageMetadata::from_runs(4, &[2, 3], Duration::ZERO).is_err());
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_trust() {
+    // 0 and 4 cover the same, and so do 1 and 5, so the one handled first wins
+    let received = [
+        (ClientId(2), 0, ExitKind::Ok),
+        (ClientId(3), 4, ExitKind::Ok),
+        (ClientId(2), 1, ExitKind::Crash),
+        (ClientId(4), 5, ExitKind::Ok),
+    ];
+
+    // Without trust levels, in order of arrival
+    assert_eq!(
+        run_trusted_main_node(CentralizedEventManager::builder(), &[], &received),
+        (2, 2, 4, vec![(0, ClientId(2)), (1, ClientId(2))])
+    );
+
+    // The trusted client goes first, the untrusted one last, re-executed before it is evaluated.
+    // Its crash did not reproduce, so that testcase is discarded right after the first re-execution.
+    let trust = [(ClientId(3), 5), (ClientId(2), -1)];
+    assert_eq!(
+        run_trusted_main_node(CentralizedEventManager::builder(), &trust, &received),
+        (
+            2,
+            2,
+            1 + 1 + (DEFAULT_LOW_TRUST_REEXECS as u64 + 1) + 1,
+            vec![(4, ClientId(3)), (5, ClientId(4))]
+        )
+    );
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_low_trust_recheck_fixup() {
+    let mut feedback = FirstByteFeedback::default();
+    let mut objective = ConstFeedback::new(false);
+    let mut state = bytes_state(&mut feedback, &mut objective);
+    // The fixup turns every input into one that crashes, the filter rejects inputs starting with 2
+    let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective)
+        .input_fixup(|input: &mut BytesInput| input.as_mut().push(9))
+        .set_input_filter(|_: &_, input: &BytesInput| input.as_ref()[0] != 2);
+
+    let (inner, centralized_client) = client_pair(ClientId(1));
+    let mut mgr = CentralizedEventManager::builder()
+        .is_main(true)
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+    mgr.set_trust(ClientId(2), -1);
+    let mut harness = |input: &BytesInput| {
+        if input.as_ref().last() == Some(&9) {
+            ExitKind::Crash
+        } else {
+            ExitKind::Ok
+        }
+    };
+    let mut executor = InProcessExecutor::new(
+        &mut harness,
+        tuple_list!(),
+        &mut fuzzer,
+        &mut state,
+        &mut mgr,
+    )
+    .unwrap();
+
+    let crash = |byte| Event::NewTestcase {
+        input: BytesInput::new(vec![byte]),
+        observers_buf: None,
+        exit_kind: ExitKind::Crash,
+        corpus_size: 0,
+        client_config: EventConfig::AlwaysUnique,
+        time: Duration::ZERO,
+        forward_id: Some(ClientId(2)),
+        generation: None,
+        calibration: None,
+        forced: false,
+        #[cfg(feature = "multi_machine")]
+        node_id: None,
+    };
+
+    // The crash reproduces, as the re-executions run the fixed up input
+    mgr.inject_event(
+        &mut fuzzer,
+        &mut state,
+        &mut executor,
+        ClientId(2),
+        crash(1),
+    )
+    .unwrap();
+    assert_eq!((mgr.stats.accepted, mgr.stats.discarded), (1, 0));
+
+    // The filtered input is not re-executed at all
+    let executions = *state.executions();
+    mgr.inject_event(
+        &mut fuzzer,
+        &mut state,
+        &mut executor,
+        ClientId(2),
+        crash(2),
+    )
+    .unwrap();
+    // Skipped, not discarded, as it never ran
+    assert_eq!((mgr.stats.accepted, mgr.stats.discarded), (1, 0));
+    assert_eq!(*state.executions(), executions);
+    assert_eq!(
+        state.metadata::<SkippedInputsMetadata>().unwrap().count(),
+        1
+    );
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_forwarding_quota_round_robin() {
+    // 0 and 4 cover the same, and so do 1 and 5, so the one handled first wins
+    let received = [
+        (ClientId(2), 0, ExitKind::Ok),
+        (ClientId(2), 1, ExitKind::Ok),
+        (ClientId(4), 5, ExitKind::Ok),
+        (ClientId(3), 4, ExitKind::Ok),
+    ];
+
+    // The first testcase of each client goes first
+    let builder = CentralizedEventManager::builder().forwarding_quota(16, Duration::from_secs(60));
+    assert_eq!(
+        run_trusted_main_node(builder, &[], &received),
+        (2, 2, 4, vec![(0, ClientId(2)), (5, ClientId(4))])
+    );
+}
+
+#[test]
+fn test_forwarding_quota() {
+    let testcase = |client_id: u32, byte: u8| {
+        (
+            ClientId(client_id),
+            Event::NewTestcase {
+                input: BytesInput::new(vec![byte]),
+                observers_buf: None,
+                exit_kind: ExitKind::Ok,
+                corpus_size: 0,
+                client_config: EventConfig::AlwaysUnique,
+                time: Duration::ZERO,
+                forward_id: Some(ClientId(client_id)),
+                generation: None,
+                calibration: None,
+                forced: false,
+                #[cfg(feature = "multi_machine")]
+                node_id: None,
+            },
+        )
+    };
+    let bytes = |admitted: Vec<(ClientId, Event<BytesInput>)>| {
+        admitted
+            .into_iter()
+            .map(|(client_id, event)| match event {
+                Event::NewTestcase { input, .. } => (client_id.0, input.as_ref()[0]),
+                _ => panic!("Only testcases were sent"),
+            })
+            .collect::<Vec<_>>()
+    };
+    let secs = Duration::from_secs;
+    let mut quota = ForwardingQuota::new(2, secs(10), 2);
+
+    // Client 2 floods, client 3 is not affected
+    let received = vec![
+        testcase(2, 0),
+        testcase(2, 1),
+        testcase(2, 2),
+        testcase(3, 9),
+        testcase(2, 3),
+    ];
+    assert_eq!(
+        bytes(quota.admit(received, secs(0), 2)),
+        [(2, 0), (2, 1), (3, 9)]
+    );
+
+    // Once the deferred testcases are full, the oldest ones are dropped
+    let received = vec![testcase(2, 4), testcase(3, 8)];
+    assert_eq!(bytes(quota.admit(received, secs(1), 2)), [(3, 8)]);
+    let stats = quota.clients[&ClientId(2)].stats;
+    assert_eq!((stats.deferred, stats.dropped), (3, 1));
+    assert_eq!(quota.clients[&ClientId(3)].stats, QuotaStats::default());
+
+    // With nothing else to do, the deferred testcases are handled
+    assert_eq!(bytes(quota.admit(vec![], secs(2), 2)), [(2, 3), (2, 4)]);
+    assert!(quota.admit(vec![], secs(3), 2).is_empty());
+
+    // In the next window, client 2 is within its quota again
+    assert_eq!(
+        bytes(quota.admit(vec![testcase(2, 5)], secs(10), 2)),
+        [(2, 5)]
+    );
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_load_shedding() {
+    let mut shmem_provider = StdShMemProvider::new().unwrap();
+    let client = unbrokered_client(&mut shmem_provider, ClientId(0));
+    let centralized_client = unbrokered_client(&mut shmem_provider, ClientId(1));
+    let mut to_main = LlmpReceiver::on_existing_from_description(
+        shmem_provider.clone(),
+        &centralized_client.sender().describe().unwrap(),
+    )
+    .unwrap();
+    let mut from_inner = LlmpReceiver::on_existing_from_description(
+        shmem_provider.clone(),
+        &client.sender().describe().unwrap(),
+    )
+    .unwrap();
+    let inner = LlmpEventManager::builder()
+        .build_from_client(client, "fuzzer".into(), None)
+        .unwrap();
+    let mut mgr = CentralizedEventManager::builder()
+        .load_shedding(2)
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+    assert_eq!(mgr.shedding_stats(), Some(SheddingStats::default()));
+
+    // While the sender is saturated, stats are dropped, and only the newest testcases are held back
+    let testcase = |byte| PendingForward {
+        tag: _LLMP_TAG_TO_MAIN,
+        flags: LLMP_FLAG_INITIALIZED,
+        buf: vec![byte],
+        testcase: true,
+    };
+    mgr.shed(testcase(1), Shed::Defer);
+    mgr.shed(testcase(2), Shed::Defer);
+    mgr.shed(testcase(0), Shed::Drop);
+    let exiting = PendingForward {
+        testcase: false,
+        ..testcase(9)
+    };
+    mgr.shed(exiting, Shed::Keep);
+    mgr.shed(testcase(3), Shed::Defer);
+    assert_eq!(
+        mgr.shedding_stats(),
+        Some(SheddingStats {
+            dropped_stats: 1,
+            deferred_testcases: 3,
+            dropped_testcases: 1,
+        })
+    );
+
+    // Once the broker catches up, the held back messages go out first, in order
+    mgr.send_to_main(
+        _LLMP_TAG_TO_MAIN,
+        LLMP_FLAG_INITIALIZED,
+        vec![4],
+        Shed::Defer,
+    )
+    .unwrap();
+    assert!(mgr.pending.is_empty());
+    for byte in [2, 9, 3, 4] {
+        let (_, _, buf) = to_main.recv_buf().unwrap().unwrap();
+        assert_eq!(buf, [byte]);
+    }
+    assert!(to_main.recv_buf().unwrap().is_none());
+    assert!(!mgr.client.sender().would_block(1));
+
+    // Operators see the shedding in the user stats
+    let mut state = StdState::nop::<BytesInput>().unwrap();
+    mgr.maybe_report_stats(&mut state).unwrap();
+    let mut shed = Vec::new();
+    while let Some((_, _, buf)) = from_inner.recv_buf().unwrap() {
+        if let Ok(Event::<BytesInput>::UpdateUserStats { name, value, .. }) =
+            postcard::from_bytes(buf)
+        {
+            if let (true, UserStatsValue::Number(count)) = (name.starts_with("shed"), value.value())
+            {
+                shed.push((name, *count));
+            }
+        }
+    }
+    assert_eq!(
+        shed,
+        [
+            (CENTRALIZED_SHED_STATS_STAT.into(), 1),
+            (CENTRALIZED_SHED_DEFERRED_STAT.into(), 3),
+            (CENTRALIZED_SHED_DROPPED_STAT.into(), 1),
+        ]
+    );
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_load_shedding_deltas() {
+    let (inner, centralized_client) = client_pair::<NopState<NopInput>>(ClientId(1));
+    let mut mgr = CentralizedEventManager::builder()
+        .forward_map_deltas(true)
+        .load_shedding(1)
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+    let mut decoder = DeltaDecoder::default();
+
+    // The first testcase sets the base on both ends
+    let encoder = mgr.delta_encoder.as_mut().unwrap();
+    let first = encoder.encode(vec![0, 1, 2, 3]);
+    assert_eq!(decoder.decode(ClientId(1), first), Some(vec![0, 1, 2, 3]));
+
+    // While the sender is saturated, a delta testcase is dropped to make room for the next one
+    let delta = |byte| PendingForward {
+        tag: _LLMP_TAG_TO_MAIN_DELTA,
+        flags: LLMP_FLAG_INITIALIZED,
+        buf: vec![byte],
+        testcase: true,
+    };
+    mgr.shed(delta(1), Shed::Defer);
+    mgr.shed(delta(2), Shed::Defer);
+    assert_eq!(mgr.shedding_stats().unwrap().dropped_testcases, 1);
+
+    // The main node never saw the dropped base, so the next testcase goes out in full
+    let next = mgr.delta_encoder.as_mut().unwrap().encode(vec![0, 4, 5, 3]);
+    assert_eq!(next, ObserversPayload::Full(vec![0, 4, 5, 3]));
+    assert_eq!(decoder.decode(ClientId(1), next), Some(vec![0, 4, 5, 3]));
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_restart_flushes_pending() {
+    let (inner, centralized_client) = client_pair(ClientId(1));
+    // Read what the secondary sends, like the centralized broker would
+    let mut to_main = LlmpReceiver::on_existing_from_description(
+        StdShMemProvider::new().unwrap(),
+        &centralized_client.sender().describe().unwrap(),
+    )
+    .unwrap();
+    let mut mgr = CentralizedEventManager::builder()
+        .restart_flush_timeout(Duration::from_millis(100))
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+
+    // A testcase that could not be sent earlier
+    let queued = postcard::to_allocvec(&Event::NewTestcase {
+        input: BytesInput::new(vec![42]),
+        observers_buf: None,
+        exit_kind: ExitKind::Ok,
+        corpus_size: 1,
+        client_config: EventConfig::AlwaysUnique,
+        time: Duration::ZERO,
+        forward_id: Some(ClientId(0)),
+        generation: Some(0),
+        calibration: None,
+        forced: false,
+        #[cfg(feature = "multi_machine")]
+        node_id: None,
+    })
+    .unwrap();
+    mgr.pending.push_back(PendingForward {
+        tag: _LLMP_TAG_TO_MAIN,
+        flags: LLMP_FLAG_INITIALIZED,
+        buf: queued.clone(),
+        testcase: true,
+    });
+
+    let mut state = StdState::nop::<BytesInput>().unwrap();
+    *state.executions_mut() = 1234;
+    mgr.on_restart(&mut state).unwrap();
+    assert!(mgr.pending.is_empty());
+
+    // The queued testcase went out first, then the final keepalive
+    let (_, tag, buf) = to_main.recv_buf().unwrap().unwrap();
+    assert_eq!((tag, buf), (_LLMP_TAG_TO_MAIN, queued.as_slice()));
+    let (_, tag, buf) = to_main.recv_buf().unwrap().unwrap();
+    assert_eq!(tag, _LLMP_TAG_TO_MAIN);
+    match postcard::from_bytes::<Event<BytesInput>>(buf).unwrap() {
+        Event::UpdateExecStats { executions, .. } => assert_eq!(executions, 1234),
+        event => panic!("Expected a keepalive, got {}", event.name()),
+    }
+    assert!(to_main.recv_buf().unwrap().is_none());
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_send_pending_drops_poison() {
+    let mut shmem_provider = StdShMemProvider::new().unwrap();
+    let client = unbrokered_client(&mut shmem_provider, ClientId(0));
+    let centralized_client = unbrokered_client(&mut shmem_provider, ClientId(1));
+    let mut to_main = LlmpReceiver::on_existing_from_description(
+        shmem_provider.clone(),
+        &centralized_client.sender().describe().unwrap(),
+    )
+    .unwrap();
+    let mut from_inner = LlmpReceiver::on_existing_from_description(
+        shmem_provider.clone(),
+        &client.sender().describe().unwrap(),
+    )
+    .unwrap();
+    let inner = LlmpEventManager::builder()
+        .build_from_client(client, "fuzzer".into(), None)
+        .unwrap();
+    let mut mgr = CentralizedEventManager::builder()
+        .build_from_client(inner, tuple_list!(), centralized_client, None)
+        .unwrap();
+
+    // LLMP rejects the reserved tag every time, the message after it must still go out
+    mgr.pending.push_back(PendingForward {
+        tag: Tag(0xDEADAF),
+        flags: LLMP_FLAG_INITIALIZED,
+        buf: vec![1],
+        testcase: false,
+    });
+    mgr.pending.push_back(PendingForward {
+        tag: _LLMP_TAG_TO_MAIN,
+        flags: LLMP_FLAG_INITIALIZED,
+        buf: vec![2],
+        testcase: true,
+    });
+
+    // The first failure backs off, so trying again right away does not count as an attempt
+    mgr.send_pending().unwrap();
+    mgr.send_pending().unwrap();
+    assert_eq!((mgr.stats.send_errors, mgr.pending.len()), (1, 2));
+
+    assert!(mgr.flush_pending(Duration::from_secs(1)));
+    assert_eq!(mgr.stats.send_errors, u64::from(MAX_SEND_ATTEMPTS));
+    assert_eq!(mgr.stats.send_dropped, 1);
+    assert_eq!(mgr.stats.send_retry.attempts, 0);
+    let (_, tag, buf) = to_main.recv_buf().unwrap().unwrap();
+    assert_eq!((tag, buf), (_LLMP_TAG_TO_MAIN, [2].as_slice()));
+
+    // Operators see the failures in the user stats
+    let mut state = StdState::nop::<BytesInput>().unwrap();
+    mgr.maybe_report_stats(&mut state).unwrap();
+    let mut send_stats = Vec::new();
+    while let Some((_, _, buf)) = from_inner.recv_buf().unwrap() {
+        if let Ok(Event::<BytesInput>::UpdateUserStats { name, value, .. }) =
+            postcard::from_bytes(buf)
+        {
+            if let (true, UserStatsValue::Number(count)) = (name.starts_with("send"), value.value())
+            {
+                send_stats.push((name, *count));
+            }
+        }
+    }
+    assert_eq!(
+        send_stats,
+        [
+            (
+                CENTRALIZED_SEND_ERRORS_STAT.into(),
+                u64::from(MAX_SEND_ATTEMPTS)
+            ),
+            (CENTRALIZED_SEND_DROPPED_STAT.into(), 1),
+        ]
+    );
+}
+
+#[test]
+#[serial]
+#[cfg(unix)]
+#[cfg_attr(miri, ignore)]
+fn test_build_on_uds() {
+    let path = env::temp_dir().join(format!("libafl_centralized_{}.sock", process::id()));
+    let _ = fs::remove_file(&path);
+
+    // The secondary runs in its own process and connects before the broker is up
+    let child = match unsafe { fork() }.unwrap() {
+        ForkResult::Parent(child) => child,
+        ForkResult::Child => {
+            let res = std::panic::catch_unwind(|| {
+                let mut shmem_provider = StdShMemProvider::new().unwrap();
+                let client = unbrokered_client(&mut shmem_provider, ClientId(0));
+                let inner = LlmpEventManager::builder()
+                    .build_from_client(client, "fuzzer".into(), None)
+                    .unwrap();
+                let mut mgr = CentralizedEventManager::builder()
+                    .build_on_uds(inner, tuple_list!(), shmem_provider, &path, None)
+                    .unwrap();
+
+                let mut state = StdState::nop::<BytesInput>().unwrap();
+                mgr.fire(
+                    &mut state,
+                    Event::NewTestcase {
+                        input: BytesInput::new(vec![0x42]),
+                        observers_buf: None,
+                        exit_kind: ExitKind::Ok,
+                        corpus_size: 1,
+                        client_config: EventConfig::AlwaysUnique,
+                        time: Duration::ZERO,
+                        forward_id: None,
+                        generation: None,
+                        calibration: None,
+                        forced: false,
+                        #[cfg(feature = "multi_machine")]
+                        node_id: None,
+                    },
+                )
+                .unwrap();
+                // Stay around until the broker mapped the message
+                mgr.await_restart_safe();
+            });
+            process::exit(i32::from(res.is_err()));
+        }
+    };
+
+    // The centralized broker, and the main node attached to it
+    let shmem_provider = StdShMemProvider::new().unwrap();
+    let mut broker = LlmpBroker::new(shmem_provider.clone(), tuple_list!()).unwrap();
+    broker.inner_mut().launch_uds_listener_on(&path).unwrap();
+    let mut main = LlmpClient::create_attach_to_uds(shmem_provider, &path).unwrap();
+
+    let start = Instant::now();
+    let forwarded = loop {
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "Nothing forwarded over {}",
+            path.display()
+        );
+        broker.broker_once().unwrap();
+        if let Some((_, tag, buf)) = main.recv_buf().unwrap() {
+            if tag == _LLMP_TAG_TO_MAIN {
+                break postcard::from_bytes::<Event<BytesInput>>(buf).unwrap();
+            }
+        }
+    };
+    let Event::NewTestcase {
+        input, forward_id, ..
+    } = forwarded
+    else {
+        panic!("Unexpected event {}", forwarded.name());
+    };
+    assert_eq!(input.as_ref(), &[0x42]);
+    assert!(forward_id.is_some());
+
+    assert_eq!(child.status(), 0);
+    fs::remove_file(&path).unwrap();
+}
+
+/// The role [`CentralizedEventManagerBuilder::build_from_client_infer_role`] infers for client 3,
+/// with the role environment variable set to `role`, if any
+fn infer_role(builder: CentralizedEventManagerBuilder, role: Option<&str>) -> Result<bool, Error> {
+    match role {
+        Some(role) => env::set_var(CENTRALIZED_ROLE_ENV, role),
+        None => env::remove_var(CENTRALIZED_ROLE_ENV),
+    }
+    let mut shmem_provider = StdShMemProvider::new().unwrap();
+    let client = unbrokered_client(&mut shmem_provider, ClientId(3));
+    let inner = NopEventManager::<NopState<BytesInput>>::new();
+    let res = builder
+        .build_from_client_infer_role(inner, tuple_list!(), client, None)
+        .map(|mgr| mgr.is_main());
+    env::remove_var(CENTRALIZED_ROLE_ENV);
+    res
+}
+
+#[test]
+#[serial]
+#[cfg_attr(miri, ignore)]
+fn test_infer_role() {
+    let builder = CentralizedEventManager::builder;
+
+    // The explicit role comes first
+    assert!(infer_role(builder().is_main(true), Some("secondary")).unwrap());
+    assert!(!infer_role(builder().is_main(false).main_client_id(ClientId(3)), None).unwrap());
+
+    // Then the environment
+    assert!(infer_role(builder(), Some("main")).unwrap());
+    assert!(!infer_role(builder().main_client_id(ClientId(3)), Some("secondary")).unwrap());
+    assert!(infer_role(builder(), Some("primary")).is_err());
+
+    // Then the client id
+    assert!(infer_role(builder().main_client_id(ClientId(3)), None).unwrap());
+    assert!(!infer_role(builder().main_client_id(ClientId(1)), None).unwrap());
+    assert!(infer_role(builder(), None).is_err());
+}
@@ -0,0 +1,316 @@
+//! A log of the testcases an event manager accepted from other nodes, to rebuild its corpus offline.
+//!
+//! Record the log with [`crate::events::LlmpEventManager::set_event_log`], or, on a main node,
+//! with [`crate::events::CentralizedEventManager::set_event_log`]. Later, e.g., for a post-mortem,
+//! rebuild the corpus as it was at any point in time with an [`EventLogReader`], without executing anything.
+
+use alloc::boxed::Box;
+use core::{fmt::Debug, time::Duration};
+use std::io::{Read, Write};
+
+use hashbrown::HashMap;
+use libafl_bolts::{current_time, ClientId};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    corpus::{Corpus, CorpusId, Testcase},
+    events::framing::{self, ReadRecord},
+    inputs::Input,
+    state::HasCorpus,
+    Error,
+};
+
+/// A testcase an event manager accepted from another node, as recorded by an [`EventLogWriter`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventLogRecord<I> {
+    /// When the testcase was accepted
+    pub time: Duration,
+    /// The client the testcase came from
+    pub client_id: ClientId,
+    /// The id of the testcase in the corpus of the recording node
+    pub corpus_id: CorpusId,
+    /// The testcase, with its input and metadata
+    pub testcase: Testcase<I>,
+}
+
+/// Writes the testcases an event manager accepted from other nodes to a log, see the [module docs](self).
+///
+/// Each record is the little-endian `u32` length of the record, followed by the
+/// `postcard`-serialized [`EventLogRecord`], like the records of an [`crate::events::EventTap`].
+/// Records are flushed as they are written, and a record
+/// cut short because the fuzzer was killed is skipped by the [`EventLogReader`].
+/// To keep one log across restarts, open the file in append mode.
+pub struct EventLogWriter {
+    writer: Box<dyn Write>,
+}
+
+impl Debug for EventLogWriter {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EventLogWriter").finish_non_exhaustive()
+    }
+}
+
+impl EventLogWriter {
+    /// Create a new [`EventLogWriter`], writing all records to `writer`
+    pub fn new<W>(writer: W) -> Self
+    where
+        W: Write + 'static,
+    {
+        Self {
+            writer: Box::new(writer),
+        }
+    }
+
+    /// Append a record for the testcase `corpus_id` of `state`, accepted from `client_id`
+    pub fn record<S>(
+        &mut self,
+        state: &S,
+        client_id: ClientId,
+        corpus_id: CorpusId,
+    ) -> Result<(), Error>
+    where
+        S: HasCorpus,
+        <S::Corpus as Corpus>::Input: Input,
+    {
+        let corpus = state.corpus();
+        let mut testcase = corpus.get(corpus_id)?.borrow().clone();
+        if testcase.input().is_none() {
+            testcase.set_input(corpus.cloned_input_for_id(corpus_id)?);
+        }
+        // The files of the recording node are of no use offline
+        *testcase.file_path_mut() = None;
+        *testcase.metadata_path_mut() = None;
+
+        let record = EventLogRecord {
+            time: current_time(),
+            client_id,
+            corpus_id,
+            testcase,
+        };
+        framing::write_record(&mut self.writer, &record)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Reads a log written by an [`EventLogWriter`], and replays it into a fresh corpus, see the [module docs](self).
+///
+/// A truncated or corrupted record ends the log, as the fuzzer may have been killed while writing it,
+/// see [`EventLogReader::truncated`].
+#[derive(Debug)]
+pub struct EventLogReader<R> {
+    reader: R,
+    stop_at_time: Option<Duration>,
+    stop_at_index: Option<usize>,
+    /// The records read so far
+    index: usize,
+    truncated: bool,
+}
+
+impl<R> EventLogReader<R>
+where
+    R: Read,
+{
+    /// Create a new [`EventLogReader`], reading the records from `reader`
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            stop_at_time: None,
+            stop_at_index: None,
+            index: 0,
+            truncated: false,
+        }
+    }
+
+    /// Stop before the first record accepted after `time`
+    #[must_use]
+    pub fn stop_at_time(self, time: Duration) -> Self {
+        Self {
+            stop_at_time: Some(time),
+            ..self
+        }
+    }
+
+    /// Stop before the record at `index`, so only the first `index` records are read
+    #[must_use]
+    pub fn stop_at_index(self, index: usize) -> Self {
+        Self {
+            stop_at_index: Some(index),
+            ..self
+        }
+    }
+
+    /// If the log ended in a truncated or corrupted record
+    #[must_use]
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Read the next record, or `None` at the end of the log, or where it was told to stop
+    pub fn next_record<I>(&mut self) -> Result<Option<EventLogRecord<I>>, Error>
+    where
+        I: Input,
+    {
+        if self.truncated
+            || self
+                .stop_at_index
+                .is_some_and(|stop_at_index| self.index >= stop_at_index)
+        {
+            return Ok(None);
+        }
+        let record: EventLogRecord<I> = match framing::read_record(&mut self.reader)? {
+            ReadRecord::Record(record) => record,
+            ReadRecord::End => return Ok(None),
+            ReadRecord::Truncated(why) => return Ok(self.truncate(why)),
+        };
+        if self
+            .stop_at_time
+            .is_some_and(|stop_at_time| record.time > stop_at_time)
+        {
+            return Ok(None);
+        }
+        self.index += 1;
+        Ok(Some(record))
+    }
+
+    /// Add the testcases of the log, in order, to the corpus of `state`, without executing them.
+    ///
+    /// The parents of the testcases are linked to their entries in this corpus,
+    /// links to parents that are not part of the log are dropped. Returns the number of added testcases.
+    pub fn replay_into<S>(&mut self, state: &mut S) -> Result<usize, Error>
+    where
+        S: HasCorpus,
+        <S::Corpus as Corpus>::Input: Input,
+    {
+        let mut ids = HashMap::new();
+        let mut count = 0;
+        while let Some(record) = self.next_record()? {
+            let mut testcase = record.testcase;
+            let parent_id = testcase.parent_id().and_then(|id| ids.get(&id).copied());
+            testcase.set_parent_id_optional(parent_id);
+            let id = state.corpus_mut().add(testcase)?;
+            ids.insert(record.corpus_id, id);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn truncate<I>(&mut self, why: &str) -> Option<EventLogRecord<I>> {
+        log::warn!(
+            "Skipping the end of the event log at record {}, as {why}",
+            self.index
+        );
+        self.truncated = true;
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use core::time::Duration;
+    use std::{cell::RefCell, io::Write, rc::Rc};
+
+    use libafl_bolts::ClientId;
+
+    use crate::{
+        corpus::{Corpus, CorpusId, SchedulerTestcaseMetadata, Testcase},
+        events::{EventLogReader, EventLogWriter},
+        inputs::BytesInput,
+        state::{HasCorpus, StdState},
+        HasMetadata,
+    };
+
+    /// A writer to a buffer the test keeps looking at
+    #[derive(Clone, Default)]
+    struct SharedBuf(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_event_log_replay() {
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        let buf = SharedBuf::default();
+        let mut log = EventLogWriter::new(buf.clone());
+
+        // A seed that is not part of the log, then three accepted testcases
+        let seed = state
+            .corpus_mut()
+            .add(Testcase::new(BytesInput::new(vec![0])))
+            .unwrap();
+        let first = state
+            .corpus_mut()
+            .add(Testcase::with_parent_id(BytesInput::new(vec![1]), seed))
+            .unwrap();
+        state
+            .corpus()
+            .get(first)
+            .unwrap()
+            .borrow_mut()
+            .add_metadata(SchedulerTestcaseMetadata::new(1));
+        log.record(&state, ClientId(1), first).unwrap();
+        let second = state
+            .corpus_mut()
+            .add(Testcase::with_parent_id(BytesInput::new(vec![2]), first))
+            .unwrap();
+        log.record(&state, ClientId(2), second).unwrap();
+        let complete = buf.0.borrow().len();
+        let third = state
+            .corpus_mut()
+            .add(Testcase::new(BytesInput::new(vec![3])))
+            .unwrap();
+        log.record(&state, ClientId(1), third).unwrap();
+        let recorded = buf.0.borrow().clone();
+
+        let replay = |reader: EventLogReader<&[u8]>| {
+            let mut reader = reader;
+            let mut state = StdState::nop::<BytesInput>().unwrap();
+            let count = reader.replay_into(&mut state).unwrap();
+            (count, reader.truncated(), state)
+        };
+
+        let (count, truncated, replayed) = replay(EventLogReader::new(&recorded[..]));
+        assert_eq!((count, truncated), (3, false));
+        let inputs = replayed
+            .corpus()
+            .ids()
+            .map(|id| replayed.corpus().cloned_input_for_id(id).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            inputs,
+            [1, 2, 3].map(|byte| BytesInput::new(vec![byte])).to_vec()
+        );
+        let ids = replayed.corpus().ids().collect::<Vec<_>>();
+        let testcase = |id: CorpusId| replayed.corpus().get(id).unwrap().borrow().clone();
+        // The seed is not part of the log, the first testcase is
+        assert_eq!(testcase(ids[0]).parent_id(), None);
+        assert!(testcase(ids[0]).has_metadata::<SchedulerTestcaseMetadata>());
+        assert_eq!(testcase(ids[1]).parent_id(), Some(ids[0]));
+
+        // Stop early
+        let (count, truncated, _) = replay(EventLogReader::new(&recorded[..]).stop_at_index(2));
+        assert_eq!((count, truncated), (2, false));
+        let (count, _, _) = replay(EventLogReader::new(&recorded[..]).stop_at_time(Duration::ZERO));
+        assert_eq!(count, 0);
+
+        // The fuzzer was killed while writing the last record, or the log is corrupted
+        for len in [complete + 2, recorded.len() - 1] {
+            let (count, truncated, _) = replay(EventLogReader::new(&recorded[..len]));
+            assert_eq!((count, truncated), (2, true));
+        }
+        for garbage in [[4, 0, 0, 0, 0xff, 0xff, 0xff, 0xff], [0xff; 8]] {
+            let mut corrupted = recorded[..complete].to_vec();
+            corrupted.extend_from_slice(&garbage);
+            let (count, truncated, _) = replay(EventLogReader::new(&corrupted[..]));
+            assert_eq!((count, truncated), (2, true));
+        }
+    }
+}
@@ -2,6 +2,8 @@
 //!
 //! This will allow user to define pre/post-processing code when the event manager receives any message from
 //! other clients
+use alloc::{vec, vec::Vec};
+
 use libafl_bolts::ClientId;
 
 use crate::{events::Event, state::State, Error};
@@ -35,6 +37,14 @@ where
     fn post_exec(&mut self, _state: &mut S, _client_id: ClientId) -> Result<bool, Error> {
         Ok(true)
     }
+
+    /// The priority of this hook, where it is honored, such as in the
+    /// [`crate::events::CentralizedEventManager`]: hooks with a higher priority run first,
+    /// hooks with the same priority run in the order of the tuple.
+    /// Defaults to `0`.
+    fn priority(&self) -> i32 {
+        0
+    }
 }
 
 /// The tuples contains `broker_hooks` to be executed for `handle_in_client`
@@ -60,6 +70,27 @@ where
 
     /// The hook that runs after `handle_in_client`
     fn post_exec_all(&mut self, state: &mut S, client_id: ClientId) -> Result<bool, Error>;
+
+    /// The [`EventManagerHook::priority`] of each hook, in the order of the tuple
+    fn priorities(&self) -> Vec<i32>;
+
+    /// Run [`EventManagerHook::on_fire`] of the hook at `index` in the tuple only
+    fn on_fire_nth(
+        &mut self,
+        index: usize,
+        state: &mut S,
+        client_id: ClientId,
+        event: &Event<S::Input>,
+    ) -> Result<(), Error>;
+
+    /// The indices of the hooks, ordered by descending [`EventManagerHook::priority`],
+    /// and by their position in the tuple among hooks of the same priority
+    fn priority_order(&self) -> Vec<usize> {
+        let priorities = self.priorities();
+        let mut order = (0..priorities.len()).collect::<Vec<_>>();
+        order.sort_by_key(|index| core::cmp::Reverse(priorities[*index]));
+        order
+    }
 }
 
 impl<S> EventManagerHooksTuple<S> for ()
@@ -89,6 +120,22 @@ where
     fn post_exec_all(&mut self, _state: &mut S, _client_id: ClientId) -> Result<bool, Error> {
         Ok(true)
     }
+
+    fn priorities(&self) -> Vec<i32> {
+        Vec::new()
+    }
+
+    fn on_fire_nth(
+        &mut self,
+        index: usize,
+        _state: &mut S,
+        _client_id: ClientId,
+        _event: &Event<S::Input>,
+    ) -> Result<(), Error> {
+        Err(Error::illegal_argument(format!(
+            "There is no event manager hook at index {index}"
+        )))
+    }
 }
 
 impl<Head, Tail, S> EventManagerHooksTuple<S> for (Head, Tail)
@@ -125,4 +172,24 @@ where
         let second = self.1.post_exec_all(state, client_id)?;
         Ok(first & second)
     }
+
+    fn priorities(&self) -> Vec<i32> {
+        let mut priorities = vec![self.0.priority()];
+        priorities.extend(self.1.priorities());
+        priorities
+    }
+
+    fn on_fire_nth(
+        &mut self,
+        index: usize,
+        state: &mut S,
+        client_id: ClientId,
+        event: &Event<S::Input>,
+    ) -> Result<(), Error> {
+        if index == 0 {
+            self.0.on_fire(state, client_id, event)
+        } else {
+            self.1.on_fire_nth(index - 1, state, client_id, event)
+        }
+    }
 }
@@ -0,0 +1,139 @@
+//! The framing of the records written by an [`crate::events::EventTap`] or an [`crate::events::EventLogWriter`],
+//! and of exported corpus partitions.
+//!
+//! Each record is the little-endian `u32` length of the record, followed by the
+//! `postcard`-serialized record.
+
+use alloc::vec::Vec;
+use std::io::{self, Read, Write};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::Error;
+
+/// The buffer a record is read into starts at most this large, and only grows as the record is read,
+/// so a corrupted length cannot allocate more than the stream actually holds
+const INITIAL_RECORD_CAPACITY: usize = 64 * 1024;
+
+/// A record read by [`read_record`]
+#[derive(Debug)]
+pub(crate) enum ReadRecord<T> {
+    /// A complete record
+    Record(T),
+    /// The end of the stream, after the last complete record
+    End,
+    /// A record that is cut short or corrupted, with the reason. Nothing after it can be trusted.
+    Truncated(&'static str),
+}
+
+/// Append `record` to `writer`
+pub(crate) fn write_record<T, W>(writer: &mut W, record: &T) -> Result<(), Error>
+where
+    T: Serialize,
+    W: Write + ?Sized,
+{
+    write_bytes(writer, &postcard::to_allocvec(record)?)
+}
+
+/// Append a record that is already serialized to `writer`
+pub(crate) fn write_bytes<W>(writer: &mut W, bytes: &[u8]) -> Result<(), Error>
+where
+    W: Write + ?Sized,
+{
+    let len = u32::try_from(bytes.len())
+        .map_err(|_| Error::illegal_argument("Record is too large to be written"))?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(bytes)?;
+    Ok(())
+}
+
+/// Read the next record from `reader`
+pub(crate) fn read_record<T, R>(reader: &mut R) -> Result<ReadRecord<T>, Error>
+where
+    T: DeserializeOwned,
+    R: Read,
+{
+    Ok(match read_bytes(reader)? {
+        ReadRecord::Record(buf) => match postcard::from_bytes(&buf) {
+            Ok(record) => ReadRecord::Record(record),
+            Err(_) => ReadRecord::Truncated("it is corrupted"),
+        },
+        ReadRecord::End => ReadRecord::End,
+        ReadRecord::Truncated(why) => ReadRecord::Truncated(why),
+    })
+}
+
+/// Read the next record from `reader`, without deserializing it
+pub(crate) fn read_bytes<R>(reader: &mut R) -> Result<ReadRecord<Vec<u8>>, Error>
+where
+    R: Read,
+{
+    let mut len = [0; 4];
+    let mut read = 0;
+    while read < len.len() {
+        match reader.read(&mut len[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+    if read == 0 {
+        return Ok(ReadRecord::End);
+    }
+    if read < len.len() {
+        return Ok(ReadRecord::Truncated("its length is cut short"));
+    }
+
+    let len = u32::from_le_bytes(len);
+    let mut buf = Vec::with_capacity((len as usize).min(INITIAL_RECORD_CAPACITY));
+    reader.take(u64::from(len)).read_to_end(&mut buf)?;
+    if buf.len() < len as usize {
+        return Ok(ReadRecord::Truncated("it is cut short"));
+    }
+    Ok(ReadRecord::Record(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec::Vec};
+
+    use super::{read_record, write_record, ReadRecord};
+
+    fn read_all(mut bytes: &[u8]) -> (Vec<String>, Option<&'static str>) {
+        let mut records = Vec::new();
+        loop {
+            match read_record::<String, _>(&mut bytes).unwrap() {
+                ReadRecord::Record(record) => records.push(record),
+                ReadRecord::End => return (records, None),
+                ReadRecord::Truncated(why) => return (records, Some(why)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_record_framing() {
+        let mut written = Vec::new();
+        write_record(&mut written, &"first").unwrap();
+        let complete = written.len();
+        write_record(&mut written, &"second").unwrap();
+
+        let (records, truncated) = read_all(&written);
+        assert_eq!(records, ["first", "second"]);
+        assert_eq!(truncated, None);
+
+        // Killed while writing the length, or the record
+        for len in [complete + 2, written.len() - 1] {
+            let (records, truncated) = read_all(&written[..len]);
+            assert_eq!(records, ["first"]);
+            assert!(truncated.is_some());
+        }
+
+        // A corrupted length claiming almost 4 GiB must not be trusted
+        let mut corrupted = written[..complete].to_vec();
+        corrupted.extend_from_slice(&[0xff, 0xff, 0xff, 0xff, 1, 2, 3]);
+        let (records, truncated) = read_all(&corrupted);
+        assert_eq!(records, ["first"]);
+        assert_eq!(truncated, Some("it is cut short"));
+    }
+}
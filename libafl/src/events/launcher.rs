@@ -550,6 +550,9 @@ pub struct CentralizedLauncher<'a, CF, MF, MT, SP> {
     /// Consider this testcase as interesting always if true
     #[builder(default = false)]
     always_interesting: bool,
+    /// Let the main node announce the testcases it passes on by hash, see [`crate::events::LlmpEventManagerBuilder::hash_first`]
+    #[builder(default = false)]
+    hash_first: bool,
     /// The 'main' function to run for each secondary client forked. This probably shouldn't return
     #[builder(default, setter(strip_option))]
     secondary_run_client: Option<CF>,
@@ -651,6 +654,7 @@ where
                 // Fuzzer client. keeps retrying the connection to broker till the broker starts
                 let builder = RestartingMgr::<(), MT, S, SP>::builder()
                     .always_interesting(centralized_launcher.always_interesting)
+                    .hash_first(centralized_launcher.hash_first)
                     .shmem_provider(centralized_launcher.shmem_provider.clone())
                     .broker_port(centralized_launcher.broker_port)
                     .kind(ManagerKind::Client { client_description })
@@ -3,11 +3,12 @@
 
 #[cfg(feature = "std")]
 use alloc::string::ToString;
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{boxed::Box, collections::VecDeque, vec::Vec};
 use core::{marker::PhantomData, time::Duration};
 #[cfg(feature = "std")]
 use std::net::TcpStream;
 
+use hashbrown::HashMap;
 #[cfg(feature = "llmp_compression")]
 use libafl_bolts::{
     compress::GzipCompressor,
@@ -15,10 +16,10 @@ use libafl_bolts::{
 };
 use libafl_bolts::{
     current_time,
-    llmp::{LlmpClient, LlmpClientDescription, LLMP_FLAG_FROM_MM},
+    llmp::{LlmpClient, LlmpClientDescription, Tag, LLMP_FLAG_FROM_MM},
     shmem::{NopShMemProvider, ShMemProvider},
     tuples::Handle,
-    ClientId,
+    ClientId, DistributedError, DistributedPhase,
 };
 #[cfg(feature = "std")]
 use libafl_bolts::{
@@ -29,18 +30,29 @@ use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "llmp_compression")]
 use crate::events::llmp::COMPRESS_THRESHOLD;
+#[cfg(feature = "std")]
+use crate::events::EventLogWriter;
+#[cfg(feature = "std")]
+use crate::monitors::ClientIdentity;
+#[cfg(feature = "std")]
+use crate::stages::ReattachableEventManager;
 use crate::{
-    corpus::Corpus,
+    corpus::{Corpus, CorpusId},
     events::{
-        llmp::{LLMP_TAG_EVENT_TO_BOTH, _LLMP_TAG_EVENT_TO_BROKER},
-        AdaptiveSerializer, CustomBufEventResult, CustomBufHandlerFn, Event, EventConfig,
-        EventFirer, EventManager, EventManagerHooksTuple, EventManagerId, EventProcessor,
-        EventRestarter, HasCustomBufHandlers, HasEventManagerId, ProgressReporter,
+        llmp::{
+            _LLMP_TAG_EVENT_TO_BROKER, _LLMP_TAG_TESTCASE_FETCH, _LLMP_TAG_TESTCASE_FETCHED,
+            _LLMP_TAG_TESTCASE_HASH, LLMP_TAG_EVENT_TO_BOTH,
+        },
+        AdaptiveSerializer, AdaptiveSerializerStats, CustomBufEventResult, CustomBufHandlerFn,
+        Event, EventConfig, EventFirer, EventManager, EventManagerHooksTuple, EventManagerId,
+        EventProcessor, EventRestarter, HasCustomBufHandlers, HasEventManagerId, HasPendingEvents,
+        ProgressReporter, ProvenanceMetadata, SHUTDOWN_UNMAP_TIMEOUT,
     },
     executors::{Executor, HasObservers},
-    fuzzer::{Evaluator, EvaluatorObservers, ExecutionProcessor},
-    inputs::{NopInput, UsesInput},
+    fuzzer::{Evaluator, EvaluatorObservers, ExecutionProcessor, ForcedInputMetadata},
+    inputs::{Input, NopInput, UsesInput},
     observers::{ObserversTuple, TimeObserver},
+    stages::CalibrationHint,
     state::{HasCorpus, HasExecutions, HasImported, HasLastReportTime, NopState, State, UsesState},
     Error, HasMetadata,
 };
@@ -63,6 +75,13 @@ where
     llmp: LlmpClient<SP>,
     /// The custom buf handler
     custom_buf_handlers: Vec<Box<CustomBufHandlerFn<S>>>,
+    /// The testcases announced by hash, see [`LlmpEventManagerBuilder::hash_first`]
+    hash_first: HashFirst,
+    /// The time spent waiting for the broker, see [`LlmpEventManager::unmap_wait_stats`]
+    unmap_wait: UnmapWait,
+    /// See [`LlmpEventManager::set_event_log`]
+    #[cfg(feature = "std")]
+    event_log: Option<EventLogWriter>,
     #[cfg(feature = "llmp_compression")]
     compressor: GzipCompressor,
     /// The configuration defines this specific fuzzer.
@@ -73,6 +92,7 @@ where
     deserialization_time: Duration,
     serializations_cnt: usize,
     should_serialize_cnt: usize,
+    serializer_stats: AdaptiveSerializerStats,
     pub(crate) time_ref: Option<Handle<TimeObserver>>,
     phantom: PhantomData<S>,
 }
@@ -91,6 +111,9 @@ pub struct LlmpEventManagerBuilder<EMH> {
     throttle: Option<Duration>,
     hooks: EMH,
     always_interesting: bool,
+    hash_first: bool,
+    identity_label: Option<&'static str>,
+    unmap_wait_warning: Option<Duration>,
 }
 
 impl Default for LlmpEventManagerBuilder<()> {
@@ -107,6 +130,9 @@ impl LlmpEventManagerBuilder<()> {
             throttle: None,
             hooks: (),
             always_interesting: false,
+            hash_first: false,
+            identity_label: None,
+            unmap_wait_warning: None,
         }
     }
 
@@ -116,6 +142,9 @@ impl LlmpEventManagerBuilder<()> {
             throttle: self.throttle,
             hooks,
             always_interesting: self.always_interesting,
+            hash_first: self.hash_first,
+            identity_label: self.identity_label,
+            unmap_wait_warning: self.unmap_wait_warning,
         }
     }
 
@@ -126,6 +155,9 @@ impl LlmpEventManagerBuilder<()> {
             throttle: self.throttle,
             hooks: self.hooks,
             always_interesting,
+            hash_first: self.hash_first,
+            identity_label: self.identity_label,
+            unmap_wait_warning: self.unmap_wait_warning,
         }
     }
 }
@@ -138,6 +170,35 @@ impl<EMH> LlmpEventManagerBuilder<EMH> {
         self
     }
 
+    /// Announce testcases other nodes found by the hash of their input, instead of sending them again.
+    ///
+    /// This is meant for nodes re-broadcasting testcases, such as the main node of a
+    /// [`crate::events::CentralizedEventManager`]: the node that found the testcase, its `forward_id`,
+    /// already has it and skips the announcement, all other nodes fetch the full testcase once.
+    /// Nodes always answer and fetch announced testcases, this only changes what this node sends.
+    #[must_use]
+    pub fn hash_first(mut self, hash_first: bool) -> Self {
+        self.hash_first = hash_first;
+        self
+    }
+
+    /// Label this client in the [`Event::ClientIdentity`] it registers with, e.g., with the sanitizer it runs
+    #[must_use]
+    pub fn identity_label(mut self, label: &'static str) -> Self {
+        self.identity_label = Some(label);
+        self
+    }
+
+    /// Warn whenever a single wait for the broker to map our last messages, e.g., before a restart,
+    /// takes longer than `threshold`, see [`LlmpEventManager::unmap_wait_stats`].
+    ///
+    /// A long wait usually means the broker, or a peer it is busy with, lags behind.
+    #[must_use]
+    pub fn unmap_wait_warning(mut self, threshold: Duration) -> Self {
+        self.unmap_wait_warning = Some(threshold);
+        self
+    }
+
     /// Create a manager from a raw LLMP client
     pub fn build_from_client<S, SP>(
         self,
@@ -149,12 +210,14 @@ impl<EMH> LlmpEventManagerBuilder<EMH> {
         SP: ShMemProvider,
         S: State,
     {
-        Ok(LlmpEventManager {
+        let mut mgr = LlmpEventManager {
             throttle: self.throttle,
             last_sent: Duration::from_secs(0),
             hooks: self.hooks,
             always_interesting: self.always_interesting,
             llmp,
+            hash_first: HashFirst::new(self.hash_first),
+            unmap_wait: UnmapWait::new(self.unmap_wait_warning),
             #[cfg(feature = "llmp_compression")]
             compressor: GzipCompressor::with_threshold(COMPRESS_THRESHOLD),
             configuration,
@@ -162,10 +225,15 @@ impl<EMH> LlmpEventManagerBuilder<EMH> {
             deserialization_time: Duration::ZERO,
             serializations_cnt: 0,
             should_serialize_cnt: 0,
+            serializer_stats: AdaptiveSerializerStats::default(),
             time_ref,
             phantom: PhantomData,
             custom_buf_handlers: vec![],
-        })
+            #[cfg(feature = "std")]
+            event_log: None,
+        };
+        mgr.register_identity(self.identity_label)?;
+        Ok(mgr)
     }
 
     /// Create an LLMP event manager on a port.
@@ -183,12 +251,14 @@ impl<EMH> LlmpEventManagerBuilder<EMH> {
         S: State,
     {
         let llmp = LlmpClient::create_attach_to_tcp(shmem_provider, port)?;
-        Ok(LlmpEventManager {
+        let mut mgr = LlmpEventManager {
             throttle: self.throttle,
             last_sent: Duration::from_secs(0),
             hooks: self.hooks,
             always_interesting: self.always_interesting,
             llmp,
+            hash_first: HashFirst::new(self.hash_first),
+            unmap_wait: UnmapWait::new(self.unmap_wait_warning),
             #[cfg(feature = "llmp_compression")]
             compressor: GzipCompressor::with_threshold(COMPRESS_THRESHOLD),
             configuration,
@@ -196,10 +266,15 @@ impl<EMH> LlmpEventManagerBuilder<EMH> {
             deserialization_time: Duration::ZERO,
             serializations_cnt: 0,
             should_serialize_cnt: 0,
+            serializer_stats: AdaptiveSerializerStats::default(),
             time_ref,
             phantom: PhantomData,
             custom_buf_handlers: vec![],
-        })
+            #[cfg(feature = "std")]
+            event_log: None,
+        };
+        mgr.register_identity(self.identity_label)?;
+        Ok(mgr)
     }
 
     /// If a client respawns, it may reuse the existing connection, previously
@@ -217,12 +292,14 @@ impl<EMH> LlmpEventManagerBuilder<EMH> {
         S: State,
     {
         let llmp = LlmpClient::on_existing_from_env(shmem_provider, env_name)?;
-        Ok(LlmpEventManager {
+        let mut mgr = LlmpEventManager {
             throttle: self.throttle,
             last_sent: Duration::from_secs(0),
             hooks: self.hooks,
             always_interesting: self.always_interesting,
             llmp,
+            hash_first: HashFirst::new(self.hash_first),
+            unmap_wait: UnmapWait::new(self.unmap_wait_warning),
             #[cfg(feature = "llmp_compression")]
             compressor: GzipCompressor::with_threshold(COMPRESS_THRESHOLD),
             configuration,
@@ -230,10 +307,15 @@ impl<EMH> LlmpEventManagerBuilder<EMH> {
             deserialization_time: Duration::ZERO,
             serializations_cnt: 0,
             should_serialize_cnt: 0,
+            serializer_stats: AdaptiveSerializerStats::default(),
             time_ref,
             phantom: PhantomData,
             custom_buf_handlers: vec![],
-        })
+            #[cfg(feature = "std")]
+            event_log: None,
+        };
+        mgr.register_identity(self.identity_label)?;
+        Ok(mgr)
     }
 
     /// Create an existing client from description
@@ -249,12 +331,14 @@ impl<EMH> LlmpEventManagerBuilder<EMH> {
         S: State,
     {
         let llmp = LlmpClient::existing_client_from_description(shmem_provider, description)?;
-        Ok(LlmpEventManager {
+        let mut mgr = LlmpEventManager {
             throttle: self.throttle,
             last_sent: Duration::from_secs(0),
             hooks: self.hooks,
             always_interesting: self.always_interesting,
             llmp,
+            hash_first: HashFirst::new(self.hash_first),
+            unmap_wait: UnmapWait::new(self.unmap_wait_warning),
             #[cfg(feature = "llmp_compression")]
             compressor: GzipCompressor::with_threshold(COMPRESS_THRESHOLD),
             configuration,
@@ -262,10 +346,147 @@ impl<EMH> LlmpEventManagerBuilder<EMH> {
             deserialization_time: Duration::ZERO,
             serializations_cnt: 0,
             should_serialize_cnt: 0,
+            serializer_stats: AdaptiveSerializerStats::default(),
             time_ref,
             phantom: PhantomData,
             custom_buf_handlers: vec![],
-        })
+            #[cfg(feature = "std")]
+            event_log: None,
+        };
+        mgr.register_identity(self.identity_label)?;
+        Ok(mgr)
+    }
+}
+
+/// How long an event manager was blocked waiting for the broker to map its last messages,
+/// see [`LlmpEventManager::unmap_wait_stats`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct UnmapWaitStats {
+    /// How often the manager waited
+    pub waits: usize,
+    /// The total time the manager was blocked
+    pub blocked: Duration,
+    /// The longest single wait
+    pub longest: Duration,
+}
+
+/// Measures the waits of an event manager for the broker to map its last messages
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct UnmapWait {
+    /// Warn about single waits longer than this
+    warning: Option<Duration>,
+    stats: UnmapWaitStats,
+}
+
+impl UnmapWait {
+    pub(crate) fn new(warning: Option<Duration>) -> Self {
+        Self {
+            warning,
+            stats: UnmapWaitStats::default(),
+        }
+    }
+
+    /// Run the blocking `wait`, and account for the time it took
+    pub(crate) fn measure<R>(&mut self, wait: impl FnOnce() -> R) -> R {
+        let start = current_time();
+        let res = wait();
+        let blocked = current_time().saturating_sub(start);
+        self.stats.waits += 1;
+        self.stats.blocked += blocked;
+        self.stats.longest = self.stats.longest.max(blocked);
+        if self.warning.is_some_and(|warning| blocked > warning) {
+            log::warn!(
+                "Blocked for {blocked:?} until the broker mapped our last messages, is a peer lagging behind?"
+            );
+        }
+        res
+    }
+
+    pub(crate) fn stats(&self) -> &UnmapWaitStats {
+        &self.stats
+    }
+}
+
+/// How long a node waits for the answer to a fetch before asking again
+const FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+/// How often a node asks the announcer, and then the node that found it, for an announced testcase before giving up on it
+const MAX_FETCH_ATTEMPTS: usize = 3;
+/// How many announced testcases a node keeps to answer fetches, older ones are looked up in the corpus
+const MAX_ANNOUNCED: usize = 512;
+/// How many announced testcases a node waits for at once
+const MAX_PENDING_FETCHES: usize = 4096;
+
+/// A testcase announced by the hash of its input, found by `origin`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Announcement {
+    hash: u64,
+    origin: ClientId,
+}
+
+/// Asks the `announcer` for the full testcase of `hash`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Fetch {
+    hash: u64,
+    announcer: ClientId,
+}
+
+/// A testcase this node announced, kept to answer fetches
+#[derive(Debug)]
+struct AnnouncedTestcase {
+    hash: u64,
+    serialized: Vec<u8>,
+    /// When this node last answered a fetch, the other nodes waiting share the answer
+    answered: Option<Duration>,
+}
+
+/// An announced testcase this node waits for
+#[derive(Debug, Clone, Copy)]
+struct PendingFetch {
+    /// The node asked for the testcase, the announcer, or the node that found it once the announcer did not answer
+    announcer: ClientId,
+    /// The node that found the testcase
+    origin: ClientId,
+    requested: Duration,
+    attempts: usize,
+}
+
+/// What an [`LlmpEventManager`] announced and fetched, see [`LlmpEventManagerBuilder::hash_first`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HashFirstStats {
+    /// Testcases this node announced by hash instead of sending them
+    pub announced: usize,
+    /// Announced testcases this node skipped, as it found them itself
+    pub skipped: usize,
+    /// Fetches this node sent, including the retries
+    pub fetches: usize,
+    /// Fetches this node answered
+    pub served: usize,
+    /// Announced testcases this node gave up on, as its fetches were not answered
+    pub lost: usize,
+}
+
+/// The state of the hash-first mode of an [`LlmpEventManager`]
+#[derive(Debug, Default)]
+struct HashFirst {
+    /// If this node announces testcases by hash
+    enabled: bool,
+    /// The testcases this node announced, oldest first
+    announced: VecDeque<AnnouncedTestcase>,
+    /// The announced testcases this node waits for, by hash
+    pending: HashMap<u64, PendingFetch>,
+    /// The corpus entries by the hash of their input, to answer fetches of testcases no longer announced
+    index: HashMap<u64, CorpusId>,
+    /// The newest corpus entry in the `index`
+    indexed: Option<CorpusId>,
+    stats: HashFirstStats,
+}
+
+impl HashFirst {
+    fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Self::default()
+        }
     }
 }
 
@@ -300,6 +521,13 @@ where
         &mut self.should_serialize_cnt
     }
 
+    fn serializer_stats(&self) -> &AdaptiveSerializerStats {
+        &self.serializer_stats
+    }
+    fn serializer_stats_mut(&mut self) -> &mut AdaptiveSerializerStats {
+        &mut self.serializer_stats
+    }
+
     fn time_ref(&self) -> &Option<Handle<TimeObserver>> {
         &self.time_ref
     }
@@ -377,6 +605,178 @@ where
         self.llmp.describe()
     }
 
+    /// Log the testcases this node accepts from other nodes with the given [`EventLogWriter`],
+    /// or stop logging with `None`, to rebuild the corpus offline, see [`crate::events::EventLogReader`]
+    #[cfg(feature = "std")]
+    pub fn set_event_log(&mut self, event_log: Option<EventLogWriter>) {
+        self.event_log = event_log;
+    }
+
+    /// What this node announced and fetched so far, see [`LlmpEventManagerBuilder::hash_first`]
+    #[must_use]
+    pub fn hash_first_stats(&self) -> &HashFirstStats {
+        &self.hash_first.stats
+    }
+
+    /// How long this manager was blocked waiting for the broker to map its last messages,
+    /// before restarting or exiting, see [`LlmpEventManagerBuilder::unmap_wait_warning`]
+    #[must_use]
+    pub fn unmap_wait_stats(&self) -> &UnmapWaitStats {
+        self.unmap_wait.stats()
+    }
+
+    /// Send a serialized event with the given `tag`, compressed if it is large enough
+    fn send_event_buf(&mut self, tag: Tag, serialized: &[u8]) -> Result<(), Error> {
+        #[cfg(feature = "llmp_compression")]
+        if let Some(comp_buf) = self.compressor.maybe_compress(serialized) {
+            return self.llmp.send_buf_with_flags(
+                tag,
+                LLMP_FLAG_INITIALIZED | LLMP_FLAG_COMPRESSED,
+                &comp_buf,
+            );
+        }
+        self.llmp.send_buf(tag, serialized)
+    }
+
+    /// Register this client with the broker, see [`Event::ClientIdentity`]
+    #[allow(clippy::unnecessary_wraps)]
+    fn register_identity(&mut self, label: Option<&str>) -> Result<(), Error> {
+        #[cfg(feature = "std")]
+        {
+            let event: Event<S::Input> = Event::ClientIdentity {
+                identity: ClientIdentity::current(label.map(Into::into)),
+            };
+            let serialized = postcard::to_allocvec(&event)?;
+            self.send_event_buf(LLMP_TAG_EVENT_TO_BOTH, &serialized)?;
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = label;
+        Ok(())
+    }
+
+    /// Announce a testcase another node found by the hash of its input, if hash-first is enabled.
+    ///
+    /// Returns `true` if the testcase was announced instead of sent.
+    fn announce(&mut self, event: &Event<S::Input>, serialized: &[u8]) -> Result<bool, Error> {
+        let Event::NewTestcase {
+            input,
+            forward_id: Some(origin),
+            ..
+        } = event
+        else {
+            return Ok(false);
+        };
+        if !self.hash_first.enabled || *origin == self.llmp.sender().id() {
+            return Ok(false);
+        }
+
+        let hash = input.input_hash()?;
+        let announced = &mut self.hash_first.announced;
+        if announced.len() >= MAX_ANNOUNCED {
+            announced.pop_front();
+        }
+        announced.push_back(AnnouncedTestcase {
+            hash,
+            serialized: serialized.to_vec(),
+            answered: None,
+        });
+        self.hash_first.stats.announced += 1;
+        let announcement = Announcement {
+            hash,
+            origin: *origin,
+        };
+        self.llmp.send_buf(
+            _LLMP_TAG_TESTCASE_HASH,
+            &postcard::to_allocvec(&announcement)?,
+        )?;
+        Ok(true)
+    }
+
+    /// Fetch an announced testcase, unless this node found it itself
+    fn on_announcement(
+        &mut self,
+        announcer: ClientId,
+        announcement: Announcement,
+    ) -> Result<(), Error> {
+        if announcement.origin == self.llmp.sender().id() {
+            self.hash_first.stats.skipped += 1;
+            return Ok(());
+        }
+        let pending = &mut self.hash_first.pending;
+        if pending.contains_key(&announcement.hash) {
+            return Ok(());
+        }
+        if pending.len() >= MAX_PENDING_FETCHES {
+            log::warn!(
+                "Waiting for {MAX_PENDING_FETCHES} announced testcases already, dropping {:016x}",
+                announcement.hash
+            );
+            self.hash_first.stats.lost += 1;
+            return Ok(());
+        }
+        pending.insert(
+            announcement.hash,
+            PendingFetch {
+                announcer,
+                origin: announcement.origin,
+                requested: current_time(),
+                attempts: 1,
+            },
+        );
+        self.send_fetch(announcement.hash, announcer)
+    }
+
+    fn send_fetch(&mut self, hash: u64, announcer: ClientId) -> Result<(), Error> {
+        self.hash_first.stats.fetches += 1;
+        self.llmp.send_buf(
+            _LLMP_TAG_TESTCASE_FETCH,
+            &postcard::to_allocvec(&Fetch { hash, announcer })?,
+        )
+    }
+
+    /// Ask again for the announced testcases whose fetches were not answered in time.
+    ///
+    /// After [`MAX_FETCH_ATTEMPTS`], the full testcase is requested from the node that found it instead,
+    /// which has it in its corpus, and after as many more attempts, this node gives up.
+    fn retry_fetches(&mut self) -> Result<(), Error> {
+        if self.hash_first.pending.is_empty() {
+            return Ok(());
+        }
+        let now = current_time();
+        let mut retries = Vec::new();
+        let HashFirst { pending, stats, .. } = &mut self.hash_first;
+        pending.retain(|hash, pending| {
+            if now.saturating_sub(pending.requested) < FETCH_TIMEOUT {
+                return true;
+            }
+            if pending.attempts >= MAX_FETCH_ATTEMPTS {
+                if pending.announcer == pending.origin {
+                    log::warn!(
+                        "{:?} did not answer {MAX_FETCH_ATTEMPTS} fetches of testcase {hash:016x}, giving up",
+                        pending.announcer
+                    );
+                    stats.lost += 1;
+                    return false;
+                }
+                log::info!(
+                    "{:?} did not answer {MAX_FETCH_ATTEMPTS} fetches of testcase {hash:016x}, asking {:?}, which found it",
+                    pending.announcer,
+                    pending.origin
+                );
+                pending.announcer = pending.origin;
+                pending.attempts = 0;
+            }
+            pending.attempts += 1;
+            pending.requested = now;
+            retries.push((*hash, pending.announcer));
+            true
+        });
+        for (hash, announcer) in retries {
+            self.send_fetch(hash, announcer)?;
+        }
+        Ok(())
+    }
+
     /// Write the config for a client [`EventManager`] to env vars, a new
     /// client can reattach using [`LlmpEventManagerBuilder::build_existing_client_from_env()`].
     #[cfg(feature = "std")]
@@ -385,6 +785,17 @@ where
     }
 }
 
+#[cfg(feature = "std")]
+impl<EMH, S, SP> ReattachableEventManager for LlmpEventManager<EMH, S, SP>
+where
+    S: State,
+    SP: ShMemProvider,
+{
+    fn to_env(&self, env_name: &str) {
+        self.to_env(env_name);
+    }
+}
+
 impl<EMH, S, SP> LlmpEventManager<EMH, S, SP>
 where
     EMH: EventManagerHooksTuple<S>,
@@ -392,6 +803,99 @@ where
     S::Corpus: Corpus<Input = S::Input>,
     SP: ShMemProvider,
 {
+    /// Answer a fetch of a testcase this node announced, or found.
+    ///
+    /// Testcases no longer kept since the announcement are looked up in the corpus, and run again,
+    /// so the answer carries their exit kind and observers, as a fresh announcement would.
+    fn answer_fetch<E, Z>(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut S,
+        hash: u64,
+    ) -> Result<(), Error>
+    where
+        E: Executor<Self, Z, State = S> + HasObservers,
+        E::Observers: ObserversTuple<S::Input, S> + Serialize,
+        Z: EvaluatorObservers<E, Self, <S::Corpus as Corpus>::Input, S>,
+    {
+        let now = current_time();
+        let announced = self
+            .hash_first
+            .announced
+            .iter_mut()
+            .find(|announced| announced.hash == hash);
+        let serialized = match announced {
+            Some(announced)
+                if announced
+                    .answered
+                    .is_some_and(|answered| now.saturating_sub(answered) < FETCH_TIMEOUT) =>
+            {
+                // The answer is on its way already
+                return Ok(());
+            }
+            Some(announced) => {
+                announced.answered = Some(now);
+                announced.serialized.clone()
+            }
+            None => {
+                let Some(id) = self.find_in_corpus(state, hash)? else {
+                    log::warn!("Testcase {hash:016x} was fetched, but is not in the corpus");
+                    return Ok(());
+                };
+                let input = state.corpus().cloned_input_for_id(id)?;
+                let Some(exit_kind) =
+                    fuzzer.run_input_unevaluated(state, executor, self, input.clone())?
+                else {
+                    log::warn!("Testcase {hash:016x} was fetched, but the input filter skips it");
+                    return Ok(());
+                };
+                let observers_buf = postcard::to_allocvec(&*executor.observers())?;
+                postcard::to_allocvec(&Event::NewTestcase {
+                    input,
+                    observers_buf: Some(observers_buf),
+                    exit_kind,
+                    corpus_size: state.corpus().count(),
+                    client_config: self.configuration,
+                    time: now,
+                    forward_id: None,
+                    generation: None,
+                    calibration: None,
+                    forced: false,
+                    #[cfg(all(unix, feature = "std", feature = "multi_machine"))]
+                    node_id: None,
+                })?
+            }
+        };
+        self.hash_first.stats.served += 1;
+        self.send_event_buf(_LLMP_TAG_TESTCASE_FETCHED, &serialized)
+    }
+
+    /// The enabled corpus entry with the input of the given `hash`, if any.
+    ///
+    /// The entries added since the last lookup are hashed first, so each entry is hashed once.
+    fn find_in_corpus(&mut self, state: &S, hash: u64) -> Result<Option<CorpusId>, Error> {
+        let corpus = state.corpus();
+        let HashFirst { index, indexed, .. } = &mut self.hash_first;
+        let mut next = match *indexed {
+            Some(last) if corpus.get(last).is_ok() => corpus.next(last),
+            _ => {
+                index.clear();
+                corpus.first()
+            }
+        };
+        while let Some(id) = next {
+            next = corpus.next(id);
+            index.insert(corpus.cloned_input_for_id(id)?.input_hash()?, id);
+            *indexed = Some(id);
+        }
+        // Entries disabled or removed since they were indexed are not answered
+        Ok(index
+            .get(&hash)
+            .copied()
+            .filter(|id| corpus.get(*id).is_ok()))
+    }
+
     // Handle arriving events in the client
     #[allow(clippy::unused_self)]
     fn handle_in_client<E, Z>(
@@ -414,22 +918,33 @@ where
             return Ok(());
         }
         let evt_name = event.name_detailed();
+        let provenance = ProvenanceMetadata::from_event(client_id, &event);
         match event {
             Event::NewTestcase {
                 input,
                 client_config,
                 exit_kind,
                 observers_buf,
+                calibration,
+                forced,
                 #[cfg(feature = "std")]
                 forward_id,
                 ..
             } => {
                 #[cfg(feature = "std")]
                 log::debug!("[{}] Received new Testcase {evt_name} from {client_id:?} ({client_config:?}, forward {forward_id:?})", std::process::id());
+                // The calibration of the sender only tells us something if it runs the same target
+                let calibration =
+                    calibration.filter(|_| client_config.match_with(&self.configuration));
 
                 if self.always_interesting {
                     let item = fuzzer.add_input(state, executor, self, input)?;
                     log::debug!("Added received Testcase as item #{item}");
+                    Self::add_import_metadata(state, item, provenance, calibration, forced)?;
+                    #[cfg(feature = "std")]
+                    if let Some(event_log) = &mut self.event_log {
+                        event_log.record(state, client_id, item)?;
+                    }
                 } else {
                     let res = if client_config.match_with(&self.configuration)
                         && observers_buf.is_some()
@@ -456,6 +971,11 @@ where
                     if let Some(item) = res.1 {
                         *state.imported_mut() += 1;
                         log::debug!("Added received Testcase {evt_name} as item #{item}");
+                        Self::add_import_metadata(state, item, provenance, calibration, forced)?;
+                        #[cfg(feature = "std")]
+                        if let Some(event_log) = &mut self.event_log {
+                            event_log.record(state, client_id, item)?;
+                        }
                     } else {
                         log::debug!("Testcase {evt_name} was discarded");
                     }
@@ -472,16 +992,137 @@ where
                 state.request_stop();
             }
             _ => {
-                return Err(Error::unknown(format!(
-                    "Received illegal message that message should not have arrived: {:?}.",
-                    event.name()
-                )));
+                return Err(Error::distributed(
+                    DistributedError::new(
+                        DistributedPhase::Receiving,
+                        "Received illegal message that message should not have arrived",
+                    )
+                    .from_client(client_id)
+                    .with_event(evt_name),
+                ));
             }
         }
 
         self.hooks.post_exec_all(state, client_id)?;
         Ok(())
     }
+
+    /// Attach the `provenance`, the `calibration` hint, and the forced marker of a received testcase to its entry `item`
+    fn add_import_metadata(
+        state: &S,
+        item: CorpusId,
+        provenance: Option<ProvenanceMetadata>,
+        calibration: Option<CalibrationHint>,
+        forced: bool,
+    ) -> Result<(), Error> {
+        let mut testcase = state.corpus().get(item)?.borrow_mut();
+        if let Some(provenance) = provenance {
+            testcase.add_metadata(provenance);
+        }
+        if let Some(calibration) = calibration {
+            testcase.add_metadata(calibration);
+        }
+        if forced {
+            testcase.add_metadata(ForcedInputMetadata);
+        }
+        Ok(())
+    }
+
+    /// Receive the next message, and handle the event it carries, if any.
+    ///
+    /// Returns `None` if there was no message, and if an event was handled otherwise.
+    fn process_next<E, Z>(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut S,
+        executor: &mut E,
+    ) -> Result<Option<bool>, Error>
+    where
+        E: Executor<Self, Z, State = S> + HasObservers,
+        E::Observers: ObserversTuple<S::Input, S> + Serialize,
+        for<'a> E::Observers: Deserialize<'a>,
+        Z: ExecutionProcessor<Self, <S::Corpus as Corpus>::Input, E::Observers, S>
+            + EvaluatorObservers<E, Self, <S::Corpus as Corpus>::Input, S>
+            + Evaluator<E, Self, <S::Corpus as Corpus>::Input, S>,
+    {
+        // TODO: Get around local event copy by moving handle_in_client
+        let self_id = self.llmp.sender().id();
+        let Some((client_id, tag, flags, msg)) = self.llmp.recv_buf_with_flags()? else {
+            return Ok(None);
+        };
+        assert!(
+            tag != _LLMP_TAG_EVENT_TO_BROKER,
+            "EVENT_TO_BROKER parcel should not have arrived in the client!"
+        );
+
+        if client_id == self_id {
+            return Ok(Some(false));
+        }
+        let deserializing = |err: Error| {
+            err.in_distributed_flow(
+                DistributedError::new(DistributedPhase::Deserializing, "Could not decode an event")
+                    .from_client(client_id)
+                    .with_tag(tag),
+            )
+        };
+        if tag == _LLMP_TAG_TESTCASE_HASH {
+            let announcement =
+                postcard::from_bytes(msg).map_err(|err| deserializing(err.into()))?;
+            self.on_announcement(client_id, announcement)?;
+            return Ok(Some(false));
+        }
+        if tag == _LLMP_TAG_TESTCASE_FETCH {
+            let fetch: Fetch =
+                postcard::from_bytes(msg).map_err(|err| deserializing(err.into()))?;
+            if fetch.announcer == self_id {
+                self.answer_fetch(fuzzer, executor, state, fetch.hash)?;
+            }
+            return Ok(Some(false));
+        }
+        #[cfg(not(feature = "llmp_compression"))]
+        let event_bytes = msg;
+        #[cfg(feature = "llmp_compression")]
+        let compressed;
+        #[cfg(feature = "llmp_compression")]
+        let event_bytes = if flags & LLMP_FLAG_COMPRESSED == LLMP_FLAG_COMPRESSED {
+            compressed = self.compressor.decompress(msg).map_err(deserializing)?;
+            &compressed
+        } else {
+            msg
+        };
+        let event: Event<S::Input> =
+            postcard::from_bytes(event_bytes).map_err(|err| deserializing(err.into()))?;
+        log::debug!("Received event in normal llmp {}", event.name_detailed());
+
+        // Registrations are for the broker, which only passes them on without one
+        if matches!(event, Event::ClientIdentity { .. }) {
+            return Ok(Some(false));
+        }
+
+        // If the message comes from another machine, do not
+        // consider other events than new testcase.
+        if !event.is_new_testcase() && (flags & LLMP_FLAG_FROM_MM == LLMP_FLAG_FROM_MM) {
+            return Ok(Some(false));
+        }
+
+        // Answers to fetches are broadcast, only handle those this node waits for
+        if tag == _LLMP_TAG_TESTCASE_FETCHED {
+            let Event::NewTestcase { input, .. } = &event else {
+                return Ok(Some(false));
+            };
+            if self
+                .hash_first
+                .pending
+                .remove(&input.input_hash()?)
+                .is_none()
+            {
+                return Ok(Some(false));
+            }
+        }
+
+        self.handle_in_client(fuzzer, executor, state, client_id, event)?;
+        Ok(Some(true))
+    }
 }
 
 impl<EMH, S: State, SP: ShMemProvider> LlmpEventManager<EMH, S, SP> {
@@ -521,6 +1162,10 @@ where
         event: Event<<Self::State as UsesInput>::Input>,
     ) -> Result<(), Error> {
         let serialized = postcard::to_allocvec(&event)?;
+        if self.announce(&event, &serialized)? {
+            self.last_sent = current_time();
+            return Ok(());
+        }
         let flags = LLMP_FLAG_INITIALIZED;
 
         match self.compressor.maybe_compress(&serialized) {
@@ -547,7 +1192,9 @@ where
         event: Event<<Self::State as UsesInput>::Input>,
     ) -> Result<(), Error> {
         let serialized = postcard::to_allocvec(&event)?;
-        self.llmp.send_buf(LLMP_TAG_EVENT_TO_BOTH, &serialized)?;
+        if !self.announce(&event, &serialized)? {
+            self.llmp.send_buf(LLMP_TAG_EVENT_TO_BOTH, &serialized)?;
+        }
         Ok(())
     }
 
@@ -578,7 +1225,14 @@ where
     /// Otherwise, the OS may already have removed the shared maps.
     fn await_restart_safe(&mut self) {
         // wait until we can drop the message safely.
-        self.llmp.await_safe_to_unmap_blocking();
+        self.unmap_wait
+            .measure(|| self.llmp.await_safe_to_unmap_blocking());
+    }
+
+    #[cfg(feature = "std")]
+    fn await_restart_safe_for(&mut self, timeout: Duration) -> bool {
+        self.unmap_wait
+            .measure(|| self.llmp.await_safe_to_unmap_timeout(timeout))
     }
 }
 
@@ -601,46 +1255,40 @@ where
         state: &mut Self::State,
         executor: &mut E,
     ) -> Result<usize, Error> {
-        // TODO: Get around local event copy by moving handle_in_client
-        let self_id = self.llmp.sender().id();
         let mut count = 0;
-        while let Some((client_id, tag, flags, msg)) = self.llmp.recv_buf_with_flags()? {
-            assert!(
-                tag != _LLMP_TAG_EVENT_TO_BROKER,
-                "EVENT_TO_BROKER parcel should not have arrived in the client!"
-            );
-
-            if client_id == self_id {
-                continue;
-            }
-            #[cfg(not(feature = "llmp_compression"))]
-            let event_bytes = msg;
-            #[cfg(feature = "llmp_compression")]
-            let compressed;
-            #[cfg(feature = "llmp_compression")]
-            let event_bytes = if flags & LLMP_FLAG_COMPRESSED == LLMP_FLAG_COMPRESSED {
-                compressed = self.compressor.decompress(msg)?;
-                &compressed
-            } else {
-                msg
-            };
-            let event: Event<S::Input> = postcard::from_bytes(event_bytes)?;
-            log::debug!("Received event in normal llmp {}", event.name_detailed());
+        while let Some(handled) = self.process_next(fuzzer, state, executor)? {
+            count += usize::from(handled);
+        }
+        self.retry_fetches()?;
+        Ok(count)
+    }
 
-            // If the message comes from another machine, do not
-            // consider other events than new testcase.
-            if !event.is_new_testcase() && (flags & LLMP_FLAG_FROM_MM == LLMP_FLAG_FROM_MM) {
-                continue;
+    fn process_one(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut Self::State,
+        executor: &mut E,
+    ) -> Result<bool, Error> {
+        let mut handled = false;
+        while let Some(next) = self.process_next(fuzzer, state, executor)? {
+            if next {
+                handled = true;
+                break;
             }
-
-            self.handle_in_client(fuzzer, executor, state, client_id, event)?;
-            count += 1;
         }
-        Ok(count)
+        self.retry_fetches()?;
+        Ok(handled)
     }
 
     fn on_shutdown(&mut self) -> Result<(), Error> {
-        self.send_exiting()
+        self.send_exiting()?;
+        // Give the broker a chance to get our last messages before we unmap, but don't hang if it is gone
+        if !self.await_restart_safe_for(SHUTDOWN_UNMAP_TIMEOUT) {
+            log::warn!(
+                "The broker did not map our last messages within {SHUTDOWN_UNMAP_TIMEOUT:?}, exiting anyway"
+            );
+        }
+        Ok(())
     }
 }
 
@@ -659,6 +1307,16 @@ where
 {
 }
 
+impl<EMH, S, SP> HasPendingEvents for LlmpEventManager<EMH, S, SP>
+where
+    S: State,
+    SP: ShMemProvider,
+{
+    fn pending_events(&self) -> bool {
+        self.llmp.has_pending()
+    }
+}
+
 impl<EMH, S, SP> HasCustomBufHandlers for LlmpEventManager<EMH, S, SP>
 where
     S: State,
@@ -677,6 +1335,9 @@ where
     S: State + HasExecutions + HasMetadata + HasLastReportTime,
     SP: ShMemProvider,
 {
+    fn report_manager_stats(&mut self, state: &mut Self::State) -> Result<(), Error> {
+        self.report_serializer_stats(state)
+    }
 }
 
 impl<EMH, S, SP> HasEventManagerId for LlmpEventManager<EMH, S, SP>
@@ -689,3 +1350,215 @@ where
         EventManagerId(self.llmp.sender().id().0 as usize)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use core::time::Duration;
+    use std::thread;
+
+    use libafl_bolts::{
+        llmp::{LlmpClient, LlmpSender, LlmpSharedMap},
+        rands::StdRand,
+        shmem::{ShMemProvider, StdShMemProvider},
+        tuples::tuple_list,
+        ClientId,
+    };
+    use serial_test::serial;
+
+    use super::{PendingFetch, MAX_FETCH_ATTEMPTS};
+    use crate::{
+        corpus::{Corpus, InMemoryCorpus, Testcase},
+        events::{
+            Event, EventConfig, EventFirer, EventProcessor, EventRestarter, LlmpEventManager,
+            ProvenanceMetadata,
+        },
+        executors::{ExitKind, InProcessExecutor},
+        feedbacks::ConstFeedback,
+        inputs::BytesInput,
+        schedulers::QueueScheduler,
+        state::{HasCorpus, NopState, StdState},
+        testing::llmp_ring,
+        StdFuzzer,
+    };
+
+    #[test]
+    #[serial]
+    #[cfg_attr(miri, ignore)]
+    fn test_hash_first() {
+        let mut feedback = ConstFeedback::new(true);
+        let mut objective = ConstFeedback::new(false);
+        let mut states = (0..3)
+            .map(|seed| {
+                StdState::new(
+                    StdRand::with_seed(seed),
+                    InMemoryCorpus::<BytesInput>::new(),
+                    InMemoryCorpus::new(),
+                    &mut feedback,
+                    &mut objective,
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+
+        // The origin and the other node listen to the main node, which listens to the other node,
+        // without a broker in between
+        let clients = llmp_ring();
+        let mut managers = clients
+            .into_iter()
+            .map(|client| {
+                LlmpEventManager::builder()
+                    .hash_first(true)
+                    .build_from_client(client, EventConfig::AlwaysUnique, None)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let mut harness = |_: &BytesInput| ExitKind::Ok;
+        let mut executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(),
+            &mut fuzzer,
+            &mut states[0],
+            &mut managers[0],
+        )
+        .unwrap();
+
+        let (main, origin, other) = (0, 1, 2);
+        let testcase = |byte| Event::NewTestcase {
+            input: BytesInput::new(vec![byte]),
+            observers_buf: None,
+            exit_kind: ExitKind::Ok,
+            corpus_size: 0,
+            client_config: EventConfig::AlwaysUnique,
+            time: Duration::ZERO,
+            forward_id: Some(ClientId(origin as u32)),
+            generation: None,
+            calibration: None,
+            forced: false,
+            #[cfg(all(unix, feature = "std", feature = "multi_machine"))]
+            node_id: None,
+        };
+        let mut process = |managers: &mut Vec<LlmpEventManager<_, _, _>>,
+                           states: &mut Vec<StdState<_, _, _, _>>,
+                           node: usize| {
+            managers[node]
+                .process(&mut fuzzer, &mut states[node], &mut executor)
+                .unwrap()
+        };
+
+        managers[main]
+            .fire(&mut StdState::nop().unwrap(), testcase(1))
+            .unwrap();
+        assert_eq!(managers[main].hash_first_stats().announced, 1);
+
+        // The origin has the testcase already, the other node fetches it
+        assert_eq!(process(&mut managers, &mut states, origin), 0);
+        assert_eq!(managers[origin].hash_first_stats().skipped, 1);
+        assert_eq!(process(&mut managers, &mut states, other), 0);
+        assert_eq!(managers[other].hash_first_stats().fetches, 1);
+        assert_eq!(process(&mut managers, &mut states, main), 0);
+        assert_eq!(managers[main].hash_first_stats().served, 1);
+        assert_eq!(process(&mut managers, &mut states, other), 1);
+        assert_eq!(process(&mut managers, &mut states, origin), 0);
+        // The testcase is attributed to the node that found it, not to the main node
+        let id = states[other].corpus().first().unwrap();
+        let fetched = states[other].corpus().get(id).unwrap().borrow();
+        assert_eq!(
+            ProvenanceMetadata::of(&fetched).map(|provenance| provenance.client_id),
+            Some(ClientId(origin as u32))
+        );
+        drop(fetched);
+
+        // Testcases no longer kept are run again, and answered from the corpus
+        states[main]
+            .corpus_mut()
+            .add(Testcase::new(BytesInput::new(vec![2])))
+            .unwrap();
+        managers[main]
+            .fire(&mut StdState::nop().unwrap(), testcase(2))
+            .unwrap();
+        managers[main].hash_first.announced.clear();
+        assert_eq!(process(&mut managers, &mut states, other), 0);
+        assert_eq!(process(&mut managers, &mut states, main), 0);
+        assert_eq!(managers[main].hash_first_stats().served, 2);
+        assert_eq!(managers[main].hash_first.index.len(), 1);
+        assert_eq!(process(&mut managers, &mut states, other), 1);
+        assert_eq!(states[other].corpus().count(), 2);
+
+        // Unanswered fetches are retried, then sent to the node that found the testcase, and eventually given up on
+        let pending = &mut managers[other].hash_first.pending;
+        for (hash, attempts, origin) in [
+            (3, 1, main),
+            (4, MAX_FETCH_ATTEMPTS, main),
+            (5, MAX_FETCH_ATTEMPTS, origin),
+        ] {
+            pending.insert(
+                hash,
+                PendingFetch {
+                    announcer: ClientId(main as u32),
+                    origin: ClientId(origin as u32),
+                    requested: Duration::ZERO,
+                    attempts,
+                },
+            );
+        }
+        assert_eq!(process(&mut managers, &mut states, other), 0);
+        let stats = *managers[other].hash_first_stats();
+        assert_eq!((stats.fetches, stats.lost), (4, 1));
+        let pending = &managers[other].hash_first.pending;
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[&5].announcer, ClientId(origin as u32));
+    }
+
+    #[test]
+    #[serial]
+    #[cfg_attr(miri, ignore)]
+    fn test_unmap_wait_stats() {
+        const DELAY: Duration = Duration::from_millis(100);
+
+        let mut shmem_provider = StdShMemProvider::new().unwrap();
+        let client = LlmpClient::new(
+            shmem_provider.clone(),
+            LlmpSharedMap::new(ClientId(0), shmem_provider.new_shmem(1024).unwrap()),
+            ClientId(0),
+        )
+        .unwrap();
+        let description = client.sender().describe().unwrap();
+        let mut mgr: LlmpEventManager<_, NopState<BytesInput>, _> = LlmpEventManager::builder()
+            .unmap_wait_warning(DELAY / 2)
+            .build_from_client(client, EventConfig::AlwaysUnique, None)
+            .unwrap();
+        assert_eq!(mgr.unmap_wait_stats().waits, 0);
+
+        // A broker that takes its time to map the messages of the manager
+        let broker = thread::spawn(move || {
+            let mut sender = LlmpSender::on_existing_from_description(
+                StdShMemProvider::new().unwrap(),
+                &description,
+            )
+            .unwrap();
+            thread::sleep(DELAY);
+            unsafe {
+                sender.mark_safe_to_unmap();
+            }
+        });
+        mgr.await_restart_safe();
+        broker.join().unwrap();
+        let stats = *mgr.unmap_wait_stats();
+        assert_eq!(stats.waits, 1);
+        assert!(
+            stats.blocked >= DELAY / 2,
+            "blocked for {:?}",
+            stats.blocked
+        );
+        assert_eq!(stats.longest, stats.blocked);
+
+        // Once mapped, waiting is quick
+        mgr.await_restart_safe();
+        let stats = *mgr.unmap_wait_stats();
+        assert_eq!(stats.waits, 2);
+        assert!(stats.blocked < stats.longest + DELAY / 2);
+    }
+}
@@ -44,6 +44,12 @@ pub(crate) const _LLMP_TAG_EVENT_TO_BROKER: Tag = Tag(0x2B80438);
 pub(crate) const LLMP_TAG_EVENT_TO_BOTH: Tag = Tag(0x2B0741);
 pub(crate) const _LLMP_TAG_RESTART: Tag = Tag(0x8357A87);
 pub(crate) const _LLMP_TAG_NO_RESTART: Tag = Tag(0x57A7EE71);
+/// Announce a testcase by the hash of its input, see [`LlmpEventManagerBuilder::hash_first`]
+pub(crate) const _LLMP_TAG_TESTCASE_HASH: Tag = Tag(0x4A54F125);
+/// Ask the announcing node for the full testcase of an announced hash
+pub(crate) const _LLMP_TAG_TESTCASE_FETCH: Tag = Tag(0xFE7C4);
+/// The full testcase, answering a fetch
+pub(crate) const _LLMP_TAG_TESTCASE_FETCHED: Tag = Tag(0xFE7C4ED);
 
 /// The minimum buffer size at which to compress LLMP IPC messages.
 #[cfg(feature = "llmp_compression")]
@@ -366,7 +372,12 @@ where
                 "EVENT_TO_BROKER parcel should not have arrived in the client!"
             );
 
-            if client_id == self_id {
+            // The hash-first messages are only for the event managers
+            if client_id == self_id
+                || tag == _LLMP_TAG_TESTCASE_HASH
+                || tag == _LLMP_TAG_TESTCASE_FETCH
+                || tag == _LLMP_TAG_TESTCASE_FETCHED
+            {
                 continue;
             }
             #[cfg(not(feature = "llmp_compression"))]
@@ -437,6 +448,9 @@ where
                 observers_buf,
                 time,
                 forward_id,
+                generation,
+                calibration,
+                forced,
                 #[cfg(all(unix, feature = "std", feature = "multi_machine"))]
                 node_id,
             } => Event::NewTestcase {
@@ -447,6 +461,9 @@ where
                 observers_buf,
                 time,
                 forward_id,
+                generation,
+                calibration,
+                forced,
                 #[cfg(all(unix, feature = "std", feature = "multi_machine"))]
                 node_id,
             },
@@ -494,6 +511,9 @@ where
                 observers_buf,
                 time,
                 forward_id,
+                generation,
+                calibration,
+                forced,
                 #[cfg(all(unix, feature = "std", feature = "multi_machine"))]
                 node_id,
             } => Event::NewTestcase {
@@ -504,6 +524,9 @@ where
                 observers_buf,
                 time,
                 forward_id,
+                generation,
+                calibration,
+                forced,
                 #[cfg(all(unix, feature = "std", feature = "multi_machine"))]
                 node_id,
             },
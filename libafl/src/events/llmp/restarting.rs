@@ -3,14 +3,14 @@
 //! When the target crashes, a watch process (the parent) will
 //! restart/refork it.
 
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{borrow::Cow, boxed::Box, vec::Vec};
 use core::{
     marker::PhantomData,
     num::NonZeroUsize,
     sync::atomic::{compiler_fence, Ordering},
     time::Duration,
 };
-use std::net::SocketAddr;
+use std::{net::SocketAddr, time::Instant};
 
 #[cfg(any(windows, not(feature = "fork")))]
 use libafl_bolts::os::startable_self;
@@ -23,7 +23,7 @@ use libafl_bolts::{
     llmp::{Broker, LlmpBroker, LlmpConnection},
     os::CTRL_C_EXIT,
     shmem::{ShMemProvider, StdShMemProvider},
-    staterestore::StateRestorer,
+    staterestore::{SnapshotStats, StateRestorer},
     tuples::{tuple_list, Handle},
 };
 use serde::{Deserialize, Serialize};
@@ -34,16 +34,21 @@ use crate::events::EVENTMGR_SIGHANDLER_STATE;
 use crate::{
     corpus::Corpus,
     events::{
-        launcher::ClientDescription, AdaptiveSerializer, CustomBufEventResult, Event, EventConfig,
-        EventFirer, EventManager, EventManagerHooksTuple, EventManagerId, EventProcessor,
-        EventRestarter, HasCustomBufHandlers, HasEventManagerId, LlmpEventManager,
-        LlmpShouldSaveState, ProgressReporter, StdLlmpEventHook,
+        launcher::ClientDescription, AdaptiveSerializer, AdaptiveSerializerStats,
+        CustomBufEventResult, Event, EventConfig, EventFirer, EventManager, EventManagerHooksTuple,
+        EventManagerId, EventProcessor, EventRestarter, HasCustomBufHandlers, HasEventManagerId,
+        HasPendingEvents, LlmpEventManager, LlmpShouldSaveState, ProgressReporter,
+        StdLlmpEventHook, SHUTDOWN_UNMAP_TIMEOUT,
     },
     executors::{Executor, HasObservers},
     fuzzer::{Evaluator, EvaluatorObservers, ExecutionProcessor},
     inputs::UsesInput,
-    monitors::Monitor,
+    monitors::{
+        AggregatorOps, Monitor, UserStats, UserStatsValue, STATE_LOAD_TIME_STAT,
+        STATE_SAVE_TIME_STAT, STATE_SNAPSHOT_SIZE_STAT,
+    },
     observers::{ObserversTuple, TimeObserver},
+    stages::ReattachableEventManager,
     state::{HasCorpus, HasExecutions, HasImported, HasLastReportTime, State, UsesState},
     Error, HasMetadata,
 };
@@ -95,6 +100,13 @@ where
         self.llmp_mgr.should_serialize_cnt_mut()
     }
 
+    fn serializer_stats(&self) -> &AdaptiveSerializerStats {
+        self.llmp_mgr.serializer_stats()
+    }
+    fn serializer_stats_mut(&mut self) -> &mut AdaptiveSerializerStats {
+        self.llmp_mgr.serializer_stats_mut()
+    }
+
     fn time_ref(&self) -> &Option<Handle<TimeObserver>> {
         &self.llmp_mgr.time_ref
     }
@@ -113,6 +125,9 @@ where
     S: State + HasExecutions + HasMetadata + HasLastReportTime,
     SP: ShMemProvider,
 {
+    fn report_manager_stats(&mut self, state: &mut Self::State) -> Result<(), Error> {
+        self.report_serializer_stats(state)
+    }
 }
 
 impl<EMH, S, SP> EventFirer for LlmpRestartingEventManager<EMH, S, SP>
@@ -161,6 +176,11 @@ where
         self.llmp_mgr.await_restart_safe();
     }
 
+    #[inline]
+    fn await_restart_safe_for(&mut self, timeout: Duration) -> bool {
+        self.llmp_mgr.await_restart_safe_for(timeout)
+    }
+
     /// Reset the single page (we reuse it over and over from pos 0), then send the current state to the next runner.
     fn on_restart(&mut self, state: &mut S) -> Result<(), Error> {
         state.on_restart()?;
@@ -212,8 +232,36 @@ where
         Ok(res)
     }
 
+    fn process_one(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut S,
+        executor: &mut E,
+    ) -> Result<bool, Error> {
+        let res = self.llmp_mgr.process_one(fuzzer, state, executor)?;
+        self.intermediate_save()?;
+        Ok(res)
+    }
+
     fn on_shutdown(&mut self) -> Result<(), Error> {
-        self.send_exiting()
+        self.send_exiting()?;
+        // Give the broker a chance to get our last messages before we unmap, but don't hang if it is gone
+        if !self.await_restart_safe_for(SHUTDOWN_UNMAP_TIMEOUT) {
+            log::warn!(
+                "The broker did not map our last messages within {SHUTDOWN_UNMAP_TIMEOUT:?}, exiting anyway"
+            );
+        }
+        Ok(())
+    }
+}
+
+impl<EMH, S, SP> HasPendingEvents for LlmpRestartingEventManager<EMH, S, SP>
+where
+    S: State,
+    SP: ShMemProvider,
+{
+    fn pending_events(&self) -> bool {
+        self.llmp_mgr.pending_events()
     }
 }
 
@@ -246,6 +294,16 @@ where
     }
 }
 
+impl<EMH, S, SP> ReattachableEventManager for LlmpRestartingEventManager<EMH, S, SP>
+where
+    S: State,
+    SP: ShMemProvider,
+{
+    fn to_env(&self, env_name: &str) {
+        self.llmp_mgr.to_env(env_name);
+    }
+}
+
 impl<EMH, S, SP> HasCustomBufHandlers for LlmpRestartingEventManager<EMH, S, SP>
 where
     S: State,
@@ -313,6 +371,32 @@ where
         }
         Ok(())
     }
+
+    /// Report the size of the state snapshot this client was restored from,
+    /// and how long saving and loading it took, as user stats
+    fn report_snapshot_stats(
+        &mut self,
+        state: &mut S,
+        stats: SnapshotStats,
+        load_time: Duration,
+    ) -> Result<(), Error> {
+        let millis = |time: Duration| time.as_millis().try_into().unwrap_or(u64::MAX);
+        for (name, value) in [
+            (STATE_SNAPSHOT_SIZE_STAT, stats.stored_len as u64),
+            (STATE_SAVE_TIME_STAT, millis(stats.save_time)),
+            (STATE_LOAD_TIME_STAT, millis(load_time)),
+        ] {
+            self.llmp_mgr.fire(
+                state,
+                Event::UpdateUserStats {
+                    name: Cow::Borrowed(name),
+                    value: UserStats::new(UserStatsValue::Number(value), AggregatorOps::Avg),
+                    phantom: PhantomData,
+                },
+            )?;
+        }
+        Ok(())
+    }
 }
 
 /// The kind of manager we're creating right now
@@ -397,7 +481,11 @@ where
 /// The [`RestartingMgr`] is is a combination of a
 /// `restarter` and `runner`, that can be used on systems both with and without `fork` support. The
 /// `restarter` will start a new process each time the child crashes or times out.
-#[allow(clippy::default_trait_access, clippy::ignored_unit_patterns)]
+#[allow(
+    clippy::default_trait_access,
+    clippy::ignored_unit_patterns,
+    clippy::struct_excessive_bools
+)]
 #[derive(TypedBuilder, Debug)]
 pub struct RestartingMgr<EMH, MT, S, SP> {
     /// The shared memory provider to use for the broker or client spawned by the restarting
@@ -406,6 +494,9 @@ pub struct RestartingMgr<EMH, MT, S, SP> {
     #[builder(default = false)]
     /// Consider this testcase as interesting always if true
     always_interesting: bool,
+    /// Announce testcases other nodes found by hash, see [`crate::events::LlmpEventManagerBuilder::hash_first`]
+    #[builder(default = false)]
+    hash_first: bool,
     /// The configuration
     configuration: EventConfig,
     /// The monitor to use
@@ -431,6 +522,18 @@ pub struct RestartingMgr<EMH, MT, S, SP> {
     /// Tell the manager to serialize or not the state on restart
     #[builder(default = LlmpShouldSaveState::OnRestart)]
     serialize_state: LlmpShouldSaveState,
+    /// Compress the serialized state before handing it to the next client.
+    /// Requires the `gzip` feature, without it, the state is stored uncompressed.
+    #[builder(default = false)]
+    compress_state: bool,
+    /// The campaign schema version stored with the serialized state.
+    /// Bump it when the layout of some metadata changes, and register migrations for the old layout,
+    /// see [`libafl_bolts::serdeany::RegistryBuilder::register_migration`].
+    #[builder(default = 0)]
+    state_schema_version: u32,
+    /// Drop metadata that can not be restored after a restart, instead of aborting the campaign
+    #[builder(default = false)]
+    drop_unknown_metadata: bool,
     /// The hooks passed to event manager:
     hooks: EMH,
     #[builder(default = None)]
@@ -450,7 +553,7 @@ where
     /// Launch the broker and the clients and fuzz
     pub fn launch(&mut self) -> Result<(Option<S>, LlmpRestartingEventManager<EMH, S, SP>), Error> {
         // We start ourselves as child process to actually fuzz
-        let (staterestorer, new_shmem_provider, core_id) = if std::env::var(_ENV_FUZZER_SENDER)
+        let (mut staterestorer, new_shmem_provider, core_id) = if std::env::var(_ENV_FUZZER_SENDER)
             .is_err()
         {
             let broker_things = |mut broker: LlmpBroker<_, SP>, remote_broker_addr| {
@@ -496,6 +599,7 @@ where
                         LlmpConnection::IsClient { client } => {
                             let mgr: LlmpEventManager<EMH, S, SP> = LlmpEventManager::builder()
                                 .always_interesting(self.always_interesting)
+                                .hash_first(self.hash_first)
                                 .hooks(self.hooks)
                                 .build_from_client(
                                     client,
@@ -522,6 +626,7 @@ where
                     // We are a client
                     let mgr = LlmpEventManager::builder()
                         .always_interesting(self.always_interesting)
+                        .hash_first(self.hash_first)
                         .hooks(self.hooks)
                         .build_on_port(
                             self.shmem_provider.clone(),
@@ -651,10 +756,24 @@ where
             core_id.set_affinity()?;
         }
 
+        #[cfg(feature = "gzip")]
+        staterestorer.set_compression(self.compress_state);
+        staterestorer.set_schema_version(self.state_schema_version);
+        staterestorer.set_drop_unknown_metadata(self.drop_unknown_metadata);
+        #[cfg(not(feature = "gzip"))]
+        if self.compress_state {
+            log::warn!(
+                "State compression requires the gzip feature, storing the state uncompressed"
+            );
+        }
+
         // If we're restarting, deserialize the old state.
-        let (state, mut mgr) =
+        let snapshot_stats = staterestorer.snapshot_stats();
+        let load_start = Instant::now();
+        let (mut state, mut mgr) =
             if let Some((state_opt, mgr_description)) = staterestorer.restore()? {
                 let llmp_mgr = LlmpEventManager::builder()
+                    .hash_first(self.hash_first)
                     .hooks(self.hooks)
                     .build_existing_client_from_description(
                         new_shmem_provider,
@@ -674,6 +793,7 @@ where
                 log::info!("First run. Let's set it all up");
                 // Mgr to send and receive msgs from/to all other fuzzer instances
                 let mgr = LlmpEventManager::builder()
+                    .hash_first(self.hash_first)
                     .hooks(self.hooks)
                     .build_existing_client_from_env(
                         new_shmem_provider,
@@ -691,6 +811,7 @@ where
                     ),
                 )
             };
+        let load_time = load_start.elapsed();
         // We reset the staterestorer, the next staterestorer and receiver (after crash) will reuse the page from the initial message.
         if self.serialize_state.oom_safe() {
             mgr.intermediate_save()?;
@@ -698,6 +819,10 @@ where
             mgr.staterestorer.reset();
         }
 
+        if let (Some(state), Some(stats)) = (state.as_mut(), snapshot_stats) {
+            mgr.report_snapshot_stats(state, stats, load_time)?;
+        }
+
         /* TODO: Not sure if this is needed
         // We commit an empty NO_RESTART message to this buf, against infinite loops,
         // in case something crashes in the fuzzer.
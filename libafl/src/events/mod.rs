@@ -11,6 +11,12 @@ pub mod centralized;
 #[cfg(all(unix, feature = "std"))]
 pub use centralized::*;
 #[cfg(feature = "std")]
+pub mod event_log;
+#[cfg(feature = "std")]
+pub use event_log::*;
+#[cfg(feature = "std")]
+pub(crate) mod framing;
+#[cfg(feature = "std")]
 #[allow(clippy::ignored_unit_patterns)]
 pub mod launcher;
 #[allow(clippy::ignored_unit_patterns)]
@@ -21,7 +27,7 @@ pub use llmp::*;
 pub mod tcp;
 
 pub mod broker_hooks;
-use alloc::{borrow::Cow, boxed::Box, string::String, vec::Vec};
+use alloc::{borrow::Cow, boxed::Box, collections::BTreeMap, string::String, vec::Vec};
 use core::{
     fmt,
     hash::{BuildHasher, Hasher},
@@ -40,6 +46,7 @@ use libafl_bolts::os::CTRL_C_EXIT;
 use libafl_bolts::{
     current_time,
     tuples::{Handle, MatchNameRef},
+    ClientId,
 };
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "std")]
@@ -47,19 +54,22 @@ use uuid::Uuid;
 
 #[cfg(feature = "introspection")]
 use crate::state::HasClientPerfMonitor;
+#[cfg(feature = "scalability_introspection")]
+use crate::state::HasScalabilityMonitor;
 use crate::{
+    corpus::Testcase,
     executors::ExitKind,
+    fuzzer::SkippedInputsMetadata,
     inputs::Input,
-    monitors::UserStats,
+    monitors::{
+        AggregatorOps, ClientIdentity, Monitor, UserStats, UserStatsValue, OBSERVERS_BYTES_STAT,
+        OBSERVERS_SERIALIZATION_TIME_STAT, OBSERVERS_SERIALIZED_STAT, OBSERVERS_SKIPPED_STAT,
+        SKIPPED_INPUTS_STAT,
+    },
     observers::ObserversTuple,
-    state::{HasExecutions, HasLastReportTime, State},
+    state::{HasExecutions, HasLastReportTime, State, DEFAULT_REPORT_CHANNEL},
     Error, HasMetadata,
 };
-#[cfg(feature = "scalability_introspection")]
-use crate::{
-    monitors::{AggregatorOps, UserStatsValue},
-    state::HasScalabilityMonitor,
-};
 
 /// Multi-machine mode
 #[cfg(all(unix, feature = "std", feature = "multi_machine"))]
@@ -117,8 +127,13 @@ pub struct EventManagerId(
 use crate::events::multi_machine::NodeId;
 #[cfg(feature = "introspection")]
 use crate::monitors::ClientPerfMonitor;
+#[cfg(feature = "std")]
+use crate::stages::ReattachableEventManager;
 use crate::{
-    inputs::UsesInput, observers::TimeObserver, stages::HasCurrentStageId, state::UsesState,
+    inputs::UsesInput,
+    observers::TimeObserver,
+    stages::{CalibrationHint, HasCurrentStageId},
+    state::UsesState,
 };
 
 /// The log event severity
@@ -174,6 +189,46 @@ pub enum BrokerEventResult {
     Forward,
 }
 
+/// How every broker handles the lifecycle events of a client, recording them in its [`ClientStats`](crate::monitors::ClientStats):
+/// the final numbers of an [`Event::ClientExiting`], and the identity of an [`Event::ClientIdentity`]
+pub(crate) fn handle_client_lifecycle<I, MT>(
+    monitor: &mut MT,
+    client_id: ClientId,
+    event: &Event<I>,
+) -> BrokerEventResult
+where
+    I: Input,
+    MT: Monitor,
+{
+    match event {
+        Event::ClientExiting {
+            time,
+            executions,
+            corpus_size,
+            objective_size,
+        } => {
+            monitor.client_stats_insert(client_id);
+            let client = monitor.client_stats_mut_for(client_id);
+            client.update_executions(*executions, *time);
+            client.update_corpus_size(*corpus_size as u64);
+            client.update_objective_size(*objective_size as u64);
+            monitor.display(event.name(), client_id);
+            log::info!("Client {} is exiting", client_id.0);
+        }
+        Event::ClientIdentity { identity } => {
+            monitor.client_stats_insert(client_id);
+            monitor.client_stats_mut_for(client_id).identity = Some(identity.clone());
+            monitor.display(event.name(), client_id);
+            log::info!("Client {} is {identity}", client_id.0);
+        }
+        _ => {}
+    }
+    BrokerEventResult::Handled
+}
+
+/// How long a manager waits on shutdown for the broker to map its last messages, before it exits anyway
+pub const SHUTDOWN_UNMAP_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Distinguish a fuzzer by its config
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EventConfig {
@@ -190,6 +245,16 @@ pub enum EventConfig {
         /// The build-time [`Uuid`]
         id: Uuid,
     },
+    /// A fuzzer config that also pins down the build and the coverage map,
+    /// see [`EventConfig::with_build_hash`] and [`EventConfig::with_map_len`]
+    Detailed {
+        /// The name hash, or the folded build-time [`Uuid`], of the config this was created from
+        config_hash: u64,
+        /// A hash of the fuzzer binary, or of its instrumentation, if known
+        build_hash: Option<u64>,
+        /// The length of the coverage map, if known
+        map_len: Option<usize>,
+    },
 }
 
 impl EventConfig {
@@ -212,23 +277,117 @@ impl EventConfig {
         }
     }
 
+    /// Also carry a hash of the fuzzer binary, or a user-supplied hash of its instrumentation,
+    /// so peers built differently do not trust each other's observers.
+    ///
+    /// [`EventConfig::AlwaysUnique`] stays as it is.
+    #[must_use]
+    pub fn with_build_hash(self, build_hash: u64) -> Self {
+        match self.detailed() {
+            EventConfig::Detailed {
+                config_hash,
+                map_len,
+                ..
+            } => EventConfig::Detailed {
+                config_hash,
+                build_hash: Some(build_hash),
+                map_len,
+            },
+            config => config,
+        }
+    }
+
+    /// Also carry a hash of the running fuzzer binary, see [`EventConfig::with_build_hash`]
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn with_binary_hash(self) -> Self {
+        let (high, low) = libafl_bolts::build_id::get().as_u64_pair();
+        self.with_build_hash(high ^ low)
+    }
+
+    /// Also carry the length of the coverage map, so peers with a different map do not trust each other's observers.
+    ///
+    /// [`EventConfig::AlwaysUnique`] stays as it is.
+    #[must_use]
+    pub fn with_map_len(self, map_len: usize) -> Self {
+        match self.detailed() {
+            EventConfig::Detailed {
+                config_hash,
+                build_hash,
+                ..
+            } => EventConfig::Detailed {
+                config_hash,
+                build_hash,
+                map_len: Some(map_len),
+            },
+            config => config,
+        }
+    }
+
+    /// The hash identifying the config, `None` for [`EventConfig::AlwaysUnique`]
+    fn config_hash(&self) -> Option<u64> {
+        match self {
+            EventConfig::AlwaysUnique => None,
+            EventConfig::FromName { name_hash } => Some(*name_hash),
+            #[cfg(feature = "std")]
+            EventConfig::BuildID { id } => {
+                let (high, low) = id.as_u64_pair();
+                Some(high ^ low)
+            }
+            EventConfig::Detailed { config_hash, .. } => Some(*config_hash),
+        }
+    }
+
+    /// This config as [`EventConfig::Detailed`], unless it is [`EventConfig::AlwaysUnique`]
+    fn detailed(self) -> Self {
+        match self.config_hash() {
+            Some(config_hash) if !matches!(self, EventConfig::Detailed { .. }) => {
+                EventConfig::Detailed {
+                    config_hash,
+                    build_hash: None,
+                    map_len: None,
+                }
+            }
+            _ => self,
+        }
+    }
+
     /// Match if the current [`EventConfig`] matches another given config
+    ///
+    /// An [`EventConfig::Detailed`] matches another one if the configs they were created from match,
+    /// and all fields present in both are equal. It matches the config it was created from,
+    /// so peers that do not send the details yet are still trusted.
     #[must_use]
     pub fn match_with(&self, other: &EventConfig) -> bool {
-        match self {
-            EventConfig::AlwaysUnique => false,
-            EventConfig::FromName { name_hash: a } => match other {
-                #[cfg(not(feature = "std"))]
-                EventConfig::AlwaysUnique => false,
-                EventConfig::FromName { name_hash: b } => a == b,
-                #[cfg(feature = "std")]
-                EventConfig::AlwaysUnique | EventConfig::BuildID { id: _ } => false,
-            },
+        fn agree<T: PartialEq>(a: Option<T>, b: Option<T>) -> bool {
+            a.zip(b).is_none_or(|(a, b)| a == b)
+        }
+
+        match (self, other) {
+            (EventConfig::AlwaysUnique, _) | (_, EventConfig::AlwaysUnique) => false,
+            (
+                EventConfig::Detailed {
+                    config_hash: a,
+                    build_hash: a_build,
+                    map_len: a_len,
+                },
+                EventConfig::Detailed {
+                    config_hash: b,
+                    build_hash: b_build,
+                    map_len: b_len,
+                },
+            ) => a == b && agree(*a_build, *b_build) && agree(*a_len, *b_len),
+            (EventConfig::Detailed { .. }, _) | (_, EventConfig::Detailed { .. }) => {
+                self.config_hash() == other.config_hash()
+            }
+            (EventConfig::FromName { name_hash: a }, EventConfig::FromName { name_hash: b }) => {
+                a == b
+            }
             #[cfg(feature = "std")]
-            EventConfig::BuildID { id: a } => match other {
-                EventConfig::AlwaysUnique | EventConfig::FromName { name_hash: _ } => false,
-                EventConfig::BuildID { id: b } => a == b,
-            },
+            (EventConfig::BuildID { id: a }, EventConfig::BuildID { id: b }) => a == b,
+            #[cfg(feature = "std")]
+            (EventConfig::FromName { .. }, EventConfig::BuildID { .. })
+            | (EventConfig::BuildID { .. }, EventConfig::FromName { .. }) => false,
         }
     }
 }
@@ -287,7 +446,13 @@ where
         /// The time of generation of the event
         time: Duration,
         /// The original sender if, if forwarded
-        forward_id: Option<libafl_bolts::ClientId>,
+        forward_id: Option<ClientId>,
+        /// The generation of the forwarding node, i.e., how often it restarted, if tracked
+        generation: Option<u64>,
+        /// The calibration the sender measured for this testcase, if any, see [`CalibrationHint`]
+        calibration: Option<CalibrationHint>,
+        /// If the sender added it regardless of its feedback, see [`crate::fuzzer::ForcedInputMetadata`]
+        forced: bool,
         /// The (multi-machine) node from which the tc is from, if any
         #[cfg(all(unix, feature = "std", feature = "multi_machine"))]
         node_id: Option<NodeId>,
@@ -348,6 +513,23 @@ where
     },
     /// Exit gracefully
     Stop,
+    /// A client stops gracefully, with its final numbers.
+    /// It has flushed its corpora, and won't send anything after this.
+    ClientExiting {
+        /// The time when this event was created
+        time: Duration,
+        /// The final executions of this client
+        executions: u64,
+        /// The final corpus size of this client
+        corpus_size: usize,
+        /// The final objective corpus size of this client
+        objective_size: usize,
+    },
+    /// A client registers its host and process, once after connecting, and again after each restart
+    ClientIdentity {
+        /// The identity of the client
+        identity: ClientIdentity,
+    },
     /*/// A custom type
     Custom {
         // TODO: Allow custom events
@@ -360,7 +542,7 @@ where
     I: Input,
 {
     /// Event's corresponding name
-    pub fn name(&self) -> &str {
+    pub fn name(&self) -> &'static str {
         match self {
             Event::NewTestcase { .. } => "Testcase",
             Event::UpdateExecStats { .. } => "Client Heartbeat",
@@ -374,6 +556,8 @@ where
                 sender_id: _, /*custom_event} => custom_event.name()*/
             } => "todo",*/
             Event::Stop => "Stop",
+            Event::ClientExiting { .. } => "Client Exiting",
+            Event::ClientIdentity { .. } => "Client Identity",
         }
     }
 
@@ -391,6 +575,8 @@ where
             Event::Log { .. } => Cow::Borrowed("Log"),
             Event::CustomBuf { .. } => Cow::Borrowed("CustomBuf"),
             Event::Stop => Cow::Borrowed("Stop"),
+            Event::ClientExiting { .. } => Cow::Borrowed("Client Exiting"),
+            Event::ClientIdentity { .. } => Cow::Borrowed("Client Identity"),
             /*Event::Custom {
                 sender_id: _, /*custom_event} => custom_event.name()*/
             } => "todo",*/
@@ -403,6 +589,82 @@ where
     }
 }
 
+/// Where a testcase imported from another client came from.
+///
+/// The event managers attach it to each testcase they add to the corpus from an [`Event::NewTestcase`],
+/// testcases found by this client have none, see [`ProvenanceMetadata::is_imported`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+pub struct ProvenanceMetadata {
+    /// The client that found the testcase
+    pub client_id: ClientId,
+    /// The generation of that client when it sent the testcase, i.e., how often it restarted, if tracked
+    pub generation: Option<u64>,
+    /// The (multi-machine) node the testcase came from, if any
+    #[cfg(all(unix, feature = "std", feature = "multi_machine"))]
+    pub node_id: Option<NodeId>,
+    /// When this client received the testcase
+    pub received_time: Duration,
+    /// When the testcase was found, as stamped on its [`Event::NewTestcase`]
+    pub original_found_time: Duration,
+}
+
+libafl_bolts::impl_serdeany!(ProvenanceMetadata);
+
+impl ProvenanceMetadata {
+    /// The provenance of the testcase `event` carries, received just now from `client_id`,
+    /// or `None` if it is no [`Event::NewTestcase`].
+    ///
+    /// For a forwarded testcase, e.g., one the main node of a centralized setup re-broadcast,
+    /// the original sender is the client that found it.
+    #[must_use]
+    pub fn from_event<I>(client_id: ClientId, event: &Event<I>) -> Option<Self>
+    where
+        I: Input,
+    {
+        let Event::NewTestcase {
+            time,
+            forward_id,
+            generation,
+            #[cfg(all(unix, feature = "std", feature = "multi_machine"))]
+            node_id,
+            ..
+        } = event
+        else {
+            return None;
+        };
+        Some(Self {
+            client_id: forward_id.unwrap_or(client_id),
+            generation: *generation,
+            #[cfg(all(unix, feature = "std", feature = "multi_machine"))]
+            node_id: *node_id,
+            received_time: current_time(),
+            original_found_time: *time,
+        })
+    }
+
+    /// The provenance of `testcase`, or `None` if it was found by this client
+    #[must_use]
+    pub fn of<I>(testcase: &Testcase<I>) -> Option<&Self> {
+        testcase.metadata::<Self>().ok()
+    }
+
+    /// If `testcase` was imported from another client, rather than found by this client
+    #[must_use]
+    pub fn is_imported<I>(testcase: &Testcase<I>) -> bool {
+        testcase.has_metadata::<Self>()
+    }
+
+    /// How long it took from finding the testcase to receiving it here
+    #[must_use]
+    pub fn latency(&self) -> Duration {
+        self.received_time.saturating_sub(self.original_found_time)
+    }
+}
+
 /// [`EventFirer`] fires an event.
 pub trait EventFirer: UsesState {
     /// Send off an [`Event`] to the broker
@@ -462,27 +724,36 @@ where
     /// Given the last time, if `monitor_timeout` seconds passed, send off an info/monitor/heartbeat message to the broker.
     /// Returns the new `last` time (so the old one, unless `monitor_timeout` time has passed and monitor have been sent)
     /// Will return an [`Error`], if the stats could not be sent.
+    ///
+    /// Before each report, the manager reports its own stats with [`ProgressReporter::report_manager_stats`],
+    /// and on every call, whether a report is due or not, [`ProgressReporter::on_progress_check`] runs.
     fn maybe_report_progress(
         &mut self,
         state: &mut Self::State,
         monitor_timeout: Duration,
     ) -> Result<(), Error> {
-        let Some(last_report_time) = state.last_report_time() else {
-            // this is the first time we execute, no need to report progress just yet.
-            *state.last_report_time_mut() = Some(current_time());
-            return Ok(());
-        };
-        let cur = current_time();
-        // default to 0 here to avoid crashes on clock skew
-        if cur.checked_sub(*last_report_time).unwrap_or_default() > monitor_timeout {
-            // report_progress sets a new `last_report_time` internally.
-            self.report_progress(state)?;
-        }
+        state.maybe_report(DEFAULT_REPORT_CHANNEL, monitor_timeout, |state| {
+            self.report_manager_stats(state)?;
+            self.report_progress(state)
+        })?;
+        self.on_progress_check(state)
+    }
+
+    /// Report the stats of this manager itself, such as its serialization stats,
+    /// before each report of [`ProgressReporter::maybe_report_progress`]. Does nothing by default.
+    fn report_manager_stats(&mut self, _state: &mut Self::State) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called on every [`ProgressReporter::maybe_report_progress`], for managers that need to act
+    /// more often than they report, e.g., to send a keepalive. Does nothing by default.
+    fn on_progress_check(&mut self, _state: &mut Self::State) -> Result<(), Error> {
         Ok(())
     }
 
     /// Send off an info/monitor/heartbeat message to the broker.
     /// Will return an [`Error`], if the stats could not be sent.
+    /// Along with the executions, it reports the inputs the input filter skipped, see [`SkippedInputsMetadata`].
     fn report_progress(&mut self, state: &mut Self::State) -> Result<(), Error> {
         let executions = *state.executions();
         let cur = current_time();
@@ -498,6 +769,18 @@ where
             },
         )?;
 
+        if let Ok(skipped) = state.metadata::<SkippedInputsMetadata>() {
+            let skipped = skipped.count();
+            self.fire(
+                state,
+                Event::UpdateUserStats {
+                    name: Cow::Borrowed(SKIPPED_INPUTS_STAT),
+                    value: UserStats::new(UserStatsValue::Number(skipped), AggregatorOps::Sum),
+                    phantom: PhantomData,
+                },
+            )?;
+        }
+
         // If performance monitor are requested, fire the `UpdatePerfMonitor` event
         #[cfg(feature = "introspection")]
         {
@@ -566,6 +849,17 @@ pub trait EventRestarter: UsesState {
     /// Block until we are safe to exit, usually called inside `on_restart`.
     #[inline]
     fn await_restart_safe(&mut self) {}
+
+    /// Like [`EventRestarter::await_restart_safe`], but give up after `timeout`.
+    ///
+    /// Returns `true` if it is safe to exit, `false` if the timeout passed first.
+    /// Managers that cannot bound their wait block like [`EventRestarter::await_restart_safe`].
+    #[inline]
+    fn await_restart_safe_for(&mut self, timeout: Duration) -> bool {
+        let _ = timeout;
+        self.await_restart_safe();
+        true
+    }
 }
 
 /// [`EventProcessor`] process all the incoming messages
@@ -579,9 +873,34 @@ pub trait EventProcessor<E, Z>: UsesState {
         executor: &mut E,
     ) -> Result<usize, Error>;
 
+    /// Process at most one incoming event, returning if there was one.
+    ///
+    /// Use this with [`HasPendingEvents::pending_events`] to interleave the event processing with other work,
+    /// e.g., in an async runtime. Managers that can not bound the work fall back to [`EventProcessor::process`].
+    fn process_one(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut Self::State,
+        executor: &mut E,
+    ) -> Result<bool, Error> {
+        Ok(self.process(fuzzer, state, executor)? > 0)
+    }
+
     /// Shutdown gracefully; typically without saving state.
     fn on_shutdown(&mut self) -> Result<(), Error>;
 }
+
+/// Tells, without processing anything, if an [`EventProcessor`] has incoming events
+pub trait HasPendingEvents {
+    /// If events are waiting to be processed.
+    ///
+    /// This is cheap, and does not consume anything. It is never `false` while [`EventProcessor::process`] or
+    /// [`EventProcessor::process_one`] would process an event, so polling it before either does not miss
+    /// any wakeup, whichever of them, or the blocking APIs, consumed events before.
+    /// It may be `true` for messages that turn out to carry nothing to process, e.g., the own events of a
+    /// client, which the next call to [`EventProcessor::process`] or [`EventProcessor::process_one`] consumes.
+    fn pending_events(&self) -> bool;
+}
 /// The id of this [`EventManager`].
 /// For multi processed [`EventManager`]s,
 /// each connected client should have a unique ids.
@@ -591,6 +910,37 @@ pub trait HasEventManagerId {
     fn mgr_id(&self) -> EventManagerId;
 }
 
+/// A snapshot of the counters of a centralized event manager, see [`HasCentralizedMetrics`]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CentralizedMetrics {
+    /// If the node is the main node
+    pub is_main: bool,
+    /// Testcases this secondary node forwarded to the main node
+    pub forwarded: u64,
+    /// Forwarded testcases this main node accepted
+    pub accepted: u64,
+    /// Forwarded testcases this main node discarded
+    pub discarded: u64,
+    /// Forwarded messages this main node handled in the last `process` call
+    pub backlog: u64,
+    /// Messages this main node received from itself, which hints at a misconfiguration
+    pub self_messages: u64,
+    /// Messages to the main node that could not be sent yet
+    pub pending: usize,
+    /// The bytes in use on the current page of the centralized map
+    pub map_used: usize,
+    /// The bytes the current page of the centralized map can hold
+    pub map_capacity: usize,
+    /// How many events of each kind this node forwarded to, or received as, the main node
+    pub event_counts: BTreeMap<String, u64>,
+}
+
+/// An event manager that keeps [`CentralizedMetrics`], read by the [`crate::stages::CentralizedMetricsDump`] stage
+pub trait HasCentralizedMetrics {
+    /// The current counters of this manager
+    fn centralized_metrics(&self) -> CentralizedMetrics;
+}
+
 /// [`EventManager`] is the main communications hub.
 /// For the "normal" multi-processed mode, you may want to look into [`LlmpRestartingEventManager`]
 pub trait EventManager<E, Z>:
@@ -670,11 +1020,26 @@ where
         Ok(0)
     }
 
+    fn process_one(
+        &mut self,
+        _fuzzer: &mut Z,
+        _state: &mut Self::State,
+        _executor: &mut E,
+    ) -> Result<bool, Error> {
+        Ok(false)
+    }
+
     fn on_shutdown(&mut self) -> Result<(), Error> {
         Ok(())
     }
 }
 
+impl<S> HasPendingEvents for NopEventManager<S> {
+    fn pending_events(&self) -> bool {
+        false
+    }
+}
+
 impl<E, S, Z> EventManager<E, Z> for NopEventManager<S> where
     S: State + HasExecutions + HasLastReportTime + HasMetadata
 {
@@ -789,6 +1154,11 @@ where
     fn await_restart_safe(&mut self) {
         self.inner.await_restart_safe();
     }
+
+    #[inline]
+    fn await_restart_safe_for(&mut self, timeout: Duration) -> bool {
+        self.inner.await_restart_safe_for(timeout)
+    }
 }
 
 impl<E, EM, M, Z> EventProcessor<E, Z> for MonitorTypedEventManager<EM, M>
@@ -805,11 +1175,31 @@ where
         self.inner.process(fuzzer, state, executor)
     }
 
+    #[inline]
+    fn process_one(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut Self::State,
+        executor: &mut E,
+    ) -> Result<bool, Error> {
+        self.inner.process_one(fuzzer, state, executor)
+    }
+
     fn on_shutdown(&mut self) -> Result<(), Error> {
         self.inner.on_shutdown()
     }
 }
 
+impl<EM, M> HasPendingEvents for MonitorTypedEventManager<EM, M>
+where
+    EM: HasPendingEvents,
+{
+    #[inline]
+    fn pending_events(&self) -> bool {
+        self.inner.pending_events()
+    }
+}
+
 impl<E, EM, M, Z> EventManager<E, Z> for MonitorTypedEventManager<EM, M>
 where
     EM: EventManager<E, Z>,
@@ -852,6 +1242,16 @@ where
     fn report_progress(&mut self, state: &mut Self::State) -> Result<(), Error> {
         self.inner.report_progress(state)
     }
+
+    #[inline]
+    fn report_manager_stats(&mut self, state: &mut Self::State) -> Result<(), Error> {
+        self.inner.report_manager_stats(state)
+    }
+
+    #[inline]
+    fn on_progress_check(&mut self, state: &mut Self::State) -> Result<(), Error> {
+        self.inner.on_progress_check(state)
+    }
 }
 
 impl<EM, M> HasEventManagerId for MonitorTypedEventManager<EM, M>
@@ -864,6 +1264,141 @@ where
     }
 }
 
+#[cfg(feature = "std")]
+impl<EM, M> ReattachableEventManager for MonitorTypedEventManager<EM, M>
+where
+    EM: ReattachableEventManager,
+{
+    #[inline]
+    fn to_env(&self, env_name: &str) {
+        self.inner.to_env(env_name);
+    }
+}
+
+/// Hashes inputs for the features of the event managers that need to recognize the same input,
+/// e.g., [`crate::events::CentralizedEventManagerBuilder::dedup`].
+///
+/// `()` is the default, using [`Input::input_hash`], so two inputs only get the same hash if they are equal.
+/// Any `Fn(&I) -> u64` is an [`InputHasher`] as well, to hash inputs the way the target sees them,
+/// e.g., normalizing them first, so inputs the target can not tell apart get the same hash.
+pub trait InputHasher<I> {
+    /// The hash of `input`
+    fn hash_input(&self, input: &I) -> Result<u64, Error>;
+}
+
+impl<I> InputHasher<I> for ()
+where
+    I: Input,
+{
+    fn hash_input(&self, input: &I) -> Result<u64, Error> {
+        input.input_hash()
+    }
+}
+
+impl<F, I> InputHasher<I> for F
+where
+    F: Fn(&I) -> u64,
+{
+    fn hash_input(&self, input: &I) -> Result<u64, Error> {
+        Ok(self(input))
+    }
+}
+
+/// What an [`AdaptiveSerializer`] did so far
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdaptiveSerializerStats {
+    /// How often the observers were serialized
+    pub serialized: u64,
+    /// How often serializing the observers was skipped
+    pub skipped: u64,
+    /// The bytes of all serialized observers
+    pub bytes: u64,
+    /// The time spent serializing observers
+    pub time: Duration,
+}
+
+impl AdaptiveSerializerStats {
+    /// The average size of the serialized observers, in bytes
+    #[must_use]
+    pub fn avg_bytes(&self) -> u64 {
+        self.bytes.checked_div(self.serialized).unwrap_or_default()
+    }
+
+    /// The average time it took to serialize the observers
+    #[must_use]
+    pub fn avg_time(&self) -> Duration {
+        u32::try_from(self.serialized)
+            .ok()
+            .and_then(|serialized| self.time.checked_div(serialized))
+            .unwrap_or_default()
+    }
+}
+
+/// The outcome of [`benchmark_serialization`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerializationBenchmark {
+    /// How often the observers were serialized
+    pub iterations: usize,
+    /// The size of the serialized observers, in bytes
+    pub bytes: usize,
+    /// The time all serializations took
+    pub total: Duration,
+    /// The fastest serialization
+    pub min: Duration,
+    /// The slowest serialization
+    pub max: Duration,
+}
+
+impl SerializationBenchmark {
+    /// The average time a serialization took
+    #[must_use]
+    pub fn mean(&self) -> Duration {
+        u32::try_from(self.iterations)
+            .ok()
+            .and_then(|iterations| self.total.checked_div(iterations))
+            .unwrap_or_default()
+    }
+}
+
+/// Serialize `observers` `iterations` times, the way an [`AdaptiveSerializer`] does, and measure it.
+///
+/// Compare the [`SerializationBenchmark::mean`] to the typical execution time of the target to pick
+/// the `time_factor` of [`AdaptiveSerializer::serialize_observers_adaptive`]: the observers are
+/// only worth serializing if doing so, and deserializing them on the other side, is a lot faster than
+/// running the target again.
+pub fn benchmark_serialization<OT>(
+    observers: &OT,
+    iterations: usize,
+) -> Result<SerializationBenchmark, Error>
+where
+    OT: Serialize,
+{
+    if iterations == 0 {
+        return Err(Error::illegal_argument(
+            "Benchmarking the serialization needs at least one iteration",
+        ));
+    }
+    let mut bytes = 0;
+    let mut total = Duration::ZERO;
+    let mut min = Duration::MAX;
+    let mut max = Duration::ZERO;
+    for _ in 0..iterations {
+        let start = current_time();
+        bytes = postcard::to_allocvec(observers)?.len();
+        let elapsed = current_time().saturating_sub(start);
+        total += elapsed;
+        min = min.min(elapsed);
+        max = max.max(elapsed);
+    }
+    Ok(SerializationBenchmark {
+        iterations,
+        bytes,
+        total,
+        min,
+        max,
+    })
+}
+
 /// Collected stats to decide if observers must be serialized or not
 pub trait AdaptiveSerializer {
     /// Expose the collected observers serialization time
@@ -884,6 +1419,11 @@ pub trait AdaptiveSerializer {
     /// How many times shoukd have been serialized an observer (mut)
     fn should_serialize_cnt_mut(&mut self) -> &mut usize;
 
+    /// What this serializer did so far
+    fn serializer_stats(&self) -> &AdaptiveSerializerStats;
+    /// What this serializer did so far (mut)
+    fn serializer_stats_mut(&mut self) -> &mut AdaptiveSerializerStats;
+
     /// A [`Handle`] to the time observer to determine the `time_factor`
     fn time_ref(&self) -> &Option<Handle<TimeObserver>>;
 
@@ -924,18 +1464,54 @@ pub trait AdaptiveSerializer {
                 {
                     let start = current_time();
                     let ser = postcard::to_allocvec(observers)?;
-                    *self.serialization_time_mut() = current_time() - start;
+                    let elapsed = current_time() - start;
+                    *self.serialization_time_mut() = elapsed;
 
                     *self.serializations_cnt_mut() += 1;
+                    let stats = self.serializer_stats_mut();
+                    stats.serialized += 1;
+                    stats.bytes += ser.len() as u64;
+                    stats.time += elapsed;
                     Ok(Some(ser))
                 } else {
                     *self.serializations_cnt_mut() += 1;
+                    self.serializer_stats_mut().skipped += 1;
                     Ok(None)
                 }
             }
             None => Ok(None),
         }
     }
+
+    /// Fire the [`AdaptiveSerializer::serializer_stats`] as user stats, such as [`OBSERVERS_SERIALIZED_STAT`],
+    /// once observers were serialized or skipped
+    fn report_serializer_stats<S>(&mut self, state: &mut S) -> Result<(), Error>
+    where
+        Self: EventFirer<State = S>,
+        S: State,
+    {
+        let stats = *self.serializer_stats();
+        if stats.serialized + stats.skipped == 0 {
+            return Ok(());
+        }
+        let time = u64::try_from(stats.time.as_micros()).unwrap_or(u64::MAX);
+        for (name, value) in [
+            (OBSERVERS_SERIALIZED_STAT, stats.serialized),
+            (OBSERVERS_SKIPPED_STAT, stats.skipped),
+            (OBSERVERS_BYTES_STAT, stats.bytes),
+            (OBSERVERS_SERIALIZATION_TIME_STAT, time),
+        ] {
+            self.fire(
+                state,
+                Event::UpdateUserStats {
+                    name: Cow::Borrowed(name),
+                    value: UserStats::new(UserStatsValue::Number(value), AggregatorOps::Sum),
+                    phantom: PhantomData,
+                },
+            )?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -945,7 +1521,7 @@ mod tests {
     use tuple_list::tuple_list_type;
 
     use crate::{
-        events::{Event, EventConfig},
+        events::{benchmark_serialization, Event, EventConfig},
         executors::ExitKind,
         inputs::bytes::BytesInput,
         observers::StdMapObserver,
@@ -972,6 +1548,9 @@ mod tests {
             client_config: EventConfig::AlwaysUnique,
             time: current_time(),
             forward_id: None,
+            generation: None,
+            calibration: None,
+            forced: false,
             #[cfg(all(unix, feature = "std", feature = "multi_machine"))]
             node_id: None,
         };
@@ -988,4 +1567,52 @@ mod tests {
             _ => panic!("mistmatch"),
         };
     }
+
+    #[test]
+    fn test_benchmark_serialization() {
+        let map_ptr = &raw const MAP;
+        let obv = unsafe {
+            let len = (*map_ptr).len();
+            StdMapObserver::from_mut_ptr("test", &raw mut MAP as *mut u32, len)
+        };
+        let observers = tuple_list!(obv);
+        assert!(benchmark_serialization(&observers, 0).is_err());
+
+        let bench = benchmark_serialization(&observers, 16).unwrap();
+        assert_eq!(bench.iterations, 16);
+        assert_eq!(
+            bench.bytes,
+            postcard::to_allocvec(&observers).unwrap().len()
+        );
+        assert!(bench.min <= bench.mean() && bench.mean() <= bench.max);
+    }
+
+    #[test]
+    fn test_event_config_match() {
+        let old = EventConfig::from_name("fuzzer");
+        let new = old.with_build_hash(0x1234).with_map_len(65536);
+        assert!(new.match_with(&new));
+        // Peers that send the old variant are still trusted
+        assert!(new.match_with(&old));
+        assert!(old.match_with(&new));
+        assert!(!new.match_with(&EventConfig::from_name("other")));
+        assert!(!new.match_with(&EventConfig::AlwaysUnique));
+        assert_eq!(
+            EventConfig::AlwaysUnique.with_map_len(65536),
+            EventConfig::AlwaysUnique
+        );
+
+        // All fields present on both sides must agree
+        assert!(!new.match_with(&old.with_build_hash(0x1234).with_map_len(1024)));
+        assert!(!new.match_with(&old.with_build_hash(0x4321)));
+        assert!(new.match_with(&old.with_map_len(65536)));
+
+        // The old variants keep their encoding
+        let serialized = postcard::to_allocvec(&old).unwrap();
+        assert_eq!(serialized[0], 1);
+        assert_eq!(
+            postcard::from_bytes::<EventConfig>(&serialized).unwrap(),
+            old
+        );
+    }
 }
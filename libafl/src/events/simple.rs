@@ -25,8 +25,8 @@ use super::{CustomBufEventResult, CustomBufHandlerFn, HasCustomBufHandlers, Prog
 use crate::events::EVENTMGR_SIGHANDLER_STATE;
 use crate::{
     events::{
-        BrokerEventResult, Event, EventFirer, EventManager, EventManagerId, EventProcessor,
-        EventRestarter, HasEventManagerId,
+        handle_client_lifecycle, BrokerEventResult, Event, EventFirer, EventManager,
+        EventManagerId, EventProcessor, EventRestarter, HasEventManagerId,
     },
     inputs::UsesInput,
     monitors::Monitor,
@@ -36,6 +36,7 @@ use crate::{
 #[cfg(feature = "std")]
 use crate::{
     monitors::{ClientStats, SimplePrintingMonitor},
+    stages::ReattachableEventManager,
     state::{HasCorpus, HasSolutions},
 };
 
@@ -172,6 +173,15 @@ where
     }
 }
 
+/// Keeps no connection, so there is nothing to reattach to
+#[cfg(feature = "std")]
+impl<MT, S> ReattachableEventManager for SimpleEventManager<MT, S>
+where
+    S: UsesInput + Stoppable,
+{
+    fn to_env(&self, _env_name: &str) {}
+}
+
 #[cfg(feature = "std")]
 impl<S> SimpleEventManager<SimplePrintingMonitor, S>
 where
@@ -269,6 +279,9 @@ where
             }
             Event::CustomBuf { .. } => Ok(BrokerEventResult::Forward),
             Event::Stop => Ok(BrokerEventResult::Forward),
+            Event::ClientExiting { .. } | Event::ClientIdentity { .. } => {
+                Ok(handle_client_lifecycle(monitor, ClientId(0), event))
+            }
         }
     }
 
@@ -432,6 +445,18 @@ where
     }
 }
 
+/// The state restorer is found again through the env vars the re-executed process inherits
+#[cfg(feature = "std")]
+impl<MT, S, SP> ReattachableEventManager for SimpleRestartingEventManager<MT, S, SP>
+where
+    S: UsesInput + Stoppable,
+    SP: ShMemProvider,
+{
+    fn to_env(&self, env_name: &str) {
+        self.simple_event_mgr.to_env(env_name);
+    }
+}
+
 #[cfg(feature = "std")]
 #[allow(clippy::type_complexity, clippy::too_many_lines)]
 impl<MT, S, SP> SimpleRestartingEventManager<MT, S, SP>
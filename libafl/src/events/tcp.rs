@@ -44,15 +44,16 @@ use crate::events::EVENTMGR_SIGHANDLER_STATE;
 use crate::{
     corpus::Corpus,
     events::{
-        BrokerEventResult, Event, EventConfig, EventFirer, EventManager, EventManagerHooksTuple,
-        EventManagerId, EventProcessor, EventRestarter, HasCustomBufHandlers, HasEventManagerId,
-        ProgressReporter,
+        handle_client_lifecycle, BrokerEventResult, Event, EventConfig, EventFirer, EventManager,
+        EventManagerHooksTuple, EventManagerId, EventProcessor, EventRestarter,
+        HasCustomBufHandlers, HasEventManagerId, ProgressReporter,
     },
     executors::{Executor, HasObservers},
     fuzzer::{EvaluatorObservers, ExecutionProcessor},
     inputs::{Input, UsesInput},
     monitors::Monitor,
     observers::ObserversTuple,
+    stages::ReattachableEventManager,
     state::{HasCorpus, HasExecutions, HasImported, HasLastReportTime, State, UsesState},
     Error, HasMetadata,
 };
@@ -401,7 +402,9 @@ where
                 Ok(BrokerEventResult::Handled)
             }
             Event::CustomBuf { .. } | Event::Stop => Ok(BrokerEventResult::Forward),
-            //_ => Ok(BrokerEventResult::Forward),
+            Event::ClientExiting { .. } | Event::ClientIdentity { .. } => {
+                Ok(handle_client_lifecycle(monitor, client_id, event))
+            } //_ => Ok(BrokerEventResult::Forward),
         }
     }
 }
@@ -667,6 +670,17 @@ where
     }
 }
 
+impl<EMH, S> ReattachableEventManager for TcpEventManager<EMH, S>
+where
+    EMH: EventManagerHooksTuple<S>,
+    S: State + HasExecutions + HasMetadata + HasImported + HasCorpus,
+    S::Corpus: Corpus<Input = S::Input>,
+{
+    fn to_env(&self, env_name: &str) {
+        self.to_env(env_name);
+    }
+}
+
 impl<EMH, S> TcpEventManager<EMH, S>
 where
     EMH: EventManagerHooksTuple<S>,
@@ -1003,6 +1017,18 @@ where
     }
 }
 
+impl<EMH, S, SP> ReattachableEventManager for TcpRestartingEventManager<EMH, S, SP>
+where
+    EMH: EventManagerHooksTuple<S>,
+    S: State + HasExecutions + HasMetadata + HasImported + HasCorpus,
+    S::Corpus: Corpus<Input = S::Input>,
+    SP: ShMemProvider + 'static,
+{
+    fn to_env(&self, env_name: &str) {
+        self.tcp_mgr.to_env(env_name);
+    }
+}
+
 /// The tcp connection from the actual fuzzer to the process supervising it
 const _ENV_FUZZER_SENDER: &str = "_AFL_ENV_FUZZER_SENDER";
 const _ENV_FUZZER_RECEIVER: &str = "_AFL_ENV_FUZZER_RECEIVER";
@@ -253,6 +253,54 @@ impl TimerStruct {
         me
     }
 
+    /// The timeout of a single run
+    #[cfg(target_os = "linux")]
+    #[must_use]
+    pub fn exec_tmout(&self) -> Duration {
+        self.exec_tmout
+    }
+
+    /// The timeout of a single run
+    #[cfg(all(unix, not(target_os = "linux")))]
+    #[must_use]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn exec_tmout(&self) -> Duration {
+        // Mirrors the encoding of `TimerStruct::new`
+        Duration::from_secs(self.itimerval.it_value.tv_sec as u64)
+            + Duration::from_millis(self.itimerval.it_value.tv_usec as u64)
+    }
+
+    /// The timeout of a single run
+    #[cfg(windows)]
+    #[must_use]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn exec_tmout(&self) -> Duration {
+        Duration::from_millis(self.milli_sec as u64)
+    }
+
+    /// Change the timeout of the next runs
+    #[cfg(target_os = "linux")]
+    pub fn set_exec_tmout(&mut self, exec_tmout: Duration) {
+        let milli_sec = exec_tmout.as_millis();
+        self.itimerspec.it_value = libc::timespec {
+            tv_sec: (milli_sec / 1000) as _,
+            tv_nsec: ((milli_sec % 1000) * 1000 * 1000) as _,
+        };
+        self.exec_tmout = exec_tmout;
+    }
+
+    /// Change the timeout of the next runs
+    #[cfg(all(unix, not(target_os = "linux")))]
+    pub fn set_exec_tmout(&mut self, exec_tmout: Duration) {
+        self.itimerval = Self::new(exec_tmout).itimerval;
+    }
+
+    /// Change the timeout of the next runs
+    #[cfg(windows)]
+    pub fn set_exec_tmout(&mut self, exec_tmout: Duration) {
+        self.milli_sec = exec_tmout.as_millis() as i64;
+    }
+
     #[cfg(all(unix, not(target_os = "linux")))]
     /// Set up timer
     pub fn set_timer(&mut self) {
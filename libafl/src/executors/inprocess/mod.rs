@@ -18,6 +18,8 @@ use libafl_bolts::tuples::{tuple_list, RefIndexable};
 
 #[cfg(any(unix, feature = "std"))]
 use crate::executors::hooks::inprocess::GLOBAL_STATE;
+#[cfg(feature = "std")]
+use crate::executors::HasTimeout;
 use crate::{
     corpus::{Corpus, Testcase},
     events::{Event, EventFirer, EventRestarter},
@@ -151,6 +153,26 @@ where
     }
 }
 
+#[cfg(feature = "std")]
+impl<H, HB, HT, OT, S> HasTimeout for GenericInProcessExecutor<H, HB, HT, OT, S>
+where
+    H: FnMut(&S::Input) -> ExitKind + ?Sized,
+    HB: BorrowMut<H>,
+    HT: ExecutorHooksTuple<S>,
+    OT: ObserversTuple<S::Input, S>,
+    S: State,
+{
+    #[inline]
+    fn timeout(&self) -> Duration {
+        self.inner.hooks.0.timer.exec_tmout()
+    }
+
+    #[inline]
+    fn set_timeout(&mut self, timeout: Duration) {
+        self.inner.hooks.0.timer.set_exec_tmout(timeout);
+    }
+}
+
 impl<'a, H, OT, S> InProcessExecutor<'a, H, OT, S>
 where
     H: FnMut(&S::Input) -> ExitKind + ?Sized,
@@ -10,6 +10,8 @@ use core::{
 
 use libafl_bolts::tuples::{tuple_list, RefIndexable};
 
+#[cfg(feature = "std")]
+use crate::executors::HasTimeout;
 use crate::{
     corpus::Corpus,
     events::{EventFirer, EventRestarter},
@@ -142,6 +144,26 @@ where
     }
 }
 
+#[cfg(feature = "std")]
+impl<H, HB, HT, OT, S, ES> HasTimeout for StatefulGenericInProcessExecutor<H, HB, HT, OT, S, ES>
+where
+    H: FnMut(&mut ES, &mut S, &S::Input) -> ExitKind + ?Sized,
+    HB: BorrowMut<H>,
+    HT: ExecutorHooksTuple<S>,
+    OT: ObserversTuple<S::Input, S>,
+    S: State,
+{
+    #[inline]
+    fn timeout(&self) -> Duration {
+        self.inner.hooks.0.timer.exec_tmout()
+    }
+
+    #[inline]
+    fn set_timeout(&mut self, timeout: Duration) {
+        self.inner.hooks.0.timer.set_exec_tmout(timeout);
+    }
+}
+
 impl<'a, H, OT, S, ES> StatefulInProcessExecutor<'a, H, OT, S, ES>
 where
     H: FnMut(&mut ES, &mut S, &<S as UsesInput>::Input) -> ExitKind + ?Sized,
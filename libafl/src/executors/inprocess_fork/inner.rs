@@ -26,7 +26,7 @@ use crate::{
             inprocess_fork::{InChildProcessHooks, FORK_EXECUTOR_GLOBAL_DATA},
             ExecutorHooksTuple,
         },
-        ExitKind, HasObservers,
+        ExitKind, HasObservers, HasTimeout,
     },
     inputs::UsesInput,
     observers::ObserversTuple,
@@ -322,3 +322,36 @@ where
         RefIndexable::from(&mut self.observers)
     }
 }
+
+impl<HT, OT, S, SP, EM, Z> HasTimeout for GenericInProcessForkExecutorInner<HT, OT, S, SP, EM, Z> {
+    #[cfg(target_os = "linux")]
+    #[inline]
+    #[allow(clippy::cast_sign_loss)]
+    fn timeout(&self) -> Duration {
+        Duration::new(
+            self.itimerspec.it_value.tv_sec as u64,
+            self.itimerspec.it_value.tv_nsec as u32,
+        )
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    #[inline]
+    #[allow(clippy::cast_sign_loss)]
+    fn timeout(&self) -> Duration {
+        // Mirrors the encoding of `parse_itimerval`
+        Duration::from_secs(self.itimerval.it_value.tv_sec as u64)
+            + Duration::from_millis(self.itimerval.it_value.tv_usec as u64)
+    }
+
+    #[cfg(target_os = "linux")]
+    #[inline]
+    fn set_timeout(&mut self, timeout: Duration) {
+        self.itimerspec = parse_itimerspec(timeout);
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    #[inline]
+    fn set_timeout(&mut self, timeout: Duration) {
+        self.itimerval = parse_itimerval(timeout);
+    }
+}
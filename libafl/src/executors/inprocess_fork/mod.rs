@@ -18,6 +18,7 @@ use crate::{
     executors::{
         hooks::inprocess_fork::InProcessForkExecutorGlobalData,
         inprocess_fork::inner::GenericInProcessForkExecutorInner, Executor, ExitKind, HasObservers,
+        HasTimeout,
     },
     feedbacks::Feedback,
     fuzzer::HasObjective,
@@ -251,6 +252,27 @@ where
     }
 }
 
+impl<H, HT, OT, S, SP, EM, Z> HasTimeout
+    for GenericInProcessForkExecutor<'_, H, HT, OT, S, SP, EM, Z>
+where
+    H: FnMut(&S::Input) -> ExitKind + ?Sized,
+    HT: ExecutorHooksTuple<S>,
+    S: State,
+    OT: ObserversTuple<S::Input, S>,
+    SP: ShMemProvider,
+    EM: UsesState<State = S>,
+{
+    #[inline]
+    fn timeout(&self) -> Duration {
+        self.inner.timeout()
+    }
+
+    #[inline]
+    fn set_timeout(&mut self, timeout: Duration) {
+        self.inner.set_timeout(timeout);
+    }
+}
+
 /// signal hooks and `panic_hooks` for the child process
 pub mod child_signal_handlers {
     use alloc::boxed::Box;
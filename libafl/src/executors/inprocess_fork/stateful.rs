@@ -18,7 +18,7 @@ use crate::{
     events::{EventFirer, EventRestarter},
     executors::{
         hooks::ExecutorHooksTuple, inprocess_fork::GenericInProcessForkExecutorInner, Executor,
-        ExitKind, HasObservers,
+        ExitKind, HasObservers, HasTimeout,
     },
     feedbacks::Feedback,
     fuzzer::HasObjective,
@@ -226,3 +226,20 @@ where
         self.inner.observers_mut()
     }
 }
+
+impl<H, HT, OT, S, SP, ES, EM, Z> HasTimeout
+    for StatefulGenericInProcessForkExecutor<'_, H, HT, OT, S, SP, ES, EM, Z>
+where
+    H: FnMut(&mut ES, &S::Input) -> ExitKind + ?Sized,
+    S: UsesInput,
+{
+    #[inline]
+    fn timeout(&self) -> Duration {
+        self.inner.timeout()
+    }
+
+    #[inline]
+    fn set_timeout(&mut self, timeout: Duration) {
+        self.inner.set_timeout(timeout);
+    }
+}
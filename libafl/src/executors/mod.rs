@@ -42,6 +42,8 @@ pub mod with_observers;
 pub mod hooks;
 
 /// How an execution finished.
+///
+/// Outcomes specific to a harness are reported next to it, with [`crate::observers::set_user_exit`].
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[cfg_attr(
     any(not(feature = "serdeany_autoreg"), miri),
@@ -51,6 +51,9 @@ pub type MaxMapOneOrFilledFeedback<C, O> = MapFeedback<C, OneOrFilledIsNovel, O,
 
 /// A `Reducer` function is used to aggregate values for the novelty search
 pub trait Reducer<T> {
+    /// If only a new value larger than the history can change it, as with [`MaxReducer`]
+    const ONLY_LARGER_CHANGES: bool = false;
+
     /// Reduce two values to one value, with the current [`Reducer`].
     fn reduce(first: T, second: T) -> T;
 }
@@ -102,6 +105,8 @@ impl<T> Reducer<T> for MaxReducer
 where
     T: PartialOrd,
 {
+    const ONLY_LARGER_CHANGES: bool = true;
+
     #[inline]
     fn reduce(first: T, second: T) -> T {
         if first > second {
@@ -132,6 +137,9 @@ where
 
 /// A `IsNovel` function is used to discriminate if a reduced value is considered novel.
 pub trait IsNovel<T> {
+    /// If an unchanged value is never novel, i.e., `is_novel(old, old)` is always `false`
+    const UNCHANGED_IS_NOT_NOVEL: bool = false;
+
     /// If a new value in the [`MapFeedback`] was found,
     /// this filter can decide if the result is considered novel or not.
     fn is_novel(old: T, new: T) -> bool;
@@ -173,6 +181,8 @@ impl<T> IsNovel<T> for DifferentIsNovel
 where
     T: PartialEq + Default + Copy + 'static,
 {
+    const UNCHANGED_IS_NOT_NOVEL: bool = true;
+
     #[inline]
     fn is_novel(old: T, new: T) -> bool {
         old != new
@@ -187,6 +197,8 @@ impl<T> IsNovel<T> for NextPow2IsNovel
 where
     T: PrimInt + Default + Copy + 'static,
 {
+    const UNCHANGED_IS_NOT_NOVEL: bool = true;
+
     #[inline]
     fn is_novel(old: T, new: T) -> bool {
         // We use a trait so we build our numbers from scratch here.
@@ -208,6 +220,8 @@ impl<T> IsNovel<T> for OneOrFilledIsNovel
 where
     T: PrimInt + Default + Copy + 'static,
 {
+    const UNCHANGED_IS_NOT_NOVEL: bool = true;
+
     #[inline]
     fn is_novel(old: T, new: T) -> bool {
         (new == T::one() || new == T::max_value()) && new > old
@@ -359,6 +373,21 @@ where
     }
 }
 
+/// The [`MapFeedback`]s of a map observer, stored as named metadata named like the observer.
+///
+/// Lets [`crate::observers::map_may_be_interesting`] find the histories to compare the map to.
+#[derive(Default, Serialize, Deserialize, Clone, Debug)]
+#[allow(clippy::unsafe_derive_deserialize)] // for SerdeAny
+pub struct MapObserverFeedbacksMetadata {
+    /// The names of the [`MapFeedbackMetadata`] of the feedbacks only a larger entry is novel for,
+    /// such as [`MaxMapFeedback`]
+    pub maximizing: Vec<Cow<'static, str>>,
+    /// If another kind of feedback uses the map, so the histories can not tell if a run is interesting
+    pub other: bool,
+}
+
+libafl_bolts::impl_serdeany!(MapObserverFeedbacksMetadata);
+
 /// The most common AFL-like feedback type
 #[derive(Clone, Debug)]
 pub struct MapFeedback<C, N, O, R> {
@@ -380,14 +409,28 @@ pub struct MapFeedback<C, N, O, R> {
 
 impl<C, N, O, R, S> StateInitializer<S> for MapFeedback<C, N, O, R>
 where
+    N: IsNovel<O::Entry>,
     O: MapObserver,
     O::Entry: 'static + Default + Debug + DeserializeOwned + Serialize,
+    R: Reducer<O::Entry>,
     S: HasNamedMetadata,
 {
     fn init_state(&mut self, state: &mut S) -> Result<(), Error> {
         // Initialize `MapFeedbackMetadata` with an empty vector and add it to the state.
         // The `MapFeedbackMetadata` would be resized on-demand in `is_interesting`
         state.add_named_metadata(&self.name, MapFeedbackMetadata::<O::Entry>::default());
+
+        let feedbacks = state.named_metadata_or_insert_with(
+            self.map_ref.name(),
+            MapObserverFeedbacksMetadata::default,
+        );
+        if R::ONLY_LARGER_CHANGES && N::UNCHANGED_IS_NOT_NOVEL {
+            if !feedbacks.maximizing.contains(&self.name) {
+                feedbacks.maximizing.push(self.name.clone());
+            }
+        } else {
+            feedbacks.other = true;
+        }
         Ok(())
     }
 }
@@ -4,9 +4,7 @@
 
 // TODO: make S of Feedback<S> an associated type when specialisation + AT is stable
 
-use alloc::borrow::Cow;
-#[cfg(feature = "track_hit_feedbacks")]
-use alloc::vec::Vec;
+use alloc::{borrow::Cow, vec::Vec};
 use core::{fmt::Debug, marker::PhantomData};
 
 #[cfg(feature = "std")]
@@ -47,9 +45,10 @@ pub mod new_hash_feedback;
 #[cfg(feature = "std")]
 pub mod stdio;
 pub mod transferred;
-
+pub mod user_exit;
 #[cfg(feature = "std")]
 pub use capture_feedback::CaptureTimeoutFeedback;
+pub use user_exit::*;
 
 #[cfg(feature = "introspection")]
 use crate::state::HasClientPerfMonitor;
@@ -131,6 +130,20 @@ pub trait Feedback<EM, I, OT, S>: StateInitializer<S> + Named {
         Ok(())
     }
 
+    /// Make feedbacks with nested [`Feedback`]s, such as [`CombinedFeedback`], record the results of the
+    /// nested feedbacks in each [`Feedback::is_interesting`] run, for [`Feedback::append_verdicts`].
+    ///
+    /// Off by default, so the usual evaluation does not pay for it. Feedbacks with nested feedbacks must pass it on.
+    #[inline]
+    fn set_record_verdicts(&mut self, _record: bool) {}
+
+    /// Append the name and the result of each nested [`Feedback`] that ran in the last [`Feedback::is_interesting`]
+    /// run, while recording with [`Feedback::set_record_verdicts`]. Deeper nested feedbacks come first.
+    ///
+    /// The result of this feedback itself is not appended, only its parent knows it.
+    #[inline]
+    fn append_verdicts(&self, _verdicts: &mut Vec<(Cow<'static, str>, bool)>) {}
+
     /// Append to the testcase the generated metadata in case of a new corpus item
     ///
     /// Precondition: `testcase` must contain an input.
@@ -170,6 +183,9 @@ pub struct CombinedFeedback<A, B, FL> {
     /// Second [`Feedback`]
     pub second: B,
     name: Cow<'static, str>,
+    record_verdicts: bool,
+    /// The results of `first` and `second` in the last run, if they ran and [`Feedback::set_record_verdicts`] is on
+    verdicts: [Option<bool>; 2],
     phantom: PhantomData<FL>,
 }
 
@@ -197,6 +213,8 @@ where
             first,
             second,
             name,
+            record_verdicts: false,
+            verdicts: [None; 2],
             phantom: PhantomData,
         }
     }
@@ -229,14 +247,27 @@ where
         observers: &OT,
         exit_kind: &ExitKind,
     ) -> Result<bool, Error> {
+        let record = self.record_verdicts;
+        self.verdicts = [None; 2];
+        let [first_verdict, second_verdict] = &mut self.verdicts;
         FL::is_pair_interesting(
             |state, manager, input, observers, exit_kind| {
-                self.first
-                    .is_interesting(state, manager, input, observers, exit_kind)
+                let res = self
+                    .first
+                    .is_interesting(state, manager, input, observers, exit_kind)?;
+                if record {
+                    *first_verdict = Some(res);
+                }
+                Ok(res)
             },
             |state, manager, input, observers, exit_kind| {
-                self.second
-                    .is_interesting(state, manager, input, observers, exit_kind)
+                let res = self
+                    .second
+                    .is_interesting(state, manager, input, observers, exit_kind)?;
+                if record {
+                    *second_verdict = Some(res);
+                }
+                Ok(res)
             },
             state,
             manager,
@@ -259,14 +290,27 @@ where
     where
         S: HasClientPerfMonitor,
     {
+        let record = self.record_verdicts;
+        self.verdicts = [None; 2];
+        let [first_verdict, second_verdict] = &mut self.verdicts;
         FL::is_pair_interesting(
             |state, manager, input, observers, exit_kind| {
-                self.first
-                    .is_interesting_introspection(state, manager, input, observers, exit_kind)
+                let res = self
+                    .first
+                    .is_interesting_introspection(state, manager, input, observers, exit_kind)?;
+                if record {
+                    *first_verdict = Some(res);
+                }
+                Ok(res)
             },
             |state, manager, input, observers, exit_kind| {
-                self.second
-                    .is_interesting_introspection(state, manager, input, observers, exit_kind)
+                let res = self
+                    .second
+                    .is_interesting_introspection(state, manager, input, observers, exit_kind)?;
+                if record {
+                    *second_verdict = Some(res);
+                }
+                Ok(res)
             },
             state,
             manager,
@@ -292,6 +336,24 @@ where
         )
     }
 
+    fn set_record_verdicts(&mut self, record: bool) {
+        self.record_verdicts = record;
+        self.verdicts = [None; 2];
+        self.first.set_record_verdicts(record);
+        self.second.set_record_verdicts(record);
+    }
+
+    fn append_verdicts(&self, verdicts: &mut Vec<(Cow<'static, str>, bool)>) {
+        if let Some(res) = self.verdicts[0] {
+            self.first.append_verdicts(verdicts);
+            verdicts.push((self.first.name().clone(), res));
+        }
+        if let Some(res) = self.verdicts[1] {
+            self.second.append_verdicts(verdicts);
+            verdicts.push((self.second.name().clone(), res));
+        }
+    }
+
     #[inline]
     fn append_metadata(
         &mut self,
@@ -625,6 +687,9 @@ pub struct NotFeedback<A> {
     pub inner: A,
     /// The name
     name: Cow<'static, str>,
+    record_verdicts: bool,
+    /// The result of `inner` in the last run, if [`Feedback::set_record_verdicts`] is on
+    verdict: Option<bool>,
 }
 
 impl<A, S> StateInitializer<S> for NotFeedback<A>
@@ -649,9 +714,13 @@ where
         observers: &OT,
         exit_kind: &ExitKind,
     ) -> Result<bool, Error> {
-        Ok(!self
+        let res = self
             .inner
-            .is_interesting(state, manager, input, observers, exit_kind)?)
+            .is_interesting(state, manager, input, observers, exit_kind)?;
+        if self.record_verdicts {
+            self.verdict = Some(res);
+        }
+        Ok(!res)
     }
 
     #[cfg(feature = "track_hit_feedbacks")]
@@ -659,6 +728,19 @@ where
         Ok(!self.inner.last_result()?)
     }
 
+    fn set_record_verdicts(&mut self, record: bool) {
+        self.record_verdicts = record;
+        self.verdict = None;
+        self.inner.set_record_verdicts(record);
+    }
+
+    fn append_verdicts(&self, verdicts: &mut Vec<(Cow<'static, str>, bool)>) {
+        if let Some(res) = self.verdict {
+            self.inner.append_verdicts(verdicts);
+            verdicts.push((self.inner.name().clone(), res));
+        }
+    }
+
     #[inline]
     fn append_metadata(
         &mut self,
@@ -700,7 +782,12 @@ where
     /// Creates a new [`NotFeedback`].
     pub fn new(inner: A) -> Self {
         let name = Cow::from(format!("Not({})", inner.name()));
-        Self { inner, name }
+        Self {
+            inner,
+            name,
+            record_verdicts: false,
+            verdict: None,
+        }
     }
 }
 
@@ -13,10 +13,11 @@ use serde::{Deserialize, Serialize};
 #[cfg(feature = "track_hit_feedbacks")]
 use crate::feedbacks::premature_last_result_err;
 use crate::{
+    corpus::{CrashSignatureMetadata, Testcase},
     executors::ExitKind,
     feedbacks::{Feedback, HasObserverHandle, StateInitializer},
     observers::ObserverWithHashField,
-    Error, HasNamedMetadata,
+    Error, HasMetadata, HasNamedMetadata,
 };
 
 /// The prefix of the metadata names
@@ -168,6 +169,24 @@ where
         self.has_interesting_backtrace_hash_observation(state, observers)
     }
 
+    /// Keep the backtrace hash with the testcase, so duplicates can be told apart later,
+    /// see [`crate::stages::SolutionPruning`]
+    fn append_metadata(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        observers: &OT,
+        testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        if let Some(signature) = observers
+            .get(&self.o_ref)
+            .and_then(ObserverWithHashField::hash)
+        {
+            testcase.add_metadata(CrashSignatureMetadata { signature });
+        }
+        Ok(())
+    }
+
     #[cfg(feature = "track_hit_feedbacks")]
     fn last_result(&self) -> Result<bool, Error> {
         self.last_result.ok_or(premature_last_result_err())
@@ -0,0 +1,123 @@
+//! Feedback on the outcome a harness reports for its own run, see [`crate::observers::UserExitObserver`]
+
+use alloc::{borrow::Cow, vec::Vec};
+
+use libafl_bolts::{
+    tuples::{Handle, Handled, MatchName, MatchNameRef},
+    Named,
+};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "track_hit_feedbacks")]
+use crate::feedbacks::premature_last_result_err;
+use crate::{
+    corpus::Testcase,
+    executors::ExitKind,
+    feedbacks::{Feedback, HasObserverHandle, StateInitializer},
+    observers::UserExitObserver,
+    Error, HasMetadata,
+};
+
+/// The outcome the harness reported for the run that found a testcase
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UserExitMetadata {
+    /// The value passed to [`crate::observers::set_user_exit`]
+    pub value: u32,
+}
+
+libafl_bolts::impl_serdeany!(UserExitMetadata);
+
+/// Considers a run interesting if the harness reported one of the accepted outcomes.
+///
+/// Combine it with other feedbacks to let them depend on the outcome, for example
+/// `feedback_and_fast!(UserExitFeedback::new(&observer, &[FULLY_PROCESSED]), map_feedback)`
+/// only considers coverage novel if the harness fully processed the input.
+/// Testcases keep the outcome in their [`UserExitMetadata`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UserExitFeedback {
+    o_ref: Handle<UserExitObserver>,
+    accepted: Vec<u32>,
+    #[cfg(feature = "track_hit_feedbacks")]
+    // The previous run's result of `Self::is_interesting`
+    last_result: Option<bool>,
+}
+
+impl UserExitFeedback {
+    /// Creates a new [`UserExitFeedback`], accepting the outcomes in `accepted` reported to the `observer`.
+    #[must_use]
+    pub fn new(observer: &UserExitObserver, accepted: &[u32]) -> Self {
+        Self {
+            o_ref: observer.handle(),
+            accepted: accepted.to_vec(),
+            #[cfg(feature = "track_hit_feedbacks")]
+            last_result: None,
+        }
+    }
+
+    /// If the harness reported one of the accepted outcomes, `None` counts as not accepted
+    #[must_use]
+    pub fn accepts(&self, value: Option<u32>) -> bool {
+        value.is_some_and(|value| self.accepted.contains(&value))
+    }
+}
+
+impl<S> StateInitializer<S> for UserExitFeedback {}
+
+impl<EM, I, OT, S> Feedback<EM, I, OT, S> for UserExitFeedback
+where
+    OT: MatchName,
+{
+    #[allow(clippy::wrong_self_convention)]
+    fn is_interesting(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        _input: &I,
+        observers: &OT,
+        _exit_kind: &ExitKind,
+    ) -> Result<bool, Error> {
+        let observer = observers
+            .get(&self.o_ref)
+            .expect("A UserExitFeedback needs a UserExitObserver");
+        let res = self.accepts(observer.value());
+        #[cfg(feature = "track_hit_feedbacks")]
+        {
+            self.last_result = Some(res);
+        }
+        Ok(res)
+    }
+
+    fn append_metadata(
+        &mut self,
+        _state: &mut S,
+        _manager: &mut EM,
+        observers: &OT,
+        testcase: &mut Testcase<I>,
+    ) -> Result<(), Error> {
+        if let Some(value) = observers.get(&self.o_ref).and_then(UserExitObserver::value) {
+            testcase.add_metadata(UserExitMetadata { value });
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "track_hit_feedbacks")]
+    fn last_result(&self) -> Result<bool, Error> {
+        self.last_result.ok_or(premature_last_result_err())
+    }
+}
+
+impl Named for UserExitFeedback {
+    #[inline]
+    fn name(&self) -> &Cow<'static, str> {
+        self.o_ref.name()
+    }
+}
+
+impl HasObserverHandle for UserExitFeedback {
+    type Observer = UserExitObserver;
+
+    #[inline]
+    fn observer_handle(&self) -> &Handle<UserExitObserver> {
+        &self.o_ref
+    }
+}
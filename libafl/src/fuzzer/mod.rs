@@ -1,20 +1,21 @@
 //! The `Fuzzer` is the main struct for a fuzz campaign.
 
-use alloc::{string::ToString, vec::Vec};
-use core::{fmt::Debug, time::Duration};
+use alloc::{borrow::Cow, format, string::ToString, vec::Vec};
+use core::{fmt::Debug, marker::PhantomData, ops::ControlFlow, time::Duration};
 
-use libafl_bolts::{current_time, tuples::MatchName};
-use serde::Serialize;
+use libafl_bolts::{current_time, serdeany::SerdeAnyMap, tuples::MatchName};
+use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "introspection")]
 use crate::monitors::PerfFeature;
 use crate::{
     corpus::{Corpus, CorpusId, HasCurrentCorpusId, HasTestcase, Testcase},
-    events::{Event, EventConfig, EventFirer, EventProcessor, ProgressReporter},
+    events::{Event, EventConfig, EventFirer, EventProcessor, LogSeverity, ProgressReporter},
     executors::{Executor, ExitKind, HasObservers},
     feedbacks::Feedback,
     inputs::{Input, UsesInput},
     mark_feature_time,
+    monitors::{AggregatorOps, UserStats, UserStatsValue, CAMPAIGN_BUDGET_STAT},
     observers::ObserversTuple,
     schedulers::Scheduler,
     stages::{HasCurrentStageId, StagesTuple},
@@ -23,7 +24,7 @@ use crate::{
         HasCorpus, HasCurrentTestcase, HasExecutions, HasLastFoundTime, HasLastReportTime,
         HasSolutions, MaybeHasClientPerfMonitor, State, UsesState,
     },
-    Error, HasMetadata,
+    Error, HasMetadata, HasNamedMetadata,
 };
 
 /// Send a monitor update all 15 (or more) seconds
@@ -65,6 +66,15 @@ pub trait HasObjective {
     fn objective_mut(&mut self) -> &mut Self::Objective;
 }
 
+/// Holds an [`InputFilter`], see [`StdFuzzer::set_input_filter`]
+pub trait HasInputFilter<I, S> {
+    /// Checks if the `input` must not be executed, for stages that run inputs on their own, such as the calibration.
+    ///
+    /// If so, it was counted in the [`SkippedInputsMetadata`].
+    /// The evaluation methods of the [`Evaluator`] check this themselves, and return [`ExecuteInputResult::Skipped`] then.
+    fn skips_input(&mut self, state: &mut S, input: &I) -> bool;
+}
+
 /// Evaluates if an input is interesting using the feedback
 pub trait ExecutionProcessor<EM, I, OT, S> {
     /// Check the outcome of the execution, find if it is worth for corpus or objectives
@@ -120,6 +130,22 @@ pub trait ExecutionProcessor<EM, I, OT, S> {
         exit_kind: &ExitKind,
         send_events: bool,
     ) -> Result<(ExecuteInputResult, Option<CorpusId>), Error>;
+
+    /// Like [`ExecutionProcessor::evaluate_execution`], but only checks the feedback, not the objective.
+    ///
+    /// Use this for executions already known not to be solutions, such as `Ok` testcases forwarded by a trusted,
+    /// compatible node. Falls back to [`ExecutionProcessor::evaluate_execution`] by default.
+    fn evaluate_execution_without_objective(
+        &mut self,
+        state: &mut S,
+        manager: &mut EM,
+        input: I,
+        observers: &OT,
+        exit_kind: &ExitKind,
+        send_events: bool,
+    ) -> Result<(ExecuteInputResult, Option<CorpusId>), Error> {
+        self.evaluate_execution(state, manager, input, observers, exit_kind, send_events)
+    }
 }
 
 /// Evaluates an input modifying the state of the fuzzer
@@ -135,6 +161,41 @@ pub trait EvaluatorObservers<E, EM, I, S> {
         input: I,
         send_events: bool,
     ) -> Result<(ExecuteInputResult, Option<CorpusId>), Error>;
+
+    /// Runs each of the `inputs` like [`EvaluatorObservers::evaluate_input_with_observers`],
+    /// returning the results in the same order.
+    ///
+    /// Implementations may defer the events until the whole batch ran, see [`Evaluator::evaluate_inputs_batch`].
+    /// The progress is kept in the [`BatchProgressMetadata`].
+    fn evaluate_inputs_with_observers_batch(
+        &mut self,
+        state: &mut S,
+        executor: &mut E,
+        manager: &mut EM,
+        inputs: Vec<I>,
+        send_events: bool,
+    ) -> Result<Vec<(ExecuteInputResult, Option<CorpusId>)>, Error>
+    where
+        S: HasMetadata,
+    {
+        BatchProgressMetadata::run(state, inputs, |state, input| {
+            self.evaluate_input_with_observers(state, executor, manager, input, send_events)
+        })
+    }
+
+    /// Runs the input like [`EvaluatorObservers::evaluate_input_with_observers`], fixed up, checked
+    /// against the input filter, and with the observers, but does not evaluate it:
+    /// nothing is added to the corpora, and no events are fired.
+    ///
+    /// Use this to run an input again, e.g., to check what another node claimed about it.
+    /// Returns `None` if the input filter skipped the input.
+    fn run_input_unevaluated(
+        &mut self,
+        state: &mut S,
+        executor: &mut E,
+        manager: &mut EM,
+        input: I,
+    ) -> Result<Option<ExitKind>, Error>;
 }
 
 /// Evaluate an input modifying the state of the fuzzer
@@ -163,10 +224,37 @@ pub trait Evaluator<E, EM, I, S> {
         send_events: bool,
     ) -> Result<(ExecuteInputResult, Option<CorpusId>), Error>;
 
+    /// Runs each of the `inputs` like [`Evaluator::evaluate_input`], returning the results in the same order.
+    ///
+    /// Use this to import many inputs at once, e.g., seeds or testcases of other nodes.
+    /// [`StdFuzzer`] fires the events only after the whole batch ran, so all [`Event::NewTestcase`]s
+    /// report the final corpus size, and all solutions are reported as one [`Event::Objective`].
+    ///
+    /// The progress is kept in the [`BatchProgressMetadata`], so after a crash, the caller knows which input crashed,
+    /// and the next batch fires the events the crash held back.
+    fn evaluate_inputs_batch(
+        &mut self,
+        state: &mut S,
+        executor: &mut E,
+        manager: &mut EM,
+        inputs: Vec<I>,
+    ) -> Result<Vec<(ExecuteInputResult, Option<CorpusId>)>, Error>
+    where
+        S: HasMetadata,
+    {
+        BatchProgressMetadata::run(state, inputs, |state, input| {
+            self.evaluate_input(state, executor, manager, input)
+        })
+    }
+
     /// Runs the input and triggers observers and feedback.
     /// Adds an input, to the corpus even if it's not considered `interesting` by the `feedback`.
     /// Returns the `index` of the new testcase in the corpus.
     /// Usually, you want to use [`Evaluator::evaluate_input`], unless you know what you are doing.
+    ///
+    /// The new testcase is marked with a [`ForcedInputMetadata`], and announced with the same events as
+    /// an interesting input, if the manager sends events, so it also reaches the main node of a centralized setup.
+    /// The [`Event::NewTestcase`] is marked as `forced`, so the receivers that add it mark it as well.
     fn add_input(
         &mut self,
         state: &mut S,
@@ -180,6 +268,9 @@ pub trait Evaluator<E, EM, I, S> {
     /// Disabled testcases are only used for splicing
     /// Returns the `index` of the new testcase in the corpus.
     /// Usually, you want to use [`Evaluator::evaluate_input`], unless you know what you are doing.
+    ///
+    /// The input is marked with a [`ForcedInputMetadata`].
+    /// It is not executed, and not announced as a new testcase, since it is not meant to be scheduled.
     fn add_disabled_input(&mut self, state: &mut S, input: I) -> Result<CorpusId, Error>;
 }
 
@@ -203,6 +294,10 @@ pub trait Fuzzer<E, EM, S, ST> {
     ) -> Result<CorpusId, Error>;
 
     /// Fuzz forever (or until stopped)
+    ///
+    /// Once a stop is requested, see [`crate::state::Stoppable::request_stop`], the current
+    /// iteration finishes, the corpora are flushed, and the final stats and an
+    /// [`Event::ClientExiting`] are sent, before this returns [`Error::ShuttingDown`].
     fn fuzz_loop(
         &mut self,
         stages: &mut ST,
@@ -230,26 +325,343 @@ pub trait Fuzzer<E, EM, S, ST> {
     ) -> Result<CorpusId, Error>;
 }
 
+/// Marks a [`Testcase`] that was added with [`Evaluator::add_input`] or [`Evaluator::add_disabled_input`],
+/// regardless of what the feedback thought of it
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct ForcedInputMetadata;
+
+libafl_bolts::impl_serdeany!(ForcedInputMetadata);
+
+/// The progress of the batch being evaluated, see [`Evaluator::evaluate_inputs_batch`].
+///
+/// It is kept in the state, so if an input crashes the fuzzer, the restarted process knows
+/// how many inputs of the batch were evaluated before, and still fires the events held back for them.
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct BatchProgressMetadata {
+    /// The number of inputs of the batch that were evaluated
+    pub evaluated: usize,
+    /// The new testcases not yet announced, with their exit kind and serialized observers
+    pub new_testcases: Vec<(CorpusId, ExitKind, Option<Vec<u8>>)>,
+    /// Whether the batch found a solution not yet announced
+    pub found_solution: bool,
+}
+
+libafl_bolts::impl_serdeany!(BatchProgressMetadata);
+
+impl BatchProgressMetadata {
+    /// Start a new batch, evaluate each of the `inputs` with `evaluate`, and count them
+    fn run<I, S, F>(
+        state: &mut S,
+        inputs: Vec<I>,
+        mut evaluate: F,
+    ) -> Result<Vec<(ExecuteInputResult, Option<CorpusId>)>, Error>
+    where
+        S: HasMetadata,
+        F: FnMut(&mut S, I) -> Result<(ExecuteInputResult, Option<CorpusId>), Error>,
+    {
+        state.add_metadata(Self::default());
+        let mut results = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            results.push(evaluate(state, input)?);
+            state.metadata_mut::<Self>()?.evaluated += 1;
+        }
+        Ok(results)
+    }
+
+    /// Fire the events held back for the batch, if any, e.g., by a batch a crash interrupted
+    fn fire_held_back<EM, S>(state: &mut S, manager: &mut EM) -> Result<(), Error>
+    where
+        EM: EventFirer<State = S>,
+        S: HasCorpus + HasSolutions + HasMetadata + UsesInput<Input = <S::Corpus as Corpus>::Input>,
+        <S::Corpus as Corpus>::Input: Input,
+    {
+        let Some(progress) = state.metadata_map_mut().get_mut::<Self>() else {
+            return Ok(());
+        };
+        let new_testcases = core::mem::take(&mut progress.new_testcases);
+        let found_solution = core::mem::take(&mut progress.found_solution);
+
+        let corpus_size = state.corpus().count();
+        for (id, exit_kind, observers_buf) in new_testcases {
+            let input = state.corpus().cloned_input_for_id(id)?;
+            manager.fire(
+                state,
+                Event::NewTestcase {
+                    input,
+                    observers_buf,
+                    exit_kind,
+                    corpus_size,
+                    client_config: manager.configuration(),
+                    time: current_time(),
+                    forward_id: None,
+                    generation: None,
+                    calibration: None,
+                    forced: false,
+                    #[cfg(all(unix, feature = "std", feature = "multi_machine"))]
+                    node_id: None,
+                },
+            )?;
+        }
+        if found_solution {
+            manager.fire(
+                state,
+                Event::Objective {
+                    objective_size: state.solutions().count(),
+                    time: current_time(),
+                },
+            )?;
+        }
+        Ok(())
+    }
+}
+
 /// The corpus this input should be added to
 #[derive(Debug, PartialEq, Eq)]
 pub enum ExecuteInputResult {
     /// No special input
     None,
+    /// This input was not executed, the input filter skipped it, see [`HasInputFilter::skips_input`]
+    Skipped,
     /// This input should be stored in the corpus
     Corpus,
     /// This input leads to a solution
     Solution,
 }
 
+/// Why an input was kept, or not, see [`StdFuzzer::evaluate_input_detailed`]
+#[derive(Debug)]
+pub struct EvaluationVerdict {
+    /// The corpus the input was added to
+    pub result: ExecuteInputResult,
+    /// The id of the new [`Testcase`], if the input was added to the corpus
+    pub corpus_id: Option<CorpusId>,
+    /// The name and result of each feedback that ran, see [`Feedback::append_verdicts`],
+    /// followed by the whole feedback. Empty for solutions, the feedback does not run for them.
+    pub feedbacks: Vec<(Cow<'static, str>, bool)>,
+    /// The name and result of each objective that ran, followed by the whole objective
+    pub objectives: Vec<(Cow<'static, str>, bool)>,
+    /// How the target exited
+    pub exit_kind: ExitKind,
+    /// How long the execution took, including the observers
+    pub exec_time: Duration,
+    /// If the observers were serialized and sent along with the new testcase
+    pub observers_serialized: bool,
+}
+
+/// How an input behaved when it was re-run, see [`StdFuzzer::reproduce`]
+#[derive(Debug)]
+pub struct ReproReport {
+    /// The position of the input among the reproduced inputs
+    pub index: usize,
+    /// How the target exited
+    pub exit_kind: ExitKind,
+    /// If the objective considered the input interesting, i.e., if it still crashes
+    pub is_objective: bool,
+    /// How long the execution took, including the observers
+    pub exec_time: Duration,
+    /// The metadata the objective attached, such as sanitizer reports or stack hashes, if it was interesting
+    pub metadata: SerdeAnyMap,
+}
+
+/// What happened in one iteration of [`StdFuzzer::fuzz_loop_with`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IterationSummary {
+    /// The number of this iteration, counting from `0` for each call
+    pub iteration: u64,
+    /// The corpus entry that was fuzzed
+    pub corpus_id: CorpusId,
+    /// How much the corpus grew, negative if entries were removed
+    pub corpus_delta: isize,
+    /// How many objectives were found, negative if solutions were removed
+    pub objectives_delta: isize,
+    /// How often the target was executed
+    pub executions: u64,
+}
+
+/// A budget of a fuzzing campaign that ran out, see [`CampaignBudget`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BudgetKind {
+    /// The target was executed often enough
+    Executions,
+    /// The campaign ran long enough
+    Duration,
+    /// Enough objectives were found
+    Objectives,
+}
+
+impl BudgetKind {
+    /// The name of this budget, as reported in the [`CAMPAIGN_BUDGET_STAT`]
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Executions => "executions",
+            Self::Duration => "duration",
+            Self::Objectives => "objectives",
+        }
+    }
+}
+
+/// When the [`StdFuzzer`] first ran on a state with a duration budget, see [`CampaignBudget`]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CampaignStartMetadata {
+    start_time: Duration,
+}
+
+libafl_bolts::impl_serdeany!(CampaignStartMetadata);
+
+impl CampaignStartMetadata {
+    /// Create a new [`CampaignStartMetadata`] for a campaign started at `start_time`
+    #[must_use]
+    pub fn new(start_time: Duration) -> Self {
+        Self { start_time }
+    }
+
+    /// The time the campaign started at
+    #[must_use]
+    pub fn start_time(&self) -> Duration {
+        self.start_time
+    }
+}
+
+/// The number of inputs the input filter rejected in this state, see [`StdFuzzer::set_input_filter`].
+///
+/// Reported in the [`crate::monitors::SKIPPED_INPUTS_STAT`] along with the other progress, see [`crate::events::ProgressReporter`].
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SkippedInputsMetadata {
+    count: u64,
+}
+
+libafl_bolts::impl_serdeany!(SkippedInputsMetadata);
+
+impl SkippedInputsMetadata {
+    /// The number of skipped inputs
+    #[must_use]
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+/// Limits after which the [`StdFuzzer`] stops the campaign gracefully, as if a stop was requested.
+///
+/// The budgets are measured from the state, that is, from its executions, solutions, and [`CampaignStartMetadata`].
+/// Restarts restore them, so a crashing target cannot extend the campaign.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CampaignBudget {
+    executions: Option<u64>,
+    duration: Option<Duration>,
+    objectives: Option<u64>,
+}
+
+impl CampaignBudget {
+    /// The first budget that ran out for the given state, if any
+    pub fn exhausted<S>(&self, state: &S) -> Option<BudgetKind>
+    where
+        S: HasExecutions + HasSolutions + HasMetadata,
+    {
+        if self
+            .executions
+            .is_some_and(|max| *state.executions() >= max)
+        {
+            Some(BudgetKind::Executions)
+        } else if self
+            .objectives
+            .is_some_and(|max| state.solutions().count() as u64 >= max)
+        {
+            Some(BudgetKind::Objectives)
+        } else if self.duration.is_some_and(|max| {
+            state
+                .metadata::<CampaignStartMetadata>()
+                .is_ok_and(|meta| current_time().saturating_sub(meta.start_time) >= max)
+        }) {
+            Some(BudgetKind::Duration)
+        } else {
+            None
+        }
+    }
+}
+
+/// Post-processes an input right before the [`StdFuzzer`] executes it, see [`StdFuzzer::input_fixup`].
+///
+/// Use it to get mutated inputs past checks the target does before it reaches the interesting code,
+/// e.g., to recompute a checksum, or to patch a length field.
+/// The fixed input is the one that is executed, reported to other nodes, and stored in the corpora.
+///
+/// A fixup is a pure function of the input, without state of its own, so restarts don't affect it.
+/// Inputs from other nodes are fixed again before they are re-executed, so a fixup should leave
+/// an already fixed input unchanged.
+pub trait InputFixup<I> {
+    /// Fix the `input` in place, or replace it
+    fn fixup(&self, input: &mut I);
+}
+
+impl<I> InputFixup<I> for () {
+    fn fixup(&self, _input: &mut I) {}
+}
+
+impl<F, I> InputFixup<I> for F
+where
+    F: Fn(&mut I),
+{
+    fn fixup(&self, input: &mut I) {
+        self(input);
+    }
+}
+
+/// Decides whether an input may be executed at all, see [`StdFuzzer::set_input_filter`].
+///
+/// Use it to deny-list inputs that are known to waste the time of the target, e.g., hang it just below the timeout.
+pub trait InputFilter<I, S> {
+    /// Returns `true` if the `input` may be executed, `false` if it must be skipped
+    fn allows(&mut self, state: &S, input: &I) -> bool;
+}
+
+impl<I, S> InputFilter<I, S> for () {
+    fn allows(&mut self, _state: &S, _input: &I) -> bool {
+        true
+    }
+}
+
+impl<F, I, S> InputFilter<I, S> for F
+where
+    F: FnMut(&S, &I) -> bool,
+{
+    fn allows(&mut self, state: &S, input: &I) -> bool {
+        self(state, input)
+    }
+}
+
 /// Your default fuzzer instance, for everyday use.
+///
+/// `FX` is the [`InputFixup`] applied to every input before it is executed, if any.
+/// `IF` is the [`InputFilter`] consulted before an input is executed, if any.
 #[derive(Debug)]
-pub struct StdFuzzer<CS, F, OF> {
+pub struct StdFuzzer<CS, F, OF, FX = (), IF = ()> {
     scheduler: CS,
     feedback: F,
     objective: OF,
+    budget: CampaignBudget,
+    fixup: FX,
+    filter: IF,
 }
 
-impl<CS, F, OF, S> HasScheduler<<S::Corpus as Corpus>::Input, S> for StdFuzzer<CS, F, OF>
+impl<CS, F, FX, IF, OF, S> HasScheduler<<S::Corpus as Corpus>::Input, S>
+    for StdFuzzer<CS, F, OF, FX, IF>
 where
     S: HasCorpus,
     CS: Scheduler<<S::Corpus as Corpus>::Input, S>,
@@ -265,7 +677,7 @@ where
     }
 }
 
-impl<CS, F, OF> HasFeedback for StdFuzzer<CS, F, OF> {
+impl<CS, F, FX, IF, OF> HasFeedback for StdFuzzer<CS, F, OF, FX, IF> {
     type Feedback = F;
 
     fn feedback(&self) -> &Self::Feedback {
@@ -277,7 +689,7 @@ impl<CS, F, OF> HasFeedback for StdFuzzer<CS, F, OF> {
     }
 }
 
-impl<CS, F, OF> HasObjective for StdFuzzer<CS, F, OF> {
+impl<CS, F, FX, IF, OF> HasObjective for StdFuzzer<CS, F, OF, FX, IF> {
     type Objective = OF;
 
     fn objective(&self) -> &OF {
@@ -289,8 +701,8 @@ impl<CS, F, OF> HasObjective for StdFuzzer<CS, F, OF> {
     }
 }
 
-impl<CS, EM, F, OF, OT, S> ExecutionProcessor<EM, <S::Corpus as Corpus>::Input, OT, S>
-    for StdFuzzer<CS, F, OF>
+impl<CS, EM, F, FX, IF, OF, OT, S> ExecutionProcessor<EM, <S::Corpus as Corpus>::Input, OT, S>
+    for StdFuzzer<CS, F, OF, FX, IF>
 where
     CS: Scheduler<<S::Corpus as Corpus>::Input, S>,
     EM: EventFirer<State = S>,
@@ -362,6 +774,37 @@ where
         Ok((exec_res, corpus_id))
     }
 
+    fn evaluate_execution_without_objective(
+        &mut self,
+        state: &mut S,
+        manager: &mut EM,
+        input: <S::Corpus as Corpus>::Input,
+        observers: &OT,
+        exit_kind: &ExitKind,
+        send_events: bool,
+    ) -> Result<(ExecuteInputResult, Option<CorpusId>), Error> {
+        #[cfg(not(feature = "introspection"))]
+        let corpus_worthy = self
+            .feedback_mut()
+            .is_interesting(state, manager, &input, observers, exit_kind)?;
+
+        #[cfg(feature = "introspection")]
+        let corpus_worthy = self
+            .feedback_mut()
+            .is_interesting_introspection(state, manager, &input, observers, exit_kind)?;
+
+        let exec_res = if corpus_worthy {
+            ExecuteInputResult::Corpus
+        } else {
+            ExecuteInputResult::None
+        };
+        let corpus_id = self.process_execution(state, manager, &input, &exec_res, observers)?;
+        if send_events {
+            self.serialize_and_dispatch(state, manager, input, &exec_res, observers, exit_kind)?;
+        }
+        Ok((exec_res, corpus_id))
+    }
+
     fn serialize_and_dispatch(
         &mut self,
         state: &mut S,
@@ -372,22 +815,7 @@ where
         exit_kind: &ExitKind,
     ) -> Result<(), Error> {
         // Now send off the event
-        let observers_buf = match exec_res {
-            ExecuteInputResult::Corpus => {
-                if manager.should_send() {
-                    // TODO set None for fast targets
-                    if manager.configuration() == EventConfig::AlwaysUnique {
-                        None
-                    } else {
-                        manager.serialize_observers::<OT>(observers)?
-                    }
-                } else {
-                    None
-                }
-            }
-            _ => None,
-        };
-
+        let observers_buf = Self::observers_buf(manager, exec_res, observers)?;
         self.dispatch_event(state, manager, input, exec_res, observers_buf, exit_kind)?;
         Ok(())
     }
@@ -415,6 +843,9 @@ where
                             client_config: manager.configuration(),
                             time: current_time(),
                             forward_id: None,
+                            generation: None,
+                            calibration: None,
+                            forced: false,
                             #[cfg(all(unix, feature = "std", feature = "multi_machine"))]
                             node_id: None,
                         },
@@ -432,7 +863,7 @@ where
                     )?;
                 }
             }
-            ExecuteInputResult::None => (),
+            ExecuteInputResult::None | ExecuteInputResult::Skipped => (),
         }
         Ok(())
     }
@@ -447,7 +878,7 @@ where
         observers: &OT,
     ) -> Result<Option<CorpusId>, Error> {
         match exec_res {
-            ExecuteInputResult::None => {
+            ExecuteInputResult::None | ExecuteInputResult::Skipped => {
                 self.feedback_mut().discard_metadata(state, input)?;
                 self.objective_mut().discard_metadata(state, input)?;
                 Ok(None)
@@ -491,10 +922,12 @@ where
     }
 }
 
-impl<CS, E, EM, F, OF, S> EvaluatorObservers<E, EM, <S::Corpus as Corpus>::Input, S>
-    for StdFuzzer<CS, F, OF>
+impl<CS, E, EM, F, FX, IF, OF, S> EvaluatorObservers<E, EM, <S::Corpus as Corpus>::Input, S>
+    for StdFuzzer<CS, F, OF, FX, IF>
 where
     CS: Scheduler<<S::Corpus as Corpus>::Input, S>,
+    FX: InputFixup<<S::Corpus as Corpus>::Input>,
+    IF: InputFilter<<S::Corpus as Corpus>::Input, S>,
     E: HasObservers + Executor<EM, Self, State = S>,
     E::Observers: MatchName + ObserversTuple<<S::Corpus as Corpus>::Input, S> + Serialize,
     EM: EventFirer<State = S>,
@@ -505,7 +938,8 @@ where
         + MaybeHasClientPerfMonitor
         + HasCurrentTestcase
         + UsesInput<Input = <S::Corpus as Corpus>::Input>
-        + HasExecutions,
+        + HasExecutions
+        + HasMetadata,
     <S::Corpus as Corpus>::Input: Input,
     S::Solutions: Corpus<Input = <S::Corpus as Corpus>::Input>,
 {
@@ -516,21 +950,91 @@ where
         state: &mut S,
         executor: &mut E,
         manager: &mut EM,
-        input: <S::Corpus as Corpus>::Input,
+        mut input: <S::Corpus as Corpus>::Input,
         send_events: bool,
     ) -> Result<(ExecuteInputResult, Option<CorpusId>), Error> {
-        let exit_kind = self.execute_input(state, executor, manager, &input)?;
+        let Some(exit_kind) = self.fixup_and_run(state, executor, manager, &mut input)? else {
+            return Ok((ExecuteInputResult::Skipped, None));
+        };
         let observers = executor.observers();
 
         self.scheduler.on_evaluation(state, &input, &*observers)?;
 
         self.evaluate_execution(state, manager, input, &*observers, &exit_kind, send_events)
     }
+
+    fn run_input_unevaluated(
+        &mut self,
+        state: &mut S,
+        executor: &mut E,
+        manager: &mut EM,
+        mut input: <S::Corpus as Corpus>::Input,
+    ) -> Result<Option<ExitKind>, Error> {
+        self.fixup_and_run(state, executor, manager, &mut input)
+    }
+
+    /// Process the inputs one after the other, firing the events once all of them ran
+    fn evaluate_inputs_with_observers_batch(
+        &mut self,
+        state: &mut S,
+        executor: &mut E,
+        manager: &mut EM,
+        inputs: Vec<<S::Corpus as Corpus>::Input>,
+        send_events: bool,
+    ) -> Result<Vec<(ExecuteInputResult, Option<CorpusId>)>, Error>
+    where
+        S: HasMetadata,
+    {
+        // Left over by a batch a crash interrupted
+        BatchProgressMetadata::fire_held_back(state, manager)?;
+
+        let send_events = send_events && manager.should_send();
+        let results = BatchProgressMetadata::run(state, inputs, |state, mut input| {
+            let Some(exit_kind) = self.fixup_and_run(state, executor, manager, &mut input)? else {
+                return Ok((ExecuteInputResult::Skipped, None));
+            };
+            let observers = executor.observers();
+
+            self.scheduler.on_evaluation(state, &input, &*observers)?;
+
+            let (exec_res, corpus_id) =
+                self.evaluate_execution(state, manager, input, &*observers, &exit_kind, false)?;
+            if send_events {
+                match (&exec_res, corpus_id) {
+                    (ExecuteInputResult::Corpus, Some(id)) => {
+                        let observers_buf = if manager.configuration() == EventConfig::AlwaysUnique
+                        {
+                            None
+                        } else {
+                            manager.serialize_observers(&*observers)?
+                        };
+                        state
+                            .metadata_mut::<BatchProgressMetadata>()?
+                            .new_testcases
+                            .push((id, exit_kind, observers_buf));
+                    }
+                    (ExecuteInputResult::Solution, _) => {
+                        state
+                            .metadata_mut::<BatchProgressMetadata>()?
+                            .found_solution = true;
+                    }
+                    _ => (),
+                }
+            }
+            Ok((exec_res, corpus_id))
+        })?;
+
+        BatchProgressMetadata::fire_held_back(state, manager)?;
+        Ok(results)
+    }
 }
 
-impl<CS, E, EM, F, OF, S> Evaluator<E, EM, <S::Corpus as Corpus>::Input, S> for StdFuzzer<CS, F, OF>
+impl<CS, E, EM, F, FX, IF, OF, S> Evaluator<E, EM, <S::Corpus as Corpus>::Input, S>
+    for StdFuzzer<CS, F, OF, FX, IF>
 where
     CS: Scheduler<<S::Corpus as Corpus>::Input, S>,
+    FX: InputFixup<<S::Corpus as Corpus>::Input>,
+    IF: InputFilter<<S::Corpus as Corpus>::Input, S>,
     E: HasObservers + Executor<EM, Self, State = S>,
     E::Observers: MatchName + ObserversTuple<<S::Corpus as Corpus>::Input, S> + Serialize,
     EM: EventFirer<State = S>,
@@ -542,6 +1046,7 @@ where
         + HasCurrentTestcase
         + HasLastFoundTime
         + HasExecutions
+        + HasMetadata
         + UsesInput<Input = <S::Corpus as Corpus>::Input>,
     <S::Corpus as Corpus>::Input: Input,
     S::Solutions: Corpus<Input = <S::Corpus as Corpus>::Input>,
@@ -558,28 +1063,45 @@ where
     ) -> Result<(ExecuteInputResult, Option<CorpusId>), Error> {
         self.evaluate_input_with_observers(state, executor, manager, input, send_events)
     }
+
+    #[inline]
+    fn evaluate_inputs_batch(
+        &mut self,
+        state: &mut S,
+        executor: &mut E,
+        manager: &mut EM,
+        inputs: Vec<<S::Corpus as Corpus>::Input>,
+    ) -> Result<Vec<(ExecuteInputResult, Option<CorpusId>)>, Error>
+    where
+        S: HasMetadata,
+    {
+        self.evaluate_inputs_with_observers_batch(state, executor, manager, inputs, true)
+    }
+
     fn add_disabled_input(
         &mut self,
         state: &mut S,
-        input: <S::Corpus as Corpus>::Input,
+        mut input: <S::Corpus as Corpus>::Input,
     ) -> Result<CorpusId, Error> {
-        let mut testcase = Testcase::from(input.clone());
-        testcase.set_disabled(true);
-        // Add the disabled input to the main corpus
-        let id = state.corpus_mut().add_disabled(testcase)?;
-        Ok(id)
+        self.fixup.fixup(&mut input);
+        Self::add_fixed_disabled_input(state, input)
     }
-    /// Adds an input, even if it's not considered `interesting` by any of the executors
+
+    /// Adds an input, even if it's not considered `interesting` by any of the executors.
+    ///
+    /// An input the input filter rejects is not executed, but added as a disabled testcase instead.
     fn add_input(
         &mut self,
         state: &mut S,
         executor: &mut E,
         manager: &mut EM,
-        input: <S::Corpus as Corpus>::Input,
+        mut input: <S::Corpus as Corpus>::Input,
     ) -> Result<CorpusId, Error> {
         *state.last_found_time_mut() = current_time();
 
-        let exit_kind = self.execute_input(state, executor, manager, &input)?;
+        let Some(exit_kind) = self.fixup_and_run(state, executor, manager, &mut input)? else {
+            return Self::add_fixed_disabled_input(state, input);
+        };
         let observers = executor.observers();
         // Always consider this to be "interesting"
         let mut testcase = Testcase::from(input.clone());
@@ -607,12 +1129,13 @@ where
                 .append_metadata(state, manager, &*observers, &mut testcase)?;
             let id = state.solutions_mut().add(testcase)?;
 
-            manager.fire(
+            self.dispatch_event(
                 state,
-                Event::Objective {
-                    objective_size: state.solutions().count(),
-                    time: current_time(),
-                },
+                manager,
+                input,
+                &ExecuteInputResult::Solution,
+                None,
+                &exit_kind,
             )?;
             return Ok(id);
         }
@@ -642,33 +1165,37 @@ where
         // Add the input to the main corpus
         self.feedback_mut()
             .append_metadata(state, manager, &*observers, &mut testcase)?;
+        testcase.add_metadata(ForcedInputMetadata);
         let id = state.corpus_mut().add(testcase)?;
         self.scheduler_mut().on_add(state, id)?;
 
-        let observers_buf = if manager.configuration() == EventConfig::AlwaysUnique {
-            None
-        } else {
-            manager.serialize_observers::<E::Observers>(&*observers)?
-        };
-        manager.fire(
-            state,
-            Event::NewTestcase {
-                input,
-                observers_buf,
-                exit_kind,
-                corpus_size: state.corpus().count(),
-                client_config: manager.configuration(),
-                time: current_time(),
-                forward_id: None,
-                #[cfg(all(unix, feature = "std", feature = "multi_machine"))]
-                node_id: None,
-            },
-        )?;
+        // Announce it like any other new testcase
+        if manager.should_send() {
+            let observers_buf =
+                Self::observers_buf(manager, &ExecuteInputResult::Corpus, &*observers)?;
+            manager.fire(
+                state,
+                Event::NewTestcase {
+                    input,
+                    observers_buf,
+                    exit_kind,
+                    corpus_size: state.corpus().count(),
+                    client_config: manager.configuration(),
+                    time: current_time(),
+                    forward_id: None,
+                    generation: None,
+                    calibration: None,
+                    forced: true,
+                    #[cfg(all(unix, feature = "std", feature = "multi_machine"))]
+                    node_id: None,
+                },
+            )?;
+        }
         Ok(id)
     }
 }
 
-impl<CS, E, EM, F, OF, S, ST> Fuzzer<E, EM, S, ST> for StdFuzzer<CS, F, OF>
+impl<CS, E, EM, F, FX, IF, OF, S, ST> Fuzzer<E, EM, S, ST> for StdFuzzer<CS, F, OF, FX, IF>
 where
     CS: Scheduler<S::Input, S>,
     E: UsesState<State = S>,
@@ -676,6 +1203,7 @@ where
     S: HasExecutions
         + HasMetadata
         + HasCorpus
+        + HasSolutions
         + HasLastReportTime
         + HasTestcase
         + HasCurrentCorpusId
@@ -694,6 +1222,31 @@ where
         #[cfg(feature = "introspection")]
         state.introspection_monitor_mut().start_timer();
 
+        // Once the budget ran out, stop gracefully, skipping all stages
+        if self.budget.duration.is_some() {
+            state.metadata_or_insert_with(|| CampaignStartMetadata::new(current_time()));
+        }
+        if !state.stop_requested() {
+            if let Some(kind) = self.budget.exhausted(state) {
+                log::info!(
+                    "The {} budget of this campaign ran out, stopping",
+                    kind.name()
+                );
+                manager.fire(
+                    state,
+                    Event::UpdateUserStats {
+                        name: Cow::Borrowed(CAMPAIGN_BUDGET_STAT),
+                        value: UserStats::new(
+                            UserStatsValue::String(Cow::Borrowed(kind.name())),
+                            AggregatorOps::None,
+                        ),
+                        phantom: PhantomData,
+                    },
+                )?;
+                state.request_stop();
+            }
+        }
+
         // Get the next index from the scheduler
         let id = if let Some(id) = state.current_corpus_id()? {
             id // we are resuming
@@ -711,8 +1264,13 @@ where
         #[cfg(feature = "introspection")]
         state.introspection_monitor_mut().reset_stage_index();
 
-        // Execute all stages
-        stages.perform_all(self, executor, state, manager)?;
+        // Execute all stages, they return early once a stop is requested
+        if !state.stop_requested() {
+            match stages.perform_all(self, executor, state, manager) {
+                Err(Error::ShuttingDown) if state.stop_requested() => {}
+                res => res?,
+            }
+        }
 
         // Init timer for manager
         #[cfg(feature = "introspection")]
@@ -737,6 +1295,8 @@ where
 
         if state.stop_requested() {
             state.discard_stop_request();
+
+            Self::flush_and_report(state, manager)?;
             manager.on_shutdown()?;
             return Err(Error::shutting_down());
         }
@@ -792,6 +1352,332 @@ where
     }
 }
 
+impl<CS, F, FX, IF, OF> StdFuzzer<CS, F, OF, FX, IF> {
+    /// Like [`Fuzzer::fuzz_loop`], but calls `callback` after the stages and the events of each iteration.
+    ///
+    /// Once `callback` returns [`ControlFlow::Break`], the corpora are flushed, and the final stats and an
+    /// [`Event::ClientExiting`] are sent, just like for a requested stop, before this returns `Ok(())`.
+    /// A requested stop still returns [`Error::ShuttingDown`].
+    pub fn fuzz_loop_with<E, EM, S, ST, CB>(
+        &mut self,
+        stages: &mut ST,
+        executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+        mut callback: CB,
+    ) -> Result<(), Error>
+    where
+        Self: Fuzzer<E, EM, S, ST>,
+        EM: ProgressReporter<State = S> + EventProcessor<E, Self>,
+        S: HasExecutions + HasMetadata + HasCorpus + HasSolutions + HasLastReportTime + State,
+        CB: FnMut(&mut S, &IterationSummary) -> ControlFlow<()>,
+    {
+        let monitor_timeout = STATS_TIMEOUT_DEFAULT;
+        let mut iteration = 0;
+        loop {
+            manager.maybe_report_progress(state, monitor_timeout)?;
+
+            let (_, flow) =
+                self.fuzz_one_with(stages, executor, state, manager, iteration, &mut callback)?;
+            if flow.is_break() {
+                Self::flush_and_report(state, manager)?;
+                return manager.on_shutdown();
+            }
+            iteration += 1;
+        }
+    }
+
+    /// Like [`Fuzzer::fuzz_loop_for`], but calls `callback` after the stages and the events of each iteration.
+    ///
+    /// Once `callback` returns [`ControlFlow::Break`], the loop exits early, see [`StdFuzzer::fuzz_loop_with`].
+    /// Returns the index of the last fuzzed corpus item.
+    pub fn fuzz_loop_for_with<E, EM, S, ST, CB>(
+        &mut self,
+        stages: &mut ST,
+        executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+        iters: u64,
+        mut callback: CB,
+    ) -> Result<CorpusId, Error>
+    where
+        Self: Fuzzer<E, EM, S, ST>,
+        EM: ProgressReporter<State = S> + EventProcessor<E, Self>,
+        S: HasExecutions + HasMetadata + HasCorpus + HasSolutions + HasLastReportTime + State,
+        CB: FnMut(&mut S, &IterationSummary) -> ControlFlow<()>,
+    {
+        if iters == 0 {
+            return Err(Error::illegal_argument(
+                "Cannot fuzz for 0 iterations!".to_string(),
+            ));
+        }
+
+        let mut ret = None;
+        let monitor_timeout = STATS_TIMEOUT_DEFAULT;
+
+        for iteration in 0..iters {
+            manager.maybe_report_progress(state, monitor_timeout)?;
+
+            let (id, flow) =
+                self.fuzz_one_with(stages, executor, state, manager, iteration, &mut callback)?;
+            ret = Some(id);
+            if flow.is_break() {
+                Self::flush_and_report(state, manager)?;
+                manager.on_shutdown()?;
+                return Ok(id);
+            }
+        }
+
+        manager.report_progress(state)?;
+
+        Ok(ret.unwrap())
+    }
+
+    /// Fuzz for a single iteration, then summarize it for `callback`
+    fn fuzz_one_with<E, EM, S, ST, CB>(
+        &mut self,
+        stages: &mut ST,
+        executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+        iteration: u64,
+        callback: &mut CB,
+    ) -> Result<(CorpusId, ControlFlow<()>), Error>
+    where
+        Self: Fuzzer<E, EM, S, ST>,
+        S: HasExecutions + HasCorpus + HasSolutions,
+        CB: FnMut(&mut S, &IterationSummary) -> ControlFlow<()>,
+    {
+        let corpus_size = state.corpus().count();
+        let objectives_size = state.solutions().count();
+        let executions = *state.executions();
+
+        let corpus_id = self.fuzz_one(stages, executor, state, manager)?;
+
+        #[allow(clippy::cast_possible_wrap)]
+        let summary = IterationSummary {
+            iteration,
+            corpus_id,
+            corpus_delta: state.corpus().count() as isize - corpus_size as isize,
+            objectives_delta: state.solutions().count() as isize - objectives_size as isize,
+            executions: state.executions().saturating_sub(executions),
+        };
+        Ok((corpus_id, callback(state, &summary)))
+    }
+
+    /// Like [`Evaluator::evaluate_input`], but also reports why the input was kept, or not: the result of
+    /// each nested feedback and objective, the exit kind, the execution time, and if the observers were sent.
+    ///
+    /// The feedbacks only record their nested results during this call, [`Evaluator::evaluate_input`]
+    /// keeps its cost.
+    pub fn evaluate_input_detailed<E, EM, S>(
+        &mut self,
+        state: &mut S,
+        executor: &mut E,
+        manager: &mut EM,
+        input: <S::Corpus as Corpus>::Input,
+    ) -> Result<EvaluationVerdict, Error>
+    where
+        CS: Scheduler<<S::Corpus as Corpus>::Input, S>,
+        FX: InputFixup<<S::Corpus as Corpus>::Input>,
+        IF: InputFilter<<S::Corpus as Corpus>::Input, S>,
+        E: HasObservers + Executor<EM, Self, State = S>,
+        E::Observers: MatchName + ObserversTuple<<S::Corpus as Corpus>::Input, S> + Serialize,
+        EM: EventFirer<State = S>,
+        F: Feedback<EM, <S::Corpus as Corpus>::Input, E::Observers, S>,
+        OF: Feedback<EM, <S::Corpus as Corpus>::Input, E::Observers, S>,
+        S: HasCorpus
+            + HasSolutions
+            + MaybeHasClientPerfMonitor
+            + HasCurrentTestcase
+            + HasExecutions
+            + HasMetadata
+            + UsesInput<Input = <S::Corpus as Corpus>::Input>,
+        <S::Corpus as Corpus>::Input: Input,
+        S::Solutions: Corpus<Input = <S::Corpus as Corpus>::Input>,
+    {
+        self.feedback.set_record_verdicts(true);
+        self.objective.set_record_verdicts(true);
+        let verdict = self.evaluate_input_recorded(state, executor, manager, input);
+        self.feedback.set_record_verdicts(false);
+        self.objective.set_record_verdicts(false);
+        verdict
+    }
+
+    /// Evaluate the input for [`StdFuzzer::evaluate_input_detailed`], with the feedbacks recording
+    fn evaluate_input_recorded<E, EM, S>(
+        &mut self,
+        state: &mut S,
+        executor: &mut E,
+        manager: &mut EM,
+        mut input: <S::Corpus as Corpus>::Input,
+    ) -> Result<EvaluationVerdict, Error>
+    where
+        CS: Scheduler<<S::Corpus as Corpus>::Input, S>,
+        FX: InputFixup<<S::Corpus as Corpus>::Input>,
+        IF: InputFilter<<S::Corpus as Corpus>::Input, S>,
+        E: HasObservers + Executor<EM, Self, State = S>,
+        E::Observers: MatchName + ObserversTuple<<S::Corpus as Corpus>::Input, S> + Serialize,
+        EM: EventFirer<State = S>,
+        F: Feedback<EM, <S::Corpus as Corpus>::Input, E::Observers, S>,
+        OF: Feedback<EM, <S::Corpus as Corpus>::Input, E::Observers, S>,
+        S: HasCorpus
+            + HasSolutions
+            + MaybeHasClientPerfMonitor
+            + HasCurrentTestcase
+            + HasExecutions
+            + HasMetadata
+            + UsesInput<Input = <S::Corpus as Corpus>::Input>,
+        <S::Corpus as Corpus>::Input: Input,
+        S::Solutions: Corpus<Input = <S::Corpus as Corpus>::Input>,
+    {
+        let start = current_time();
+        let Some(exit_kind) = self.fixup_and_run(state, executor, manager, &mut input)? else {
+            return Ok(EvaluationVerdict {
+                result: ExecuteInputResult::Skipped,
+                corpus_id: None,
+                feedbacks: Vec::new(),
+                objectives: Vec::new(),
+                exit_kind: ExitKind::Ok,
+                exec_time: Duration::ZERO,
+                observers_serialized: false,
+            });
+        };
+        let exec_time = current_time().saturating_sub(start);
+        let observers = executor.observers();
+
+        self.scheduler.on_evaluation(state, &input, &*observers)?;
+
+        // The events are dispatched below, to know if the observers were sent along
+        let (result, corpus_id) = self.evaluate_execution(
+            state,
+            manager,
+            input.clone(),
+            &*observers,
+            &exit_kind,
+            false,
+        )?;
+        let observers_buf = Self::observers_buf(manager, &result, &*observers)?;
+        let observers_serialized = observers_buf.is_some();
+        self.dispatch_event(state, manager, input, &result, observers_buf, &exit_kind)?;
+
+        let mut objectives = Vec::new();
+        self.objective.append_verdicts(&mut objectives);
+        objectives.push((
+            self.objective.name().clone(),
+            result == ExecuteInputResult::Solution,
+        ));
+        let mut feedbacks = Vec::new();
+        if result != ExecuteInputResult::Solution {
+            self.feedback.append_verdicts(&mut feedbacks);
+            feedbacks.push((
+                self.feedback.name().clone(),
+                result == ExecuteInputResult::Corpus,
+            ));
+        }
+
+        Ok(EvaluationVerdict {
+            result,
+            corpus_id,
+            feedbacks,
+            objectives,
+            exit_kind,
+            exec_time,
+            observers_serialized,
+        })
+    }
+
+    /// Re-run each of the `inputs`, e.g., a directory of crashes against a rebuilt target, and report how it behaved.
+    ///
+    /// Only the objective looks at the executions: the inputs are fixed up like any other, but not mutated,
+    /// the feedback and the scheduler never see them, and nothing is added to the corpora.
+    /// Timeouts and retries are up to the `executor`, wrap it accordingly.
+    /// Each input is judged by a copy of the objective and of the state metadata as they were before,
+    /// so objectives that only keep novel results, such as [`crate::feedbacks::NewHashFeedback`],
+    /// neither remember the reproduced inputs nor compare them with each other.
+    ///
+    /// A log event summarizes each input, and all of them at the end.
+    pub fn reproduce<E, EM, S, II>(
+        &mut self,
+        state: &mut S,
+        executor: &mut E,
+        manager: &mut EM,
+        inputs: II,
+    ) -> Result<Vec<ReproReport>, Error>
+    where
+        CS: Scheduler<<S::Corpus as Corpus>::Input, S>,
+        E: HasObservers + Executor<EM, Self, State = S>,
+        E::Observers: ObserversTuple<<S::Corpus as Corpus>::Input, S>,
+        EM: EventFirer<State = S>,
+        FX: InputFixup<<S::Corpus as Corpus>::Input>,
+        OF: Feedback<EM, <S::Corpus as Corpus>::Input, E::Observers, S> + Clone,
+        S: HasCorpus
+            + HasExecutions
+            + HasMetadata
+            + HasNamedMetadata
+            + MaybeHasClientPerfMonitor
+            + UsesInput<Input = <S::Corpus as Corpus>::Input>,
+        II: IntoIterator<Item = <S::Corpus as Corpus>::Input>,
+    {
+        let saved_metadata = state.metadata_map().clone();
+        let saved_named_metadata = state.named_metadata_map().clone();
+
+        let mut reports = Vec::new();
+        for (index, mut input) in inputs.into_iter().enumerate() {
+            self.fixup.fixup(&mut input);
+            let start = current_time();
+            let exit_kind = self.execute_input(state, executor, manager, &input)?;
+            let exec_time = current_time().saturating_sub(start);
+            let observers = executor.observers();
+
+            let mut objective = self.objective.clone();
+            let is_objective =
+                objective.is_interesting(state, manager, &input, &*observers, &exit_kind)?;
+            let metadata = if is_objective {
+                let mut testcase = Testcase::new(input);
+                objective.append_metadata(state, manager, &*observers, &mut testcase)?;
+                core::mem::take(testcase.metadata_map_mut())
+            } else {
+                objective.discard_metadata(state, &input)?;
+                SerdeAnyMap::new()
+            };
+            *state.metadata_map_mut() = saved_metadata.clone();
+            *state.named_metadata_map_mut() = saved_named_metadata.clone();
+
+            manager.log(
+                state,
+                LogSeverity::Info,
+                format!(
+                    "Reproduced input #{index}: {exit_kind:?} after {exec_time:?}, {}",
+                    if is_objective {
+                        "still an objective"
+                    } else {
+                        "no longer an objective"
+                    }
+                ),
+            )?;
+            reports.push(ReproReport {
+                index,
+                exit_kind,
+                is_objective,
+                exec_time,
+                metadata,
+            });
+        }
+
+        let objectives = reports.iter().filter(|report| report.is_objective).count();
+        manager.log(
+            state,
+            LogSeverity::Info,
+            format!(
+                "Reproduced {} inputs, {objectives} of them are still objectives",
+                reports.len()
+            ),
+        )?;
+        Ok(reports)
+    }
+}
+
 impl<CS, F, OF> StdFuzzer<CS, F, OF> {
     /// Create a new `StdFuzzer` with standard behavior.
     pub fn new(scheduler: CS, feedback: F, objective: OF) -> Self {
@@ -799,10 +1685,188 @@ impl<CS, F, OF> StdFuzzer<CS, F, OF> {
             scheduler,
             feedback,
             objective,
+            budget: CampaignBudget::default(),
+            fixup: (),
+            filter: (),
         }
     }
 }
 
+impl<CS, F, FX, IF, OF> StdFuzzer<CS, F, OF, FX, IF> {
+    /// Before shutting down gracefully: flush the corpora, and report our final numbers,
+    /// so they reach the broker before the manager detaches.
+    fn flush_and_report<EM, S>(state: &mut S, manager: &mut EM) -> Result<(), Error>
+    where
+        EM: ProgressReporter<State = S>,
+        S: HasExecutions + HasMetadata + HasCorpus + HasSolutions + HasLastReportTime + State,
+    {
+        state.corpus_mut().flush()?;
+        state.solutions_mut().flush()?;
+
+        manager.report_progress(state)?;
+        let event = Event::ClientExiting {
+            time: current_time(),
+            executions: *state.executions(),
+            corpus_size: state.corpus().count(),
+            objective_size: state.solutions().count(),
+        };
+        manager.fire(state, event)
+    }
+
+    /// Stop the campaign once the target ran `executions` times, over all restarts
+    #[must_use]
+    pub fn stop_after_executions(mut self, executions: u64) -> Self {
+        self.budget.executions = Some(executions);
+        self
+    }
+
+    /// Stop the campaign once `duration` passed since this fuzzer first ran on the state, over all restarts, see [`CampaignStartMetadata`]
+    #[must_use]
+    pub fn stop_after_duration(mut self, duration: Duration) -> Self {
+        self.budget.duration = Some(duration);
+        self
+    }
+
+    /// Stop the campaign once `objectives` solutions were found, over all restarts
+    #[must_use]
+    pub fn stop_after_objectives(mut self, objectives: u64) -> Self {
+        self.budget.objectives = Some(objectives);
+        self
+    }
+
+    /// The budget of this campaign
+    #[must_use]
+    pub fn budget(&self) -> &CampaignBudget {
+        &self.budget
+    }
+
+    /// Fix up every input right before it is evaluated or added, see [`InputFixup`].
+    ///
+    /// Stages that re-run stored inputs, such as the calibration, execute them as they are,
+    /// as they were fixed before they got stored.
+    #[must_use]
+    pub fn input_fixup<FX2>(self, fixup: FX2) -> StdFuzzer<CS, F, OF, FX2, IF> {
+        StdFuzzer {
+            scheduler: self.scheduler,
+            feedback: self.feedback,
+            objective: self.objective,
+            budget: self.budget,
+            fixup,
+            filter: self.filter,
+        }
+    }
+
+    /// The [`InputFixup`] applied to every input before it is executed
+    #[must_use]
+    pub fn fixup(&self) -> &FX {
+        &self.fixup
+    }
+
+    /// Consult `filter` before every input is executed, and skip the inputs it rejects, see [`InputFilter`].
+    ///
+    /// The filter sees the input after the [`InputFixup`]. A skipped input is not executed, so it does not count
+    /// as an execution, and the evaluation methods report it as [`ExecuteInputResult::Skipped`].
+    /// Stages that run inputs on their own, such as the calibration, check [`Evaluator::skips_input`].
+    /// [`StdFuzzer::reproduce`] runs its inputs regardless.
+    /// The number of skipped inputs is kept in the [`SkippedInputsMetadata`], and reported in the [`crate::monitors::SKIPPED_INPUTS_STAT`].
+    #[must_use]
+    pub fn set_input_filter<IF2>(self, filter: IF2) -> StdFuzzer<CS, F, OF, FX, IF2> {
+        StdFuzzer {
+            scheduler: self.scheduler,
+            feedback: self.feedback,
+            objective: self.objective,
+            budget: self.budget,
+            fixup: self.fixup,
+            filter,
+        }
+    }
+
+    /// Add an input that was already fixed up as a disabled testcase, see [`Evaluator::add_disabled_input`]
+    fn add_fixed_disabled_input<S>(
+        state: &mut S,
+        input: <S::Corpus as Corpus>::Input,
+    ) -> Result<CorpusId, Error>
+    where
+        S: HasCorpus,
+    {
+        let mut testcase = Testcase::from(input);
+        testcase.set_disabled(true);
+        testcase.add_metadata(ForcedInputMetadata);
+        // Add the disabled input to the main corpus
+        state.corpus_mut().add_disabled(testcase)
+    }
+
+    /// The observers to send along with the event for an execution, if any
+    fn observers_buf<EM, OT>(
+        manager: &mut EM,
+        exec_res: &ExecuteInputResult,
+        observers: &OT,
+    ) -> Result<Option<Vec<u8>>, Error>
+    where
+        EM: EventFirer,
+        OT: ObserversTuple<<EM::State as UsesInput>::Input, EM::State> + Serialize,
+    {
+        // TODO set None for fast targets
+        if *exec_res == ExecuteInputResult::Corpus
+            && manager.should_send()
+            && manager.configuration() != EventConfig::AlwaysUnique
+        {
+            manager.serialize_observers::<OT>(observers)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Fix up the `input` and run it, unless the input filter rejects it, returning `None` then
+    fn fixup_and_run<E, EM, S>(
+        &mut self,
+        state: &mut S,
+        executor: &mut E,
+        manager: &mut EM,
+        input: &mut <S::Corpus as Corpus>::Input,
+    ) -> Result<Option<ExitKind>, Error>
+    where
+        Self: ExecutesInput<E, EM, <S::Corpus as Corpus>::Input, S>,
+        FX: InputFixup<<S::Corpus as Corpus>::Input>,
+        IF: InputFilter<<S::Corpus as Corpus>::Input, S>,
+        EM: EventFirer<State = S>,
+        S: HasCorpus + HasMetadata + UsesInput<Input = <S::Corpus as Corpus>::Input>,
+        <S::Corpus as Corpus>::Input: Input,
+    {
+        self.fixup.fixup(input);
+        if self.skip_filtered(state, input) {
+            return Ok(None);
+        }
+        self.execute_input(state, executor, manager, input)
+            .map(Some)
+    }
+
+    /// Checks if the input filter rejects the `input`, and if so, counts it in the [`SkippedInputsMetadata`]
+    fn skip_filtered<I, S>(&mut self, state: &mut S, input: &I) -> bool
+    where
+        IF: InputFilter<I, S>,
+        S: HasMetadata,
+    {
+        if self.filter.allows(state, input) {
+            return false;
+        }
+        state
+            .metadata_or_insert_with(SkippedInputsMetadata::default)
+            .count += 1;
+        true
+    }
+}
+
+impl<CS, F, FX, I, IF, OF, S> HasInputFilter<I, S> for StdFuzzer<CS, F, OF, FX, IF>
+where
+    IF: InputFilter<I, S>,
+    S: HasMetadata,
+{
+    fn skips_input(&mut self, state: &mut S, input: &I) -> bool {
+        self.skip_filtered(state, input)
+    }
+}
+
 /// Structs with this trait will execute an input
 pub trait ExecutesInput<E, EM, I, S> {
     /// Runs the input and triggers observers and feedback
@@ -815,8 +1879,8 @@ pub trait ExecutesInput<E, EM, I, S> {
     ) -> Result<ExitKind, Error>;
 }
 
-impl<CS, E, EM, F, OF, S> ExecutesInput<E, EM, <S::Corpus as Corpus>::Input, S>
-    for StdFuzzer<CS, F, OF>
+impl<CS, E, EM, F, FX, IF, OF, S> ExecutesInput<E, EM, <S::Corpus as Corpus>::Input, S>
+    for StdFuzzer<CS, F, OF, FX, IF>
 where
     CS: Scheduler<<S::Corpus as Corpus>::Input, S>,
     E: Executor<EM, Self, State = S> + HasObservers,
@@ -909,3 +1973,909 @@ where
         unimplemented!("NopFuzzer cannot fuzz");
     }
 }
+
+#[cfg(feature = "std")]
+#[cfg(test)]
+mod tests {
+    use alloc::{borrow::Cow, rc::Rc, string::ToString, vec, vec::Vec};
+    use core::{
+        cell::{Cell, RefCell},
+        ops::ControlFlow,
+        time::Duration,
+    };
+    use std::{fs, path::PathBuf};
+
+    use libafl_bolts::{current_time, rands::StdRand, tuples::tuple_list, Named};
+
+    use crate::{
+        corpus::{
+            ondisk::OnDiskMetadataFormat, Corpus, CorpusId, HasCurrentCorpusId, InMemoryCorpus,
+            InMemoryOnDiskCorpus, OnDiskCorpus, Testcase,
+        },
+        events::{NopEventManager, ProgressReporter, SimpleEventManager},
+        executors::{ExitKind, InProcessExecutor},
+        feedbacks::{
+            ConstFeedback, CrashFeedback, EagerOrFeedback, FastAndFeedback, Feedback,
+            MapIndexesMetadata, NotFeedback, StateInitializer,
+        },
+        fuzzer::{
+            BatchProgressMetadata, BudgetKind, CampaignStartMetadata, Evaluator,
+            ExecuteInputResult, ForcedInputMetadata, Fuzzer, SkippedInputsMetadata,
+        },
+        inputs::{BytesInput, Input},
+        monitors::SimpleMonitor,
+        mutators::{mutations::BitFlipMutator, StdScheduledMutator},
+        schedulers::QueueScheduler,
+        stages::{ClosureStage, StdMutationalStage},
+        state::{HasCorpus, HasExecutions, HasSolutions, StdState, Stoppable},
+        testing::{bytes_state, bytes_state_with_corpus, RecordingEventManager},
+        Error, HasMetadata, StdFuzzer,
+    };
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_graceful_stop() {
+        type TestState = StdState<
+            BytesInput,
+            InMemoryOnDiskCorpus<BytesInput>,
+            StdRand,
+            OnDiskCorpus<BytesInput>,
+        >;
+
+        let dir = PathBuf::from("target/.test/graceful_stop");
+        drop(fs::remove_dir_all(&dir));
+
+        let mut corpus = InMemoryOnDiskCorpus::<BytesInput>::with_meta_format(
+            dir.join("queue"),
+            Some(OnDiskMetadataFormat::Json),
+        )
+        .unwrap();
+        corpus.add(Testcase::new(vec![0; 4].into())).unwrap();
+
+        let mut feedback = ConstFeedback::new(false);
+        let mut objective = ConstFeedback::new(true);
+        let mut state: TestState = StdState::new(
+            StdRand::with_seed(0),
+            corpus,
+            OnDiskCorpus::new(dir.join("crashes")).unwrap(),
+            &mut feedback,
+            &mut objective,
+        )
+        .unwrap();
+
+        let log = Rc::new(RefCell::new(vec![]));
+        let monitor_log = log.clone();
+        let monitor = SimpleMonitor::new(move |s| monitor_log.borrow_mut().push(s.to_string()));
+        let mut event_manager = SimpleEventManager::new(monitor);
+
+        let mut fuzzer = StdFuzzer::new(
+            QueueScheduler::new(),
+            ConstFeedback::new(false),
+            ConstFeedback::new(true),
+        );
+        let mut harness = |_buf: &BytesInput| ExitKind::Ok;
+        let mut executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(),
+            &mut fuzzer,
+            &mut state,
+            &mut event_manager,
+        )
+        .unwrap();
+
+        // Tag the entry after it was stored, then stop
+        let stop = ClosureStage::new(
+            |_fuzzer: &mut _, _executor: &mut _, state: &mut TestState, _manager: &mut _| {
+                let id = state.current_corpus_id()?.unwrap();
+                state
+                    .corpus()
+                    .get(id)?
+                    .borrow_mut()
+                    .add_metadata(MapIndexesMetadata::new(vec![1337]));
+                state.request_stop();
+                Ok(())
+            },
+        );
+        let mutator = StdScheduledMutator::new(tuple_list!(BitFlipMutator::new()));
+        let mut stages = tuple_list!(StdMutationalStage::new(mutator), stop);
+
+        let res = fuzzer.fuzz_loop(&mut stages, &mut executor, &mut state, &mut event_manager);
+        assert!(matches!(res, Err(Error::ShuttingDown)));
+
+        // The metadata added after storing the entry was flushed
+        let id = state.corpus().first().unwrap();
+        let testcase = state.corpus().get(id).unwrap().borrow();
+        let metadata = fs::read_to_string(testcase.metadata_path().as_ref().unwrap()).unwrap();
+        assert!(metadata.contains("1337"), "{metadata}");
+
+        // Every solution was written completely
+        assert!(state.solutions().count() > 0);
+        for nth in 0..state.solutions().count() {
+            let id = state.solutions().nth(nth);
+            let solution = state.solutions().get(id).unwrap().borrow();
+            BytesInput::from_file(solution.file_path().as_ref().unwrap()).unwrap();
+        }
+
+        // The final numbers reached the monitor
+        assert!(log
+            .borrow()
+            .last()
+            .is_some_and(|line| line.contains("Client Exiting")));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_campaign_budget() {
+        let mut corpus = InMemoryCorpus::<BytesInput>::new();
+        corpus.add(Testcase::new(vec![0; 4].into())).unwrap();
+        let mut feedback = ConstFeedback::new(false);
+        let mut objective = ConstFeedback::new(false);
+        let mut state = bytes_state_with_corpus(corpus, &mut feedback, &mut objective);
+
+        let monitor = SimpleMonitor::new(|_| {});
+        let mut event_manager = SimpleEventManager::new(monitor);
+        let mut fuzzer = StdFuzzer::new(
+            QueueScheduler::new(),
+            ConstFeedback::new(false),
+            ConstFeedback::new(false),
+        )
+        .stop_after_executions(10)
+        .stop_after_duration(Duration::from_secs(3600));
+        let mut harness = |_buf: &BytesInput| ExitKind::Ok;
+        let mut executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(),
+            &mut fuzzer,
+            &mut state,
+            &mut event_manager,
+        )
+        .unwrap();
+        let mutator = StdScheduledMutator::new(tuple_list!(BitFlipMutator::new()));
+        let mut stages = tuple_list!(StdMutationalStage::new(mutator));
+
+        let res = fuzzer.fuzz_loop(&mut stages, &mut executor, &mut state, &mut event_manager);
+        assert!(matches!(res, Err(Error::ShuttingDown)));
+        assert!(*state.executions() >= 10);
+        assert_eq!(
+            fuzzer.budget().exhausted(&state),
+            Some(BudgetKind::Executions)
+        );
+
+        // A restored state with the budget already spent does not execute the target again
+        let executions = *state.executions();
+        let res = fuzzer.fuzz_loop(&mut stages, &mut executor, &mut state, &mut event_manager);
+        assert!(matches!(res, Err(Error::ShuttingDown)));
+        assert_eq!(*state.executions(), executions);
+
+        // The elapsed time counts from the start of the campaign
+        let mut fuzzer = StdFuzzer::new(
+            QueueScheduler::new(),
+            ConstFeedback::new(false),
+            ConstFeedback::new(false),
+        )
+        .stop_after_duration(Duration::from_secs(3600));
+        state.add_metadata(CampaignStartMetadata::new(
+            current_time().saturating_sub(Duration::from_secs(7200)),
+        ));
+        let res = fuzzer.fuzz_loop(&mut stages, &mut executor, &mut state, &mut event_manager);
+        assert!(matches!(res, Err(Error::ShuttingDown)));
+        assert_eq!(*state.executions(), executions);
+        assert_eq!(
+            fuzzer.budget().exhausted(&state),
+            Some(BudgetKind::Duration)
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_input_fixup() {
+        // The target only accepts inputs that end in the sum of all other bytes
+        fn checksum(bytes: &[u8]) -> u8 {
+            bytes.iter().fold(0, |sum, byte| sum.wrapping_add(*byte))
+        }
+        fn is_valid(bytes: &[u8]) -> bool {
+            bytes
+                .split_last()
+                .is_some_and(|(last, rest)| *last == checksum(rest))
+        }
+
+        let mut feedback = ConstFeedback::new(true);
+        let mut objective = ConstFeedback::new(false);
+        let mut state = bytes_state(&mut feedback, &mut objective);
+
+        let mut event_manager = SimpleEventManager::new(SimpleMonitor::new(|_| {}));
+        let mut fuzzer = StdFuzzer::new(
+            QueueScheduler::new(),
+            ConstFeedback::new(true),
+            ConstFeedback::new(false),
+        )
+        .input_fixup(|input: &mut BytesInput| {
+            let bytes = input.as_mut();
+            if let Some((last, rest)) = bytes.split_last_mut() {
+                *last = checksum(rest);
+            } else {
+                bytes.push(0);
+            }
+        });
+
+        let executed = Rc::new(Cell::new(0));
+        let invalid = Rc::new(Cell::new(0));
+        let (harness_executed, harness_invalid) = (executed.clone(), invalid.clone());
+        let mut harness = |input: &BytesInput| {
+            harness_executed.set(harness_executed.get() + 1);
+            if !is_valid(input.as_ref()) {
+                harness_invalid.set(harness_invalid.get() + 1);
+            }
+            ExitKind::Ok
+        };
+        let mut executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(),
+            &mut fuzzer,
+            &mut state,
+            &mut event_manager,
+        )
+        .unwrap();
+
+        fuzzer
+            .add_input(
+                &mut state,
+                &mut executor,
+                &mut event_manager,
+                BytesInput::new(vec![1, 2, 3, 0]),
+            )
+            .unwrap();
+        fuzzer
+            .evaluate_input(
+                &mut state,
+                &mut executor,
+                &mut event_manager,
+                BytesInput::new(vec![]),
+            )
+            .unwrap();
+
+        let mutator = StdScheduledMutator::new(tuple_list!(BitFlipMutator::new()));
+        let mut stages = tuple_list!(StdMutationalStage::new(mutator));
+        for _ in 0..16 {
+            fuzzer
+                .fuzz_one(&mut stages, &mut executor, &mut state, &mut event_manager)
+                .unwrap();
+        }
+
+        // Forced and reproduced inputs are fixed up as well
+        let forced = add_disabled(
+            &mut fuzzer,
+            &executor,
+            &mut state,
+            &event_manager,
+            BytesInput::new(vec![7, 7]),
+        );
+        let executions = executed.get();
+        fuzzer
+            .reproduce(
+                &mut state,
+                &mut executor,
+                &mut event_manager,
+                [BytesInput::new(vec![5, 0])],
+            )
+            .unwrap();
+        assert_eq!(executed.get(), executions + 1);
+
+        // Every execution saw a fixed input, and only fixed inputs were stored
+        assert!(executed.get() > 16);
+        assert_eq!(invalid.get(), 0);
+        assert!(state.corpus().count() > 2);
+        for id in state.corpus().ids().chain([forced]) {
+            let testcase = state.corpus().get_from_all(id).unwrap().borrow();
+            assert!(is_valid(testcase.input().as_ref().unwrap().as_ref()));
+        }
+    }
+
+    /// Pins the executor and the manager type, which `add_disabled_input` cannot infer on its own
+    fn add_disabled<E, EM, I, S, Z>(
+        fuzzer: &mut Z,
+        _executor: &E,
+        state: &mut S,
+        _manager: &EM,
+        input: I,
+    ) -> CorpusId
+    where
+        Z: Evaluator<E, EM, I, S>,
+    {
+        fuzzer.add_disabled_input(state, input).unwrap()
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_evaluate_inputs_batch() {
+        let mut feedback = ConstFeedback::new(true);
+        let mut objective = CrashFeedback::new();
+        let mut state = bytes_state(&mut feedback, &mut objective);
+
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let monitor_log = log.clone();
+        let mut event_manager = SimpleEventManager::new(SimpleMonitor::new(move |line| {
+            monitor_log.borrow_mut().push(line.to_string());
+        }));
+        let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+        // Odd inputs "crash"
+        let mut harness = |input: &BytesInput| {
+            if input.as_ref()[0] % 2 == 1 {
+                ExitKind::Crash
+            } else {
+                ExitKind::Ok
+            }
+        };
+        let mut executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(),
+            &mut fuzzer,
+            &mut state,
+            &mut event_manager,
+        )
+        .unwrap();
+
+        let inputs = (0..5).map(|byte| BytesInput::new(vec![byte])).collect();
+        let results = fuzzer
+            .evaluate_inputs_batch(&mut state, &mut executor, &mut event_manager, inputs)
+            .unwrap();
+
+        let kinds = results.iter().map(|(res, _)| res).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                &ExecuteInputResult::Corpus,
+                &ExecuteInputResult::Solution,
+                &ExecuteInputResult::Corpus,
+                &ExecuteInputResult::Solution,
+                &ExecuteInputResult::Corpus,
+            ]
+        );
+        assert!(results
+            .iter()
+            .all(|(res, id)| id.is_some() == (*res == ExecuteInputResult::Corpus)));
+        assert_eq!(state.corpus().count(), 3);
+        assert_eq!(state.solutions().count(), 2);
+        assert_eq!(*state.executions(), 5);
+
+        // Every new testcase reports the final corpus size, both solutions are reported at once
+        let lines = log.borrow();
+        let testcases = lines
+            .iter()
+            .filter(|line| line.starts_with("[Testcase"))
+            .collect::<Vec<_>>();
+        assert_eq!(testcases.len(), 3);
+        assert!(testcases.iter().all(|line| line.contains("corpus: 3")));
+        let objectives = lines
+            .iter()
+            .filter(|line| line.starts_with("[Objective"))
+            .collect::<Vec<_>>();
+        assert_eq!(objectives.len(), 1);
+        assert!(objectives[0].contains("objectives: 2"));
+        drop(lines);
+
+        // The events a crash held back are fired by the next batch, even an empty one
+        let first = state.corpus().first().unwrap();
+        state.add_metadata(BatchProgressMetadata {
+            evaluated: 1,
+            new_testcases: vec![(first, ExitKind::Ok, None)],
+            found_solution: false,
+        });
+        fuzzer
+            .evaluate_inputs_batch(&mut state, &mut executor, &mut event_manager, vec![])
+            .unwrap();
+        assert_eq!(
+            log.borrow()
+                .iter()
+                .filter(|line| line.starts_with("[Testcase"))
+                .count(),
+            4
+        );
+        let progress = state.metadata::<BatchProgressMetadata>().unwrap();
+        assert_eq!(progress.evaluated, 0);
+        assert!(progress.new_testcases.is_empty());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_evaluate_input_detailed() {
+        let mut feedback = EagerOrFeedback::new(
+            CrashFeedback::new(),
+            NotFeedback::new(ConstFeedback::new(false)),
+        );
+        let mut objective = FastAndFeedback::new(CrashFeedback::new(), ConstFeedback::new(true));
+        let feedback_name = feedback.name().clone();
+        let objective_name = objective.name().clone();
+        let mut state = bytes_state(&mut feedback, &mut objective);
+
+        let mut event_manager = NopEventManager::new();
+        let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+        // Odd inputs "crash"
+        let mut harness = |input: &BytesInput| {
+            if input.as_ref()[0] % 2 == 1 {
+                ExitKind::Crash
+            } else {
+                ExitKind::Ok
+            }
+        };
+        let mut executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(),
+            &mut fuzzer,
+            &mut state,
+            &mut event_manager,
+        )
+        .unwrap();
+
+        let verdict = fuzzer
+            .evaluate_input_detailed(
+                &mut state,
+                &mut executor,
+                &mut event_manager,
+                BytesInput::new(vec![0]),
+            )
+            .unwrap();
+        assert_eq!(verdict.result, ExecuteInputResult::Corpus);
+        assert_eq!(verdict.corpus_id, Some(CorpusId(0)));
+        assert_eq!(verdict.exit_kind, ExitKind::Ok);
+        assert!(!verdict.observers_serialized);
+        // The fast AND stops after the first objective
+        assert_eq!(
+            verdict.objectives,
+            [
+                ("CrashFeedback".into(), false),
+                (objective_name.clone(), false)
+            ]
+        );
+        assert_eq!(
+            verdict.feedbacks,
+            [
+                ("CrashFeedback".into(), false),
+                ("ConstFeedback".into(), false),
+                ("Not(ConstFeedback)".into(), true),
+                (feedback_name, true),
+            ]
+        );
+
+        let verdict = fuzzer
+            .evaluate_input_detailed(
+                &mut state,
+                &mut executor,
+                &mut event_manager,
+                BytesInput::new(vec![1]),
+            )
+            .unwrap();
+        assert_eq!(verdict.result, ExecuteInputResult::Solution);
+        assert_eq!(verdict.exit_kind, ExitKind::Crash);
+        assert_eq!(
+            verdict.objectives,
+            [
+                ("CrashFeedback".into(), true),
+                ("ConstFeedback".into(), true),
+                (objective_name, true),
+            ]
+        );
+        assert!(verdict.feedbacks.is_empty());
+        assert_eq!(state.corpus().count(), 1);
+        assert_eq!(state.solutions().count(), 1);
+
+        // Feedbacks without nested feedbacks are reported on their own
+        let mut feedback = CrashFeedback::new();
+        let mut objective = ConstFeedback::new(false);
+        let mut state = bytes_state(&mut feedback, &mut objective);
+        let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+        let mut executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(),
+            &mut fuzzer,
+            &mut state,
+            &mut event_manager,
+        )
+        .unwrap();
+        let verdict = fuzzer
+            .evaluate_input_detailed(
+                &mut state,
+                &mut executor,
+                &mut event_manager,
+                BytesInput::new(vec![1]),
+            )
+            .unwrap();
+        assert_eq!(verdict.result, ExecuteInputResult::Corpus);
+        assert_eq!(verdict.objectives, [("ConstFeedback".into(), false)]);
+        assert_eq!(verdict.feedbacks, [("CrashFeedback".into(), true)]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_fuzz_loop_with() {
+        let mut corpus = InMemoryCorpus::<BytesInput>::new();
+        corpus.add(Testcase::new(vec![0; 4].into())).unwrap();
+        let mut feedback = ConstFeedback::new(true);
+        let mut objective = ConstFeedback::new(false);
+        let mut state = bytes_state_with_corpus(corpus, &mut feedback, &mut objective);
+
+        let log = Rc::new(RefCell::new(vec![]));
+        let monitor_log = log.clone();
+        let monitor = SimpleMonitor::new(move |s| monitor_log.borrow_mut().push(s.to_string()));
+        let mut event_manager = SimpleEventManager::new(monitor);
+        let mut fuzzer = StdFuzzer::new(
+            QueueScheduler::new(),
+            ConstFeedback::new(true),
+            ConstFeedback::new(false),
+        );
+        let mut harness = |_buf: &BytesInput| ExitKind::Ok;
+        let mut executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(),
+            &mut fuzzer,
+            &mut state,
+            &mut event_manager,
+        )
+        .unwrap();
+        let mutator = StdScheduledMutator::new(tuple_list!(BitFlipMutator::new()));
+        let mut stages = tuple_list!(StdMutationalStage::new(mutator));
+
+        // Break out after three iterations
+        let mut summaries = vec![];
+        let corpus_size = state.corpus().count();
+        let executions = *state.executions();
+        fuzzer
+            .fuzz_loop_with(
+                &mut stages,
+                &mut executor,
+                &mut state,
+                &mut event_manager,
+                |_state, summary| {
+                    summaries.push(*summary);
+                    if summaries.len() == 3 {
+                        ControlFlow::Break(())
+                    } else {
+                        ControlFlow::Continue(())
+                    }
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            summaries.iter().map(|s| s.iteration).collect::<Vec<_>>(),
+            [0, 1, 2]
+        );
+        assert!(summaries.iter().all(|s| s.corpus_delta > 0));
+        assert!(summaries.iter().all(|s| s.objectives_delta == 0));
+        assert_eq!(
+            summaries.iter().map(|s| s.corpus_delta).sum::<isize>(),
+            isize::try_from(state.corpus().count() - corpus_size).unwrap()
+        );
+        assert_eq!(
+            summaries.iter().map(|s| s.executions).sum::<u64>(),
+            *state.executions() - executions
+        );
+        // The loop exited through the graceful stop
+        assert!(log
+            .borrow()
+            .last()
+            .is_some_and(|line| line.contains("Client Exiting")));
+
+        // Without a break, all iterations run
+        let mut iterations = 0;
+        fuzzer
+            .fuzz_loop_for_with(
+                &mut stages,
+                &mut executor,
+                &mut state,
+                &mut event_manager,
+                5,
+                |_state, _summary| {
+                    iterations += 1;
+                    ControlFlow::Continue(())
+                },
+            )
+            .unwrap();
+        assert_eq!(iterations, 5);
+
+        // A requested stop is still reported as such
+        let res = fuzzer.fuzz_loop_with(
+            &mut stages,
+            &mut executor,
+            &mut state,
+            &mut event_manager,
+            |state, _summary| {
+                state.request_stop();
+                ControlFlow::Continue(())
+            },
+        );
+        assert!(matches!(res, Err(Error::ShuttingDown)));
+    }
+
+    /// Finds crashes with a tag not seen before, like a stack hash, and tags each with the first byte of its input.
+    /// The seen tags are kept in the state.
+    #[derive(Clone)]
+    struct TaggingCrashFeedback {
+        #[cfg(feature = "track_hit_feedbacks")]
+        last_result: Option<bool>,
+    }
+
+    impl Named for TaggingCrashFeedback {
+        fn name(&self) -> &Cow<'static, str> {
+            static NAME: Cow<'static, str> = Cow::Borrowed("TaggingCrashFeedback");
+            &NAME
+        }
+    }
+
+    impl<S> StateInitializer<S> for TaggingCrashFeedback {}
+
+    impl<EM, OT, S> Feedback<EM, BytesInput, OT, S> for TaggingCrashFeedback
+    where
+        S: HasMetadata,
+    {
+        fn is_interesting(
+            &mut self,
+            state: &mut S,
+            _manager: &mut EM,
+            input: &BytesInput,
+            _observers: &OT,
+            exit_kind: &ExitKind,
+        ) -> Result<bool, Error> {
+            let tag = usize::from(input.as_ref()[0]);
+            let seen = state.metadata_or_insert_with(|| MapIndexesMetadata::new(vec![]));
+            let interesting = *exit_kind == ExitKind::Crash && !seen.list.contains(&tag);
+            if interesting {
+                seen.list.push(tag);
+            }
+            #[cfg(feature = "track_hit_feedbacks")]
+            {
+                self.last_result = Some(interesting);
+            }
+            Ok(interesting)
+        }
+
+        fn append_metadata(
+            &mut self,
+            _state: &mut S,
+            _manager: &mut EM,
+            _observers: &OT,
+            testcase: &mut Testcase<BytesInput>,
+        ) -> Result<(), Error> {
+            let tag = testcase.input().as_ref().unwrap().as_ref()[0];
+            testcase.add_metadata(MapIndexesMetadata::new(vec![tag.into()]));
+            Ok(())
+        }
+
+        #[cfg(feature = "track_hit_feedbacks")]
+        fn last_result(&self) -> Result<bool, Error> {
+            self.last_result
+                .ok_or(Error::illegal_state("No last result set"))
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_reproduce() {
+        let mut feedback = ConstFeedback::new(true);
+        let mut objective = TaggingCrashFeedback {
+            #[cfg(feature = "track_hit_feedbacks")]
+            last_result: None,
+        };
+        let mut state = bytes_state(&mut feedback, &mut objective);
+
+        let mut event_manager = NopEventManager::new();
+        let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+        // Odd inputs "crash"
+        let mut harness = |input: &BytesInput| {
+            if input.as_ref()[0] % 2 == 1 {
+                ExitKind::Crash
+            } else {
+                ExitKind::Ok
+            }
+        };
+        let mut executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(),
+            &mut fuzzer,
+            &mut state,
+            &mut event_manager,
+        )
+        .unwrap();
+
+        // The repeated crash is judged like the first one
+        let inputs = [vec![1], vec![2], vec![3], vec![1]].map(BytesInput::new);
+        let reports = fuzzer
+            .reproduce(&mut state, &mut executor, &mut event_manager, inputs)
+            .unwrap();
+        assert_eq!(
+            reports
+                .iter()
+                .map(|report| (report.index, report.exit_kind, report.is_objective))
+                .collect::<Vec<_>>(),
+            [
+                (0, ExitKind::Crash, true),
+                (1, ExitKind::Ok, false),
+                (2, ExitKind::Crash, true),
+                (3, ExitKind::Crash, true)
+            ]
+        );
+        assert_eq!(
+            reports[2]
+                .metadata
+                .get::<MapIndexesMetadata>()
+                .unwrap()
+                .list,
+            [3]
+        );
+        assert!(reports[1].metadata.get::<MapIndexesMetadata>().is_none());
+
+        // Nothing was stored, even though the feedback likes everything, and the objective saw no tags
+        assert_eq!(state.corpus().count(), 0);
+        assert_eq!(state.solutions().count(), 0);
+        assert_eq!(*state.executions(), 4);
+        assert!(!state.has_metadata::<MapIndexesMetadata>());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_add_input_events() {
+        let mut feedback = ConstFeedback::new(false);
+        let mut objective = CrashFeedback::new();
+        let mut state = bytes_state(&mut feedback, &mut objective);
+
+        let mut event_manager = RecordingEventManager::new();
+        let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+        // Odd inputs "crash"
+        let mut harness = |input: &BytesInput| {
+            if input.as_ref()[0] % 2 == 1 {
+                ExitKind::Crash
+            } else {
+                ExitKind::Ok
+            }
+        };
+        let mut executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(),
+            &mut fuzzer,
+            &mut state,
+            &mut event_manager,
+        )
+        .unwrap();
+
+        // Forced inputs are announced like interesting ones, and marked
+        let id = fuzzer
+            .add_input(
+                &mut state,
+                &mut executor,
+                &mut event_manager,
+                BytesInput::new(vec![0]),
+            )
+            .unwrap();
+        assert!(state
+            .corpus()
+            .get(id)
+            .unwrap()
+            .borrow()
+            .has_metadata::<ForcedInputMetadata>());
+        fuzzer
+            .add_input(
+                &mut state,
+                &mut executor,
+                &mut event_manager,
+                BytesInput::new(vec![1]),
+            )
+            .unwrap();
+        let id = add_disabled(
+            &mut fuzzer,
+            &executor,
+            &mut state,
+            &event_manager,
+            BytesInput::new(vec![2]),
+        );
+        assert!(state
+            .corpus()
+            .get_from_all(id)
+            .unwrap()
+            .borrow()
+            .has_metadata::<ForcedInputMetadata>());
+        assert_eq!(event_manager.fired, ["Testcase", "Objective"]);
+        assert_eq!(state.corpus().count(), 1);
+        assert_eq!(state.corpus().count_disabled(), 1);
+        assert_eq!(state.solutions().count(), 1);
+
+        // Nothing is announced if the manager does not send
+        event_manager.should_send = false;
+        fuzzer
+            .add_input(
+                &mut state,
+                &mut executor,
+                &mut event_manager,
+                BytesInput::new(vec![4]),
+            )
+            .unwrap();
+        add_disabled(
+            &mut fuzzer,
+            &executor,
+            &mut state,
+            &event_manager,
+            BytesInput::new(vec![6]),
+        );
+        assert_eq!(event_manager.fired.len(), 2);
+        assert_eq!(state.corpus().count(), 2);
+        assert_eq!(state.corpus().count_disabled(), 2);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_input_filter() {
+        let mut feedback = ConstFeedback::new(true);
+        let mut objective = CrashFeedback::new();
+        let mut state = bytes_state(&mut feedback, &mut objective);
+
+        let mut event_manager = RecordingEventManager::new();
+        // Inputs starting with 0xff "hang" the target, keep them away from it
+        let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective)
+            .set_input_filter(|_state: &_, input: &BytesInput| input.as_ref()[0] != 0xff);
+        let mut harness = |input: &BytesInput| {
+            assert_ne!(input.as_ref()[0], 0xff, "a filtered input was executed");
+            ExitKind::Ok
+        };
+        let mut executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(),
+            &mut fuzzer,
+            &mut state,
+            &mut event_manager,
+        )
+        .unwrap();
+
+        let (res, id) = fuzzer
+            .evaluate_input(
+                &mut state,
+                &mut executor,
+                &mut event_manager,
+                BytesInput::new(vec![0xff]),
+            )
+            .unwrap();
+        assert_eq!(res, ExecuteInputResult::Skipped);
+        assert!(id.is_none());
+        assert_eq!(*state.executions(), 0);
+        assert!(event_manager.fired.is_empty());
+
+        let inputs = [0, 0xff, 1]
+            .into_iter()
+            .map(|byte| BytesInput::new(vec![byte]))
+            .collect();
+        let results = fuzzer
+            .evaluate_inputs_batch(&mut state, &mut executor, &mut event_manager, inputs)
+            .unwrap();
+        let kinds = results.iter().map(|(res, _)| res).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                &ExecuteInputResult::Corpus,
+                &ExecuteInputResult::Skipped,
+                &ExecuteInputResult::Corpus,
+            ]
+        );
+        assert_eq!(*state.executions(), 2);
+
+        // A forced input is stored, but not executed
+        fuzzer
+            .add_input(
+                &mut state,
+                &mut executor,
+                &mut event_manager,
+                BytesInput::new(vec![0xff]),
+            )
+            .unwrap();
+        assert_eq!(state.corpus().count_disabled(), 1);
+        assert_eq!(*state.executions(), 2);
+        assert_eq!(state.corpus().count(), 2);
+
+        assert_eq!(
+            state.metadata::<SkippedInputsMetadata>().unwrap().count(),
+            3
+        );
+        // The count is only reported along with the progress
+        assert!(!event_manager.fired.contains(&"UserStats"));
+        event_manager.report_progress(&mut state).unwrap();
+        assert!(event_manager.fired.contains(&"UserStats"));
+    }
+}
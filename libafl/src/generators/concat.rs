@@ -0,0 +1,124 @@
+//! Concatenate the outputs of several [`Generator`]s, e.g., a fixed header and a random body.
+
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt::{self, Debug, Formatter};
+
+use crate::{generators::Generator, inputs::BytesInput, Error};
+
+/// A piece of the inputs of a [`ConcatGenerator`]
+enum Piece<S> {
+    /// Always the same bytes
+    Literal(Vec<u8>),
+    /// The bytes of a generated input
+    Generated(Box<dyn Generator<BytesInput, S>>),
+}
+
+/// Generates [`BytesInput`]s by concatenating literal bytes and the outputs of other [`Generator`]s,
+/// in the order they were added.
+///
+/// For example, a seed for a text protocol could be a literal request line, followed by a random body
+/// from a [`super::RandBytesGenerator`] with [`super::Alphabet::Printable`].
+pub struct ConcatGenerator<S> {
+    pieces: Vec<Piece<S>>,
+}
+
+impl<S> Debug for ConcatGenerator<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let pieces = self
+            .pieces
+            .iter()
+            .map(|piece| match piece {
+                Piece::Literal(bytes) => Some(bytes),
+                Piece::Generated(_) => None,
+            })
+            .collect::<Vec<_>>();
+        f.debug_struct("ConcatGenerator")
+            .field("pieces", &pieces)
+            .finish()
+    }
+}
+
+impl<S> Default for ConcatGenerator<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> ConcatGenerator<S> {
+    /// Creates a new [`ConcatGenerator`], generating empty inputs until pieces are added
+    #[must_use]
+    pub fn new() -> Self {
+        Self { pieces: Vec::new() }
+    }
+
+    /// Appends the given bytes to every input
+    #[must_use]
+    pub fn literal<B>(mut self, bytes: B) -> Self
+    where
+        B: Into<Vec<u8>>,
+    {
+        self.pieces.push(Piece::Literal(bytes.into()));
+        self
+    }
+
+    /// Appends the output of `generator` to every input
+    #[must_use]
+    pub fn generated<G>(mut self, generator: G) -> Self
+    where
+        G: Generator<BytesInput, S> + 'static,
+    {
+        self.pieces.push(Piece::Generated(Box::new(generator)));
+        self
+    }
+}
+
+impl<S> Generator<BytesInput, S> for ConcatGenerator<S> {
+    fn generate(&mut self, state: &mut S) -> Result<BytesInput, Error> {
+        let mut bytes = Vec::new();
+        for piece in &mut self.pieces {
+            match piece {
+                Piece::Literal(literal) => bytes.extend_from_slice(literal),
+                Piece::Generated(generator) => {
+                    bytes.extend_from_slice(generator.generate(state)?.as_ref());
+                }
+            }
+        }
+        Ok(BytesInput::new(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        generators::{Alphabet, ConcatGenerator, Generator, RandBytesGenerator},
+        inputs::BytesInput,
+        nonzero,
+        state::StdState,
+    };
+
+    #[test]
+    fn test_header_and_body() {
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        let mut generator = ConcatGenerator::new()
+            .literal(b"GET /".as_slice())
+            .generated(RandBytesGenerator::new(nonzero!(16)).alphabet(Alphabet::Alphanumeric))
+            .literal(b" HTTP/1.1\r\n\r\n".as_slice())
+            .generated(RandBytesGenerator::new(nonzero!(32)).alphabet(Alphabet::Printable));
+
+        for _ in 0..100 {
+            let input = generator.generate(&mut state).unwrap();
+            let bytes: &[u8] = input.as_ref();
+            assert!(bytes.starts_with(b"GET /"));
+            let end = bytes
+                .windows(4)
+                .position(|window| window == b"\r\n\r\n")
+                .unwrap();
+            let path = &bytes[5..end - 9];
+            assert!(!path.is_empty() && path.iter().all(u8::is_ascii_alphanumeric));
+            assert_eq!(&bytes[end - 9..end], b" HTTP/1.1");
+            assert!(bytes[end + 4..]
+                .iter()
+                .all(|byte| Alphabet::Printable.contains(*byte)));
+        }
+    }
+}
@@ -0,0 +1,379 @@
+//! A lightweight generator for [`BytesInput`]s from a context-free grammar, e.g., to seed the initial corpus.
+//!
+//! Unlike [`super::NautilusGenerator`], it does not keep the derivation tree around,
+//! the inputs are plain bytes.
+use alloc::{
+    borrow::ToOwned,
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::num::NonZero;
+#[cfg(feature = "std")]
+use std::{fs, path::Path};
+
+use libafl_bolts::rands::Rand;
+use serde::{Deserialize, Serialize};
+
+use crate::{generators::Generator, inputs::BytesInput, state::HasRand, Error};
+
+/// How often [`GrammarGenerator`] tries to derive an input that fits the maximum length,
+/// before it truncates the last one
+const MAX_LEN_TRIES: usize = 16;
+
+/// The json layout of a [`Grammar`]
+///
+/// ```json
+/// {
+///   "start": "<expr>",
+///   "rules": {
+///     "<expr>": [["<num>"], ["<expr>", "+", "<expr>"]],
+///     "<num>": [["1"], ["2"], ["3"]]
+///   }
+/// }
+/// ```
+///
+/// Every rule maps a nonterminal to its alternatives, an alternative is a list of symbols.
+/// A symbol is a nonterminal if there is a rule for it, else it is a terminal.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct GrammarRules {
+    /// The nonterminal to start derivations from
+    pub start: String,
+    /// The alternatives for each nonterminal
+    pub rules: BTreeMap<String, Vec<Vec<String>>>,
+}
+
+/// A symbol in an alternative of a [`Grammar`] rule
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Symbol {
+    /// Bytes to emit
+    Terminal(Vec<u8>),
+    /// The index of the rule to expand
+    NonTerminal(usize),
+}
+
+/// A context-free grammar, checked to be able to terminate
+#[derive(Clone, Debug)]
+pub struct Grammar {
+    /// The names of the nonterminals
+    names: Vec<String>,
+    /// The alternatives for each nonterminal
+    rules: Vec<Vec<Vec<Symbol>>>,
+    /// The minimum derivation depth of each nonterminal
+    min_depths: Vec<usize>,
+    /// The nonterminal to start with
+    start: usize,
+}
+
+impl TryFrom<GrammarRules> for Grammar {
+    type Error = Error;
+
+    fn try_from(rules: GrammarRules) -> Result<Self, Self::Error> {
+        Self::new(&rules.start, rules.rules)
+    }
+}
+
+impl Grammar {
+    /// Creates a new [`Grammar`], from the alternatives for each nonterminal.
+    ///
+    /// Fails if a nonterminal has no alternatives, or can never be fully derived,
+    /// e.g., because all its alternatives are left-recursive.
+    pub fn new<R>(start: &str, rules: R) -> Result<Self, Error>
+    where
+        R: IntoIterator<Item = (String, Vec<Vec<String>>)>,
+    {
+        let (names, alternatives): (Vec<_>, Vec<_>) = rules.into_iter().unzip();
+        let index = |name: &str| names.iter().position(|n| n == name);
+
+        let start_idx = index(start).ok_or_else(|| {
+            Error::illegal_argument(format!("No rule for the start symbol {start}"))
+        })?;
+        let mut rules = Vec::with_capacity(alternatives.len());
+        for (name, alternatives) in names.iter().zip(alternatives) {
+            if alternatives.is_empty() {
+                return Err(Error::illegal_argument(format!(
+                    "The rule for {name} has no alternatives"
+                )));
+            }
+            rules.push(
+                alternatives
+                    .into_iter()
+                    .map(|alternative| {
+                        alternative
+                            .into_iter()
+                            .map(|symbol| match index(&symbol) {
+                                Some(idx) => Symbol::NonTerminal(idx),
+                                None => Symbol::Terminal(symbol.into_bytes()),
+                            })
+                            .collect()
+                    })
+                    .collect(),
+            );
+        }
+
+        let min_depths = Self::min_depths(&rules);
+        if let Some(idx) = min_depths.iter().position(|depth| *depth == usize::MAX) {
+            return Err(Error::illegal_argument(format!(
+                "The rule for {} can never terminate",
+                names[idx]
+            )));
+        }
+
+        Ok(Self {
+            names,
+            rules,
+            min_depths,
+            start: start_idx,
+        })
+    }
+
+    /// Creates a new [`Grammar`] from its json representation, see [`GrammarRules`]
+    #[cfg(feature = "std")]
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let rules: GrammarRules = serde_json::from_str(json)
+            .map_err(|err| Error::illegal_argument(format!("Invalid json grammar: {err:?}")))?;
+        rules.try_into()
+    }
+
+    /// Loads a [`Grammar`] from a json file, see [`GrammarRules`]
+    #[cfg(feature = "std")]
+    pub fn from_file<P>(grammar_file: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let grammar_file = grammar_file.as_ref();
+        Self::from_json(&fs::read_to_string(grammar_file)?).map_err(|err| {
+            Error::illegal_argument(format!(
+                "Error loading grammar file {}: {err:?}",
+                grammar_file.display()
+            ))
+        })
+    }
+
+    /// Creates a [`Grammar`] from string slices, mostly for tests and hand-written grammars
+    pub fn from_slices(start: &str, rules: &[(&str, &[&[&str]])]) -> Result<Self, Error> {
+        Self::new(
+            start,
+            rules.iter().map(|(name, alternatives)| {
+                (
+                    (*name).to_owned(),
+                    alternatives
+                        .iter()
+                        .map(|alternative| alternative.iter().map(ToString::to_string).collect())
+                        .collect(),
+                )
+            }),
+        )
+    }
+
+    /// The minimum depth of a derivation tree for each nonterminal, `usize::MAX` if there is none
+    fn min_depths(rules: &[Vec<Vec<Symbol>>]) -> Vec<usize> {
+        let mut min_depths = vec![usize::MAX; rules.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (idx, alternatives) in rules.iter().enumerate() {
+                let depth = alternatives
+                    .iter()
+                    .map(|alternative| Self::alternative_depth(&min_depths, alternative))
+                    .min()
+                    .unwrap_or(usize::MAX);
+                if depth < min_depths[idx] {
+                    min_depths[idx] = depth;
+                    changed = true;
+                }
+            }
+        }
+        min_depths
+    }
+
+    /// The minimum depth of a derivation tree for an alternative
+    fn alternative_depth(min_depths: &[usize], alternative: &[Symbol]) -> usize {
+        alternative
+            .iter()
+            .map(|symbol| match symbol {
+                Symbol::Terminal(_) => 0,
+                Symbol::NonTerminal(idx) => min_depths[*idx],
+            })
+            .max()
+            .unwrap_or(0)
+            .saturating_add(1)
+    }
+
+    /// The minimum derivation depth to generate anything from the start symbol
+    #[must_use]
+    pub fn min_depth(&self) -> usize {
+        self.min_depths[self.start]
+    }
+
+    /// The nonterminals of this grammar
+    pub fn nonterminals(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+
+    /// Appends a random derivation of `nonterminal` of at most `depth` levels to `out`
+    fn derive<R>(&self, rand: &mut R, nonterminal: usize, depth: usize, out: &mut Vec<u8>)
+    where
+        R: Rand,
+    {
+        let fitting = self.rules[nonterminal]
+            .iter()
+            .filter(|alternative| Self::alternative_depth(&self.min_depths, alternative) <= depth)
+            .collect::<Vec<_>>();
+        // The callers only descend into nonterminals that fit in the remaining depth
+        let alternative = fitting[rand.below(NonZero::new(fitting.len()).unwrap())];
+        for symbol in alternative {
+            match symbol {
+                Symbol::Terminal(bytes) => out.extend_from_slice(bytes),
+                Symbol::NonTerminal(idx) => self.derive(rand, *idx, depth - 1, out),
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+/// Generates [`BytesInput`]s from a [`Grammar`], picking alternatives with the state's [`Rand`]
+pub struct GrammarGenerator {
+    grammar: Grammar,
+    max_depth: usize,
+    max_len: usize,
+}
+
+impl<S> Generator<BytesInput, S> for GrammarGenerator
+where
+    S: HasRand,
+{
+    fn generate(&mut self, state: &mut S) -> Result<BytesInput, Error> {
+        let mut bytes = Vec::new();
+        for _ in 0..MAX_LEN_TRIES {
+            bytes.clear();
+            self.grammar.derive(
+                state.rand_mut(),
+                self.grammar.start,
+                self.max_depth,
+                &mut bytes,
+            );
+            if bytes.len() <= self.max_len {
+                return Ok(BytesInput::new(bytes));
+            }
+        }
+        bytes.truncate(self.max_len);
+        Ok(BytesInput::new(bytes))
+    }
+}
+
+impl GrammarGenerator {
+    /// Creates a new [`GrammarGenerator`], deriving at most `max_depth` levels deep.
+    ///
+    /// Derivations longer than `max_len` bytes are retried a few times, then truncated.
+    /// Fails if the grammar needs more than `max_depth` levels to derive anything.
+    pub fn new(grammar: Grammar, max_depth: usize, max_len: usize) -> Result<Self, Error> {
+        if grammar.min_depth() > max_depth {
+            return Err(Error::illegal_argument(format!(
+                "The grammar needs a derivation depth of at least {}, the maximum is {max_depth}",
+                grammar.min_depth()
+            )));
+        }
+        Ok(Self {
+            grammar,
+            max_depth,
+            max_len,
+        })
+    }
+
+    /// Creates a new [`GrammarGenerator`] for the grammar in a json file, see [`GrammarRules`]
+    #[cfg(feature = "std")]
+    pub fn from_file<P>(grammar_file: P, max_depth: usize, max_len: usize) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        Self::new(Grammar::from_file(grammar_file)?, max_depth, max_len)
+    }
+
+    /// The [`Grammar`] of this generator
+    #[must_use]
+    pub fn grammar(&self) -> &Grammar {
+        &self.grammar
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use libafl_bolts::rands::StdRand;
+
+    use crate::{
+        corpus::InMemoryCorpus,
+        generators::{Generator, Grammar, GrammarGenerator},
+        inputs::BytesInput,
+        state::{HasRand, StdState},
+    };
+
+    const EXPR: &str = r#"{
+        "start": "<expr>",
+        "rules": {
+            "<expr>": [["<num>"], ["(", "<expr>", "+", "<expr>", ")"], ["<expr>", "*", "<num>"]],
+            "<num>": [["1"], ["2"], ["<num>", "0"]]
+        }
+    }"#;
+
+    fn seeded_state(
+        seed: u64,
+    ) -> StdState<BytesInput, InMemoryCorpus<BytesInput>, StdRand, InMemoryCorpus<BytesInput>> {
+        let mut state = StdState::nop().unwrap();
+        *state.rand_mut() = StdRand::with_seed(seed);
+        state
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_grammar_generator() {
+        let grammar = Grammar::from_json(EXPR).unwrap();
+        assert_eq!(grammar.min_depth(), 2);
+        let mut generator = GrammarGenerator::new(grammar, 6, 32).unwrap();
+
+        let mut state = seeded_state(1337);
+        let inputs = (0..64)
+            .map(|_| generator.generate(&mut state).unwrap())
+            .collect::<Vec<_>>();
+        for input in &inputs {
+            let bytes: &[u8] = input.as_ref();
+            assert!(!bytes.is_empty() && bytes.len() <= 32);
+            assert!(bytes.iter().all(|b| b"()+*120".contains(b)));
+        }
+        assert!(inputs.iter().any(|input| input.as_ref().contains(&b'(')));
+
+        // The same seed gives the same inputs
+        let mut state = seeded_state(1337);
+        for input in &inputs {
+            assert_eq!(&generator.generate(&mut state).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn test_grammar_depth() {
+        // Only left-recursive alternatives, this never terminates
+        let looping = Grammar::from_slices("<a>", &[("<a>", &[&["<a>", "x"]])]);
+        assert!(looping.is_err());
+
+        let deep = Grammar::from_slices(
+            "<a>",
+            &[
+                ("<a>", &[&["<b>"]]),
+                ("<b>", &[&["<c>"]]),
+                ("<c>", &[&["c"]]),
+            ],
+        )
+        .unwrap();
+        assert_eq!(deep.min_depth(), 3);
+        assert!(GrammarGenerator::new(deep.clone(), 2, 8).is_err());
+
+        let mut generator = GrammarGenerator::new(deep, 3, 8).unwrap();
+        let input = generator.generate(&mut seeded_state(0)).unwrap();
+        assert_eq!(input.as_ref(), b"c");
+
+        assert!(Grammar::from_slices("<missing>", &[("<a>", &[&["a"]])]).is_err());
+        assert!(Grammar::from_slices("<a>", &[("<a>", &[])]).is_err());
+    }
+}
@@ -12,6 +12,20 @@ use core::cmp::max;
 
 pub use gramatron::*;
 
+pub mod grammar;
+pub use grammar::*;
+
+pub mod concat;
+pub use concat::*;
+
+pub mod ranged;
+pub use ranged::*;
+
+#[cfg(feature = "multipart_inputs")]
+pub mod multi;
+#[cfg(feature = "multipart_inputs")]
+pub use multi::*;
+
 #[cfg(feature = "nautilus")]
 pub mod nautilus;
 #[cfg(feature = "nautilus")]
@@ -71,11 +85,110 @@ where
     }
 }
 
+/// The bytes a [`RandBytesGenerator`] picks from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Alphabet {
+    /// Any byte
+    Bytes,
+    /// Printable ASCII, from `' '` to `'~'`
+    Printable,
+    /// ASCII letters and digits
+    Alphanumeric,
+    /// Lower case hex digits
+    HexDigits,
+    /// The given bytes, bytes listed more than once are picked more often
+    Custom(Vec<u8>),
+}
+
+/// The ASCII letters and digits, for [`Alphabet::Alphanumeric`]
+const ALPHANUMERIC: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// The hex digits, for [`Alphabet::HexDigits`]
+const HEX_DIGITS: &[u8] = b"0123456789abcdef";
+
+impl Alphabet {
+    /// Checks if `byte` can be picked from this alphabet
+    #[must_use]
+    pub fn contains(&self, byte: u8) -> bool {
+        match self {
+            Self::Bytes => true,
+            Self::Printable => (b' '..=b'~').contains(&byte),
+            Self::Alphanumeric => byte.is_ascii_alphanumeric(),
+            Self::HexDigits => HEX_DIGITS.contains(&byte),
+            Self::Custom(bytes) => bytes.contains(&byte),
+        }
+    }
+
+    /// Pick a random byte of this alphabet, `None` if it is empty
+    #[allow(clippy::cast_possible_truncation)]
+    fn pick<R>(&self, rand: &mut R) -> Option<u8>
+    where
+        R: Rand,
+    {
+        match self {
+            Self::Bytes => Some(rand.below(nonzero!(256)) as u8),
+            Self::Printable => Some(rand.between(usize::from(b' '), usize::from(b'~')) as u8),
+            Self::Alphanumeric => rand.choose(ALPHANUMERIC).copied(),
+            Self::HexDigits => rand.choose(HEX_DIGITS).copied(),
+            Self::Custom(bytes) => rand.choose(bytes).copied(),
+        }
+    }
+}
+
+/// How a [`RandBytesGenerator`] picks the length of an input
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LengthDistribution {
+    /// Any length from `min` to `max`, both inclusive, with the same probability
+    Uniform {
+        /// The minimum length
+        min: NonZeroUsize,
+        /// The maximum length
+        max: NonZeroUsize,
+    },
+    /// Geometrically distributed lengths, starting at `1`: short inputs are common, long ones rare.
+    ///
+    /// Lengths above `max` are cut to `max`, which lowers the mean if `max` is not far above it.
+    Geometric {
+        /// The average length, at least `1`
+        mean: f64,
+        /// The maximum length
+        max: NonZeroUsize,
+    },
+    /// Always the same length
+    Fixed(NonZeroUsize),
+}
+
+impl LengthDistribution {
+    /// Draw a length
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn sample<R>(&self, rand: &mut R) -> usize
+    where
+        R: Rand,
+    {
+        match *self {
+            Self::Uniform { min, max: upper } => max(rand.between(min.get(), upper.get()), 1),
+            Self::Geometric { mean, max } => {
+                if mean <= 1.0 {
+                    return 1;
+                }
+                // Inverse transform sampling, with a success probability of `1 / mean` per byte
+                let failure = libm::log(1.0 - 1.0 / mean);
+                let len = libm::ceil(libm::log(1.0 - rand.next_float()) / failure);
+                (len as usize).clamp(1, max.get())
+            }
+            Self::Fixed(len) => len.get(),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 /// Generates random bytes
+///
+/// By default, any byte can occur, see [`RandBytesGenerator::alphabet`], and
+/// the lengths are uniformly distributed, see [`RandBytesGenerator::length_distribution`].
 pub struct RandBytesGenerator {
-    min_size: NonZeroUsize,
-    max_size: NonZeroUsize,
+    alphabet: Alphabet,
+    length: LengthDistribution,
 }
 
 impl<S> Generator<BytesInput, S> for RandBytesGenerator
@@ -83,13 +196,14 @@ where
     S: HasRand,
 {
     fn generate(&mut self, state: &mut S) -> Result<BytesInput, Error> {
-        let mut size = state
-            .rand_mut()
-            .between(self.min_size.get(), self.max_size.get());
-        size = max(size, 1);
-        let random_bytes: Vec<u8> = (0..size)
-            .map(|_| state.rand_mut().below(nonzero!(256)) as u8)
-            .collect();
+        let size = self.length.sample(state.rand_mut());
+        let random_bytes = (0..size)
+            .map(|_| {
+                self.alphabet
+                    .pick(state.rand_mut())
+                    .ok_or_else(|| Error::illegal_argument("The alphabet is empty"))
+            })
+            .collect::<Result<Vec<u8>, Error>>()?;
         Ok(BytesInput::new(random_bytes))
     }
 }
@@ -98,16 +212,33 @@ impl RandBytesGenerator {
     /// Returns a new [`RandBytesGenerator`], generating up to `max_size` random bytes.
     #[must_use]
     pub fn new(max_size: NonZeroUsize) -> Self {
-        Self {
-            min_size: nonzero!(1),
-            max_size,
-        }
+        Self::with_min_size(nonzero!(1), max_size)
     }
 
     /// Returns a new [`RandBytesGenerator`], generating from `min_size` up to `max_size` random bytes.
     #[must_use]
     pub fn with_min_size(min_size: NonZeroUsize, max_size: NonZeroUsize) -> Self {
-        Self { min_size, max_size }
+        Self {
+            alphabet: Alphabet::Bytes,
+            length: LengthDistribution::Uniform {
+                min: min_size,
+                max: max_size,
+            },
+        }
+    }
+
+    /// Only generate bytes from the given [`Alphabet`]
+    #[must_use]
+    pub fn alphabet(mut self, alphabet: Alphabet) -> Self {
+        self.alphabet = alphabet;
+        self
+    }
+
+    /// Pick the lengths of the generated inputs from the given [`LengthDistribution`]
+    #[must_use]
+    pub fn length_distribution(mut self, length: LengthDistribution) -> Self {
+        self.length = length;
+        self
     }
 }
 
@@ -151,3 +282,72 @@ impl RandPrintablesGenerator {
         Self { min_size, max_size }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use libafl_bolts::rands::StdRand;
+
+    use crate::{
+        corpus::InMemoryCorpus,
+        generators::{Alphabet, Generator, LengthDistribution, RandBytesGenerator},
+        inputs::BytesInput,
+        nonzero,
+        state::{HasRand, StdState},
+    };
+
+    #[test]
+    fn test_alphabets() {
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        for alphabet in [
+            Alphabet::Printable,
+            Alphabet::Alphanumeric,
+            Alphabet::HexDigits,
+            Alphabet::Custom(vec![b'a', b'\0']),
+        ] {
+            let mut generator = RandBytesGenerator::new(nonzero!(64)).alphabet(alphabet.clone());
+            for _ in 0..100 {
+                let input = generator.generate(&mut state).unwrap();
+                assert!(input.as_ref().iter().all(|byte| alphabet.contains(*byte)));
+            }
+        }
+
+        let mut empty = RandBytesGenerator::new(nonzero!(64)).alphabet(Alphabet::Custom(vec![]));
+        assert!(empty.generate(&mut state).is_err());
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn test_length_distributions() {
+        const SAMPLES: usize = 10_000;
+        const MEAN: f64 = 8.0;
+
+        let mut state: StdState<BytesInput, InMemoryCorpus<BytesInput>, StdRand, _> =
+            StdState::nop().unwrap();
+        *state.rand_mut() = StdRand::with_seed(1337);
+
+        let mut generator = RandBytesGenerator::new(nonzero!(1))
+            .length_distribution(LengthDistribution::Fixed(nonzero!(5)));
+        assert_eq!(generator.generate(&mut state).unwrap().as_ref().len(), 5);
+
+        let mut generator = RandBytesGenerator::new(nonzero!(1)).length_distribution(
+            LengthDistribution::Geometric {
+                mean: MEAN,
+                max: nonzero!(1024),
+            },
+        );
+        let lengths = (0..SAMPLES)
+            .map(|_| generator.generate(&mut state).unwrap().as_ref().len())
+            .collect::<Vec<_>>();
+        assert!(lengths.iter().all(|len| (1..=1024).contains(len)));
+        let mean = lengths.iter().sum::<usize>() as f64 / SAMPLES as f64;
+        assert!(
+            (mean - MEAN).abs() < 0.5,
+            "mean length {mean}, expected {MEAN}"
+        );
+        // Geometric: length 1 is the most common one
+        let ones = lengths.iter().filter(|len| **len == 1).count() as f64 / SAMPLES as f64;
+        assert!((ones - 1.0 / MEAN).abs() < 0.03);
+    }
+}
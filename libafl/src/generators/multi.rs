@@ -0,0 +1,118 @@
+//! Generators for [`MultipartInput`]s. See [`crate::inputs::multi`] for details.
+
+use alloc::{boxed::Box, string::String, vec::Vec};
+use core::fmt::{self, Debug, Formatter};
+
+use crate::{generators::Generator, inputs::MultipartInput, Error};
+
+/// Assembles a [`MultipartInput`] from one [`Generator`] per named part.
+///
+/// The parts are generated in the order they were added. Names may repeat, e.g., for sections
+/// that occur multiple times, each occurrence then gets its own generated part.
+pub struct MultipartGenerator<I, S> {
+    parts: Vec<(String, Box<dyn Generator<I, S>>)>,
+}
+
+impl<I, S> Debug for MultipartGenerator<I, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultipartGenerator")
+            .field(
+                "parts",
+                &self.parts.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl<I, S> Default for MultipartGenerator<I, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I, S> MultipartGenerator<I, S> {
+    /// Creates a new [`MultipartGenerator`] without any parts
+    #[must_use]
+    pub fn new() -> Self {
+        Self { parts: Vec::new() }
+    }
+
+    /// Adds a part with the given `name`, generated by `generator`
+    #[must_use]
+    pub fn with_part<N, G>(mut self, name: N, generator: G) -> Self
+    where
+        N: Into<String>,
+        G: Generator<I, S> + 'static,
+    {
+        self.parts.push((name.into(), Box::new(generator)));
+        self
+    }
+
+    /// The names of the parts this generator assembles, in order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.parts.iter().map(|(name, _)| name.as_str())
+    }
+}
+
+impl<I, S> Generator<MultipartInput<I>, S> for MultipartGenerator<I, S> {
+    fn generate(&mut self, state: &mut S) -> Result<MultipartInput<I>, Error> {
+        let mut input = MultipartInput::new();
+        for (name, generator) in &mut self.parts {
+            input.add_part(name.clone(), generator.generate(state)?);
+        }
+        Ok(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use libafl_bolts::rands::StdRand;
+
+    use crate::{
+        generators::{Generator, MultipartGenerator, RandBytesGenerator},
+        inputs::{BytesInput, HasMutatorBytes},
+        mutators::{ByteIncMutator, MutationResult, Mutator, PartMutator},
+        nonzero,
+        state::{HasRand, NopState},
+    };
+
+    #[test]
+    fn test_named_parts() {
+        let mut state: NopState<BytesInput> = NopState::new();
+        *state.rand_mut() = StdRand::with_seed(1337);
+
+        let mut generator = MultipartGenerator::new()
+            .with_part("handshake", vec![BytesInput::new(vec![1])].into_iter())
+            .with_part("payload", RandBytesGenerator::new(nonzero!(8)))
+            .with_part("payload", vec![BytesInput::new(vec![3])].into_iter());
+        let mut input = generator.generate(&mut state).unwrap();
+        assert_eq!(input.names(), ["handshake", "payload", "payload"]);
+        assert_eq!(input.part_by_name("handshake").unwrap().bytes(), &[1]);
+        assert_eq!(input.parts_with_name("payload").count(), 2);
+        assert!(input.part_by_name("auth").is_none());
+
+        // Only the handshake is ever mutated
+        let payloads = input
+            .parts_with_name("payload")
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut mutator = PartMutator::for_name("handshake", ByteIncMutator::new());
+        for _ in 0..10 {
+            assert_eq!(
+                mutator.mutate(&mut state, &mut input).unwrap(),
+                MutationResult::Mutated
+            );
+        }
+        assert_eq!(input.part_by_name("handshake").unwrap().bytes(), &[11]);
+        assert!(input.parts_with_name("payload").eq(payloads.iter()));
+
+        let mut mutator =
+            PartMutator::new(|name: &str| name.starts_with("auth"), ByteIncMutator::new());
+        assert_eq!(
+            mutator.mutate(&mut state, &mut input).unwrap(),
+            MutationResult::Skipped
+        );
+    }
+}
@@ -0,0 +1,162 @@
+//! Generators for [`RangedValueInput`]s and [`crate::inputs::RangedStructInput`]s,
+//! sampling new values in the range of a template input.
+
+#[cfg(feature = "multipart_inputs")]
+use crate::inputs::MultipartInput;
+use crate::{
+    generators::Generator,
+    inputs::{RangedInput, RangedNumeric, RangedValueInput},
+    state::HasRand,
+    Error,
+};
+
+/// Generates copies of a template input with random values in the range of the template.
+///
+/// By default, all values in the range are equally likely, see [`RangedGenerator::log_uniform`].
+#[derive(Clone, Debug)]
+pub struct RangedGenerator<I> {
+    template: I,
+    log_uniform: bool,
+}
+
+impl<I> RangedGenerator<I> {
+    /// Creates a new [`RangedGenerator`], generating inputs with the ranges of `template`
+    pub fn new(template: I) -> Self {
+        Self {
+            template,
+            log_uniform: false,
+        }
+    }
+
+    /// Make each order of magnitude equally likely, e.g., for sizes.
+    /// Ranges with negative numbers are still sampled uniformly.
+    #[must_use]
+    pub fn log_uniform(mut self) -> Self {
+        self.log_uniform = true;
+        self
+    }
+}
+
+impl<T> RangedGenerator<RangedValueInput<T>>
+where
+    T: RangedNumeric,
+{
+    /// Creates a new [`RangedGenerator`], generating values from `min` to `max`, both inclusive
+    pub fn with_range(min: T, max: T) -> Result<Self, Error> {
+        if min.is_nan() || max.is_nan() || min > max {
+            return Err(Error::illegal_argument(format!(
+                "Invalid range for RangedGenerator: {min:?} to {max:?}"
+            )));
+        }
+        Ok(Self::new(RangedValueInput::new(min).with_range(min, max)))
+    }
+}
+
+impl<I, S> Generator<I, S> for RangedGenerator<I>
+where
+    I: RangedInput + Clone,
+    S: HasRand,
+{
+    fn generate(&mut self, state: &mut S) -> Result<I, Error> {
+        let mut input = self.template.clone();
+        input.resample(state.rand_mut(), self.log_uniform);
+        Ok(input)
+    }
+}
+
+#[cfg(feature = "multipart_inputs")]
+impl<I, S> Generator<MultipartInput<I>, S> for RangedGenerator<MultipartInput<I>>
+where
+    I: RangedInput + Clone,
+    S: HasRand,
+{
+    fn generate(&mut self, state: &mut S) -> Result<MultipartInput<I>, Error> {
+        let mut input = self.template.clone();
+        for idx in 0..input.parts().len() {
+            input
+                .part_mut(idx)
+                .unwrap()
+                .resample(state.rand_mut(), self.log_uniform);
+        }
+        Ok(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libafl_bolts::rands::StdRand;
+
+    use super::RangedGenerator;
+    use crate::{
+        corpus::InMemoryCorpus, generators::Generator, inputs::RangedValueInput, state::StdState,
+    };
+
+    #[test]
+    fn test_ranged_generator() {
+        let mut state = StdState::new(
+            StdRand::with_seed(0),
+            InMemoryCorpus::<RangedValueInput<u32>>::new(),
+            InMemoryCorpus::new(),
+            &mut (),
+            &mut (),
+        )
+        .unwrap();
+
+        assert!(RangedGenerator::with_range(2_u32, 1).is_err());
+        assert!(RangedGenerator::with_range(f64::NAN, 1.0).is_err());
+
+        let mut uniform = RangedGenerator::with_range(1_u32, 1 << 20).unwrap();
+        let mut log_uniform = RangedGenerator::with_range(1_u32, 1 << 20)
+            .unwrap()
+            .log_uniform();
+        let (mut small_uniform, mut small_log_uniform) = (0, 0);
+        for _ in 0..1000 {
+            let value = uniform.generate(&mut state).unwrap();
+            assert!((1..=1 << 20).contains(&value.value()));
+            assert_eq!(value.upper(), 1 << 20);
+            small_uniform += usize::from(value.value() < 1 << 10);
+
+            let value = log_uniform.generate(&mut state).unwrap();
+            assert!((1..=1 << 20).contains(&value.value()));
+            small_log_uniform += usize::from(value.value() < 1 << 10);
+        }
+        assert!(small_uniform < small_log_uniform);
+    }
+
+    #[test]
+    #[cfg(feature = "multipart_inputs")]
+    fn test_ranged_struct_generator() {
+        use alloc::string::String;
+
+        use crate::inputs::{RangedScalar, RangedStructInput};
+
+        let mut template = RangedStructInput::new();
+        template.add_part(
+            String::from("flags"),
+            RangedValueInput::new(0_u8).with_range(0, 3).into(),
+        );
+        template.add_part(
+            String::from("offset"),
+            RangedValueInput::new(0_i64).with_range(-10, 10).into(),
+        );
+        let mut generator = RangedGenerator::new(template);
+        let mut state = StdState::new(
+            StdRand::with_seed(0),
+            InMemoryCorpus::<RangedStructInput>::new(),
+            InMemoryCorpus::new(),
+            &mut (),
+            &mut (),
+        )
+        .unwrap();
+
+        for _ in 0..100 {
+            let input = generator.generate(&mut state).unwrap();
+            assert_eq!(input.names(), ["flags", "offset"]);
+            let [RangedScalar::U8(flags), RangedScalar::I64(offset)] = input.parts() else {
+                panic!("unexpected fields: {input:?}");
+            };
+            assert!(flags.value() <= 3);
+            assert!((-10..=10).contains(&offset.value()));
+        }
+    }
+}
@@ -0,0 +1,438 @@
+//! Codecs convert between the [`EncodedInput`]s the fuzzer mutates and the raw [`BytesInput`]s
+//! the target, on-disk corpora, and triage tools consume.
+//!
+//! The [`TokenCodec`] learns its [`TokenVocabulary`] from a [`Tokenizer`] and a dictionary of [`Tokens`],
+//! and keeps it in the state metadata. Its [`TokenDecoder`] applies it wherever there is no state at hand:
+//! it is a [`TargetBytesConverter`], e.g., for the [`crate::executors::ForkserverExecutor`] and for the
+//! exported bytes of an [`crate::corpus::InMemoryOnDiskCorpus`], it wraps harnesses, see [`TokenDecoder::harness`],
+//! and gives the bytes dumped to disk, see [`TokenDecoder::testcase_bytes`].
+
+use alloc::vec::Vec;
+
+use hashbrown::HashMap;
+use libafl_bolts::{impl_serdeany, ownedref::OwnedSlice, Error};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    corpus::Testcase,
+    executors::ExitKind,
+    inputs::{BytesInput, EncodedInput, TargetBytesConverter, Tokenizer},
+    mutators::Tokens,
+    HasMetadata,
+};
+
+/// Converts between [`EncodedInput`]s and [`BytesInput`]s.
+///
+/// The tables of the codec may be kept in the state `S`, so they survive restarts.
+pub trait InputCodec<S> {
+    /// Encode raw bytes
+    fn encode(&mut self, state: &mut S, input: &BytesInput) -> Result<EncodedInput, Error>;
+
+    /// Decode an encoded input back to raw bytes
+    fn decode(&self, state: &S, input: &EncodedInput) -> Result<BytesInput, Error>;
+}
+
+/// The number of ids reserved for single bytes in a [`TokenVocabulary`]
+const BYTE_IDS: usize = 256;
+
+/// The tokens of a [`TokenCodec`], each with a stable id.
+///
+/// The ids `0..256` stand for the single bytes, so every byte string can be encoded.
+/// Longer tokens get the next free id when they are added, and keep it for good.
+/// The vocabulary is kept in the state metadata, so ids stay the same across restarts.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(from = "Vec<Vec<u8>>", into = "Vec<Vec<u8>>")]
+pub struct TokenVocabulary {
+    /// The tokens longer than a single byte, the first one has id `256`
+    tokens: Vec<Vec<u8>>,
+    /// The ids of the tokens
+    ids: HashMap<Vec<u8>, u32>,
+    /// The length of the longest token
+    max_len: usize,
+}
+
+impl_serdeany!(TokenVocabulary);
+
+impl From<Vec<Vec<u8>>> for TokenVocabulary {
+    fn from(tokens: Vec<Vec<u8>>) -> Self {
+        let mut vocabulary = Self::default();
+        for token in &tokens {
+            vocabulary.add_token(token);
+        }
+        vocabulary
+    }
+}
+
+impl From<TokenVocabulary> for Vec<Vec<u8>> {
+    fn from(vocabulary: TokenVocabulary) -> Self {
+        vocabulary.tokens
+    }
+}
+
+impl TokenVocabulary {
+    /// The number of ids in use, including the single bytes
+    #[must_use]
+    pub fn len(&self) -> usize {
+        BYTE_IDS + self.tokens.len()
+    }
+
+    /// Always `false`, the single bytes are always part of the vocabulary
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Add a token, returning its id. Tokens that are known already keep their id.
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn add_token(&mut self, token: &[u8]) -> u32 {
+        match token {
+            [] => 0,
+            [byte] => u32::from(*byte),
+            _ => {
+                if let Some(id) = self.ids.get(token) {
+                    return *id;
+                }
+                let id = self.len() as u32;
+                self.tokens.push(token.to_vec());
+                self.ids.insert(token.to_vec(), id);
+                self.max_len = self.max_len.max(token.len());
+                id
+            }
+        }
+    }
+
+    /// Encode `bytes`, always picking the longest known token at the current position
+    #[must_use]
+    pub fn encode(&self, bytes: &[u8]) -> EncodedInput {
+        let mut codes = Vec::new();
+        let mut pos = 0;
+        while pos < bytes.len() {
+            let rest = &bytes[pos..];
+            let (id, len) = (2..=self.max_len.min(rest.len()))
+                .rev()
+                .find_map(|len| self.ids.get(&rest[..len]).map(|id| (*id, len)))
+                .unwrap_or((u32::from(rest[0]), 1));
+            codes.push(id);
+            pos += len;
+        }
+        EncodedInput::new(codes)
+    }
+
+    /// Decode the codes of an [`EncodedInput`].
+    ///
+    /// Mutations may produce codes that are not in the vocabulary,
+    /// they wrap around to a known id, like for [`super::TokenInputEncoderDecoder`].
+    #[must_use]
+    pub fn decode(&self, input: &EncodedInput) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(input.codes().len());
+        for code in input.codes() {
+            let id = *code as usize % self.len();
+            if let Some(token) = id.checked_sub(BYTE_IDS) {
+                bytes.extend_from_slice(&self.tokens[token]);
+            } else {
+                #[allow(clippy::cast_possible_truncation)]
+                bytes.push(id as u8);
+            }
+        }
+        bytes
+    }
+}
+
+/// An [`InputCodec`] that encodes inputs as the ids of their tokens in a [`TokenVocabulary`].
+///
+/// Tokens are learned while encoding, from what the [`Tokenizer`] finds in the input, and from the
+/// dictionary given to [`TokenCodec::with_tokens`]; use `()` to only use the dictionary.
+/// Unlike the [`Tokenizer`] output, the encoding is lossless: decoding gives back the exact bytes.
+/// The learned tokens go to the [`TokenVocabulary`] in the state.
+#[derive(Debug, Clone)]
+pub struct TokenCodec<T> {
+    tokenizer: T,
+    dictionary: Vec<Vec<u8>>,
+    /// If the dictionary was added to the vocabulary in the state already
+    dictionary_added: bool,
+}
+
+impl<T> TokenCodec<T> {
+    /// Creates a new [`TokenCodec`] learning tokens with the given [`Tokenizer`]
+    #[must_use]
+    pub fn new(tokenizer: T) -> Self {
+        Self {
+            tokenizer,
+            dictionary: Vec::new(),
+            dictionary_added: false,
+        }
+    }
+
+    /// Adds all tokens of the dictionary to the vocabulary, before any token learned from an input
+    #[must_use]
+    pub fn with_tokens(mut self, tokens: &Tokens) -> Self {
+        self.dictionary.extend(tokens.tokens().iter().cloned());
+        self.dictionary_added = false;
+        self
+    }
+
+    /// The vocabulary in the state, with the dictionary added
+    fn vocabulary_mut<'a, S>(&mut self, state: &'a mut S) -> &'a mut TokenVocabulary
+    where
+        S: HasMetadata,
+    {
+        let vocabulary = state.metadata_or_insert_with(TokenVocabulary::default);
+        if !self.dictionary_added {
+            for token in &self.dictionary {
+                vocabulary.add_token(token);
+            }
+            self.dictionary_added = true;
+        }
+        vocabulary
+    }
+
+    /// A [`TokenDecoder`] with the vocabulary in the `state`, including the dictionary of this codec.
+    ///
+    /// The decoder does not follow tokens learned later on, so create it once the initial inputs are encoded.
+    pub fn decoder<S>(&mut self, state: &mut S) -> TokenDecoder
+    where
+        S: HasMetadata,
+    {
+        TokenDecoder {
+            vocabulary: self.vocabulary_mut(state).clone(),
+        }
+    }
+}
+
+impl<S, T> InputCodec<S> for TokenCodec<T>
+where
+    S: HasMetadata,
+    T: Tokenizer,
+{
+    fn encode(&mut self, state: &mut S, input: &BytesInput) -> Result<EncodedInput, Error> {
+        let bytes: &[u8] = input.as_ref();
+        let tokens = self.tokenizer.tokenize(bytes);
+        let vocabulary = self.vocabulary_mut(state);
+        match tokens {
+            Ok(tokens) => {
+                for token in tokens {
+                    vocabulary.add_token(token.as_bytes());
+                }
+            }
+            // Still encodable, with the tokens we know
+            Err(err) => log::debug!("Could not tokenize input: {err}"),
+        }
+        Ok(vocabulary.encode(bytes))
+    }
+
+    fn decode(&self, state: &S, input: &EncodedInput) -> Result<BytesInput, Error> {
+        let bytes = match state.metadata::<TokenVocabulary>() {
+            Ok(vocabulary) => vocabulary.decode(input),
+            Err(_) => TokenVocabulary::default().decode(input),
+        };
+        Ok(BytesInput::new(bytes))
+    }
+}
+
+/// Decodes [`EncodedInput`]s with a copy of the vocabulary of a [`TokenCodec`], see [`TokenCodec::decoder`]
+#[derive(Debug, Clone)]
+pub struct TokenDecoder {
+    vocabulary: TokenVocabulary,
+}
+
+impl TokenDecoder {
+    /// Decode an [`EncodedInput`] to raw bytes
+    #[must_use]
+    pub fn decode(&self, input: &EncodedInput) -> BytesInput {
+        BytesInput::new(self.vocabulary.decode(input))
+    }
+
+    /// Wrap a harness for raw bytes, so it can run [`EncodedInput`]s,
+    /// e.g., in an [`crate::executors::InProcessExecutor`]
+    pub fn harness<H>(&self, mut harness: H) -> impl FnMut(&EncodedInput) -> ExitKind
+    where
+        H: FnMut(&BytesInput) -> ExitKind,
+    {
+        let decoder = self.clone();
+        move |input| harness(&decoder.decode(input))
+    }
+
+    /// The raw bytes of a testcase, so that, e.g., [`crate::stages::DumpToDiskStage`] writes decoded inputs to disk
+    pub fn testcase_bytes<S>(&self) -> impl FnMut(&Testcase<EncodedInput>, &S) -> Vec<u8> {
+        let decoder = self.clone();
+        move |testcase, _state| {
+            testcase
+                .input()
+                .as_ref()
+                .map(|input| decoder.decode(input).into_inner())
+                .unwrap_or_default()
+        }
+    }
+}
+
+impl TargetBytesConverter for TokenDecoder {
+    type Input = EncodedInput;
+
+    fn to_target_bytes<'a>(&mut self, input: &'a EncodedInput) -> OwnedSlice<'a, u8> {
+        OwnedSlice::from(self.vocabulary.decode(input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::String, vec, vec::Vec};
+
+    use libafl_bolts::{
+        nonzero,
+        rands::{Rand, StdRand},
+        serdeany::SerdeAnyMap,
+    };
+
+    use crate::{
+        corpus::Testcase,
+        executors::ExitKind,
+        inputs::{BytesInput, InputCodec, TargetBytesConverter, TokenCodec, TokenVocabulary},
+        mutators::Tokens,
+        state::{NopState, StdState},
+        HasMetadata,
+    };
+
+    /// Random inputs, partly made of `words`, so there is something to tokenize
+    fn random_corpus(rand: &mut StdRand, words: &[&str]) -> Vec<BytesInput> {
+        (0..256)
+            .map(|_| {
+                let mut bytes = vec![];
+                for _ in 0..rand.below(nonzero!(16)) {
+                    if rand.coinflip(0.5) {
+                        bytes.extend_from_slice(rand.choose(words).unwrap().as_bytes());
+                        bytes.push(b' ');
+                    } else {
+                        bytes.push(rand.next() as u8);
+                    }
+                }
+                BytesInput::new(bytes)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut rand = StdRand::with_seed(1337);
+        let mut state = NopState::<BytesInput>::new();
+        let tokens = Tokens::from(vec![
+            b"GET".to_vec(),
+            b"\r\n".to_vec(),
+            b"\xff\x00".to_vec(),
+        ]);
+        let mut codec = TokenCodec::new(()).with_tokens(&tokens);
+
+        for input in random_corpus(&mut rand, &["GET", "POST", "\r\n"]) {
+            let encoded = codec.encode(&mut state, &input).unwrap();
+            assert!(encoded.codes().len() <= input.as_ref().len());
+            assert_eq!(codec.decode(&state, &encoded).unwrap(), input);
+        }
+        let get = codec
+            .encode(&mut state, &BytesInput::new(b"GET".to_vec()))
+            .unwrap();
+        assert_eq!(get.codes(), [256]);
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    #[cfg_attr(all(miri, target_arch = "aarch64", target_vendor = "apple"), ignore)] // Regex miri fails on M1
+    fn test_round_trip_tokenizer() {
+        let mut rand = StdRand::with_seed(42);
+        let mut state = NopState::<BytesInput>::new();
+        let mut codec = TokenCodec::new(crate::inputs::NaiveTokenizer::default());
+
+        let corpus = random_corpus(&mut rand, &["int", "main", "(", ")", "'a b'", "// c"]);
+        let encoded = corpus
+            .iter()
+            .map(|input| codec.encode(&mut state, input).unwrap())
+            .collect::<Vec<_>>();
+        // Learning new tokens does not change the meaning of earlier encodings
+        for (input, encoded) in corpus.iter().zip(&encoded) {
+            assert_eq!(&codec.decode(&state, encoded).unwrap(), input);
+        }
+        assert!(state.metadata::<TokenVocabulary>().unwrap().len() > 256);
+    }
+
+    #[test]
+    fn test_restart() {
+        let mut rand = StdRand::with_seed(7);
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        let corpus = random_corpus(&mut rand, &["alpha", "beta", "gamma"]);
+        let tokens = Tokens::from(vec![b"alpha".to_vec(), b"beta".to_vec(), b"gamma".to_vec()]);
+        let mut codec = TokenCodec::new(()).with_tokens(&tokens);
+        let encoded = corpus
+            .iter()
+            .map(|input| codec.encode(&mut state, input).unwrap())
+            .collect::<Vec<_>>();
+
+        // Simulate a restart: the state is serialized, the codec starts from scratch
+        let restored: SerdeAnyMap =
+            postcard::from_bytes(&postcard::to_allocvec(state.metadata_map()).unwrap()).unwrap();
+        *state.metadata_map_mut() = restored;
+
+        // The dictionary in a different order would give different ids
+        let reversed = Tokens::from(vec![b"gamma".to_vec(), b"beta".to_vec(), b"alpha".to_vec()]);
+        let mut restarted = TokenCodec::new(()).with_tokens(&reversed);
+        for (input, encoded) in corpus.iter().zip(&encoded) {
+            assert_eq!(&restarted.encode(&mut state, input).unwrap(), encoded);
+            assert_eq!(&restarted.decode(&state, encoded).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn test_decoder_integration() {
+        let mut state = NopState::<BytesInput>::new();
+        let tokens = Tokens::from(vec![b"hello".to_vec()]);
+        let mut codec = TokenCodec::new(()).with_tokens(&tokens);
+        let encoded = codec
+            .encode(&mut state, &BytesInput::new(b"hello world".to_vec()))
+            .unwrap();
+        let mut decoder = codec.decoder(&mut state);
+
+        let mut seen = String::new();
+        let mut harness = decoder.harness(|input: &BytesInput| {
+            seen = String::from_utf8(input.as_ref().clone()).unwrap();
+            ExitKind::Ok
+        });
+        assert_eq!(harness(&encoded), ExitKind::Ok);
+        drop(harness);
+        assert_eq!(seen, "hello world");
+
+        let mut to_bytes = decoder.testcase_bytes::<()>();
+        assert_eq!(
+            to_bytes(&Testcase::new(encoded.clone()), &()),
+            b"hello world"
+        );
+
+        // What an executor writes to the target
+        assert_eq!(&*decoder.to_target_bytes(&encoded), b"hello world");
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_on_disk_corpus() {
+        use std::{env, fs, process};
+
+        use crate::{
+            corpus::{Corpus, InMemoryOnDiskCorpus},
+            inputs::EncodedInput,
+        };
+
+        let mut state = NopState::<BytesInput>::new();
+        let mut codec = TokenCodec::new(()).with_tokens(&Tokens::from(vec![b"GET".to_vec()]));
+        let encoded = codec
+            .encode(&mut state, &BytesInput::new(b"GET /".to_vec()))
+            .unwrap();
+
+        // The corpus keeps the encoded form the fuzzer mutates, and the decoded bytes next to it
+        let dir = env::temp_dir().join(format!("libafl_token_codec_{}", process::id()));
+        let mut corpus = InMemoryOnDiskCorpus::<EncodedInput>::no_meta(&dir).unwrap();
+        corpus.export_target_bytes(codec.decoder(&mut state), "bytes");
+        let id = corpus.add(Testcase::new(encoded)).unwrap();
+        let filename = corpus.get(id).unwrap().borrow().filename().clone().unwrap();
+        assert_eq!(
+            fs::read(dir.join(format!("{filename}.bytes"))).unwrap(),
+            b"GET /"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -6,21 +6,20 @@
 #[cfg(feature = "regex")]
 use alloc::string::ToString;
 use alloc::{borrow::ToOwned, rc::Rc, string::String, vec::Vec};
+use core::cell::RefCell;
 #[cfg(feature = "regex")]
 use core::str::from_utf8;
-use core::{
-    cell::RefCell,
-    hash::{BuildHasher, Hasher},
-};
 
-use ahash::RandomState;
 use hashbrown::HashMap;
 use libafl_bolts::{Error, HasLen};
 #[cfg(feature = "regex")]
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
-use crate::{corpus::CorpusId, inputs::Input};
+use crate::{
+    corpus::CorpusId,
+    inputs::{Input, StableHash},
+};
 
 /// Trait to encode bytes to an [`EncodedInput`] using the given [`Tokenizer`]
 pub trait InputEncoder<T>
@@ -44,6 +43,13 @@ pub trait Tokenizer {
     fn tokenize(&self, bytes: &[u8]) -> Result<Vec<String>, Error>;
 }
 
+/// A [`Tokenizer`] that finds no tokens
+impl Tokenizer for () {
+    fn tokenize(&self, _bytes: &[u8]) -> Result<Vec<String>, Error> {
+        Ok(Vec::new())
+    }
+}
+
 /// A token input encoder/decoder
 #[derive(Clone, Debug)]
 pub struct TokenInputEncoderDecoder {
@@ -204,11 +210,11 @@ impl Input for EncodedInput {
     /// Generate a name for this input
     #[must_use]
     fn generate_name(&self, _id: Option<CorpusId>) -> String {
-        let mut hasher = RandomState::with_seeds(0, 0, 0, 0).build_hasher();
-        for code in &self.codes {
-            hasher.write(&code.to_le_bytes());
-        }
-        format!("{:016x}", hasher.finish())
+        format!("{:016x}", self.stable_hash())
+    }
+
+    fn input_hash(&self) -> Result<u64, Error> {
+        Ok(self.stable_hash())
     }
 }
 
@@ -1,7 +1,12 @@
 //! The `GeneralizedInput` is an input that ca be generalized to represent a rule, used by Grimoire
 
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
+use core::fmt::Write as _;
+#[cfg(feature = "std")]
+use std::{fs, path::Path};
 
+#[cfg(feature = "std")]
+use libafl_bolts::fs::write_file_atomic;
 use libafl_bolts::impl_serdeany;
 use serde::{Deserialize, Serialize};
 
@@ -13,6 +18,13 @@ use crate::{
     Error, HasMetadata,
 };
 
+/// The marker for a [`GeneralizedItem::Gap`] in the text form of a [`GeneralizedInputMetadata`],
+/// see [`GeneralizedInputMetadata::to_text`]
+pub const GENERALIZED_GAP_MARKER: &str = "\u{ab}GAP\u{bb}";
+
+/// The extension of files holding the text form of a [`GeneralizedInputMetadata`]
+pub const GENERALIZED_TEXT_EXTENSION: &str = "generalized";
+
 /// An item of the generalized input
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum GeneralizedItem {
@@ -103,6 +115,133 @@ impl GeneralizedInputMetadata {
     pub fn generalized_mut(&mut self) -> &mut Vec<GeneralizedItem> {
         &mut self.generalized
     }
+
+    /// A human-readable form of the generalized input, to edit it and load it again with [`Self::from_text`].
+    ///
+    /// Gaps are [`GENERALIZED_GAP_MARKER`]. Printable ASCII bytes are written as they are,
+    /// except for `\`, which becomes `\\`. Newlines, carriage returns and tabs become `\n`, `\r` and `\t`,
+    /// all other bytes `\xHH`. Since the marker is not ASCII, bytes of the marker in the input are always escaped.
+    /// A byte item that is empty, or followed by another byte item, ends with `\|`, so converting back is exact.
+    #[must_use]
+    pub fn to_text(&self) -> String {
+        let mut text = String::with_capacity(self.generalized_len());
+        for (idx, item) in self.generalized.iter().enumerate() {
+            match item {
+                GeneralizedItem::Gap => text.push_str(GENERALIZED_GAP_MARKER),
+                GeneralizedItem::Bytes(bytes) => {
+                    for &byte in bytes {
+                        match byte {
+                            b'\\' => text.push_str("\\\\"),
+                            b'\n' => text.push_str("\\n"),
+                            b'\r' => text.push_str("\\r"),
+                            b'\t' => text.push_str("\\t"),
+                            b' '..=b'~' => text.push(char::from(byte)),
+                            _ => write!(text, "\\x{byte:02x}").unwrap(),
+                        }
+                    }
+                    let next_is_bytes = matches!(
+                        self.generalized.get(idx + 1),
+                        Some(GeneralizedItem::Bytes(_))
+                    );
+                    if bytes.is_empty() || next_is_bytes {
+                        text.push_str("\\|");
+                    }
+                }
+            }
+        }
+        text
+    }
+
+    /// Parse the text form written by [`Self::to_text`].
+    ///
+    /// Line breaks in the text are ignored, so it can be wrapped; use `\n` and `\r` for line breaks in the input.
+    /// Characters that are not ASCII stand for their UTF-8 bytes. `\|` ends a byte item, even an empty one.
+    pub fn from_text(text: &str) -> Result<Self, Error> {
+        let mut generalized = vec![];
+        let mut bytes = vec![];
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            if let Some(after) = rest.strip_prefix(GENERALIZED_GAP_MARKER) {
+                if !bytes.is_empty() {
+                    generalized.push(GeneralizedItem::Bytes(core::mem::take(&mut bytes)));
+                }
+                generalized.push(GeneralizedItem::Gap);
+                rest = after;
+                continue;
+            }
+            rest = &rest[c.len_utf8()..];
+            match c {
+                '\n' | '\r' => {}
+                '\\' => {
+                    let offset = text.len() - rest.len() - 1;
+                    let escaped = rest.chars().next();
+                    let (byte, len) = match escaped {
+                        Some('\\') => (b'\\', 1),
+                        Some('n') => (b'\n', 1),
+                        Some('r') => (b'\r', 1),
+                        Some('t') => (b'\t', 1),
+                        Some('|') => {
+                            generalized.push(GeneralizedItem::Bytes(core::mem::take(&mut bytes)));
+                            rest = &rest[1..];
+                            continue;
+                        }
+                        Some('x') => {
+                            let byte = rest
+                                .get(1..3)
+                                .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                                .ok_or_else(|| {
+                                    Error::illegal_argument(format!(
+                                        "Invalid \\x escape at offset {offset} of generalized input"
+                                    ))
+                                })?;
+                            (byte, 3)
+                        }
+                        _ => {
+                            return Err(Error::illegal_argument(format!(
+                                "Invalid escape {escaped:?} at offset {offset} of generalized input"
+                            )))
+                        }
+                    };
+                    bytes.push(byte);
+                    rest = &rest[len..];
+                }
+                c => {
+                    let mut buf = [0; 4];
+                    bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                }
+            }
+        }
+        if !bytes.is_empty() {
+            generalized.push(GeneralizedItem::Bytes(bytes));
+        }
+        Ok(Self { generalized })
+    }
+
+    /// Write the text form, see [`Self::to_text`], to a file
+    #[cfg(feature = "std")]
+    pub fn to_text_file<P>(&self, path: P) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+    {
+        write_file_atomic(path, self.to_text().as_bytes())
+    }
+
+    /// Load a generalized input from a file with its text form, see [`Self::from_text`]
+    #[cfg(feature = "std")]
+    pub fn from_text_file<P>(path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|err| {
+            Error::illegal_argument(format!(
+                "Could not read generalized input {}: {err}",
+                path.display()
+            ))
+        })?;
+        Self::from_text(&text)
+    }
 }
 
 impl<S> MutatedTransform<BytesInput, S> for GeneralizedInputMetadata
@@ -131,3 +270,106 @@ where
 }
 
 impl<S> MutatedTransformPost<S> for GeneralizedInputMetadata where S: HasCorpus {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+    use core::num::NonZero;
+
+    use libafl_bolts::rands::{Rand, StdRand};
+
+    use super::{GeneralizedInputMetadata, GeneralizedItem, GENERALIZED_GAP_MARKER};
+
+    fn roundtrip(generalized: Vec<GeneralizedItem>) -> alloc::string::String {
+        let meta = GeneralizedInputMetadata { generalized };
+        let text = meta.to_text();
+        assert_eq!(GeneralizedInputMetadata::from_text(&text).unwrap(), meta);
+        text
+    }
+
+    #[test]
+    fn test_generalized_text_roundtrip() {
+        let text = roundtrip(vec![
+            GeneralizedItem::Gap,
+            GeneralizedItem::Bytes(b"GET /a\\b\r\n\t\x00\xff".to_vec()),
+            GeneralizedItem::Gap,
+            GeneralizedItem::Gap,
+        ]);
+        assert_eq!(
+            text,
+            "\u{ab}GAP\u{bb}GET /a\\\\b\\r\\n\\t\\x00\\xff\u{ab}GAP\u{bb}\u{ab}GAP\u{bb}"
+        );
+
+        // the marker, and things that look like escapes, in the input itself
+        let text = roundtrip(vec![
+            GeneralizedItem::Bytes(GENERALIZED_GAP_MARKER.as_bytes().to_vec()),
+            GeneralizedItem::Gap,
+            GeneralizedItem::Bytes(b"\\x41\\n\\".to_vec()),
+        ]);
+        assert!(!text.starts_with(GENERALIZED_GAP_MARKER));
+        assert_eq!(text.matches(GENERALIZED_GAP_MARKER).count(), 1);
+
+        // a partial marker
+        roundtrip(vec![GeneralizedItem::Bytes(
+            "\u{ab}GAP".as_bytes().to_vec(),
+        )]);
+        roundtrip(vec![]);
+
+        // adjacent and empty byte items
+        let text = roundtrip(vec![
+            GeneralizedItem::Bytes(b"a".to_vec()),
+            GeneralizedItem::Bytes(vec![]),
+            GeneralizedItem::Bytes(b"b".to_vec()),
+            GeneralizedItem::Gap,
+            GeneralizedItem::Bytes(vec![]),
+        ]);
+        assert_eq!(text, "a\\|\\|b\u{ab}GAP\u{bb}\\|");
+    }
+
+    #[test]
+    fn test_generalized_text_roundtrip_random() {
+        // bytes that are escaped, or parts of escapes and the marker
+        let mut alphabet = b"\\|xnrt0a \n\r\t\x00\xff".to_vec();
+        alphabet.extend_from_slice(GENERALIZED_GAP_MARKER.as_bytes());
+
+        let mut rand = StdRand::with_seed(0);
+        for _ in 0..10_000 {
+            let items = rand.below(NonZero::new(8).unwrap());
+            let generalized = (0..items)
+                .map(|_| {
+                    if rand.coinflip(0.3) {
+                        GeneralizedItem::Gap
+                    } else {
+                        let len = rand.below(NonZero::new(6).unwrap());
+                        GeneralizedItem::Bytes(
+                            (0..len).map(|_| *rand.choose(&alphabet).unwrap()).collect(),
+                        )
+                    }
+                })
+                .collect();
+            roundtrip(generalized);
+        }
+    }
+
+    #[test]
+    fn test_generalized_text_edited() {
+        // line breaks are ignored, raw unicode is taken as UTF-8
+        let meta =
+            GeneralizedInputMetadata::from_text("\u{ab}GAP\u{bb}a\nb\r\n\u{e4}\u{ab}GAP\u{bb}\n")
+                .unwrap();
+        assert_eq!(
+            meta.generalized(),
+            [
+                GeneralizedItem::Gap,
+                GeneralizedItem::Bytes("ab\u{e4}".as_bytes().to_vec()),
+                GeneralizedItem::Gap,
+            ]
+        );
+
+        assert!(GeneralizedInputMetadata::from_text("a\\").is_err());
+        assert!(GeneralizedInputMetadata::from_text("a\\q").is_err());
+        assert!(GeneralizedInputMetadata::from_text("a\\x4").is_err());
+        assert!(GeneralizedInputMetadata::from_text("a\\x+1").is_err());
+        assert!(GeneralizedInputMetadata::from_text("a\\x\u{e4}").is_err());
+    }
+}
@@ -6,9 +6,15 @@ pub use bytes::BytesInput;
 pub mod value;
 pub use value::ValueInput;
 
+pub mod ranged;
+pub use ranged::*;
+
 pub mod encoded;
 pub use encoded::*;
 
+pub mod codec;
+pub use codec::*;
+
 pub mod gramatron;
 pub use gramatron::*;
 
@@ -18,6 +24,12 @@ pub use generalized::*;
 pub mod bytessub;
 pub use bytessub::BytesSubInput;
 
+pub mod shared_bytes;
+pub use shared_bytes::SharedBytesInput;
+
+pub mod stable_hash;
+pub use stable_hash::*;
+
 #[cfg(feature = "multipart_inputs")]
 pub mod multi;
 #[cfg(feature = "multipart_inputs")]
@@ -69,6 +81,13 @@ pub trait Input: Clone + Serialize + serde::de::DeserializeOwned + Debug {
 
     /// Generate a name for this input
     fn generate_name(&self, id: Option<CorpusId>) -> String;
+
+    /// A hash of this input that stays the same across runs and machines, to recognize the same input.
+    ///
+    /// Inputs with a [`StableHash`] return it, others hash their serialized bytes, see [`stable_hash_serialized`].
+    fn input_hash(&self) -> Result<u64, Error> {
+        stable_hash_serialized(self)
+    }
 }
 
 /// An input for the target
@@ -95,6 +114,13 @@ pub trait Input: Clone + Serialize + serde::de::DeserializeOwned + Debug {
 
     /// Generate a name for this input, the user is responsible for making each name of testcase unique.
     fn generate_name(&self, id: Option<CorpusId>) -> String;
+
+    /// A hash of this input that stays the same across runs and machines, to recognize the same input.
+    ///
+    /// Inputs with a [`StableHash`] return it, others hash their serialized bytes, see [`stable_hash_serialized`].
+    fn input_hash(&self) -> Result<u64, Error> {
+        stable_hash_serialized(self)
+    }
 }
 
 /// Convert between two input types with a state
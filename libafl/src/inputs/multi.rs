@@ -12,7 +12,11 @@ use alloc::{
 use arrayvec::ArrayVec;
 use serde::{Deserialize, Serialize};
 
-use crate::{corpus::CorpusId, inputs::Input};
+use crate::{
+    corpus::CorpusId,
+    inputs::{Input, StableHash, StableHasher},
+    Error,
+};
 
 /// An input composed of multiple parts. Use in situations where subcomponents are not necessarily
 /// related, or represent distinct parts of the input.
@@ -123,6 +127,26 @@ impl<I> MultipartInput<I> {
             .filter_map(move |(i, (s, item))| (s == name).then_some((i, item)))
     }
 
+    /// Gets the first part with the provided name.
+    #[must_use]
+    pub fn part_by_name(&self, name: &str) -> Option<&I> {
+        self.parts_by_name(name).next().map(|(_, part)| part)
+    }
+
+    /// Gets the first part with the provided name mutably.
+    pub fn part_by_name_mut(&mut self, name: &str) -> Option<&mut I> {
+        self.parts_by_name_mut(name).next().map(|(_, part)| part)
+    }
+
+    /// Gets each part with the provided name, in the order they were added.
+    /// Names may repeat, e.g., for sections that occur multiple times.
+    pub fn parts_with_name<'a, 'b>(&'b self, name: &'a str) -> impl Iterator<Item = &'b I> + 'a
+    where
+        'b: 'a,
+    {
+        self.parts_by_name(name).map(|(_, part)| part)
+    }
+
     /// Adds a part to this input, potentially with the same name as an existing part.
     pub fn add_part(&mut self, name: String, part: I) {
         self.parts.push(part);
@@ -166,4 +190,15 @@ where
                 .join(",")
         }
     }
+
+    /// The same as the [`crate::inputs::StableHash`] of this input, if the parts have one
+    fn input_hash(&self) -> Result<u64, Error> {
+        let mut hasher = StableHasher::new();
+        hasher.write_len(self.parts.len());
+        for (name, part) in self.names.iter().zip(&self.parts) {
+            name.as_bytes().stable_hash_into(&mut hasher);
+            hasher.write_u64(part.input_hash()?);
+        }
+        Ok(hasher.finish())
+    }
 }
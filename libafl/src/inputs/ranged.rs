@@ -0,0 +1,572 @@
+//! Numeric inputs with an optional, inclusive range, see [`RangedValueInput`].
+//!
+//! The mutators in [`crate::mutators::ranged`] and the [`crate::generators::RangedGenerator`]
+//! keep the values of these inputs in their range.
+
+use alloc::string::String;
+use core::fmt::Debug;
+
+use libafl_bolts::rands::Rand;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+#[cfg(feature = "multipart_inputs")]
+use crate::inputs::MultipartInput;
+use crate::{
+    corpus::CorpusId,
+    inputs::{Input, StableHash, StableHasher},
+    Error,
+};
+
+/// A number a [`RangedValueInput`] can hold
+pub trait RangedNumeric:
+    Copy + PartialOrd + Debug + Serialize + DeserializeOwned + 'static
+{
+    /// The smallest (finite) value of this type
+    const MIN: Self;
+    /// The largest (finite) value of this type
+    const MAX: Self;
+
+    /// The value halfway between `self` and `other`, rounded down for integers
+    #[must_use]
+    fn midpoint(self, other: Self) -> Self;
+
+    /// Checks if this is not a number
+    fn is_nan(self) -> bool {
+        false
+    }
+
+    /// A random value from `min` to `max`, both inclusive, with the same probability
+    fn sample_uniform<R>(rand: &mut R, min: Self, max: Self) -> Self
+    where
+        R: Rand;
+
+    /// A random value from `min` to `max`, both inclusive, where each order of magnitude
+    /// is equally likely: small values are common, large ones rare.
+    ///
+    /// Falls back to [`RangedNumeric::sample_uniform`] if the range contains negative numbers.
+    fn sample_log_uniform<R>(rand: &mut R, min: Self, max: Self) -> Self
+    where
+        R: Rand;
+
+    /// The value as 8 little-endian bytes, for [`StableHash`]: integers are sign-extended, floats are their bits
+    fn to_stable_bits(self) -> u64;
+}
+
+/// A random number below `bound`, `0` meaning `2^64`
+#[allow(clippy::cast_possible_truncation)]
+fn below_u64<R>(rand: &mut R, bound: u64) -> u64
+where
+    R: Rand,
+{
+    if bound == 0 {
+        rand.next()
+    } else {
+        ((u128::from(rand.next()) * u128::from(bound)) >> 64) as u64
+    }
+}
+
+/// A random number from `min` to `max`, both inclusive, at most `2^64` apart
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn between_i128<R>(rand: &mut R, min: i128, max: i128) -> i128
+where
+    R: Rand,
+{
+    // wraps to `0`, i.e. `2^64`, for the full range of 64 bit types
+    let span = (max - min + 1) as u64;
+    min + i128::from(below_u64(rand, span))
+}
+
+/// A log-uniform random number from `min` to `max`, both inclusive, at most `2^64` apart
+fn log_between_i128<R>(rand: &mut R, min: i128, max: i128) -> i128
+where
+    R: Rand,
+{
+    if min < 0 {
+        return between_i128(rand, min, max);
+    }
+    let bit_len = |value: i128| 128 - value.leading_zeros() as usize;
+    let bits = rand.between(bit_len(min), bit_len(max));
+    let (lo, hi) = if bits == 0 {
+        (0, 0)
+    } else {
+        (1_i128 << (bits - 1), (1_i128 << bits) - 1)
+    };
+    between_i128(rand, lo.max(min), hi.min(max))
+}
+
+macro_rules! impl_ranged_numeric_int {
+    ($($t:ty)*) => ($(
+        // the casts go through `i128`, which holds all these types
+        #[allow(
+            trivial_numeric_casts,
+            clippy::cast_lossless,
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss
+        )]
+        impl RangedNumeric for $t {
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+
+            #[inline]
+            fn midpoint(self, other: Self) -> Self {
+                ((self as i128 + other as i128) >> 1) as $t
+            }
+
+            fn sample_uniform<R>(rand: &mut R, min: Self, max: Self) -> Self
+            where
+                R: Rand,
+            {
+                between_i128(rand, min as i128, max as i128) as $t
+            }
+
+            fn sample_log_uniform<R>(rand: &mut R, min: Self, max: Self) -> Self
+            where
+                R: Rand,
+            {
+                log_between_i128(rand, min as i128, max as i128) as $t
+            }
+
+            #[inline]
+            fn to_stable_bits(self) -> u64 {
+                self as i128 as u64
+            }
+        }
+    )*)
+}
+
+impl_ranged_numeric_int!(u8 u16 u32 u64 usize i8 i16 i32 i64 isize);
+
+macro_rules! impl_ranged_numeric_float {
+    ($($t:ty)*) => ($(
+        impl RangedNumeric for $t {
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+
+            #[inline]
+            fn midpoint(self, other: Self) -> Self {
+                // halve first, so large values don't overflow
+                self / 2.0 + other / 2.0
+            }
+
+            #[inline]
+            fn is_nan(self) -> bool {
+                <$t>::is_nan(self)
+            }
+
+            #[allow(trivial_numeric_casts, clippy::cast_possible_truncation)]
+            fn sample_uniform<R>(rand: &mut R, min: Self, max: Self) -> Self
+            where
+                R: Rand,
+            {
+                let unit = rand.next_float();
+                // interpolate instead of `min + (max - min) * unit`, which overflows for wide ranges
+                let value = (f64::from(min) * (1.0 - unit) + f64::from(max) * unit) as $t;
+                value.clamp(min, max)
+            }
+
+            #[allow(trivial_numeric_casts, clippy::cast_possible_truncation)]
+            fn sample_log_uniform<R>(rand: &mut R, min: Self, max: Self) -> Self
+            where
+                R: Rand,
+            {
+                if min <= 0.0 {
+                    return Self::sample_uniform(rand, min, max);
+                }
+                let (log_min, log_max) = (libm::log(f64::from(min)), libm::log(f64::from(max)));
+                let unit = rand.next_float();
+                let value = libm::exp(log_min * (1.0 - unit) + log_max * unit) as $t;
+                value.clamp(min, max)
+            }
+
+            #[inline]
+            fn to_stable_bits(self) -> u64 {
+                u64::from(self.to_bits())
+            }
+        }
+    )*)
+}
+
+impl_ranged_numeric_float!(f32 f64);
+
+/// A number with an optional, inclusive range.
+///
+/// The value is always kept in the range: setting a value outside of it clamps it to the nearest bound,
+/// `NaN` goes to the lower bound. Deserializing a value outside of its range fails.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(bound = "T: RangedNumeric", try_from = "UncheckedRangedValue<T>")]
+pub struct RangedValueInput<T> {
+    value: T,
+    min: Option<T>,
+    max: Option<T>,
+}
+
+/// A deserialized [`RangedValueInput`], before checking the value is in range
+#[derive(Deserialize)]
+#[serde(bound = "T: RangedNumeric")]
+struct UncheckedRangedValue<T> {
+    value: T,
+    min: Option<T>,
+    max: Option<T>,
+}
+
+impl<T> TryFrom<UncheckedRangedValue<T>> for RangedValueInput<T>
+where
+    T: RangedNumeric,
+{
+    type Error = Error;
+
+    fn try_from(unchecked: UncheckedRangedValue<T>) -> Result<Self, Self::Error> {
+        let UncheckedRangedValue { value, min, max } = unchecked;
+        if min.is_some_and(RangedNumeric::is_nan) || max.is_some_and(RangedNumeric::is_nan) {
+            return Err(Error::illegal_argument(
+                "The bounds of a ranged value are NaN",
+            ));
+        }
+        if let (Some(min), Some(max)) = (min, max) {
+            if min > max {
+                return Err(Error::illegal_argument(format!(
+                    "The range {min:?}..={max:?} of a ranged value is empty"
+                )));
+            }
+        }
+        let input = Self { value, min, max };
+        if input.clamp(value).to_stable_bits() != value.to_stable_bits() {
+            return Err(Error::illegal_argument(format!(
+                "The ranged value {value:?} is not in {:?}..={:?}",
+                input.lower(),
+                input.upper()
+            )));
+        }
+        Ok(input)
+    }
+}
+
+impl<T> From<T> for RangedValueInput<T>
+where
+    T: RangedNumeric,
+{
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T> RangedValueInput<T>
+where
+    T: RangedNumeric,
+{
+    /// Create a new [`RangedValueInput`] without a range
+    pub fn new(value: T) -> Self {
+        let mut input = Self {
+            value,
+            min: None,
+            max: None,
+        };
+        input.set_value(value);
+        input
+    }
+
+    /// Keep the value at least `min`, clamping the current value
+    #[must_use]
+    pub fn with_min(mut self, min: T) -> Self {
+        self.min = Some(min);
+        self.set_value(self.value);
+        self
+    }
+
+    /// Keep the value at most `max`, clamping the current value
+    #[must_use]
+    pub fn with_max(mut self, max: T) -> Self {
+        self.max = Some(max);
+        self.set_value(self.value);
+        self
+    }
+
+    /// Keep the value from `min` to `max`, both inclusive, clamping the current value
+    #[must_use]
+    pub fn with_range(self, min: T, max: T) -> Self {
+        self.with_min(min).with_max(max)
+    }
+
+    /// The value
+    pub fn value(&self) -> T {
+        self.value
+    }
+
+    /// Set the value, clamped to the range
+    pub fn set_value(&mut self, value: T) {
+        self.value = self.clamp(value);
+    }
+
+    /// The lower bound of the range, [`RangedNumeric::MIN`] if there is none
+    pub fn lower(&self) -> T {
+        self.min.unwrap_or(T::MIN)
+    }
+
+    /// The upper bound of the range, [`RangedNumeric::MAX`] if there is none.
+    ///
+    /// If the upper bound is below the lower bound, the lower bound wins.
+    pub fn upper(&self) -> T {
+        let upper = self.max.unwrap_or(T::MAX);
+        if upper < self.lower() {
+            self.lower()
+        } else {
+            upper
+        }
+    }
+
+    /// Clamp `value` into the range of this input
+    pub fn clamp(&self, value: T) -> T {
+        if value.is_nan() || value < self.lower() {
+            self.lower()
+        } else if value > self.upper() {
+            self.upper()
+        } else {
+            value
+        }
+    }
+}
+
+impl<T> StableHash for RangedValueInput<T>
+where
+    T: RangedNumeric,
+{
+    fn stable_hash_into(&self, hasher: &mut StableHasher) {
+        hasher.write_u64(self.value.to_stable_bits());
+    }
+}
+
+impl<T> Input for RangedValueInput<T>
+where
+    T: RangedNumeric,
+{
+    fn generate_name(&self, _id: Option<CorpusId>) -> String {
+        format!("{:016x}", self.stable_hash())
+    }
+
+    fn input_hash(&self) -> Result<u64, Error> {
+        Ok(self.stable_hash())
+    }
+}
+
+/// The operations the ranged mutators and generators need, see [`crate::mutators::ranged`]
+pub trait RangedInput {
+    /// Set the value to the upper (`upper`) or lower bound. Returns `false` if the value did not change.
+    fn to_bound(&mut self, upper: bool) -> bool;
+
+    /// Set the value to halfway between this and the `other` value.
+    /// Returns `false` if the value did not change, or `other` holds a different type.
+    fn average_with(&mut self, other: &Self) -> bool;
+
+    /// Set a random value in range, see [`RangedNumeric::sample_uniform`] and [`RangedNumeric::sample_log_uniform`]
+    fn resample<R>(&mut self, rand: &mut R, log_uniform: bool)
+    where
+        R: Rand;
+}
+
+impl<T> RangedValueInput<T>
+where
+    T: RangedNumeric,
+{
+    /// Set the value, clamped to the range, returning `true` if it changed
+    pub fn replace(&mut self, value: T) -> bool {
+        let old = self.value;
+        self.set_value(value);
+        // compare bits, so `-0.0` and `0.0` differ
+        old.to_stable_bits() != self.value.to_stable_bits()
+    }
+}
+
+impl<T> RangedInput for RangedValueInput<T>
+where
+    T: RangedNumeric,
+{
+    fn to_bound(&mut self, upper: bool) -> bool {
+        self.replace(if upper { self.upper() } else { self.lower() })
+    }
+
+    fn average_with(&mut self, other: &Self) -> bool {
+        self.replace(self.value.midpoint(other.value))
+    }
+
+    fn resample<R>(&mut self, rand: &mut R, log_uniform: bool)
+    where
+        R: Rand,
+    {
+        let value = if log_uniform {
+            T::sample_log_uniform(rand, self.lower(), self.upper())
+        } else {
+            T::sample_uniform(rand, self.lower(), self.upper())
+        };
+        self.set_value(value);
+    }
+}
+
+macro_rules! impl_ranged_scalar {
+    ($($t:ty => $variant:ident, $name:ident),+ $(,)?) => {
+        $(
+            /// Input wrapping a ranged <$t>
+            pub type $name = RangedValueInput<$t>;
+
+            impl From<RangedValueInput<$t>> for RangedScalar {
+                fn from(value: RangedValueInput<$t>) -> Self {
+                    Self::$variant(value)
+                }
+            }
+        )*
+
+        /// One [`RangedValueInput`] of any type, to combine values of different types in one input,
+        /// see [`RangedStructInput`]
+        #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+        #[allow(missing_docs)]
+        pub enum RangedScalar {
+            $($variant(RangedValueInput<$t>)),*
+        }
+
+        impl StableHash for RangedScalar {
+            fn stable_hash_into(&self, hasher: &mut StableHasher) {
+                match self {
+                    $(Self::$variant(value) => value.stable_hash_into(hasher)),*
+                }
+            }
+        }
+
+        impl RangedInput for RangedScalar {
+            fn to_bound(&mut self, upper: bool) -> bool {
+                match self {
+                    $(Self::$variant(value) => value.to_bound(upper)),*
+                }
+            }
+
+            fn average_with(&mut self, other: &Self) -> bool {
+                match (self, other) {
+                    $((Self::$variant(value), Self::$variant(other)) => value.average_with(other),)*
+                    _ => false,
+                }
+            }
+
+            fn resample<R>(&mut self, rand: &mut R, log_uniform: bool)
+            where
+                R: Rand,
+            {
+                match self {
+                    $(Self::$variant(value) => value.resample(rand, log_uniform)),*
+                }
+            }
+        }
+    };
+}
+
+impl_ranged_scalar!(
+    u8 => U8, RangedU8Input,
+    u16 => U16, RangedU16Input,
+    u32 => U32, RangedU32Input,
+    u64 => U64, RangedU64Input,
+    usize => Usize, RangedUsizeInput,
+    i8 => I8, RangedI8Input,
+    i16 => I16, RangedI16Input,
+    i32 => I32, RangedI32Input,
+    i64 => I64, RangedI64Input,
+    isize => Isize, RangedIsizeInput,
+    f32 => F32, RangedF32Input,
+    f64 => F64, RangedF64Input,
+);
+
+impl Input for RangedScalar {
+    fn generate_name(&self, _id: Option<CorpusId>) -> String {
+        format!("{:016x}", self.stable_hash())
+    }
+
+    fn input_hash(&self) -> Result<u64, Error> {
+        Ok(self.stable_hash())
+    }
+}
+
+/// Several named [`RangedValueInput`]s of different types, e.g., the arguments of an API.
+///
+/// The ranged mutators mutate one random field at a time, see [`crate::mutators::ranged`].
+#[cfg(feature = "multipart_inputs")]
+pub type RangedStructInput = MultipartInput<RangedScalar>;
+
+#[cfg(test)]
+mod tests {
+    use libafl_bolts::rands::StdRand;
+
+    use super::{RangedInput, RangedNumeric, RangedScalar, RangedValueInput};
+
+    #[test]
+    fn test_ranged_clamp() {
+        let mut input = RangedValueInput::new(200_u8).with_range(10, 100);
+        assert_eq!(input.value(), 100);
+        input.set_value(3);
+        assert_eq!(input.value(), 10);
+
+        assert!(!input.to_bound(false));
+        assert!(input.replace(11));
+        assert_eq!(input.value(), 11);
+        assert!(input.to_bound(true));
+        assert!(!input.replace(101));
+        assert_eq!(input.value(), 100);
+
+        let mut float = RangedValueInput::new(0.5_f64).with_range(-1.0, 1.0);
+        float.set_value(f64::NAN);
+        assert!((float.value() - -1.0).abs() < f64::EPSILON);
+        assert!(float.replace(f64::INFINITY));
+        assert!((float.value() - 1.0).abs() < f64::EPSILON);
+
+        // mismatching types don't mix
+        let mut scalar = RangedScalar::from(RangedValueInput::new(8_i32));
+        assert!(!scalar.average_with(&RangedScalar::from(RangedValueInput::new(2_u8))));
+        assert!(scalar.average_with(&RangedScalar::from(RangedValueInput::new(2_i32))));
+        assert_eq!(scalar, RangedScalar::from(RangedValueInput::new(5_i32)));
+    }
+
+    #[test]
+    fn test_ranged_sample() {
+        let mut rand = StdRand::with_seed(0);
+        for _ in 0..1000 {
+            let value = i16::sample_uniform(&mut rand, -3, 3);
+            assert!((-3..=3).contains(&value));
+            let value = f32::sample_uniform(&mut rand, f32::MIN, f32::MAX);
+            assert!(value.is_finite());
+            let value = f64::sample_log_uniform(&mut rand, 1.0, 1e9);
+            assert!((1.0..=1e9).contains(&value));
+        }
+
+        // the full range of 64 bit types works, too
+        let negative = (0..1000)
+            .filter(|_| i64::sample_uniform(&mut rand, i64::MIN, i64::MAX) < 0)
+            .count();
+        assert!((400..600).contains(&negative), "{negative}");
+
+        // log-uniform sizes are mostly small
+        let small = (0..1000)
+            .filter(|_| usize::sample_log_uniform(&mut rand, 1, 1 << 20) < 1 << 10)
+            .count();
+        assert!(small > 400, "{small}");
+        let small = (0..1000)
+            .filter(|_| usize::sample_uniform(&mut rand, 1, 1 << 20) < 1 << 10)
+            .count();
+        assert!(small < 20, "{small}");
+    }
+
+    #[test]
+    fn test_ranged_serialize() {
+        let input = RangedValueInput::new(7_u32).with_range(1, 4096);
+        let bytes = postcard::to_allocvec(&input).unwrap();
+        let restored: RangedValueInput<u32> = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(restored, input);
+        assert_eq!(restored.upper(), 4096);
+
+        // values out of range, empty and NaN ranges are rejected
+        let unchecked = |value: f64, min: Option<f64>, max: Option<f64>| {
+            let bytes = postcard::to_allocvec(&(value, min, max)).unwrap();
+            postcard::from_bytes::<RangedValueInput<f64>>(&bytes)
+        };
+        assert!(unchecked(0.5, Some(0.0), Some(1.0)).is_ok());
+        assert!(unchecked(2.0, None, None).is_ok());
+        assert!(unchecked(2.0, Some(0.0), Some(1.0)).is_err());
+        assert!(unchecked(-2.0, Some(0.0), None).is_err());
+        assert!(unchecked(0.5, Some(1.0), Some(0.0)).is_err());
+        assert!(unchecked(0.5, Some(f64::NAN), None).is_err());
+    }
+}
@@ -0,0 +1,238 @@
+//! The [`SharedBytesInput`] is a [`BytesInput`] with a copy-on-write buffer.
+//! Clones share the same bytes, only mutating an input copies them.
+
+use alloc::{
+    borrow::ToOwned,
+    string::String,
+    sync::Arc,
+    vec::{self, Vec},
+};
+use core::{hash::Hash, ops::RangeBounds};
+
+use libafl_bolts::{ownedref::OwnedSlice, HasLen};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    corpus::CorpusId,
+    inputs::{BytesInput, HasMutatorBytes, HasTargetBytes, Input, StableHash},
+    Error,
+};
+
+/// A bytes input backed by a shared, copy-on-write buffer.
+///
+/// Cloning it, e.g., to mutate a testcase from the corpus, is `O(1)`.
+/// The buffer is copied once, on the first mutable access of a clone, see [`Arc::make_mut`].
+/// It serializes exactly like a [`BytesInput`], so both can read each other's corpora.
+///
+/// The buffer is a shared [`Vec`] rather than a shared slice, so that [`HasMutatorBytes::splice`]
+/// and [`HasMutatorBytes::drain`] can operate on it in place.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SharedBytesInput {
+    bytes: Arc<Vec<u8>>,
+}
+
+impl SharedBytesInput {
+    /// Creates a new [`SharedBytesInput`]
+    #[must_use]
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes: Arc::new(bytes),
+        }
+    }
+
+    /// Returns `true` if this input and `other` still share the same buffer
+    #[must_use]
+    pub fn shares_bytes_with(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.bytes, &other.bytes)
+    }
+
+    /// The bytes, copied first if the buffer is shared with another input
+    fn make_mut(&mut self) -> &mut Vec<u8> {
+        Arc::make_mut(&mut self.bytes)
+    }
+
+    /// Extracts the bytes, copying them only if the buffer is shared with another input
+    #[must_use]
+    pub fn into_inner(self) -> Vec<u8> {
+        Arc::unwrap_or_clone(self.bytes)
+    }
+}
+
+impl Input for SharedBytesInput {
+    fn generate_name(&self, _id: Option<CorpusId>) -> String {
+        format!("{:016x}", self.stable_hash())
+    }
+
+    fn input_hash(&self) -> Result<u64, Error> {
+        Ok(self.stable_hash())
+    }
+}
+
+impl Serialize for SharedBytesInput {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // Same layout as the newtype `ValueInput<Vec<u8>>`
+        serializer.serialize_newtype_struct("ValueInput", self.bytes.as_slice())
+    }
+}
+
+impl<'de> Deserialize<'de> for SharedBytesInput {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        BytesInput::deserialize(deserializer).map(Self::from)
+    }
+}
+
+impl HasMutatorBytes for SharedBytesInput {
+    fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        self.make_mut()
+    }
+
+    fn resize(&mut self, new_len: usize, value: u8) {
+        self.make_mut().resize(new_len, value);
+    }
+
+    fn extend<'a, I: IntoIterator<Item = &'a u8>>(&mut self, iter: I) {
+        self.make_mut().extend(iter);
+    }
+
+    fn splice<R, I>(&mut self, range: R, replace_with: I) -> vec::Splice<'_, I::IntoIter>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = u8>,
+    {
+        self.make_mut().splice(range, replace_with)
+    }
+
+    fn drain<R>(&mut self, range: R) -> vec::Drain<'_, u8>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.make_mut().drain(range)
+    }
+}
+
+impl HasTargetBytes for SharedBytesInput {
+    #[inline]
+    fn target_bytes(&self) -> OwnedSlice<u8> {
+        OwnedSlice::from(self.bytes.as_slice())
+    }
+}
+
+impl HasLen for SharedBytesInput {
+    fn len(&self) -> usize {
+        self.bytes.len()
+    }
+}
+
+impl AsRef<[u8]> for SharedBytesInput {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl From<&[u8]> for SharedBytesInput {
+    fn from(bytes: &[u8]) -> Self {
+        Self::new(bytes.to_owned())
+    }
+}
+
+impl From<Vec<u8>> for SharedBytesInput {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::new(bytes)
+    }
+}
+
+impl From<BytesInput> for SharedBytesInput {
+    fn from(input: BytesInput) -> Self {
+        Self::new(input.into_inner())
+    }
+}
+
+impl From<SharedBytesInput> for BytesInput {
+    fn from(input: SharedBytesInput) -> Self {
+        Self::new(input.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use libafl_bolts::rands::StdRand;
+
+    use crate::{
+        corpus::{Corpus, InMemoryCorpus, Testcase},
+        feedbacks::ConstFeedback,
+        inputs::{BytesInput, HasMutatorBytes, Input, SharedBytesInput},
+        mutators::{havoc_mutations, Mutator, StdScheduledMutator},
+        state::StdState,
+    };
+
+    #[test]
+    fn test_copy_on_write() {
+        let parent = SharedBytesInput::new(vec![1, 2, 3, 4]);
+        let mut child = parent.clone();
+        assert!(child.shares_bytes_with(&parent));
+
+        // Reading keeps the buffer shared
+        assert_eq!(child.bytes(), &[1, 2, 3, 4]);
+        assert!(child.shares_bytes_with(&parent));
+
+        child.bytes_mut()[0] = 0;
+        child.splice(1..3, [5]);
+        assert!(!child.shares_bytes_with(&parent));
+        assert_eq!(child.bytes(), &[0, 5, 4]);
+        assert_eq!(parent.bytes(), &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_serialization_compat() {
+        let bytes = BytesInput::new(vec![0, 1, 0xff, 0x42]);
+        let shared = SharedBytesInput::from(bytes.clone());
+
+        let serialized = postcard::to_allocvec(&bytes).unwrap();
+        assert_eq!(postcard::to_allocvec(&shared).unwrap(), serialized);
+        assert_eq!(
+            postcard::from_bytes::<SharedBytesInput>(&serialized).unwrap(),
+            shared
+        );
+        assert_eq!(shared.generate_name(None), bytes.generate_name(None));
+    }
+
+    #[test]
+    fn test_havoc() {
+        let mut corpus = InMemoryCorpus::<SharedBytesInput>::new();
+        corpus
+            .add(Testcase::new(b"abcdefgh".to_vec().into()))
+            .unwrap();
+        let parent = corpus.cloned_input_for_id(corpus.first().unwrap()).unwrap();
+
+        let mut feedback = ConstFeedback::new(false);
+        let mut objective = ConstFeedback::new(false);
+        let mut state = StdState::new(
+            StdRand::with_seed(0x1337),
+            corpus,
+            InMemoryCorpus::new(),
+            &mut feedback,
+            &mut objective,
+        )
+        .unwrap();
+        let mut havoc = StdScheduledMutator::new(havoc_mutations());
+
+        for _ in 0..42 {
+            let mut child = parent.clone();
+            havoc.mutate(&mut state, &mut child).unwrap();
+        }
+        // The children copied the bytes before mutating them
+        assert_eq!(parent.bytes(), b"abcdefgh");
+    }
+}
@@ -0,0 +1,229 @@
+//! Hashes of inputs that stay the same across runs, machines, and versions, see [`StableHash`].
+//!
+//! Use them to name inputs on disk, or to deduplicate inputs across campaigns.
+//! [`crate::inputs::Input::input_hash`] returns them, and the event managers use it to recognize the same input.
+
+use core::fmt::{self, Debug, Formatter};
+
+use serde::Serialize;
+use xxhash_rust::xxh3::Xxh3;
+
+#[cfg(feature = "multipart_inputs")]
+use crate::inputs::MultipartInput;
+use crate::{
+    inputs::{
+        BytesInput, EncodedInput, GeneralizedInputMetadata, GeneralizedItem, SharedBytesInput,
+    },
+    Error,
+};
+
+/// The version of the [`StableHash`] algorithm.
+///
+/// It only changes if the hash of some input changes, so hashes of the same version can be compared.
+pub const STABLE_HASH_VERSION: u32 = 1;
+
+/// Feeds the canonical encoding of an input into a hash, see [`StableHash`]
+#[derive(Clone)]
+pub struct StableHasher {
+    xxh3: Xxh3,
+}
+
+impl Debug for StableHasher {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StableHasher")
+            .field("hash", &self.finish())
+            .finish()
+    }
+}
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StableHasher {
+    /// Creates a new [`StableHasher`], `xxh3_64` with seed `0`
+    #[must_use]
+    pub fn new() -> Self {
+        Self { xxh3: Xxh3::new() }
+    }
+
+    /// Feed raw bytes
+    pub fn write(&mut self, bytes: &[u8]) {
+        self.xxh3.update(bytes);
+    }
+
+    /// Feed a number, as 8 little-endian bytes
+    pub fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    /// Feed a length or count, as 8 little-endian bytes on every platform
+    pub fn write_len(&mut self, len: usize) {
+        self.write_u64(len as u64);
+    }
+
+    /// The hash of everything fed so far
+    #[must_use]
+    pub fn finish(&self) -> u64 {
+        self.xxh3.digest()
+    }
+}
+
+/// A hash of an input with a fixed, documented algorithm.
+///
+/// Unlike [`core::hash::Hash`], the hash does not depend on the platform, the Rust version,
+/// or the version of this crate, as long as [`STABLE_HASH_VERSION`] is the same.
+/// The hash is `xxh3_64`, with seed `0`, of a canonical encoding of the input, where lengths and counts
+/// are 8 little-endian bytes:
+/// - bytes ([`BytesInput`], [`SharedBytesInput`]): the length, followed by the bytes
+/// - [`EncodedInput`]: the number of codes, followed by each code as 4 little-endian bytes
+/// - [`crate::inputs::RangedValueInput`]: the value as 8 little-endian bytes, sign-extended for integers,
+///   the bits for floats, without the range
+/// - `MultipartInput`: the number of parts, followed by the name of each part,
+///   encoded like bytes, and the stable hash of the part, as 8 little-endian bytes
+/// - [`GeneralizedInputMetadata`]: the number of items, followed by `0x00` for each gap,
+///   or `0x01` and the bytes, encoded like bytes
+pub trait StableHash {
+    /// Feed the canonical encoding of this input into `hasher`
+    fn stable_hash_into(&self, hasher: &mut StableHasher);
+
+    /// The stable hash of this input
+    #[must_use]
+    fn stable_hash(&self) -> u64 {
+        let mut hasher = StableHasher::new();
+        self.stable_hash_into(&mut hasher);
+        hasher.finish()
+    }
+}
+
+/// The stable hash of an input without a [`StableHash`], its serialized bytes hashed like bytes.
+///
+/// This is the default of [`crate::inputs::Input::input_hash`]. It is only stable as long as the
+/// serialization of the input is.
+pub fn stable_hash_serialized<T>(input: &T) -> Result<u64, Error>
+where
+    T: Serialize + ?Sized,
+{
+    Ok(postcard::to_allocvec(input)?.stable_hash())
+}
+
+impl StableHash for [u8] {
+    fn stable_hash_into(&self, hasher: &mut StableHasher) {
+        hasher.write_len(self.len());
+        hasher.write(self);
+    }
+}
+
+impl StableHash for BytesInput {
+    fn stable_hash_into(&self, hasher: &mut StableHasher) {
+        self.as_ref().as_slice().stable_hash_into(hasher);
+    }
+}
+
+impl StableHash for SharedBytesInput {
+    fn stable_hash_into(&self, hasher: &mut StableHasher) {
+        <Self as AsRef<[u8]>>::as_ref(self).stable_hash_into(hasher);
+    }
+}
+
+impl StableHash for EncodedInput {
+    fn stable_hash_into(&self, hasher: &mut StableHasher) {
+        hasher.write_len(self.codes().len());
+        for code in self.codes() {
+            hasher.write(&code.to_le_bytes());
+        }
+    }
+}
+
+#[cfg(feature = "multipart_inputs")]
+impl<I> StableHash for MultipartInput<I>
+where
+    I: StableHash,
+{
+    fn stable_hash_into(&self, hasher: &mut StableHasher) {
+        hasher.write_len(self.parts().len());
+        for (name, part) in self.names().iter().zip(self.parts()) {
+            name.as_bytes().stable_hash_into(hasher);
+            hasher.write_u64(part.stable_hash());
+        }
+    }
+}
+
+impl StableHash for GeneralizedInputMetadata {
+    fn stable_hash_into(&self, hasher: &mut StableHasher) {
+        hasher.write_len(self.generalized().len());
+        for item in self.generalized() {
+            match item {
+                GeneralizedItem::Gap => hasher.write(&[0]),
+                GeneralizedItem::Bytes(bytes) => {
+                    hasher.write(&[1]);
+                    bytes.as_slice().stable_hash_into(hasher);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use crate::{
+        events::InputHasher,
+        inputs::{
+            BytesInput, EncodedInput, GeneralizedInputMetadata, Input, NopInput, SharedBytesInput,
+            StableHash,
+        },
+    };
+
+    // Golden values: if any of these change, bump `STABLE_HASH_VERSION`
+
+    #[test]
+    fn test_stable_hash_bytes() {
+        let bytes = BytesInput::new(b"LibAFL".to_vec());
+        assert_eq!(bytes.stable_hash(), 0x5503_c98e_0a03_660f);
+        assert_eq!(BytesInput::new(vec![]).stable_hash(), 0xc77b_3abb_6f87_acd9);
+        assert_eq!(
+            SharedBytesInput::from(bytes.clone()).stable_hash(),
+            bytes.stable_hash()
+        );
+        assert_eq!(bytes.generate_name(None), "5503c98e0a03660f");
+        assert_eq!(bytes.input_hash().unwrap(), bytes.stable_hash());
+        assert_eq!(().hash_input(&bytes).unwrap(), bytes.stable_hash());
+    }
+
+    #[test]
+    fn test_stable_hash_encoded() {
+        let encoded = EncodedInput::new(vec![0, 1, 0xdead_beef]);
+        assert_eq!(encoded.stable_hash(), 0x6e6b_9adf_2382_3d24);
+        assert_eq!(encoded.generate_name(None), "6e6b9adf23823d24");
+    }
+
+    #[test]
+    fn test_stable_hash_serialized() {
+        // Inputs without a `StableHash` fall back to their serialized bytes
+        assert_eq!(NopInput {}.input_hash().unwrap(), 0xc77b_3abb_6f87_acd9);
+    }
+
+    #[test]
+    fn test_stable_hash_generalized() {
+        let generalized =
+            GeneralizedInputMetadata::generalized_from_options(&[Some(b'a'), None, Some(b'b')]);
+        assert_eq!(generalized.stable_hash(), 0x56e0_2e1e_455d_3717);
+    }
+
+    #[test]
+    #[cfg(feature = "multipart_inputs")]
+    fn test_stable_hash_multipart() {
+        let mut multipart = crate::inputs::MultipartInput::new();
+        multipart.add_part(
+            alloc::string::String::from("header"),
+            BytesInput::new(b"GET".to_vec()),
+        );
+        multipart.add_part(alloc::string::String::from("body"), BytesInput::new(vec![]));
+        assert_eq!(multipart.stable_hash(), 0x046a_4a45_ac6e_c5e4);
+        assert_eq!(multipart.input_hash().unwrap(), multipart.stable_hash());
+    }
+}
@@ -11,12 +11,12 @@ use libafl_bolts::{generic_hash_std, rands::Rand};
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "std")]
 use {
-    libafl_bolts::{fs::write_file_atomic, Error},
+    libafl_bolts::fs::write_file_atomic,
     std::{fs::File, io::Read, path::Path},
 };
 
-use super::{Input, MappedInput};
-use crate::{corpus::CorpusId, mutators::numeric::Numeric};
+use super::{Input, MappedInput, StableHash};
+use crate::{corpus::CorpusId, mutators::numeric::Numeric, Error};
 
 /// Newtype pattern wrapper around an underlying structure to implement inputs
 ///
@@ -91,7 +91,11 @@ impl_input_for_value_input!(
 /// manually implemented because files can be written more efficiently
 impl Input for ValueInput<Vec<u8>> {
     fn generate_name(&self, _id: Option<CorpusId>) -> String {
-        format!("{:016x}", generic_hash_std(self))
+        format!("{:016x}", self.stable_hash())
+    }
+
+    fn input_hash(&self) -> Result<u64, Error> {
+        Ok(self.stable_hash())
     }
 
     /// Write this input to the file
@@ -81,6 +81,8 @@ pub mod observers;
 pub mod schedulers;
 pub mod stages;
 pub mod state;
+#[cfg(test)]
+pub(crate) mod testing;
 
 pub use fuzzer::*;
 pub use libafl_bolts::{nonzero, Error};
@@ -1,17 +1,27 @@
 //! Monitors that wrap a base monitor and also log to disk using different formats like `JSON` and `TOML`.
 
-use alloc::{string::String, vec::Vec};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 use core::time::Duration;
 use std::{
-    fs::{File, OpenOptions},
+    fs::{self, File, OpenOptions},
     io::Write,
     path::PathBuf,
 };
 
+#[cfg(feature = "gzip")]
+use libafl_bolts::compress::GzipCompressor;
 use libafl_bolts::{current_time, format_duration_hms, ClientId};
 use serde_json::json;
 
-use crate::monitors::{ClientStats, Monitor, NopMonitor};
+#[cfg(feature = "introspection")]
+use crate::monitors::IntrospectionSummary;
+use crate::{
+    monitors::{ClientStats, Monitor, NopMonitor},
+    Error,
+};
 
 /// Wrap a monitor and log the current state of the monitor into a Toml file.
 #[derive(Debug, Clone)]
@@ -154,8 +164,56 @@ impl OnDiskTomlMonitor<NopMonitor> {
     }
 }
 
-#[derive(Debug, Clone)]
+/// What the [`OnDiskJsonMonitor`] writes for each logged update
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JsonRecordMode {
+    /// Every line is a full snapshot of the global stats and of all clients
+    #[default]
+    Snapshot,
+    /// Every line only holds the update that triggered it: the event message and the stats of the sending client
+    Update,
+}
+
+/// When and how the [`OnDiskJsonMonitor`] rotates its log file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct JsonLogRotation {
+    /// Rotate before a line would make the current file larger than this many bytes
+    max_size: Option<u64>,
+    /// Rotate once the current file has been written to for longer than this
+    max_age: Option<Duration>,
+    /// How many rotated files to keep around, the oldest ones get deleted
+    retained: usize,
+    /// Compress rotated files
+    #[cfg(feature = "gzip")]
+    compress: bool,
+}
+
+impl Default for JsonLogRotation {
+    fn default() -> Self {
+        Self {
+            max_size: None,
+            max_age: None,
+            retained: usize::MAX,
+            #[cfg(feature = "gzip")]
+            compress: false,
+        }
+    }
+}
+
+impl JsonLogRotation {
+    fn enabled(&self) -> bool {
+        self.max_size.is_some() || self.max_age.is_some()
+    }
+}
+
 /// Wraps a base monitor and continuously appends the current statistics to a Json lines file.
+///
+/// Optionally, the log gets rotated once it grows too large or too old.
+/// Rotated files are named `<filename>.<unix time in ms>`, so they sort by age,
+/// and with the `gzip` feature they can be gzip-compressed (`<filename>.<unix time in ms>.gz`).
+/// Rotation only ever happens between two records, a line is never split across files.
+/// When the monitor is dropped, the current file is rotated one last time so that it is complete on disk.
+#[derive(Debug)]
 pub struct OnDiskJsonMonitor<F, M>
 where
     F: FnMut(&mut M) -> bool,
@@ -165,6 +223,28 @@ where
     path: PathBuf,
     /// A function that has the current runtime as argument and decides, whether a record should be logged
     log_record: F,
+    record_mode: JsonRecordMode,
+    rotation: JsonLogRotation,
+    /// When this instance first wrote to the current file
+    file_opened: Option<Duration>,
+}
+
+impl<F, M> Clone for OnDiskJsonMonitor<F, M>
+where
+    F: FnMut(&mut M) -> bool + Clone,
+    M: Monitor + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            base: self.base.clone(),
+            path: self.path.clone(),
+            log_record: self.log_record.clone(),
+            record_mode: self.record_mode,
+            rotation: self.rotation,
+            // The clone did not write anything yet, it must not rotate the file on drop
+            file_opened: None,
+        }
+    }
 }
 
 impl<F, M> OnDiskJsonMonitor<F, M>
@@ -183,7 +263,174 @@ where
             base,
             path,
             log_record,
+            record_mode: JsonRecordMode::default(),
+            rotation: JsonLogRotation::default(),
+            file_opened: None,
+        }
+    }
+
+    /// Set what gets written for each logged update
+    #[must_use]
+    pub fn with_record_mode(mut self, record_mode: JsonRecordMode) -> Self {
+        self.record_mode = record_mode;
+        self
+    }
+
+    /// Rotate the log file before it grows larger than `max_size` bytes.
+    /// A single record larger than `max_size` still ends up in a file of its own.
+    #[must_use]
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.rotation.max_size = Some(max_size);
+        self
+    }
+
+    /// Rotate the log file after it has been written to for `max_age`
+    #[must_use]
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.rotation.max_age = Some(max_age);
+        self
+    }
+
+    /// Only keep the `retained` newest rotated files, older ones get deleted. Keeps all of them by default.
+    #[must_use]
+    pub fn with_retained_files(mut self, retained: usize) -> Self {
+        self.rotation.retained = retained;
+        self
+    }
+
+    /// Gzip-compress rotated files, readable with `gzip -d`
+    #[cfg(feature = "gzip")]
+    #[must_use]
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.rotation.compress = compress;
+        self
+    }
+
+    /// The record for a logged update, without trailing newline
+    fn record(&mut self, event_msg: &str, sender_id: ClientId) -> String {
+        let run_time = current_time().saturating_sub(self.base.start_time());
+        #[allow(unused_mut)]
+        let mut record = match self.record_mode {
+            JsonRecordMode::Snapshot => json!({
+                "run_time": run_time,
+                "clients": self.client_stats_count(),
+                "corpus": self.base.corpus_size(),
+                "objectives": self.base.objective_size(),
+                "executions": self.base.total_execs(),
+                "exec_sec": self.base.execs_per_sec(),
+                "client_stats": self.client_stats(),
+            }),
+            JsonRecordMode::Update => json!({
+                "run_time": run_time,
+                "event": event_msg,
+                "client": sender_id.0,
+                "client_stats": self.client_stats().get(sender_id.0 as usize),
+            }),
+        };
+
+        #[cfg(feature = "introspection")]
+        {
+            let summary = match self.record_mode {
+                JsonRecordMode::Snapshot => self.introspection_summary(),
+                JsonRecordMode::Update => {
+                    let mut summary = IntrospectionSummary::new();
+                    if let Some(client) = self.client_stats().get(sender_id.0 as usize) {
+                        summary.add(&client.introspection_monitor);
+                    }
+                    summary
+                }
+            };
+            record["introspection"] = summary
+                .fractions()
+                .map(|(name, fraction)| (name.to_string(), json!(fraction)))
+                .collect::<serde_json::Map<_, _>>()
+                .into();
+        }
+
+        record.to_string()
+    }
+
+    /// Checks if writing `line_len` more bytes to the current file requires a rotation first
+    fn should_rotate(&self, line_len: u64) -> bool {
+        if !self.rotation.enabled() {
+            return false;
+        }
+        let cur_size = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata.len(),
+            Err(_) => return false,
+        };
+        if cur_size == 0 {
+            return false;
+        }
+        if let Some(max_size) = self.rotation.max_size {
+            if cur_size + line_len > max_size {
+                return true;
+            }
+        }
+        if let (Some(max_age), Some(file_opened)) = (self.rotation.max_age, self.file_opened) {
+            if current_time().saturating_sub(file_opened) >= max_age {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Moves the current log file out of the way, compresses it if requested, and deletes old rotated files
+    fn rotate(&mut self) -> Result<(), Error> {
+        self.file_opened = None;
+
+        let Some(file_name) = self.path.file_name().and_then(|name| name.to_str()) else {
+            return Err(Error::illegal_argument(format!(
+                "Cannot rotate the log file {}, it has no valid file name",
+                self.path.display()
+            )));
+        };
+        let prefix = format!("{file_name}.");
+
+        // Zero-padded, so that the rotated files sort by age; never overwrite an older one
+        let mut timestamp = current_time().as_millis();
+        let rotated_name =
+            |timestamp: u128| self.path.with_file_name(format!("{prefix}{timestamp:020}"));
+        let compressed_name = |timestamp: u128| {
+            self.path
+                .with_file_name(format!("{prefix}{timestamp:020}.gz"))
+        };
+        while rotated_name(timestamp).exists() || compressed_name(timestamp).exists() {
+            timestamp += 1;
         }
+        let rotated = rotated_name(timestamp);
+        fs::rename(&self.path, &rotated)?;
+
+        #[cfg(feature = "gzip")]
+        if self.rotation.compress {
+            let compressed = GzipCompressor::new().compress_gzip(&fs::read(&rotated)?);
+            fs::write(compressed_name(timestamp), compressed)?;
+            fs::remove_file(&rotated)?;
+        }
+
+        let dir = match self.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let mut rotated_files = fs::read_dir(dir)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(|name| name.strip_prefix(&prefix))
+                    .is_some_and(|suffix| suffix.starts_with(|c: char| c.is_ascii_digit()))
+            })
+            .collect::<Vec<_>>();
+        if rotated_files.len() > self.rotation.retained {
+            rotated_files.sort();
+            let excess = rotated_files.len() - self.rotation.retained;
+            for old in &rotated_files[..excess] {
+                fs::remove_file(old)?;
+            }
+        }
+
+        Ok(())
     }
 }
 
@@ -210,23 +457,144 @@ where
 
     fn display(&mut self, event_msg: &str, sender_id: ClientId) {
         if (self.log_record)(&mut self.base) {
-            let file = OpenOptions::new()
+            let mut line = self.record(event_msg, sender_id);
+            line.push('\n');
+
+            if self.should_rotate(line.len() as u64) {
+                if let Err(err) = self.rotate() {
+                    log::error!("Failed to rotate {}: {err}", self.path.display());
+                }
+            }
+
+            let mut file = OpenOptions::new()
                 .append(true)
                 .create(true)
                 .open(&self.path)
                 .expect("Failed to open logging file");
-
-            let line = json!({
-                "run_time": current_time() - self.base.start_time(),
-                "clients": self.client_stats_count(),
-                "corpus": self.base.corpus_size(),
-                "objectives": self.base.objective_size(),
-                "executions": self.base.total_execs(),
-                "exec_sec": self.base.execs_per_sec(),
-                "client_stats": self.client_stats(),
-            });
-            writeln!(&file, "{line}").expect("Unable to write Json to file");
+            // A single write, so that the record is never torn
+            file.write_all(line.as_bytes())
+                .expect("Unable to write Json to file");
+            if self.file_opened.is_none() {
+                self.file_opened = Some(current_time());
+            }
         }
         self.base.display(event_msg, sender_id);
     }
 }
+
+impl<F, M> Drop for OnDiskJsonMonitor<F, M>
+where
+    F: FnMut(&mut M) -> bool,
+    M: Monitor,
+{
+    fn drop(&mut self) {
+        if self.rotation.enabled() && self.file_opened.is_some() {
+            if let Err(err) = self.rotate() {
+                log::error!("Failed to rotate {} on exit: {err}", self.path.display());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+    use std::{fs, path::PathBuf, process};
+
+    use libafl_bolts::ClientId;
+
+    use crate::monitors::{JsonRecordMode, Monitor, NopMonitor, OnDiskJsonMonitor};
+
+    fn log_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("libafl_{name}_{}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn rotated_files(dir: &PathBuf) -> Vec<PathBuf> {
+        let mut files = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.file_name().unwrap() != "stats.json")
+            .collect::<Vec<_>>();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_json_rotation() {
+        let dir = log_dir("json_rotation");
+        let path = dir.join("stats.json");
+
+        let mut monitor = OnDiskJsonMonitor::new(&path, NopMonitor::new(), |_| true)
+            .with_max_size(1000)
+            .with_retained_files(2);
+        monitor.client_stats_insert(ClientId(0));
+        for _ in 0..20 {
+            monitor.display("Testcase", ClientId(0));
+        }
+        assert!(fs::metadata(&path).unwrap().len() <= 1000);
+        assert_eq!(rotated_files(&dir).len(), 2);
+
+        drop(monitor);
+        assert!(!path.exists());
+        let rotated = rotated_files(&dir);
+        assert_eq!(rotated.len(), 2);
+        for file in rotated {
+            for line in fs::read_to_string(file).unwrap().lines() {
+                serde_json::from_str::<serde_json::Value>(line).unwrap();
+            }
+        }
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_json_update_records() {
+        let dir = log_dir("json_updates");
+        let path = dir.join("stats.json");
+
+        let mut monitor = OnDiskJsonMonitor::new(&path, NopMonitor::new(), |_| true)
+            .with_record_mode(JsonRecordMode::Update);
+        monitor.client_stats_insert(ClientId(0));
+        monitor.client_stats_insert(ClientId(1));
+        monitor.display("Testcase", ClientId(1));
+        drop(monitor);
+
+        // Without rotation, the log stays in place
+        let log = fs::read_to_string(&path).unwrap();
+        let record: serde_json::Value = serde_json::from_str(log.trim_end()).unwrap();
+        assert_eq!(record["event"], "Testcase");
+        assert_eq!(record["client"], 1);
+        assert!(record.get("clients").is_none());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_json_rotation_compressed() {
+        use libafl_bolts::compress::GzipCompressor;
+
+        let dir = log_dir("json_compressed");
+        let path = dir.join("stats.json");
+
+        let mut monitor = OnDiskJsonMonitor::new(&path, NopMonitor::new(), |_| true)
+            .with_max_size(1 << 20)
+            .with_compression(true);
+        monitor.client_stats_insert(ClientId(0));
+        monitor.display("Testcase", ClientId(0));
+        drop(monitor);
+
+        let rotated = rotated_files(&dir);
+        assert_eq!(rotated.len(), 1);
+        assert_eq!(rotated[0].extension().unwrap(), "gz");
+        let log = GzipCompressor::new()
+            .decompress_gzip(&fs::read(&rotated[0]).unwrap())
+            .unwrap();
+        serde_json::from_slice::<serde_json::Value>(log.trim_ascii_end()).unwrap();
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
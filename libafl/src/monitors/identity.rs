@@ -0,0 +1,239 @@
+//! Who is behind a [`ClientId`]: the host and the process of each client, so that logs and monitors
+//! can show `host-b/pid 4242` instead of an opaque id.
+
+use alloc::string::String;
+use core::{fmt, time::Duration};
+
+use hashbrown::HashMap;
+use libafl_bolts::ClientId;
+use serde::{Deserialize, Serialize};
+
+/// The default amount of clients a [`ClientRegistry`] keeps
+pub const DEFAULT_CLIENT_REGISTRY_CAPACITY: usize = 1024;
+
+/// The host and the process of a client.
+///
+/// Each client sends it once after connecting, see [`crate::events::Event::ClientIdentity`],
+/// and again after each restart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientIdentity {
+    /// The hostname of the machine the client runs on
+    pub hostname: String,
+    /// The process id of the client
+    pub pid: u32,
+    /// A hash of the fuzzer binary, if known
+    pub binary_hash: Option<u64>,
+    /// A free-form label, set by the user
+    pub label: Option<String>,
+}
+
+impl ClientIdentity {
+    /// The identity of this process, with an optional free-form `label`
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn current(label: Option<String>) -> Self {
+        let (high, low) = libafl_bolts::build_id::get().as_u64_pair();
+        Self {
+            hostname: libafl_bolts::os::hostname(),
+            pid: std::process::id(),
+            binary_hash: Some(high ^ low),
+            label,
+        }
+    }
+}
+
+impl fmt::Display for ClientIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/pid {}", self.hostname, self.pid)?;
+        if let Some(label) = &self.label {
+            write!(f, " ({label})")?;
+        }
+        Ok(())
+    }
+}
+
+/// A client in a [`ClientRegistry`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisteredClient {
+    /// The identity the client sent last
+    pub identity: ClientIdentity,
+    /// How often the client registered, one more than its restarts
+    pub registrations: u64,
+    /// When the client was last heard of
+    pub last_seen: Duration,
+}
+
+/// The identities of the clients, by [`ClientId`].
+///
+/// A client registering again, e.g., after a restart, updates its entry.
+/// To stay small over long campaigns, the registry keeps at most `capacity` clients,
+/// dropping the one heard of least recently, and, with [`ClientRegistry::max_age`],
+/// forgets clients not heard of for a while.
+#[derive(Debug, Clone)]
+pub struct ClientRegistry {
+    clients: HashMap<ClientId, RegisteredClient>,
+    capacity: usize,
+    max_age: Option<Duration>,
+}
+
+impl Default for ClientRegistry {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_CLIENT_REGISTRY_CAPACITY)
+    }
+}
+
+impl ClientRegistry {
+    /// Create a new [`ClientRegistry`] keeping up to [`DEFAULT_CLIENT_REGISTRY_CAPACITY`] clients
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new [`ClientRegistry`] keeping up to `capacity` clients, at least one
+    #[must_use]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            clients: HashMap::new(),
+            capacity: capacity.max(1),
+            max_age: None,
+        }
+    }
+
+    /// Forget the clients not heard of for `max_age`
+    #[must_use]
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Register the `identity` of a client at the time `now`, updating its entry if it registered before.
+    ///
+    /// Returns `true` if the client is new.
+    pub fn register(
+        &mut self,
+        client_id: ClientId,
+        identity: ClientIdentity,
+        now: Duration,
+    ) -> bool {
+        self.expire(now);
+        if let Some(client) = self.clients.get_mut(&client_id) {
+            client.identity = identity;
+            client.registrations += 1;
+            client.last_seen = now;
+            return false;
+        }
+        if self.clients.len() >= self.capacity {
+            let oldest = self
+                .clients
+                .iter()
+                .min_by_key(|(_, client)| client.last_seen)
+                .map(|(client_id, _)| *client_id);
+            if let Some(oldest) = oldest {
+                self.clients.remove(&oldest);
+            }
+        }
+        self.clients.insert(
+            client_id,
+            RegisteredClient {
+                identity,
+                registrations: 1,
+                last_seen: now,
+            },
+        );
+        true
+    }
+
+    /// Note that a registered client was heard of at the time `now`
+    pub fn seen(&mut self, client_id: ClientId, now: Duration) {
+        if let Some(client) = self.clients.get_mut(&client_id) {
+            client.last_seen = now;
+        }
+    }
+
+    /// Forget the clients not heard of for the [`ClientRegistry::max_age`] at the time `now`
+    pub fn expire(&mut self, now: Duration) {
+        if let Some(max_age) = self.max_age {
+            self.clients
+                .retain(|_, client| now.saturating_sub(client.last_seen) < max_age);
+        }
+    }
+
+    /// The registered client with this id, if any
+    #[must_use]
+    pub fn get(&self, client_id: ClientId) -> Option<&RegisteredClient> {
+        self.clients.get(&client_id)
+    }
+
+    /// The registered clients, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (ClientId, &RegisteredClient)> {
+        self.clients
+            .iter()
+            .map(|(client_id, client)| (*client_id, client))
+    }
+
+    /// The amount of registered clients
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// If no client is registered
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.clients.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::{String, ToString};
+    use core::time::Duration;
+
+    use libafl_bolts::ClientId;
+
+    use super::{ClientIdentity, ClientRegistry};
+
+    fn identity(pid: u32) -> ClientIdentity {
+        ClientIdentity {
+            hostname: "host-b".into(),
+            pid,
+            binary_hash: None,
+            label: None,
+        }
+    }
+
+    #[test]
+    fn test_client_registry() {
+        let secs = Duration::from_secs;
+        let mut registry = ClientRegistry::with_capacity(2).max_age(secs(100));
+        assert!(registry.register(ClientId(1), identity(4242), secs(0)));
+        assert_eq!(
+            registry.get(ClientId(1)).unwrap().identity.to_string(),
+            "host-b/pid 4242"
+        );
+
+        // A restart updates the entry
+        assert!(!registry.register(ClientId(1), identity(4243), secs(10)));
+        let client = registry.get(ClientId(1)).unwrap();
+        assert_eq!((client.identity.pid, client.registrations), (4243, 2));
+        assert_eq!(registry.len(), 1);
+
+        // The client heard of least recently makes room
+        assert!(registry.register(ClientId(2), identity(1), secs(20)));
+        registry.seen(ClientId(1), secs(30));
+        let mut labeled = identity(2);
+        labeled.label = Some(String::from("asan"));
+        assert!(registry.register(ClientId(3), labeled, secs(40)));
+        assert_eq!(registry.len(), 2);
+        assert!(registry.get(ClientId(2)).is_none());
+        assert_eq!(
+            registry.get(ClientId(3)).unwrap().identity.to_string(),
+            "host-b/pid 2 (asan)"
+        );
+
+        // Clients not heard of for a while are forgotten
+        registry.expire(secs(135));
+        assert!(registry.get(ClientId(1)).is_none());
+        assert!(registry.get(ClientId(3)).is_some());
+    }
+}
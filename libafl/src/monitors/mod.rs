@@ -12,22 +12,107 @@ use alloc::string::ToString;
 
 #[cfg(all(feature = "prometheus_monitor", feature = "std"))]
 pub use prometheus::PrometheusMonitor;
+
+#[cfg(all(feature = "statsd_monitor", feature = "std"))]
+pub mod statsd;
+#[cfg(all(feature = "statsd_monitor", feature = "std"))]
+pub use statsd::StatsdMonitor;
 #[cfg(feature = "std")]
 pub mod disk;
+pub mod identity;
 use alloc::{borrow::Cow, fmt::Debug, string::String, vec::Vec};
 use core::{fmt, fmt::Write, time::Duration};
 
 #[cfg(feature = "std")]
-pub use disk::{OnDiskJsonMonitor, OnDiskTomlMonitor};
+pub use disk::{JsonRecordMode, OnDiskJsonMonitor, OnDiskTomlMonitor};
 use hashbrown::HashMap;
+pub use identity::{ClientIdentity, ClientRegistry, RegisteredClient};
 use libafl_bolts::{current_time, format_duration_hms, ClientId};
 use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "afl_exec_sec")]
 const CLIENT_STATS_TIME_WINDOW_SECS: u64 = 5; // 5 seconds
 
+/// The user stat holding the role of a client in the centralized architecture, `main` or `secondary`
+pub const CENTRALIZED_ROLE_STAT: &str = "role";
+/// The user stat counting the testcases a secondary node forwarded to the main node
+pub const CENTRALIZED_FORWARDED_STAT: &str = "forwarded";
+/// The user stat counting the forwarded testcases the main node accepted
+pub const CENTRALIZED_ACCEPTED_STAT: &str = "forwarded accepted";
+/// The user stat counting the forwarded testcases the main node discarded
+pub const CENTRALIZED_DISCARDED_STAT: &str = "forwarded discarded";
+/// The user stat holding the amount of forwarded messages the main node handled in its last `process` call
+pub const CENTRALIZED_BACKLOG_STAT: &str = "main backlog";
+/// The user stat counting the messages the main node received from itself, which hints at a misconfiguration
+pub const CENTRALIZED_SELF_MESSAGES_STAT: &str = "self messages";
+/// The prefix of the user stats counting the testcases of a secondary the main node deferred,
+/// followed by the secondary, see [`crate::events::CentralizedEventManagerBuilder::forwarding_quota`]
+pub const CENTRALIZED_DEFERRED_STAT: &str = "deferred from";
+/// The prefix of the user stats counting the deferred testcases of a secondary the main node dropped,
+/// followed by the secondary
+pub const CENTRALIZED_DROPPED_STAT: &str = "dropped from";
+/// The user stat holding the events the main loop handled in its last busy iteration,
+/// see [`crate::events::CentralizedEventManager::run_main_loop`]
+pub const CENTRALIZED_LOOP_EVENTS_STAT: &str = "main loop events";
+/// The user stat holding how long the main loop took to handle the events of its last busy iteration,
+/// in milliseconds
+pub const CENTRALIZED_DRAIN_TIME_STAT: &str = "main loop drain ms";
+/// The user stat counting how often the corpus of a secondary did not match the checksum of the main node,
+/// see [`crate::events::CentralizedEventManagerBuilder::corpus_checksum`]
+pub const CENTRALIZED_CHECKSUM_MISMATCHES_STAT: &str = "corpus checksum mismatches";
+/// The user stat counting the stats and logs a secondary node dropped while its LLMP sender was saturated,
+/// see [`crate::events::CentralizedEventManagerBuilder::load_shedding`]
+pub const CENTRALIZED_SHED_STATS_STAT: &str = "shed stats";
+/// The user stat counting the testcases a secondary node held back while its LLMP sender was saturated
+pub const CENTRALIZED_SHED_DEFERRED_STAT: &str = "shed deferred testcases";
+/// The user stat counting the held back testcases a secondary node dropped, as too many were held back
+pub const CENTRALIZED_SHED_DROPPED_STAT: &str = "shed dropped testcases";
+/// The user stat counting the failed attempts of a secondary node to send a message to the main node
+pub const CENTRALIZED_SEND_ERRORS_STAT: &str = "send errors";
+/// The user stat counting the messages a secondary node dropped, as sending them to the main node kept failing
+pub const CENTRALIZED_SEND_DROPPED_STAT: &str = "send dropped";
+
+/// The user stat holding the size of the state snapshot a restarted client was restored from, in bytes
+pub const STATE_SNAPSHOT_SIZE_STAT: &str = "state snapshot size";
+/// The user stat holding how long saving the state snapshot took before a restart, in milliseconds
+pub const STATE_SAVE_TIME_STAT: &str = "state save ms";
+/// The user stat holding how long loading the state snapshot took after a restart, in milliseconds
+pub const STATE_LOAD_TIME_STAT: &str = "state load ms";
+
+/// The user stat holding which budget of the campaign ran out, see [`crate::fuzzer::CampaignBudget`]
+pub const CAMPAIGN_BUDGET_STAT: &str = "budget exhausted";
+
+/// The user stat counting the executions whose observers were serialized, see [`crate::events::AdaptiveSerializer`]
+pub const OBSERVERS_SERIALIZED_STAT: &str = "observers serialized";
+/// The user stat counting the executions whose observers were not serialized, see [`crate::events::AdaptiveSerializer`]
+pub const OBSERVERS_SKIPPED_STAT: &str = "observers skipped";
+/// The user stat holding the bytes of all serialized observers, see [`crate::events::AdaptiveSerializer`]
+pub const OBSERVERS_BYTES_STAT: &str = "observers bytes";
+/// The user stat holding the time spent serializing observers, in microseconds, see [`crate::events::AdaptiveSerializer`]
+pub const OBSERVERS_SERIALIZATION_TIME_STAT: &str = "observers serialization us";
+
+/// The user stat counting the inputs the input filter rejected, see [`crate::fuzzer::StdFuzzer::set_input_filter`]
+pub const SKIPPED_INPUTS_STAT: &str = "skipped inputs";
+
+/// The user stat holding the number of tokens in the dictionary, see [`crate::stages::SyncTokensStage`]
+pub const DICTIONARY_SIZE_STAT: &str = "dictionary size";
+
+/// The user stat holding the number of indices newly covered since the previous snapshot, see [`crate::stages::CoverageDiffStage`]
+pub const NEW_COVERAGE_STAT: &str = "new coverage";
+
+/// The global stat a monitor with a [`StallAlert`] sets to `1` while no client finds anything new, `0` otherwise
+pub const STALLED_STAT: &str = "stalled";
+
+/// The default window for the smoothed executions per second, see [`ClientStats::smoothed_execs_per_sec`]
+pub const DEFAULT_EXEC_SEC_WINDOW: Duration = Duration::from_secs(60);
+
 /// Definition of how we aggreate this across multiple clients
-#[derive(Serialize, Deserialize, Debug, Clone)]
+///
+/// The firing client picks the op for each of its stats, the aggregating monitors honor it for the global stats.
+/// New ops are only ever added right before [`AggregatorOps::Unknown`], so that the serialized form of the existing ones never changes.
+/// An op unknown to the receiving side, e.g., sent by a newer client, is deserialized as [`AggregatorOps::Unknown`]
+/// and aggregated like [`AggregatorOps::Latest`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum AggregatorOps {
     /// Do nothing
     None,
@@ -39,6 +124,14 @@ pub enum AggregatorOps {
     Min,
     /// Get the max
     Max,
+    /// Take the value of the client that updated this stat last
+    Latest,
+    /// Add up the numerators and the denominators of [`UserStatsValue::Ratio`] stats separately,
+    /// so that, e.g., `hits/total` counters of all clients combine to a global `hits/total`
+    Ratio,
+    /// An op this build does not know about; never send this, it is aggregated like [`AggregatorOps::Latest`]
+    #[serde(other)]
+    Unknown,
 }
 
 /// The standard aggregator, plug this into the monitor to use
@@ -73,6 +166,23 @@ impl Aggregator {
             }
         };
 
+        if matches!(op, AggregatorOps::Latest | AggregatorOps::Unknown) {
+            // The most recent update wins, on ties the client with the highest id
+            let latest = client_stats
+                .iter()
+                .filter_map(|client| {
+                    let stat = client.user_monitor.get(name)?;
+                    let updated = client.user_stats_updated.get(name).copied();
+                    Some((updated.unwrap_or_default(), stat))
+                })
+                .max_by_key(|(updated, _)| *updated)
+                .map(|(_, stat)| stat.value().clone());
+            if let Some(latest) = latest {
+                self.aggregated.insert(name.to_string(), latest);
+            }
+            return;
+        }
+
         for item in gather {
             match op {
                 AggregatorOps::None => {
@@ -103,6 +213,15 @@ impl Aggregator {
                         }
                     };
                 }
+                AggregatorOps::Ratio => {
+                    init = match init.stats_ratio_add(item.value()) {
+                        Some(x) => x,
+                        _ => {
+                            return;
+                        }
+                    };
+                }
+                AggregatorOps::Latest | AggregatorOps::Unknown => unreachable!("handled above"),
             }
         }
 
@@ -284,6 +403,14 @@ impl UserStatsValue {
             _ => None,
         }
     }
+
+    /// add up the numerators and the denominators of two ratios
+    pub fn stats_ratio_add(&mut self, other: &Self) -> Option<Self> {
+        match (self, other) {
+            (Self::Ratio(x, a), Self::Ratio(y, b)) => Some(Self::Ratio(*x + *y, *a + *b)),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for UserStats {
@@ -362,10 +489,23 @@ pub struct ClientStats {
     pub last_execs_per_sec: f64,
     /// The last time we got this information
     pub last_window_time: Duration,
+    /// The exponentially smoothed executions per second
+    pub smoothed_execs_per_sec: f64,
+    /// The executions at the last update of the smoothed executions per second
+    pub last_smoothed_executions: u64,
+    /// The time of the last update of the smoothed executions per second
+    pub last_smoothed_time: Duration,
     /// the start time of the client
     pub start_time: Duration,
+    /// The time the last event of this client arrived
+    pub last_update_time: Duration,
     /// User-defined monitor
     pub user_monitor: HashMap<Cow<'static, str>, UserStats>,
+    /// The time each user-defined stat was last updated, used to aggregate with [`AggregatorOps::Latest`]
+    pub user_stats_updated: HashMap<Cow<'static, str>, Duration>,
+    /// The host and the process of this client, once it registered
+    #[serde(default)]
+    pub identity: Option<ClientIdentity>,
     /// Client performance statistics
     #[cfg(feature = "introspection")]
     pub introspection_monitor: ClientPerfMonitor,
@@ -409,6 +549,61 @@ impl ClientStats {
     /// We got a new information about objective corpus size for this client, insert them.
     pub fn update_objective_size(&mut self, objective_size: u64) {
         self.objective_size = objective_size;
+        self.last_objective_time = current_time();
+    }
+
+    /// The time since this client last found a new corpus entry, or since it started if it never did
+    #[must_use]
+    pub fn time_since_last_find(&self, cur_time: Duration) -> Duration {
+        cur_time.saturating_sub(self.last_corpus_time.max(self.start_time))
+    }
+
+    /// The time since this client last found an objective, or since it started if it never did
+    #[must_use]
+    pub fn time_since_last_objective(&self, cur_time: Duration) -> Duration {
+        cur_time.saturating_sub(self.last_objective_time.max(self.start_time))
+    }
+
+    /// The time since the last event of this client arrived, or since it started if none did
+    #[must_use]
+    pub fn time_since_last_update(&self, cur_time: Duration) -> Duration {
+        cur_time.saturating_sub(self.last_update_time.max(self.start_time))
+    }
+
+    /// Get the exponentially smoothed executions per second for this client.
+    ///
+    /// The rate measured since the last call is weighed in with `1 - e^(-elapsed / window)`,
+    /// so rates older than `window` have mostly faded out.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn smoothed_execs_per_sec(&mut self, cur_time: Duration, window: Duration) -> f64 {
+        if self.last_smoothed_time == Duration::ZERO {
+            // First measurement, start from the plain average
+            self.smoothed_execs_per_sec = self.execs_per_sec(cur_time);
+            self.last_smoothed_time = cur_time;
+            self.last_smoothed_executions = self.executions;
+            return self.smoothed_execs_per_sec;
+        }
+
+        let elapsed = cur_time
+            .saturating_sub(self.last_smoothed_time)
+            .as_secs_f64();
+        if elapsed == 0.0 {
+            return self.smoothed_execs_per_sec;
+        }
+
+        let cur_rate = self
+            .executions
+            .saturating_sub(self.last_smoothed_executions) as f64
+            / elapsed;
+        let alpha = if window.is_zero() {
+            1.0
+        } else {
+            1.0 - libm::exp(-elapsed / window.as_secs_f64())
+        };
+        self.smoothed_execs_per_sec += alpha * (cur_rate - self.smoothed_execs_per_sec);
+        self.last_smoothed_time = cur_time;
+        self.last_smoothed_executions = self.executions;
+        self.smoothed_execs_per_sec
     }
 
     /// Get the calculated executions per second for this client
@@ -471,6 +666,7 @@ impl ClientStats {
         name: Cow<'static, str>,
         value: UserStats,
     ) -> Option<UserStats> {
+        self.user_stats_updated.insert(name.clone(), current_time());
         self.user_monitor.insert(name, value)
     }
 
@@ -549,6 +745,39 @@ pub trait Monitor {
         prettify_float(self.execs_per_sec())
     }
 
+    /// Exponentially smoothed executions per second, see [`ClientStats::smoothed_execs_per_sec`]
+    fn smoothed_execs_per_sec(&mut self, window: Duration) -> f64 {
+        let cur_time = current_time();
+        self.client_stats_mut()
+            .iter_mut()
+            .filter(|client| client.enabled)
+            .fold(0.0, |acc, x| {
+                acc + x.smoothed_execs_per_sec(cur_time, window)
+            })
+    }
+
+    /// The time since any client last found a new corpus entry, or since the start if none did
+    fn time_since_last_find(&self) -> Duration {
+        let cur_time = current_time();
+        self.client_stats()
+            .iter()
+            .filter(|client| client.enabled)
+            .map(|client| client.time_since_last_find(cur_time))
+            .min()
+            .unwrap_or_else(|| cur_time.saturating_sub(self.start_time()))
+    }
+
+    /// The time since any client last found an objective, or since the start if none did
+    fn time_since_last_objective(&self) -> Duration {
+        let cur_time = current_time();
+        self.client_stats()
+            .iter()
+            .filter(|client| client.enabled)
+            .map(|client| client.time_since_last_objective(cur_time))
+            .min()
+            .unwrap_or_else(|| cur_time.saturating_sub(self.start_time()))
+    }
+
     /// The client monitor for a specific id, creating new if it doesn't exist
     fn client_stats_insert(&mut self, client_id: ClientId) {
         let total_client_stat_count = self.client_stats().len();
@@ -560,14 +789,15 @@ pub trait Monitor {
                 ..ClientStats::default()
             });
         }
+        let timestamp = current_time();
         let new_stat = self.client_stats_mut_for(client_id);
         if !new_stat.enabled {
-            let timestamp = current_time();
             // I have never seen this man in my life
             new_stat.start_time = timestamp;
             new_stat.last_window_time = timestamp;
             new_stat.enabled = true;
         }
+        new_stat.last_update_time = timestamp;
     }
 
     /// Get mutable reference to client stats
@@ -580,10 +810,193 @@ pub trait Monitor {
         &self.client_stats()[client_id.0 as usize]
     }
 
+    /// The introspection stats of all clients, summed up
+    #[cfg(feature = "introspection")]
+    fn introspection_summary(&self) -> IntrospectionSummary {
+        let mut summary = IntrospectionSummary::new();
+        for client in self.client_stats().iter().filter(|client| client.enabled) {
+            summary.add(&client.introspection_monitor);
+        }
+        summary
+    }
+
     /// Aggregate the results in case there're multiple clients
     fn aggregate(&mut self, _name: &str) {}
 }
 
+/// Alerts when no client found a new corpus entry for a while.
+///
+/// The alert goes out as a `warn` log message, i.e., with the same severity and through the same
+/// channel as a [`crate::events::Event::Log`] arriving in the broker, so it ends up in log files, too.
+/// Monitors using it also expose the [`STALLED_STAT`] global stat for external tooling.
+#[derive(Debug, Clone, Copy)]
+pub struct StallAlert {
+    timeout: Duration,
+    stalled: bool,
+}
+
+impl StallAlert {
+    /// Create a new [`StallAlert`] that fires when nothing was found for `timeout`
+    #[must_use]
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            stalled: false,
+        }
+    }
+
+    /// If the campaign was stalled at the last [`StallAlert::check`]
+    #[must_use]
+    pub fn is_stalled(&self) -> bool {
+        self.stalled
+    }
+
+    /// Check for a stall, given the time since the last find of any client.
+    ///
+    /// Returns a message when the campaign starts or stops stalling, and logs it.
+    pub fn check(&mut self, since_last_find: Duration) -> Option<String> {
+        let stalled = since_last_find >= self.timeout;
+        if stalled == self.stalled {
+            return None;
+        }
+        self.stalled = stalled;
+
+        if stalled {
+            let msg = format!(
+                "No client found a new corpus entry for {}",
+                format_duration_hms(&since_last_find)
+            );
+            log::warn!("{msg}");
+            Some(msg)
+        } else {
+            let msg = String::from("Stall is over, a client found a new corpus entry");
+            log::info!("{msg}");
+            Some(msg)
+        }
+    }
+}
+
+/// Tells clients that still report apart from clients that went silent, e.g., because they died.
+///
+/// A client is silent once no event arrived from it for `silent_after`. Aggregating monitors
+/// leave silent clients out of the global exec/sec and show their corpus and objectives separately.
+/// After the optional, longer `drop_after`, a client is not displayed at all anymore,
+/// until it reports again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientLiveness {
+    silent_after: Duration,
+    drop_after: Option<Duration>,
+}
+
+impl ClientLiveness {
+    /// Create a new [`ClientLiveness`], marking clients as silent after `silent_after`
+    #[must_use]
+    pub fn new(silent_after: Duration) -> Self {
+        Self {
+            silent_after,
+            drop_after: None,
+        }
+    }
+
+    /// Stop displaying clients that were silent for `drop_after`
+    #[must_use]
+    pub fn with_drop_after(mut self, drop_after: Duration) -> Self {
+        self.drop_after = Some(drop_after);
+        self
+    }
+
+    /// The time after which a client is silent
+    #[must_use]
+    pub fn silent_after(&self) -> Duration {
+        self.silent_after
+    }
+
+    /// The time after which a silent client is no longer displayed, if any
+    #[must_use]
+    pub fn drop_after(&self) -> Option<Duration> {
+        self.drop_after
+    }
+
+    /// If no event arrived from this client for longer than `silent_after`
+    #[must_use]
+    pub fn is_silent(&self, client: &ClientStats, cur_time: Duration) -> bool {
+        client.time_since_last_update(cur_time) > self.silent_after
+    }
+
+    /// If no event arrived from this client for longer than `drop_after`
+    #[must_use]
+    pub fn is_dropped(&self, client: &ClientStats, cur_time: Duration) -> bool {
+        self.drop_after
+            .is_some_and(|drop_after| client.time_since_last_update(cur_time) > drop_after)
+    }
+
+    /// Sum up the enabled clients, keeping the silent ones apart and skipping the dropped ones
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn summary(&self, clients: &[ClientStats], cur_time: Duration) -> LivenessSummary {
+        let mut summary = LivenessSummary::default();
+        for (id, client) in clients.iter().enumerate() {
+            if !client.enabled {
+                continue;
+            }
+            if self.is_dropped(client, cur_time) {
+                summary.dropped_clients += 1;
+            } else if self.is_silent(client, cur_time) {
+                summary.silent_clients.push(ClientId(id as u32));
+                summary.silent_corpus_size += client.corpus_size;
+                summary.silent_objective_size += client.objective_size;
+            } else {
+                summary.live_clients += 1;
+                summary.live_corpus_size += client.corpus_size;
+                summary.live_objective_size += client.objective_size;
+            }
+        }
+        summary
+    }
+
+    /// Executions per second of the clients that are not silent
+    pub fn execs_per_sec(&self, clients: &mut [ClientStats], cur_time: Duration) -> f64 {
+        clients
+            .iter_mut()
+            .filter(|client| client.enabled && !self.is_silent(client, cur_time))
+            .fold(0.0, |acc, x| acc + x.execs_per_sec(cur_time))
+    }
+
+    /// Exponentially smoothed executions per second of the clients that are not silent
+    pub fn smoothed_execs_per_sec(
+        &self,
+        clients: &mut [ClientStats],
+        cur_time: Duration,
+        window: Duration,
+    ) -> f64 {
+        clients
+            .iter_mut()
+            .filter(|client| client.enabled && !self.is_silent(client, cur_time))
+            .fold(0.0, |acc, x| {
+                acc + x.smoothed_execs_per_sec(cur_time, window)
+            })
+    }
+}
+
+/// The clients of a monitor, split by [`ClientLiveness`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LivenessSummary {
+    /// The number of clients that still report
+    pub live_clients: usize,
+    /// The clients that went silent, but are still displayed
+    pub silent_clients: Vec<ClientId>,
+    /// The number of clients that are no longer displayed
+    pub dropped_clients: usize,
+    /// The corpus size of the clients that still report
+    pub live_corpus_size: u64,
+    /// The corpus size of the silent clients
+    pub silent_corpus_size: u64,
+    /// The objective size of the clients that still report
+    pub live_objective_size: u64,
+    /// The objective size of the silent clients
+    pub silent_objective_size: u64,
+}
+
 /// Monitor that print exactly nothing.
 /// Not good for debugging, very good for speed.
 #[derive(Debug, Clone)]
@@ -727,6 +1140,8 @@ where
     start_time: Duration,
     print_user_monitor: bool,
     client_stats: Vec<ClientStats>,
+    exec_sec_window: Duration,
+    stall_alert: Option<StallAlert>,
 }
 
 impl<F> Debug for SimpleMonitor<F>
@@ -766,8 +1181,9 @@ where
     }
 
     fn display(&mut self, event_msg: &str, sender_id: ClientId) {
+        let since_last_find = self.time_since_last_find();
         let mut fmt = format!(
-            "[{} #{}] run time: {}, clients: {}, corpus: {}, objectives: {}, executions: {}, exec/sec: {}",
+            "[{} #{}] run time: {}, clients: {}, corpus: {}, objectives: {}, executions: {}, exec/sec: {}, smoothed exec/sec: {}, last find: {}, last objective: {}",
             event_msg,
             sender_id.0,
             format_duration_hms(&(current_time() - self.start_time)),
@@ -775,9 +1191,24 @@ where
             self.corpus_size(),
             self.objective_size(),
             self.total_execs(),
-            self.execs_per_sec_pretty()
+            self.execs_per_sec_pretty(),
+            prettify_float(self.smoothed_execs_per_sec(self.exec_sec_window)),
+            format_duration_hms(&since_last_find),
+            format_duration_hms(&self.time_since_last_objective()),
         );
 
+        if let Some(stall_alert) = &mut self.stall_alert {
+            if let Some(msg) = stall_alert.check(since_last_find) {
+                (self.print_fn)(&format!("[Stall] {msg}"));
+            }
+            write!(
+                fmt,
+                ", {STALLED_STAT}: {}",
+                u8::from(stall_alert.is_stalled())
+            )
+            .unwrap();
+        }
+
         if self.print_user_monitor {
             self.client_stats_insert(sender_id);
             let client = self.client_stats_mut_for(sender_id);
@@ -791,11 +1222,8 @@ where
         // Only print perf monitor if the feature is enabled
         #[cfg(feature = "introspection")]
         {
-            // Print the client performance monitor.
-            let fmt = format!(
-                "Client {:03}:\n{}",
-                sender_id.0, self.client_stats[sender_id.0 as usize].introspection_monitor
-            );
+            // Print the performance monitor of all clients.
+            let fmt = format!("Introspection:\n{}", self.introspection_summary());
             (self.print_fn)(&fmt);
 
             // Separate the spacing just a bit
@@ -815,6 +1243,8 @@ where
             start_time: current_time(),
             print_user_monitor: false,
             client_stats: vec![],
+            exec_sec_window: DEFAULT_EXEC_SEC_WINDOW,
+            stall_alert: None,
         }
     }
 
@@ -825,6 +1255,8 @@ where
             start_time,
             print_user_monitor: false,
             client_stats: vec![],
+            exec_sec_window: DEFAULT_EXEC_SEC_WINDOW,
+            stall_alert: None,
         }
     }
 
@@ -835,8 +1267,24 @@ where
             start_time: current_time(),
             print_user_monitor: true,
             client_stats: vec![],
+            exec_sec_window: DEFAULT_EXEC_SEC_WINDOW,
+            stall_alert: None,
         }
     }
+
+    /// Set the window of the smoothed executions per second, [`DEFAULT_EXEC_SEC_WINDOW`] by default
+    #[must_use]
+    pub fn with_exec_sec_window(mut self, window: Duration) -> Self {
+        self.exec_sec_window = window;
+        self
+    }
+
+    /// Alert when no client found a new corpus entry for `timeout`, see [`StallAlert`]
+    #[must_use]
+    pub fn with_stall_alert(mut self, timeout: Duration) -> Self {
+        self.stall_alert = Some(StallAlert::new(timeout));
+        self
+    }
 }
 
 /// Start the timer
@@ -896,6 +1344,10 @@ pub struct ClientPerfMonitor {
     /// Clock cycles spent in the the various features of each stage
     stages: Vec<[u64; PerfFeature::Count as usize]>,
 
+    /// The names of the stages, if they reported one, see [`ClientPerfMonitor::finish_named_stage`]
+    #[serde(default)]
+    stage_names: Vec<Option<String>>,
+
     /// Clock cycles spent in each feedback mechanism of the fuzzer.
     feedbacks: HashMap<String, u64>,
 
@@ -910,6 +1362,10 @@ pub struct ScalabilityMonitor {
     pub testcase_with_observers: usize,
     /// Imported testcase received without observer
     pub testcase_without_observers: usize,
+    /// Enabled corpus entries after the last run of [`crate::stages::CorpusPruning`]
+    pub corpus_enabled: usize,
+    /// Disabled corpus entries after the last run of [`crate::stages::CorpusPruning`]
+    pub corpus_disabled: usize,
 }
 
 impl ScalabilityMonitor {
@@ -919,6 +1375,8 @@ impl ScalabilityMonitor {
         Self {
             testcase_with_observers: 0,
             testcase_without_observers: 0,
+            corpus_enabled: 0,
+            corpus_disabled: 0,
         }
     }
 }
@@ -1025,6 +1483,7 @@ impl ClientPerfMonitor {
             curr_stage: 0,
             stages: vec![],
             stages_used: vec![],
+            stage_names: vec![],
             feedbacks: HashMap::new(),
             timer_start: None,
         }
@@ -1049,6 +1508,11 @@ impl ClientPerfMonitor {
         self.update_manager(monitor.manager);
         self.update_stages(&monitor.stages);
         self.update_feedbacks(&monitor.feedbacks);
+        for (stage_index, name) in monitor.stage_names.iter().enumerate() {
+            if let Some(name) = name {
+                self.set_stage_name(stage_index, name);
+            }
+        }
     }
 
     /// Gets the elapsed time since the internal timer started. Resets the timer when
@@ -1132,6 +1596,30 @@ impl ClientPerfMonitor {
         self.curr_stage += 1;
     }
 
+    /// Like [`ClientPerfMonitor::finish_stage`], but also name the finished stage,
+    /// so that its time can be told apart from the other stages in the monitors
+    #[inline]
+    pub fn finish_named_stage(&mut self, name: &str) {
+        self.set_stage_name(self.curr_stage.into(), name);
+        self.finish_stage();
+    }
+
+    /// Set the name of the stage at `stage_index`
+    fn set_stage_name(&mut self, stage_index: usize, name: &str) {
+        if stage_index >= self.stage_names.len() {
+            self.stage_names.resize(stage_index + 1, None);
+        }
+        if self.stage_names[stage_index].as_deref() != Some(name) {
+            self.stage_names[stage_index] = Some(name.into());
+        }
+    }
+
+    /// The name of the stage at `stage_index`, if it reported one
+    #[must_use]
+    pub fn stage_name(&self, stage_index: usize) -> Option<&str> {
+        self.stage_names.get(stage_index)?.as_deref()
+    }
+
     /// Reset the stage index counter to zero
     #[inline]
     pub fn reset_stage_index(&mut self) {
@@ -1230,6 +1718,165 @@ impl ClientPerfMonitor {
     pub fn feedbacks(&self) -> &HashMap<String, u64> {
         &self.feedbacks
     }
+
+    /// The amount of cycles spent executing the target, over all stages
+    #[must_use]
+    pub fn target_cycles(&self) -> u64 {
+        self.used_stages()
+            .map(|(_, features)| features[PerfFeature::TargetExecution as usize])
+            .sum()
+    }
+
+    /// The measured cycles, split into named [`IntrospectionStat`]s that do not overlap.
+    ///
+    /// The time spent executing the target is taken out of the stages and reported on its own.
+    #[must_use]
+    pub fn named_stats(&self) -> Vec<IntrospectionStat> {
+        let mut stats = vec![
+            IntrospectionStat::new(
+                IntrospectionCategory::Scheduler,
+                "scheduler",
+                self.scheduler,
+            ),
+            IntrospectionStat::new(IntrospectionCategory::Manager, "manager", self.manager),
+        ];
+        for (stage_index, features) in self.used_stages() {
+            let name = match self.stage_name(stage_index) {
+                Some(name) => format!("stage[{stage_index}]:{name}"),
+                None => format!("stage[{stage_index}]"),
+            };
+            let cycles =
+                features.iter().sum::<u64>() - features[PerfFeature::TargetExecution as usize];
+            stats.push(IntrospectionStat::new(
+                IntrospectionCategory::Stage,
+                name,
+                cycles,
+            ));
+        }
+        let mut feedbacks = self.feedbacks.iter().collect::<Vec<_>>();
+        feedbacks.sort_unstable();
+        for (name, cycles) in feedbacks {
+            stats.push(IntrospectionStat::new(
+                IntrospectionCategory::Feedback,
+                format!("feedback:{name}"),
+                *cycles,
+            ));
+        }
+        stats.push(IntrospectionStat::new(
+            IntrospectionCategory::Target,
+            "target",
+            self.target_cycles(),
+        ));
+        stats
+    }
+}
+
+#[cfg(feature = "introspection")]
+/// The category of an [`IntrospectionStat`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IntrospectionCategory {
+    /// Time spent in the scheduler
+    Scheduler,
+    /// Time spent in the event manager
+    Manager,
+    /// Time spent in a stage, without executing the target
+    Stage,
+    /// Time spent in a feedback
+    Feedback,
+    /// Time spent executing the target
+    Target,
+}
+
+#[cfg(feature = "introspection")]
+/// The cycles spent in a named part of the fuzzer, see [`ClientPerfMonitor::named_stats`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntrospectionStat {
+    /// The category of this stat
+    pub category: IntrospectionCategory,
+    /// The name, e.g., `stage[1]:mutational` or `feedback:mapfeedback`
+    pub name: String,
+    /// The clock cycles spent
+    pub cycles: u64,
+}
+
+#[cfg(feature = "introspection")]
+impl IntrospectionStat {
+    /// Create a new [`IntrospectionStat`]
+    #[must_use]
+    pub fn new(category: IntrospectionCategory, name: impl Into<String>, cycles: u64) -> Self {
+        Self {
+            category,
+            name: name.into(),
+            cycles,
+        }
+    }
+}
+
+#[cfg(feature = "introspection")]
+/// The [`IntrospectionStat`]s of one or more clients, summed up by name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntrospectionSummary {
+    /// The elapsed cycles, summed up over all clients
+    pub elapsed: u64,
+    /// The stats, summed up over all clients
+    pub stats: Vec<IntrospectionStat>,
+}
+
+#[cfg(feature = "introspection")]
+impl IntrospectionSummary {
+    /// Create an empty [`IntrospectionSummary`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the stats of a client
+    pub fn add(&mut self, monitor: &ClientPerfMonitor) {
+        self.elapsed += monitor.elapsed_cycles();
+        for stat in monitor.named_stats() {
+            if let Some(existing) = self
+                .stats
+                .iter_mut()
+                .find(|existing| existing.category == stat.category && existing.name == stat.name)
+            {
+                existing.cycles += stat.cycles;
+            } else {
+                self.stats.push(stat);
+            }
+        }
+    }
+
+    /// The share of the elapsed time of each stat, followed by the share that was `not_measured`
+    #[allow(clippy::cast_precision_loss)]
+    pub fn fractions(&self) -> impl Iterator<Item = (&str, f64)> {
+        let elapsed = self.elapsed.max(1) as f64;
+        let measured = self.stats.iter().map(|stat| stat.cycles).sum::<u64>() as f64;
+        self.stats
+            .iter()
+            .map(move |stat| (stat.name.as_str(), stat.cycles as f64 / elapsed))
+            .chain(core::iter::once((
+                "not_measured",
+                (1.0 - measured / elapsed).max(0.0),
+            )))
+    }
+}
+
+#[cfg(feature = "introspection")]
+impl fmt::Display for IntrospectionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let width = self
+            .fractions()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or_default();
+        for (i, (name, fraction)) in self.fractions().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "  {name:<width$} {:6.2}%", fraction * 100.0)?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(feature = "introspection")]
@@ -1258,7 +1905,10 @@ impl fmt::Display for ClientPerfMonitor {
         // Make sure we only iterate over used stages
         for (stage_index, features) in self.used_stages() {
             // Write the stage header
-            writeln!(f, "  Stage {stage_index}:")?;
+            match self.stage_name(stage_index) {
+                Some(name) => writeln!(f, "  Stage {stage_index} ({name}):")?,
+                None => writeln!(f, "  Stage {stage_index}:")?,
+            }
 
             for (feature_index, feature) in features.iter().enumerate() {
                 // Calculate this current stage's percentage
@@ -1311,3 +1961,252 @@ impl Default for ClientPerfMonitor {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::{borrow::Cow, string::ToString};
+    use core::time::Duration;
+
+    use libafl_bolts::{current_time, ClientId};
+
+    use crate::monitors::{
+        Aggregator, AggregatorOps, ClientLiveness, ClientStats, Monitor, MultiMonitor, StallAlert,
+        UserStats, UserStatsValue,
+    };
+
+    fn aggregate(op: &AggregatorOps, values: &[UserStatsValue]) -> UserStatsValue {
+        let client_stats = values
+            .iter()
+            .map(|value| {
+                let mut client = ClientStats::default();
+                client.update_user_stats(
+                    Cow::from("stat"),
+                    UserStats::new(value.clone(), op.clone()),
+                );
+                client
+            })
+            .collect::<alloc::vec::Vec<_>>();
+        let mut aggregator = Aggregator::new();
+        aggregator.aggregate("stat", &client_stats);
+        aggregator.aggregated["stat"].clone()
+    }
+
+    #[test]
+    fn test_aggregator_ops() {
+        let numbers = [
+            UserStatsValue::Number(3),
+            UserStatsValue::Number(1),
+            UserStatsValue::Number(2),
+        ];
+        assert!(matches!(
+            aggregate(&AggregatorOps::Sum, &numbers),
+            UserStatsValue::Number(6)
+        ));
+        assert!(matches!(
+            aggregate(&AggregatorOps::Min, &numbers),
+            UserStatsValue::Number(1)
+        ));
+        assert!(matches!(
+            aggregate(&AggregatorOps::Max, &numbers),
+            UserStatsValue::Number(3)
+        ));
+        assert!(matches!(
+            aggregate(&AggregatorOps::Latest, &numbers),
+            UserStatsValue::Number(2)
+        ));
+        assert!(
+            matches!(aggregate(&AggregatorOps::Avg, &numbers), UserStatsValue::Float(avg) if (avg - 2.0).abs() < f64::EPSILON)
+        );
+
+        let ratios = [UserStatsValue::Ratio(1, 2), UserStatsValue::Ratio(3, 8)];
+        assert!(matches!(
+            aggregate(&AggregatorOps::Ratio, &ratios),
+            UserStatsValue::Ratio(4, 10)
+        ));
+    }
+
+    #[test]
+    fn test_aggregator_ops_compat() {
+        // The serialized form of the ops older clients know about is unchanged
+        for (op, index) in [
+            (AggregatorOps::None, 0),
+            (AggregatorOps::Sum, 1),
+            (AggregatorOps::Avg, 2),
+            (AggregatorOps::Min, 3),
+            (AggregatorOps::Max, 4),
+        ] {
+            assert_eq!(postcard::to_allocvec(&op).unwrap(), [index]);
+        }
+
+        // Unknown ops are aggregated like `Latest`
+        let stats: UserStats = postcard::from_bytes(&[0, 7, 42]).unwrap();
+        assert_eq!(*stats.aggregator_op(), AggregatorOps::Unknown);
+        assert!(matches!(stats.value(), UserStatsValue::Number(7)));
+        let numbers = [UserStatsValue::Number(3), UserStatsValue::Number(1)];
+        assert!(matches!(
+            aggregate(&AggregatorOps::Unknown, &numbers),
+            UserStatsValue::Number(1)
+        ));
+    }
+
+    #[test]
+    fn test_smoothed_execs_per_sec() {
+        let window = Duration::from_secs(10);
+        let mut client = ClientStats {
+            start_time: Duration::from_secs(1),
+            last_window_time: Duration::from_secs(1),
+            ..ClientStats::default()
+        };
+
+        // 100 execs/sec for a long time
+        for secs in 2..=100 {
+            client.update_executions((secs - 1) * 100, Duration::from_secs(secs));
+            client.smoothed_execs_per_sec(Duration::from_secs(secs), window);
+        }
+        assert!((client.smoothed_execs_per_sec - 100.0).abs() < 1.0);
+
+        // A single fast second only moves the smoothed rate by a fraction of the jump
+        client.update_executions(99 * 100 + 10_000, Duration::from_secs(101));
+        let smoothed = client.smoothed_execs_per_sec(Duration::from_secs(101), window);
+        assert!(smoothed > 100.0 && smoothed < 2_000.0, "{smoothed}");
+    }
+
+    #[test]
+    fn test_stall_alert() {
+        let client = ClientStats {
+            start_time: Duration::from_secs(10),
+            last_corpus_time: Duration::from_secs(20),
+            ..ClientStats::default()
+        };
+        assert_eq!(
+            client.time_since_last_find(Duration::from_secs(50)),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            client.time_since_last_objective(Duration::from_secs(50)),
+            Duration::from_secs(40)
+        );
+
+        let mut alert = StallAlert::new(Duration::from_secs(60));
+        assert!(alert.check(Duration::from_secs(30)).is_none());
+        assert!(alert.check(Duration::from_secs(60)).is_some());
+        assert!(alert.is_stalled());
+        // Only alert once per stall
+        assert!(alert.check(Duration::from_secs(90)).is_none());
+        assert!(alert.check(Duration::from_secs(1)).is_some());
+        assert!(!alert.is_stalled());
+    }
+
+    #[test]
+    fn test_client_liveness() {
+        let liveness =
+            ClientLiveness::new(Duration::from_secs(60)).with_drop_after(Duration::from_secs(580));
+        // A synthetic stream of clients, reporting last at 100s, 50s and 10s
+        let client = |corpus_size, last_update| ClientStats {
+            enabled: true,
+            corpus_size,
+            objective_size: 1,
+            executions: 1000,
+            last_update_time: Duration::from_secs(last_update),
+            ..ClientStats::default()
+        };
+        let mut clients = vec![
+            ClientStats::default(),
+            client(10, 100),
+            client(20, 50),
+            client(30, 10),
+        ];
+
+        let summary = liveness.summary(&clients, Duration::from_secs(65));
+        assert_eq!(summary.live_clients, 3);
+        assert!(summary.silent_clients.is_empty());
+        assert_eq!(summary.live_corpus_size, 60);
+
+        let summary = liveness.summary(&clients, Duration::from_secs(140));
+        assert_eq!(summary.live_clients, 1);
+        assert_eq!(summary.silent_clients, [ClientId(2), ClientId(3)]);
+        assert_eq!(summary.live_corpus_size, 10);
+        assert_eq!(summary.silent_corpus_size, 50);
+        assert_eq!(summary.silent_objective_size, 2);
+
+        let summary = liveness.summary(&clients, Duration::from_secs(640));
+        assert_eq!(summary.silent_clients, [ClientId(1)]);
+        assert_eq!(summary.dropped_clients, 2);
+        assert_eq!(summary.live_clients, 0);
+
+        // Client #1 reports again
+        clients[1].last_update_time = Duration::from_secs(640);
+        let summary = liveness.summary(&clients, Duration::from_secs(640));
+        assert_eq!(summary.live_clients, 1);
+        assert!(summary.silent_clients.is_empty());
+
+        // Only live clients count for the exec/sec
+        let cur_time = Duration::from_secs(650);
+        let live = liveness.execs_per_sec(&mut clients, cur_time);
+        let all = clients[1].execs_per_sec(cur_time) * 3.0;
+        assert!(live > 0.0);
+        assert!((live * 3.0 - all).abs() < f64::EPSILON * all);
+    }
+
+    #[test]
+    fn test_multi_monitor_liveness() {
+        let mut lines = vec![];
+        let mut monitor = MultiMonitor::new(|line: &str| lines.push(line.to_string()))
+            .with_client_liveness(ClientLiveness::new(Duration::from_secs(60)));
+        monitor.client_stats_insert(ClientId(1));
+        monitor.client_stats_insert(ClientId(2));
+        monitor.client_stats_mut_for(ClientId(1)).corpus_size = 10;
+        let silent = monitor.client_stats_mut_for(ClientId(2));
+        silent.corpus_size = 20;
+        silent.last_update_time = current_time().saturating_sub(Duration::from_secs(120));
+        silent.start_time = silent.last_update_time;
+
+        monitor.display("Testcase", ClientId(1));
+        drop(monitor);
+        assert!(lines[0].contains("clients: 1 (silent: #2)"), "{}", lines[0]);
+        assert!(lines[0].contains("corpus: 10 (+20 silent)"), "{}", lines[0]);
+    }
+
+    #[test]
+    #[cfg(feature = "introspection")]
+    fn test_introspection_summary() {
+        use alloc::vec::Vec;
+
+        use crate::monitors::{ClientPerfMonitor, IntrospectionSummary, PerfFeature};
+
+        let mut perf = ClientPerfMonitor::new();
+        perf.set_current_time(perf.start_time + 100);
+        perf.update_scheduler(10);
+        perf.update_feature(PerfFeature::Mutate, 20);
+        perf.update_feature(PerfFeature::TargetExecution, 40);
+        perf.finish_named_stage("mutational");
+        perf.update_feedback("map", 5);
+        assert_eq!(perf.stage_name(0), Some("mutational"));
+
+        let mut summary = IntrospectionSummary::new();
+        summary.add(&perf);
+        summary.add(&perf);
+        let fractions = summary.fractions().collect::<Vec<_>>();
+        let names = fractions.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "scheduler",
+                "manager",
+                "stage[0]:mutational",
+                "feedback:map",
+                "target",
+                "not_measured"
+            ]
+        );
+        let fraction = |name| fractions.iter().find(|(n, _)| *n == name).unwrap().1;
+        assert!((fraction("stage[0]:mutational") - 0.2).abs() < 1e-9);
+        assert!((fraction("target") - 0.4).abs() < 1e-9);
+        assert!((fraction("not_measured") - 0.25).abs() < 1e-9);
+
+        // Stage names survive a round trip through the broker
+        let mut broker_side = ClientPerfMonitor::new();
+        broker_side.update(&perf);
+        assert_eq!(broker_side.stage_name(0), Some("mutational"));
+    }
+}
@@ -1,6 +1,9 @@
 //! The [`MultiMonitor`] displays both cumulative and per-client stats.
 
-use alloc::{string::String, vec::Vec};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 use core::{
     fmt::{Debug, Formatter, Write},
     time::Duration,
@@ -8,8 +11,10 @@ use core::{
 
 use libafl_bolts::{current_time, format_duration_hms, ClientId};
 
-use super::Aggregator;
-use crate::monitors::{ClientStats, Monitor};
+use super::{
+    Aggregator, ClientLiveness, StallAlert, UserStatsValue, DEFAULT_EXEC_SEC_WINDOW, STALLED_STAT,
+};
+use crate::monitors::{prettify_float, ClientStats, Monitor};
 
 /// Tracking monitor during fuzzing and display both per-client and cumulative info.
 #[derive(Clone)]
@@ -21,6 +26,9 @@ where
     start_time: Duration,
     client_stats: Vec<ClientStats>,
     aggregator: Aggregator,
+    exec_sec_window: Duration,
+    stall_alert: Option<StallAlert>,
+    liveness: Option<ClientLiveness>,
 }
 
 impl<F> Debug for MultiMonitor<F>
@@ -71,15 +79,69 @@ where
             String::new()
         };
         let head = format!("{event_msg}{pad} {sender}");
+
+        let since_last_find = self.time_since_last_find();
+        if let Some(stall_alert) = &mut self.stall_alert {
+            if let Some(msg) = stall_alert.check(since_last_find) {
+                (self.print_fn)(&format!("[Stall] {msg}"));
+            }
+            self.aggregator.aggregated.insert(
+                STALLED_STAT.into(),
+                UserStatsValue::Number(stall_alert.is_stalled().into()),
+            );
+        }
+
+        let cur_time = current_time();
+        let (clients, corpus, objectives, exec_sec, smoothed_exec_sec) =
+            if let Some(liveness) = self.liveness {
+                let summary = liveness.summary(&self.client_stats, cur_time);
+                let mut clients = summary.live_clients.to_string();
+                let mut corpus = summary.live_corpus_size.to_string();
+                let mut objectives = summary.live_objective_size.to_string();
+                if !summary.silent_clients.is_empty() {
+                    let silent = summary
+                        .silent_clients
+                        .iter()
+                        .map(|id| format!("#{}", id.0))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    write!(clients, " (silent: {silent})").unwrap();
+                    write!(corpus, " (+{} silent)", summary.silent_corpus_size).unwrap();
+                    write!(objectives, " (+{} silent)", summary.silent_objective_size).unwrap();
+                }
+                (
+                    clients,
+                    corpus,
+                    objectives,
+                    prettify_float(liveness.execs_per_sec(&mut self.client_stats, cur_time)),
+                    prettify_float(liveness.smoothed_execs_per_sec(
+                        &mut self.client_stats,
+                        cur_time,
+                        self.exec_sec_window,
+                    )),
+                )
+            } else {
+                (
+                    self.client_stats_count().to_string(),
+                    self.corpus_size().to_string(),
+                    self.objective_size().to_string(),
+                    self.execs_per_sec_pretty(),
+                    prettify_float(self.smoothed_execs_per_sec(self.exec_sec_window)),
+                )
+            };
+
         let mut global_fmt = format!(
-            "[{}]  (GLOBAL) run time: {}, clients: {}, corpus: {}, objectives: {}, executions: {}, exec/sec: {}",
+            "[{}]  (GLOBAL) run time: {}, clients: {}, corpus: {}, objectives: {}, executions: {}, exec/sec: {}, smoothed exec/sec: {}, last find: {}, last objective: {}",
             head,
-            format_duration_hms(&(current_time() - self.start_time)),
-            self.client_stats_count(),
-            self.corpus_size(),
-            self.objective_size(),
+            format_duration_hms(&(cur_time - self.start_time)),
+            clients,
+            corpus,
+            objectives,
             self.total_execs(),
-            self.execs_per_sec_pretty()
+            exec_sec,
+            smoothed_exec_sec,
+            format_duration_hms(&since_last_find),
+            format_duration_hms(&self.time_since_last_objective()),
         );
         for (key, val) in &self.aggregator.aggregated {
             write!(global_fmt, ", {key}: {val}").unwrap();
@@ -88,14 +150,23 @@ where
         (self.print_fn)(&global_fmt);
 
         self.client_stats_insert(sender_id);
+        let exec_sec_window = self.exec_sec_window;
         let client = self.client_stats_mut_for(sender_id);
-        let cur_time = current_time();
         let exec_sec = client.execs_per_sec_pretty(cur_time);
+        let smoothed_exec_sec =
+            prettify_float(client.smoothed_execs_per_sec(cur_time, exec_sec_window));
 
         let pad = " ".repeat(head.len());
         let mut fmt = format!(
-            " {}   (CLIENT) corpus: {}, objectives: {}, executions: {}, exec/sec: {}",
-            pad, client.corpus_size, client.objective_size, client.executions, exec_sec
+            " {}   (CLIENT) corpus: {}, objectives: {}, executions: {}, exec/sec: {}, smoothed exec/sec: {}, last find: {}, last objective: {}",
+            pad,
+            client.corpus_size,
+            client.objective_size,
+            client.executions,
+            exec_sec,
+            smoothed_exec_sec,
+            format_duration_hms(&client.time_since_last_find(cur_time)),
+            format_duration_hms(&client.time_since_last_objective(cur_time)),
         );
         for (key, val) in &client.user_monitor {
             write!(fmt, ", {key}: {val}").unwrap();
@@ -128,6 +199,9 @@ where
             start_time: current_time(),
             client_stats: vec![],
             aggregator: Aggregator::new(),
+            exec_sec_window: DEFAULT_EXEC_SEC_WINDOW,
+            stall_alert: None,
+            liveness: None,
         }
     }
 
@@ -138,6 +212,35 @@ where
             start_time,
             client_stats: vec![],
             aggregator: Aggregator::new(),
+            exec_sec_window: DEFAULT_EXEC_SEC_WINDOW,
+            stall_alert: None,
+            liveness: None,
         }
     }
+
+    /// Set the window of the smoothed executions per second, [`DEFAULT_EXEC_SEC_WINDOW`] by default
+    #[must_use]
+    pub fn with_exec_sec_window(mut self, window: Duration) -> Self {
+        self.exec_sec_window = window;
+        self
+    }
+
+    /// Alert when no client found a new corpus entry for `timeout`, see [`StallAlert`].
+    ///
+    /// While stalled, the [`STALLED_STAT`] global stat is `1`.
+    #[must_use]
+    pub fn with_stall_alert(mut self, timeout: Duration) -> Self {
+        self.stall_alert = Some(StallAlert::new(timeout));
+        self
+    }
+
+    /// Keep track of clients that went silent, e.g., because they died, see [`ClientLiveness`].
+    ///
+    /// Silent clients are marked, left out of the global exec/sec, and their corpus and objectives
+    /// are shown apart. Executions still count all clients.
+    #[must_use]
+    pub fn with_client_liveness(mut self, liveness: ClientLiveness) -> Self {
+        self.liveness = Some(liveness);
+        self
+    }
 }
@@ -26,8 +26,26 @@
 //! ```
 //!
 //! When using docker, you may need to point `prometheus.yml` to the `docker0` interface or `host.docker.internal`
+//!
+//! ## Labels
+//!
+//! Every series carries a `client_id` label. Per-client series are labeled with the numeric id of the
+//! client, the series aggregated over all clients are labeled with `client_id="global"`.
+//! User stats are exported as `custom_stat`, with the name of the stat in the `stat` label.
+//! An optional, user-assigned `fuzzer_instance` label (see [`PrometheusMonitor::with_instance`]) tells
+//! apart multiple campaigns scraped by the same prometheus server.
+//!
+//! To keep the cardinality in check, [`PrometheusMonitor::with_max_labeled_clients`] caps the number of
+//! clients that get their own series, clients beyond it only show up in the global series.
+//! [`PrometheusMonitor::with_stale_client_timeout`] removes the series of clients that have not reported
+//! for a while.
 
-use alloc::{borrow::Cow, fmt::Debug, string::String, vec::Vec};
+use alloc::{
+    borrow::Cow,
+    fmt::Debug,
+    string::{String, ToString},
+    vec::Vec,
+};
 use core::{fmt, time::Duration};
 use std::{
     sync::{atomic::AtomicU64, Arc},
@@ -36,6 +54,7 @@ use std::{
 
 // using thread in order to start the HTTP server in a separate thread
 use futures::executor::block_on;
+use hashbrown::HashMap;
 use libafl_bolts::{current_time, format_duration_hms, ClientId};
 // using the official rust client library for Prometheus: https://github.com/prometheus/client_rust
 use prometheus_client::{
@@ -46,7 +65,10 @@ use prometheus_client::{
 // using tide for the HTTP server library (fast, async, simple)
 use tide::Request;
 
-use crate::monitors::{ClientStats, Monitor, UserStatsValue};
+use crate::monitors::{Aggregator, ClientStats, Monitor, UserStatsValue};
+
+/// The `client_id` label value of the series aggregated over all clients
+pub const GLOBAL_CLIENT_ID: &str = "global";
 
 /// Tracking monitor during fuzzing.
 #[derive(Clone)]
@@ -57,13 +79,16 @@ where
     print_fn: F,
     start_time: Duration,
     client_stats: Vec<ClientStats>,
-    corpus_count: Family<Labels, Gauge>,
-    objective_count: Family<Labels, Gauge>,
-    executions: Family<Labels, Gauge>,
-    exec_rate: Family<Labels, Gauge<f64, AtomicU64>>,
-    runtime: Family<Labels, Gauge>,
-    clients_count: Family<Labels, Gauge>,
-    custom_stat: Family<Labels, Gauge<f64, AtomicU64>>,
+    aggregator: Aggregator,
+    metrics: PrometheusMetrics,
+    /// The value of the `fuzzer_instance` label
+    instance: Cow<'static, str>,
+    /// The maximum amount of clients that get their own series
+    max_labeled_clients: Option<usize>,
+    /// Remove the series of clients that did not report for this long
+    stale_client_timeout: Option<Duration>,
+    /// The clients that currently have their own series, with the last time they reported
+    labeled_clients: HashMap<ClientId, Duration>,
 }
 
 impl<F> Debug for PrometheusMonitor<F>
@@ -74,6 +99,9 @@ where
         f.debug_struct("PrometheusMonitor")
             .field("start_time", &self.start_time)
             .field("client_stats", &self.client_stats)
+            .field("instance", &self.instance)
+            .field("max_labeled_clients", &self.max_labeled_clients)
+            .field("stale_client_timeout", &self.stale_client_timeout)
             .finish_non_exhaustive()
     }
 }
@@ -102,96 +130,83 @@ where
         self.start_time = time;
     }
 
-    #[allow(clippy::cast_sign_loss)]
+    fn aggregate(&mut self, name: &str) {
+        self.aggregator.aggregate(name, &self.client_stats);
+    }
+
     fn display(&mut self, event_msg: &str, sender_id: ClientId) {
+        let cur_time = current_time();
+        self.client_stats_insert(sender_id);
+
         // Update the prometheus metrics
-        // Label each metric with the sender / client_id
         // The gauges must take signed i64's, with max value of 2^63-1 so it is
         // probably fair to error out at a count of nine quintillion across any
         // of these counts.
         // realistically many of these metrics should be counters but would
         // require a fair bit of logic to handle "amount to increment given
         // time since last observation"
+        let global = self.labels(Cow::Borrowed(GLOBAL_CLIENT_ID), Cow::Borrowed(""));
         let corpus_size = self.corpus_size();
-        self.corpus_count
-            .get_or_create(&Labels {
-                client: sender_id.0,
-                stat: Cow::from(""),
-            })
+        self.metrics
+            .corpus_count
+            .get_or_create(&global)
             .set(corpus_size.try_into().unwrap());
         let objective_size = self.objective_size();
-        self.objective_count
-            .get_or_create(&Labels {
-                client: sender_id.0,
-                stat: Cow::from(""),
-            })
+        self.metrics
+            .objective_count
+            .get_or_create(&global)
             .set(objective_size.try_into().unwrap());
         let total_execs = self.total_execs();
-        self.executions
-            .get_or_create(&Labels {
-                client: sender_id.0,
-                stat: Cow::from(""),
-            })
+        self.metrics
+            .executions
+            .get_or_create(&global)
             .set(total_execs.try_into().unwrap());
         let execs_per_sec = self.execs_per_sec();
-        self.exec_rate
-            .get_or_create(&Labels {
-                client: sender_id.0,
-                stat: Cow::from(""),
-            })
+        self.metrics
+            .exec_rate
+            .get_or_create(&global)
             .set(execs_per_sec);
-        let run_time = (current_time() - self.start_time).as_secs();
-        self.runtime
-            .get_or_create(&Labels {
-                client: sender_id.0,
-                stat: Cow::from(""),
-            })
-            .set(run_time.try_into().unwrap()); // run time in seconds, which can be converted to a time format by Grafana or similar
+        let run_time = cur_time.saturating_sub(self.start_time);
+        self.metrics
+            .runtime
+            .get_or_create(&global)
+            .set(run_time.as_secs().try_into().unwrap()); // run time in seconds, which can be converted to a time format by Grafana or similar
         let total_clients = self.client_stats_count().try_into().unwrap(); // convert usize to u64 (unlikely that # of clients will be > 2^64 -1...)
-        self.clients_count
-            .get_or_create(&Labels {
-                client: sender_id.0,
-                stat: Cow::from(""),
-            })
+        self.metrics
+            .clients_count
+            .get_or_create(&global)
             .set(total_clients);
+        for (key, val) in &self.aggregator.aggregated {
+            let labels = self.labels(Cow::Borrowed(GLOBAL_CLIENT_ID), Cow::Owned(key.clone()));
+            self.metrics
+                .custom_stat
+                .get_or_create(&labels)
+                .set(user_stats_value_to_f64(val));
+        }
+
+        self.remove_stale_clients(sender_id, cur_time);
+        if self.labeled_clients.contains_key(&sender_id)
+            || self
+                .max_labeled_clients
+                .is_none_or(|max| self.labeled_clients.len() < max)
+        {
+            self.labeled_clients.insert(sender_id, cur_time);
+            self.update_client_metrics(sender_id, cur_time);
+        }
 
         // display stats in a SimpleMonitor format
         let fmt = format!(
             "[Prometheus] [{} #{}] run time: {}, clients: {}, corpus: {}, objectives: {}, executions: {}, exec/sec: {}",
             event_msg,
             sender_id.0,
-            format_duration_hms(&(current_time() - self.start_time)),
+            format_duration_hms(&run_time),
             self.client_stats_count(),
-            self.corpus_size(),
-            self.objective_size(),
-            self.total_execs(),
+            corpus_size,
+            objective_size,
+            total_execs,
             self.execs_per_sec_pretty()
         );
         (self.print_fn)(&fmt);
-
-        self.client_stats_insert(sender_id);
-        let cur_client = self.client_stats_mut_for(sender_id);
-        let cur_client_clone = cur_client.clone();
-
-        for (key, val) in cur_client_clone.user_monitor {
-            // Update metrics added to the user_stats hashmap by feedback event-fires
-            // You can filter for each custom stat in promQL via labels of both the stat name and client id
-            log::info!("{key}: {val}");
-            #[allow(clippy::cast_precision_loss)]
-            let value: f64 = match val.value() {
-                UserStatsValue::Number(n) => *n as f64,
-                UserStatsValue::Float(f) => *f,
-                UserStatsValue::String(_s) => 0.0,
-                UserStatsValue::Ratio(a, b) => (*a as f64 / *b as f64) * 100.0,
-                UserStatsValue::Percent(p) => *p * 100.0,
-            };
-            self.custom_stat
-                .get_or_create(&Labels {
-                    client: sender_id.0,
-                    stat: key.clone(),
-                })
-                .set(value);
-        }
     }
 }
 
@@ -203,140 +218,226 @@ where
     /// The `listener` is the address to send logs to.
     /// The `print_fn` is the printing function that can output the logs otherwise.
     pub fn new(listener: String, print_fn: F) -> Self {
+        Self::with_time(listener, print_fn, current_time())
+    }
+
+    /// Creates the monitor with a given `start_time`.
+    pub fn with_time(listener: String, print_fn: F, start_time: Duration) -> Self {
         // Gauge's implementation of clone uses Arc
-        let corpus_count = Family::<Labels, Gauge>::default();
-        let corpus_count_clone = corpus_count.clone();
-        let objective_count = Family::<Labels, Gauge>::default();
-        let objective_count_clone = objective_count.clone();
-        let executions = Family::<Labels, Gauge>::default();
-        let executions_clone = executions.clone();
-        let exec_rate = Family::<Labels, Gauge<f64, AtomicU64>>::default();
-        let exec_rate_clone = exec_rate.clone();
-        let runtime = Family::<Labels, Gauge>::default();
-        let runtime_clone = runtime.clone();
-        let clients_count = Family::<Labels, Gauge>::default();
-        let clients_count_clone = clients_count.clone();
-        let custom_stat = Family::<Labels, Gauge<f64, AtomicU64>>::default();
-        let custom_stat_clone = custom_stat.clone();
+        let metrics = PrometheusMetrics::default();
+        let metrics_clone = metrics.clone();
 
         // Need to run the metrics server in a different thread to avoid blocking
         thread::spawn(move || {
-            block_on(serve_metrics(
-                listener,
-                corpus_count_clone,
-                objective_count_clone,
-                executions_clone,
-                exec_rate_clone,
-                runtime_clone,
-                clients_count_clone,
-                custom_stat_clone,
-            ))
-            .map_err(|err| log::error!("{err:?}"))
-            .ok();
+            block_on(serve_metrics(listener, &metrics_clone))
+                .map_err(|err| log::error!("{err:?}"))
+                .ok();
         });
-        Self {
-            print_fn,
-            start_time: current_time(),
-            client_stats: vec![],
-            corpus_count,
-            objective_count,
-            executions,
-            exec_rate,
-            runtime,
-            clients_count,
-            custom_stat,
-        }
+        Self::with_metrics(print_fn, start_time, metrics)
     }
-    /// Creates the monitor with a given `start_time`.
-    pub fn with_time(listener: String, print_fn: F, start_time: Duration) -> Self {
-        let corpus_count = Family::<Labels, Gauge>::default();
-        let corpus_count_clone = corpus_count.clone();
-        let objective_count = Family::<Labels, Gauge>::default();
-        let objective_count_clone = objective_count.clone();
-        let executions = Family::<Labels, Gauge>::default();
-        let executions_clone = executions.clone();
-        let exec_rate = Family::<Labels, Gauge<f64, AtomicU64>>::default();
-        let exec_rate_clone = exec_rate.clone();
-        let runtime = Family::<Labels, Gauge>::default();
-        let runtime_clone = runtime.clone();
-        let clients_count = Family::<Labels, Gauge>::default();
-        let clients_count_clone = clients_count.clone();
-        let custom_stat = Family::<Labels, Gauge<f64, AtomicU64>>::default();
-        let custom_stat_clone = custom_stat.clone();
 
-        thread::spawn(move || {
-            block_on(serve_metrics(
-                listener,
-                corpus_count_clone,
-                objective_count_clone,
-                executions_clone,
-                exec_rate_clone,
-                runtime_clone,
-                clients_count_clone,
-                custom_stat_clone,
-            ))
-            .map_err(|err| log::error!("{err:?}"))
-            .ok();
-        });
+    /// Creates the monitor on top of the given metrics, without serving them.
+    fn with_metrics(print_fn: F, start_time: Duration, metrics: PrometheusMetrics) -> Self {
         Self {
             print_fn,
             start_time,
             client_stats: vec![],
-            corpus_count,
-            objective_count,
-            executions,
-            exec_rate,
-            runtime,
-            clients_count,
-            custom_stat,
+            aggregator: Aggregator::new(),
+            metrics,
+            instance: Cow::Borrowed(""),
+            max_labeled_clients: None,
+            stale_client_timeout: None,
+            labeled_clients: HashMap::new(),
+        }
+    }
+
+    /// Label all series of this monitor with the given `fuzzer_instance`,
+    /// to tell multiple campaigns apart.
+    #[must_use]
+    pub fn with_instance<N>(mut self, instance: N) -> Self
+    where
+        N: Into<Cow<'static, str>>,
+    {
+        self.instance = instance.into();
+        self
+    }
+
+    /// Only export per-client series for the first `max` clients that report.
+    /// All other clients are only accounted for in the `global` series.
+    #[must_use]
+    pub fn with_max_labeled_clients(mut self, max: usize) -> Self {
+        self.max_labeled_clients = Some(max);
+        self
+    }
+
+    /// Remove the per-client series of clients that did not report for the given `timeout`.
+    /// A removed client gets its series back the next time it reports.
+    #[must_use]
+    pub fn with_stale_client_timeout(mut self, timeout: Duration) -> Self {
+        self.stale_client_timeout = Some(timeout);
+        self
+    }
+
+    fn labels(&self, client_id: Cow<'static, str>, stat: Cow<'static, str>) -> Labels {
+        Labels {
+            client_id,
+            fuzzer_instance: self.instance.clone(),
+            stat,
+        }
+    }
+
+    /// Update the series labeled with the given client
+    fn update_client_metrics(&mut self, client_id: ClientId, cur_time: Duration) {
+        let labels = self.labels(Cow::Owned(client_id.0.to_string()), Cow::Borrowed(""));
+        let client = self.client_stats_mut_for(client_id);
+        let corpus_size = client.corpus_size;
+        let objective_size = client.objective_size;
+        let executions = client.executions;
+        let execs_per_sec = client.execs_per_sec(cur_time);
+        let user_stats = client
+            .user_monitor
+            .iter()
+            .map(|(key, val)| (key.clone(), user_stats_value_to_f64(val.value())))
+            .collect::<Vec<_>>();
+
+        self.metrics
+            .corpus_count
+            .get_or_create(&labels)
+            .set(corpus_size.try_into().unwrap());
+        self.metrics
+            .objective_count
+            .get_or_create(&labels)
+            .set(objective_size.try_into().unwrap());
+        self.metrics
+            .executions
+            .get_or_create(&labels)
+            .set(executions.try_into().unwrap());
+        self.metrics
+            .exec_rate
+            .get_or_create(&labels)
+            .set(execs_per_sec);
+
+        for (key, value) in user_stats {
+            // Update metrics added to the user_stats hashmap by feedback event-fires
+            // You can filter for each custom stat in promQL via labels of both the stat name and client id
+            log::info!("{key}: {value}");
+            self.metrics
+                .custom_stat
+                .get_or_create(&Labels {
+                    stat: key,
+                    ..labels.clone()
+                })
+                .set(value);
+        }
+    }
+
+    /// Remove the series of all clients, other than `sender_id`, that did not report in time
+    fn remove_stale_clients(&mut self, sender_id: ClientId, cur_time: Duration) {
+        let Some(timeout) = self.stale_client_timeout else {
+            return;
+        };
+        let stale = self
+            .labeled_clients
+            .iter()
+            .filter(|(client_id, last_seen)| {
+                **client_id != sender_id && cur_time.saturating_sub(**last_seen) > timeout
+            })
+            .map(|(client_id, _)| *client_id)
+            .collect::<Vec<_>>();
+
+        for client_id in stale {
+            self.labeled_clients.remove(&client_id);
+            let labels = self.labels(Cow::Owned(client_id.0.to_string()), Cow::Borrowed(""));
+            self.metrics.corpus_count.remove(&labels);
+            self.metrics.objective_count.remove(&labels);
+            self.metrics.executions.remove(&labels);
+            self.metrics.exec_rate.remove(&labels);
+            for key in self.client_stats_for(client_id).user_monitor.keys() {
+                self.metrics.custom_stat.remove(&Labels {
+                    stat: key.clone(),
+                    ..labels.clone()
+                });
+            }
         }
     }
 }
 
-/// Set up an HTTP endpoint /metrics
-#[allow(clippy::too_many_arguments)]
-pub(crate) async fn serve_metrics(
-    listener: String,
-    corpus: Family<Labels, Gauge>,
-    objectives: Family<Labels, Gauge>,
+/// Convert a [`UserStatsValue`] to the value of a gauge
+#[allow(clippy::cast_precision_loss)]
+fn user_stats_value_to_f64(value: &UserStatsValue) -> f64 {
+    match value {
+        UserStatsValue::Number(n) => *n as f64,
+        UserStatsValue::Float(f) => *f,
+        UserStatsValue::String(_s) => 0.0,
+        UserStatsValue::Ratio(_, 0) => 0.0,
+        UserStatsValue::Ratio(a, b) => (*a as f64 / *b as f64) * 100.0,
+        UserStatsValue::Percent(p) => *p * 100.0,
+    }
+}
+
+/// All metrics exported by the [`PrometheusMonitor`]
+#[derive(Clone, Default)]
+struct PrometheusMetrics {
+    corpus_count: Family<Labels, Gauge>,
+    objective_count: Family<Labels, Gauge>,
     executions: Family<Labels, Gauge>,
     exec_rate: Family<Labels, Gauge<f64, AtomicU64>>,
     runtime: Family<Labels, Gauge>,
     clients_count: Family<Labels, Gauge>,
     custom_stat: Family<Labels, Gauge<f64, AtomicU64>>,
-) -> Result<(), std::io::Error> {
-    let mut registry = Registry::default();
-
-    registry.register("corpus_count", "Number of test cases in the corpus", corpus);
-    registry.register(
-        "objective_count",
-        "Number of times the objective has been achieved (e.g., crashes)",
-        objectives,
-    );
-    registry.register(
-        "executions_total",
-        "Number of executions the fuzzer has done",
-        executions,
-    );
-    registry.register("execution_rate", "Rate of executions per second", exec_rate);
-    registry.register(
-        "runtime",
-        "How long the fuzzer has been running for (seconds)",
-        runtime,
-    );
-    registry.register(
-        "clients_count",
-        "How many clients have been spawned for the fuzzing job",
-        clients_count,
-    );
-    registry.register(
-        "custom_stat",
-        "A metric to contain custom stats returned by feedbacks, filterable by label",
-        custom_stat,
-    );
+}
+
+impl PrometheusMetrics {
+    /// A [`Registry`] containing all metrics
+    fn registry(&self) -> Registry {
+        let mut registry = Registry::default();
 
+        registry.register(
+            "corpus_count",
+            "Number of test cases in the corpus",
+            self.corpus_count.clone(),
+        );
+        registry.register(
+            "objective_count",
+            "Number of times the objective has been achieved (e.g., crashes)",
+            self.objective_count.clone(),
+        );
+        registry.register(
+            "executions_total",
+            "Number of executions the fuzzer has done",
+            self.executions.clone(),
+        );
+        registry.register(
+            "execution_rate",
+            "Rate of executions per second",
+            self.exec_rate.clone(),
+        );
+        registry.register(
+            "runtime",
+            "How long the fuzzer has been running for (seconds)",
+            self.runtime.clone(),
+        );
+        registry.register(
+            "clients_count",
+            "How many clients have been spawned for the fuzzing job",
+            self.clients_count.clone(),
+        );
+        registry.register(
+            "custom_stat",
+            "A metric to contain custom stats returned by feedbacks, filterable by label",
+            self.custom_stat.clone(),
+        );
+        registry
+    }
+}
+
+/// Set up an HTTP endpoint /metrics
+async fn serve_metrics(
+    listener: String,
+    metrics: &PrometheusMetrics,
+) -> Result<(), std::io::Error> {
     let mut app = tide::with_state(State {
-        registry: Arc::new(registry),
+        registry: Arc::new(metrics.registry()),
     });
 
     app.at("/")
@@ -358,8 +459,11 @@ pub(crate) async fn serve_metrics(
 /// Struct used to define the labels in `prometheus`.
 #[derive(Clone, Hash, PartialEq, Eq, EncodeLabelSet, Debug)]
 pub struct Labels {
-    /// The `sender_id` helps to differentiate between clients when multiple are spawned.
-    client: u32,
+    /// The `sender_id` helps to differentiate between clients when multiple are spawned,
+    /// [`GLOBAL_CLIENT_ID`] for series aggregated over all clients.
+    client_id: Cow<'static, str>,
+    /// The user-assigned name of this fuzzer instance, see [`PrometheusMonitor::with_instance`].
+    fuzzer_instance: Cow<'static, str>,
     /// Used for `custom_stat` filtering.
     stat: Cow<'static, str>,
 }
@@ -369,3 +473,72 @@ pub struct Labels {
 struct State {
     registry: Arc<Registry>,
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::{borrow::Cow, string::String};
+    use core::time::Duration;
+
+    use libafl_bolts::{current_time, ClientId};
+    use prometheus_client::encoding::text::encode;
+
+    use super::{PrometheusMetrics, PrometheusMonitor};
+    use crate::monitors::{AggregatorOps, Monitor, UserStats, UserStatsValue};
+
+    fn encoded(metrics: &PrometheusMetrics) -> String {
+        let mut encoded = String::new();
+        encode(&mut encoded, &metrics.registry()).unwrap();
+        encoded
+    }
+
+    #[test]
+    fn test_prometheus_client_labels() {
+        let metrics = PrometheusMetrics::default();
+        let mut monitor = PrometheusMonitor::with_metrics(|_| {}, current_time(), metrics.clone())
+            .with_instance("campaign")
+            .with_max_labeled_clients(1);
+
+        for (client, execs) in [(1, 100), (2, 200)] {
+            let client_id = ClientId(client);
+            monitor.client_stats_insert(client_id);
+            let stats = monitor.client_stats_mut_for(client_id);
+            stats.update_executions(execs, current_time());
+            stats.update_user_stats(
+                Cow::Borrowed("edges"),
+                UserStats::new(UserStatsValue::Number(execs), AggregatorOps::Sum),
+            );
+            monitor.aggregate("edges");
+            monitor.display("Testcase", client_id);
+        }
+
+        let encoded = encoded(&metrics);
+        assert!(encoded
+            .contains(r#"executions_total{client_id="1",fuzzer_instance="campaign",stat=""} 100"#));
+        assert!(encoded.contains(
+            r#"executions_total{client_id="global",fuzzer_instance="campaign",stat=""} 300"#
+        ));
+        assert!(encoded
+            .contains(r#"custom_stat{client_id="1",fuzzer_instance="campaign",stat="edges"} 100"#));
+        assert!(encoded.contains(
+            r#"custom_stat{client_id="global",fuzzer_instance="campaign",stat="edges"} 300"#
+        ));
+        // Over the cap, client 2 only shows up in the global series
+        assert!(!encoded.contains(r#"client_id="2""#));
+    }
+
+    #[test]
+    fn test_prometheus_stale_clients() {
+        let metrics = PrometheusMetrics::default();
+        let mut monitor = PrometheusMonitor::with_metrics(|_| {}, current_time(), metrics.clone())
+            .with_stale_client_timeout(Duration::ZERO);
+
+        monitor.display("Testcase", ClientId(1));
+        assert!(encoded(&metrics).contains(r#"client_id="1""#));
+
+        std::thread::sleep(Duration::from_millis(10));
+        monitor.display("Testcase", ClientId(2));
+        let encoded = encoded(&metrics);
+        assert!(!encoded.contains(r#"client_id="1""#));
+        assert!(encoded.contains(r#"client_id="2""#));
+    }
+}
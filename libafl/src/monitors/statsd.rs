@@ -0,0 +1,535 @@
+//! The [`StatsdMonitor`] pushes fuzzer progress to a `StatsD` endpoint over UDP.
+//!
+//! ## Overview
+//!
+//! Every flush interval, the monitor sends the standard stats (executions, executions per second,
+//! corpus size, objectives, clients, run time) and all numeric user stats as gauges, both for
+//! every client and aggregated over all clients. Metrics are batched into datagrams that fit the MTU.
+//!
+//! Metrics are tagged in the `DogStatsD` format, `|#client_id:<id>,campaign:<name>`,
+//! with `client_id:global` for the aggregated metrics. Plain `StatsD` servers that don't know about
+//! tags can be served by disabling them with [`StatsdMonitor::with_tags`].
+//!
+//! ## Resilience
+//!
+//! The monitor never panics on network errors. The first failure is logged, and the monitor retries
+//! with an exponential backoff, dropping the metrics in between.
+//!
+//! ## How to use it
+//!
+//! ```rust
+//! use libafl::monitors::StatsdMonitor;
+//!
+//! let mon = StatsdMonitor::new("127.0.0.1:8125", |s| log::info!("{s}"))
+//!     .with_prefix("fuzzing")
+//!     .with_campaign("libpng");
+//!
+//! // pass it into the event manager like any other monitor:
+//! // let mgr = SimpleEventManager::new(mon);
+//! ```
+
+use alloc::{
+    borrow::Cow,
+    fmt::Debug,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, fmt::Write, time::Duration};
+use std::{
+    io,
+    net::{ToSocketAddrs, UdpSocket},
+};
+
+use libafl_bolts::{current_time, format_duration_hms, ClientId};
+
+use crate::monitors::{Aggregator, ClientStats, Monitor, UserStatsValue};
+
+/// The default prefix of all metric names
+pub const DEFAULT_STATSD_PREFIX: &str = "libafl";
+/// The default interval between two flushes
+pub const DEFAULT_STATSD_FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+/// The default maximum size of a datagram, fits into an Ethernet MTU with IPv6 and UDP headers
+pub const DEFAULT_STATSD_DATAGRAM_SIZE: usize = 1432;
+/// The `client_id` tag of the metrics aggregated over all clients
+pub const STATSD_GLOBAL_CLIENT_ID: &str = "global";
+
+/// The first delay before reconnecting after a network error
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// The maximum delay before reconnecting after a network error
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Tracking monitor that pushes the stats to a `StatsD` server.
+pub struct StatsdMonitor<F>
+where
+    F: FnMut(&str),
+{
+    print_fn: F,
+    start_time: Duration,
+    client_stats: Vec<ClientStats>,
+    aggregator: Aggregator,
+    /// The address of the `StatsD` server
+    target: String,
+    /// The prefix of all metric names
+    prefix: Cow<'static, str>,
+    /// The value of the `campaign` tag
+    campaign: Option<Cow<'static, str>>,
+    /// Add `DogStatsD` tags to the metrics
+    tags: bool,
+    flush_interval: Duration,
+    max_datagram_size: usize,
+    last_flush: Duration,
+    /// The connected socket, `None` until the first flush or after a network error
+    socket: Option<UdpSocket>,
+    /// Don't try to reconnect before this time
+    next_connect: Duration,
+    /// The current delay between reconnects
+    backoff: Duration,
+    /// If a network error has been logged since the last successful flush
+    error_logged: bool,
+}
+
+impl<F> Debug for StatsdMonitor<F>
+where
+    F: FnMut(&str),
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StatsdMonitor")
+            .field("start_time", &self.start_time)
+            .field("client_stats", &self.client_stats)
+            .field("target", &self.target)
+            .field("prefix", &self.prefix)
+            .field("campaign", &self.campaign)
+            .field("flush_interval", &self.flush_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F> Clone for StatsdMonitor<F>
+where
+    F: FnMut(&str) + Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            print_fn: self.print_fn.clone(),
+            start_time: self.start_time,
+            client_stats: self.client_stats.clone(),
+            aggregator: self.aggregator.clone(),
+            target: self.target.clone(),
+            prefix: self.prefix.clone(),
+            campaign: self.campaign.clone(),
+            tags: self.tags,
+            flush_interval: self.flush_interval,
+            max_datagram_size: self.max_datagram_size,
+            last_flush: self.last_flush,
+            // The clone connects on its own
+            socket: None,
+            next_connect: Duration::ZERO,
+            backoff: INITIAL_BACKOFF,
+            error_logged: false,
+        }
+    }
+}
+
+impl<F> Monitor for StatsdMonitor<F>
+where
+    F: FnMut(&str),
+{
+    /// the client monitor, mutable
+    fn client_stats_mut(&mut self) -> &mut Vec<ClientStats> {
+        &mut self.client_stats
+    }
+
+    /// the client monitor
+    fn client_stats(&self) -> &[ClientStats] {
+        &self.client_stats
+    }
+
+    /// Time this fuzzing run stated
+    fn start_time(&self) -> Duration {
+        self.start_time
+    }
+
+    /// Set creation time
+    fn set_start_time(&mut self, time: Duration) {
+        self.start_time = time;
+    }
+
+    fn aggregate(&mut self, name: &str) {
+        self.aggregator.aggregate(name, &self.client_stats);
+    }
+
+    fn display(&mut self, event_msg: &str, sender_id: ClientId) {
+        let cur_time = current_time();
+        self.client_stats_insert(sender_id);
+
+        if cur_time.saturating_sub(self.last_flush) >= self.flush_interval {
+            self.last_flush = cur_time;
+            let metrics = self.metrics(cur_time);
+            self.send(&metrics, cur_time);
+        }
+
+        // display stats in a SimpleMonitor format
+        let fmt = format!(
+            "[StatsD] [{} #{}] run time: {}, clients: {}, corpus: {}, objectives: {}, executions: {}, exec/sec: {}",
+            event_msg,
+            sender_id.0,
+            format_duration_hms(&cur_time.saturating_sub(self.start_time)),
+            self.client_stats_count(),
+            self.corpus_size(),
+            self.objective_size(),
+            self.total_execs(),
+            self.execs_per_sec_pretty()
+        );
+        (self.print_fn)(&fmt);
+    }
+}
+
+impl<F> StatsdMonitor<F>
+where
+    F: FnMut(&str),
+{
+    /// Create a new [`StatsdMonitor`] sending to the `StatsD` server at `target`, e.g., `127.0.0.1:8125`.
+    /// The `print_fn` is the printing function that can output the logs otherwise.
+    pub fn new<T>(target: T, print_fn: F) -> Self
+    where
+        T: Into<String>,
+    {
+        Self::with_time(target, print_fn, current_time())
+    }
+
+    /// Creates the monitor with a given `start_time`.
+    pub fn with_time<T>(target: T, print_fn: F, start_time: Duration) -> Self
+    where
+        T: Into<String>,
+    {
+        Self {
+            print_fn,
+            start_time,
+            client_stats: vec![],
+            aggregator: Aggregator::new(),
+            target: target.into(),
+            prefix: Cow::Borrowed(DEFAULT_STATSD_PREFIX),
+            campaign: None,
+            tags: true,
+            flush_interval: DEFAULT_STATSD_FLUSH_INTERVAL,
+            max_datagram_size: DEFAULT_STATSD_DATAGRAM_SIZE,
+            last_flush: Duration::ZERO,
+            socket: None,
+            next_connect: Duration::ZERO,
+            backoff: INITIAL_BACKOFF,
+            error_logged: false,
+        }
+    }
+
+    /// Prefix all metric names with `prefix`, [`DEFAULT_STATSD_PREFIX`] by default
+    #[must_use]
+    pub fn with_prefix<P>(mut self, prefix: P) -> Self
+    where
+        P: Into<Cow<'static, str>>,
+    {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Tag all metrics with `campaign:<campaign>`
+    #[must_use]
+    pub fn with_campaign<N>(mut self, campaign: N) -> Self
+    where
+        N: Into<Cow<'static, str>>,
+    {
+        self.campaign = Some(campaign.into());
+        self
+    }
+
+    /// Send `DogStatsD` tags, on by default.
+    /// Without tags, the client id becomes part of the metric name instead.
+    #[must_use]
+    pub fn with_tags(mut self, tags: bool) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Push the metrics at most once per `flush_interval`, [`DEFAULT_STATSD_FLUSH_INTERVAL`] by default
+    #[must_use]
+    pub fn with_flush_interval(mut self, flush_interval: Duration) -> Self {
+        self.flush_interval = flush_interval;
+        self
+    }
+
+    /// Limit datagrams to `max_datagram_size` bytes, [`DEFAULT_STATSD_DATAGRAM_SIZE`] by default
+    #[must_use]
+    pub fn with_max_datagram_size(mut self, max_datagram_size: usize) -> Self {
+        self.max_datagram_size = max_datagram_size;
+        self
+    }
+
+    /// Format a single gauge
+    fn gauge(&self, metrics: &mut Vec<String>, name: &str, value: f64, client_id: &str) {
+        if !value.is_finite() {
+            return;
+        }
+        let name = sanitize_name(name);
+        let mut line = if self.tags {
+            format!("{}.{name}:{value}|g|#client_id:{client_id}", self.prefix)
+        } else {
+            format!("{}.{client_id}.{name}:{value}|g", self.prefix)
+        };
+        if self.tags {
+            if let Some(campaign) = &self.campaign {
+                write!(line, ",campaign:{}", sanitize_tag(campaign)).unwrap();
+            }
+        }
+        metrics.push(line);
+    }
+
+    /// All metrics of this flush
+    #[allow(clippy::cast_precision_loss)]
+    fn metrics(&mut self, cur_time: Duration) -> Vec<String> {
+        let mut metrics = vec![];
+
+        let execs_per_sec = self.execs_per_sec();
+        let global = [
+            ("executions", self.total_execs() as f64),
+            ("execs_per_sec", execs_per_sec),
+            ("corpus", self.corpus_size() as f64),
+            ("objectives", self.objective_size() as f64),
+            ("clients", self.client_stats_count() as f64),
+            (
+                "run_time",
+                cur_time.saturating_sub(self.start_time).as_secs_f64(),
+            ),
+        ];
+        for (name, value) in global {
+            self.gauge(&mut metrics, name, value, STATSD_GLOBAL_CLIENT_ID);
+        }
+        for (name, value) in &self.aggregator.aggregated {
+            if let Some(value) = user_stats_value_to_f64(value) {
+                self.gauge(
+                    &mut metrics,
+                    &format!("user.{name}"),
+                    value,
+                    STATSD_GLOBAL_CLIENT_ID,
+                );
+            }
+        }
+
+        let mut client_stats = core::mem::take(&mut self.client_stats);
+        for (id, client) in client_stats.iter_mut().enumerate() {
+            if !client.enabled {
+                continue;
+            }
+            let id = id.to_string();
+            let stats = [
+                ("executions", client.executions as f64),
+                ("execs_per_sec", client.execs_per_sec(cur_time)),
+                ("corpus", client.corpus_size as f64),
+                ("objectives", client.objective_size as f64),
+            ];
+            for (name, value) in stats {
+                self.gauge(&mut metrics, name, value, &id);
+            }
+            for (name, stat) in &client.user_monitor {
+                if let Some(value) = user_stats_value_to_f64(stat.value()) {
+                    self.gauge(&mut metrics, &format!("user.{name}"), value, &id);
+                }
+            }
+        }
+        self.client_stats = client_stats;
+
+        metrics
+    }
+
+    /// Send the metrics, batched into datagrams of at most `max_datagram_size` bytes
+    fn send(&mut self, metrics: &[String], cur_time: Duration) {
+        if self.socket.is_none() {
+            if cur_time < self.next_connect {
+                // Still backing off, drop these metrics
+                return;
+            }
+            match connect(&self.target) {
+                Ok(socket) => self.socket = Some(socket),
+                Err(err) => {
+                    self.network_error(&err, cur_time);
+                    return;
+                }
+            }
+        }
+
+        for datagram in batch(metrics, self.max_datagram_size) {
+            let res = self.socket.as_ref().unwrap().send(datagram.as_bytes());
+            if let Err(err) = res {
+                self.socket = None;
+                self.network_error(&err, cur_time);
+                return;
+            }
+        }
+
+        self.backoff = INITIAL_BACKOFF;
+        self.error_logged = false;
+    }
+
+    /// Log the first network error and back off before reconnecting
+    fn network_error(&mut self, err: &io::Error, cur_time: Duration) {
+        if !self.error_logged {
+            log::error!(
+                "Failed to send metrics to StatsD at {}, retrying with backoff: {err}",
+                self.target
+            );
+            self.error_logged = true;
+        }
+        self.next_connect = cur_time + self.backoff;
+        self.backoff = (self.backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+/// Bind a socket of the address family of `target`, and connect it
+fn connect(target: &str) -> Result<UdpSocket, io::Error> {
+    let addr = target.to_socket_addrs()?.next().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{target} did not resolve to any address"),
+        )
+    })?;
+    let socket = if addr.is_ipv4() {
+        UdpSocket::bind("0.0.0.0:0")?
+    } else {
+        UdpSocket::bind("[::]:0")?
+    };
+    socket.connect(addr)?;
+    Ok(socket)
+}
+
+/// Joins the metrics into newline-separated datagrams of at most `max_size` bytes.
+/// A single metric larger than `max_size` is sent on its own.
+fn batch(metrics: &[String], max_size: usize) -> Vec<String> {
+    let mut datagrams = vec![];
+    let mut cur = String::new();
+    for metric in metrics {
+        if !cur.is_empty() && cur.len() + 1 + metric.len() > max_size {
+            datagrams.push(core::mem::take(&mut cur));
+        }
+        if !cur.is_empty() {
+            cur.push('\n');
+        }
+        cur.push_str(metric);
+    }
+    if !cur.is_empty() {
+        datagrams.push(cur);
+    }
+    datagrams
+}
+
+/// Metric names may only contain alphanumerics, `_` and `.`
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Tag values must not contain the `DogStatsD` separators
+fn sanitize_tag(tag: &str) -> String {
+    tag.chars()
+        .map(|c| {
+            if matches!(c, ',' | '|' | '#' | '\n') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// The value of numeric user stats, with ratios and percentages in percent
+#[allow(clippy::cast_precision_loss)]
+fn user_stats_value_to_f64(value: &UserStatsValue) -> Option<f64> {
+    match value {
+        UserStatsValue::Number(n) => Some(*n as f64),
+        UserStatsValue::Float(f) => Some(*f),
+        UserStatsValue::String(_) => None,
+        UserStatsValue::Ratio(_, 0) => Some(0.0),
+        UserStatsValue::Ratio(a, b) => Some((*a as f64 / *b as f64) * 100.0),
+        UserStatsValue::Percent(p) => Some(*p * 100.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{
+        borrow::Cow,
+        string::{String, ToString},
+        vec::Vec,
+    };
+    use core::time::Duration;
+    use std::net::UdpSocket;
+
+    use libafl_bolts::ClientId;
+
+    use super::batch;
+    use crate::monitors::{AggregatorOps, Monitor, StatsdMonitor, UserStats, UserStatsValue};
+
+    #[test]
+    fn test_statsd_metrics() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+
+        let mut monitor = StatsdMonitor::new(server.local_addr().unwrap().to_string(), |_| {})
+            .with_prefix("fuzz")
+            .with_campaign("test,campaign")
+            .with_flush_interval(Duration::ZERO);
+        monitor.client_stats_insert(ClientId(1));
+        let client = monitor.client_stats_mut_for(ClientId(1));
+        client.update_corpus_size(42);
+        client.update_user_stats(
+            Cow::from("edges"),
+            UserStats::new(UserStatsValue::Ratio(1, 4), AggregatorOps::Avg),
+        );
+        monitor.aggregate("edges");
+        monitor.display("Testcase", ClientId(1));
+
+        let mut buf = [0; 2048];
+        let mut received = String::new();
+        while let Ok(len) = server.recv(&mut buf) {
+            assert!(len <= super::DEFAULT_STATSD_DATAGRAM_SIZE);
+            received.push_str(core::str::from_utf8(&buf[..len]).unwrap());
+            received.push('\n');
+            if received.contains("fuzz.user.edges:25|g|#client_id:1") {
+                break;
+            }
+        }
+        let lines = received.lines().collect::<Vec<_>>();
+        assert!(lines.contains(&"fuzz.corpus:42|g|#client_id:global,campaign:test_campaign"));
+        assert!(lines.contains(&"fuzz.corpus:42|g|#client_id:1,campaign:test_campaign"));
+        assert!(lines.contains(&"fuzz.user.edges:25|g|#client_id:global,campaign:test_campaign"));
+    }
+
+    #[test]
+    fn test_statsd_batching() {
+        let metrics = (0..100)
+            .map(|i| format!("libafl.metric_{i}:{i}|g"))
+            .collect::<Vec<_>>();
+        let datagrams = batch(&metrics, 100);
+        assert!(datagrams.len() > 1);
+        assert!(datagrams.iter().all(|datagram| datagram.len() <= 100));
+        assert_eq!(datagrams.join("\n").lines().count(), metrics.len());
+    }
+
+    #[test]
+    fn test_statsd_unreachable() {
+        // Errors are logged and retried later, but never panic
+        let mut monitor =
+            StatsdMonitor::new("not a valid address", |_| {}).with_flush_interval(Duration::ZERO);
+        for _ in 0..3 {
+            monitor.display("Testcase", ClientId(0));
+        }
+        assert!(monitor.socket.is_none());
+        assert!(monitor.error_logged);
+    }
+}
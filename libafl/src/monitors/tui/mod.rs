@@ -29,8 +29,12 @@ use serde_json::Value;
 use typed_builder::TypedBuilder;
 
 #[cfg(feature = "introspection")]
-use super::{ClientPerfMonitor, PerfFeature};
-use crate::monitors::{Aggregator, AggregatorOps, ClientStats, Monitor, UserStats, UserStatsValue};
+use super::{ClientPerfMonitor, IntrospectionSummary, PerfFeature};
+use crate::monitors::{
+    Aggregator, AggregatorOps, ClientLiveness, ClientStats, LivenessSummary, Monitor, UserStats,
+    UserStatsValue, CENTRALIZED_ACCEPTED_STAT, CENTRALIZED_BACKLOG_STAT,
+    CENTRALIZED_DISCARDED_STAT, CENTRALIZED_FORWARDED_STAT, CENTRALIZED_ROLE_STAT,
+};
 
 #[allow(missing_docs)]
 pub mod ui;
@@ -58,6 +62,9 @@ pub struct TuiMonitorConfig {
     /// Enables unicode TUI graphics, Looks better but may interfere with old terminals.
     #[builder(default = true)]
     pub enhanced_graphics: bool,
+    /// Mark clients that went silent, e.g., because they died, see [`ClientLiveness`].
+    #[builder(default, setter(strip_option))]
+    pub client_liveness: Option<ClientLiveness>,
 }
 
 /// A single status entry for timings
@@ -136,6 +143,8 @@ pub struct PerfTuiContext {
     pub unmeasured: f64,
     /// Time spent in each individual stage
     pub stages: Vec<Vec<(String, f64)>>,
+    /// The label of each individual stage, including its name if it reported one
+    pub stage_labels: Vec<String>,
     /// Time spent in each individual feedback
     pub feedbacks: Vec<(String, f64)>,
 }
@@ -158,10 +167,15 @@ impl PerfTuiContext {
         other_percent -= self.manager;
 
         self.stages.clear();
+        self.stage_labels.clear();
 
         // Calculate each stage
         // Make sure we only iterate over used stages
-        for (_stage_index, features) in m.used_stages() {
+        for (stage_index, features) in m.used_stages() {
+            self.stage_labels.push(match m.stage_name(stage_index) {
+                Some(name) => format!("stage {stage_index} ({name})"),
+                None => format!("stage {stage_index}"),
+            });
             let mut features_percentages = vec![];
 
             for (feature_index, feature) in features.iter().enumerate() {
@@ -265,6 +279,54 @@ pub struct ClientTuiContext {
     pub process_timing: ProcessTiming,
     pub item_geometry: ItemGeometry,
     pub user_stats: HashMap<Cow<'static, str>, UserStats>,
+
+    /// The role of this client in the centralized architecture, if it reported one
+    pub role: Option<String>,
+    /// The host and the process of this client, once it registered
+    pub identity: Option<String>,
+    pub forwarding: ForwardingStats,
+    /// If this client went silent, see [`ClientLiveness`]
+    pub silent: bool,
+}
+
+/// The forwarding stats reported by the centralized event manager
+#[allow(missing_docs)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ForwardingStats {
+    pub forwarded: u64,
+    pub accepted: u64,
+    pub discarded: u64,
+    pub main_backlog: u64,
+}
+
+impl ForwardingStats {
+    /// Grab the forwarding stats of a single client
+    fn grab_data(&mut self, client: &ClientStats) {
+        let number = |name| match client.get_user_stats(name).map(UserStats::value) {
+            Some(UserStatsValue::Number(n)) => *n,
+            _ => 0,
+        };
+        self.forwarded = number(CENTRALIZED_FORWARDED_STAT);
+        self.accepted = number(CENTRALIZED_ACCEPTED_STAT);
+        self.discarded = number(CENTRALIZED_DISCARDED_STAT);
+        self.main_backlog = number(CENTRALIZED_BACKLOG_STAT);
+    }
+
+    /// Sum up the forwarding stats of multiple clients
+    fn add(&mut self, other: &Self) {
+        self.forwarded += other.forwarded;
+        self.accepted += other.accepted;
+        self.discarded += other.discarded;
+        self.main_backlog = self.main_backlog.max(other.main_backlog);
+    }
+}
+
+/// The centralized manager role of a client, taken from its [`CENTRALIZED_ROLE_STAT`] user stat
+fn client_role(client: &ClientStats) -> Option<String> {
+    match client.get_user_stats(CENTRALIZED_ROLE_STAT)?.value() {
+        UserStatsValue::String(role) => Some(role.to_string()),
+        _ => None,
+    }
 }
 
 impl ClientTuiContext {
@@ -318,6 +380,10 @@ impl ClientTuiContext {
         for (key, val) in &client.user_monitor {
             self.user_stats.insert(key.clone(), val.clone());
         }
+
+        self.role = client_role(client);
+        self.identity = client.identity.as_ref().map(ToString::to_string);
+        self.forwarding.grab_data(client);
     }
 }
 
@@ -334,6 +400,9 @@ pub struct TuiContext {
 
     #[cfg(feature = "introspection")]
     pub introspection: HashMap<usize, PerfTuiContext>,
+    /// The introspection stats of all clients, summed up
+    #[cfg(feature = "introspection")]
+    pub total_introspection: IntrospectionSummary,
 
     pub clients: HashMap<usize, ClientTuiContext>,
 
@@ -348,8 +417,17 @@ pub struct TuiContext {
     pub total_cycles_done: u64,
     pub total_corpus_count: u64,
 
+    /// The number of silent clients, and their objectives and corpus count, see [`ClientLiveness`]
+    pub silent_clients_num: usize,
+    pub silent_solutions: u64,
+    pub silent_corpus_count: u64,
+
     pub total_process_timing: ProcessTiming,
     pub total_item_geometry: ItemGeometry,
+
+    /// The forwarding stats over all clients, `None` if no client reported a centralized role
+    pub total_forwarding: Option<ForwardingStats>,
+    pub forwarded_timed: TimedStats,
 }
 
 impl TuiContext {
@@ -364,6 +442,8 @@ impl TuiContext {
 
             #[cfg(feature = "introspection")]
             introspection: HashMap::default(),
+            #[cfg(feature = "introspection")]
+            total_introspection: IntrospectionSummary::new(),
             clients: HashMap::default(),
 
             client_logs: VecDeque::with_capacity(DEFAULT_LOGS_NUMBER),
@@ -376,9 +456,38 @@ impl TuiContext {
             total_solutions: 0,
             total_cycles_done: 0,
             total_corpus_count: 0,
+            silent_clients_num: 0,
+            silent_solutions: 0,
+            silent_corpus_count: 0,
             total_item_geometry: ItemGeometry::new(),
             total_process_timing: ProcessTiming::new(),
+
+            total_forwarding: None,
+            forwarded_timed: TimedStats::new(Duration::from_secs(DEFAULT_TIME_WINDOW)),
+        }
+    }
+
+    /// The forwarded testcases per second, over the time window of the stats
+    ///
+    /// The forwarded count drops when a secondary restarts and its stats start over,
+    /// such a drop counts as no progress rather than as negative progress.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn forwarded_per_sec(&self) -> f64 {
+        let series = &self.forwarded_timed.series;
+        let (Some(first), Some(last)) = (series.front(), series.back()) else {
+            return 0.0;
+        };
+        let secs = last.time.saturating_sub(first.time).as_secs_f64();
+        if secs == 0.0 {
+            return 0.0;
         }
+        let forwarded = series
+            .iter()
+            .zip(series.iter().skip(1))
+            .map(|(prev, next)| next.item.saturating_sub(prev.item))
+            .sum::<u64>();
+        forwarded as f64 / secs
     }
 }
 
@@ -390,15 +499,18 @@ pub struct TuiMonitor {
     start_time: Duration,
     client_stats: Vec<ClientStats>,
     aggregator: Aggregator,
+    liveness: Option<ClientLiveness>,
 }
 
 impl From<TuiMonitorConfig> for TuiMonitor {
     #[allow(deprecated)]
     fn from(builder: TuiMonitorConfig) -> Self {
-        Self::with_time(
+        let mut monitor = Self::with_time(
             TuiUi::with_version(builder.title, builder.version, builder.enhanced_graphics),
             builder.start_time,
-        )
+        );
+        monitor.liveness = builder.client_liveness;
+        monitor
     }
 }
 
@@ -433,24 +545,47 @@ impl Monitor for TuiMonitor {
 
         {
             // TODO implement floating-point support for TimedStat
-            let execsec = self.execs_per_sec() as u64;
+            let (execsec, corpus_size, objective_size, silent) =
+                if let Some(liveness) = self.liveness {
+                    let summary = liveness.summary(&self.client_stats, cur_time);
+                    (
+                        liveness.execs_per_sec(&mut self.client_stats, cur_time) as u64,
+                        summary.live_corpus_size,
+                        summary.live_objective_size,
+                        summary,
+                    )
+                } else {
+                    (
+                        self.execs_per_sec() as u64,
+                        self.corpus_size(),
+                        self.objective_size(),
+                        LivenessSummary::default(),
+                    )
+                };
             let totalexec = self.total_execs();
             let run_time = cur_time - self.start_time;
             let total_process_timing = self.process_timing();
+            let total_forwarding = self.forwarding();
 
             let mut ctx = self.context.write().unwrap();
             ctx.total_process_timing = total_process_timing;
-            ctx.corpus_size_timed.add(run_time, self.corpus_size());
-            ctx.objective_size_timed
-                .add(run_time, self.objective_size());
+            ctx.corpus_size_timed.add(run_time, corpus_size);
+            ctx.objective_size_timed.add(run_time, objective_size);
             ctx.execs_per_sec_timed.add(run_time, execsec);
             ctx.total_execs = totalexec;
             ctx.clients_num = self.client_stats.len();
             ctx.total_map_density = self.map_density();
-            ctx.total_solutions = self.objective_size();
+            ctx.total_solutions = objective_size;
             ctx.total_cycles_done = 0;
-            ctx.total_corpus_count = self.corpus_size();
+            ctx.total_corpus_count = corpus_size;
+            ctx.silent_clients_num = silent.silent_clients.len();
+            ctx.silent_solutions = silent.silent_objective_size;
+            ctx.silent_corpus_count = silent.silent_corpus_size;
             ctx.total_item_geometry = self.item_geometry();
+            if let Some(forwarding) = total_forwarding {
+                ctx.forwarded_timed.add(run_time, forwarding.forwarded);
+            }
+            ctx.total_forwarding = total_forwarding;
         }
 
         self.client_stats_insert(sender_id);
@@ -482,6 +617,15 @@ impl Monitor for TuiMonitor {
                 .entry(sender_id.0 as usize)
                 .or_default()
                 .grab_data(client, exec_sec);
+            if let Some(liveness) = self.liveness {
+                for (id, client) in self.client_stats.iter().enumerate() {
+                    if liveness.is_dropped(client, cur_time) {
+                        ctx.clients.remove(&id);
+                    } else if let Some(client_ctx) = ctx.clients.get_mut(&id) {
+                        client_ctx.silent = liveness.is_silent(client, cur_time);
+                    }
+                }
+            }
             while ctx.client_logs.len() >= DEFAULT_LOGS_NUMBER {
                 ctx.client_logs.pop_front();
             }
@@ -490,6 +634,8 @@ impl Monitor for TuiMonitor {
 
         #[cfg(feature = "introspection")]
         {
+            let total_introspection = self.introspection_summary();
+            self.context.write().unwrap().total_introspection = total_introspection;
             // Print the client performance monitor. Skip the Client IDs that have never sent anything.
             for (i, client) in self.client_stats.iter().filter(|x| x.enabled).enumerate() {
                 self.context
@@ -571,6 +717,7 @@ impl TuiMonitor {
             start_time,
             client_stats: vec![],
             aggregator: Aggregator::new(),
+            liveness: None,
         }
     }
 
@@ -627,6 +774,22 @@ impl TuiMonitor {
         total_item_geometry
     }
 
+    /// The forwarding stats over all clients, if any client reported a centralized role
+    fn forwarding(&self) -> Option<ForwardingStats> {
+        let mut total: Option<ForwardingStats> = None;
+        for client in self.client_stats().iter().filter(|client| client.enabled) {
+            if client_role(client).is_none() {
+                continue;
+            }
+            let mut forwarding = ForwardingStats::default();
+            forwarding.grab_data(client);
+            total
+                .get_or_insert_with(ForwardingStats::default)
+                .add(&forwarding);
+        }
+        total
+    }
+
     fn process_timing(&mut self) -> ProcessTiming {
         let mut total_process_timing = ProcessTiming::new();
         total_process_timing.exec_speed = self.execs_per_sec_pretty();
@@ -736,3 +899,25 @@ fn run_tui_thread<W: Write + Send + Sync + 'static>(
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use super::TuiContext;
+
+    #[test]
+    fn test_forwarded_per_sec() {
+        let mut context = TuiContext::new(Duration::ZERO);
+        assert!(context.forwarded_per_sec().abs() < f64::EPSILON);
+
+        context.forwarded_timed.add(Duration::from_secs(0), 10);
+        context.forwarded_timed.add(Duration::from_secs(2), 30);
+        assert!((context.forwarded_per_sec() - 10.0).abs() < f64::EPSILON);
+
+        // A secondary restarted and its count started over, the drop is no progress
+        context.forwarded_timed.add(Duration::from_secs(3), 5);
+        context.forwarded_timed.add(Duration::from_secs(4), 25);
+        assert!((context.forwarded_per_sec() - 10.0).abs() < f64::EPSILON);
+    }
+}
@@ -1,6 +1,6 @@
 use alloc::{string::ToString, vec::Vec};
 use std::{
-    cmp::{max, min},
+    cmp::{max, min, Ordering},
     sync::{Arc, RwLock},
 };
 
@@ -16,16 +16,126 @@ use ratatui::{
 };
 
 use super::{
-    current_time, format_duration_hms, Duration, ItemGeometry, ProcessTiming, String, TimedStats,
-    TuiContext,
+    current_time, format_duration_hms, ClientTuiContext, Duration, ItemGeometry, ProcessTiming,
+    String, TimedStats, TuiContext,
 };
 
+/// The columns of the clients table, which it can be sorted by
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ClientsColumn {
+    #[default]
+    Id,
+    Role,
+    Corpus,
+    Objectives,
+    Executions,
+    Forwarded,
+    Accepted,
+    Discarded,
+    Backlog,
+}
+
+impl ClientsColumn {
+    const ALL: [Self; 9] = [
+        Self::Id,
+        Self::Role,
+        Self::Corpus,
+        Self::Objectives,
+        Self::Executions,
+        Self::Forwarded,
+        Self::Accepted,
+        Self::Discarded,
+        Self::Backlog,
+    ];
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Id => "client",
+            Self::Role => "role",
+            Self::Corpus => "corpus",
+            Self::Objectives => "objectives",
+            Self::Executions => "execs",
+            Self::Forwarded => "forwarded",
+            Self::Accepted => "accepted",
+            Self::Discarded => "discarded",
+            Self::Backlog => "backlog",
+        }
+    }
+
+    /// The next column, wrapping around
+    #[must_use]
+    pub fn next(self) -> Self {
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+
+    /// The cell of this column for the given client
+    fn cell(self, id: usize, client: &ClientTuiContext) -> String {
+        match self {
+            Self::Id => {
+                let mut cell = format!("#{id}");
+                if let Some(identity) = &client.identity {
+                    cell = format!("{cell} {identity}");
+                }
+                if client.silent {
+                    cell.push_str(" (silent)");
+                }
+                cell
+            }
+            Self::Role => client.role.clone().unwrap_or_else(|| "-".to_string()),
+            Self::Corpus => client.corpus.to_string(),
+            Self::Objectives => client.objectives.to_string(),
+            Self::Executions => client.executions.to_string(),
+            Self::Forwarded => client.forwarding.forwarded.to_string(),
+            Self::Accepted => client.forwarding.accepted.to_string(),
+            Self::Discarded => client.forwarding.discarded.to_string(),
+            Self::Backlog => client.forwarding.main_backlog.to_string(),
+        }
+    }
+
+    /// Sort the `(id, client)` pairs by this column, ties are broken by the id
+    pub fn sort(self, clients: &mut [(usize, &ClientTuiContext)], reverse: bool) {
+        clients.sort_by(|(id_a, a), (id_b, b)| {
+            let ordering = match self {
+                Self::Id => Ordering::Equal,
+                Self::Role => a.role.cmp(&b.role),
+                Self::Corpus => a.corpus.cmp(&b.corpus),
+                Self::Objectives => a.objectives.cmp(&b.objectives),
+                Self::Executions => a.executions.cmp(&b.executions),
+                Self::Forwarded => a.forwarding.forwarded.cmp(&b.forwarding.forwarded),
+                Self::Accepted => a.forwarding.accepted.cmp(&b.forwarding.accepted),
+                Self::Discarded => a.forwarding.discarded.cmp(&b.forwarding.discarded),
+                Self::Backlog => a.forwarding.main_backlog.cmp(&b.forwarding.main_backlog),
+            }
+            .then(id_a.cmp(id_b));
+            if reverse {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+}
+
+/// A total, followed by the part of silent clients if there is one
+fn with_silent(total: u64, silent: u64) -> String {
+    if silent == 0 {
+        format!("{total}")
+    } else {
+        format!("{total} (+{silent} silent)")
+    }
+}
+
 #[derive(Default, Debug)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct TuiUi {
     title: String,
     version: String,
     enhanced_graphics: bool,
     show_logs: bool,
+    show_clients: bool,
+    clients_sort: ClientsColumn,
+    clients_sort_reverse: bool,
     clients_idx: usize,
     clients: usize,
     charts_tab_idx: usize,
@@ -63,6 +173,15 @@ impl TuiUi {
             't' => {
                 self.show_logs = !self.show_logs;
             }
+            'c' => {
+                self.show_clients = !self.show_clients;
+            }
+            's' => {
+                self.clients_sort = self.clients_sort.next();
+            }
+            'r' => {
+                self.clients_sort_reverse = !self.clients_sort_reverse;
+            }
             _ => {}
         }
     }
@@ -125,7 +244,11 @@ impl TuiUi {
 
         if self.show_logs {
             let bottom_body = body[2];
-            self.draw_logs(f, app, bottom_body);
+            if self.show_clients {
+                self.draw_clients(f, app, bottom_body);
+            } else {
+                self.draw_logs(f, app, bottom_body);
+            }
         }
     }
 
@@ -241,13 +364,43 @@ impl TuiUi {
             }
             _ => {}
         }
-        self.draw_overall_generic_text(f, app, bottom_layout);
+        let has_forwarding = app.read().unwrap().total_forwarding.is_some();
+        if has_forwarding {
+            let generic_layout = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+                .split(bottom_layout);
+            self.draw_overall_generic_text(f, app, generic_layout[0]);
+            self.draw_forwarding_text(f, app, generic_layout[1]);
+        } else {
+            self.draw_overall_generic_text(f, app, bottom_layout);
+        }
     }
 
     fn draw_client_ui(&mut self, f: &mut Frame, app: &Arc<RwLock<TuiContext>>, area: Rect) {
+        let (role, identity) = app
+            .read()
+            .unwrap()
+            .clients
+            .get(&self.clients_idx)
+            .map_or_else(Default::default, |client| {
+                (
+                    client
+                        .role
+                        .as_ref()
+                        .map_or_else(String::new, |role| format!(" [{role}]")),
+                    client
+                        .identity
+                        .as_ref()
+                        .map_or_else(String::new, |identity| format!(" {identity}")),
+                )
+            });
         let client_block = Block::default()
             .title(Span::styled(
-                format!("client #{} (l/r arrows to switch)", self.clients_idx),
+                format!(
+                    "client #{}{identity}{role} (l/r arrows to switch)",
+                    self.clients_idx
+                ),
                 Style::default()
                     .fg(Color::LightCyan)
                     .add_modifier(Modifier::BOLD),
@@ -574,7 +727,11 @@ impl TuiUi {
             vec![
                 Row::new(vec![
                     Cell::from(Span::raw("clients")),
-                    Cell::from(Span::raw(format!("{}", self.clients))),
+                    Cell::from(Span::raw(if app.silent_clients_num == 0 {
+                        format!("{}", self.clients)
+                    } else {
+                        format!("{} ({} silent)", self.clients, app.silent_clients_num)
+                    })),
                     Cell::from(Span::raw("total execs")),
                     Cell::from(Span::raw(format!("{}", app.total_execs))),
                     Cell::from(Span::raw("map density")),
@@ -582,11 +739,17 @@ impl TuiUi {
                 ]),
                 Row::new(vec![
                     Cell::from(Span::raw("solutions")),
-                    Cell::from(Span::raw(format!("{}", app.total_solutions))),
+                    Cell::from(Span::raw(with_silent(
+                        app.total_solutions,
+                        app.silent_solutions,
+                    ))),
                     Cell::from(Span::raw("cycle done")),
                     Cell::from(Span::raw(format!("{}", app.total_cycles_done))),
                     Cell::from(Span::raw("corpus count")),
-                    Cell::from(Span::raw(format!("{}", app.total_corpus_count))),
+                    Cell::from(Span::raw(with_silent(
+                        app.total_corpus_count,
+                        app.silent_corpus_count,
+                    ))),
                 ]),
             ]
         };
@@ -618,6 +781,96 @@ impl TuiUi {
         f.render_widget(table, chunks[0]);
     }
 
+    fn draw_forwarding_text(&mut self, f: &mut Frame, app: &Arc<RwLock<TuiContext>>, area: Rect) {
+        let items = {
+            let app = app.read().unwrap();
+            let forwarding = app.total_forwarding.unwrap_or_default();
+            vec![
+                Row::new(vec![
+                    Cell::from(Span::raw("forwarded")),
+                    Cell::from(Span::raw(format!("{}", forwarding.forwarded))),
+                    Cell::from(Span::raw("fwd/sec")),
+                    Cell::from(Span::raw(format!("{:.2}", app.forwarded_per_sec()))),
+                ]),
+                Row::new(vec![
+                    Cell::from(Span::raw("accepted")),
+                    Cell::from(Span::raw(format!("{}", forwarding.accepted))),
+                    Cell::from(Span::raw("discarded")),
+                    Cell::from(Span::raw(format!("{}", forwarding.discarded))),
+                ]),
+                Row::new(vec![
+                    Cell::from(Span::raw("main backlog")),
+                    Cell::from(Span::raw(format!("{}", forwarding.main_backlog))),
+                ]),
+            ]
+        };
+
+        let table = Table::default()
+            .rows(items)
+            .block(
+                Block::default()
+                    .title(Span::styled(
+                        "centralized",
+                        Style::default()
+                            .fg(Color::LightCyan)
+                            .add_modifier(Modifier::BOLD),
+                    ))
+                    .borders(Borders::ALL),
+            )
+            .widths([
+                Constraint::Percentage(30),
+                Constraint::Percentage(20),
+                Constraint::Percentage(30),
+                Constraint::Percentage(20),
+            ]);
+        f.render_widget(table, area);
+    }
+
+    fn draw_clients(&mut self, f: &mut Frame, app: &Arc<RwLock<TuiContext>>, area: Rect) {
+        let app = app.read().unwrap();
+        let mut clients: Vec<(usize, &ClientTuiContext)> = app
+            .clients
+            .iter()
+            .map(|(id, client)| (*id, client))
+            .collect();
+        self.clients_sort
+            .sort(&mut clients, self.clients_sort_reverse);
+
+        let header = Row::new(ClientsColumn::ALL.map(|column| {
+            if column == self.clients_sort {
+                let arrow = if self.clients_sort_reverse { "v" } else { "^" };
+                Cell::from(Span::styled(
+                    format!("{} {arrow}", column.name()),
+                    Style::default().fg(Color::LightYellow),
+                ))
+            } else {
+                Cell::from(Span::raw(column.name()))
+            }
+        }));
+        let rows: Vec<Row> = clients
+            .iter()
+            .map(|(id, client)| {
+                Row::new(ClientsColumn::ALL.map(|column| Cell::from(column.cell(*id, client))))
+            })
+            .collect();
+
+        let table = Table::default()
+            .header(header)
+            .rows(rows)
+            .block(
+                Block::default()
+                    .title(Span::styled(
+                        "clients (`c` logs, `s` sort, `r` reverse)",
+                        Style::default()
+                            .fg(Color::LightCyan)
+                            .add_modifier(Modifier::BOLD),
+                    ))
+                    .borders(Borders::ALL),
+            )
+            .widths([Constraint::Ratio(1, ClientsColumn::ALL.len() as u32); 9]);
+        f.render_widget(table, area);
+    }
+
     fn draw_client_results_text(
         &mut self,
         f: &mut Frame,
@@ -737,7 +990,7 @@ impl TuiUi {
                 ]));
                 for i in 0..client.stages.len() {
                     items.push(Row::new(vec![
-                        Cell::from(Span::raw(format!("stage {i}"))),
+                        Cell::from(Span::raw(client.stage_labels[i].clone())),
                         Cell::from(Span::raw("")),
                     ]));
 
@@ -758,7 +1011,19 @@ impl TuiUi {
                     Cell::from(Span::raw("not measured")),
                     Cell::from(Span::raw(format!("{:.2}%", client.unmeasured * 100.0))),
                 ]));
-            };
+            }
+            if ctx.clients.len() > 1 {
+                items.push(Row::new(vec![
+                    Cell::from(Span::raw("all clients")),
+                    Cell::from(Span::raw("")),
+                ]));
+                for (name, fraction) in ctx.total_introspection.fractions() {
+                    items.push(Row::new(vec![
+                        Cell::from(Span::raw(name.to_string())),
+                        Cell::from(Span::raw(format!("{:.2}%", fraction * 100.0))),
+                    ]));
+                }
+            }
         }
 
         let table = Table::default()
@@ -786,7 +1051,7 @@ impl TuiUi {
             .collect();
         let logs = List::new(logs).block(
             Block::default().borders(Borders::ALL).title(Span::styled(
-                "clients logs (`t` to show/hide)",
+                "clients logs (`t` to show/hide, `c` clients)",
                 Style::default()
                     .fg(Color::LightCyan)
                     .add_modifier(Modifier::BOLD),
@@ -795,3 +1060,40 @@ impl TuiUi {
         f.render_widget(logs, area);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::ClientsColumn;
+    use crate::monitors::tui::ClientTuiContext;
+
+    #[test]
+    fn test_clients_sort() {
+        let main = ClientTuiContext {
+            role: Some("main".into()),
+            corpus: 10,
+            ..ClientTuiContext::default()
+        };
+        let secondary = ClientTuiContext {
+            role: Some("secondary".into()),
+            corpus: 20,
+            ..ClientTuiContext::default()
+        };
+        let plain = ClientTuiContext::default();
+
+        let mut clients = vec![(3, &secondary), (1, &plain), (2, &main)];
+        let ids = |clients: &[(usize, &ClientTuiContext)]| {
+            clients.iter().map(|(id, _)| *id).collect::<Vec<_>>()
+        };
+
+        ClientsColumn::Id.sort(&mut clients, false);
+        assert_eq!(ids(&clients), [1, 2, 3]);
+        ClientsColumn::Corpus.sort(&mut clients, true);
+        assert_eq!(ids(&clients), [3, 2, 1]);
+        // Clients without a role come first
+        ClientsColumn::Role.sort(&mut clients, false);
+        assert_eq!(ids(&clients), [1, 2, 3]);
+        assert_eq!(ClientsColumn::Backlog.next(), ClientsColumn::Id);
+    }
+}
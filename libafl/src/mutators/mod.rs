@@ -15,6 +15,8 @@ pub mod havoc_mutations;
 pub use havoc_mutations::*;
 pub mod numeric;
 pub use numeric::{int_mutators, mapped_int_mutators};
+pub mod ranged;
+pub use ranged::*;
 pub mod encoded_mutations;
 pub use encoded_mutations::*;
 pub mod mopt_mutator;
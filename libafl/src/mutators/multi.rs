@@ -1,11 +1,12 @@
 //! Mutator definitions for [`MultipartInput`]s. See [`crate::inputs::multi`] for details.
 
+use alloc::{borrow::Cow, string::String, vec::Vec};
 use core::{
     cmp::{min, Ordering},
     num::NonZero,
 };
 
-use libafl_bolts::{rands::Rand, Error};
+use libafl_bolts::{rands::Rand, Error, Named};
 
 use crate::{
     corpus::{Corpus, CorpusId},
@@ -57,6 +58,105 @@ where
     }
 }
 
+/// Selects the parts of a [`MultipartInput`] a [`PartMutator`] may mutate, by their name.
+pub trait PartSelector {
+    /// Returns `true` if parts with this `name` are selected
+    fn selects(&self, name: &str) -> bool;
+}
+
+impl<F> PartSelector for F
+where
+    F: Fn(&str) -> bool,
+{
+    fn selects(&self, name: &str) -> bool {
+        self(name)
+    }
+}
+
+/// Selects all parts with the given name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartName(pub String);
+
+impl PartSelector for PartName {
+    fn selects(&self, name: &str) -> bool {
+        self.0 == name
+    }
+}
+
+/// Restricts an inner [`Mutator`] to the parts of a [`MultipartInput`] the [`PartSelector`] selects.
+///
+/// Each mutation picks one of the selected parts at random.
+/// If the input has none of them, the mutation is skipped.
+#[derive(Debug)]
+pub struct PartMutator<M, P> {
+    inner: M,
+    selector: P,
+    name: Cow<'static, str>,
+}
+
+impl<M, P> PartMutator<M, P>
+where
+    M: Named,
+    P: PartSelector,
+{
+    /// Creates a new [`PartMutator`], mutating the parts selected by `selector`, e.g., a predicate
+    pub fn new(selector: P, inner: M) -> Self {
+        let name = Cow::Owned(format!("PartMutator<{}>", inner.name()));
+        Self {
+            inner,
+            selector,
+            name,
+        }
+    }
+}
+
+impl<M> PartMutator<M, PartName>
+where
+    M: Named,
+{
+    /// Creates a new [`PartMutator`], mutating the parts with the given name
+    pub fn for_name<N>(name: N, inner: M) -> Self
+    where
+        N: Into<String>,
+    {
+        Self::new(PartName(name.into()), inner)
+    }
+}
+
+impl<I, M, P, S> Mutator<MultipartInput<I>, S> for PartMutator<M, P>
+where
+    M: Mutator<I, S>,
+    P: PartSelector,
+    S: HasRand,
+{
+    fn mutate(
+        &mut self,
+        state: &mut S,
+        input: &mut MultipartInput<I>,
+    ) -> Result<MutationResult, Error> {
+        let selected = input
+            .names()
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, name)| self.selector.selects(name).then_some(idx))
+            .collect::<Vec<_>>();
+        let Some(idx) = state.rand_mut().choose(selected) else {
+            return Ok(MutationResult::Skipped);
+        };
+        self.inner.mutate(state, input.part_mut(idx).unwrap())
+    }
+
+    fn post_exec(&mut self, state: &mut S, new_corpus_id: Option<CorpusId>) -> Result<(), Error> {
+        self.inner.post_exec(state, new_corpus_id)
+    }
+}
+
+impl<M, P> Named for PartMutator<M, P> {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
 mod macros {
     /// Implements the marker trait [`super::DefaultMultipartMutator`] for one to many types, e.g.:
     ///
@@ -209,6 +209,46 @@ macro_rules! impl_numeric_128_bits_randomize {
 // Apply the macro to all desired integer types
 impl_numeric_128_bits_randomize! { u128 i128 }
 
+// Macro to implement the Numeric trait for floats, working on their bits where integers would wrap
+macro_rules! impl_numeric_float {
+    ($($t:ty)*) => ($(
+        impl Numeric for $t {
+            #[inline]
+            fn flip_all_bits(&mut self) {
+                *self = <$t>::from_bits(!self.to_bits());
+            }
+
+            #[inline]
+            fn flip_bit_at(&mut self, offset: usize) {
+                *self = <$t>::from_bits(self.to_bits() ^ (1 << offset));
+            }
+
+            #[inline]
+            fn wrapping_inc(&mut self) {
+                *self += 1.0;
+            }
+
+            #[inline]
+            fn wrapping_dec(&mut self) {
+                *self -= 1.0;
+            }
+
+            #[inline]
+            fn twos_complement(&mut self) {
+                *self = -*self;
+            }
+
+            #[inline]
+            #[allow(trivial_numeric_casts, clippy::cast_possible_truncation)]
+            fn randomize<R: Rand>(&mut self, rand: &mut R) {
+                *self = <$t>::from_bits(rand.next() as _);
+            }
+        }
+    )*)
+}
+
+impl_numeric_float! { f32 f64 }
+
 /// Bitflip mutation for integer-like inputs
 #[derive(Debug)]
 pub struct BitFlipMutator;
@@ -0,0 +1,359 @@
+//! Mutators for [`crate::inputs::RangedValueInput`]s and other [`RangedInput`]s, which keep the values in their range.
+//!
+//! The [`RangedMutator`] runs a mutator for plain numbers, e.g., from [`crate::mutators::numeric`],
+//! on the value and clamps the result back into the range.
+//!
+//! With the `multipart_inputs` feature, they also mutate [`crate::inputs::RangedStructInput`]s,
+//! picking one random field for each mutation.
+
+use alloc::borrow::Cow;
+#[cfg(feature = "multipart_inputs")]
+use core::num::NonZero;
+
+use libafl_bolts::{
+    rands::Rand,
+    tuples::{Map as _, MappingFunctor, Merge as _},
+    Error, Named,
+};
+use tuple_list::{tuple_list, tuple_list_type};
+
+use crate::{
+    corpus::{Corpus, CorpusId},
+    inputs::{Input, RangedInput, RangedNumeric, RangedScalar, RangedValueInput},
+    mutators::{
+        numeric::{
+            int_mutators_no_crossover, BitFlipMutator, DecMutator, IncMutator, NegateMutator,
+            RandMutator, TwosComplementMutator,
+        },
+        MutationResult, Mutator,
+    },
+    random_corpus_id_with_disabled,
+    state::{HasCorpus, HasRand},
+};
+#[cfg(feature = "multipart_inputs")]
+use crate::{inputs::MultipartInput, mutators::DefaultMultipartMutator};
+
+/// All mutators for ranged inputs
+pub type RangedMutatorsType = tuple_list_type!(
+    RangedMutator<BitFlipMutator>,
+    RangedMutator<NegateMutator>,
+    RangedMutator<IncMutator>,
+    RangedMutator<DecMutator>,
+    RangedMutator<TwosComplementMutator>,
+    RangedMutator<RandMutator>,
+    RangedBoundaryMutator,
+    RangedCrossoverMutator
+);
+
+/// Mutators for ranged inputs: the [`crate::mutators::int_mutators`] without crossover,
+/// jumping to a bound and crossover, all keeping the values in their range
+#[must_use]
+pub fn ranged_mutators() -> RangedMutatorsType {
+    int_mutators_no_crossover()
+        .map(ToRangedMutatorMapper)
+        .merge(tuple_list!(RangedBoundaryMutator, RangedCrossoverMutator))
+}
+
+/// Runs the inner mutator on the value of a [`RangedValueInput`], then clamps it back into the range.
+///
+/// Skips if the clamped value equals the old one.
+#[derive(Debug)]
+pub struct RangedMutator<M> {
+    inner: M,
+    name: Cow<'static, str>,
+}
+
+impl<M> RangedMutator<M> {
+    /// Creates a new [`RangedMutator`]
+    pub fn new(inner: M) -> Self
+    where
+        M: Named,
+    {
+        let name = Cow::Owned(format!("RangedMutator<{}>", inner.name()));
+        Self { inner, name }
+    }
+}
+
+impl<M, S, T> Mutator<RangedValueInput<T>, S> for RangedMutator<M>
+where
+    M: Mutator<T, S>,
+    T: RangedNumeric,
+{
+    fn mutate(
+        &mut self,
+        state: &mut S,
+        input: &mut RangedValueInput<T>,
+    ) -> Result<MutationResult, Error> {
+        let mut value = input.value();
+        if self.inner.mutate(state, &mut value)? == MutationResult::Skipped {
+            return Ok(MutationResult::Skipped);
+        }
+        if input.replace(value) {
+            Ok(MutationResult::Mutated)
+        } else {
+            Ok(MutationResult::Skipped)
+        }
+    }
+
+    fn post_exec(&mut self, state: &mut S, new_corpus_id: Option<CorpusId>) -> Result<(), Error> {
+        self.inner.post_exec(state, new_corpus_id)
+    }
+}
+
+macro_rules! impl_ranged_scalar_mutator {
+    ($($t:ty => $variant:ident),+ $(,)?) => {
+        impl<M, S> Mutator<RangedScalar, S> for RangedMutator<M>
+        where
+            $(M: Mutator<$t, S>,)+
+        {
+            fn mutate(
+                &mut self,
+                state: &mut S,
+                input: &mut RangedScalar,
+            ) -> Result<MutationResult, Error> {
+                match input {
+                    $(RangedScalar::$variant(value) => {
+                        Mutator::<RangedValueInput<$t>, S>::mutate(self, state, value)
+                    })+
+                }
+            }
+        }
+    };
+}
+
+impl_ranged_scalar_mutator!(
+    u8 => U8,
+    u16 => U16,
+    u32 => U32,
+    u64 => U64,
+    usize => Usize,
+    i8 => I8,
+    i16 => I16,
+    i32 => I32,
+    i64 => I64,
+    isize => Isize,
+    f32 => F32,
+    f64 => F64,
+);
+
+impl<M> Named for RangedMutator<M> {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+#[cfg(feature = "multipart_inputs")]
+impl<M> DefaultMultipartMutator for RangedMutator<M> {}
+
+/// Mapper to use to map a [`tuple_list`] of [`Mutator`]s using [`RangedMutator`]s.
+#[derive(Debug)]
+pub struct ToRangedMutatorMapper;
+
+impl<M> MappingFunctor<M> for ToRangedMutatorMapper
+where
+    M: Named,
+{
+    type Output = RangedMutator<M>;
+
+    fn apply(&mut self, from: M) -> Self::Output {
+        RangedMutator::new(from)
+    }
+}
+
+/// Jumps to the lower or upper bound of the range
+#[derive(Debug)]
+pub struct RangedBoundaryMutator;
+
+impl<I, S> Mutator<I, S> for RangedBoundaryMutator
+where
+    S: HasRand,
+    I: RangedInput,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut I) -> Result<MutationResult, Error> {
+        let upper = state.rand_mut().coinflip(0.5);
+        if input.to_bound(upper) || input.to_bound(!upper) {
+            Ok(MutationResult::Mutated)
+        } else {
+            Ok(MutationResult::Skipped)
+        }
+    }
+}
+
+impl Named for RangedBoundaryMutator {
+    fn name(&self) -> &Cow<'static, str> {
+        &Cow::Borrowed("RangedBoundaryMutator")
+    }
+}
+
+/// Sets the value to the average of itself and the value of a random corpus entry.
+///
+/// For [`crate::inputs::RangedStructInput`]s, it averages a random field with the first field
+/// of the same name in the other entry.
+#[derive(Debug)]
+pub struct RangedCrossoverMutator;
+
+impl<I, S> Mutator<I, S> for RangedCrossoverMutator
+where
+    S: HasRand + HasCorpus,
+    S::Corpus: Corpus<Input = I>,
+    I: RangedInput + Input,
+{
+    fn mutate(&mut self, state: &mut S, input: &mut I) -> Result<MutationResult, Error> {
+        let id = random_corpus_id_with_disabled!(state.corpus(), state.rand_mut());
+
+        if state.corpus().current().is_some_and(|cur| cur == id) {
+            return Ok(MutationResult::Skipped);
+        }
+
+        let mut other_testcase = state.corpus().get_from_all(id)?.borrow_mut();
+        let other = other_testcase.load_input(state.corpus())?;
+        if input.average_with(other) {
+            Ok(MutationResult::Mutated)
+        } else {
+            Ok(MutationResult::Skipped)
+        }
+    }
+}
+
+#[cfg(feature = "multipart_inputs")]
+impl<I, S> Mutator<MultipartInput<I>, S> for RangedCrossoverMutator
+where
+    S: HasRand + HasCorpus,
+    S::Corpus: Corpus<Input = MultipartInput<I>>,
+    I: RangedInput + Input,
+{
+    fn mutate(
+        &mut self,
+        state: &mut S,
+        input: &mut MultipartInput<I>,
+    ) -> Result<MutationResult, Error> {
+        let Some(parts_len) = NonZero::new(input.parts().len()) else {
+            return Ok(MutationResult::Skipped);
+        };
+        let selected = state.rand_mut().below(parts_len);
+
+        let id = random_corpus_id_with_disabled!(state.corpus(), state.rand_mut());
+        if state.corpus().current().is_some_and(|cur| cur == id) {
+            return Ok(MutationResult::Skipped);
+        }
+
+        let mut other_testcase = state.corpus().get_from_all(id)?.borrow_mut();
+        let other = other_testcase.load_input(state.corpus())?;
+        let Some(other_part) = other.part_by_name(&input.names()[selected]) else {
+            return Ok(MutationResult::Skipped);
+        };
+        if input.part_mut(selected).unwrap().average_with(other_part) {
+            Ok(MutationResult::Mutated)
+        } else {
+            Ok(MutationResult::Skipped)
+        }
+    }
+}
+
+impl Named for RangedCrossoverMutator {
+    fn name(&self) -> &Cow<'static, str> {
+        &Cow::Borrowed("RangedCrossoverMutator")
+    }
+}
+
+// crossover has a custom implementation above
+#[cfg(feature = "multipart_inputs")]
+impl DefaultMultipartMutator for RangedBoundaryMutator {}
+
+#[cfg(test)]
+mod tests {
+    use core::num::NonZero;
+
+    use libafl_bolts::{
+        rands::{Rand, StdRand},
+        tuples::{HasConstLen, IntoVec as _},
+    };
+
+    use super::{ranged_mutators, RangedMutatorsType};
+    use crate::{
+        corpus::{Corpus as _, InMemoryCorpus, Testcase},
+        inputs::{RangedI32Input, RangedValueInput},
+        mutators::MutationResult,
+        state::{HasRand, StdState},
+    };
+
+    #[test]
+    fn test_ranged_mutators_stay_in_range() {
+        let mut corpus = InMemoryCorpus::new();
+        corpus
+            .add(Testcase::new(RangedValueInput::new(-50).with_range(-5, 5)))
+            .unwrap();
+        let mut state = StdState::new(
+            StdRand::with_seed(0),
+            corpus,
+            InMemoryCorpus::new(),
+            &mut (),
+            &mut (),
+        )
+        .unwrap();
+
+        let mut mutators = ranged_mutators().into_vec();
+        let len = NonZero::new(RangedMutatorsType::LEN).unwrap();
+        let mut input: RangedI32Input = RangedValueInput::new(3).with_range(-5, 5);
+        let mut seen = [false; 11];
+        for _ in 0..1000 {
+            let idx = state.rand_mut().below(len);
+            let result = mutators[idx].mutate(&mut state, &mut input).unwrap();
+            assert!((-5..=5).contains(&input.value()), "{input:?}");
+            if result == MutationResult::Mutated {
+                seen[usize::try_from(input.value() + 5).unwrap()] = true;
+            }
+        }
+        // both bounds and everything in between were reached
+        assert!(seen.iter().all(|seen| *seen), "{seen:?}");
+    }
+
+    #[test]
+    #[cfg(feature = "multipart_inputs")]
+    fn test_ranged_struct_mutators() {
+        use alloc::string::String;
+
+        use crate::inputs::{RangedScalar, RangedStructInput};
+
+        let template = || {
+            let mut input = RangedStructInput::new();
+            input.add_part(
+                String::from("size"),
+                RangedValueInput::new(16_usize).with_range(1, 64).into(),
+            );
+            input.add_part(
+                String::from("ratio"),
+                RangedValueInput::new(0.5_f64).with_range(0.0, 1.0).into(),
+            );
+            input
+        };
+
+        let mut corpus = InMemoryCorpus::new();
+        corpus.add(Testcase::new(template())).unwrap();
+        let mut state = StdState::new(
+            StdRand::with_seed(0),
+            corpus,
+            InMemoryCorpus::new(),
+            &mut (),
+            &mut (),
+        )
+        .unwrap();
+
+        let mut mutators = ranged_mutators().into_vec();
+        let len = NonZero::new(RangedMutatorsType::LEN).unwrap();
+        let mut input = template();
+        for _ in 0..1000 {
+            let idx = state.rand_mut().below(len);
+            mutators[idx].mutate(&mut state, &mut input).unwrap();
+            let RangedScalar::Usize(size) = input.part_by_name("size").unwrap() else {
+                panic!("size changed its type");
+            };
+            assert!((1..=64).contains(&size.value()));
+            let RangedScalar::F64(ratio) = input.part_by_name("ratio").unwrap() else {
+                panic!("ratio changed its type");
+            };
+            assert!((0.0..=1.0).contains(&ratio.value()));
+        }
+        assert_ne!(input.parts(), template().parts());
+    }
+}
@@ -8,7 +8,9 @@ use core::{
     slice,
 };
 
-use libafl_bolts::{AsIter, AsIterMut, AsSlice, AsSliceMut, HasLen, Named, Truncate};
+use libafl_bolts::{
+    serdeany::NamedSerdeAnyMap, AsIter, AsIterMut, AsSlice, AsSliceMut, HasLen, Named, Truncate,
+};
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -142,6 +144,11 @@ where
 
         self.base.post_exec(state, input, exit_kind)
     }
+
+    #[inline]
+    fn may_be_interesting(&self, metadata: &NamedSerdeAnyMap) -> Option<bool> {
+        self.base.may_be_interesting(metadata)
+    }
 }
 
 impl<M> Named for HitcountsMapObserver<M>
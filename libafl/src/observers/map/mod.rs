@@ -8,11 +8,15 @@ use core::{
 };
 
 use ahash::RandomState;
-use libafl_bolts::{ownedref::OwnedMutSlice, AsSlice, AsSliceMut, HasLen, Named, Truncate};
+use libafl_bolts::{
+    ownedref::OwnedMutSlice, serdeany::NamedSerdeAnyMap, AsSlice, AsSliceMut, HasLen, Named,
+    Truncate,
+};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
     executors::ExitKind,
+    feedbacks::{MapFeedbackMetadata, MapObserverFeedbacksMetadata},
     observers::{DifferentialObserver, Observer},
     Error,
 };
@@ -385,6 +389,35 @@ pub trait MapObserver:
     fn how_many_set(&self, indexes: &[usize]) -> usize;
 }
 
+/// Guess if `map` hits an entry beyond the histories of the maximizing [`crate::feedbacks::MapFeedback`]s
+/// of the map, for [`Observer::may_be_interesting`].
+///
+/// Returns `None` if another kind of feedback uses the map, see [`MapObserverFeedbacksMetadata`],
+/// or if there is no such history yet.
+pub fn map_may_be_interesting<M>(map: &M, metadata: &NamedSerdeAnyMap) -> Option<bool>
+where
+    M: MapObserver + Named,
+    M::Entry: PartialOrd + Serialize + DeserializeOwned + Debug + 'static,
+{
+    let feedbacks = metadata.get::<MapObserverFeedbacksMetadata>(map.name())?;
+    if feedbacks.other || feedbacks.maximizing.is_empty() {
+        return None;
+    }
+    let len = map.usable_count();
+    for name in &feedbacks.maximizing {
+        let history = &metadata
+            .get::<MapFeedbackMetadata<M::Entry>>(name)?
+            .history_map;
+        if history.len() < len {
+            return None;
+        }
+        if (0..len).any(|idx| map.get(idx) > history[idx]) {
+            return Some(true);
+        }
+    }
+    Some(false)
+}
+
 /// The "real" length of the underlying map could change at any point in time.
 /// Thus, the size of the map should be fetched each time it is used.
 pub trait VarLenMapObserver: MapObserver {
@@ -446,12 +479,18 @@ pub struct StdMapObserver<'a, T, const DIFFERENTIAL: bool> {
 
 impl<I, S, T> Observer<I, S> for StdMapObserver<'_, T, false>
 where
-    Self: MapObserver,
+    Self: MapObserver<Entry = T>,
+    T: PartialOrd + Serialize + DeserializeOwned + Debug + 'static,
 {
     #[inline]
     fn pre_exec(&mut self, _state: &mut S, _input: &I) -> Result<(), Error> {
         self.reset_map()
     }
+
+    #[inline]
+    fn may_be_interesting(&self, metadata: &NamedSerdeAnyMap) -> Option<bool> {
+        map_may_be_interesting(self, metadata)
+    }
 }
 
 impl<I, S, T> Observer<I, S> for StdMapObserver<'_, T, true> {}
@@ -26,6 +26,9 @@ pub use map::*;
 
 pub mod value;
 
+pub mod user_exit;
+pub use user_exit::*;
+
 /// List observer
 pub mod list;
 use core::{fmt::Debug, time::Duration};
@@ -34,12 +37,12 @@ use std::time::Instant;
 
 #[cfg(not(feature = "std"))]
 use libafl_bolts::current_time;
-use libafl_bolts::{tuples::MatchName, Named};
+use libafl_bolts::{serdeany::NamedSerdeAnyMap, tuples::MatchName, Named};
 pub use list::*;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 pub use value::*;
 
-use crate::{executors::ExitKind, Error};
+use crate::{executors::ExitKind, Error, HasNamedMetadata};
 
 /// Observers observe different information about the target.
 /// They can then be used by various sorts of feedback.
@@ -84,6 +87,48 @@ pub trait Observer<I, S>: Named {
     ) -> Result<(), Error> {
         Ok(())
     }
+
+    /// A quick guess if the run this observer was received for can be interesting at all, see [`LazyObserversTuple`].
+    ///
+    /// `Some(false)` means no feedback will consider it interesting, `None` means this observer can not tell.
+    /// `metadata` is the named metadata of the state, where feedbacks keep their history.
+    #[inline]
+    fn may_be_interesting(&self, _metadata: &NamedSerdeAnyMap) -> Option<bool> {
+        None
+    }
+}
+
+/// Observer tuples that can be deserialized one observer at a time, stopping as soon as one of them rules out
+/// that the run is interesting, see [`Observer::may_be_interesting`].
+///
+/// Used by main nodes with [`crate::events::CentralizedEventManagerBuilder::lazy_observers`],
+/// to skip decoding large observers for testcases that will be rejected anyway.
+pub trait LazyObserversTuple<I, S>: DeserializeOwned {
+    /// Deserialize the observers from the start of `bytes`, returning them and the remaining bytes,
+    /// or `None` if an observer ruled out that the run is interesting.
+    #[allow(clippy::type_complexity)]
+    fn take_lazy<'a>(bytes: &'a [u8], state: &S) -> Result<Option<(Self, &'a [u8])>, Error>;
+}
+
+impl<I, S> LazyObserversTuple<I, S> for () {
+    fn take_lazy<'a>(bytes: &'a [u8], _state: &S) -> Result<Option<(Self, &'a [u8])>, Error> {
+        Ok(Some(((), bytes)))
+    }
+}
+
+impl<Head, Tail, I, S> LazyObserversTuple<I, S> for (Head, Tail)
+where
+    Head: Observer<I, S> + DeserializeOwned,
+    Tail: LazyObserversTuple<I, S>,
+    S: HasNamedMetadata,
+{
+    fn take_lazy<'a>(bytes: &'a [u8], state: &S) -> Result<Option<(Self, &'a [u8])>, Error> {
+        let (head, bytes) = postcard::take_from_bytes::<Head>(bytes)?;
+        if head.may_be_interesting(state.named_metadata_map()) == Some(false) {
+            return Ok(None);
+        }
+        Ok(Tail::take_lazy(bytes, state)?.map(|(tail, bytes)| ((head, tail), bytes)))
+    }
 }
 
 /// A haskell-style tuple of observers
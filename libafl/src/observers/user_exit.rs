@@ -0,0 +1,145 @@
+//! An observer for the outcome a harness reports for its own run, such as "parse rejected" or "fully processed".
+//!
+//! The harness calls [`set_user_exit`] with a small value of its choice, the [`UserExitObserver`] captures it after
+//! each run, and the [`crate::feedbacks::UserExitFeedback`] lets other feedbacks depend on it.
+
+use alloc::borrow::Cow;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use libafl_bolts::Named;
+use serde::{Deserialize, Serialize};
+
+use crate::{executors::ExitKind, observers::Observer, Error};
+
+/// The outcome of the current run, set by the harness, only valid while [`USER_EXIT_SET`] is.
+///
+/// Plain atomics rather than a thread local, so it can be read from the crash handlers as well.
+/// 32 bit wide, so they exist on every target.
+static USER_EXIT: AtomicU32 = AtomicU32::new(0);
+
+/// If the harness set an outcome for the current run
+static USER_EXIT_SET: AtomicBool = AtomicBool::new(false);
+
+/// Report the outcome of the current run, to be picked up by a [`UserExitObserver`].
+///
+/// Call this from an in-process harness, the last value set before the run ends, or crashes, wins.
+pub fn set_user_exit(value: u32) {
+    USER_EXIT.store(value, Ordering::Relaxed);
+    USER_EXIT_SET.store(true, Ordering::Release);
+}
+
+/// The outcome reported by the harness for the current run, if any
+#[must_use]
+pub fn user_exit() -> Option<u32> {
+    USER_EXIT_SET
+        .load(Ordering::Acquire)
+        .then(|| USER_EXIT.load(Ordering::Relaxed))
+}
+
+/// Forget the outcome of the last run, done by the [`UserExitObserver`] before each run
+pub fn reset_user_exit() {
+    USER_EXIT_SET.store(false, Ordering::Release);
+}
+
+/// Captures the outcome the harness reported with [`set_user_exit`], if any.
+///
+/// The outcome is reset before each run and captured after it, also on the path of the in-process crash
+/// and timeout handlers, so objectives see the outcome reported before the crash.
+/// The captured outcome is serialized with the observer, so a main node evaluating the run sees it too.
+///
+/// Only in-process executors share the outcome with the fuzzer, a forked child reports it to its own copy.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UserExitObserver {
+    name: Cow<'static, str>,
+    value: Option<u32>,
+}
+
+impl UserExitObserver {
+    /// Creates a new [`UserExitObserver`] with the given name.
+    #[must_use]
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name: Cow::from(name),
+            value: None,
+        }
+    }
+
+    /// The outcome the harness reported in the last run, if any
+    #[must_use]
+    pub fn value(&self) -> Option<u32> {
+        self.value
+    }
+}
+
+impl<I, S> Observer<I, S> for UserExitObserver {
+    fn pre_exec(&mut self, _state: &mut S, _input: &I) -> Result<(), Error> {
+        reset_user_exit();
+        self.value = None;
+        Ok(())
+    }
+
+    fn post_exec(
+        &mut self,
+        _state: &mut S,
+        _input: &I,
+        _exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        self.value = user_exit();
+        Ok(())
+    }
+
+    fn pre_exec_child(&mut self, state: &mut S, input: &I) -> Result<(), Error> {
+        self.pre_exec(state, input)
+    }
+
+    fn post_exec_child(
+        &mut self,
+        state: &mut S,
+        input: &I,
+        exit_kind: &ExitKind,
+    ) -> Result<(), Error> {
+        self.post_exec(state, input, exit_kind)
+    }
+}
+
+impl Named for UserExitObserver {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        executors::ExitKind,
+        inputs::BytesInput,
+        observers::{set_user_exit, user_exit, Observer, UserExitObserver},
+    };
+
+    #[test]
+    fn test_user_exit_observer() {
+        let input = BytesInput::new(vec![]);
+        let mut observer = UserExitObserver::new("user_exit");
+
+        set_user_exit(7);
+        Observer::<BytesInput, ()>::pre_exec(&mut observer, &mut (), &input).unwrap();
+        assert_eq!(user_exit(), None);
+        Observer::<BytesInput, ()>::post_exec(&mut observer, &mut (), &input, &ExitKind::Ok)
+            .unwrap();
+        assert_eq!(observer.value(), None);
+
+        Observer::<BytesInput, ()>::pre_exec(&mut observer, &mut (), &input).unwrap();
+        set_user_exit(3);
+        // The crash handlers run the observers before anything else resets the outcome
+        Observer::<BytesInput, ()>::post_exec(&mut observer, &mut (), &input, &ExitKind::Crash)
+            .unwrap();
+        assert_eq!(observer.value(), Some(3));
+
+        let bytes = postcard::to_allocvec(&observer).unwrap();
+        let received: UserExitObserver = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(received.value(), Some(3));
+
+        Observer::<BytesInput, ()>::pre_exec(&mut observer, &mut (), &input).unwrap();
+        assert_eq!(observer.value(), None);
+    }
+}
@@ -338,7 +338,11 @@ where
 
         let mut acc = HashSet::new();
 
-        for (key, id) in &top_rated.map {
+        // Visit the indexes in order, the iteration order of the map would make the favored entries differ between runs
+        let mut top_rated = top_rated.map.iter().collect::<Vec<_>>();
+        top_rated.sort_unstable_by_key(|(key, _)| **key);
+
+        for (key, id) in top_rated {
             if !acc.contains(key) {
                 let mut entry = state.corpus().get(*id)?.borrow_mut();
                 let meta = entry.metadata_map().get::<M>().ok_or_else(|| {
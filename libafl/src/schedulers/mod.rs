@@ -7,7 +7,7 @@ pub mod testcase_score;
 pub use testcase_score::{LenTimeMulTestcaseScore, TestcaseScore};
 
 pub mod queue;
-pub use queue::QueueScheduler;
+pub use queue::{OrderedQueueScheduler, QueueOrderMetadata, QueueScheduler};
 
 pub mod minimizer;
 pub use minimizer::{
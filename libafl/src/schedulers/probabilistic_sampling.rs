@@ -156,10 +156,14 @@ where
             let threshold = meta.total_probability * rand_prob;
             let mut k: f64 = 0.0;
             let mut ret = *meta.map.keys().last().unwrap();
-            for (idx, prob) in &meta.map {
+            // Walk the entries in corpus order, the iteration order of the map differs between runs
+            for idx in state.corpus().ids() {
+                let Some(prob) = meta.map.get(&idx) else {
+                    continue;
+                };
+                ret = idx;
                 k += prob;
                 if k >= threshold {
-                    ret = *idx;
                     break;
                 }
             }
@@ -1,14 +1,73 @@
 //! The queue corpus scheduler implements an AFL-like queue mechanism
 
-use alloc::borrow::ToOwned;
+use alloc::{borrow::ToOwned, vec::Vec};
+
+use serde::{Deserialize, Serialize};
 
 use crate::{
     corpus::{Corpus, CorpusId},
     schedulers::{HasQueueCycles, RemovableScheduler, Scheduler},
     state::HasCorpus,
-    Error,
+    Error, HasMetadata,
 };
 
+/// A custom order to walk the corpus in, for example set by the [`crate::stages::CorpusShuffle`] stage.
+///
+/// If this metadata is present, the [`OrderedQueueScheduler`] follows it instead of the insertion order.
+/// Entries added later are appended, ids that are no longer enabled are skipped.
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct QueueOrderMetadata {
+    order: Vec<CorpusId>,
+    /// The position of the last scheduled entry in `order`
+    #[serde(default)]
+    cursor: Option<usize>,
+}
+
+libafl_bolts::impl_serdeany!(QueueOrderMetadata);
+
+impl QueueOrderMetadata {
+    /// Create a new [`QueueOrderMetadata`] walking the corpus in the given order
+    #[must_use]
+    pub fn new(order: Vec<CorpusId>) -> Self {
+        Self {
+            order,
+            cursor: None,
+        }
+    }
+
+    /// The order to walk the corpus in
+    #[must_use]
+    pub fn order(&self) -> &[CorpusId] {
+        &self.order
+    }
+
+    /// Move to the first enabled entry following `current` in this order, wrapping around at the end.
+    ///
+    /// The position of the last scheduled entry is remembered, so walking the order takes constant time per step
+    /// unless the current entry was changed from the outside.
+    fn next_after<C>(&mut self, corpus: &C, current: Option<CorpusId>) -> Option<CorpusId>
+    where
+        C: Corpus,
+    {
+        let start = match self.cursor {
+            Some(pos) if current.is_some() && self.order.get(pos).copied() == current => pos + 1,
+            _ => current
+                .and_then(|current| self.order.iter().position(|id| *id == current))
+                .map_or(0, |pos| pos + 1),
+        };
+        let len = self.order.len();
+        let pos = (start..len)
+            .chain(0..start.min(len))
+            .find(|pos| corpus.get(self.order[*pos]).is_ok())?;
+        self.cursor = Some(pos);
+        Some(self.order[pos])
+    }
+}
+
 /// Walk the corpus in a queue-like fashion
 #[derive(Debug, Clone)]
 pub struct QueueScheduler {
@@ -92,6 +151,71 @@ impl HasQueueCycles for QueueScheduler {
     }
 }
 
+/// Walk the corpus in a queue-like fashion, following the [`QueueOrderMetadata`] if present.
+///
+/// Without the metadata, this behaves exactly like the [`QueueScheduler`].
+#[derive(Debug, Clone, Default)]
+pub struct OrderedQueueScheduler {
+    inner: QueueScheduler,
+}
+
+impl<I, S> RemovableScheduler<I, S> for OrderedQueueScheduler {}
+
+impl<I, S> Scheduler<I, S> for OrderedQueueScheduler
+where
+    S: HasCorpus + HasMetadata,
+{
+    fn on_add(&mut self, state: &mut S, id: CorpusId) -> Result<(), Error> {
+        <QueueScheduler as Scheduler<I, S>>::on_add(&mut self.inner, state, id)?;
+        if let Ok(meta) = state.metadata_mut::<QueueOrderMetadata>() {
+            meta.order.push(id);
+        }
+        Ok(())
+    }
+
+    /// Gets the next entry in the queue
+    fn next(&mut self, state: &mut S) -> Result<CorpusId, Error> {
+        let Some(mut meta) = state.metadata_map_mut().remove::<QueueOrderMetadata>() else {
+            return <QueueScheduler as Scheduler<I, S>>::next(&mut self.inner, state);
+        };
+        let current = *state.corpus().current();
+        let id = meta.next_after(state.corpus(), current);
+        state.metadata_map_mut().insert_boxed(meta);
+        let Some(id) = id else {
+            return <QueueScheduler as Scheduler<I, S>>::next(&mut self.inner, state);
+        };
+
+        self.inner.runs_in_current_cycle += 1;
+        if self.inner.runs_in_current_cycle >= state.corpus().count() as u64 {
+            self.inner.queue_cycles += 1;
+        }
+        <Self as Scheduler<I, S>>::set_current_scheduled(self, state, Some(id))?;
+        Ok(id)
+    }
+
+    fn set_current_scheduled(
+        &mut self,
+        state: &mut S,
+        next_id: Option<CorpusId>,
+    ) -> Result<(), Error> {
+        <QueueScheduler as Scheduler<I, S>>::set_current_scheduled(&mut self.inner, state, next_id)
+    }
+}
+
+impl OrderedQueueScheduler {
+    /// Creates a new `OrderedQueueScheduler`
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl HasQueueCycles for OrderedQueueScheduler {
+    fn queue_cycles(&self) -> u64 {
+        self.inner.queue_cycles
+    }
+}
+
 #[cfg(test)]
 #[cfg(feature = "std")]
 mod tests {
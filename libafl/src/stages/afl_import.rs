@@ -0,0 +1,448 @@
+//! Warm-start a campaign from the output directory of an AFL++ instance, see [`AflQueueImporter`].
+//!
+//! AFL++ encodes the history of each queue entry in its file name, e.g.,
+//! `id:000012,src:000003+000007,time:1234,execs:5678,op:splice,rep:2,+cov`,
+//! and keeps its global stats in a `fuzzer_stats` file of `key : value` lines.
+//! The importer reconstructs the parent links and depths of the entries from their names,
+//! so the schedulers do not start from scratch, and the [`crate::stages::CalibrationStage`] only runs a reduced
+//! pass on them.
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{str::FromStr, time::Duration};
+#[cfg(feature = "std")]
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+#[cfg(feature = "std")]
+use crate::{
+    corpus::{Corpus, CorpusId, SchedulerTestcaseMetadata},
+    fuzzer::Evaluator,
+    inputs::Input,
+    schedulers::powersched::SchedulerMetadata,
+    state::HasCorpus,
+    HasMetadata,
+};
+
+/// The history of a testcase imported from an AFL++ queue, parsed from its file name,
+/// see [`AflQueueEntryMetadata::parse`].
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AflQueueEntryMetadata {
+    /// The id of the entry in the AFL++ queue
+    pub id: usize,
+    /// The ids of the entries this one was derived from, two for splicing, none for seeds
+    pub sources: Vec<usize>,
+    /// When AFL++ found the entry, since the start of its campaign
+    pub time: Option<Duration>,
+    /// The executions AFL++ had done when it found the entry
+    pub execs: Option<u64>,
+    /// The mutation operator that produced the entry, e.g., `havoc`
+    pub op: Option<String>,
+    /// The original file name, for seeds
+    pub orig: Option<String>,
+    /// If the entry hit new edges, not just new hit counts, marked by `+cov`
+    pub new_coverage: bool,
+}
+
+libafl_bolts::impl_serdeany!(AflQueueEntryMetadata);
+
+/// Parse the value of the `key` field in a queue file name
+fn parse_field<T>(name: &str, key: &str, value: &str) -> Result<T, Error>
+where
+    T: FromStr,
+{
+    value.parse().map_err(|_| {
+        Error::illegal_argument(format!(
+            "Invalid {key} `{value}` in AFL++ queue entry name {name}"
+        ))
+    })
+}
+
+impl AflQueueEntryMetadata {
+    /// Parse the name of an AFL++ queue file, such as `id:000012,src:000003+000007,time:1234,op:splice,+cov`.
+    ///
+    /// Only the `id` is required, fields this does not know, such as `rep` or `sync`, are skipped.
+    /// The `orig` field of seeds is the last one, and taken as it is, even if the original name contains commas.
+    pub fn parse(name: &str) -> Result<Self, Error> {
+        let (fields, orig) = match name.find("orig:") {
+            Some(start) => (
+                &name[..start],
+                Some(name[start + "orig:".len()..].to_string()),
+            ),
+            None => (name, None),
+        };
+
+        let mut id = None;
+        let mut entry = Self {
+            id: 0,
+            sources: Vec::new(),
+            time: None,
+            execs: None,
+            op: None,
+            orig,
+            new_coverage: false,
+        };
+        for field in fields.split(',').filter(|field| !field.is_empty()) {
+            if field == "+cov" {
+                entry.new_coverage = true;
+                continue;
+            }
+            let Some((key, value)) = field.split_once(':') else {
+                continue;
+            };
+            match key {
+                "id" => id = Some(parse_field(name, key, value)?),
+                "src" => {
+                    entry.sources = value
+                        .split('+')
+                        .map(|source| parse_field(name, key, source))
+                        .collect::<Result<_, _>>()?;
+                }
+                "time" => entry.time = Some(Duration::from_millis(parse_field(name, key, value)?)),
+                "execs" => entry.execs = Some(parse_field(name, key, value)?),
+                "op" => entry.op = Some(value.to_string()),
+                _ => (),
+            }
+        }
+        entry.id = id.ok_or_else(|| {
+            Error::illegal_argument(format!("AFL++ queue entry name {name} has no id"))
+        })?;
+        Ok(entry)
+    }
+}
+
+/// The `fuzzer_stats` of an AFL++ instance, see [`AflFuzzerStats::parse`].
+///
+/// Kept in the state by [`AflQueueImporter::import`].
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AflFuzzerStats {
+    fields: HashMap<String, String>,
+}
+
+libafl_bolts::impl_serdeany!(AflFuzzerStats);
+
+impl AflFuzzerStats {
+    /// Parse the `key : value` lines of a `fuzzer_stats` file, skipping all other lines
+    #[must_use]
+    pub fn parse(text: &str) -> Self {
+        let fields = text
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .collect();
+        Self { fields }
+    }
+
+    /// Read and parse the `fuzzer_stats` file at `path`
+    #[cfg(feature = "std")]
+    pub fn from_file<P>(path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    /// The raw value of `key`, e.g., `afl_version`
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields.get(key).map(String::as_str)
+    }
+
+    /// The value of `key`, parsed, if present and valid
+    #[must_use]
+    pub fn get_parsed<T>(&self, key: &str) -> Option<T>
+    where
+        T: FromStr,
+    {
+        self.get(key)?.parse().ok()
+    }
+
+    /// The executions of the whole campaign, `execs_done`
+    #[must_use]
+    pub fn execs_done(&self) -> Option<u64> {
+        self.get_parsed("execs_done")
+    }
+
+    /// The average time of one execution, derived from `execs_per_sec`
+    #[must_use]
+    pub fn avg_exec_time(&self) -> Option<Duration> {
+        let execs_per_sec: f64 = self.get_parsed("execs_per_sec")?;
+        (execs_per_sec > 0.0).then(|| Duration::from_secs_f64(1.0 / execs_per_sec))
+    }
+}
+
+/// Imports the queue of an AFL++ instance into the corpus, keeping the scheduling history of its entries.
+///
+/// Each queue entry, in the order of their ids, is added to the corpus with [`Evaluator::add_input`],
+/// so it runs once, gets the metadata of the feedbacks, and reaches the scheduler like any other testcase.
+/// The new testcase then gets
+/// - its [`AflQueueEntryMetadata`], which makes the [`crate::stages::CalibrationStage`] run a reduced pass on it,
+/// - the entry it was derived from first as its parent, and the depth that follows from it in its
+///   [`SchedulerTestcaseMetadata`], seeds having depth `1`,
+/// - the average execution time of the AFL++ campaign, if known, until it is calibrated.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct AflQueueImporter {
+    out_dir: PathBuf,
+}
+
+#[cfg(feature = "std")]
+impl AflQueueImporter {
+    /// Create a new importer for the output directory of one AFL++ instance,
+    /// the one containing the `queue` directory and the `fuzzer_stats`, e.g., `out/default`
+    #[must_use]
+    pub fn new<P>(out_dir: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        Self {
+            out_dir: out_dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// The queue entries, parsed and sorted by their ids, with their paths.
+    ///
+    /// Files that are not named like queue entries are skipped.
+    pub fn entries(&self) -> Result<Vec<(AflQueueEntryMetadata, PathBuf)>, Error> {
+        let mut entries = Vec::new();
+        for dir_entry in fs::read_dir(self.out_dir.join("queue"))? {
+            let path = dir_entry?.path();
+            if !path.is_file() {
+                continue;
+            }
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            match AflQueueEntryMetadata::parse(name) {
+                Ok(entry) => entries.push((entry, path)),
+                Err(err) => log::warn!("Skipping {}: {err}", path.display()),
+            }
+        }
+        entries.sort_by_key(|(entry, _)| entry.id);
+        Ok(entries)
+    }
+
+    /// The `fuzzer_stats` of the instance, if there are any
+    pub fn fuzzer_stats(&self) -> Result<Option<AflFuzzerStats>, Error> {
+        let path = self.out_dir.join("fuzzer_stats");
+        if path.is_file() {
+            Ok(Some(AflFuzzerStats::from_file(path)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Import the queue into the corpus of the `state`, returning the ids of the new testcases in queue order.
+    ///
+    /// Also keeps the [`AflFuzzerStats`] in the `state`, and, if a power schedule is used, seeds its
+    /// [`SchedulerMetadata`] with the average execution time of the AFL++ campaign.
+    pub fn import<E, EM, S, Z>(
+        &self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+    ) -> Result<Vec<CorpusId>, Error>
+    where
+        Z: Evaluator<E, EM, <S::Corpus as Corpus>::Input, S>,
+        S: HasCorpus + HasMetadata,
+        <S::Corpus as Corpus>::Input: Input,
+    {
+        let stats = self.fuzzer_stats()?;
+        let avg_exec_time = stats.as_ref().and_then(AflFuzzerStats::avg_exec_time);
+
+        // The corpus id and depth of each imported AFL++ id
+        let mut imported: HashMap<usize, (CorpusId, u64)> = HashMap::default();
+        let mut ids = Vec::new();
+        for (entry, path) in self.entries()? {
+            let input = <S::Corpus as Corpus>::Input::from_file(&path)?;
+            let id = fuzzer.add_input(state, executor, manager, input)?;
+
+            // The scheduler took the testcase for a child of the current one, if any
+            let parent = entry
+                .sources
+                .first()
+                .and_then(|source| imported.get(source))
+                .copied();
+            let depth = parent.map_or(1, |(_, depth)| depth + 1);
+            let mut testcase = state.corpus().get_from_all(id)?.borrow_mut();
+            testcase.set_parent_id_optional(parent.map(|(parent_id, _)| parent_id));
+            match testcase.metadata_mut::<SchedulerTestcaseMetadata>() {
+                Ok(meta) => meta.set_depth(depth),
+                Err(_) => testcase.add_metadata(SchedulerTestcaseMetadata::new(depth)),
+            }
+            if let Some(exec_time) = avg_exec_time {
+                testcase.set_exec_time(exec_time);
+            }
+            let afl_id = entry.id;
+            testcase.add_metadata(entry);
+            drop(testcase);
+
+            imported.insert(afl_id, (id, depth));
+            ids.push(id);
+        }
+
+        if let (Some(exec_time), Ok(psmeta)) =
+            (avg_exec_time, state.metadata_mut::<SchedulerMetadata>())
+        {
+            let count = ids.len() as u64;
+            psmeta.set_exec_time(psmeta.exec_time() + exec_time * u32::try_from(count)?);
+            psmeta.set_cycles(psmeta.cycles() + count);
+        }
+        if let Some(stats) = stats {
+            state.add_metadata(stats);
+        }
+        log::info!(
+            "Imported {} AFL++ queue entries from {}",
+            ids.len(),
+            self.out_dir.display()
+        );
+        Ok(ids)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod tests {
+    use core::time::Duration;
+    use std::{env, fs, process};
+
+    use libafl_bolts::{rands::StdRand, tuples::tuple_list};
+
+    use super::{AflFuzzerStats, AflQueueEntryMetadata, AflQueueImporter};
+    use crate::{
+        corpus::{Corpus, InMemoryCorpus, SchedulerTestcaseMetadata},
+        events::NopEventManager,
+        executors::{ExitKind, InProcessExecutor},
+        feedbacks::ConstFeedback,
+        inputs::BytesInput,
+        schedulers::{powersched::SchedulerMetadata, QueueScheduler},
+        state::{HasCorpus, HasExecutions, StdState},
+        HasMetadata, StdFuzzer,
+    };
+
+    #[test]
+    fn test_parse_entry_name() {
+        let entry = AflQueueEntryMetadata::parse(
+            "id:000012,src:000003+000007,time:1234,execs:5678,op:splice,rep:2,+cov",
+        )
+        .unwrap();
+        assert_eq!(entry.id, 12);
+        assert_eq!(entry.sources, [3, 7]);
+        assert_eq!(entry.time, Some(Duration::from_millis(1234)));
+        assert_eq!(entry.execs, Some(5678));
+        assert_eq!(entry.op.as_deref(), Some("splice"));
+        assert!(entry.new_coverage);
+
+        let seed = AflQueueEntryMetadata::parse("id:000000,time:0,execs:0,orig:a,b:c").unwrap();
+        assert!(seed.sources.is_empty());
+        assert_eq!(seed.orig.as_deref(), Some("a,b:c"));
+        assert!(!seed.new_coverage);
+
+        assert!(AflQueueEntryMetadata::parse("src:000001,op:havoc").is_err());
+        assert!(AflQueueEntryMetadata::parse("id:abc").is_err());
+        assert!(AflQueueEntryMetadata::parse("README.txt").is_err());
+    }
+
+    #[test]
+    fn test_import_queue() {
+        // Colons are not allowed in file names on every platform, so the fixture is created here
+        let out_dir = env::temp_dir().join(format!("libafl_afl_import_{}", process::id()));
+        let queue = out_dir.join("queue");
+        _ = fs::remove_dir_all(&out_dir);
+        fs::create_dir_all(queue.join(".state")).unwrap();
+        fs::write(
+            out_dir.join("fuzzer_stats"),
+            "start_time        : 1700000000\nexecs_done        : 100000\nexecs_per_sec     : 1000.00\nafl_version       : ++4.21c\n",
+        )
+        .unwrap();
+        for (name, content) in [
+            ("id:000000,time:0,execs:0,orig:seed", "seed"),
+            (
+                "id:000001,src:000000,time:10,execs:100,op:havoc,rep:2,+cov",
+                "seeds",
+            ),
+            (
+                "id:000002,src:000001,time:20,execs:200,op:flip1,pos:0",
+                "Seeds",
+            ),
+            (
+                "id:000003,src:000002+000000,time:30,execs:300,op:splice,rep:4,+cov",
+                "Seed",
+            ),
+            ("README.txt", "not an entry"),
+        ] {
+            fs::write(queue.join(name), content).unwrap();
+        }
+
+        let mut feedback = ConstFeedback::new(false);
+        let mut objective = ConstFeedback::new(false);
+        let mut state = StdState::new(
+            StdRand::with_seed(0),
+            InMemoryCorpus::<BytesInput>::new(),
+            InMemoryCorpus::new(),
+            &mut feedback,
+            &mut objective,
+        )
+        .unwrap();
+        state.add_metadata(SchedulerMetadata::new(None));
+        let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+        let mut manager = NopEventManager::new();
+        let mut harness = |_: &BytesInput| ExitKind::Ok;
+        let mut executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(),
+            &mut fuzzer,
+            &mut state,
+            &mut manager,
+        )
+        .unwrap();
+
+        let ids = AflQueueImporter::new(&out_dir)
+            .import(&mut fuzzer, &mut executor, &mut state, &mut manager)
+            .unwrap();
+        fs::remove_dir_all(&out_dir).unwrap();
+        assert_eq!(ids.len(), 4);
+        assert_eq!(state.corpus().count(), 4);
+        assert_eq!(*state.executions(), 4);
+
+        let expected = [(None, 1), (Some(0), 2), (Some(1), 3), (Some(2), 4)];
+        for (id, (parent, depth)) in ids.iter().zip(expected) {
+            let testcase = state.corpus().get(*id).unwrap().borrow();
+            assert_eq!(testcase.parent_id(), parent.map(|parent| ids[parent]));
+            assert_eq!(
+                testcase
+                    .metadata::<SchedulerTestcaseMetadata>()
+                    .unwrap()
+                    .depth(),
+                depth
+            );
+            assert!(testcase.has_metadata::<AflQueueEntryMetadata>());
+            assert_eq!(*testcase.exec_time(), Some(Duration::from_millis(1)));
+        }
+
+        let stats = state.metadata::<AflFuzzerStats>().unwrap();
+        assert_eq!(stats.get("afl_version"), Some("++4.21c"));
+        assert_eq!(stats.execs_done(), Some(100_000));
+        let psmeta = state.metadata::<SchedulerMetadata>().unwrap();
+        assert_eq!(psmeta.cycles(), 4);
+        assert_eq!(psmeta.exec_time(), Duration::from_millis(4));
+    }
+}
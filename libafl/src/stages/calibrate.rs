@@ -13,16 +13,16 @@ use num_traits::Bounded;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    corpus::{Corpus, HasCurrentCorpusId, SchedulerTestcaseMetadata},
+    corpus::{Corpus, CorpusId, HasCurrentCorpusId, SchedulerTestcaseMetadata},
     events::{Event, EventFirer, LogSeverity},
     executors::{Executor, ExitKind, HasObservers},
     feedbacks::{map::MapFeedbackMetadata, HasObserverHandle},
-    fuzzer::Evaluator,
+    fuzzer::{Evaluator, HasInputFilter},
     inputs::{Input, UsesInput},
     monitors::{AggregatorOps, UserStats, UserStatsValue},
     observers::{MapObserver, ObserversTuple},
     schedulers::powersched::SchedulerMetadata,
-    stages::{RetryCountRestartHelper, Stage},
+    stages::{AflQueueEntryMetadata, RetryCountRestartHelper, Stage},
     state::{HasCorpus, HasCurrentTestcase, HasExecutions},
     Error, HasMetadata, HasNamedMetadata,
 };
@@ -69,6 +69,41 @@ impl Default for UnstableEntriesMetadata {
     }
 }
 
+/// The calibration another node measured for a testcase it sent, see [`CalibrationStage::forwarded_runs`]
+///
+/// The centralized main node attaches it to the testcases it passes on, and the receiving
+/// event managers keep it as metadata of the imported testcase, if the configuration of the sender matches theirs.
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CalibrationHint {
+    /// The execution time the sender measured
+    pub exec_time: Duration,
+    /// If the target was stable on the sender, i.e., it calibrated entries and found no unstable map entries
+    pub stable: bool,
+}
+impl_serdeany!(CalibrationHint);
+
+impl CalibrationHint {
+    /// The hint for the corpus entry `id` of `state`, if its execution time was measured,
+    /// e.g., by a [`crate::feedbacks::TimeFeedback`]
+    pub fn of<S>(state: &S, id: CorpusId) -> Result<Option<Self>, Error>
+    where
+        S: HasCorpus + HasMetadata,
+    {
+        let Some(exec_time) = *state.corpus().get(id)?.borrow().exec_time() else {
+            return Ok(None);
+        };
+        let stable = state
+            .metadata_map()
+            .get::<UnstableEntriesMetadata>()
+            .is_some_and(|meta| meta.unstable_entries.is_empty());
+        Ok(Some(Self { exec_time, stable }))
+    }
+}
+
 /// Default name for `CalibrationStage`; derived from AFL++
 pub const CALIBRATION_STAGE_NAME: &str = "calibration";
 /// The calibration stage will measure the average exec time and the target's stability for this input.
@@ -80,11 +115,14 @@ pub struct CalibrationStage<C, E, O, OT, S> {
     stage_max: usize,
     /// If we should track stability
     track_stability: bool,
+    /// How often to run testcases that come with a stable [`CalibrationHint`]
+    forwarded_runs: usize,
     phantom: PhantomData<(E, O, OT, S)>,
 }
 
 const CAL_STAGE_START: usize = 4; // AFL++'s CAL_CYCLES_FAST + 1
 const CAL_STAGE_MAX: usize = 8; // AFL++'s CAL_CYCLES + 1
+const CAL_STAGE_WARM_START: usize = 2; // entries already calibrated by AFL++, see `AflQueueImporter`
 
 impl<C, E, EM, O, OT, S, Z> Stage<E, EM, S, Z> for CalibrationStage<C, E, O, OT, S>
 where
@@ -102,7 +140,8 @@ where
         + HasCurrentTestcase
         + HasCurrentCorpusId
         + UsesInput<Input = <S::Corpus as Corpus>::Input>,
-    Z: Evaluator<E, EM, <S::Corpus as Corpus>::Input, S>,
+    Z: Evaluator<E, EM, <S::Corpus as Corpus>::Input, S>
+        + HasInputFilter<<S::Corpus as Corpus>::Input, S>,
     <S::Corpus as Corpus>::Input: Input,
 {
     #[inline]
@@ -119,18 +158,38 @@ where
         mgr: &mut EM,
     ) -> Result<(), Error> {
         // Run this stage only once for each corpus entry and only if we haven't already inspected it
-        {
+        let (warm_start, hint) = {
             let testcase = state.current_testcase()?;
             // println!("calibration; corpus.scheduled_count() : {}", corpus.scheduled_count());
 
             if testcase.scheduled_count() > 0 {
                 return Ok(());
             }
-        }
+            (
+                testcase.has_metadata::<AflQueueEntryMetadata>(),
+                testcase.metadata_map().get::<CalibrationHint>().copied(),
+            )
+        };
 
         let mut iter = self.stage_max;
+        // Imported entries were calibrated by AFL++ already, only check them once more.
+        // Unstable or crashing runs still increase the iterations, as usual.
+        if warm_start {
+            iter = iter.min(CAL_STAGE_WARM_START);
+        }
+        // The sender calibrated forwarded entries on a stable target already, only confirm them.
+        if let Some(hint) = hint.filter(|hint| hint.stable) {
+            if self.forwarded_runs == 0 && !state.has_metadata::<SchedulerMetadata>() {
+                state.current_testcase_mut()?.set_exec_time(hint.exec_time);
+                return Ok(());
+            }
+            iter = iter.min(self.forwarded_runs.max(1));
+        }
         // If we restarted after a timeout or crash, do less iterations.
         let input = state.current_input_cloned()?;
+        if fuzzer.skips_input(state, &input) {
+            return Ok(());
+        }
 
         // Run once to get the initial calibration map
         executor.observers_mut().pre_exec_all(state, &input)?;
@@ -402,6 +461,7 @@ where
             map_name: map_name.clone(),
             stage_max: CAL_STAGE_START,
             track_stability: true,
+            forwarded_runs: 1,
             phantom: PhantomData,
             name: Cow::Owned(
                 CALIBRATION_STAGE_NAME.to_owned() + ":" + map_name.into_owned().as_str(),
@@ -419,6 +479,17 @@ where
         ret.track_stability = false;
         ret
     }
+
+    /// How often to run testcases that another node calibrated on a stable target already, see [`CalibrationHint`].
+    ///
+    /// Defaults to a single confirmation run. With `0`, the execution time of the hint is taken as is,
+    /// unless a power or weighted scheduler needs the bitmap size, which takes one run.
+    /// Entries without a hint, or with an unstable one, are calibrated fully.
+    #[must_use]
+    pub fn forwarded_runs(mut self, forwarded_runs: usize) -> Self {
+        self.forwarded_runs = forwarded_runs;
+        self
+    }
 }
 
 impl<C, E, O, OT, S> Named for CalibrationStage<C, E, O, OT, S> {
@@ -426,3 +497,84 @@ impl<C, E, O, OT, S> Named for CalibrationStage<C, E, O, OT, S> {
         &self.name
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use core::{cell::Cell, time::Duration};
+
+    use libafl_bolts::tuples::tuple_list;
+
+    use crate::{
+        corpus::{Corpus, HasCurrentCorpusId, Testcase},
+        events::NopEventManager,
+        executors::{ExitKind, InProcessExecutor},
+        feedbacks::{ConstFeedback, MaxMapFeedback},
+        inputs::BytesInput,
+        observers::StdMapObserver,
+        schedulers::QueueScheduler,
+        stages::{calibrate::UnstableEntriesMetadata, CalibrationHint, CalibrationStage, Stage},
+        state::HasCorpus,
+        testing::bytes_state,
+        HasMetadata, StdFuzzer,
+    };
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_calibration_hint() {
+        let observer = StdMapObserver::owned("edges", vec![0_u8; 4]);
+        let mut feedback = MaxMapFeedback::new(&observer);
+        let mut objective = ConstFeedback::new(false);
+        let mut state = bytes_state(&mut feedback, &mut objective);
+        let stage = CalibrationStage::new(&feedback);
+        let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+        let mut mgr = NopEventManager::new();
+        let runs = Cell::new(0);
+        let mut harness = |_: &BytesInput| {
+            runs.set(runs.get() + 1);
+            ExitKind::Ok
+        };
+        let mut executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(observer),
+            &mut fuzzer,
+            &mut state,
+            &mut mgr,
+        )
+        .unwrap();
+
+        // The hint carries the measured execution time, and the stability seen so far
+        let mut testcase = Testcase::new(BytesInput::new(vec![0]));
+        testcase.set_exec_time(Duration::from_millis(3));
+        let id = state.corpus_mut().add(testcase).unwrap();
+        assert_eq!(
+            CalibrationHint::of(&state, id)
+                .unwrap()
+                .map(|hint| hint.stable),
+            Some(false)
+        );
+        state.add_metadata(UnstableEntriesMetadata::new());
+        let hint = CalibrationHint::of(&state, id).unwrap().unwrap();
+        assert_eq!(
+            hint,
+            CalibrationHint {
+                exec_time: Duration::from_millis(3),
+                stable: true,
+            }
+        );
+
+        // Without a power schedule, stable hints are taken as is
+        let mut stage = stage.forwarded_runs(0);
+        let mut testcase = Testcase::new(BytesInput::new(vec![1]));
+        testcase.add_metadata(hint);
+        let id = state.corpus_mut().add(testcase).unwrap();
+        state.set_corpus_id(id).unwrap();
+        stage
+            .perform(&mut fuzzer, &mut executor, &mut state, &mut mgr)
+            .unwrap();
+        assert_eq!(runs.get(), 0);
+        assert_eq!(
+            *state.corpus().get(id).unwrap().borrow().exec_time(),
+            Some(Duration::from_millis(3))
+        );
+    }
+}
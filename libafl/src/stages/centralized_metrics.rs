@@ -0,0 +1,309 @@
+//! The [`CentralizedMetricsDump`] stage periodically appends the counters of a
+//! [`crate::events::CentralizedEventManager`] to a file, one JSON record per line.
+//!
+//! On `no_std`, the stage does nothing.
+
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::{
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "std")]
+use libafl_bolts::current_time;
+use libafl_bolts::impl_serdeany;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    events::{CentralizedMetrics, HasCentralizedMetrics},
+    stages::Stage,
+    state::Stoppable,
+    Error, HasMetadata,
+};
+
+/// One line of the file written by [`CentralizedMetricsDump`]
+#[cfg(feature = "std")]
+#[derive(Debug, Serialize)]
+struct MetricsRecord<'a> {
+    /// The time the counters were read, in milliseconds since the epoch
+    timestamp_ms: u64,
+    #[serde(flatten)]
+    metrics: &'a CentralizedMetrics,
+}
+
+/// The latest counters a [`CentralizedMetricsDump`] read, but did not write yet, and when it read them.
+///
+/// They are kept in the state, so a restarted process writes them, even if the last one exited right away.
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnwrittenCentralizedMetrics {
+    /// When the counters were read
+    pub time: Duration,
+    /// The counters
+    pub metrics: CentralizedMetrics,
+}
+
+impl_serdeany!(UnwrittenCentralizedMetrics);
+
+/// A [`Stage`] that appends the [`CentralizedMetrics`] of the manager to a file,
+/// as one timestamped JSON record per line, at most every `interval`.
+///
+/// The counters read since the last record are kept in the state, see [`UnwrittenCentralizedMetrics`].
+/// They are written once a stop is requested, by the first run after a restart,
+/// or on [`CentralizedMetricsDump::flush`], so the file is up to date on shutdown.
+#[derive(Debug)]
+pub struct CentralizedMetricsDump {
+    #[cfg(feature = "std")]
+    path: PathBuf,
+    interval: Duration,
+    /// The last time a record was written, `None` if this process did not write one yet
+    last_dump: Option<Duration>,
+    /// The source of the current time
+    clock: fn() -> Duration,
+}
+
+impl CentralizedMetricsDump {
+    /// Create a new [`CentralizedMetricsDump`] stage, appending to the file at `path` every `interval`
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn new<P>(path: P, interval: Duration) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        Self {
+            path: path.into(),
+            interval,
+            last_dump: None,
+            clock: current_time,
+        }
+    }
+
+    /// Create a new [`CentralizedMetricsDump`] stage, doing nothing on `no_std`
+    #[cfg(not(feature = "std"))]
+    #[must_use]
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_dump: None,
+            clock: || Duration::ZERO,
+        }
+    }
+
+    /// The file the records are appended to
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write the counters read since the last record, if any.
+    ///
+    /// Call this before exiting the process in any other way than requesting a stop.
+    pub fn flush<S>(&mut self, state: &mut S) -> Result<(), Error>
+    where
+        S: HasMetadata,
+    {
+        if let Some(unwritten) = state
+            .metadata_map_mut()
+            .remove::<UnwrittenCentralizedMetrics>()
+        {
+            self.write_record(unwritten.time, &unwritten.metrics)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    fn write_record(&mut self, time: Duration, metrics: &CentralizedMetrics) -> Result<(), Error> {
+        let record = MetricsRecord {
+            timestamp_ms: time.as_millis().try_into().unwrap_or(u64::MAX),
+            metrics,
+        };
+        let mut line = serde_json::to_vec(&record)
+            .map_err(|err| Error::serialize(format!("Failed to json-ify metrics: {err:?}")))?;
+        line.push(b'\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(&line)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "std"))]
+    #[allow(clippy::unnecessary_wraps)]
+    fn write_record(
+        &mut self,
+        _time: Duration,
+        _metrics: &CentralizedMetrics,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<E, EM, S, Z> Stage<E, EM, S, Z> for CentralizedMetricsDump
+where
+    EM: HasCentralizedMetrics,
+    S: HasMetadata + Stoppable,
+{
+    fn perform(
+        &mut self,
+        _fuzzer: &mut Z,
+        _executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        if cfg!(not(feature = "std")) {
+            return Ok(());
+        }
+
+        if self.last_dump.is_none() {
+            // Left over by the process before a restart
+            self.flush(state)?;
+        }
+        let cur = (self.clock)();
+        let metrics = manager.centralized_metrics();
+        let due = self
+            .last_dump
+            .is_none_or(|last| cur.saturating_sub(last) >= self.interval);
+        if due || state.stop_requested() {
+            let _ = state
+                .metadata_map_mut()
+                .remove::<UnwrittenCentralizedMetrics>();
+            self.write_record(cur, &metrics)?;
+            self.last_dump = Some(cur);
+        } else {
+            state.add_metadata(UnwrittenCentralizedMetrics { time: cur, metrics });
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn should_restart(&mut self, _state: &mut S) -> Result<bool, Error> {
+        // Not executing the target, so restart safety is not needed
+        Ok(true)
+    }
+
+    #[inline]
+    fn clear_progress(&mut self, _state: &mut S) -> Result<(), Error> {
+        // Not executing the target, so restart safety is not needed
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use core::{
+        sync::atomic::{AtomicU64, Ordering},
+        time::Duration,
+    };
+    use std::{env, fs, path::Path, process, vec::Vec};
+
+    use super::CentralizedMetricsDump;
+    use crate::{
+        events::{CentralizedMetrics, HasCentralizedMetrics},
+        inputs::NopInput,
+        stages::Stage,
+        state::{NopState, Stoppable},
+    };
+
+    struct FakeManager {
+        forwarded: u64,
+    }
+
+    impl HasCentralizedMetrics for FakeManager {
+        fn centralized_metrics(&self) -> CentralizedMetrics {
+            CentralizedMetrics {
+                forwarded: self.forwarded,
+                ..CentralizedMetrics::default()
+            }
+        }
+    }
+
+    /// The current time of [`fake_clock`], in seconds
+    static FAKE_TIME_SECS: AtomicU64 = AtomicU64::new(0);
+
+    fn fake_clock() -> Duration {
+        Duration::from_secs(FAKE_TIME_SECS.load(Ordering::Relaxed))
+    }
+
+    fn records(path: &Path) -> Vec<serde_json::Value> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_centralized_metrics_dump() {
+        let path = env::temp_dir().join(format!(
+            "libafl_centralized_metrics_dump_{}.jsonl",
+            process::id()
+        ));
+        let _ = fs::remove_file(&path);
+
+        let new_stage = || {
+            let mut stage = CentralizedMetricsDump::new(&path, Duration::from_secs(10));
+            stage.clock = fake_clock;
+            stage
+        };
+        let mut stage = new_stage();
+        let mut state = NopState::<NopInput>::new();
+        let mut manager = FakeManager { forwarded: 0 };
+
+        let mut perform_at =
+            |stage: &mut CentralizedMetricsDump, state: &mut _, secs, forwarded| {
+                FAKE_TIME_SECS.store(secs, Ordering::Relaxed);
+                manager.forwarded = forwarded;
+                Stage::<(), FakeManager, NopState<NopInput>, ()>::perform(
+                    stage,
+                    &mut (),
+                    &mut (),
+                    state,
+                    &mut manager,
+                )
+                .unwrap();
+            };
+
+        perform_at(&mut stage, &mut state, 0, 1);
+        perform_at(&mut stage, &mut state, 5, 2);
+        perform_at(&mut stage, &mut state, 10, 3);
+        // Two intervals, two records
+        let written = records(&path);
+        assert_eq!(written.len(), 2);
+        assert_eq!(written[0]["timestamp_ms"], 0);
+        assert_eq!(written[0]["forwarded"], 1);
+        assert_eq!(written[1]["timestamp_ms"], 10_000);
+        assert_eq!(written[1]["forwarded"], 3);
+
+        // The process exits without dropping the stage, the next one writes the counters left in the state
+        perform_at(&mut stage, &mut state, 15, 4);
+        assert_eq!(records(&path).len(), 2);
+        let mut stage = new_stage();
+        perform_at(&mut stage, &mut state, 16, 5);
+        let written = records(&path);
+        assert_eq!(written.len(), 4);
+        assert_eq!(written[2]["timestamp_ms"], 15_000);
+        assert_eq!(written[2]["forwarded"], 4);
+        assert_eq!(written[3]["forwarded"], 5);
+
+        // Once a stop is requested, the counters are written right away
+        perform_at(&mut stage, &mut state, 17, 6);
+        assert_eq!(records(&path).len(), 4);
+        state.request_stop();
+        perform_at(&mut stage, &mut state, 18, 7);
+        let written = records(&path);
+        assert_eq!(written.len(), 5);
+        assert_eq!(written[4]["forwarded"], 7);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,155 @@
+//! The [`CheckpointStage`] periodically writes a durable checkpoint of the state to disk.
+//!
+//! See [`crate::state::checkpoint`] for how to continue from such a checkpoint.
+
+use core::time::Duration;
+use std::path::PathBuf;
+
+use libafl_bolts::current_time;
+use serde::{Deserialize, Serialize};
+
+use crate::{stages::Stage, state::checkpoint::save_checkpoint, Error, HasMetadata};
+
+/// Metadata used to store when the last checkpoint was written
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct CheckpointMetadata {
+    /// The last time a checkpoint was written
+    pub last_time: Duration,
+}
+
+libafl_bolts::impl_serdeany!(CheckpointMetadata);
+
+/// A stage that writes a checkpoint of the state to disk every `interval`,
+/// keeping the last `keep` of them, see [`crate::state::checkpoint::save_checkpoint`].
+///
+/// The time of the last checkpoint is kept in the state, so restarts don't reset the interval.
+#[derive(Debug, Clone)]
+pub struct CheckpointStage {
+    path: PathBuf,
+    keep: usize,
+    interval: Duration,
+}
+
+impl CheckpointStage {
+    /// Create a new [`CheckpointStage`], writing to `path` every `interval`, and keeping only the newest checkpoint
+    #[must_use]
+    pub fn new(path: PathBuf, interval: Duration) -> Self {
+        Self {
+            path,
+            keep: 1,
+            interval,
+        }
+    }
+
+    /// Keep the last `keep` checkpoints as `path`, `path.1`, ..., at least one
+    #[must_use]
+    pub fn keep(mut self, keep: usize) -> Self {
+        self.keep = keep.max(1);
+        self
+    }
+
+    /// The path the newest checkpoint is written to
+    #[must_use]
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+}
+
+impl<E, EM, S, Z> Stage<E, EM, S, Z> for CheckpointStage
+where
+    S: HasMetadata + Serialize,
+{
+    fn perform(
+        &mut self,
+        _fuzzer: &mut Z,
+        _executor: &mut E,
+        state: &mut S,
+        _manager: &mut EM,
+    ) -> Result<(), Error> {
+        let now = current_time();
+        if let Ok(meta) = state.metadata::<CheckpointMetadata>() {
+            if now.saturating_sub(meta.last_time) < self.interval {
+                return Ok(());
+            }
+        }
+
+        // Store the time first, so a loaded checkpoint does not immediately write the next one
+        state.add_metadata(CheckpointMetadata { last_time: now });
+        log::info!("Writing a checkpoint to {}", self.path.display());
+        save_checkpoint(state, &self.path, self.keep)
+    }
+
+    #[inline]
+    fn should_restart(&mut self, _state: &mut S) -> Result<bool, Error> {
+        // Not executing the target, so restart safety is not needed
+        Ok(true)
+    }
+
+    #[inline]
+    fn clear_progress(&mut self, _state: &mut S) -> Result<(), Error> {
+        // Not executing the target, so restart safety is not needed
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+    use std::fs;
+
+    use crate::{
+        feedbacks::ConstFeedback,
+        stages::{CheckpointMetadata, CheckpointStage, Stage},
+        state::{checkpoint::checkpoint_path, CheckpointLoadMode, HasExecutions},
+        testing::{bytes_state, BytesState},
+        HasMetadata,
+    };
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_checkpoint_stage() {
+        let dir =
+            std::env::temp_dir().join(format!("libafl_checkpoint_stage_{}", std::process::id()));
+        drop(fs::remove_dir_all(&dir));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state");
+
+        let mut feedback = ConstFeedback::new(false);
+        let mut objective = ConstFeedback::new(false);
+        let mut state = bytes_state(&mut feedback, &mut objective);
+
+        let mut stage = CheckpointStage::new(path.clone(), Duration::from_secs(3600)).keep(2);
+        *state.executions_mut() = 1;
+        stage
+            .perform(&mut (), &mut (), &mut state, &mut ())
+            .unwrap();
+        // Within the interval, nothing is written
+        *state.executions_mut() = 2;
+        stage
+            .perform(&mut (), &mut (), &mut state, &mut ())
+            .unwrap();
+        let loaded = BytesState::load_from(&path, CheckpointLoadMode::Resume).unwrap();
+        assert_eq!(*loaded.executions(), 1);
+        assert!(loaded.metadata::<CheckpointMetadata>().is_ok());
+
+        // Once the interval passed, the next checkpoint is written, and the old one rotated
+        state
+            .metadata_mut::<CheckpointMetadata>()
+            .unwrap()
+            .last_time = Duration::ZERO;
+        stage
+            .perform(&mut (), &mut (), &mut state, &mut ())
+            .unwrap();
+        let loaded = BytesState::load_from(&path, CheckpointLoadMode::Fresh).unwrap();
+        assert_eq!(*loaded.executions(), 2);
+        let older =
+            BytesState::load_from(checkpoint_path(&path, 1), CheckpointLoadMode::Fresh).unwrap();
+        assert_eq!(*older.executions(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -23,7 +23,7 @@ use crate::{
     observers::{MapObserver, ObserversTuple},
     stages::{RetryCountRestartHelper, Stage},
     state::{HasCorpus, HasCurrentTestcase, HasRand, UsesState},
-    Error, HasMetadata, HasNamedMetadata,
+    Error, HasInputFilter, HasMetadata, HasNamedMetadata,
 };
 
 // Bigger range is better
@@ -92,6 +92,7 @@ where
     <S::Corpus as Corpus>::Input: HasMutatorBytes + Clone,
     O: MapObserver,
     C: AsRef<O> + Named,
+    Z: HasInputFilter<<S::Corpus as Corpus>::Input, S>,
 {
     #[inline]
     #[allow(clippy::let_and_return)]
@@ -173,6 +174,7 @@ where
         + HasCurrentTestcase
         + UsesInput<Input = <S::Corpus as Corpus>::Input>,
     <S::Corpus as Corpus>::Input: HasMutatorBytes + Clone,
+    Z: HasInputFilter<<S::Corpus as Corpus>::Input, S>,
 {
     #[inline]
     #[allow(clippy::let_and_return)]
@@ -184,6 +186,9 @@ where
         observer_handle: &Handle<C>,
     ) -> Result<<S::Corpus as Corpus>::Input, Error> {
         let mut input = state.current_input_cloned()?;
+        if fuzzer.skips_input(state, &input) {
+            return Ok(input);
+        }
         // The backup of the input
         let backup = input.clone();
         // This is the buffer we'll randomly mutate during type_replace
@@ -231,16 +236,21 @@ where
                     );
                 }
 
-                let changed_hash = Self::get_raw_map_hash_run(
-                    fuzzer,
-                    executor,
-                    state,
-                    manager,
-                    &input,
-                    observer_handle,
-                )?;
-
-                if orig_hash == changed_hash {
+                // An input the filter rejects cannot be shown to keep the coverage
+                let changed_hash = if fuzzer.skips_input(state, &input) {
+                    None
+                } else {
+                    Some(Self::get_raw_map_hash_run(
+                        fuzzer,
+                        executor,
+                        state,
+                        manager,
+                        &input,
+                        observer_handle,
+                    )?)
+                };
+
+                if changed_hash == Some(orig_hash) {
                     // The change in this range is safe!
                     // println!("this range safe to change: {:#?}", range_start..range_end);
 
@@ -21,7 +21,7 @@ use crate::{
     observers::{concolic::ConcolicObserver, ObserversTuple},
     stages::{RetryCountRestartHelper, Stage, TracingStage},
     state::{HasCorpus, HasCurrentTestcase, HasExecutions, MaybeHasClientPerfMonitor, UsesState},
-    Error, HasMetadata, HasNamedMetadata,
+    Error, HasInputFilter, HasMetadata, HasNamedMetadata,
 };
 #[cfg(feature = "concolic_mutation")]
 use crate::{
@@ -60,6 +60,7 @@ where
         + MaybeHasClientPerfMonitor
         + UsesInput<Input = <S::Corpus as Corpus>::Input>,
     EM: UsesState<State = S>,
+    Z: HasInputFilter<<S::Corpus as Corpus>::Input, S>,
 {
     #[inline]
     fn perform(
@@ -69,7 +70,9 @@ where
         state: &mut S,
         manager: &mut EM,
     ) -> Result<(), Error> {
-        self.inner.trace(fuzzer, state, manager)?;
+        if !self.inner.trace(fuzzer, state, manager)? {
+            return Ok(());
+        }
         if let Some(observer) = self.inner.executor().observers().get(&self.observer_handle) {
             let metadata = observer.create_metadata_from_current_map();
             state
@@ -0,0 +1,451 @@
+//! The [`CoverageDiffStage`] periodically snapshots the coverage of a map feedback to disk,
+//! and reports which indices were newly covered since the previous snapshot.
+//!
+//! This answers "what new code did the fuzzer reach since yesterday" without replaying the corpus.
+
+use alloc::{borrow::Cow, string::String, vec, vec::Vec};
+use core::{marker::PhantomData, time::Duration};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use hashbrown::HashMap;
+use libafl_bolts::{
+    compress::GzipCompressor, current_time, serdeany::SerdeAny, tuples::Handle, Named,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    events::{Event, EventFirer, LogSeverity},
+    feedbacks::MapFeedbackMetadata,
+    monitors::{AggregatorOps, UserStats, UserStatsValue, NEW_COVERAGE_STAT},
+    observers::MapObserver,
+    stages::Stage,
+    state::State,
+    Error, HasNamedMetadata,
+};
+
+/// The extension of the compressed coverage snapshots written by the [`CoverageDiffStage`]
+pub const COVERAGE_SNAPSHOT_EXTENSION: &str = "coverage";
+
+/// The default number of snapshots the [`CoverageDiffStage`] keeps, see [`CoverageDiffStage::keep`]
+pub const DEFAULT_KEPT_COVERAGE_SNAPSHOTS: usize = 7;
+
+/// The covered indices of a map, as written to disk by the [`CoverageDiffStage`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CoverageSnapshot {
+    /// When the snapshot was taken
+    pub time: Duration,
+    /// The size of the map
+    pub len: usize,
+    /// One bit per index of the map, set if the index was covered
+    pub bits: Vec<u64>,
+}
+
+impl CoverageSnapshot {
+    fn from_history<T>(time: Duration, history: &[T]) -> Self
+    where
+        T: PartialEq + Default,
+    {
+        let mut bits = vec![0_u64; history.len().div_ceil(64)];
+        let initial = T::default();
+        for (idx, entry) in history.iter().enumerate() {
+            if *entry != initial {
+                bits[idx / 64] |= 1 << (idx % 64);
+            }
+        }
+        Self {
+            time,
+            len: history.len(),
+            bits,
+        }
+    }
+
+    /// If the index `idx` was covered
+    #[must_use]
+    pub fn is_covered(&self, idx: usize) -> bool {
+        idx < self.len && self.bits[idx / 64] & (1 << (idx % 64)) != 0
+    }
+
+    /// The number of covered indices
+    #[must_use]
+    pub fn covered(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// The indices covered in this snapshot, but not in the `previous` one
+    #[must_use]
+    pub fn diff(&self, previous: &Self) -> CoverageDiff {
+        if self.len != previous.len {
+            return CoverageDiff::Incomparable {
+                previous_len: previous.len,
+                len: self.len,
+            };
+        }
+        let mut new = Vec::new();
+        for (word, (now, before)) in self.bits.iter().zip(&previous.bits).enumerate() {
+            let mut added = now & !before;
+            while added != 0 {
+                new.push(word * 64 + added.trailing_zeros() as usize);
+                added &= added - 1;
+            }
+        }
+        CoverageDiff::New(new)
+    }
+}
+
+/// How the coverage changed between two snapshots
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum CoverageDiff {
+    /// There was no previous snapshot to compare to
+    First,
+    /// The size of the map changed, e.g., after the target was rebuilt, so the indices don't match up
+    Incomparable {
+        /// The size of the map in the previous snapshot
+        previous_len: usize,
+        /// The size of the map now
+        len: usize,
+    },
+    /// The indices covered now, but not in the previous snapshot
+    New(Vec<usize>),
+}
+
+/// The report the [`CoverageDiffStage`] writes next to each snapshot, as JSON
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CoverageDiffReport {
+    /// When the snapshot was taken
+    pub time: Duration,
+    /// When the previous snapshot was taken, if there was one
+    pub previous_time: Option<Duration>,
+    /// The number of covered indices
+    pub covered: usize,
+    /// The indices covered since the previous snapshot
+    pub diff: CoverageDiff,
+    /// The source locations of the newly covered indices, in the same order,
+    /// if the stage was given a table, see [`CoverageDiffStage::locations`].
+    /// Indices missing from the table have no location.
+    pub locations: Option<Vec<Option<String>>>,
+}
+
+/// A stage that writes a compressed snapshot of the history map of a map feedback to `dir` every `interval`,
+/// compares it to the previous snapshot, and reports the newly covered indices.
+///
+/// Each snapshot is accompanied by a [`CoverageDiffReport`], and the number of newly covered indices
+/// is fired as the [`NEW_COVERAGE_STAT`] user stat, and as a log message.
+/// The snapshots are named after the time they were taken, and only the last
+/// [`CoverageDiffStage::keep`] of them are kept, with their reports.
+/// The previous snapshot is read from `dir`, so the comparison survives restarts.
+#[derive(Debug, Clone)]
+pub struct CoverageDiffStage<T> {
+    name: Cow<'static, str>,
+    dir: PathBuf,
+    interval: Duration,
+    keep: usize,
+    locations: Option<HashMap<usize, String>>,
+    last_time: Option<Duration>,
+    phantom: PhantomData<T>,
+}
+
+impl<T> CoverageDiffStage<T> {
+    /// Create a new [`CoverageDiffStage`] for the map feedback of the observer, writing to `dir` every `interval`
+    pub fn new<C>(
+        observer_handle: &Handle<C>,
+        dir: impl Into<PathBuf>,
+        interval: Duration,
+    ) -> Result<Self, Error>
+    where
+        C: MapObserver<Entry = T>,
+    {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            name: observer_handle.name().clone(),
+            dir,
+            interval,
+            keep: DEFAULT_KEPT_COVERAGE_SNAPSHOTS,
+            locations: None,
+            last_time: None,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Keep the last `keep` snapshots, at least one, [`DEFAULT_KEPT_COVERAGE_SNAPSHOTS`] by default
+    #[must_use]
+    pub fn keep(mut self, keep: usize) -> Self {
+        self.keep = keep.max(1);
+        self
+    }
+
+    /// Name the source location of each index in the reports, e.g., `file.c:42`
+    #[must_use]
+    pub fn locations(mut self, locations: HashMap<usize, String>) -> Self {
+        self.locations = Some(locations);
+        self
+    }
+
+    /// The snapshots in `dir`, oldest first
+    fn snapshots(&self) -> Result<Vec<PathBuf>, Error> {
+        let mut snapshots = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|ext| ext == COVERAGE_SNAPSHOT_EXTENSION)
+            {
+                snapshots.push(path);
+            }
+        }
+        // The names are zero-padded times
+        snapshots.sort();
+        Ok(snapshots)
+    }
+
+    fn read_snapshot(path: &Path) -> Result<CoverageSnapshot, Error> {
+        let compressed = fs::read(path)?;
+        Ok(postcard::from_bytes(
+            &GzipCompressor::new().decompress(&compressed)?,
+        )?)
+    }
+
+    /// Take a snapshot of the history map in the `state` now, compare it to the previous one in `dir`,
+    /// and write it to `dir`, with its report
+    pub fn snapshot<S>(&self, state: &S) -> Result<CoverageDiffReport, Error>
+    where
+        S: HasNamedMetadata,
+        T: PartialEq + Default + 'static,
+        MapFeedbackMetadata<T>: SerdeAny,
+    {
+        let history = state.named_metadata::<MapFeedbackMetadata<T>>(&self.name)?;
+        let snapshot = CoverageSnapshot::from_history(current_time(), &history.history_map);
+
+        let snapshots = self.snapshots()?;
+        let previous = snapshots
+            .last()
+            .map(|path| Self::read_snapshot(path))
+            .transpose()?;
+        let diff = match &previous {
+            Some(previous) => snapshot.diff(previous),
+            None => CoverageDiff::First,
+        };
+        let locations = match (&self.locations, &diff) {
+            (Some(locations), CoverageDiff::New(new)) => {
+                Some(new.iter().map(|idx| locations.get(idx).cloned()).collect())
+            }
+            _ => None,
+        };
+        let report = CoverageDiffReport {
+            time: snapshot.time,
+            previous_time: previous.map(|previous| previous.time),
+            covered: snapshot.covered(),
+            diff,
+            locations,
+        };
+
+        let path = self
+            .dir
+            .join(format!("{:020}", snapshot.time.as_nanos()))
+            .with_extension(COVERAGE_SNAPSHOT_EXTENSION);
+        fs::write(
+            &path,
+            GzipCompressor::new().compress(&postcard::to_allocvec(&snapshot)?),
+        )?;
+        let json = serde_json::to_vec_pretty(&report).map_err(|err| {
+            Error::serialize(format!("Failed to json-ify coverage report: {err:?}"))
+        })?;
+        fs::write(path.with_extension("json"), json)?;
+
+        let snapshots = self.snapshots()?;
+        for old in &snapshots[..snapshots.len().saturating_sub(self.keep)] {
+            fs::remove_file(old)?;
+            drop(fs::remove_file(old.with_extension("json")));
+        }
+        Ok(report)
+    }
+}
+
+impl<T> Named for CoverageDiffStage<T> {
+    fn name(&self) -> &Cow<'static, str> {
+        &self.name
+    }
+}
+
+impl<E, EM, S, T, Z> Stage<E, EM, S, Z> for CoverageDiffStage<T>
+where
+    EM: EventFirer<State = S>,
+    S: State + HasNamedMetadata,
+    T: PartialEq + Default + 'static,
+    MapFeedbackMetadata<T>: SerdeAny,
+{
+    fn perform(
+        &mut self,
+        _fuzzer: &mut Z,
+        _executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        let now = current_time();
+        let last_time = match self.last_time {
+            Some(last_time) => Some(last_time),
+            None => self
+                .snapshots()?
+                .last()
+                .map(|path| Self::read_snapshot(path))
+                .transpose()?
+                .map(|snapshot| snapshot.time),
+        };
+        if last_time.is_some_and(|last_time| now.saturating_sub(last_time) < self.interval) {
+            self.last_time = last_time;
+            return Ok(());
+        }
+        self.last_time = Some(now);
+
+        let report = self.snapshot(state)?;
+        match &report.diff {
+            CoverageDiff::First => Ok(()),
+            CoverageDiff::Incomparable { previous_len, len } => manager.log(
+                state,
+                LogSeverity::Warn,
+                format!(
+                    "Coverage of {} is incomparable to the previous snapshot, the map size changed from {previous_len} to {len}",
+                    self.name
+                ),
+            ),
+            CoverageDiff::New(new) => {
+                manager.log(
+                    state,
+                    LogSeverity::Info,
+                    format!(
+                        "{} newly covered indices of {} since the previous snapshot",
+                        new.len(),
+                        self.name
+                    ),
+                )?;
+                manager.fire(
+                    state,
+                    Event::UpdateUserStats {
+                        name: Cow::Borrowed(NEW_COVERAGE_STAT),
+                        value: UserStats::new(
+                            UserStatsValue::Number(new.len() as u64),
+                            AggregatorOps::Sum,
+                        ),
+                        phantom: PhantomData,
+                    },
+                )
+            }
+        }
+    }
+
+    #[inline]
+    fn should_restart(&mut self, _state: &mut S) -> Result<bool, Error> {
+        // Not executing the target, so restart safety is not needed
+        Ok(true)
+    }
+
+    #[inline]
+    fn clear_progress(&mut self, _state: &mut S) -> Result<(), Error> {
+        // Not executing the target, so restart safety is not needed
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{string::ToString, vec::Vec};
+    use core::time::Duration;
+    use std::{fs, process};
+
+    use hashbrown::HashMap;
+    use libafl_bolts::tuples::Handled;
+
+    use crate::{
+        events::NopEventManager,
+        feedbacks::MapFeedbackMetadata,
+        inputs::BytesInput,
+        observers::StdMapObserver,
+        stages::{
+            CoverageDiff, CoverageDiffReport, CoverageDiffStage, Stage, COVERAGE_SNAPSHOT_EXTENSION,
+        },
+        state::StdState,
+        HasNamedMetadata,
+    };
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_coverage_diff() {
+        let dir = std::env::temp_dir().join(format!("libafl_coverage_diff_{}", process::id()));
+        drop(fs::remove_dir_all(&dir));
+
+        let observer = StdMapObserver::owned("edges", vec![0_u8; 4]).handle();
+        let locations = [(1, "a.c:1"), (100, "b.c:2")]
+            .into_iter()
+            .map(|(idx, location)| (idx, location.to_string()))
+            .collect::<HashMap<_, _>>();
+        let mut stage = CoverageDiffStage::new(&observer, &dir, Duration::ZERO)
+            .unwrap()
+            .keep(2)
+            .locations(locations);
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        let mut manager = NopEventManager::new();
+        let cover = |state: &mut StdState<_, _, _, _>, history: Vec<u8>| {
+            state.add_named_metadata("edges", MapFeedbackMetadata::with_history_map(history, 0));
+        };
+
+        cover(&mut state, vec![0; 130]);
+        stage
+            .perform(&mut (), &mut (), &mut state, &mut manager)
+            .unwrap();
+        cover(&mut state, vec![0; 130]);
+        state
+            .named_metadata_mut::<MapFeedbackMetadata<u8>>("edges")
+            .unwrap()
+            .history_map[1] = 1;
+        let first = stage.snapshot(&state).unwrap();
+        assert_eq!(first.diff, CoverageDiff::New(vec![1]));
+        let mut history = vec![0; 130];
+        for idx in [1, 2, 100, 129] {
+            history[idx] = 3;
+        }
+        cover(&mut state, history);
+        let report = stage.snapshot(&state).unwrap();
+        assert_eq!(report.diff, CoverageDiff::New(vec![2, 100, 129]));
+        assert_eq!(report.covered, 4);
+        assert_eq!(report.previous_time, Some(first.time));
+        assert_eq!(
+            report.locations,
+            Some(vec![None, Some("b.c:2".to_string()), None])
+        );
+
+        // Only the last two snapshots are kept, each with its report
+        let mut files = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        files.sort();
+        assert_eq!(files.len(), 4);
+        let snapshot = files
+            .iter()
+            .rfind(|path| path.extension().unwrap() == COVERAGE_SNAPSHOT_EXTENSION)
+            .unwrap();
+        let written: CoverageDiffReport =
+            serde_json::from_slice(&fs::read(snapshot.with_extension("json")).unwrap()).unwrap();
+        assert_eq!(written, report);
+
+        // A rebuilt target with a different map size can not be compared
+        cover(&mut state, vec![1; 64]);
+        let report = stage.snapshot(&state).unwrap();
+        assert_eq!(
+            report.diff,
+            CoverageDiff::Incomparable {
+                previous_len: 130,
+                len: 64
+            }
+        );
+        assert_eq!(report.locations, None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
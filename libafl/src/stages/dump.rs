@@ -14,12 +14,16 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     corpus::{Corpus, CorpusId, Testcase},
-    inputs::Input,
+    events::ProvenanceMetadata,
+    inputs::{GeneralizedInputMetadata, Input, GENERALIZED_TEXT_EXTENSION},
     stages::Stage,
     state::{HasCorpus, HasRand, HasSolutions},
     Error, HasMetadata,
 };
 
+/// The extension of the provenance sidecar files, see [`DumpToDiskStage::provenance_sidecars`]
+pub const PROVENANCE_EXTENSION: &str = "provenance";
+
 /// Metadata used to store information about disk dump indexes for names
 #[cfg_attr(
     any(not(feature = "serdeany_autoreg"), miri),
@@ -40,6 +44,8 @@ pub struct DumpToDiskStage<CB1, CB2, EM, S, Z> {
     corpus_dir: PathBuf,
     to_bytes: CB1,
     generate_filename: CB2,
+    generalized_sidecars: bool,
+    provenance_sidecars: bool,
     phantom: PhantomData<(EM, S, Z)>,
 }
 
@@ -158,10 +164,57 @@ where
             generate_filename,
             solutions_dir,
             corpus_dir,
+            generalized_sidecars: false,
+            provenance_sidecars: false,
             phantom: PhantomData,
         })
     }
 
+    /// Also write the text form of the [`GeneralizedInputMetadata`] of each testcase, if it has one,
+    /// next to the dumped file, with the extension [`GENERALIZED_TEXT_EXTENSION`].
+    ///
+    /// The text can be edited, and loaded again with [`crate::state::StdState::load_initial_generalized_inputs`].
+    #[must_use]
+    pub fn generalized_sidecars(mut self, generalized_sidecars: bool) -> Self {
+        self.generalized_sidecars = generalized_sidecars;
+        self
+    }
+
+    /// Also write the [`ProvenanceMetadata`] of each testcase imported from another client as JSON
+    /// next to the dumped file, with the extension [`PROVENANCE_EXTENSION`],
+    /// so finds can be attributed to the clients and nodes that made them offline.
+    ///
+    /// Testcases found by this client get no sidecar.
+    #[must_use]
+    pub fn provenance_sidecars(mut self, provenance_sidecars: bool) -> Self {
+        self.provenance_sidecars = provenance_sidecars;
+        self
+    }
+
+    /// Write the enabled sidecars of `testcase` next to `fname`, if available
+    fn write_sidecars<I>(&self, testcase: &Testcase<I>, fname: &Path) -> Result<(), Error> {
+        let sidecar = |extension| {
+            let mut sidecar = fname.as_os_str().to_os_string();
+            sidecar.push(".");
+            sidecar.push(extension);
+            sidecar
+        };
+        if self.generalized_sidecars {
+            if let Some(meta) = testcase.metadata_map().get::<GeneralizedInputMetadata>() {
+                meta.to_text_file(sidecar(GENERALIZED_TEXT_EXTENSION))?;
+            }
+        }
+        if self.provenance_sidecars {
+            if let Some(provenance) = ProvenanceMetadata::of(testcase) {
+                let json = serde_json::to_vec_pretty(provenance).map_err(|err| {
+                    Error::serialize(format!("Failed to json-ify provenance: {err:?}"))
+                })?;
+                fs::write(sidecar(PROVENANCE_EXTENSION), json)?;
+            }
+        }
+        Ok(())
+    }
+
     #[inline]
     fn dump_state_to_disk<P: AsRef<Path>>(&mut self, state: &mut S) -> Result<(), Error>
     where
@@ -186,8 +239,9 @@ where
             let fname = self
                 .corpus_dir
                 .join((self.generate_filename)(&testcase, &i));
-            let mut f = File::create(fname)?;
+            let mut f = File::create(&fname)?;
             drop(f.write_all(&bytes));
+            self.write_sidecars(&testcase, &fname)?;
 
             corpus_id = state.corpus().next(i);
         }
@@ -200,8 +254,9 @@ where
             let fname = self
                 .solutions_dir
                 .join((self.generate_filename)(&testcase, &i));
-            let mut f = File::create(fname)?;
+            let mut f = File::create(&fname)?;
             drop(f.write_all(&bytes));
+            self.write_sidecars(&testcase, &fname)?;
 
             solutions_id = state.solutions().next(i);
         }
@@ -214,3 +269,133 @@ where
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+    use core::time::Duration;
+    use std::{fs, path::PathBuf};
+
+    use libafl_bolts::{tuples::tuple_list, ClientId};
+
+    use crate::{
+        corpus::{Corpus, InMemoryCorpus, Testcase},
+        events::{NopEventManager, ProvenanceMetadata},
+        executors::{ExitKind, InProcessExecutor},
+        feedbacks::ConstFeedback,
+        inputs::{BytesInput, GeneralizedInputMetadata, GeneralizedItem},
+        schedulers::QueueScheduler,
+        stages::{DumpToDiskStage, Stage, PROVENANCE_EXTENSION},
+        state::HasCorpus,
+        testing::{bytes_state, bytes_state_with_corpus},
+        HasMetadata, StdFuzzer,
+    };
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_generalized_sidecars() {
+        let dir = PathBuf::from("target/.test/generalized_sidecars");
+        drop(fs::remove_dir_all(&dir));
+        fs::create_dir_all(&dir).unwrap();
+
+        let generalized =
+            GeneralizedInputMetadata::generalized_from_options(&[Some(b'a'), None, Some(b'\\')]);
+        let mut corpus = InMemoryCorpus::<BytesInput>::new();
+        let mut testcase = Testcase::new(BytesInput::from(generalized.generalized_to_bytes()));
+        testcase.add_metadata(generalized.clone());
+        corpus.add(testcase).unwrap();
+        let mut imported = Testcase::new(vec![1].into());
+        let provenance = ProvenanceMetadata {
+            client_id: ClientId(3),
+            generation: Some(1),
+            #[cfg(all(unix, feature = "multi_machine"))]
+            node_id: None,
+            received_time: Duration::from_secs(2),
+            original_found_time: Duration::from_secs(1),
+        };
+        imported.add_metadata(provenance);
+        corpus.add(imported).unwrap();
+
+        let mut feedback = ConstFeedback::new(false);
+        let mut objective = ConstFeedback::new(false);
+        let mut state = bytes_state_with_corpus(corpus, &mut feedback, &mut objective);
+        let mut event_manager = NopEventManager::new();
+        let mut fuzzer = StdFuzzer::new(
+            QueueScheduler::new(),
+            ConstFeedback::new(false),
+            ConstFeedback::new(false),
+        );
+        let mut harness = |_buf: &BytesInput| ExitKind::Ok;
+        let mut executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(),
+            &mut fuzzer,
+            &mut state,
+            &mut event_manager,
+        )
+        .unwrap();
+
+        let mut dump = DumpToDiskStage::new(
+            |testcase: &Testcase<BytesInput>, _state: &_| {
+                testcase.input().as_ref().unwrap().as_ref().clone()
+            },
+            dir.join("queue"),
+            dir.join("crashes"),
+        )
+        .unwrap()
+        .generalized_sidecars(true)
+        .provenance_sidecars(true);
+        dump.perform(&mut fuzzer, &mut executor, &mut state, &mut event_manager)
+            .unwrap();
+
+        // Only the imported testcase got a provenance sidecar
+        let provenances = fs::read_dir(dir.join("queue"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext == PROVENANCE_EXTENSION)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(provenances.len(), 1);
+        let loaded: ProvenanceMetadata =
+            serde_json::from_slice(&fs::read(&provenances[0]).unwrap()).unwrap();
+        assert_eq!(loaded, provenance);
+
+        // Only the generalized testcase got a sidecar
+        let sidecars = fs::read_dir(dir.join("queue"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "generalized"))
+            .collect::<Vec<_>>();
+        assert_eq!(sidecars.len(), 1);
+        let text = fs::read_to_string(&sidecars[0]).unwrap();
+        assert_eq!(text, generalized.to_text());
+
+        // Load the edited form into a fresh state
+        fs::write(&sidecars[0], text.replace('a', "b\n")).unwrap();
+        let mut feedback = ConstFeedback::new(false);
+        let mut objective = ConstFeedback::new(false);
+        let mut state = bytes_state(&mut feedback, &mut objective);
+        state
+            .load_initial_generalized_inputs(
+                &mut fuzzer,
+                &mut executor,
+                &mut event_manager,
+                &[dir.join("queue")],
+            )
+            .unwrap();
+        assert_eq!(state.corpus().count(), 1);
+        let id = state.corpus().first().unwrap();
+        let testcase = state.corpus().get(id).unwrap().borrow();
+        assert_eq!(testcase.input().as_ref().unwrap().as_ref(), b"b\\");
+        let loaded = testcase
+            .metadata::<GeneralizedInputMetadata>()
+            .unwrap()
+            .generalized();
+        assert_eq!(loaded.len(), generalized.generalized().len());
+        assert_eq!(loaded[1], GeneralizedItem::Bytes(b"b".to_vec()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
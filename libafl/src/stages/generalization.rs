@@ -24,7 +24,7 @@ use crate::{
     stages::{RetryCountRestartHelper, Stage},
     start_timer,
     state::{HasCorpus, HasExecutions, MaybeHasClientPerfMonitor, UsesState},
-    Error, HasMetadata, HasNamedMetadata,
+    Error, HasInputFilter, HasMetadata, HasNamedMetadata,
 };
 
 const MAX_GENERALIZED_LEN: usize = 8192;
@@ -76,6 +76,7 @@ where
         + UsesInput<Input = BytesInput>,
     S::Corpus: Corpus<Input = BytesInput>,
     EM: UsesState<State = S>,
+    Z: HasInputFilter<BytesInput, S>,
 {
     #[inline]
     #[allow(clippy::too_many_lines)]
@@ -378,7 +379,13 @@ where
     where
         E: Executor<EM, Z, State = S> + HasObservers,
         E::Observers: ObserversTuple<BytesInput, S>,
+        Z: HasInputFilter<BytesInput, S>,
     {
+        // A rejected input cannot be shown to keep the novelties
+        if fuzzer.skips_input(state, input) {
+            return Ok(false);
+        }
+
         start_timer!(state);
         executor.observers_mut().pre_exec_all(state, input)?;
         mark_feature_time!(state, PerfFeature::PreExecObservers);
@@ -419,6 +426,7 @@ where
     ) -> Result<(), Error>
     where
         E: Executor<EM, Z, State = S> + HasObservers<Observers = OT>,
+        Z: HasInputFilter<BytesInput, S>,
     {
         let mut start = 0;
         while start < payload.len() {
@@ -457,6 +465,7 @@ where
     ) -> Result<(), Error>
     where
         E: Executor<EM, Z, State = S> + HasObservers<Observers = OT>,
+        Z: HasInputFilter<BytesInput, S>,
     {
         let mut index = 0;
         while index < payload.len() {
@@ -12,14 +12,22 @@ use alloc::{
 };
 use core::{fmt, marker::PhantomData};
 
+#[cfg(feature = "std")]
+pub use afl_import::AflQueueImporter;
+pub use afl_import::{AflFuzzerStats, AflQueueEntryMetadata};
 #[cfg(feature = "std")]
 pub use afl_stats::{AflStatsStage, CalibrationTime, FuzzTime, SyncTime};
-pub use calibrate::CalibrationStage;
+pub use calibrate::{CalibrationHint, CalibrationStage};
+pub use centralized_metrics::*;
+#[cfg(feature = "std")]
+pub use checkpoint::{CheckpointMetadata, CheckpointStage};
 pub use colorization::*;
 #[cfg(all(feature = "std", unix))]
 pub use concolic::ConcolicTracingStage;
 #[cfg(all(feature = "std", feature = "concolic_mutation", unix))]
 pub use concolic::SimpleConcolicMutationalStage;
+#[cfg(all(feature = "std", feature = "gzip"))]
+pub use coverage_diff::*;
 #[cfg(feature = "std")]
 pub use dump::*;
 pub use generalization::GeneralizationStage;
@@ -32,7 +40,12 @@ use libafl_bolts::{
 pub use logics::*;
 pub use mutational::{MutationalStage, StdMutationalStage};
 pub use power::{PowerMutationalStage, StdPowerMutationalStage};
+pub use prune::*;
+pub use prune_solutions::*;
+#[cfg(feature = "std")]
+pub use restart::{ReattachableEventManager, RestartStage, RESTART_STAGE_ENV};
 use serde::{Deserialize, Serialize};
+pub use shuffle::CorpusShuffle;
 #[cfg(feature = "std")]
 pub use sync::*;
 #[cfg(feature = "std")]
@@ -58,18 +71,29 @@ pub mod mutational;
 pub mod push;
 pub mod tmin;
 
+pub mod afl_import;
 #[cfg(feature = "std")]
 pub mod afl_stats;
 pub mod calibrate;
+pub mod centralized_metrics;
+#[cfg(feature = "std")]
+pub mod checkpoint;
 pub mod colorization;
 #[cfg(all(feature = "std", unix))]
 pub mod concolic;
+#[cfg(all(feature = "std", feature = "gzip"))]
+pub mod coverage_diff;
 #[cfg(feature = "std")]
 pub mod dump;
 pub mod generalization;
 pub mod generation;
 pub mod logics;
 pub mod power;
+pub mod prune;
+pub mod prune_solutions;
+#[cfg(feature = "std")]
+pub mod restart;
+pub mod shuffle;
 #[cfg(feature = "std")]
 pub mod sync;
 #[cfg(feature = "std")]
@@ -203,8 +227,7 @@ where
         }
 
         if state.stop_requested() {
-            state.discard_stop_request();
-            manager.on_shutdown()?;
+            // The request stays pending, the fuzzer shuts down gracefully
             return Err(Error::shutting_down());
         }
 
@@ -265,8 +288,7 @@ where
     ) -> Result<(), Error> {
         self.iter_mut().try_for_each(|x| {
             if state.stop_requested() {
-                state.discard_stop_request();
-                manager.on_shutdown()?;
+                // The request stays pending, the fuzzer shuts down gracefully
                 return Err(Error::shutting_down());
             }
             x.perform_restartable(fuzzer, executor, state, manager)
@@ -0,0 +1,2709 @@
+//! The [`CorpusPruning`] stage disables entries of the corpus, to keep it small and focused.
+//!
+//! Disabled entries are kept around in the [`Corpus`], but schedulers won't pick them anymore.
+//! With [`CorpusPruning::include_disabled`], the stage also permanently removes entries that were disabled before.
+//! With [`CorpusPruning::pareto`], entries that are best in some trade-off of several metrics are never disabled.
+//! With [`CorpusPruning::keep_unique_coverage`], entries that are the only ones covering an edge are never disabled.
+//! With [`CorpusPruning::byte_budget`], entries are disabled until the enabled inputs fit into a number of bytes.
+//! With [`CorpusPruning::reservoir`], the enabled entries are a fixed-size random sample of all entries ever added.
+//! With [`CorpusPruning::by_distance`], entries far from the target of a directed fuzzer are disabled more often.
+//! With [`CorpusPruning::diverse`], every cluster of similar entries keeps at least one representative.
+//! With [`CorpusPruning::grace_period`], entries are only disabled once they had some time to prove their value.
+//! With [`CorpusPruning::respect_minimizer`], the entries a [`crate::schedulers::MinimizerScheduler`] rated best are never disabled.
+//! With [`CorpusPruning::debug_assertions`], the stage checks its post-conditions after every run.
+//! [`CorpusPruning::compare_strategies`] reports what each [`PruningStrategy`] would disable, without disabling anything.
+//! [`CorpusPruning::estimate_coverage_loss`] reports how many edges a run would lose, without disabling anything.
+//! [`TwoPhasePruning`] marks the entries to disable first, and only holds the [`CorpusQuiesceGuard`] to sweep them.
+//! A stop request, see [`Stoppable`], interrupts a run before it changes the corpus; the next run finishes it.
+//!
+//! Solutions are deduplicated by their crash signature instead, see [`crate::stages::SolutionPruning`].
+
+use alloc::{borrow::Cow, vec::Vec};
+use core::{cmp::Ordering, time::Duration};
+
+use hashbrown::{HashMap, HashSet};
+use libafl_bolts::{current_time, rands::Rand, tuples::Handle};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "scalability_introspection")]
+use crate::state::HasScalabilityMonitor;
+use crate::{
+    corpus::{AddedAtMetadata, Corpus, CorpusId, Testcase},
+    feedbacks::MapIndexesMetadata,
+    inputs::Input,
+    schedulers::minimizer::TopRatedsMetadata,
+    stages::Stage,
+    state::{HasCorpus, HasRand, MaybeHasScalabilityMonitor, Stoppable},
+    Error, HasMetadata,
+};
+
+/// The default probability for [`CorpusPruning`] to disable an entry
+pub const DEFAULT_PRUNING_PROB: f64 = 0.05;
+
+/// A barrier that keeps stages from changing the [`Corpus`] while another stage reorganizes it.
+///
+/// [`CorpusPruning`] holds it for the duration of its `perform`. Stages that add, replace, or remove
+/// corpus entries, such as the test case minimization and the sync stages,
+/// check it and skip their run without touching the corpus while it is held.
+/// The guard lives in the state metadata, so it is visible to every stage of the tuple.
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct CorpusQuiesceGuard {
+    held: bool,
+}
+
+libafl_bolts::impl_serdeany!(CorpusQuiesceGuard);
+
+impl CorpusQuiesceGuard {
+    /// Acquire the guard. Fails if it is already held.
+    pub fn acquire<S>(state: &mut S) -> Result<(), Error>
+    where
+        S: HasMetadata,
+    {
+        let guard = state.metadata_or_insert_with(Self::default);
+        if guard.held {
+            return Err(Error::illegal_state("The corpus is already quiesced"));
+        }
+        guard.held = true;
+        Ok(())
+    }
+
+    /// Release the guard, if it is held
+    pub fn release<S>(state: &mut S)
+    where
+        S: HasMetadata,
+    {
+        if let Ok(guard) = state.metadata_mut::<Self>() {
+            guard.held = false;
+        }
+    }
+
+    /// Checks if the guard is currently held, i.e., if the corpus must not be changed
+    #[must_use]
+    pub fn is_held<S>(state: &S) -> bool
+    where
+        S: HasMetadata,
+    {
+        state.metadata::<Self>().is_ok_and(|guard| guard.held)
+    }
+}
+
+/// How [`CorpusPruning`] decides which entries to disable
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PruningStrategy {
+    /// Every enabled entry is disabled with the same probability
+    Uniform,
+    /// Older entries are disabled more often than newer ones.
+    ///
+    /// The age of an entry is the number of enabled entries that were added after it.
+    /// The disable probability of an entry with age `age` is
+    /// `prob * (1 - 2^(-age / half_life))`: the newest entry is never disabled,
+    /// an entry `half_life` entries old is disabled with half the probability, and very old
+    /// entries approach the full probability.
+    AgeWeighted {
+        /// The age (in entries) at which the disable probability reaches half of `prob`
+        half_life: f64,
+    },
+    /// Entries on the Pareto front of the [`ParetoMetrics`] are never disabled,
+    /// all dominated entries are disabled with the same probability.
+    ///
+    /// An entry dominates another one if it is at least as good in every metric, and better in at least one.
+    Pareto,
+    /// Entries are disabled until the enabled inputs take at most `max_bytes` in total,
+    /// the entries with the lowest [`ParetoMetrics`] first, compared in order, ties in random order.
+    ///
+    /// The size of an entry is the size of its serialized input, as stored on disk.
+    /// Entries kept by [`CorpusPruning::keep_unique_coverage`], or the last enabled entry,
+    /// stay enabled even if that exceeds the budget.
+    ByteBudget {
+        /// The maximum total size of the enabled inputs
+        max_bytes: usize,
+    },
+    /// The enabled entries are a uniform random sample of at most `size` of all entries added so far,
+    /// maintained by reservoir sampling.
+    ///
+    /// Each run only looks at the entries added since the last run, see [`ReservoirMetadata`]:
+    /// the `n`th entry ever seen joins the sample with probability `size / n`,
+    /// evicting a random member, otherwise it is disabled right away.
+    Reservoir {
+        /// The number of entries to keep enabled
+        size: usize,
+    },
+    /// Entries far from the target are disabled more often than close ones,
+    /// according to their [`TargetDistanceMetadata`], see [`CorpusPruning::by_distance`].
+    ///
+    /// The disable probability of an entry is `prob * (distance - min) / (max - min)`, where `min` and `max`
+    /// are the smallest and the largest distance among the enabled entries: the closest entries are never disabled,
+    /// the farthest ones with the full probability. Entries without a finite distance count as the farthest.
+    /// If all entries are equally far, each is disabled with the full probability.
+    ByDistance,
+    /// The enabled entries are grouped into `clusters` clusters by their [`DiversityFeatures`],
+    /// using k-means, and each cluster is thinned out on its own, see [`CorpusPruning::diverse`].
+    ///
+    /// Every entry is disabled with the same probability, but each cluster keeps at least one random entry,
+    /// so no region of the feature space is lost. Entries with fewer features count as `0` in the missing ones.
+    Diverse {
+        /// The number of clusters to keep entries of
+        clusters: usize,
+    },
+}
+
+/// The distances of a testcase to the targets of a directed fuzzer, populated by a directed feedback,
+/// and used by [`PruningStrategy::ByDistance`].
+///
+/// Each distance is stored under the name of the metric that measured it. Lower is closer.
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TargetDistanceMetadata {
+    distances: HashMap<Cow<'static, str>, f64>,
+}
+
+libafl_bolts::impl_serdeany!(TargetDistanceMetadata);
+
+impl TargetDistanceMetadata {
+    /// Create a new, empty [`TargetDistanceMetadata`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the distance measured by the metric called `name`
+    pub fn set_distance(&mut self, name: Cow<'static, str>, distance: f64) {
+        self.distances.insert(name, distance);
+    }
+
+    /// The distance measured by the metric called `name`, if any
+    #[must_use]
+    pub fn distance(&self, name: &str) -> Option<f64> {
+        self.distances.get(name).copied()
+    }
+}
+
+/// The progress of [`PruningStrategy::Reservoir`], kept in the state so the sample survives restarts
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ReservoirMetadata {
+    /// The number of entries that were offered to the sample
+    pub seen: usize,
+    /// The newest entry that was offered to the sample, newer enabled entries are offered in the next run
+    pub last_seen: Option<CorpusId>,
+    /// The entries in the sample
+    pub sample: Vec<CorpusId>,
+    /// The entries that were always kept when they were due, e.g., in their grace period, offered once they are not
+    #[serde(default)]
+    pub deferred: Vec<CorpusId>,
+}
+
+libafl_bolts::impl_serdeany!(ReservoirMetadata);
+
+/// The serialized size of the input of a testcase, for [`PruningStrategy::ByteBudget`].
+///
+/// Added by the first run of the strategy that sees a testcase, so later runs need not load the input again.
+/// Analyses such as [`CorpusPruning::compare_strategies`] read it, but never add it.
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputSizeMetadata {
+    /// The size of the serialized input, in bytes
+    pub bytes: usize,
+}
+
+libafl_bolts::impl_serdeany!(InputSizeMetadata);
+
+/// Per-testcase metrics for [`PruningStrategy::Pareto`], see [`CorpusPruning::pareto`],
+/// or the value of a testcase for [`PruningStrategy::ByteBudget`], see [`CorpusPruning::byte_budget_by`].
+///
+/// Higher values are better, so negate metrics that should be small, such as the input size.
+pub trait ParetoMetrics<I> {
+    /// Measure all metrics of the given testcase
+    fn measure(&self, testcase: &Testcase<I>) -> Vec<f64>;
+}
+
+impl<I> ParetoMetrics<I> for () {
+    fn measure(&self, _testcase: &Testcase<I>) -> Vec<f64> {
+        Vec::new()
+    }
+}
+
+impl<I, M> ParetoMetrics<I> for &M
+where
+    M: ParetoMetrics<I>,
+{
+    fn measure(&self, testcase: &Testcase<I>) -> Vec<f64> {
+        (*self).measure(testcase)
+    }
+}
+
+impl<F, I> ParetoMetrics<I> for Vec<F>
+where
+    F: Fn(&Testcase<I>) -> f64,
+{
+    fn measure(&self, testcase: &Testcase<I>) -> Vec<f64> {
+        self.iter().map(|metric| metric(testcase)).collect()
+    }
+}
+
+/// The feature vector of a testcase for [`PruningStrategy::Diverse`], see [`CorpusPruning::diverse`].
+///
+/// Entries with similar features end up in the same cluster. Features should be of a similar scale,
+/// since the clustering measures euclidean distances.
+pub trait DiversityFeatures<I> {
+    /// Extract the features of the given testcase
+    fn features(&self, testcase: &Testcase<I>) -> Vec<f64>;
+}
+
+impl<I> DiversityFeatures<I> for () {
+    fn features(&self, _testcase: &Testcase<I>) -> Vec<f64> {
+        Vec::new()
+    }
+}
+
+impl<F, I> DiversityFeatures<I> for F
+where
+    F: Fn(&Testcase<I>) -> Vec<f64>,
+{
+    fn features(&self, testcase: &Testcase<I>) -> Vec<f64> {
+        self(testcase)
+    }
+}
+
+/// Borrowed [`DiversityFeatures`], e.g., of a [`CorpusPruning`] that compares strategies
+#[derive(Debug)]
+struct FeaturesRef<'a, D>(&'a D);
+
+impl<D, I> DiversityFeatures<I> for FeaturesRef<'_, D>
+where
+    D: DiversityFeatures<I>,
+{
+    fn features(&self, testcase: &Testcase<I>) -> Vec<f64> {
+        self.0.features(testcase)
+    }
+}
+
+/// Checks if `a` dominates `b`, i.e., is at least as good in every metric, and better in at least one
+fn dominates(a: &[f64], b: &[f64]) -> bool {
+    a.iter().zip(b).all(|(a, b)| a >= b) && a.iter().zip(b).any(|(a, b)| a > b)
+}
+
+/// For each of the `points`, whether no other point dominates it
+fn pareto_front(points: &[Vec<f64>]) -> Vec<bool> {
+    points
+        .iter()
+        .map(|point| !points.iter().any(|other| dominates(other, point)))
+        .collect()
+}
+
+/// The squared euclidean distance between two feature vectors, missing features count as `0`
+fn squared_distance(a: &[f64], b: &[f64]) -> f64 {
+    let len = a.len().max(b.len());
+    (0..len)
+        .map(|nth| {
+            let diff = a.get(nth).copied().unwrap_or(0.0) - b.get(nth).copied().unwrap_or(0.0);
+            diff * diff
+        })
+        .sum()
+}
+
+/// The number of k-means refinements after the initial assignment
+const MAX_CLUSTERING_ROUNDS: usize = 16;
+
+/// Group the `points` into at most `k` clusters, returning the cluster of each point.
+///
+/// The centers start out as a random point and then, greedily, the point farthest from all centers so far.
+/// The clusters are then refined by k-means until no point changes its cluster.
+/// Each step takes `O(n * k)` distance computations for `n` points.
+#[allow(clippy::cast_precision_loss)]
+fn cluster<R>(rand: &mut R, points: &[Vec<f64>], k: usize) -> Vec<usize>
+where
+    R: Rand,
+{
+    if points.is_empty() {
+        return Vec::new();
+    }
+    let nearest = |centers: &[Vec<f64>], point: &[f64]| {
+        centers
+            .iter()
+            .map(|center| squared_distance(center, point))
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+            .map_or((0, 0.0), |(nth, distance)| (nth, distance))
+    };
+
+    let first = rand.below(points.len().try_into().unwrap());
+    let mut centers = vec![points[first].clone()];
+    // The distance of each point to its nearest center so far
+    let mut distances = points
+        .iter()
+        .map(|point| squared_distance(&centers[0], point))
+        .collect::<Vec<_>>();
+    while centers.len() < k {
+        let (farthest, distance) = distances
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+            .unwrap();
+        if distance <= 0.0 {
+            // All remaining points coincide with a center
+            break;
+        }
+        let center = points[farthest].clone();
+        for (point, distance) in points.iter().zip(&mut distances) {
+            *distance = distance.min(squared_distance(&center, point));
+        }
+        centers.push(center);
+    }
+
+    let mut assignment = points
+        .iter()
+        .map(|point| nearest(&centers, point).0)
+        .collect::<Vec<_>>();
+    let dims = points.iter().map(Vec::len).max().unwrap_or(0);
+    for _ in 0..MAX_CLUSTERING_ROUNDS {
+        let mut sums = vec![vec![0.0; dims]; centers.len()];
+        let mut counts = vec![0_usize; centers.len()];
+        for (point, cluster) in points.iter().zip(&assignment) {
+            counts[*cluster] += 1;
+            for (sum, feature) in sums[*cluster].iter_mut().zip(point) {
+                *sum += feature;
+            }
+        }
+        for ((center, sum), count) in centers.iter_mut().zip(sums).zip(counts) {
+            // A center without points keeps its position
+            if count > 0 {
+                *center = sum.into_iter().map(|sum| sum / count as f64).collect();
+            }
+        }
+
+        let next = points
+            .iter()
+            .map(|point| nearest(&centers, point).0)
+            .collect::<Vec<_>>();
+        if next == assignment {
+            break;
+        }
+        assignment = next;
+    }
+    assignment
+}
+
+/// What a [`PruningStrategy`] would disable, see [`CorpusPruning::compare_strategies`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrategyOutcome {
+    /// The strategy
+    pub strategy: PruningStrategy,
+    /// The number of enabled entries it would keep
+    pub retained: usize,
+    /// The enabled entries it would disable, in insertion order
+    pub disabled: Vec<CorpusId>,
+}
+
+/// The outcomes of several [`PruningStrategy`]s on the same corpus, see [`CorpusPruning::compare_strategies`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrategyComparison {
+    outcomes: Vec<StrategyOutcome>,
+}
+
+impl StrategyComparison {
+    /// The outcome of each compared strategy, in the order they were given
+    #[must_use]
+    pub fn outcomes(&self) -> &[StrategyOutcome] {
+        &self.outcomes
+    }
+
+    /// The number of entries both the `a`th and the `b`th strategy would disable
+    ///
+    /// # Panics
+    /// Panics if `a` or `b` is out of bounds
+    #[must_use]
+    pub fn overlap(&self, a: usize, b: usize) -> usize {
+        let other = &self.outcomes[b].disabled;
+        self.outcomes[a]
+            .disabled
+            .iter()
+            .filter(|id| other.contains(id))
+            .count()
+    }
+}
+
+/// A [`Stage`] that randomly disables enabled entries of the [`Corpus`].
+///
+/// At least one entry is always kept enabled.
+/// The chosen entries are removed and disabled in one go. If a stop is requested before, the run leaves
+/// the corpus as it is, and keeps its decisions as [`PruningMarksMetadata`], for the next run to sweep first.
+/// `M` are the [`ParetoMetrics`] for [`PruningStrategy::Pareto`], if any,
+/// and `D` the [`DiversityFeatures`] for [`PruningStrategy::Diverse`], if any.
+#[derive(Debug, Clone)]
+pub struct CorpusPruning<M = (), D = ()> {
+    /// The (maximum) probability of disabling a corpus entry
+    prob: f64,
+    /// How to weigh the probability for each entry
+    strategy: PruningStrategy,
+    /// Also permanently remove already disabled entries
+    include_disabled: bool,
+    /// The metrics spanning the Pareto front
+    metrics: M,
+    /// The features to cluster the entries by
+    features: D,
+    /// The name of the map observer whose edges must stay covered, see [`CorpusPruning::keep_unique_coverage`]
+    unique_coverage: Option<Cow<'static, str>>,
+    /// Check the post-conditions after each run, see [`CorpusPruning::debug_assertions`]
+    debug_assertions: bool,
+    /// The name of the distance metric, see [`CorpusPruning::by_distance`]
+    distance_metric: Option<Cow<'static, str>>,
+    /// How long new entries are never disabled, see [`CorpusPruning::grace_period`]
+    grace_period: Option<Duration>,
+    /// Keep the top-rated entries of the minimizer, see [`CorpusPruning::respect_minimizer`]
+    respect_minimizer: bool,
+}
+
+/// The corpus before a run of [`CorpusPruning`], to check the post-conditions against
+#[derive(Debug)]
+struct PruningSnapshot {
+    /// The number of enabled entries
+    enabled: usize,
+    /// The number of disabled entries
+    disabled: usize,
+    /// The entries that must stay enabled
+    protected: Vec<CorpusId>,
+    /// The top-rated entries of the minimizer, which must stay enabled as well
+    top_rated: Vec<CorpusId>,
+    /// The edges covered by the enabled entries, if coverage must be preserved
+    covered: Option<HashSet<usize>>,
+}
+
+impl CorpusPruning {
+    fn new(prob: f64, strategy: PruningStrategy) -> Self {
+        Self {
+            prob,
+            strategy,
+            include_disabled: false,
+            metrics: (),
+            features: (),
+            unique_coverage: None,
+            debug_assertions: false,
+            distance_metric: None,
+            grace_period: None,
+            respect_minimizer: false,
+        }
+    }
+
+    /// Create a new [`CorpusPruning`] that disables every enabled entry with probability `prob`.
+    ///
+    /// Returns an error if `prob` is not within `[0, 1]`.
+    pub fn try_new(prob: f64) -> Result<Self, Error> {
+        if !(0.0..=1.0).contains(&prob) {
+            log::warn!("Refusing to create CorpusPruning with probability {prob}");
+            return Err(Error::illegal_argument(format!(
+                "The pruning probability must be within [0, 1], got {prob}"
+            )));
+        }
+        Ok(Self::new(prob, PruningStrategy::Uniform))
+    }
+
+    /// Create a new [`CorpusPruning`] that never disables entries on the Pareto front of the given `metrics`,
+    /// see [`PruningStrategy::Pareto`].
+    ///
+    /// The dominated entries are disabled with probability [`DEFAULT_PRUNING_PROB`].
+    #[must_use]
+    pub fn pareto<M>(metrics: M) -> CorpusPruning<M> {
+        CorpusPruning {
+            prob: DEFAULT_PRUNING_PROB,
+            strategy: PruningStrategy::Pareto,
+            include_disabled: false,
+            metrics,
+            features: (),
+            unique_coverage: None,
+            debug_assertions: false,
+            distance_metric: None,
+            grace_period: None,
+            respect_minimizer: false,
+        }
+    }
+
+    /// Create a new [`CorpusPruning`] that prefers disabling old entries, see [`PruningStrategy::AgeWeighted`].
+    ///
+    /// The maximum disable probability is [`DEFAULT_PRUNING_PROB`].
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn age_weighted(half_life: usize) -> Self {
+        Self::new(
+            DEFAULT_PRUNING_PROB,
+            PruningStrategy::AgeWeighted {
+                half_life: half_life.max(1) as f64,
+            },
+        )
+    }
+
+    /// Create a new [`CorpusPruning`] that disables random entries until the enabled inputs take
+    /// at most `max_bytes`, see [`PruningStrategy::ByteBudget`].
+    ///
+    /// With [`CorpusPruning::include_disabled`], disabled entries are removed with probability [`DEFAULT_PRUNING_PROB`].
+    #[must_use]
+    pub fn byte_budget(max_bytes: usize) -> Self {
+        Self::new(
+            DEFAULT_PRUNING_PROB,
+            PruningStrategy::ByteBudget { max_bytes },
+        )
+    }
+
+    /// Like [`CorpusPruning::byte_budget`], but disables the entries with the lowest `value` first.
+    #[must_use]
+    pub fn byte_budget_by<M>(max_bytes: usize, value: M) -> CorpusPruning<M> {
+        CorpusPruning {
+            prob: DEFAULT_PRUNING_PROB,
+            strategy: PruningStrategy::ByteBudget { max_bytes },
+            include_disabled: false,
+            metrics: value,
+            features: (),
+            unique_coverage: None,
+            debug_assertions: false,
+            distance_metric: None,
+            grace_period: None,
+            respect_minimizer: false,
+        }
+    }
+
+    /// Create a new [`CorpusPruning`] that keeps a random sample of `size` entries enabled,
+    /// see [`PruningStrategy::Reservoir`].
+    ///
+    /// With [`CorpusPruning::include_disabled`], disabled entries are removed with probability [`DEFAULT_PRUNING_PROB`].
+    #[must_use]
+    pub fn reservoir(size: usize) -> Self {
+        Self::new(
+            DEFAULT_PRUNING_PROB,
+            PruningStrategy::Reservoir { size: size.max(1) },
+        )
+    }
+
+    /// Create a new [`CorpusPruning`] that prefers disabling entries far from the target,
+    /// as measured by the distance metric named `metric`, see [`PruningStrategy::ByDistance`].
+    ///
+    /// The maximum disable probability is [`DEFAULT_PRUNING_PROB`].
+    #[must_use]
+    pub fn by_distance(metric: Cow<'static, str>) -> Self {
+        let mut pruning = Self::new(DEFAULT_PRUNING_PROB, PruningStrategy::ByDistance);
+        pruning.distance_metric = Some(metric);
+        pruning
+    }
+
+    /// Create a new [`CorpusPruning`] that groups the enabled entries into `clusters` clusters by the
+    /// feature vectors `features` extracts, and keeps at least one entry of each, see [`PruningStrategy::Diverse`].
+    ///
+    /// The other entries are disabled with probability [`DEFAULT_PRUNING_PROB`].
+    #[must_use]
+    pub fn diverse<D>(features: D, clusters: usize) -> CorpusPruning<(), D> {
+        CorpusPruning {
+            prob: DEFAULT_PRUNING_PROB,
+            strategy: PruningStrategy::Diverse {
+                clusters: clusters.max(1),
+            },
+            include_disabled: false,
+            metrics: (),
+            features,
+            unique_coverage: None,
+            debug_assertions: false,
+            distance_metric: None,
+            grace_period: None,
+            respect_minimizer: false,
+        }
+    }
+}
+
+impl<M, D> CorpusPruning<M, D> {
+    /// Also consider the disabled entries of the [`Corpus`]: each of them is permanently removed
+    /// with the probability an enabled entry of the same age would be disabled with.
+    /// Here, the age is the number of disabled entries added after it.
+    ///
+    /// Off by default.
+    #[must_use]
+    pub fn include_disabled(mut self, include_disabled: bool) -> Self {
+        self.include_disabled = include_disabled;
+        self
+    }
+
+    /// Never disable an entry that is the only enabled entry covering some edge of the map observer of `observer_handle`,
+    /// regardless of the probability roll.
+    ///
+    /// The edges of each entry are taken from its [`MapIndexesMetadata`], so the observer has to
+    /// track indices, see [`crate::observers::CanTrack::track_indices`]. The metadata is not kept per observer,
+    /// so no other map observer may track indices; the handle only names the observer in errors.
+    #[must_use]
+    pub fn keep_unique_coverage<C>(mut self, observer_handle: &Handle<C>) -> Self {
+        self.unique_coverage = Some(observer_handle.name().clone());
+        self
+    }
+
+    /// Check, after each run, that the entries were moved between the enabled and the disabled entries as decided,
+    /// that at least one entry is still enabled, that no entry on the Pareto front was disabled,
+    /// and, with [`CorpusPruning::keep_unique_coverage`], that the enabled entries still cover the same edges.
+    ///
+    /// A violation is returned as an [`Error::IllegalState`].
+    /// The checks measure the whole corpus twice per run, so they are off by default.
+    #[must_use]
+    pub fn debug_assertions(mut self, debug_assertions: bool) -> Self {
+        self.debug_assertions = debug_assertions;
+        self
+    }
+
+    /// Never disable an entry while it is younger than the `grace_period`, so it gets a chance to prove its value first.
+    ///
+    /// The age of an entry is taken from its [`AddedAtMetadata`], entries without one are not affected.
+    #[must_use]
+    pub fn grace_period(mut self, grace_period: Duration) -> Self {
+        self.grace_period = Some(grace_period);
+        self
+    }
+
+    /// Never disable, or remove, an entry a [`crate::schedulers::MinimizerScheduler`] rated best for some feature,
+    /// i.e., any entry in its [`TopRatedsMetadata`], so the minimized corpus stays intact.
+    ///
+    /// Unlike keeping favored entries, this covers every entry of the top-rated map, not only those picked
+    /// in the last culling. Off by default.
+    #[must_use]
+    pub fn respect_minimizer(mut self, respect_minimizer: bool) -> Self {
+        self.respect_minimizer = respect_minimizer;
+        self
+    }
+
+    /// If this stage also removes disabled entries, see [`CorpusPruning::include_disabled`]
+    #[must_use]
+    pub fn includes_disabled(&self) -> bool {
+        self.include_disabled
+    }
+
+    /// The (maximum) probability of disabling an entry
+    #[must_use]
+    pub fn prob(&self) -> f64 {
+        self.prob
+    }
+
+    /// The [`PruningStrategy`] used by this stage
+    #[must_use]
+    pub fn strategy(&self) -> &PruningStrategy {
+        &self.strategy
+    }
+
+    /// The probability to disable an entry with the given `age`, i.e., the number of entries added after it.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn disable_prob(&self, age: usize) -> f64 {
+        match self.strategy {
+            PruningStrategy::Uniform
+            | PruningStrategy::Pareto
+            | PruningStrategy::ByteBudget { .. }
+            | PruningStrategy::Reservoir { .. }
+            | PruningStrategy::ByDistance
+            | PruningStrategy::Diverse { .. } => self.prob,
+            PruningStrategy::AgeWeighted { half_life } => {
+                self.prob * (1.0 - libm::exp2(-(age as f64) / half_life))
+            }
+        }
+    }
+
+    /// Decide, for each enabled entry in insertion order, whether it should be retained.
+    ///
+    /// The `protected` entries are always retained.
+    fn retain_decisions<R>(&self, rand: &mut R, protected: &[bool]) -> Vec<bool>
+    where
+        R: Rand,
+    {
+        let n_corpus = protected.len();
+        let mut do_retain = Vec::with_capacity(n_corpus);
+        for (nth, protected) in protected.iter().enumerate() {
+            if *protected {
+                do_retain.push(true);
+                continue;
+            }
+            let age = n_corpus - nth - 1;
+            let prob = self.disable_prob(age);
+            do_retain.push(!rand.coinflip(prob));
+        }
+        do_retain
+    }
+
+    /// Make sure that at least something is left in the corpus
+    fn retain_one<R>(rand: &mut R, do_retain: &mut [bool])
+    where
+        R: Rand,
+    {
+        if !do_retain.is_empty() && !do_retain.contains(&true) {
+            let nth = rand.below(do_retain.len().try_into().unwrap());
+            do_retain[nth] = true;
+        }
+    }
+
+    /// The disabled entries to remove for good
+    fn disabled_to_remove<S>(&self, state: &mut S) -> Vec<CorpusId>
+    where
+        S: HasCorpus + HasRand + HasMetadata,
+    {
+        let top_rated = self.top_rated(state);
+        let n_disabled = state.corpus().count_disabled();
+        let mut to_remove = Vec::new();
+        for nth in 0..n_disabled {
+            let age = n_disabled - nth - 1;
+            if state.rand_mut().coinflip(self.disable_prob(age)) {
+                let id = state.corpus().nth_disabled(nth);
+                if !top_rated.contains(&id) {
+                    to_remove.push(id);
+                }
+            }
+        }
+        to_remove
+    }
+}
+
+impl Default for CorpusPruning {
+    fn default() -> Self {
+        Self::try_new(DEFAULT_PRUNING_PROB).unwrap()
+    }
+}
+
+impl<M, D> CorpusPruning<M, D> {
+    /// For each enabled entry in insertion order, whether it is on the Pareto front of the metrics
+    fn protected_front<S>(&self, state: &S) -> Result<Vec<bool>, Error>
+    where
+        S: HasCorpus,
+        M: ParetoMetrics<<S::Corpus as Corpus>::Input>,
+    {
+        let corpus = state.corpus();
+        let mut points = Vec::with_capacity(corpus.count());
+        for id in corpus.ids() {
+            points.push(self.metrics.measure(&corpus.get(id)?.borrow()));
+        }
+        Ok(pareto_front(&points))
+    }
+
+    /// Decide, for each enabled entry in insertion order, whether it should be retained,
+    /// disabling the least valuable ones until the rest fits into `max_bytes`.
+    ///
+    /// The `protected` entries are always retained, but count against the budget.
+    fn retain_within_budget<R, S>(
+        &self,
+        state: &S,
+        rand: &mut R,
+        max_bytes: usize,
+        protected: &[bool],
+    ) -> Result<Vec<bool>, Error>
+    where
+        R: Rand,
+        S: HasCorpus,
+        <S::Corpus as Corpus>::Input: Input,
+        M: ParetoMetrics<<S::Corpus as Corpus>::Input>,
+    {
+        let corpus = state.corpus();
+        let mut sizes = Vec::with_capacity(corpus.count());
+        let mut values = Vec::with_capacity(corpus.count());
+        for id in corpus.ids() {
+            sizes.push(input_size(corpus, id)?);
+            values.push(self.metrics.measure(&corpus.get(id)?.borrow()));
+        }
+
+        let mut total: usize = sizes.iter().sum();
+        let mut do_retain = vec![true; sizes.len()];
+        if total <= max_bytes {
+            return Ok(do_retain);
+        }
+
+        // Shuffle first, so the stable sort breaks ties randomly
+        let mut order = (0..sizes.len())
+            .filter(|nth| !protected[*nth])
+            .collect::<Vec<_>>();
+        for nth in (1..order.len()).rev() {
+            let other = rand.below((nth + 1).try_into().unwrap());
+            order.swap(nth, other);
+        }
+        order.sort_by(|a, b| {
+            values[*a]
+                .partial_cmp(&values[*b])
+                .unwrap_or(Ordering::Equal)
+        });
+        for nth in order {
+            if total <= max_bytes {
+                break;
+            }
+            do_retain[nth] = false;
+            total -= sizes[nth];
+        }
+        Ok(do_retain)
+    }
+
+    /// Decide, for each enabled entry in insertion order, whether it stays in the reservoir of `size` entries.
+    ///
+    /// Only the entries newer than `reservoir.last_seen` are offered, `reservoir` is updated accordingly.
+    /// The corpus is walked from the newest member of the sample, so a run only visits the entries added since.
+    /// The `kept` entries are not offered, but deferred until a run in which they are no longer kept.
+    fn retain_reservoir<R, S>(
+        state: &S,
+        rand: &mut R,
+        size: usize,
+        reservoir: &mut ReservoirMetadata,
+        kept: &HashSet<CorpusId>,
+    ) -> Vec<bool>
+    where
+        R: Rand,
+        S: HasCorpus,
+    {
+        let corpus = state.corpus();
+        // Members disabled or removed by others leave the sample, and so do deferred entries
+        reservoir.sample.retain(|id| corpus.get(*id).is_ok());
+        reservoir.deferred.retain(|id| corpus.get(*id).is_ok());
+        // Entries offered before that are not members were evicted or deferred
+        let mut next = match reservoir.sample.iter().max() {
+            Some(newest) => corpus.next(*newest),
+            None => corpus.first(),
+        };
+
+        let mut due = Vec::new();
+        reservoir.deferred.retain(|id| {
+            if kept.contains(id) {
+                return true;
+            }
+            due.push(*id);
+            false
+        });
+        while let Some(id) = next {
+            next = corpus.next(id);
+            // Entries enabled again, or kept in spite of their eviction
+            if reservoir.last_seen.is_some_and(|last_seen| id <= last_seen) {
+                continue;
+            }
+            reservoir.last_seen = Some(id);
+            if kept.contains(&id) {
+                reservoir.deferred.push(id);
+            } else {
+                due.push(id);
+            }
+        }
+
+        let mut evicted = HashSet::new();
+        for id in due {
+            reservoir.seen += 1;
+            if reservoir.sample.len() < size {
+                reservoir.sample.push(id);
+                continue;
+            }
+            let slot = rand.below(reservoir.seen.try_into().unwrap());
+            if slot < size {
+                evicted.insert(core::mem::replace(&mut reservoir.sample[slot], id));
+            } else {
+                evicted.insert(id);
+            }
+        }
+        corpus.ids().map(|id| !evicted.contains(&id)).collect()
+    }
+
+    /// Decide, for each enabled entry in insertion order, whether it should be retained,
+    /// disabling far entries more often, see [`PruningStrategy::ByDistance`].
+    ///
+    /// The `protected` entries are always retained.
+    fn retain_by_distance<R, S>(
+        &self,
+        state: &S,
+        rand: &mut R,
+        protected: &[bool],
+    ) -> Result<Vec<bool>, Error>
+    where
+        R: Rand,
+        S: HasCorpus,
+    {
+        let metric_name = self.distance_metric.as_deref().ok_or_else(|| {
+            Error::illegal_argument(
+                "Pruning by distance needs a metric, see CorpusPruning::by_distance",
+            )
+        })?;
+        let corpus = state.corpus();
+        let mut distances = Vec::with_capacity(corpus.count());
+        for id in corpus.ids() {
+            let testcase = corpus.get(id)?.borrow();
+            distances.push(
+                testcase
+                    .metadata::<TargetDistanceMetadata>()
+                    .ok()
+                    .and_then(|metadata| metadata.distance(metric_name))
+                    .filter(|distance| distance.is_finite()),
+            );
+        }
+
+        let known = distances.iter().flatten();
+        let min = known.clone().copied().fold(f64::INFINITY, f64::min);
+        let max = known.copied().fold(f64::NEG_INFINITY, f64::max);
+        Ok(distances
+            .into_iter()
+            .zip(protected)
+            .map(|(distance, protected)| {
+                if *protected {
+                    return true;
+                }
+                let weight = match distance {
+                    Some(distance) if max > min => (distance - min) / (max - min),
+                    _ => 1.0,
+                };
+                !rand.coinflip(self.prob * weight)
+            })
+            .collect())
+    }
+
+    /// Decide, for each enabled entry in insertion order, whether it should be retained,
+    /// keeping at least one entry of each of the `clusters` clusters, see [`PruningStrategy::Diverse`].
+    ///
+    /// The `protected` entries are always retained, and represent their cluster.
+    fn retain_diverse<R, S>(
+        &self,
+        state: &S,
+        rand: &mut R,
+        clusters: usize,
+        protected: &[bool],
+    ) -> Result<Vec<bool>, Error>
+    where
+        R: Rand,
+        S: HasCorpus,
+        D: DiversityFeatures<<S::Corpus as Corpus>::Input>,
+    {
+        let corpus = state.corpus();
+        let mut features = Vec::with_capacity(corpus.count());
+        for id in corpus.ids() {
+            features.push(self.features.features(&corpus.get(id)?.borrow()));
+        }
+        let assignment = cluster(rand, &features, clusters);
+
+        let mut do_retain = protected
+            .iter()
+            .map(|protected| *protected || !rand.coinflip(self.prob))
+            .collect::<Vec<_>>();
+        let mut cluster_members = vec![Vec::new(); clusters];
+        for (nth, member_of) in assignment.iter().enumerate() {
+            cluster_members[*member_of].push(nth);
+        }
+        for members in cluster_members {
+            if !members.is_empty() && members.iter().all(|nth| !do_retain[*nth]) {
+                let kept = rand.below(members.len().try_into().unwrap());
+                do_retain[members[kept]] = true;
+            }
+        }
+        Ok(do_retain)
+    }
+
+    /// The edges covered by each enabled entry, in insertion order
+    fn enabled_edges<S>(state: &S) -> Result<Vec<Vec<usize>>, Error>
+    where
+        S: HasCorpus,
+    {
+        let corpus = state.corpus();
+        let mut edges = Vec::with_capacity(corpus.count());
+        for id in corpus.ids() {
+            let testcase = corpus.get(id)?.borrow();
+            let indexes = testcase.metadata::<MapIndexesMetadata>().map_err(|_| {
+                Error::key_not_found(format!(
+                    "MapIndexesMetadata not found in testcase #{id}, track the indices of the map observer"
+                ))
+            })?;
+            edges.push(indexes.list.clone());
+        }
+        Ok(edges)
+    }
+
+    /// Retain the entries that would be disabled although they are the last enabled entry covering an edge.
+    ///
+    /// The entries are visited in insertion order, and each disabled one no longer counts as a cover,
+    /// so every edge covered before pruning stays covered by at least one enabled entry.
+    fn retain_unique_coverage<S>(state: &S, do_retain: &mut [bool]) -> Result<(), Error>
+    where
+        S: HasCorpus,
+    {
+        let edges = Self::enabled_edges(state)?;
+        let mut covers: HashMap<usize, usize> = HashMap::default();
+        for edge in edges.iter().flatten() {
+            *covers.entry(*edge).or_default() += 1;
+        }
+        for (retain, edges) in do_retain.iter_mut().zip(&edges) {
+            if *retain {
+                continue;
+            }
+            if edges.iter().any(|edge| covers[edge] == 1) {
+                *retain = true;
+            } else {
+                for edge in edges {
+                    *covers.get_mut(edge).unwrap() -= 1;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The enabled entries to disable, rolling the dice with `rand`.
+    ///
+    /// For [`PruningStrategy::Reservoir`], `reservoir` is the progress of the sample, and updated.
+    /// The `kept` entries, e.g., those in their grace period, are always retained,
+    /// and left out of the decisions of the strategy, e.g., they are not offered to the reservoir while kept.
+    fn to_disable<R, S>(
+        &self,
+        state: &S,
+        rand: &mut R,
+        reservoir: &mut ReservoirMetadata,
+        kept: &HashSet<CorpusId>,
+    ) -> Result<Vec<CorpusId>, Error>
+    where
+        R: Rand,
+        S: HasCorpus,
+        <S::Corpus as Corpus>::Input: Input,
+        M: ParetoMetrics<<S::Corpus as Corpus>::Input>,
+        D: DiversityFeatures<<S::Corpus as Corpus>::Input>,
+    {
+        let mut protected = state
+            .corpus()
+            .ids()
+            .map(|id| kept.contains(&id))
+            .collect::<Vec<_>>();
+        if self.strategy == PruningStrategy::Pareto {
+            for (protected, on_front) in protected.iter_mut().zip(self.protected_front(state)?) {
+                *protected |= on_front;
+            }
+        }
+        let mut do_retain = match self.strategy {
+            PruningStrategy::ByteBudget { max_bytes } => {
+                self.retain_within_budget(state, rand, max_bytes, &protected)?
+            }
+            PruningStrategy::Reservoir { size } => {
+                Self::retain_reservoir(state, rand, size, reservoir, kept)
+            }
+            PruningStrategy::ByDistance => self.retain_by_distance(state, rand, &protected)?,
+            PruningStrategy::Diverse { clusters } => {
+                self.retain_diverse(state, rand, clusters, &protected)?
+            }
+            _ => self.retain_decisions(rand, &protected),
+        };
+        if self.unique_coverage.is_some() {
+            Self::retain_unique_coverage(state, &mut do_retain)?;
+        }
+        Self::retain_one(rand, &mut do_retain);
+        Ok(state
+            .corpus()
+            .ids()
+            .zip(do_retain)
+            .filter_map(|(id, retain)| (!retain).then_some(id))
+            .collect())
+    }
+
+    /// Compute which enabled entries each of the `strategies` would disable, without changing anything.
+    ///
+    /// All other settings of this stage, such as the probability, the [`ParetoMetrics`], the [`DiversityFeatures`],
+    /// [`CorpusPruning::keep_unique_coverage`], and [`CorpusPruning::respect_minimizer`], apply to every strategy.
+    /// Neither the corpus nor its testcases are changed, e.g., no [`InputSizeMetadata`] is added.
+    /// Each strategy rolls the same dice, starting from a copy of the random generator of the `state`.
+    /// Removals of disabled entries, see [`CorpusPruning::include_disabled`], and the
+    /// [`CorpusPruning::grace_period`] are not part of the comparison.
+    /// [`PruningStrategy::Reservoir`] is compared as if the sample started out empty.
+    pub fn compare_strategies<S>(
+        &self,
+        strategies: &[PruningStrategy],
+        state: &S,
+    ) -> Result<StrategyComparison, Error>
+    where
+        S: HasCorpus + HasRand + HasMetadata,
+        S::Rand: Clone,
+        <S::Corpus as Corpus>::Input: Input,
+        M: ParetoMetrics<<S::Corpus as Corpus>::Input>,
+        D: DiversityFeatures<<S::Corpus as Corpus>::Input>,
+    {
+        let kept = self.top_rated(state);
+        let mut outcomes = Vec::with_capacity(strategies.len());
+        for strategy in strategies {
+            let pruning = CorpusPruning {
+                prob: self.prob,
+                strategy: *strategy,
+                include_disabled: false,
+                metrics: &self.metrics,
+                features: FeaturesRef(&self.features),
+                unique_coverage: self.unique_coverage.clone(),
+                debug_assertions: false,
+                distance_metric: self.distance_metric.clone(),
+                grace_period: None,
+                respect_minimizer: self.respect_minimizer,
+            };
+            let disabled = pruning.to_disable(
+                state,
+                &mut state.rand().clone(),
+                &mut ReservoirMetadata::default(),
+                &kept,
+            )?;
+            outcomes.push(StrategyOutcome {
+                strategy: *strategy,
+                retained: state.corpus().count() - disabled.len(),
+                disabled,
+            });
+        }
+        Ok(StrategyComparison { outcomes })
+    }
+
+    /// The number of edges the enabled entries would no longer cover if this stage ran now,
+    /// without changing anything, e.g., to only prune while the loss is acceptable.
+    ///
+    /// The entries to disable are chosen as in a run of this stage, starting from a copy of the
+    /// random generator of the `state`. Unless disabled entries are removed as well,
+    /// see [`CorpusPruning::include_disabled`], the next run disables the same entries.
+    /// The edges of each entry are taken from its [`MapIndexesMetadata`], which holds the indices of the map
+    /// observer that tracks them, see [`crate::observers::CanTrack::track_indices`].
+    pub fn estimate_coverage_loss<S>(&self, state: &S) -> Result<usize, Error>
+    where
+        S: HasCorpus + HasRand + HasMetadata,
+        S::Rand: Clone,
+        <S::Corpus as Corpus>::Input: Input,
+        M: ParetoMetrics<<S::Corpus as Corpus>::Input>,
+        D: DiversityFeatures<<S::Corpus as Corpus>::Input>,
+    {
+        let mut reservoir = state
+            .metadata::<ReservoirMetadata>()
+            .cloned()
+            .unwrap_or_default();
+        let mut kept = self.in_grace_period(state)?;
+        kept.extend(self.top_rated(state));
+        let to_disable = self
+            .to_disable(state, &mut state.rand().clone(), &mut reservoir, &kept)?
+            .into_iter()
+            .collect::<HashSet<_>>();
+
+        let edges = Self::enabled_edges(state)?;
+        let mut covered = HashSet::new();
+        let mut still_covered = HashSet::<usize>::new();
+        for (id, edges) in state.corpus().ids().zip(&edges) {
+            covered.extend(edges.iter().copied());
+            if !to_disable.contains(&id) {
+                still_covered.extend(edges.iter().copied());
+            }
+        }
+        Ok(covered.len() - still_covered.len())
+    }
+
+    /// The entries in the [`TopRatedsMetadata`] of the minimizer, if they are kept, see [`CorpusPruning::respect_minimizer`]
+    fn top_rated<S>(&self, state: &S) -> HashSet<CorpusId>
+    where
+        S: HasMetadata,
+    {
+        if !self.respect_minimizer {
+            return HashSet::new();
+        }
+        state
+            .metadata::<TopRatedsMetadata>()
+            .map(|top_rated| top_rated.map.values().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// The enabled entries that are still in their grace period, see [`CorpusPruning::grace_period`]
+    fn in_grace_period<S>(&self, state: &S) -> Result<HashSet<CorpusId>, Error>
+    where
+        S: HasCorpus,
+    {
+        let mut young = HashSet::new();
+        let Some(grace_period) = self.grace_period else {
+            return Ok(young);
+        };
+        let now = current_time();
+        let corpus = state.corpus();
+        for id in corpus.ids() {
+            let testcase = corpus.get(id)?.borrow();
+            let Ok(added_at) = testcase.metadata::<AddedAtMetadata>() else {
+                continue;
+            };
+            if now.saturating_sub(added_at.time) < grace_period {
+                young.insert(id);
+            }
+        }
+        Ok(young)
+    }
+
+    /// Record what the post-conditions of a run are checked against
+    fn snapshot<S>(&self, state: &S) -> Result<PruningSnapshot, Error>
+    where
+        S: HasCorpus + HasMetadata,
+        M: ParetoMetrics<<S::Corpus as Corpus>::Input>,
+        D: DiversityFeatures<<S::Corpus as Corpus>::Input>,
+    {
+        let corpus = state.corpus();
+        let protected = if self.strategy == PruningStrategy::Pareto {
+            corpus
+                .ids()
+                .zip(self.protected_front(state)?)
+                .filter_map(|(id, on_front)| on_front.then_some(id))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let covered = if self.unique_coverage.is_some() {
+            Some(Self::enabled_edges(state)?.into_iter().flatten().collect())
+        } else {
+            None
+        };
+        let top_rated = self
+            .top_rated(state)
+            .into_iter()
+            .filter(|id| corpus.get(*id).is_ok())
+            .collect();
+        Ok(PruningSnapshot {
+            enabled: corpus.count(),
+            disabled: corpus.count_disabled(),
+            protected,
+            top_rated,
+            covered,
+        })
+    }
+
+    /// Check the post-conditions of a run that removed `removed` disabled entries and disabled `disabled` entries
+    fn check_postconditions<S>(
+        &self,
+        state: &S,
+        before: &PruningSnapshot,
+        removed: usize,
+        disabled: usize,
+    ) -> Result<(), Error>
+    where
+        S: HasCorpus,
+    {
+        let corpus = state.corpus();
+        let (enabled_after, disabled_after) = (corpus.count(), corpus.count_disabled());
+        if enabled_after + disabled != before.enabled
+            || disabled_after + removed != before.disabled + disabled
+        {
+            return Err(Error::illegal_state(format!(
+                "Pruning disabled {disabled} and removed {removed} entries, \
+                 but went from {} enabled and {} disabled entries to {enabled_after} enabled and {disabled_after} disabled entries",
+                before.enabled, before.disabled
+            )));
+        }
+        if before.enabled > 0 && enabled_after == 0 {
+            return Err(Error::illegal_state("Pruning disabled every enabled entry"));
+        }
+        if let Some(id) = before.protected.iter().find(|id| corpus.get(**id).is_err()) {
+            return Err(Error::illegal_state(format!(
+                "Pruning disabled testcase #{id}, which is on the Pareto front"
+            )));
+        }
+        if let Some(id) = before.top_rated.iter().find(|id| corpus.get(**id).is_err()) {
+            return Err(Error::illegal_state(format!(
+                "Pruning disabled testcase #{id}, which the minimizer rated best for some feature"
+            )));
+        }
+        if let (Some(covered), Some(observer_name)) = (&before.covered, &self.unique_coverage) {
+            let covered_after = Self::enabled_edges(state)?
+                .into_iter()
+                .flatten()
+                .collect::<HashSet<_>>();
+            if let Some(edge) = covered.iter().find(|edge| !covered_after.contains(*edge)) {
+                return Err(Error::illegal_state(format!(
+                    "Pruning lost edge {edge} of {observer_name}, {} of {} edges are still covered",
+                    covered_after.len(),
+                    covered.len()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Disable (and, with `include_disabled`, remove) corpus entries; the [`CorpusQuiesceGuard`] is held by the caller
+    fn prune<S>(&self, state: &mut S) -> Result<(), Error>
+    where
+        S: HasCorpus + HasRand + HasMetadata + MaybeHasScalabilityMonitor + Stoppable,
+        S::Rand: Clone,
+        <S::Corpus as Corpus>::Input: Input,
+        M: ParetoMetrics<<S::Corpus as Corpus>::Input>,
+        D: DiversityFeatures<<S::Corpus as Corpus>::Input>,
+    {
+        if state.has_metadata::<PruningMarksMetadata>() {
+            // A stop request interrupted the last run, finish it first
+            return self.sweep(state);
+        }
+        let marks = self.marks(state)?;
+        self.sweep_marks(state, &marks)
+    }
+
+    /// Decide which entries to disable (and, with `include_disabled`, remove), without changing the corpus,
+    /// and store them as [`PruningMarksMetadata`] for [`CorpusPruning::sweep`].
+    ///
+    /// This is the first phase of [`TwoPhasePruning`]. It rolls the same dice as a single run of this stage,
+    /// and does not need the [`CorpusQuiesceGuard`].
+    pub fn mark<S>(&self, state: &mut S) -> Result<(), Error>
+    where
+        S: HasCorpus + HasRand + HasMetadata,
+        S::Rand: Clone,
+        <S::Corpus as Corpus>::Input: Input,
+        M: ParetoMetrics<<S::Corpus as Corpus>::Input>,
+        D: DiversityFeatures<<S::Corpus as Corpus>::Input>,
+    {
+        let marks = self.marks(state)?;
+        state.add_metadata(marks);
+        Ok(())
+    }
+
+    /// Disable (and remove) the entries marked by [`CorpusPruning::mark`], all at once.
+    ///
+    /// This is the second phase of [`TwoPhasePruning`]; the [`CorpusQuiesceGuard`] is held by the caller.
+    /// Marked entries that were removed, disabled, or re-enabled since are skipped, and, as always,
+    /// at least one entry is kept enabled. Without marks, nothing happens.
+    pub fn sweep<S>(&self, state: &mut S) -> Result<(), Error>
+    where
+        S: HasCorpus + HasMetadata + MaybeHasScalabilityMonitor + Stoppable,
+        M: ParetoMetrics<<S::Corpus as Corpus>::Input>,
+        D: DiversityFeatures<<S::Corpus as Corpus>::Input>,
+    {
+        let Some(mut marks) = state.metadata_map_mut().remove::<PruningMarksMetadata>() else {
+            return Ok(());
+        };
+        let corpus = state.corpus();
+        marks
+            .to_remove
+            .retain(|id| corpus.get(*id).is_err() && corpus.get_from_all(*id).is_ok());
+        marks.to_disable.retain(|id| corpus.get(*id).is_ok());
+        if marks.to_disable.len() == corpus.count() {
+            marks.to_disable.pop();
+        }
+        self.sweep_marks(state, &marks)
+    }
+
+    /// The entries to disable (and remove) in this run, rolling the dice of the `state`
+    fn marks<S>(&self, state: &mut S) -> Result<PruningMarksMetadata, Error>
+    where
+        S: HasCorpus + HasRand + HasMetadata,
+        S::Rand: Clone,
+        <S::Corpus as Corpus>::Input: Input,
+        M: ParetoMetrics<<S::Corpus as Corpus>::Input>,
+        D: DiversityFeatures<<S::Corpus as Corpus>::Input>,
+    {
+        let mut reservoir = state
+            .metadata::<ReservoirMetadata>()
+            .cloned()
+            .unwrap_or_default();
+        let mut kept = self.in_grace_period(state)?;
+        kept.extend(self.top_rated(state));
+        if let PruningStrategy::ByteBudget { .. } = self.strategy {
+            cache_input_sizes(state.corpus())?;
+        }
+        let marks = self.marks_with(state, |pruning, state, rand| {
+            pruning.to_disable(state, rand, &mut reservoir, &kept)
+        });
+        if let PruningStrategy::Reservoir { .. } = self.strategy {
+            state.add_metadata(reservoir);
+        }
+        marks
+    }
+
+    /// Like [`CorpusPruning::marks`], with the enabled entries to disable chosen by `to_disable`
+    fn marks_with<S, F>(&self, state: &mut S, to_disable: F) -> Result<PruningMarksMetadata, Error>
+    where
+        S: HasCorpus + HasRand + HasMetadata,
+        S::Rand: Clone,
+        F: FnOnce(&Self, &S, &mut S::Rand) -> Result<Vec<CorpusId>, Error>,
+    {
+        // Decide on the disabled pile first, so that the entries disabled in this run are not removed right away
+        let to_remove = if self.include_disabled {
+            self.disabled_to_remove(state)
+        } else {
+            Vec::new()
+        };
+
+        let mut rand = state.rand().clone();
+        let to_disable = to_disable(self, state, &mut rand)?;
+        *state.rand_mut() = rand;
+
+        Ok(PruningMarksMetadata {
+            to_remove,
+            to_disable,
+        })
+    }
+
+    /// Like [`CorpusPruning::prune`], with the enabled entries to disable chosen by `to_disable`
+    #[cfg(test)]
+    fn prune_with<S, F>(&self, state: &mut S, to_disable: F) -> Result<(), Error>
+    where
+        S: HasCorpus + HasRand + HasMetadata + MaybeHasScalabilityMonitor + Stoppable,
+        S::Rand: Clone,
+        M: ParetoMetrics<<S::Corpus as Corpus>::Input>,
+        D: DiversityFeatures<<S::Corpus as Corpus>::Input>,
+        F: FnOnce(&Self, &S, &mut S::Rand) -> Result<Vec<CorpusId>, Error>,
+    {
+        let marks = self.marks_with(state, to_disable)?;
+        self.sweep_marks(state, &marks)
+    }
+
+    /// Remove and disable the `marks` in one go, checking the post-conditions if asked to.
+    ///
+    /// On a stop request, the corpus is left as it is, and the marks are kept as [`PruningMarksMetadata`]
+    /// for [`CorpusPruning::sweep`].
+    fn sweep_marks<S>(&self, state: &mut S, marks: &PruningMarksMetadata) -> Result<(), Error>
+    where
+        S: HasCorpus + HasMetadata + MaybeHasScalabilityMonitor + Stoppable,
+        M: ParetoMetrics<<S::Corpus as Corpus>::Input>,
+        D: DiversityFeatures<<S::Corpus as Corpus>::Input>,
+    {
+        if state.stop_requested() {
+            log::info!(
+                "Pruning stopped before removing {} and disabling {} entries, they are left for the next run",
+                marks.to_remove.len(),
+                marks.to_disable.len()
+            );
+            state.add_metadata(marks.clone());
+            return Ok(());
+        }
+        let before = if self.debug_assertions {
+            Some(self.snapshot(state)?)
+        } else {
+            None
+        };
+
+        remove_many(state.corpus_mut(), &marks.to_remove)?;
+        disable_many(state.corpus_mut(), &marks.to_disable)?;
+        let (removed, disabled) = (marks.to_remove.len(), marks.to_disable.len());
+
+        #[cfg(feature = "scalability_introspection")]
+        {
+            let (enabled, disabled) = (state.corpus().count(), state.corpus().count_disabled());
+            let monitor = state.scalability_monitor_mut();
+            monitor.corpus_enabled = enabled;
+            monitor.corpus_disabled = disabled;
+        }
+
+        match before {
+            Some(before) => self.check_postconditions(state, &before, removed, disabled),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The serialized size of the input of the entry `id` of the `corpus`, without changing the entry.
+///
+/// Unless the size is cached as [`InputSizeMetadata`], an input that is not loaded is loaded into a copy of the entry.
+fn input_size<C>(corpus: &C, id: CorpusId) -> Result<usize, Error>
+where
+    C: Corpus,
+    C::Input: Input,
+{
+    let testcase = corpus.get(id)?.borrow();
+    if let Ok(size) = testcase.metadata::<InputSizeMetadata>() {
+        return Ok(size.bytes);
+    }
+    if let Some(input) = testcase.input() {
+        return Ok(postcard::to_allocvec(input)?.len());
+    }
+    let mut copy = testcase.clone();
+    drop(testcase);
+    Ok(postcard::to_allocvec(copy.load_input(corpus)?)?.len())
+}
+
+/// Add the [`InputSizeMetadata`] to the enabled entries of the `corpus` that lack it, so later runs need not load their inputs again
+fn cache_input_sizes<C>(corpus: &C) -> Result<(), Error>
+where
+    C: Corpus,
+    C::Input: Input,
+{
+    for id in corpus.ids() {
+        let mut testcase = corpus.get(id)?.borrow_mut();
+        if testcase.has_metadata::<InputSizeMetadata>() {
+            continue;
+        }
+        let bytes = postcard::to_allocvec(testcase.load_input(corpus)?)?.len();
+        testcase.add_metadata(InputSizeMetadata { bytes });
+    }
+    Ok(())
+}
+
+/// Remove the entries `ids` of the `corpus` for good, enabled or disabled, in one go.
+///
+/// Returns an error, leaving the entries so far removed, if one of them is not in the corpus.
+pub fn remove_many<C>(corpus: &mut C, ids: &[CorpusId]) -> Result<(), Error>
+where
+    C: Corpus,
+{
+    for id in ids {
+        corpus.remove(*id)?;
+    }
+    Ok(())
+}
+
+/// Disable the enabled entries `ids` of the `corpus`, in one go.
+///
+/// Returns an error, leaving the entries so far disabled, if one of them is not enabled.
+pub fn disable_many<C>(corpus: &mut C, ids: &[CorpusId]) -> Result<(), Error>
+where
+    C: Corpus,
+{
+    for id in ids {
+        // On-disk corpora delete the file of a removed entry, so keep its input around
+        corpus.load_input_into(&mut corpus.get(*id)?.borrow_mut())?;
+        let mut removed = corpus.remove(*id)?;
+        removed.set_disabled(true);
+        corpus.add_disabled(removed)?;
+    }
+    Ok(())
+}
+
+/// The entries a [`CorpusPruning`] marked to disable and remove, until they are swept, see [`CorpusPruning::mark`]
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct PruningMarksMetadata {
+    to_remove: Vec<CorpusId>,
+    to_disable: Vec<CorpusId>,
+}
+
+libafl_bolts::impl_serdeany!(PruningMarksMetadata);
+
+impl PruningMarksMetadata {
+    /// The disabled entries marked to be removed for good
+    #[must_use]
+    pub fn to_remove(&self) -> &[CorpusId] {
+        &self.to_remove
+    }
+
+    /// The enabled entries marked to be disabled
+    #[must_use]
+    pub fn to_disable(&self) -> &[CorpusId] {
+        &self.to_disable
+    }
+}
+
+/// A [`Stage`] that runs a [`CorpusPruning`] in two phases, to keep other stages waiting as briefly as possible.
+///
+/// First, it marks the entries to disable with [`CorpusPruning::mark`], which may take a while,
+/// e.g., for [`PruningStrategy::Pareto`], but does not change the corpus. Only then it acquires the
+/// [`CorpusQuiesceGuard`], and disables all marked entries at once with [`CorpusPruning::sweep`].
+/// The result is the same as running the [`CorpusPruning`] directly.
+#[derive(Debug, Clone)]
+pub struct TwoPhasePruning<M = (), D = ()> {
+    pruning: CorpusPruning<M, D>,
+}
+
+impl<M, D> TwoPhasePruning<M, D> {
+    /// Create a new [`TwoPhasePruning`], running the given `pruning`
+    #[must_use]
+    pub fn new(pruning: CorpusPruning<M, D>) -> Self {
+        Self { pruning }
+    }
+
+    /// The [`CorpusPruning`] this stage runs
+    #[must_use]
+    pub fn pruning(&self) -> &CorpusPruning<M, D> {
+        &self.pruning
+    }
+}
+
+impl<D, E, EM, M, S, Z> Stage<E, EM, S, Z> for TwoPhasePruning<M, D>
+where
+    M: ParetoMetrics<<S::Corpus as Corpus>::Input>,
+    D: DiversityFeatures<<S::Corpus as Corpus>::Input>,
+    S: HasCorpus + HasRand + HasMetadata + MaybeHasScalabilityMonitor + Stoppable,
+    <S::Corpus as Corpus>::Input: Input,
+    S::Rand: Clone,
+{
+    fn perform(
+        &mut self,
+        _fuzzer: &mut Z,
+        _executor: &mut E,
+        state: &mut S,
+        _manager: &mut EM,
+    ) -> Result<(), Error> {
+        if CorpusQuiesceGuard::is_held(state) {
+            // Someone else is reorganizing the corpus right now
+            return Ok(());
+        }
+
+        // Unless a stop request interrupted the last sweep, which is finished first
+        if !state.has_metadata::<PruningMarksMetadata>() {
+            self.pruning.mark(state)?;
+        }
+        CorpusQuiesceGuard::acquire(state)?;
+        let res = self.pruning.sweep(state);
+        CorpusQuiesceGuard::release(state);
+        res
+    }
+
+    #[inline]
+    fn should_restart(&mut self, _state: &mut S) -> Result<bool, Error> {
+        // Not executing the target, so restart safety is not needed
+        Ok(true)
+    }
+
+    #[inline]
+    fn clear_progress(&mut self, _state: &mut S) -> Result<(), Error> {
+        // Not executing the target, so restart safety is not needed
+        Ok(())
+    }
+}
+
+impl<D, E, EM, M, S, Z> Stage<E, EM, S, Z> for CorpusPruning<M, D>
+where
+    M: ParetoMetrics<<S::Corpus as Corpus>::Input>,
+    D: DiversityFeatures<<S::Corpus as Corpus>::Input>,
+    S: HasCorpus + HasRand + HasMetadata + MaybeHasScalabilityMonitor + Stoppable,
+    <S::Corpus as Corpus>::Input: Input,
+    S::Rand: Clone,
+{
+    fn perform(
+        &mut self,
+        _fuzzer: &mut Z,
+        _executor: &mut E,
+        state: &mut S,
+        _manager: &mut EM,
+    ) -> Result<(), Error> {
+        if CorpusQuiesceGuard::is_held(state) {
+            // Someone else is reorganizing the corpus right now
+            return Ok(());
+        }
+
+        CorpusQuiesceGuard::acquire(state)?;
+        let res = self.prune(state);
+        CorpusQuiesceGuard::release(state);
+        res
+    }
+
+    #[inline]
+    fn should_restart(&mut self, _state: &mut S) -> Result<bool, Error> {
+        // Not executing the target, so restart safety is not needed
+        Ok(true)
+    }
+
+    #[inline]
+    fn clear_progress(&mut self, _state: &mut S) -> Result<(), Error> {
+        // Not executing the target, so restart safety is not needed
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use hashbrown::{HashMap, HashSet};
+    use libafl_bolts::{nonzero, rands::Rand, tuples::Handle, HasLen};
+
+    use super::pareto_front;
+    use crate::{
+        corpus::{Corpus, CorpusId, Testcase},
+        feedbacks::MapIndexesMetadata,
+        inputs::BytesInput,
+        observers::StdMapObserver,
+        schedulers::minimizer::TopRatedsMetadata,
+        stages::{
+            CorpusPruning, CorpusQuiesceGuard, InputSizeMetadata, PruningMarksMetadata,
+            PruningStrategy, ReservoirMetadata, Stage, TargetDistanceMetadata, TwoPhasePruning,
+            DEFAULT_PRUNING_PROB,
+        },
+        state::{HasCorpus, HasRand, StdState, Stoppable},
+        testing::{covered_edges, enabled_ids, FakeState, FAKE_ENTRY_INTERVAL},
+        Error, HasMetadata,
+    };
+
+    /// A stage that adds an entry to the corpus, unless it is quiesced
+    struct AddingStage;
+
+    impl<S> Stage<(), (), S, ()> for AddingStage
+    where
+        S: HasCorpus<Corpus: Corpus<Input = BytesInput>> + HasMetadata,
+    {
+        fn perform(
+            &mut self,
+            _fuzzer: &mut (),
+            _executor: &mut (),
+            state: &mut S,
+            _manager: &mut (),
+        ) -> Result<(), Error> {
+            if CorpusQuiesceGuard::is_held(state) {
+                return Ok(());
+            }
+            state
+                .corpus_mut()
+                .add(Testcase::new(BytesInput::new(vec![0])))?;
+            Ok(())
+        }
+
+        fn should_restart(&mut self, _state: &mut S) -> Result<bool, Error> {
+            Ok(true)
+        }
+
+        fn clear_progress(&mut self, _state: &mut S) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert!(CorpusPruning::try_new(0.0).is_ok());
+        assert!((CorpusPruning::try_new(1.0).unwrap().prob() - 1.0).abs() < f64::EPSILON);
+        assert!(CorpusPruning::try_new(-0.1).is_err());
+        assert!(CorpusPruning::try_new(1.5).is_err());
+        assert!(CorpusPruning::try_new(f64::NAN).is_err());
+        assert!((CorpusPruning::default().prob() - DEFAULT_PRUNING_PROB).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_age_weighted_prob() {
+        let pruning = CorpusPruning::age_weighted(8);
+        assert_eq!(
+            *pruning.strategy(),
+            PruningStrategy::AgeWeighted { half_life: 8.0 }
+        );
+        assert!(pruning.disable_prob(0).abs() < f64::EPSILON);
+        assert!((pruning.disable_prob(8) - pruning.prob() / 2.0).abs() < 1e-9);
+        assert!(pruning.disable_prob(100) > pruning.disable_prob(8));
+    }
+
+    #[test]
+    fn test_age_weighted_prunes_old_entries() {
+        const ENTRIES: usize = 64;
+        const RUNS: usize = 200;
+
+        let mut pruning = CorpusPruning::age_weighted(16);
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+
+        // how often the oldest/newest half of the corpus was disabled
+        let mut old_disabled = 0;
+        let mut new_disabled = 0;
+        for _ in 0..RUNS {
+            for nth in 0..ENTRIES {
+                state
+                    .corpus_mut()
+                    .add(Testcase::new(BytesInput::new(vec![nth as u8])))
+                    .unwrap();
+            }
+
+            pruning
+                .perform(&mut (), &mut (), &mut state, &mut ())
+                .unwrap();
+            assert!(state.corpus().count() > 0);
+
+            let corpus = state.corpus();
+            for nth in corpus.count()..corpus.count_all() {
+                let id = corpus.nth_from_all(nth);
+                let testcase = corpus.get_from_all(id).unwrap().borrow();
+                if usize::from(testcase.input().as_ref().unwrap().as_ref()[0]) < ENTRIES / 2 {
+                    old_disabled += 1;
+                } else {
+                    new_disabled += 1;
+                }
+            }
+
+            while state.corpus().count_all() > 0 {
+                let id = state.corpus().nth_from_all(0);
+                state.corpus_mut().remove(id).unwrap();
+            }
+        }
+
+        assert!(
+            old_disabled > new_disabled,
+            "old entries: {old_disabled} disabled, new entries: {new_disabled} disabled"
+        );
+    }
+
+    #[test]
+    fn test_include_disabled() {
+        const ENABLED: usize = 32;
+        const DISABLED: usize = 32;
+
+        // Entries that start out enabled have inputs below `ENABLED`, disabled ones above
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        for nth in 0..ENABLED {
+            state
+                .corpus_mut()
+                .add(Testcase::new(BytesInput::new(vec![nth as u8])))
+                .unwrap();
+        }
+        for nth in 0..DISABLED {
+            let mut testcase = Testcase::new(BytesInput::new(vec![(ENABLED + nth) as u8]));
+            testcase.set_disabled(true);
+            state.corpus_mut().add_disabled(testcase).unwrap();
+        }
+
+        // Without the flag, disabled entries are never touched
+        let mut pruning = CorpusPruning::new(0.5, PruningStrategy::Uniform);
+        assert!(!pruning.includes_disabled());
+        let mut without_flag = state.clone();
+        pruning
+            .perform(&mut (), &mut (), &mut without_flag, &mut ())
+            .unwrap();
+        assert_eq!(without_flag.corpus().count_all(), ENABLED + DISABLED);
+
+        let mut pruning = pruning.include_disabled(true);
+        pruning
+            .perform(&mut (), &mut (), &mut state, &mut ())
+            .unwrap();
+        let corpus = state.corpus();
+
+        let (mut initially_enabled, mut initially_disabled) = (0, 0);
+        for nth in 0..corpus.count_all() {
+            let id = corpus.nth_from_all(nth);
+            let testcase = corpus.get_from_all(id).unwrap().borrow();
+            if usize::from(testcase.input().as_ref().unwrap().as_ref()[0]) < ENABLED {
+                initially_enabled += 1;
+            } else {
+                initially_disabled += 1;
+            }
+        }
+
+        // Enabled entries are only ever disabled, never removed, and at least one is retained
+        assert!(corpus.count() > 0);
+        assert_eq!(initially_enabled, ENABLED);
+        // Some of the previously disabled entries are gone for good
+        assert!(initially_disabled < DISABLED);
+    }
+
+    #[test]
+    fn test_two_phase() {
+        // The inputs of the enabled and of the disabled entries
+        fn partition<S>(state: &S) -> (Vec<u8>, Vec<u8>)
+        where
+            S: HasCorpus<Corpus: Corpus<Input = BytesInput>>,
+        {
+            let corpus = state.corpus();
+            let input = |id| {
+                corpus
+                    .get_from_all(id)
+                    .unwrap()
+                    .borrow()
+                    .input()
+                    .as_ref()
+                    .unwrap()
+                    .as_ref()[0]
+            };
+            (
+                corpus.ids().map(input).collect(),
+                (0..corpus.count_disabled())
+                    .map(|nth| input(corpus.nth_disabled(nth)))
+                    .collect(),
+            )
+        }
+
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        for nth in 0..64 {
+            let mut testcase = Testcase::new(BytesInput::new(vec![nth]));
+            if nth % 2 == 0 {
+                state.corpus_mut().add(testcase).unwrap();
+            } else {
+                testcase.set_disabled(true);
+                state.corpus_mut().add_disabled(testcase).unwrap();
+            }
+        }
+        let pruning = CorpusPruning::new(0.5, PruningStrategy::Uniform)
+            .include_disabled(true)
+            .debug_assertions(true);
+
+        let mut single_phase = state.clone();
+        pruning
+            .clone()
+            .perform(&mut (), &mut (), &mut single_phase, &mut ())
+            .unwrap();
+        let mut two_phase = state.clone();
+        TwoPhasePruning::new(pruning.clone())
+            .perform(&mut (), &mut (), &mut two_phase, &mut ())
+            .unwrap();
+        assert_eq!(partition(&two_phase), partition(&single_phase));
+        assert_ne!(partition(&two_phase), partition(&state));
+        assert!(!two_phase.has_metadata::<PruningMarksMetadata>());
+        assert!(!CorpusQuiesceGuard::is_held(&two_phase));
+
+        // Entries that changed between the phases are skipped
+        pruning.mark(&mut state).unwrap();
+        let marks = state.metadata::<PruningMarksMetadata>().unwrap().clone();
+        let stale = marks.to_disable()[0];
+        state.corpus_mut().remove(stale).unwrap();
+        pruning.sweep(&mut state).unwrap();
+        assert_eq!(
+            state.corpus().count(),
+            32 - marks.to_disable().len(),
+            "the stale mark should be skipped"
+        );
+        assert_eq!(
+            state.corpus().count_disabled(),
+            32 - marks.to_remove().len() + marks.to_disable().len() - 1
+        );
+        // Without marks, sweeping does nothing
+        pruning.sweep(&mut state).unwrap();
+        assert_eq!(state.corpus().count(), 32 - marks.to_disable().len());
+    }
+
+    #[test]
+    fn test_quiesce_guard() {
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        let mut adding = AddingStage;
+        let mut pruning = CorpusPruning::new(1.0, PruningStrategy::Uniform);
+
+        // While the guard is held, the mutating stage defers, and so does pruning
+        CorpusQuiesceGuard::acquire(&mut state).unwrap();
+        assert!(CorpusQuiesceGuard::acquire(&mut state).is_err());
+        adding
+            .perform(&mut (), &mut (), &mut state, &mut ())
+            .unwrap();
+        assert_eq!(state.corpus().count_all(), 0);
+        CorpusQuiesceGuard::release(&mut state);
+
+        for _ in 0..4 {
+            adding
+                .perform(&mut (), &mut (), &mut state, &mut ())
+                .unwrap();
+        }
+        assert_eq!(state.corpus().count(), 4);
+
+        CorpusQuiesceGuard::acquire(&mut state).unwrap();
+        pruning
+            .perform(&mut (), &mut (), &mut state, &mut ())
+            .unwrap();
+        assert_eq!(state.corpus().count(), 4);
+        CorpusQuiesceGuard::release(&mut state);
+
+        // Pruning releases the guard once it is done
+        pruning
+            .perform(&mut (), &mut (), &mut state, &mut ())
+            .unwrap();
+        assert_eq!(state.corpus().count(), 1);
+        assert!(!CorpusQuiesceGuard::is_held(&state));
+    }
+
+    #[test]
+    fn test_pareto_front() {
+        let points = vec![
+            vec![1.0, 5.0],
+            vec![5.0, 1.0],
+            vec![3.0, 3.0],
+            vec![2.0, 2.0],
+            vec![3.0, 3.0],
+            vec![1.0, 4.0],
+        ];
+        assert_eq!(
+            pareto_front(&points),
+            [true, true, true, false, true, false]
+        );
+        // Without metrics, nothing is dominated
+        assert_eq!(pareto_front(&[vec![], vec![]]), [true, true]);
+    }
+
+    #[test]
+    fn test_pareto_keeps_front() {
+        // The first byte is the coverage, the second one the input size, which should be small
+        let entries: [[u8; 2]; 8] = [
+            [9, 9],
+            [5, 2],
+            [1, 1],
+            [5, 5],
+            [4, 4],
+            [1, 3],
+            [8, 9],
+            [9, 8],
+        ];
+        let front = [[5, 2], [1, 1], [9, 8]];
+        let metrics: Vec<fn(&Testcase<BytesInput>) -> f64> = vec![
+            |testcase| f64::from(testcase.input().as_ref().unwrap().as_ref()[0]),
+            |testcase| -f64::from(testcase.input().as_ref().unwrap().as_ref()[1]),
+        ];
+
+        for prob in [DEFAULT_PRUNING_PROB, 0.5, 1.0] {
+            let mut pruning = CorpusPruning {
+                prob,
+                ..CorpusPruning::pareto(metrics.clone())
+            };
+            assert_eq!(*pruning.strategy(), PruningStrategy::Pareto);
+
+            let mut state = StdState::nop::<BytesInput>().unwrap();
+            for _ in 0..16 {
+                for entry in entries {
+                    state
+                        .corpus_mut()
+                        .add(Testcase::new(BytesInput::new(entry.to_vec())))
+                        .unwrap();
+                }
+                pruning
+                    .perform(&mut (), &mut (), &mut state, &mut ())
+                    .unwrap();
+
+                let corpus = state.corpus();
+                let enabled = corpus
+                    .ids()
+                    .map(|id| {
+                        corpus
+                            .get(id)
+                            .unwrap()
+                            .borrow()
+                            .input()
+                            .as_ref()
+                            .unwrap()
+                            .as_ref()
+                            .clone()
+                    })
+                    .collect::<Vec<_>>();
+                for entry in front {
+                    assert!(enabled.contains(&entry.to_vec()), "{entry:?} was disabled");
+                }
+                if (prob - 1.0).abs() < f64::EPSILON {
+                    assert_eq!(enabled.len(), front.len());
+                }
+
+                while state.corpus().count_all() > 0 {
+                    let id = state.corpus().nth_from_all(0);
+                    state.corpus_mut().remove(id).unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_keep_unique_coverage() {
+        // The input is the name of the entry, the edges it covers are in its metadata
+        let entries: [(u8, &[usize]); 5] = [
+            (0, &[0, 1]),
+            (1, &[1, 2]),
+            (2, &[2]),
+            (3, &[0, 1, 2]),
+            (4, &[3]),
+        ];
+        let edges = Handle::<StdMapObserver<'static, u8, false>>::new("edges".into());
+        let mut pruning =
+            CorpusPruning::new(1.0, PruningStrategy::Uniform).keep_unique_coverage(&edges);
+
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        for (name, indexes) in entries {
+            let mut testcase = Testcase::new(BytesInput::new(vec![name]));
+            testcase.add_metadata(MapIndexesMetadata::new(indexes.to_vec()));
+            state.corpus_mut().add(testcase).unwrap();
+        }
+        pruning
+            .perform(&mut (), &mut (), &mut state, &mut ())
+            .unwrap();
+
+        // Every roll disables, but the last covers of each edge survive
+        let corpus = state.corpus();
+        let enabled = corpus
+            .ids()
+            .map(|id| {
+                corpus
+                    .get(id)
+                    .unwrap()
+                    .borrow()
+                    .input()
+                    .as_ref()
+                    .unwrap()
+                    .as_ref()[0]
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(enabled, [3, 4]);
+
+        // Entries without coverage information are an error
+        state
+            .corpus_mut()
+            .add(Testcase::new(BytesInput::new(vec![5])))
+            .unwrap();
+        assert!(pruning
+            .perform(&mut (), &mut (), &mut state, &mut ())
+            .is_err());
+    }
+
+    #[test]
+    fn test_estimate_coverage_loss() {
+        let entries: [&[usize]; 5] = [&[0, 1], &[1, 2], &[2], &[0, 1, 2], &[3]];
+        let edges = Handle::<StdMapObserver<'static, u8, false>>::new("edges".into());
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        for (name, indexes) in entries.into_iter().enumerate() {
+            let mut testcase = Testcase::new(BytesInput::new(vec![name as u8]));
+            testcase.add_metadata(MapIndexesMetadata::new(indexes.to_vec()));
+            state.corpus_mut().add(testcase).unwrap();
+        }
+
+        // Only redundant entries are disabled, and the estimate leaves the corpus alone
+        let keep_unique =
+            CorpusPruning::new(1.0, PruningStrategy::Uniform).keep_unique_coverage(&edges);
+        assert_eq!(keep_unique.estimate_coverage_loss(&state).unwrap(), 0);
+        assert_eq!(state.corpus().count(), entries.len());
+
+        // A single entry survives, and the estimate is what the next run loses
+        let mut pruning = CorpusPruning::new(1.0, PruningStrategy::Uniform);
+        let loss = pruning.estimate_coverage_loss(&state).unwrap();
+        assert!(loss > 0);
+        pruning
+            .perform(&mut (), &mut (), &mut state, &mut ())
+            .unwrap();
+        assert_eq!(state.corpus().count(), 1);
+        let corpus = state.corpus();
+        let testcase = corpus.get(corpus.first().unwrap()).unwrap().borrow();
+        let covered = testcase
+            .metadata::<MapIndexesMetadata>()
+            .unwrap()
+            .list
+            .len();
+        assert_eq!(covered, 4 - loss);
+    }
+
+    #[test]
+    #[cfg(feature = "scalability_introspection")]
+    fn test_scalability_monitor() {
+        use crate::state::HasScalabilityMonitor;
+
+        let mut state = FakeState::generate(0, 16, 64);
+        let mut pruning = CorpusPruning::new(0.5, PruningStrategy::Uniform).include_disabled(true);
+        for run in 0..3 {
+            pruning
+                .perform(&mut (), &mut (), &mut state, &mut ())
+                .unwrap();
+            let monitor = state.scalability_monitor();
+            let counts = (monitor.corpus_enabled, monitor.corpus_disabled);
+            assert_eq!(
+                counts,
+                (state.corpus().count(), state.corpus().count_disabled())
+            );
+            if run == 0 {
+                assert_eq!(counts.0 + counts.1, 16);
+                assert!(counts.1 > 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fake_state() {
+        const ENTRIES: usize = 64;
+        const YOUNG: usize = 10;
+        let edges = Handle::<StdMapObserver<'static, u8, false>>::new("edges".into());
+        let pruning = CorpusPruning::new(0.5, PruningStrategy::Uniform)
+            .keep_unique_coverage(&edges)
+            .grace_period(FAKE_ENTRY_INTERVAL * YOUNG as u32)
+            .debug_assertions(true);
+
+        let mut kept = Vec::new();
+        for _ in 0..2 {
+            let mut state = FakeState::generate(7, ENTRIES, 32);
+            let covered = covered_edges(state.corpus());
+            pruning
+                .clone()
+                .perform(&mut (), &mut (), &mut state, &mut ())
+                .unwrap();
+            assert_eq!(covered_edges(state.corpus()), covered);
+            assert_eq!(
+                state.corpus().count() + state.corpus().count_disabled(),
+                ENTRIES
+            );
+            kept.push(enabled_ids(state.corpus()));
+        }
+
+        // The same seed rolls the same dice
+        assert_eq!(kept[0], kept[1]);
+        assert!(kept[0].len() < ENTRIES);
+        // The entries added during the last minutes are in their grace period
+        let young = ENTRIES - YOUNG + 1..ENTRIES;
+        assert!(young.map(CorpusId).all(|id| kept[0].contains(&id)));
+    }
+
+    #[test]
+    fn test_stop_request() {
+        let pruning = CorpusPruning::new(0.5, PruningStrategy::Uniform).debug_assertions(true);
+        let mut uninterrupted = FakeState::generate(3, 64, 32);
+        pruning
+            .clone()
+            .perform(&mut (), &mut (), &mut uninterrupted, &mut ())
+            .unwrap();
+        let disabled = uninterrupted.corpus().count_disabled();
+        assert!(disabled > 5);
+
+        // A stop requested before the sweep leaves the corpus as it is, and the marks for later
+        let mut state = FakeState::generate(3, 64, 32);
+        state.request_stop();
+        pruning
+            .clone()
+            .perform(&mut (), &mut (), &mut state, &mut ())
+            .unwrap();
+        assert_eq!(state.corpus().count_disabled(), 0);
+        assert_eq!(state.corpus().count(), 64);
+        let marks = state.metadata::<PruningMarksMetadata>().unwrap();
+        assert_eq!(marks.to_disable().len(), disabled);
+        assert!(!CorpusQuiesceGuard::is_held(&state));
+
+        // While the stop is requested, nothing changes
+        pruning
+            .clone()
+            .perform(&mut (), &mut (), &mut state, &mut ())
+            .unwrap();
+        assert_eq!(state.corpus().count_disabled(), 0);
+
+        // The next run finishes the interrupted one
+        state.discard_stop_request();
+        pruning
+            .clone()
+            .perform(&mut (), &mut (), &mut state, &mut ())
+            .unwrap();
+        assert!(!state.has_metadata::<PruningMarksMetadata>());
+        assert_eq!(
+            enabled_ids(state.corpus()),
+            enabled_ids(uninterrupted.corpus())
+        );
+    }
+
+    #[test]
+    fn test_respect_minimizer() {
+        /// A fake state, with the shortest cover of each edge rated best, like the minimizer would
+        fn rated_state() -> (FakeState, HashSet<CorpusId>) {
+            let mut state = FakeState::generate(7, 64, 32);
+            let mut top_rated = TopRatedsMetadata::new();
+            let mut best_len = HashMap::<usize, usize>::new();
+            for id in state.corpus().ids().collect::<Vec<_>>() {
+                let testcase = state.corpus().get(id).unwrap().borrow();
+                let len = testcase.input().as_ref().unwrap().len();
+                for edge in &testcase.metadata::<MapIndexesMetadata>().unwrap().list {
+                    if best_len.get(edge).is_none_or(|best| len < *best) {
+                        best_len.insert(*edge, len);
+                        top_rated.map.insert(*edge, id);
+                    }
+                }
+            }
+            let rated = top_rated.map.values().copied().collect();
+            state.add_metadata(top_rated);
+            (state, rated)
+        }
+
+        let (mut unaware, _) = rated_state();
+        CorpusPruning::new(1.0, PruningStrategy::Uniform)
+            .perform(&mut (), &mut (), &mut unaware, &mut ())
+            .unwrap();
+        assert_eq!(unaware.corpus().count(), 1);
+
+        let (mut state, rated) = rated_state();
+        // Every roll disables or removes, but the top-rated entries stay enabled
+        let mut pruning = CorpusPruning::new(1.0, PruningStrategy::Uniform)
+            .include_disabled(true)
+            .respect_minimizer(true)
+            .debug_assertions(true);
+        for _ in 0..2 {
+            pruning
+                .perform(&mut (), &mut (), &mut state, &mut ())
+                .unwrap();
+        }
+        let top_rated = state.metadata::<TopRatedsMetadata>().unwrap();
+        assert!(top_rated
+            .map
+            .values()
+            .all(|id| state.corpus().get(*id).is_ok()));
+        assert_eq!(
+            state.corpus().ids().collect::<HashSet<_>>(),
+            rated,
+            "Only the top-rated entries are left"
+        );
+
+        // The comparison keeps the same entries
+        let (state, rated) = rated_state();
+        let comparison = CorpusPruning::new(1.0, PruningStrategy::Uniform)
+            .respect_minimizer(true)
+            .compare_strategies(&[PruningStrategy::Uniform], &state)
+            .unwrap();
+        let outcome = &comparison.outcomes()[0];
+        assert_eq!(outcome.retained, rated.len());
+        assert!(outcome.disabled.iter().all(|id| !rated.contains(id)));
+    }
+
+    #[test]
+    fn test_debug_assertions() {
+        #[allow(clippy::unnecessary_wraps)]
+        fn disable_all<M, S>(
+            _: &CorpusPruning<M>,
+            state: &S,
+            _: &mut S::Rand,
+        ) -> Result<Vec<CorpusId>, Error>
+        where
+            S: HasCorpus + HasRand,
+        {
+            Ok(state.corpus().ids().collect())
+        }
+
+        #[allow(clippy::unnecessary_wraps)]
+        fn disable_best<M, S>(
+            _: &CorpusPruning<M>,
+            state: &S,
+            _: &mut S::Rand,
+        ) -> Result<Vec<CorpusId>, Error>
+        where
+            S: HasCorpus + HasRand,
+        {
+            Ok(state.corpus().last().into_iter().collect())
+        }
+
+        let metrics: Vec<fn(&Testcase<BytesInput>) -> f64> =
+            vec![|testcase| f64::from(testcase.input().as_ref().unwrap().as_ref()[0])];
+        let pruning = CorpusPruning::pareto(metrics).debug_assertions(true);
+        let edges = Handle::<StdMapObserver<'static, u8, false>>::new("edges".into());
+        let covering = CorpusPruning::new(0.5, PruningStrategy::Uniform)
+            .keep_unique_coverage(&edges)
+            .debug_assertions(true);
+
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        for nth in 0..8 {
+            let mut testcase = Testcase::new(BytesInput::new(vec![nth]));
+            testcase.add_metadata(MapIndexesMetadata::new(vec![usize::from(nth % 4)]));
+            state.corpus_mut().add(testcase).unwrap();
+        }
+
+        // The real strategies hold up
+        let mut checked = state.clone();
+        pruning
+            .clone()
+            .perform(&mut (), &mut (), &mut checked, &mut ())
+            .unwrap();
+        covering
+            .clone()
+            .perform(&mut (), &mut (), &mut checked, &mut ())
+            .unwrap();
+
+        // A buggy strategy that disables everything
+        let err = pruning
+            .clone()
+            .prune_with(&mut state.clone(), disable_all)
+            .unwrap_err();
+        assert!(matches!(err, Error::IllegalState(..)), "{err:?}");
+        assert!(covering
+            .clone()
+            .prune_with(&mut state.clone(), disable_all)
+            .is_err());
+
+        // A buggy strategy that disables the best entry only trips the Pareto check
+        assert!(pruning
+            .clone()
+            .prune_with(&mut state.clone(), disable_best)
+            .is_err());
+        // Its edge is still covered by another entry
+        covering
+            .clone()
+            .prune_with(&mut state.clone(), disable_best)
+            .unwrap();
+
+        // Without the flag, nothing is checked
+        pruning
+            .debug_assertions(false)
+            .prune_with(&mut state, disable_all)
+            .unwrap();
+        assert_eq!(state.corpus().count(), 0);
+    }
+
+    #[test]
+    fn test_byte_budget() {
+        const ENTRIES: usize = 64;
+        const MAX_BYTES: usize = 1024;
+
+        fn enabled_bytes<S>(state: &S) -> usize
+        where
+            S: HasCorpus<Corpus: Corpus<Input = BytesInput>>,
+        {
+            let corpus = state.corpus();
+            corpus
+                .ids()
+                .map(|id| corpus.cloned_input_for_id(id).unwrap().len())
+                .sum()
+        }
+
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        for nth in 0..ENTRIES {
+            let len = 1 + state.rand_mut().below(nonzero!(64));
+            state
+                .corpus_mut()
+                .add(Testcase::new(BytesInput::new(vec![nth as u8; len])))
+                .unwrap();
+        }
+        assert!(enabled_bytes(&state) > MAX_BYTES);
+
+        // Random order
+        let mut random = state.clone();
+        CorpusPruning::byte_budget(MAX_BYTES)
+            .perform(&mut (), &mut (), &mut random, &mut ())
+            .unwrap();
+        assert!(enabled_bytes(&random) <= MAX_BYTES);
+        assert!(random.corpus().count() > 0);
+
+        // The sizes are cached, so the next run does not load the inputs again
+        let corpus = random.corpus();
+        for id in corpus.ids() {
+            let testcase = corpus.get(id).unwrap().borrow();
+            let size = testcase.metadata::<InputSizeMetadata>().unwrap();
+            let input = testcase.input().as_ref().unwrap();
+            assert_eq!(size.bytes, postcard::to_allocvec(input).unwrap().len());
+        }
+
+        // Lowest value first: the entries added last survive
+        let value = vec![|testcase: &Testcase<BytesInput>| {
+            f64::from(testcase.input().as_ref().unwrap().as_ref()[0])
+        }];
+        CorpusPruning::byte_budget_by(MAX_BYTES, value)
+            .perform(&mut (), &mut (), &mut state, &mut ())
+            .unwrap();
+        assert!(enabled_bytes(&state) <= MAX_BYTES);
+        let corpus = state.corpus();
+        let first_enabled = corpus.cloned_input_for_id(corpus.first().unwrap()).unwrap();
+        for nth in 0..corpus.count_disabled() {
+            let disabled = corpus.get_from_all(corpus.nth_disabled(nth)).unwrap();
+            let name = disabled.borrow().input().as_ref().unwrap().as_ref()[0];
+            assert!(name < first_enabled.as_ref()[0]);
+        }
+
+        // A budget below every entry still keeps one
+        CorpusPruning::byte_budget(0)
+            .perform(&mut (), &mut (), &mut state, &mut ())
+            .unwrap();
+        assert_eq!(state.corpus().count(), 1);
+    }
+
+    #[test]
+    fn test_compare_strategies() {
+        const ENTRIES: usize = 64;
+
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        for nth in 0..ENTRIES {
+            state
+                .corpus_mut()
+                .add(Testcase::new(BytesInput::new(vec![nth as u8])))
+                .unwrap();
+        }
+        let mut rand = *state.rand();
+
+        let pruning = CorpusPruning::new(0.5, PruningStrategy::Uniform);
+        let strategies = [
+            PruningStrategy::Uniform,
+            PruningStrategy::AgeWeighted { half_life: 4.0 },
+            PruningStrategy::Pareto,
+            PruningStrategy::ByteBudget { max_bytes: 64 },
+        ];
+        let comparison = pruning.compare_strategies(&strategies, &state).unwrap();
+
+        // Nothing changed, not even the metadata of the testcases
+        assert_eq!(state.corpus().count(), ENTRIES);
+        assert_eq!(state.corpus().count_disabled(), 0);
+        assert_eq!(state.rand_mut().next(), rand.next());
+        let corpus = state.corpus();
+        assert!(corpus
+            .ids()
+            .all(|id| corpus.get(id).unwrap().borrow().metadata_map().is_empty()));
+
+        let outcomes = comparison.outcomes();
+        assert_eq!(outcomes.len(), strategies.len());
+        for (outcome, strategy) in outcomes.iter().zip(strategies) {
+            assert_eq!(outcome.strategy, strategy);
+            assert_eq!(outcome.retained + outcome.disabled.len(), ENTRIES);
+        }
+        assert!(!outcomes[0].disabled.is_empty());
+        // The newest entries are (almost) never disabled with age weights
+        assert!(outcomes[1].disabled.len() < outcomes[0].disabled.len());
+        // Without metrics, every entry is on the Pareto front
+        assert!(outcomes[2].disabled.is_empty());
+
+        assert_eq!(comparison.overlap(0, 0), outcomes[0].disabled.len());
+        assert_eq!(comparison.overlap(0, 1), comparison.overlap(1, 0));
+        assert!(comparison.overlap(0, 1) <= outcomes[1].disabled.len());
+        assert_eq!(comparison.overlap(0, 2), 0);
+    }
+
+    #[test]
+    fn test_reservoir() {
+        const SIZE: usize = 32;
+        const ROUNDS: usize = 100;
+        const PER_ROUND: usize = 8;
+
+        let mut pruning = CorpusPruning::reservoir(SIZE);
+        assert_eq!(
+            *pruning.strategy(),
+            PruningStrategy::Reservoir { size: SIZE }
+        );
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        let mut last_added = None;
+        for round in 0..ROUNDS {
+            for _ in 0..PER_ROUND {
+                let id = state
+                    .corpus_mut()
+                    .add(Testcase::new(BytesInput::new(vec![round as u8])))
+                    .unwrap();
+                last_added = Some(id);
+            }
+            pruning
+                .perform(&mut (), &mut (), &mut state, &mut ())
+                .unwrap();
+            let added = (round + 1) * PER_ROUND;
+            assert_eq!(state.corpus().count(), added.min(SIZE));
+            assert_eq!(state.corpus().count_all(), added);
+        }
+
+        let reservoir = state.metadata::<ReservoirMetadata>().unwrap();
+        assert_eq!(reservoir.seen, ROUNDS * PER_ROUND);
+        assert_eq!(reservoir.last_seen, last_added);
+        let mut sample = reservoir.sample.clone();
+        sample.sort_unstable();
+        assert_eq!(sample, state.corpus().ids().collect::<Vec<_>>());
+
+        // The sample is spread over all rounds, not just the newest entries
+        let corpus = state.corpus();
+        let oldest_enabled = corpus
+            .ids()
+            .map(|id| corpus.cloned_input_for_id(id).unwrap().as_ref()[0])
+            .min()
+            .unwrap();
+        assert!(usize::from(oldest_enabled) < ROUNDS / 2, "{oldest_enabled}");
+    }
+
+    #[test]
+    fn test_reservoir_grace_period() {
+        const SIZE: usize = 4;
+        // Entries 9 to 15 were added less than 8 minutes ago
+        let mut state = FakeState::generate(0, 16, 32);
+        let mut pruning = CorpusPruning::reservoir(SIZE).grace_period(FAKE_ENTRY_INTERVAL * 8);
+        pruning
+            .perform(&mut (), &mut (), &mut state, &mut ())
+            .unwrap();
+
+        // The young entries are kept out of the sample, instead of taking the place of older ones
+        let reservoir = state.metadata::<ReservoirMetadata>().unwrap();
+        assert_eq!(reservoir.seen, 9);
+        assert!(reservoir.sample.iter().all(|id| id.0 < 9));
+        assert_eq!(
+            reservoir.deferred,
+            (9..16).map(CorpusId).collect::<Vec<_>>()
+        );
+        assert_eq!(state.corpus().count(), SIZE + 7);
+
+        // And offered once their grace period is over
+        let mut pruning = CorpusPruning::reservoir(SIZE);
+        pruning
+            .perform(&mut (), &mut (), &mut state, &mut ())
+            .unwrap();
+        let reservoir = state.metadata::<ReservoirMetadata>().unwrap();
+        assert_eq!(reservoir.seen, 16);
+        assert!(reservoir.deferred.is_empty());
+        assert_eq!(state.corpus().count(), SIZE);
+    }
+
+    #[test]
+    #[allow(clippy::cast_precision_loss)]
+    fn test_by_distance() {
+        const ENTRIES: usize = 64;
+        const RUNS: usize = 100;
+
+        let mut pruning = CorpusPruning {
+            prob: 0.5,
+            ..CorpusPruning::by_distance("distance".into())
+        };
+        assert_eq!(*pruning.strategy(), PruningStrategy::ByDistance);
+
+        // how often the closest/farthest half of the corpus was disabled
+        let mut near_disabled = 0;
+        let mut far_disabled = 0;
+        let mut unknown_disabled = 0;
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        for run in 0..RUNS {
+            // The input is the distance, the last entry has none, or an infinite one
+            for nth in 0..ENTRIES {
+                let mut testcase = Testcase::new(BytesInput::new(vec![nth as u8]));
+                let mut metadata = TargetDistanceMetadata::new();
+                if nth < ENTRIES - 1 {
+                    metadata.set_distance("distance".into(), nth as f64);
+                    metadata.set_distance("other".into(), (ENTRIES - nth) as f64);
+                } else if run % 2 == 1 {
+                    metadata.set_distance("distance".into(), f64::INFINITY);
+                }
+                testcase.add_metadata(metadata);
+                state.corpus_mut().add(testcase).unwrap();
+            }
+
+            pruning
+                .perform(&mut (), &mut (), &mut state, &mut ())
+                .unwrap();
+
+            let corpus = state.corpus();
+            for nth in corpus.count()..corpus.count_all() {
+                let id = corpus.nth_from_all(nth);
+                let testcase = corpus.get_from_all(id).unwrap().borrow();
+                let distance = usize::from(testcase.input().as_ref().unwrap().as_ref()[0]);
+                assert_ne!(distance, 0, "the closest entry was disabled");
+                if distance == ENTRIES - 1 {
+                    unknown_disabled += 1;
+                } else if distance < ENTRIES / 2 {
+                    near_disabled += 1;
+                } else {
+                    far_disabled += 1;
+                }
+            }
+
+            while state.corpus().count_all() > 0 {
+                let id = state.corpus().nth_from_all(0);
+                state.corpus_mut().remove(id).unwrap();
+            }
+        }
+
+        assert!(
+            far_disabled > 2 * near_disabled,
+            "near entries: {near_disabled} disabled, far entries: {far_disabled} disabled"
+        );
+        // Entries without a finite distance are disabled as if they were the farthest
+        assert!(unknown_disabled > RUNS / 4, "{unknown_disabled}");
+    }
+
+    #[test]
+    fn test_diverse() {
+        // The first byte is the region of the input, the second one a small offset within it
+        let features: fn(&Testcase<BytesInput>) -> Vec<f64> = |testcase| {
+            let bytes = testcase.input().as_ref().unwrap().as_ref();
+            vec![
+                f64::from(bytes[0]) * 100.0 + f64::from(bytes[1]),
+                f64::from(bytes[1]),
+            ]
+        };
+        // Two crowded regions, and a single outlier
+        let sizes = [20_usize, 30, 1];
+
+        for prob in [0.5, 1.0] {
+            let mut pruning = CorpusPruning {
+                prob,
+                ..CorpusPruning::diverse(features, sizes.len())
+            };
+            assert_eq!(
+                *pruning.strategy(),
+                PruningStrategy::Diverse {
+                    clusters: sizes.len()
+                }
+            );
+
+            let mut state = StdState::nop::<BytesInput>().unwrap();
+            for _ in 0..16 {
+                for (region, size) in sizes.iter().enumerate() {
+                    for offset in 0..*size {
+                        state
+                            .corpus_mut()
+                            .add(Testcase::new(BytesInput::new(vec![
+                                region as u8,
+                                (offset % 5) as u8,
+                            ])))
+                            .unwrap();
+                    }
+                }
+                pruning
+                    .perform(&mut (), &mut (), &mut state, &mut ())
+                    .unwrap();
+
+                let corpus = state.corpus();
+                let mut retained = [0; 3];
+                for id in corpus.ids() {
+                    let testcase = corpus.get(id).unwrap().borrow();
+                    retained[usize::from(testcase.input().as_ref().unwrap().as_ref()[0])] += 1;
+                }
+                assert!(
+                    retained.iter().all(|retained| *retained > 0),
+                    "a cluster lost all entries: {retained:?}"
+                );
+                if (prob - 1.0).abs() < f64::EPSILON {
+                    assert_eq!(retained, [1, 1, 1]);
+                }
+
+                while state.corpus().count_all() > 0 {
+                    let id = state.corpus().nth_from_all(0);
+                    state.corpus_mut().remove(id).unwrap();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! The [`SolutionPruning`] stage removes solutions that crash the same way as an earlier one,
+//! so each unique crash is triaged once.
+//!
+//! Unlike [`crate::stages::CorpusPruning`], this does not look at coverage, but at a crash signature,
+//! such as the hash of the stacktrace, see [`SolutionSignature`].
+
+use alloc::vec::Vec;
+
+use hashbrown::HashSet;
+
+use crate::{
+    corpus::{Corpus, CorpusId, CrashSignatureMetadata, Testcase},
+    stages::Stage,
+    state::HasSolutions,
+    Error, HasMetadata,
+};
+
+/// Extracts the crash signature of a solution, solutions with the same signature are duplicates.
+///
+/// Solutions without a signature are never considered duplicates.
+/// Implemented for closures over the [`Testcase`].
+pub trait SolutionSignature<I> {
+    /// The signature of the `testcase`, if it has one
+    fn signature(&mut self, testcase: &Testcase<I>) -> Option<u64>;
+}
+
+impl<F, I> SolutionSignature<I> for F
+where
+    F: FnMut(&Testcase<I>) -> Option<u64>,
+{
+    fn signature(&mut self, testcase: &Testcase<I>) -> Option<u64> {
+        self(testcase)
+    }
+}
+
+/// The default [`SolutionSignature`], the [`CrashSignatureMetadata`] of the solution
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MetadataSignature;
+
+impl<I> SolutionSignature<I> for MetadataSignature {
+    fn signature(&mut self, testcase: &Testcase<I>) -> Option<u64> {
+        testcase
+            .metadata::<CrashSignatureMetadata>()
+            .ok()
+            .map(|meta| meta.signature)
+    }
+}
+
+/// A stage that removes solutions with the same signature as an earlier solution,
+/// keeping the first solution found for each signature as its representative.
+///
+/// Each run only looks at the solutions added since the last one.
+#[derive(Debug, Clone)]
+pub struct SolutionPruning<SF = MetadataSignature> {
+    signature: SF,
+    removed: usize,
+    /// The signatures of the solutions kept so far
+    seen: HashSet<u64>,
+    /// The last solution kept, the next run starts after it
+    last_kept: Option<CorpusId>,
+}
+
+impl SolutionPruning {
+    /// Create a new [`SolutionPruning`] stage, using the [`CrashSignatureMetadata`] of the solutions
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_signature(MetadataSignature)
+    }
+}
+
+impl Default for SolutionPruning {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<SF> SolutionPruning<SF> {
+    /// Create a new [`SolutionPruning`] stage, using the given signature function
+    #[must_use]
+    pub fn with_signature(signature: SF) -> Self {
+        Self {
+            signature,
+            removed: 0,
+            seen: HashSet::new(),
+            last_kept: None,
+        }
+    }
+
+    /// The number of solutions this stage removed so far
+    #[must_use]
+    pub fn removed(&self) -> usize {
+        self.removed
+    }
+
+    /// Remove the solutions of the `solutions` corpus with the signature of an earlier one,
+    /// looking only at the solutions added since the last call.
+    ///
+    /// If the last solution this kept is gone, e.g., another corpus is passed, all solutions are checked again.
+    /// Returns the ids of the removed solutions.
+    pub fn prune<C>(&mut self, solutions: &mut C) -> Result<Vec<CorpusId>, Error>
+    where
+        C: Corpus,
+        SF: SolutionSignature<C::Input>,
+    {
+        let mut next = match self.last_kept {
+            Some(last) if solutions.get(last).is_ok() => solutions.next(last),
+            _ => {
+                self.seen.clear();
+                solutions.first()
+            }
+        };
+        let mut duplicates = Vec::new();
+        while let Some(id) = next {
+            next = solutions.next(id);
+            let testcase = solutions.get(id)?.borrow();
+            match self.signature.signature(&testcase) {
+                Some(signature) if !self.seen.insert(signature) => duplicates.push(id),
+                _ => self.last_kept = Some(id),
+            }
+        }
+
+        for id in &duplicates {
+            solutions.remove(*id)?;
+        }
+        self.removed += duplicates.len();
+        if !duplicates.is_empty() {
+            log::info!(
+                "Removed {} duplicate solutions, {} unique crash signatures",
+                duplicates.len(),
+                self.seen.len()
+            );
+        }
+        Ok(duplicates)
+    }
+}
+
+impl<E, EM, S, SF, Z> Stage<E, EM, S, Z> for SolutionPruning<SF>
+where
+    S: HasSolutions,
+    SF: SolutionSignature<<S::Solutions as Corpus>::Input>,
+{
+    fn perform(
+        &mut self,
+        _fuzzer: &mut Z,
+        _executor: &mut E,
+        state: &mut S,
+        _manager: &mut EM,
+    ) -> Result<(), Error> {
+        self.prune(state.solutions_mut())?;
+        Ok(())
+    }
+
+    #[inline]
+    fn should_restart(&mut self, _state: &mut S) -> Result<bool, Error> {
+        // Not executing the target, so restart safety is not needed
+        Ok(true)
+    }
+
+    #[inline]
+    fn clear_progress(&mut self, _state: &mut S) -> Result<(), Error> {
+        // Not executing the target, so restart safety is not needed
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+
+    use libafl_bolts::HasLen;
+
+    use super::SolutionPruning;
+    use crate::{
+        corpus::{Corpus, CorpusId, CrashSignatureMetadata, Testcase},
+        inputs::BytesInput,
+        stages::Stage,
+        state::{HasSolutions, StdState},
+        HasMetadata,
+    };
+
+    #[test]
+    fn test_duplicates_collapse() {
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        for (byte, signature) in [
+            (0_u8, Some(1)),
+            (1, Some(2)),
+            (2, Some(1)),
+            (3, None),
+            (4, Some(1)),
+        ] {
+            let mut testcase = Testcase::new(BytesInput::new(vec![byte; usize::from(byte) + 1]));
+            if let Some(signature) = signature {
+                testcase.add_metadata(CrashSignatureMetadata { signature });
+            }
+            state.solutions_mut().add(testcase).unwrap();
+        }
+
+        let mut pruning = SolutionPruning::new();
+        pruning
+            .perform(&mut (), &mut (), &mut state, &mut ())
+            .unwrap();
+        assert_eq!(pruning.removed(), 2);
+        let kept: Vec<_> = state.solutions().ids().collect();
+        assert_eq!(kept, [CorpusId(0), CorpusId(1), CorpusId(3)]);
+
+        // Nothing left to collapse
+        pruning
+            .perform(&mut (), &mut (), &mut state, &mut ())
+            .unwrap();
+        assert_eq!(pruning.removed(), 2);
+
+        // A new solution is checked against the signatures seen before
+        let mut testcase = Testcase::new(BytesInput::new(vec![5; 6]));
+        testcase.add_metadata(CrashSignatureMetadata { signature: 2 });
+        let id = state.solutions_mut().add(testcase).unwrap();
+        let removed = pruning.prune(state.solutions_mut()).unwrap();
+        assert_eq!(removed, [id]);
+        assert_eq!(pruning.removed(), 3);
+
+        // A custom signature, the parity of the input length
+        let mut pruning = SolutionPruning::with_signature(|testcase: &Testcase<BytesInput>| {
+            testcase
+                .input()
+                .as_ref()
+                .map(|input| input.len() as u64 % 2)
+        });
+        let removed = pruning.prune(state.solutions_mut()).unwrap();
+        assert_eq!(removed, [CorpusId(3)]);
+        assert_eq!(state.solutions().count(), 2);
+    }
+}
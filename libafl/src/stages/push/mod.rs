@@ -31,7 +31,8 @@ use crate::{
     schedulers::Scheduler,
     stages::{RetryCountRestartHelper, Stage},
     state::{HasCorpus, HasExecutions, HasLastReportTime, HasRand},
-    Error, EvaluatorObservers, ExecutesInput, ExecutionProcessor, HasMetadata, HasScheduler,
+    Error, EvaluatorObservers, ExecutesInput, ExecutionProcessor, HasInputFilter, HasMetadata,
+    HasScheduler,
 };
 
 // The shared state for all [`PushStage`]s
@@ -193,6 +194,19 @@ pub trait PushStage<EM, I, OT, S, Z> {
         Ok(())
     }
 
+    /// Called instead of [`PushStage::post_exec`] if the input filter rejected the input, so it never ran,
+    /// see [`HasInputFilter`].
+    #[inline]
+    fn post_skip(
+        &mut self,
+        _fuzzer: &mut Z,
+        _state: &mut S,
+        _event_mgr: &mut EM,
+        _input: I,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
     /// Called after the stage finished (`pre_exec` returned `None`)
     #[inline]
     fn deinit(
@@ -265,6 +279,7 @@ where
     Z: ExecutesInput<E, EM, <S::Corpus as Corpus>::Input, S>
         + ExecutionProcessor<EM, <S::Corpus as Corpus>::Input, OT, S>
         + EvaluatorObservers<E, EM, <S::Corpus as Corpus>::Input, OT>
+        + HasInputFilter<<S::Corpus as Corpus>::Input, S>
         + HasScheduler<<S::Corpus as Corpus>::Input, S>,
 {
     fn perform(
@@ -295,6 +310,11 @@ where
                     None => break,
                 };
 
+            if fuzzer.skips_input(state, &input) {
+                push_stage.post_skip(fuzzer, state, event_mgr, input)?;
+                continue;
+            }
+
             let exit_kind = fuzzer.execute_input(state, executor, event_mgr, &input)?;
 
             push_stage.post_exec(
@@ -178,6 +178,21 @@ where
         Ok(())
     }
 
+    fn post_skip(
+        &mut self,
+        _fuzzer: &mut Z,
+        state: &mut S,
+        _event_mgr: &mut EM,
+        _input: <S::Corpus as Corpus>::Input,
+    ) -> Result<(), Error> {
+        start_timer!(state);
+        self.mutator.post_exec(state, None)?;
+        mark_feature_time!(state, PerfFeature::MutatePostExec);
+        self.testcases_done += 1;
+
+        Ok(())
+    }
+
     #[inline]
     fn deinit(
         &mut self,
@@ -0,0 +1,209 @@
+//! The [`RestartStage`] restarts the fuzzer process after it ran for a while,
+//! for example, to get rid of memory leaks in the target.
+//!
+//! By default, the process exits and relies on a supervisor, such as a restarting event manager,
+//! to launch it again. With [`RestartStage::self_reexec`], the process replaces itself instead.
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::time::Duration;
+use std::{env, ffi::OsString, process, process::Command};
+
+use libafl_bolts::{current_time, os::startable_self};
+
+use crate::{
+    events::EventRestarter,
+    stages::Stage,
+    state::{State, UsesState},
+    Error,
+};
+
+/// The default env var prefix the reattach config is written to on [`RestartStage::self_reexec`]
+pub const RESTART_STAGE_ENV: &str = "_LIBAFL_RESTART_STAGE_CLIENT";
+
+/// An event manager that can write its config to env vars, for a new process to reattach to it
+///
+/// Restarting managers delegate to the manager they wrap. Managers without a connection,
+/// such as the [`crate::events::SimpleEventManager`], have nothing to write.
+pub trait ReattachableEventManager {
+    /// Write the config of this manager to env vars starting with `env_name`
+    fn to_env(&self, env_name: &str);
+}
+
+/// A [`Stage`] that restarts the fuzzer once it ran for `interval`.
+///
+/// Before restarting, the manager's [`EventRestarter::on_restart`] is called, so restarting managers
+/// can hand the state over to the next process.
+#[derive(Debug, Clone)]
+pub struct RestartStage {
+    interval: Duration,
+    started: Duration,
+    self_reexec: bool,
+    env_name: Cow<'static, str>,
+}
+
+impl RestartStage {
+    /// Create a new [`RestartStage`], restarting once the fuzzer ran for `interval`
+    #[must_use]
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            started: current_time(),
+            self_reexec: false,
+            env_name: Cow::Borrowed(RESTART_STAGE_ENV),
+        }
+    }
+
+    /// Re-execute the current binary with the same args, instead of exiting to an external supervisor.
+    ///
+    /// The manager writes its reattach config to env vars starting with [`RestartStage::env_name`],
+    /// the new process can reattach using, e.g., [`crate::events::LlmpEventManagerBuilder::build_existing_client_from_env`].
+    /// Only supported on unix.
+    #[must_use]
+    pub fn self_reexec(mut self, self_reexec: bool) -> Self {
+        self.self_reexec = self_reexec;
+        self
+    }
+
+    /// Write the reattach config to env vars starting with `env_name`, defaults to [`RESTART_STAGE_ENV`]
+    #[must_use]
+    pub fn with_env_name<N>(mut self, env_name: N) -> Self
+    where
+        N: Into<Cow<'static, str>>,
+    {
+        self.env_name = env_name.into();
+        self
+    }
+
+    /// The prefix of the env vars the reattach config is written to
+    #[must_use]
+    pub fn env_name(&self) -> &str {
+        &self.env_name
+    }
+
+    /// Prepare the [`Command`] to re-execute the current binary with the same args and the reattach config of `manager`
+    fn reexec_command<EM>(&self, manager: &EM) -> Result<Command, Error>
+    where
+        EM: ReattachableEventManager,
+    {
+        manager.to_env(&self.env_name);
+        let reattach_env: Vec<(String, OsString)> = env::vars_os()
+            .filter_map(|(key, value)| Some((key.into_string().ok()?, value)))
+            .filter(|(key, _)| key.starts_with(&*self.env_name))
+            .collect();
+
+        let mut command = startable_self()?;
+        command.envs(reattach_env);
+        Ok(command)
+    }
+
+    #[cfg(unix)]
+    fn reexec<EM>(&self, manager: &EM) -> Result<(), Error>
+    where
+        EM: ReattachableEventManager,
+    {
+        use std::os::unix::process::CommandExt;
+
+        let mut command = self.reexec_command(manager)?;
+        // `exec` only returns on failure
+        Err(Error::illegal_state(format!(
+            "Failed to re-execute the fuzzer: {}",
+            command.exec()
+        )))
+    }
+
+    #[cfg(not(unix))]
+    fn reexec<EM>(&self, _manager: &EM) -> Result<(), Error>
+    where
+        EM: ReattachableEventManager,
+    {
+        Err(Error::unsupported(
+            "RestartStage::self_reexec is only supported on unix",
+        ))
+    }
+}
+
+impl<E, EM, S, Z> Stage<E, EM, S, Z> for RestartStage
+where
+    EM: EventRestarter + UsesState<State = S> + ReattachableEventManager,
+    S: State,
+{
+    fn perform(
+        &mut self,
+        _fuzzer: &mut Z,
+        _executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        if current_time().saturating_sub(self.started) < self.interval {
+            return Ok(());
+        }
+
+        manager.on_restart(state)?;
+        if self.self_reexec {
+            log::info!("Re-executing the fuzzer after {:?}", self.interval);
+            self.reexec(manager)
+        } else {
+            log::info!("Restarting the fuzzer after {:?}", self.interval);
+            process::exit(0)
+        }
+    }
+
+    #[inline]
+    fn should_restart(&mut self, _state: &mut S) -> Result<bool, Error> {
+        // Not executing the target, so restart safety is not needed
+        Ok(true)
+    }
+
+    #[inline]
+    fn clear_progress(&mut self, _state: &mut S) -> Result<(), Error> {
+        // Not executing the target, so restart safety is not needed
+        Ok(())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use core::time::Duration;
+    use std::{env, ffi::OsStr, vec::Vec};
+
+    use super::{ReattachableEventManager, RestartStage};
+
+    struct FakeManager;
+
+    impl ReattachableEventManager for FakeManager {
+        fn to_env(&self, env_name: &str) {
+            env::set_var(format!("{env_name}_SENDER"), "1");
+            env::set_var(format!("{env_name}_RECEIVER"), "2");
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_restart_stage_reexec_command() {
+        let stage = RestartStage::new(Duration::from_secs(1))
+            .self_reexec(true)
+            .with_env_name("_LIBAFL_TEST_RESTART_STAGE");
+        let command = stage.reexec_command(&FakeManager).unwrap();
+
+        assert_eq!(command.get_program(), env::current_exe().unwrap());
+        let args: Vec<_> = command.get_args().collect();
+        let expected: Vec<_> = env::args_os().skip(1).collect();
+        assert_eq!(args, expected);
+
+        let mut envs: Vec<_> = command.get_envs().collect();
+        envs.sort();
+        assert_eq!(
+            envs,
+            [
+                (
+                    OsStr::new("_LIBAFL_TEST_RESTART_STAGE_RECEIVER"),
+                    Some(OsStr::new("2"))
+                ),
+                (
+                    OsStr::new("_LIBAFL_TEST_RESTART_STAGE_SENDER"),
+                    Some(OsStr::new("1"))
+                ),
+            ]
+        );
+    }
+}
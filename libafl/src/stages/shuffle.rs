@@ -0,0 +1,163 @@
+//! The [`CorpusShuffle`] stage reshuffles the order in which the [`crate::schedulers::OrderedQueueScheduler`] walks the corpus.
+//!
+//! After [`crate::stages::CorpusPruning`] disabled a chunk of the corpus, the remaining entries
+//! may be biased by their insertion order. Shuffling the traversal order counteracts this.
+
+use alloc::vec::Vec;
+use core::num::NonZeroUsize;
+
+use libafl_bolts::rands::{derive_seed, Rand, StdRand};
+
+use crate::{
+    corpus::{Corpus, CorpusId},
+    schedulers::QueueOrderMetadata,
+    stages::Stage,
+    state::HasCorpus,
+    Error, HasMetadata,
+};
+
+/// A [`Stage`] that shuffles the traversal order over the enabled corpus entries.
+///
+/// The order is stored as [`QueueOrderMetadata`] in the state.
+/// The stage uses its own, seeded, rng, so the same seed on the same corpus yields the same order.
+#[derive(Debug, Clone)]
+pub struct CorpusShuffle {
+    rand: StdRand,
+}
+
+impl CorpusShuffle {
+    /// Create a new [`CorpusShuffle`] stage, seeding its rng with `seed`
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rand: StdRand::with_seed(seed),
+        }
+    }
+
+    /// Create a new [`CorpusShuffle`] stage for a deterministic campaign, seeding its rng from the `master_seed`.
+    ///
+    /// `index` tells apart multiple shuffle stages of the same fuzzer, see [`derive_seed`].
+    #[must_use]
+    pub fn with_master_seed(master_seed: u64, index: usize) -> Self {
+        Self::new(derive_seed(master_seed, "CorpusShuffle", index))
+    }
+
+    /// Shuffle the ids of the enabled entries of the given corpus
+    pub fn shuffled_ids<C>(&mut self, corpus: &C) -> Vec<CorpusId>
+    where
+        C: Corpus,
+    {
+        let mut ids = corpus.ids().collect::<Vec<_>>();
+        // Fisher-Yates
+        for i in (1..ids.len()).rev() {
+            let j = self.rand.below(NonZeroUsize::new(i + 1).unwrap());
+            ids.swap(i, j);
+        }
+        ids
+    }
+}
+
+impl<E, EM, S, Z> Stage<E, EM, S, Z> for CorpusShuffle
+where
+    S: HasCorpus + HasMetadata,
+{
+    fn perform(
+        &mut self,
+        _fuzzer: &mut Z,
+        _executor: &mut E,
+        state: &mut S,
+        _manager: &mut EM,
+    ) -> Result<(), Error> {
+        let order = self.shuffled_ids(state.corpus());
+        state.add_metadata(QueueOrderMetadata::new(order));
+        Ok(())
+    }
+
+    #[inline]
+    fn should_restart(&mut self, _state: &mut S) -> Result<bool, Error> {
+        // Not executing the target, so restart safety is not needed
+        Ok(true)
+    }
+
+    #[inline]
+    fn clear_progress(&mut self, _state: &mut S) -> Result<(), Error> {
+        // Not executing the target, so restart safety is not needed
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::{
+        corpus::{Corpus, Testcase},
+        inputs::BytesInput,
+        schedulers::{OrderedQueueScheduler, QueueOrderMetadata, Scheduler},
+        stages::{CorpusShuffle, Stage},
+        state::{HasCorpus, StdState},
+        HasMetadata,
+    };
+
+    #[test]
+    fn test_corpus_shuffle() {
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        for nth in 0..32_u8 {
+            let mut testcase = Testcase::new(BytesInput::new(vec![nth]));
+            if nth % 4 == 0 {
+                testcase.set_disabled(true);
+                state.corpus_mut().add_disabled(testcase).unwrap();
+            } else {
+                state.corpus_mut().add(testcase).unwrap();
+            }
+        }
+        let enabled = state.corpus().ids().collect::<Vec<_>>();
+
+        let mut first = state.clone();
+        let mut second = state.clone();
+        CorpusShuffle::new(1337)
+            .perform(&mut (), &mut (), &mut first, &mut ())
+            .unwrap();
+        CorpusShuffle::new(1337)
+            .perform(&mut (), &mut (), &mut second, &mut ())
+            .unwrap();
+
+        let order = first.metadata::<QueueOrderMetadata>().unwrap().order();
+        assert_eq!(
+            order,
+            second.metadata::<QueueOrderMetadata>().unwrap().order()
+        );
+        assert_ne!(order, enabled.as_slice());
+
+        // Every enabled id shows up exactly once
+        let mut sorted = order.to_vec();
+        sorted.sort();
+        assert_eq!(sorted, enabled);
+
+        // The ordered queue scheduler follows the shuffled order, also after a removal
+        let mut order = order.to_vec();
+        let mut scheduler = OrderedQueueScheduler::new();
+        for id in order.iter().chain(&order) {
+            assert_eq!(
+                <OrderedQueueScheduler as Scheduler<BytesInput, _>>::next(
+                    &mut scheduler,
+                    &mut first
+                )
+                .unwrap(),
+                *id
+            );
+        }
+        let removed = order.remove(order.len() / 2);
+        first.corpus_mut().remove(removed).unwrap();
+        for id in order.iter().chain(&order) {
+            assert_eq!(
+                <OrderedQueueScheduler as Scheduler<BytesInput, _>>::next(
+                    &mut scheduler,
+                    &mut first
+                )
+                .unwrap(),
+                *id
+            );
+        }
+    }
+}
@@ -1,22 +1,28 @@
 //! The [`SyncFromDiskStage`] is a stage that imports inputs from disk for e.g. sync with AFL
+//!
+//! The [`SyncTokensStage`] shares the [`Tokens`] of each node with the other nodes over the event manager.
 
 use alloc::{
     borrow::{Cow, ToOwned},
+    boxed::Box,
     vec::Vec,
 };
 use core::{marker::PhantomData, time::Duration};
 use std::path::{Path, PathBuf};
 
+use hashbrown::HashSet;
 use libafl_bolts::{current_time, fs::find_new_files_rec, shmem::ShMemProvider, Named};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     corpus::{Corpus, CorpusId, HasCurrentCorpusId},
-    events::{llmp::LlmpEventConverter, Event, EventConfig, EventFirer},
+    events::{llmp::LlmpEventConverter, CustomBufEventResult, Event, EventConfig, EventFirer},
     executors::{Executor, ExitKind, HasObservers},
     fuzzer::{Evaluator, EvaluatorObservers, ExecutionProcessor},
     inputs::{Input, InputConverter, UsesInput},
-    stages::{RetryCountRestartHelper, Stage},
+    monitors::{AggregatorOps, UserStats, UserStatsValue, DICTIONARY_SIZE_STAT},
+    mutators::Tokens,
+    stages::{CorpusQuiesceGuard, RetryCountRestartHelper, Stage},
     state::{HasCorpus, HasExecutions, HasRand, MaybeHasClientPerfMonitor, State, Stoppable},
     Error, HasMetadata, HasNamedMetadata,
 };
@@ -86,6 +92,11 @@ where
         state: &mut S,
         manager: &mut EM,
     ) -> Result<(), Error> {
+        if CorpusQuiesceGuard::is_held(state) {
+            // The corpus is being reorganized, sync next time
+            return Ok(());
+        }
+
         let last = state
             .metadata_map()
             .get::<SyncFromDiskMetadata>()
@@ -133,7 +144,9 @@ where
         }
 
         #[cfg(feature = "introspection")]
-        state.introspection_monitor_mut().finish_stage();
+        state
+            .introspection_monitor_mut()
+            .finish_named_stage(&self.name);
 
         Ok(())
     }
@@ -260,6 +273,11 @@ where
         state: &mut S,
         manager: &mut EM,
     ) -> Result<(), Error> {
+        if CorpusQuiesceGuard::is_held(state) {
+            // The corpus is being reorganized, sync next time
+            return Ok(());
+        }
+
         if self.client.can_convert() {
             let last_id = state
                 .metadata_map()
@@ -282,6 +300,9 @@ where
                         client_config: EventConfig::AlwaysUnique,
                         time: current_time(),
                         forward_id: None,
+                        generation: None,
+                        calibration: None,
+                        forced: false,
                         #[cfg(all(unix, feature = "multi_machine"))]
                         node_id: None,
                     },
@@ -306,7 +327,9 @@ where
 
         self.client.process(fuzzer, state, executor, manager)?;
         #[cfg(feature = "introspection")]
-        state.introspection_monitor_mut().finish_stage();
+        state
+            .introspection_monitor_mut()
+            .finish_named_stage("SyncFromBrokerStage");
         Ok(())
     }
 
@@ -337,3 +360,360 @@ where
         Self { client }
     }
 }
+
+/// The tag of the [`Event::CustomBuf`] events that carry the tokens of a [`SyncTokensStage`]
+pub const TOKENS_SYNC_TAG: &str = "tokens";
+
+/// The default maximum number of tokens a [`SyncTokensStage`] announces at once
+pub const DEFAULT_TOKENS_SYNC_BATCH: usize = 256;
+
+/// The default maximum number of tokens a [`SyncTokensStage`] accepts from other nodes
+pub const DEFAULT_MAX_FOREIGN_TOKENS: usize = 4096;
+
+/// The progress of a [`SyncTokensStage`], in the state of each node
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SyncTokensMetadata {
+    /// The last time tokens were announced
+    pub last_time: Duration,
+    /// The number of [`Tokens`] that were already looked at, in the order they were added
+    pub announced: usize,
+    /// The tokens accepted from other nodes, which are never announced again, except by a relay
+    pub foreign: HashSet<Vec<u8>>,
+}
+
+libafl_bolts::impl_serdeany!(SyncTokensMetadata);
+
+/// A stage that shares the [`Tokens`] each node learned, e.g., from an autodict, with the other nodes.
+///
+/// Every `interval`, the tokens added to the [`Tokens`] metadata since the last run are announced
+/// in an [`Event::CustomBuf`] tagged [`TOKENS_SYNC_TAG`], at most [`SyncTokensStage::max_batch`] at once;
+/// the rest follow in the next runs. The other nodes merge them into their [`Tokens`] in the handler
+/// from [`SyncTokensStage::handler`], which has to be added to each event manager
+/// with [`crate::events::HasCustomBufHandlers::add_custom_buf_handler`].
+/// Tokens learned this way are not announced again, so they don't bounce around the fleet,
+/// except by a relay, see [`SyncTokensStage::relay`].
+/// The size of the dictionary is reported as the [`DICTIONARY_SIZE_STAT`] user stat.
+#[derive(Debug, Clone)]
+pub struct SyncTokensStage {
+    interval: Duration,
+    max_batch: usize,
+    max_foreign: usize,
+    relay: bool,
+}
+
+impl SyncTokensStage {
+    /// Creates a new [`SyncTokensStage`], announcing new tokens every `interval`
+    #[must_use]
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            max_batch: DEFAULT_TOKENS_SYNC_BATCH,
+            max_foreign: DEFAULT_MAX_FOREIGN_TOKENS,
+            relay: false,
+        }
+    }
+
+    /// The maximum number of tokens to announce at once, [`DEFAULT_TOKENS_SYNC_BATCH`] by default
+    #[must_use]
+    pub fn max_batch(mut self, max_batch: usize) -> Self {
+        self.max_batch = max_batch;
+        self
+    }
+
+    /// The maximum number of tokens the [`SyncTokensStage::handler`] accepts from other nodes,
+    /// in total, to keep a chatty node from flooding the dictionary, [`DEFAULT_MAX_FOREIGN_TOKENS`] by default.
+    ///
+    /// Tokens beyond the cap are dropped.
+    #[must_use]
+    pub fn max_foreign(mut self, max_foreign: usize) -> Self {
+        self.max_foreign = max_foreign;
+        self
+    }
+
+    /// Also announce the tokens learned from other nodes.
+    ///
+    /// Turn this on for the node the fleet aggregates its dictionaries at, such as the main node of a
+    /// [`crate::events::CentralizedEventManager`]: the nodes that only hear from it still converge.
+    /// A relayed token is not announced back by the nodes that learn it, so it does not loop.
+    /// Off by default.
+    #[must_use]
+    pub fn relay(mut self, relay: bool) -> Self {
+        self.relay = relay;
+        self
+    }
+
+    /// The handler that merges the tokens announced by other nodes into the [`Tokens`] of the state,
+    /// see [`crate::events::HasCustomBufHandlers::add_custom_buf_handler`]
+    #[must_use]
+    #[allow(clippy::type_complexity)]
+    pub fn handler<S>(
+        &self,
+    ) -> Box<dyn FnMut(&mut S, &str, &[u8]) -> Result<CustomBufEventResult, Error>>
+    where
+        S: HasMetadata,
+    {
+        let max_foreign = self.max_foreign;
+        Box::new(move |state, tag, buf| {
+            if tag != TOKENS_SYNC_TAG {
+                return Ok(CustomBufEventResult::Next);
+            }
+            let tokens: Vec<Vec<u8>> = postcard::from_bytes(buf)?;
+            let mut foreign = state
+                .metadata_map_mut()
+                .remove::<SyncTokensMetadata>()
+                .map_or_else(SyncTokensMetadata::default, |foreign| *foreign);
+            let dictionary = state.metadata_or_insert_with(Tokens::default);
+            let mut accepted = 0;
+            for token in tokens {
+                if foreign.foreign.len() >= max_foreign {
+                    log::debug!("Dropping tokens from other nodes, {max_foreign} were accepted");
+                    break;
+                }
+                if dictionary.add_token(&token) {
+                    foreign.foreign.insert(token);
+                    accepted += 1;
+                }
+            }
+            log::debug!("Accepted {accepted} tokens from other nodes");
+            state.add_metadata(foreign);
+            Ok(CustomBufEventResult::Handled)
+        })
+    }
+}
+
+impl<E, EM, S, Z> Stage<E, EM, S, Z> for SyncTokensStage
+where
+    EM: EventFirer<State = S>,
+    S: State + HasMetadata,
+{
+    fn perform(
+        &mut self,
+        _fuzzer: &mut Z,
+        _executor: &mut E,
+        state: &mut S,
+        manager: &mut EM,
+    ) -> Result<(), Error> {
+        let now = current_time();
+        let progress = state.metadata_or_insert_with(SyncTokensMetadata::default);
+        if progress.last_time != Duration::ZERO
+            && now.saturating_sub(progress.last_time) < self.interval
+        {
+            return Ok(());
+        }
+        progress.last_time = now;
+        let mut announced = progress.announced;
+
+        let Ok(dictionary) = state.metadata::<Tokens>() else {
+            return Ok(());
+        };
+        let foreign = &state.metadata::<SyncTokensMetadata>()?.foreign;
+        let mut batch = Vec::new();
+        for token in dictionary.tokens().iter().skip(announced) {
+            if batch.len() >= self.max_batch {
+                break;
+            }
+            announced += 1;
+            if self.relay || !foreign.contains(token) {
+                batch.push(token.clone());
+            }
+        }
+        let size = dictionary.len();
+        state.metadata_mut::<SyncTokensMetadata>()?.announced = announced;
+
+        if !batch.is_empty() {
+            log::debug!("Announcing {} tokens to other nodes", batch.len());
+            manager.fire(
+                state,
+                Event::CustomBuf {
+                    buf: postcard::to_allocvec(&batch)?,
+                    tag: TOKENS_SYNC_TAG.into(),
+                },
+            )?;
+        }
+        manager.fire(
+            state,
+            Event::UpdateUserStats {
+                name: Cow::Borrowed(DICTIONARY_SIZE_STAT),
+                value: UserStats::new(UserStatsValue::Number(size as u64), AggregatorOps::Avg),
+                phantom: PhantomData,
+            },
+        )
+    }
+
+    #[inline]
+    fn should_restart(&mut self, _state: &mut S) -> Result<bool, Error> {
+        // No restart handling needed - does not execute the target.
+        Ok(true)
+    }
+
+    #[inline]
+    fn clear_progress(&mut self, _state: &mut S) -> Result<(), Error> {
+        // Not needed - does not execute the target.
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::{vec, vec::Vec};
+    use core::time::Duration;
+
+    use hashbrown::HashSet;
+    use libafl_bolts::{rands::StdRand, tuples::tuple_list, ClientId};
+    use serial_test::serial;
+
+    use crate::{
+        corpus::InMemoryCorpus,
+        events::{
+            CustomBufEventResult, Event, EventConfig, EventManagerHook, EventProcessor,
+            HasCustomBufHandlers, LlmpEventManager,
+        },
+        executors::{ExitKind, InProcessExecutor},
+        feedbacks::ConstFeedback,
+        inputs::BytesInput,
+        mutators::Tokens,
+        schedulers::QueueScheduler,
+        stages::{Stage, SyncTokensMetadata, SyncTokensStage, TOKENS_SYNC_TAG},
+        state::{State, StdState},
+        testing::llmp_ring,
+        Error, HasMetadata, StdFuzzer,
+    };
+
+    /// Drops the user stats, as a broker would, since the nodes talk to each other directly
+    struct DropUserStats;
+
+    impl<S> EventManagerHook<S> for DropUserStats
+    where
+        S: State,
+    {
+        fn pre_exec(
+            &mut self,
+            _state: &mut S,
+            _client_id: ClientId,
+            event: &Event<S::Input>,
+        ) -> Result<bool, Error> {
+            Ok(!matches!(event, Event::UpdateUserStats { .. }))
+        }
+    }
+
+    #[test]
+    #[serial]
+    #[cfg_attr(miri, ignore)]
+    fn test_sync_tokens() {
+        let mut feedback = ConstFeedback::new(false);
+        let mut objective = ConstFeedback::new(false);
+        let mut states = (0..3)
+            .map(|seed| {
+                StdState::new(
+                    StdRand::with_seed(seed),
+                    InMemoryCorpus::<BytesInput>::new(),
+                    InMemoryCorpus::new(),
+                    &mut feedback,
+                    &mut objective,
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        let mut fuzzer = StdFuzzer::new(QueueScheduler::new(), feedback, objective);
+
+        // The other nodes listen to the main node, which listens to the last node only
+        let clients = llmp_ring();
+        let mut stages = [
+            SyncTokensStage::new(Duration::ZERO).relay(true),
+            SyncTokensStage::new(Duration::ZERO),
+            SyncTokensStage::new(Duration::ZERO).max_batch(2),
+        ];
+        let mut managers = clients
+            .into_iter()
+            .zip(&stages)
+            .map(|(client, stage)| {
+                let mut manager = LlmpEventManager::builder()
+                    .hooks(tuple_list!(DropUserStats))
+                    .build_from_client(client, EventConfig::AlwaysUnique, None)
+                    .unwrap();
+                manager.add_custom_buf_handler(stage.handler());
+                manager
+            })
+            .collect::<Vec<_>>();
+
+        let mut harness = |_: &BytesInput| ExitKind::Ok;
+        let mut executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(),
+            &mut fuzzer,
+            &mut states[0],
+            &mut managers[0],
+        )
+        .unwrap();
+
+        let (main, last) = (0, 2);
+        let mut dictionary = Tokens::new();
+        dictionary.add_token(&b"main".to_vec());
+        states[main].add_metadata(dictionary);
+        let mut dictionary = Tokens::new();
+        dictionary.add_tokens([b"b".to_vec(), b"c".to_vec(), b"d".to_vec()]);
+        states[last].add_metadata(dictionary);
+
+        for _ in 0..3 {
+            for node in 0..3 {
+                stages[node]
+                    .perform(
+                        &mut fuzzer,
+                        &mut executor,
+                        &mut states[node],
+                        &mut managers[node],
+                    )
+                    .unwrap();
+            }
+            for node in 0..3 {
+                managers[node]
+                    .process(&mut fuzzer, &mut states[node], &mut executor)
+                    .unwrap();
+            }
+        }
+
+        // Everyone converged, even the node that only hears from the main node
+        let tokens = |node: usize| {
+            states[node]
+                .metadata::<Tokens>()
+                .unwrap()
+                .iter()
+                .cloned()
+                .collect::<HashSet<_>>()
+        };
+        for node in 0..3 {
+            assert_eq!(tokens(node), tokens(main));
+        }
+        assert_eq!(tokens(main).len(), 4);
+        let foreign = |node: usize| {
+            &states[node]
+                .metadata::<SyncTokensMetadata>()
+                .unwrap()
+                .foreign
+        };
+        assert_eq!(foreign(main).len(), 3);
+        assert_eq!(foreign(last), &[b"main".to_vec()].into_iter().collect());
+
+        // The cap on foreign tokens, and other buffers are left to other handlers
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        let mut handler = SyncTokensStage::new(Duration::ZERO)
+            .max_foreign(2)
+            .handler();
+        let buf =
+            postcard::to_allocvec(&vec![b"x".to_vec(), b"y".to_vec(), b"z".to_vec()]).unwrap();
+        assert_eq!(
+            handler(&mut state, "other", &buf).unwrap(),
+            CustomBufEventResult::Next
+        );
+        assert!(!state.has_metadata::<Tokens>());
+        assert_eq!(
+            handler(&mut state, TOKENS_SYNC_TAG, &buf).unwrap(),
+            CustomBufEventResult::Handled
+        );
+        assert_eq!(state.metadata::<Tokens>().unwrap().len(), 2);
+    }
+}
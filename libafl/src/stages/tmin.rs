@@ -29,15 +29,15 @@ use crate::{
     schedulers::RemovableScheduler,
     stages::{
         mutational::{MutatedTransform, MutatedTransformPost},
-        ExecutionCountRestartHelper, Stage,
+        CorpusQuiesceGuard, ExecutionCountRestartHelper, Stage,
     },
     start_timer,
     state::{
         HasCorpus, HasCurrentTestcase, HasExecutions, HasMaxSize, HasSolutions,
         MaybeHasClientPerfMonitor, State, UsesState,
     },
-    Error, ExecutesInput, ExecutionProcessor, HasFeedback, HasMetadata, HasNamedMetadata,
-    HasScheduler,
+    Error, ExecutesInput, ExecutionProcessor, HasFeedback, HasInputFilter, HasMetadata,
+    HasNamedMetadata, HasScheduler,
 };
 
 /// The default corpus entry minimising mutational stage
@@ -62,6 +62,7 @@ where
     Z: HasScheduler<<S::Corpus as Corpus>::Input, S>
         + ExecutionProcessor<EM, <S::Corpus as Corpus>::Input, E::Observers, S>
         + ExecutesInput<E, EM, <S::Corpus as Corpus>::Input, S>
+        + HasInputFilter<<S::Corpus as Corpus>::Input, S>
         + HasFeedback,
     Z::Scheduler: RemovableScheduler<<S::Corpus as Corpus>::Input, S>,
     E: HasObservers + UsesState<State = S>,
@@ -97,10 +98,17 @@ where
         state: &mut S,
         manager: &mut EM,
     ) -> Result<(), Error> {
+        if CorpusQuiesceGuard::is_held(state) {
+            // The corpus is being reorganized, don't replace testcases now
+            return Ok(());
+        }
+
         self.perform_minification(fuzzer, executor, state, manager)?;
 
         #[cfg(feature = "introspection")]
-        state.introspection_monitor_mut().finish_stage();
+        state
+            .introspection_monitor_mut()
+            .finish_named_stage(&self.name);
 
         Ok(())
     }
@@ -133,6 +141,7 @@ where
     Z: HasScheduler<<S::Corpus as Corpus>::Input, S>
         + ExecutionProcessor<EM, <S::Corpus as Corpus>::Input, E::Observers, S>
         + ExecutesInput<E, EM, <S::Corpus as Corpus>::Input, S>
+        + HasInputFilter<<S::Corpus as Corpus>::Input, S>
         + HasFeedback,
     Z::Scheduler: RemovableScheduler<<S::Corpus as Corpus>::Input, S>,
     E: HasObservers + UsesState<State = S>,
@@ -202,6 +211,9 @@ where
         let base_hash = RandomState::with_seeds(0, 0, 0, 0).hash_one(&base);
         mark_feature_time!(state, PerfFeature::GetInputFromCorpus);
 
+        if fuzzer.skips_input(state, &base) {
+            return Ok(());
+        }
         fuzzer.execute_input(state, executor, manager, &base)?;
         let observers = executor.observers();
 
@@ -229,7 +241,7 @@ where
             }
 
             let (input, post) = input_transformed.try_transform_into(state)?;
-            let corpus_id = if input.len() < before_len {
+            let corpus_id = if input.len() < before_len && !fuzzer.skips_input(state, &input) {
                 // run the input
                 let exit_kind = fuzzer.execute_input(state, executor, manager, &input)?;
                 let observers = executor.observers();
@@ -19,7 +19,7 @@ use crate::{
     stages::{RetryCountRestartHelper, Stage},
     start_timer,
     state::{HasCorpus, HasCurrentTestcase, HasExecutions, MaybeHasClientPerfMonitor, UsesState},
-    Error, HasNamedMetadata,
+    Error, HasInputFilter, HasNamedMetadata,
 };
 
 /// A stage that runs a tracer executor
@@ -42,14 +42,25 @@ where
         + MaybeHasClientPerfMonitor
         + UsesInput<Input = <S::Corpus as Corpus>::Input>,
     EM: UsesState<State = S>, //delete me
+    Z: HasInputFilter<<S::Corpus as Corpus>::Input, S>,
 {
     #[allow(rustdoc::broken_intra_doc_links)]
     /// Perform tracing on the given `CorpusId`. Useful for if wrapping [`TracingStage`] with your
     /// own stage and you need to manage [`super::NestedStageRetryCountRestartHelper`] differently
     /// see [`super::ConcolicTracingStage`]'s implementation as an example of usage.
-    pub fn trace(&mut self, fuzzer: &mut Z, state: &mut S, manager: &mut EM) -> Result<(), Error> {
+    ///
+    /// Returns `false` if the input filter rejected the input, so the tracer did not run.
+    pub fn trace(
+        &mut self,
+        fuzzer: &mut Z,
+        state: &mut S,
+        manager: &mut EM,
+    ) -> Result<bool, Error> {
         start_timer!(state);
         let input = state.current_input_cloned()?;
+        if fuzzer.skips_input(state, &input) {
+            return Ok(false);
+        }
 
         mark_feature_time!(state, PerfFeature::GetInputFromCorpus);
 
@@ -71,7 +82,7 @@ where
             .post_exec_all(state, &input, &exit_kind)?;
         mark_feature_time!(state, PerfFeature::PostExecObservers);
 
-        Ok(())
+        Ok(true)
     }
 }
 
@@ -86,6 +97,7 @@ where
         + MaybeHasClientPerfMonitor
         + UsesInput<Input = <S::Corpus as Corpus>::Input>,
     EM: UsesState<State = S>,
+    Z: HasInputFilter<<S::Corpus as Corpus>::Input, S>,
     <S::Corpus as Corpus>::Input: Input,
 {
     #[inline]
@@ -96,7 +108,8 @@ where
         state: &mut S,
         manager: &mut EM,
     ) -> Result<(), Error> {
-        self.trace(fuzzer, state, manager)
+        self.trace(fuzzer, state, manager)?;
+        Ok(())
     }
 
     fn should_restart(&mut self, state: &mut S) -> Result<bool, Error> {
@@ -181,6 +194,7 @@ where
         + MaybeHasClientPerfMonitor
         + UsesInput<Input = <S::Corpus as Corpus>::Input>,
     EM: UsesState<State = S>,
+    Z: HasInputFilter<<S::Corpus as Corpus>::Input, S>,
 {
     #[inline]
     fn perform(
@@ -192,6 +206,9 @@ where
     ) -> Result<(), Error> {
         start_timer!(state);
         let input = state.current_input_cloned()?;
+        if fuzzer.skips_input(state, &input) {
+            return Ok(());
+        }
 
         mark_feature_time!(state, PerfFeature::GetInputFromCorpus);
 
@@ -220,7 +220,9 @@ where
         let ret = self.perform_mutational(fuzzer, executor, state, manager);
 
         #[cfg(feature = "introspection")]
-        state.introspection_monitor_mut().finish_stage();
+        state
+            .introspection_monitor_mut()
+            .finish_named_stage(&self.name);
 
         ret
     }
@@ -0,0 +1,386 @@
+//! Durable checkpoints of the fuzzer state on disk.
+//!
+//! Unlike the shared-memory based restarts of the event managers, checkpoints survive a reboot,
+//! and can be moved to a different machine to continue the campaign there.
+//! [`StdState::export_corpus_partition`] only moves the corpus, keeping which entries are disabled.
+
+use alloc::{string::ToString, vec::Vec};
+use std::{
+    fs,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+use libafl_bolts::{
+    fs::write_file_durable_with,
+    serdeany::{from_versioned_bytes, to_framed_bytes, to_versioned_bytes, SchemaHeader},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::{HasCorpus, StageStack, StdState};
+use crate::{
+    corpus::{Corpus, CorpusId, Testcase},
+    events::framing::{self, ReadRecord},
+    inputs::Input,
+    Error,
+};
+
+/// The schema version checkpoints are written with
+pub const CHECKPOINT_SCHEMA_VERSION: u32 = 0;
+
+/// The schema version corpus partitions are written with, see [`StdState::export_corpus_partition`]
+pub const CORPUS_PARTITION_SCHEMA_VERSION: u32 = 0;
+
+/// How [`StdState::load_from`] continues from a checkpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckpointLoadMode {
+    /// Continue exactly where the checkpoint was taken, including an in-progress stage.
+    ///
+    /// Use this to resume on the same machine, with the same stages.
+    Resume,
+    /// Keep the corpora, feedback state, and metadata, but start a new session:
+    /// the fuzzer picks a new entry, and the in-progress stage, pending stop request, and last report are dropped.
+    ///
+    /// Use this to continue on a different machine, with fresh event manager connections.
+    Fresh,
+}
+
+/// The path of the `nth` newest checkpoint at `path`: `path` itself for `0`, `path.{nth}` for older ones
+#[must_use]
+pub fn checkpoint_path(path: &Path, nth: usize) -> PathBuf {
+    if nth == 0 {
+        return path.to_path_buf();
+    }
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{nth}"));
+    path.with_file_name(name)
+}
+
+/// Write `state` to `path`, keeping the last `keep` checkpoints, see [`checkpoint_path`].
+///
+/// The new checkpoint is written to a temporary file and synced before it replaces the newest one,
+/// so a crash during the save never leaves a partial checkpoint behind.
+pub fn save_checkpoint<S>(state: &S, path: &Path, keep: usize) -> Result<(), Error>
+where
+    S: Serialize,
+{
+    if keep == 0 {
+        return Err(Error::illegal_argument(
+            "At least one checkpoint has to be kept".to_string(),
+        ));
+    }
+    let bytes = to_versioned_bytes(state, CHECKPOINT_SCHEMA_VERSION)?;
+
+    // Only once the new checkpoint is on disk, make room for it, starting with the oldest one.
+    // This way, a crash in between never loses the newest complete checkpoint.
+    write_file_durable_with(path, &bytes, || {
+        for nth in (1..keep).rev() {
+            let older = checkpoint_path(path, nth - 1);
+            if older.exists() {
+                fs::rename(&older, checkpoint_path(path, nth))?;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Read a state written by [`save_checkpoint`] from `path`
+pub fn load_checkpoint<S>(path: &Path) -> Result<S, Error>
+where
+    S: DeserializeOwned,
+{
+    let bytes = fs::read(path)?;
+    from_versioned_bytes(&bytes, CHECKPOINT_SCHEMA_VERSION, false)
+}
+
+impl<I, C, R, SC> StdState<I, C, R, SC>
+where
+    Self: Serialize,
+{
+    /// Write a checkpoint of this state to `path`, replacing an earlier one
+    pub fn save_to<P>(&self, path: P) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+    {
+        save_checkpoint(self, path.as_ref(), 1)
+    }
+
+    /// Write a checkpoint of this state to `path`, keeping the last `keep` checkpoints as `path.1`, `path.2`, ...
+    pub fn save_to_rotating<P>(&self, path: P, keep: usize) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+    {
+        save_checkpoint(self, path.as_ref(), keep)
+    }
+}
+
+impl<I, C, R, SC> StdState<I, C, R, SC>
+where
+    Self: DeserializeOwned,
+{
+    /// Load a state from a checkpoint written by [`StdState::save_to`]
+    pub fn load_from<P>(path: P, mode: CheckpointLoadMode) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let mut state: Self = load_checkpoint(path.as_ref())?;
+        if mode == CheckpointLoadMode::Fresh {
+            state.corpus_id = None;
+            state.stage_stack = StageStack::default();
+            state.stop_requested = false;
+            state.last_report_time = None;
+        }
+        Ok(state)
+    }
+}
+
+/// An entry of an exported corpus, and if it was disabled, see [`StdState::export_corpus_partition`]
+#[derive(Debug, Serialize, Deserialize)]
+struct PartitionEntry<I> {
+    testcase: Testcase<I>,
+    disabled: bool,
+}
+
+impl<I, C, R, SC> StdState<I, C, R, SC>
+where
+    I: Input,
+    C: Corpus<Input = I>,
+{
+    /// Write all entries of the corpus, enabled and disabled, to `writer`, in the order of their ids.
+    ///
+    /// Each entry keeps its metadata and if it is disabled, so a campaign moved to another machine with
+    /// [`StdState::import_corpus_partition`] continues with the same pruning state.
+    /// Returns the number of exported entries.
+    pub fn export_corpus_partition<W>(&self, mut writer: W) -> Result<usize, Error>
+    where
+        W: Write,
+    {
+        let corpus = self.corpus();
+        let mut ids: Vec<CorpusId> = (0..corpus.count_all())
+            .map(|nth| corpus.nth_from_all(nth))
+            .collect();
+        ids.sort_unstable();
+
+        // Entries are written one by one, so the corpus is never held in memory twice
+        framing::write_record(
+            &mut writer,
+            &(
+                SchemaHeader::new(CORPUS_PARTITION_SCHEMA_VERSION),
+                ids.len(),
+            ),
+        )?;
+        for &id in &ids {
+            let mut testcase = corpus.get_from_all(id)?.borrow().clone();
+            testcase.load_input(corpus)?;
+            // The files of on-disk corpora do not exist on the importing machine
+            *testcase.file_path_mut() = None;
+            *testcase.metadata_path_mut() = None;
+            let entry = PartitionEntry {
+                testcase,
+                disabled: corpus.get(id).is_err(),
+            };
+            framing::write_bytes(&mut writer, &to_framed_bytes(&entry)?)?;
+        }
+        Ok(ids.len())
+    }
+
+    /// Add the entries written by [`StdState::export_corpus_partition`] from `reader` to the corpus,
+    /// disabling the ones that were disabled before.
+    ///
+    /// The scheduler is not notified, call [`crate::schedulers::Scheduler::on_add`] for the returned enabled
+    /// entries if it keeps track of them. Returns the ids of all imported entries, in the order they were exported.
+    /// If the partition is cut short or corrupted, the entries read before it stay in the corpus.
+    pub fn import_corpus_partition<RD>(&mut self, mut reader: RD) -> Result<Vec<CorpusId>, Error>
+    where
+        RD: Read,
+    {
+        let (header, count): (SchemaHeader, usize) = match framing::read_record(&mut reader)? {
+            ReadRecord::Record(record) => record,
+            ReadRecord::End => return Err(Error::empty("The corpus partition is empty")),
+            ReadRecord::Truncated(why) => {
+                return Err(Error::illegal_state(format!(
+                    "The corpus partition header is unreadable, {why}"
+                )))
+            }
+        };
+        let mut loader = header.loader(CORPUS_PARTITION_SCHEMA_VERSION, false)?;
+
+        let corpus = self.corpus_mut();
+        let mut ids = Vec::new();
+        while ids.len() < count {
+            let why = match framing::read_bytes(&mut reader)? {
+                ReadRecord::Record(bytes) => {
+                    let PartitionEntry {
+                        mut testcase,
+                        disabled,
+                    } = loader.load::<PartitionEntry<I>>(&bytes)?;
+                    testcase.set_disabled(disabled);
+                    ids.push(if disabled {
+                        corpus.add_disabled(testcase)?
+                    } else {
+                        corpus.add(testcase)?
+                    });
+                    continue;
+                }
+                ReadRecord::End => "it ends early",
+                ReadRecord::Truncated(why) => why,
+            };
+            return Err(Error::illegal_state(format!(
+                "Entry {} of {count} of the corpus partition is unreadable, {why}",
+                ids.len()
+            )));
+        }
+        Ok(ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, vec::Vec};
+
+    use libafl_bolts::{rands::StdRand, serdeany::SchemaHeader};
+
+    use super::{checkpoint_path, CheckpointLoadMode, CORPUS_PARTITION_SCHEMA_VERSION};
+    use crate::{
+        corpus::{Corpus, CorpusId, HasCurrentCorpusId, InMemoryCorpus, Testcase},
+        events::framing,
+        feedbacks::ConstFeedback,
+        inputs::BytesInput,
+        state::{HasCorpus, HasExecutions, StdState, Stoppable},
+    };
+
+    type TestState =
+        StdState<BytesInput, InMemoryCorpus<BytesInput>, StdRand, InMemoryCorpus<BytesInput>>;
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn test_checkpoint_rotation() {
+        let dir = std::env::temp_dir().join(format!("libafl_checkpoint_{}", std::process::id()));
+        drop(fs::remove_dir_all(&dir));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("state");
+
+        let mut feedback = ConstFeedback::new(false);
+        let mut objective = ConstFeedback::new(false);
+        let mut state: TestState = StdState::new(
+            StdRand::with_seed(0),
+            InMemoryCorpus::new(),
+            InMemoryCorpus::new(),
+            &mut feedback,
+            &mut objective,
+        )
+        .unwrap();
+        let id = state
+            .corpus_mut()
+            .add(Testcase::new(BytesInput::new(vec![1, 2, 3])))
+            .unwrap();
+
+        for executions in 1..=4 {
+            *state.executions_mut() = executions;
+            state.save_to_rotating(&path, 3).unwrap();
+        }
+        // The newest three checkpoints are kept, newest first
+        for (nth, executions) in [4, 3, 2].into_iter().enumerate() {
+            let loaded =
+                TestState::load_from(checkpoint_path(&path, nth), CheckpointLoadMode::Resume)
+                    .unwrap();
+            assert_eq!(*loaded.executions(), executions);
+            assert_eq!(loaded.corpus().count(), 1);
+        }
+        assert!(!checkpoint_path(&path, 3).exists());
+        assert!(state.save_to_rotating(&path, 0).is_err());
+
+        // A fresh session drops the progress of the old one, but keeps the corpus
+        state.set_corpus_id(id).unwrap();
+        state.request_stop();
+        state.save_to(&path).unwrap();
+        let resumed = TestState::load_from(&path, CheckpointLoadMode::Resume).unwrap();
+        assert_eq!(resumed.current_corpus_id().unwrap(), Some(id));
+        assert!(resumed.stop_requested());
+        let fresh = TestState::load_from(&path, CheckpointLoadMode::Fresh).unwrap();
+        assert_eq!(fresh.current_corpus_id().unwrap(), None);
+        assert!(!fresh.stop_requested());
+        assert_eq!(fresh.corpus().count(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn new_state() -> TestState {
+        let mut feedback = ConstFeedback::new(false);
+        let mut objective = ConstFeedback::new(false);
+        StdState::new(
+            StdRand::with_seed(0),
+            InMemoryCorpus::new(),
+            InMemoryCorpus::new(),
+            &mut feedback,
+            &mut objective,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_corpus_partition_roundtrip() {
+        let mut state = new_state();
+        for byte in 0..5 {
+            let mut testcase = Testcase::new(BytesInput::new(vec![byte]));
+            testcase.set_scheduled_count(usize::from(byte));
+            state.corpus_mut().add(testcase).unwrap();
+        }
+        // Disable the second and fourth entry, as the pruning stage does
+        for id in [CorpusId(1), CorpusId(3)] {
+            let testcase = state.corpus_mut().remove(id).unwrap();
+            state.corpus_mut().add_disabled(testcase).unwrap();
+        }
+
+        let mut exported = Vec::new();
+        assert_eq!(state.export_corpus_partition(&mut exported).unwrap(), 5);
+
+        let mut fresh = new_state();
+        let ids = fresh.import_corpus_partition(exported.as_slice()).unwrap();
+        assert_eq!(ids.len(), 5);
+        assert_eq!(fresh.corpus().count(), 3);
+        assert_eq!(fresh.corpus().count_disabled(), 2);
+
+        // Disabling gave the entries new ids, they come back in that order, with their metadata and partition
+        for (byte, id) in [0, 2, 4, 1, 3].into_iter().zip(ids) {
+            let testcase = fresh.corpus().get_from_all(id).unwrap().borrow();
+            assert_eq!(testcase.input().as_ref().unwrap().as_ref(), &[byte]);
+            assert_eq!(testcase.scheduled_count(), usize::from(byte));
+            assert_eq!(fresh.corpus().get(id).is_err(), byte % 2 == 1);
+        }
+    }
+
+    #[test]
+    fn test_corpus_partition_rejected() {
+        let mut state = new_state();
+        for byte in 0..3 {
+            state
+                .corpus_mut()
+                .add(Testcase::new(BytesInput::new(vec![byte])))
+                .unwrap();
+        }
+        let mut exported = Vec::new();
+        state.export_corpus_partition(&mut exported).unwrap();
+
+        // Cut short in the last entry, the ones before it are still imported
+        let mut fresh = new_state();
+        assert!(fresh
+            .import_corpus_partition(&exported[..exported.len() - 1])
+            .is_err());
+        assert_eq!(fresh.corpus().count(), 2);
+
+        // Written by a newer version
+        let mut newer = Vec::new();
+        framing::write_record(
+            &mut newer,
+            &(
+                SchemaHeader::new(CORPUS_PARTITION_SCHEMA_VERSION + 1),
+                0_usize,
+            ),
+        )
+        .unwrap();
+        assert!(new_state()
+            .import_corpus_partition(newer.as_slice())
+            .is_err());
+    }
+}
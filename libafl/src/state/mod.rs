@@ -1,5 +1,6 @@
 //! The fuzzer, and state are the core pieces of every good fuzzer
 
+use alloc::string::String;
 #[cfg(feature = "std")]
 use alloc::vec::Vec;
 use core::{
@@ -15,17 +16,24 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use hashbrown::HashMap;
 #[cfg(feature = "std")]
 use libafl_bolts::core_affinity::{CoreId, Cores};
 use libafl_bolts::{
-    rands::{Rand, StdRand},
+    rands::{derive_seed, Rand, StdRand},
     serdeany::{NamedSerdeAnyMap, SerdeAnyMap},
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 mod stack;
 pub use stack::StageStack;
+#[cfg(feature = "std")]
+pub mod checkpoint;
+#[cfg(feature = "std")]
+pub use checkpoint::CheckpointLoadMode;
 
+#[cfg(feature = "std")]
+use crate::inputs::{BytesInput, GeneralizedInputMetadata, GENERALIZED_TEXT_EXTENSION};
 #[cfg(feature = "introspection")]
 use crate::monitors::ClientPerfMonitor;
 #[cfg(feature = "scalability_introspection")]
@@ -34,7 +42,7 @@ use crate::{
     corpus::{Corpus, CorpusId, HasCurrentCorpusId, HasTestcase, InMemoryCorpus, Testcase},
     events::{Event, EventFirer, LogSeverity},
     feedbacks::StateInitializer,
-    fuzzer::{Evaluator, ExecuteInputResult},
+    fuzzer::{BatchProgressMetadata, Evaluator, ExecuteInputResult},
     generators::Generator,
     inputs::{Input, NopInput, UsesInput},
     stages::{HasCurrentStageId, HasNestedStageStatus, StageId},
@@ -44,6 +52,13 @@ use crate::{
 /// The maximum size of a testcase
 pub const DEFAULT_MAX_SIZE: usize = 1_048_576;
 
+/// The number of initial inputs evaluated at once, see [`crate::fuzzer::Evaluator::evaluate_inputs_batch`].
+///
+/// If one of them crashes the fuzzer, the restarted process skips it and loads the rest of its batch,
+/// see [`BatchProgressMetadata`].
+#[cfg(feature = "std")]
+pub const INITIAL_INPUTS_BATCH_SIZE: usize = 16;
+
 /// The [`State`] of the fuzzer.
 /// Contains all important information about the current run.
 /// Will be used to restart the fuzzing process at any time.
@@ -212,6 +227,25 @@ pub trait HasLastFoundTime {
     fn last_found_time_mut(&mut self) -> &mut Duration;
 }
 
+/// The report channel backed by [`HasLastReportTime::last_report_time`], used for the progress reports
+pub const DEFAULT_REPORT_CHANNEL: &str = "default";
+
+/// The last report times of the named report channels other than the [`DEFAULT_REPORT_CHANNEL`],
+/// see [`HasLastReportTime::maybe_report`].
+///
+/// Stored in the state, so the cadence of each channel survives restarts.
+#[cfg_attr(
+    any(not(feature = "serdeany_autoreg"), miri),
+    allow(clippy::unsafe_derive_deserialize)
+)] // for SerdeAny
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct ReportChannelsMetadata {
+    /// The last report time of each channel, by name
+    pub last_times: HashMap<String, Duration>,
+}
+
+libafl_bolts::impl_serdeany!(ReportChannelsMetadata);
+
 /// Trait for the last report time, the last time this node reported progress
 pub trait HasLastReportTime {
     /// The last time we reported progress,if available/used.
@@ -221,6 +255,65 @@ pub trait HasLastReportTime {
     /// The last time we reported progress,if available/used (mutable).
     /// This information is used by fuzzer `maybe_report_progress`.
     fn last_report_time_mut(&mut self) -> &mut Option<Duration>;
+
+    /// The last time something was reported on the named `channel`, if ever
+    fn last_report_time_of(&self, channel: &str) -> Option<Duration>
+    where
+        Self: HasMetadata,
+    {
+        if channel == DEFAULT_REPORT_CHANNEL {
+            return *self.last_report_time();
+        }
+        self.metadata::<ReportChannelsMetadata>()
+            .ok()
+            .and_then(|meta| meta.last_times.get(channel).copied())
+    }
+
+    /// Set the last time something was reported on the named `channel`
+    fn set_last_report_time_of(&mut self, channel: &str, time: Duration)
+    where
+        Self: HasMetadata,
+    {
+        if channel == DEFAULT_REPORT_CHANNEL {
+            *self.last_report_time_mut() = Some(time);
+            return;
+        }
+        self.metadata_or_insert_with(ReportChannelsMetadata::default)
+            .last_times
+            .insert(channel.into(), time);
+    }
+
+    /// Run `report` if more than `interval` passed since the last report on the named `channel`.
+    ///
+    /// Each channel keeps its own last report time, so stats, heartbeats, and other periodic work
+    /// can run on independent schedules. The first call for a channel only starts its clock.
+    /// [`DEFAULT_REPORT_CHANNEL`] is the channel of [`HasLastReportTime::last_report_time`].
+    ///
+    /// Returns if `report` ran.
+    fn maybe_report<F>(
+        &mut self,
+        channel: &str,
+        interval: Duration,
+        report: F,
+    ) -> Result<bool, Error>
+    where
+        Self: HasMetadata + Sized,
+        F: FnOnce(&mut Self) -> Result<(), Error>,
+    {
+        let cur = libafl_bolts::current_time();
+        let Some(last_report_time) = self.last_report_time_of(channel) else {
+            // this is the first time we get here, no need to report just yet.
+            self.set_last_report_time_of(channel, cur);
+            return Ok(false);
+        };
+        // default to 0 here to avoid crashes on clock skew
+        if cur.checked_sub(last_report_time).unwrap_or_default() <= interval {
+            return Ok(false);
+        }
+        report(self)?;
+        self.set_last_report_time_of(channel, cur);
+        Ok(true)
+    }
 }
 
 /// Struct that holds the options for input loading
@@ -279,6 +372,9 @@ pub struct StdState<I, C, R, SC> {
     /// symlinks we have already traversed when loading `remaining_initial_files`
     dont_reenter: Option<Vec<PathBuf>>,
     #[cfg(feature = "std")]
+    /// The initial inputs being loaded as one batch, taken off `remaining_initial_files`
+    initial_batch: Option<Vec<PathBuf>>,
+    #[cfg(feature = "std")]
     /// If inputs have been processed for multicore loading
     /// relevant only for `load_initial_inputs_multicore`
     multicore_inputs_processed: Option<bool>,
@@ -636,6 +732,7 @@ where
     /// Decide if the state must load the inputs
     pub fn must_load_initial_inputs(&self) -> bool {
         self.corpus().count() == 0
+            || self.initial_batch.is_some()
             || (self.remaining_initial_files.is_some()
                 && !self.remaining_initial_files.as_ref().unwrap().is_empty())
     }
@@ -689,13 +786,14 @@ where
     fn reset_initial_files_state(&mut self) {
         self.remaining_initial_files = None;
         self.dont_reenter = None;
+        self.initial_batch = None;
     }
 
     /// Sets canonical paths for provided inputs
     fn canonicalize_input_dirs(&mut self, in_dirs: &[PathBuf]) -> Result<(), Error> {
         if let Some(remaining) = self.remaining_initial_files.as_ref() {
             // everything was loaded
-            if remaining.is_empty() {
+            if remaining.is_empty() && self.initial_batch.is_none() {
                 return Ok(());
             }
         } else {
@@ -729,7 +827,7 @@ where
     {
         if let Some(remaining) = self.remaining_initial_files.as_ref() {
             // everything was loaded
-            if remaining.is_empty() {
+            if remaining.is_empty() && self.initial_batch.is_none() {
                 return Ok(());
             }
         } else {
@@ -739,32 +837,54 @@ where
         self.continue_loading_initial_inputs_custom(fuzzer, executor, manager, load_config)
     }
 
-    fn load_file<E, EM, Z>(
+    /// Load and evaluate the files at `paths` as one batch, returning the result for each of them
+    fn load_files<E, EM, Z>(
         &mut self,
-        path: &PathBuf,
+        paths: &[PathBuf],
         manager: &mut EM,
         fuzzer: &mut Z,
         executor: &mut E,
         config: &mut LoadConfig<I, Self, Z>,
-    ) -> Result<ExecuteInputResult, Error>
+    ) -> Result<Vec<ExecuteInputResult>, Error>
     where
         E: UsesState<State = Self>,
         EM: EventFirer<State = Self>,
         Z: Evaluator<E, EM, I, Self>,
     {
-        log::info!("Loading file {:?} ...", &path);
-        let input = (config.loader)(fuzzer, self, path)?;
+        let mut inputs = Vec::with_capacity(paths.len());
+        for path in paths {
+            log::info!("Loading file {} ...", path.display());
+            inputs.push((config.loader)(fuzzer, self, path)?);
+        }
+
         if config.forced {
-            let _: CorpusId = fuzzer.add_input(self, executor, manager, input)?;
-            Ok(ExecuteInputResult::Corpus)
-        } else {
-            let (res, _) = fuzzer.evaluate_input(self, executor, manager, input.clone())?;
-            if res == ExecuteInputResult::None {
-                fuzzer.add_disabled_input(self, input)?;
-                log::warn!("input {:?} was not interesting, adding as disabled.", &path);
+            self.add_metadata(BatchProgressMetadata::default());
+            for input in inputs {
+                let _: CorpusId = fuzzer.add_input(self, executor, manager, input)?;
+                self.metadata_mut::<BatchProgressMetadata>()?.evaluated += 1;
             }
-            Ok(res)
+            return Ok(paths.iter().map(|_| ExecuteInputResult::Corpus).collect());
         }
+
+        let results = fuzzer.evaluate_inputs_batch(self, executor, manager, inputs.clone())?;
+        let mut outcomes = Vec::with_capacity(results.len());
+        for ((res, _), (input, path)) in results.into_iter().zip(inputs.into_iter().zip(paths)) {
+            match res {
+                ExecuteInputResult::None => {
+                    fuzzer.add_disabled_input(self, input)?;
+                    log::warn!(
+                        "input {} was not interesting, adding as disabled.",
+                        path.display()
+                    );
+                }
+                ExecuteInputResult::Skipped => {
+                    log::warn!("input {} was skipped by the input filter.", path.display());
+                }
+                ExecuteInputResult::Corpus | ExecuteInputResult::Solution => (),
+            }
+            outcomes.push(res);
+        }
+        Ok(outcomes)
     }
     /// Loads initial inputs from the passed-in `in_dirs`.
     /// This method takes a list of files and a `LoadConfig`
@@ -782,18 +902,44 @@ where
         Z: Evaluator<E, EM, I, Self>,
     {
         loop {
-            match self.next_file() {
-                Ok(path) => {
-                    let res = self.load_file(&path, manager, fuzzer, executor, &mut config)?;
-                    if config.exit_on_solution && matches!(res, ExecuteInputResult::Solution) {
-                        return Err(Error::invalid_corpus(format!(
-                            "Input {} resulted in a solution.",
-                            path.display()
-                        )));
+            let paths = if let Some(batch) = self.initial_batch.take() {
+                // A crash interrupted this batch: the inputs before the one that crashed were evaluated
+                let evaluated = self
+                    .metadata::<BatchProgressMetadata>()
+                    .map_or(0, |progress| progress.evaluated);
+                if let Some(crashed) = batch.get(evaluated) {
+                    log::warn!(
+                        "input {} crashed the fuzzer while loading, skipping.",
+                        crashed.display()
+                    );
+                }
+                // Even if nothing is left, load the empty batch, to fire the events the crash held back
+                batch.get(evaluated + 1..).unwrap_or_default().to_vec()
+            } else {
+                let mut paths = Vec::with_capacity(INITIAL_INPUTS_BATCH_SIZE);
+                while paths.len() < INITIAL_INPUTS_BATCH_SIZE {
+                    match self.next_file() {
+                        Ok(path) => paths.push(path),
+                        Err(Error::IteratorEnd(_, _)) => break,
+                        Err(e) => return Err(e),
                     }
                 }
-                Err(Error::IteratorEnd(_, _)) => break,
-                Err(e) => return Err(e),
+                if paths.is_empty() {
+                    break;
+                }
+                paths
+            };
+
+            self.initial_batch = Some(paths.clone());
+            let results = self.load_files(&paths, manager, fuzzer, executor, &mut config)?;
+            self.initial_batch = None;
+            for (path, res) in paths.iter().zip(results) {
+                if config.exit_on_solution && matches!(res, ExecuteInputResult::Solution) {
+                    return Err(Error::invalid_corpus(format!(
+                        "Input {} resulted in a solution.",
+                        path.display()
+                    )));
+                }
             }
         }
 
@@ -1070,6 +1216,54 @@ where
     }
 }
 
+#[cfg(feature = "std")]
+impl<C, R, SC> StdState<BytesInput, C, R, SC>
+where
+    C: Corpus<Input = BytesInput>,
+    R: Rand,
+    SC: Corpus<Input = BytesInput>,
+{
+    /// Loads the generalized inputs from the passed-in `in_dirs`, in the text form of
+    /// [`GeneralizedInputMetadata::to_text`], e.g., as written by
+    /// [`crate::stages::DumpToDiskStage::generalized_sidecars`] and edited by hand.
+    /// Only files with the extension [`GENERALIZED_TEXT_EXTENSION`] are loaded.
+    ///
+    /// Each input is added to the corpus, even if it is not interesting, together with its
+    /// [`GeneralizedInputMetadata`], so Grimoire keeps the gaps as they are.
+    pub fn load_initial_generalized_inputs<E, EM, Z>(
+        &mut self,
+        fuzzer: &mut Z,
+        executor: &mut E,
+        manager: &mut EM,
+        in_dirs: &[PathBuf],
+    ) -> Result<(), Error>
+    where
+        E: UsesState<State = Self>,
+        EM: EventFirer<State = Self>,
+        Z: Evaluator<E, EM, BytesInput, Self>,
+    {
+        let mut paths = vec![];
+        self.walk_initial_inputs(in_dirs, |path| {
+            if path
+                .extension()
+                .is_some_and(|ext| ext == GENERALIZED_TEXT_EXTENSION)
+            {
+                paths.push(path.clone());
+            }
+            Ok(())
+        })?;
+
+        for path in paths {
+            log::info!("Loading generalized input {} ...", path.display());
+            let meta = GeneralizedInputMetadata::from_text_file(&path)?;
+            let input = BytesInput::from(meta.generalized_to_bytes());
+            let id = fuzzer.add_input(self, executor, manager, input)?;
+            self.corpus().get(id)?.borrow_mut().add_metadata(meta);
+        }
+        Ok(())
+    }
+}
+
 impl<C, I, R, SC> StdState<I, C, R, SC>
 where
     I: Input,
@@ -1100,7 +1294,10 @@ where
                 added += 1;
             } else {
                 let (res, _) = fuzzer.evaluate_input(self, executor, manager, input)?;
-                if res != ExecuteInputResult::None {
+                if matches!(
+                    res,
+                    ExecuteInputResult::Corpus | ExecuteInputResult::Solution
+                ) {
                     added += 1;
                 }
             }
@@ -1185,6 +1382,8 @@ where
             remaining_initial_files: None,
             #[cfg(feature = "std")]
             dont_reenter: None,
+            #[cfg(feature = "std")]
+            initial_batch: None,
             last_report_time: None,
             last_found_time: libafl_bolts::current_time(),
             corpus_id: None,
@@ -1199,6 +1398,36 @@ where
     }
 }
 
+impl<C, I, R, SC> StdState<I, C, R, SC>
+where
+    I: Input,
+    C: Corpus<Input = <Self as UsesInput>::Input> + Serialize + DeserializeOwned,
+    R: Rand + Default,
+    SC: Corpus<Input = <Self as UsesInput>::Input> + Serialize + DeserializeOwned,
+{
+    /// Creates a new `State` for a deterministic campaign, seeding its rng from the `master_seed`,
+    /// see [`derive_seed`].
+    ///
+    /// Two runs with the same master seed, corpus, and components make the same decisions,
+    /// as long as all randomness is drawn from the state rng, or from rngs seeded with [`derive_seed`],
+    /// and the target itself is deterministic.
+    pub fn deterministic<F, O>(
+        master_seed: u64,
+        corpus: C,
+        solutions: SC,
+        feedback: &mut F,
+        objective: &mut O,
+    ) -> Result<Self, Error>
+    where
+        F: StateInitializer<Self>,
+        O: StateInitializer<Self>,
+    {
+        let mut rand = R::default();
+        rand.set_seed(derive_seed(master_seed, "StdState", 0));
+        Self::new(rand, corpus, solutions, feedback, objective)
+    }
+}
+
 impl StdState<NopInput, InMemoryCorpus<NopInput>, StdRand, InMemoryCorpus<NopInput>> {
     /// Create an empty [`StdState`] that has very minimal uses.
     /// Potentially good for testing.
@@ -1389,10 +1618,189 @@ impl<I> HasScalabilityMonitor for NopState<I> {
 
 #[cfg(test)]
 mod test {
-    use crate::{inputs::BytesInput, state::StdState};
+    use alloc::{vec, vec::Vec};
+    use core::time::Duration;
+    #[cfg(feature = "std")]
+    use std::{env, fs, process};
+
+    use libafl_bolts::{rands::StdRand, tuples::tuple_list};
+
+    use crate::{
+        corpus::{Corpus, InMemoryCorpus, Testcase},
+        events::NopEventManager,
+        executors::{ExitKind, InProcessExecutor},
+        feedbacks::ConstFeedback,
+        fuzzer::{BatchProgressMetadata, Fuzzer},
+        inputs::{BytesInput, Input},
+        mutators::{havoc_mutations, StdScheduledMutator},
+        schedulers::RandScheduler,
+        stages::StdMutationalStage,
+        state::{HasCorpus, HasLastReportTime, StdState, DEFAULT_REPORT_CHANNEL},
+        HasMetadata, StdFuzzer,
+    };
 
     #[test]
     fn test_std_state() {
         StdState::nop::<BytesInput>().expect("couldn't instantiate the test state");
     }
+
+    #[test]
+    fn test_report_channels() {
+        let mut state = StdState::nop::<BytesInput>().unwrap();
+        let hour = Duration::from_secs(3600);
+
+        // The first call only starts the clock of a channel
+        assert!(!state.maybe_report("stats", hour, |_| Ok(())).unwrap());
+        assert!(state.last_report_time_of("stats").is_some());
+        assert!(state.last_report_time().is_none());
+
+        // Channels are due independently
+        state.set_last_report_time_of("stats", Duration::ZERO);
+        state.set_last_report_time_of("heartbeat", state.last_report_time_of("stats").unwrap());
+        state.set_last_report_time_of(DEFAULT_REPORT_CHANNEL, Duration::ZERO);
+        assert_eq!(*state.last_report_time(), Some(Duration::ZERO));
+        let mut reported = 0;
+        assert!(state
+            .maybe_report("stats", hour, |_| {
+                reported += 1;
+                Ok(())
+            })
+            .unwrap());
+        assert!(!state
+            .maybe_report("stats", hour, |_| {
+                reported += 1;
+                Ok(())
+            })
+            .unwrap());
+        assert_eq!(reported, 1);
+        assert!(state
+            .maybe_report(DEFAULT_REPORT_CHANNEL, hour, |_| Ok(()))
+            .unwrap());
+        assert!(state.last_report_time().unwrap() > Duration::ZERO);
+
+        // The cadences are kept in the state
+        state.set_last_report_time_of("heartbeat", Duration::from_secs(1));
+        let state: StdState<
+            BytesInput,
+            InMemoryCorpus<BytesInput>,
+            StdRand,
+            InMemoryCorpus<BytesInput>,
+        > = postcard::from_bytes(&postcard::to_allocvec(&state).unwrap()).unwrap();
+        assert_eq!(
+            state.last_report_time_of("heartbeat"),
+            Some(Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    #[cfg_attr(miri, ignore)]
+    fn test_deterministic_campaign() {
+        let run = |master_seed: u64| {
+            let mut corpus = InMemoryCorpus::<BytesInput>::new();
+            corpus.add(Testcase::new(vec![0; 4].into())).unwrap();
+            let mut feedback = ConstFeedback::new(true);
+            let mut objective = ConstFeedback::new(false);
+            let mut state: StdState<_, _, StdRand, _> = StdState::deterministic(
+                master_seed,
+                corpus,
+                InMemoryCorpus::<BytesInput>::new(),
+                &mut feedback,
+                &mut objective,
+            )
+            .unwrap();
+
+            let mut event_manager = NopEventManager::new();
+            let mut fuzzer = StdFuzzer::new(RandScheduler::new(), feedback, objective);
+            let mut harness = |_buf: &BytesInput| ExitKind::Ok;
+            let mut executor = InProcessExecutor::new(
+                &mut harness,
+                tuple_list!(),
+                &mut fuzzer,
+                &mut state,
+                &mut event_manager,
+            )
+            .unwrap();
+            let mutator = StdScheduledMutator::new(havoc_mutations());
+            let mut stages = tuple_list!(StdMutationalStage::new(mutator));
+            for _ in 0..5 {
+                fuzzer
+                    .fuzz_one(&mut stages, &mut executor, &mut state, &mut event_manager)
+                    .unwrap();
+            }
+
+            state
+                .corpus()
+                .ids()
+                .map(|id| (id, state.corpus().cloned_input_for_id(id).unwrap()))
+                .collect::<Vec<_>>()
+        };
+
+        let first = run(1337);
+        assert!(first.len() > 1);
+        assert_eq!(first, run(1337));
+        assert_ne!(first, run(1338));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    #[cfg_attr(miri, ignore)]
+    fn test_resume_initial_batch() {
+        let dir = env::temp_dir().join(format!("libafl_resume_initial_batch_{}", process::id()));
+        drop(fs::remove_dir_all(&dir));
+        fs::create_dir_all(&dir).unwrap();
+        let paths = (0..4_u8)
+            .map(|byte| {
+                let path = dir.join(format!("seed{byte}"));
+                BytesInput::new(vec![byte]).to_file(&path).unwrap();
+                path
+            })
+            .collect::<Vec<_>>();
+
+        let mut feedback = ConstFeedback::new(true);
+        let mut objective = ConstFeedback::new(false);
+        let mut state = StdState::new(
+            StdRand::with_seed(0),
+            InMemoryCorpus::<BytesInput>::new(),
+            InMemoryCorpus::new(),
+            &mut feedback,
+            &mut objective,
+        )
+        .unwrap();
+        let mut event_manager = NopEventManager::new();
+        let mut fuzzer = StdFuzzer::new(RandScheduler::new(), feedback, objective);
+        let mut harness = |_buf: &BytesInput| ExitKind::Ok;
+        let mut executor = InProcessExecutor::new(
+            &mut harness,
+            tuple_list!(),
+            &mut fuzzer,
+            &mut state,
+            &mut event_manager,
+        )
+        .unwrap();
+
+        // The process died on the second seed of its batch, after evaluating the first one
+        state.remaining_initial_files = Some(vec![]);
+        state.initial_batch = Some(paths);
+        state.add_metadata(BatchProgressMetadata {
+            evaluated: 1,
+            ..BatchProgressMetadata::default()
+        });
+        assert!(state.must_load_initial_inputs());
+        state
+            .load_initial_inputs_by_filenames(&mut fuzzer, &mut executor, &mut event_manager, &[])
+            .unwrap();
+
+        // Only the seeds after the crashing one were loaded
+        let loaded = state
+            .corpus()
+            .ids()
+            .map(|id| state.corpus().cloned_input_for_id(id).unwrap().as_ref()[0])
+            .collect::<Vec<_>>();
+        assert_eq!(loaded, [2, 3]);
+        assert!(state.initial_batch.is_none());
+        assert!(!state.must_load_initial_inputs());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
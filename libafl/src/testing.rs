@@ -0,0 +1,354 @@
+//! Deterministic stand-ins for the fuzzer state, to unit test corpus-level logic,
+//! such as the [`crate::stages::CorpusPruning`] strategies, without a fuzzer or a target.
+//!
+//! Everything here is derived from a seed, so a test sees the same corpus and rolls the same dice on each run.
+//!
+//! The fuzzer tests share the [`BytesState`] fixture and the [`RecordingEventManager`] from here as well,
+//! the event manager tests the [`llmp_ring`] of clients.
+
+use alloc::vec::Vec;
+use core::{marker::PhantomData, time::Duration};
+
+use libafl_bolts::{
+    current_time,
+    rands::{Rand, StdRand},
+    serdeany::SerdeAnyMap,
+};
+#[cfg(feature = "std")]
+use libafl_bolts::{
+    llmp::{LlmpClient, LlmpReceiver, LlmpSharedMap},
+    shmem::{ShMemProvider, StdShMemProvider},
+    ClientId,
+};
+
+use crate::{
+    corpus::{AddedAtMetadata, Corpus, CorpusId, InMemoryCorpus, Testcase},
+    events::{
+        Event, EventFirer, EventProcessor, EventRestarter, HasPendingEvents, ProgressReporter,
+    },
+    feedbacks::{MapIndexesMetadata, StateInitializer},
+    inputs::BytesInput,
+    state::{
+        HasCorpus, HasExecutions, HasLastReportTime, HasRand, State, StdState, Stoppable, UsesState,
+    },
+    Error, HasMetadata,
+};
+#[cfg(feature = "scalability_introspection")]
+use crate::{monitors::ScalabilityMonitor, state::HasScalabilityMonitor};
+
+/// The longest input of a [`fake_corpus`] entry
+const MAX_FAKE_INPUT_LEN: usize = 16;
+/// The most edges a [`fake_corpus`] entry covers
+const MAX_FAKE_EDGES: usize = 4;
+/// The time between two [`fake_corpus`] entries, for their [`AddedAtMetadata`]
+pub(crate) const FAKE_ENTRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// An in-memory corpus of `entries` [`BytesInput`]s generated from `seed`, `map_size` has to be at least 1.
+///
+/// Entry `n` has a random input, covers up to [`MAX_FAKE_EDGES`] random edges of a map with `map_size` entries,
+/// in its [`MapIndexesMetadata`], and was added `entries - n` times [`FAKE_ENTRY_INTERVAL`] ago, in its [`AddedAtMetadata`].
+pub(crate) fn fake_corpus(
+    seed: u64,
+    entries: usize,
+    map_size: usize,
+) -> InMemoryCorpus<BytesInput> {
+    let mut rand = StdRand::with_seed(seed);
+    let mut corpus = InMemoryCorpus::new();
+    let now = current_time();
+    for n in 0..entries {
+        let len = rand.between(1, MAX_FAKE_INPUT_LEN);
+        let bytes = (0..len).map(|_| rand.next() as u8).collect::<Vec<_>>();
+        let edges = rand.between(1, MAX_FAKE_EDGES);
+        let mut indexes = (0..edges)
+            .map(|_| rand.between(0, map_size - 1))
+            .collect::<Vec<_>>();
+        indexes.sort_unstable();
+        indexes.dedup();
+
+        let mut testcase = Testcase::new(BytesInput::new(bytes));
+        testcase.add_metadata(MapIndexesMetadata::new(indexes));
+        testcase.add_metadata(AddedAtMetadata {
+            time: now.saturating_sub(FAKE_ENTRY_INTERVAL * (entries - n) as u32),
+        });
+        corpus
+            .add(testcase)
+            .expect("Adding to an in-memory corpus cannot fail");
+    }
+    corpus
+}
+
+/// The ids of the enabled entries of `corpus`, in insertion order
+pub(crate) fn enabled_ids<C>(corpus: &C) -> Vec<CorpusId>
+where
+    C: Corpus,
+{
+    corpus.ids().collect()
+}
+
+/// The edges covered by the enabled entries of `corpus`, from their [`MapIndexesMetadata`]
+pub(crate) fn covered_edges<C>(corpus: &C) -> Vec<usize>
+where
+    C: Corpus,
+{
+    let mut edges = corpus
+        .ids()
+        .flat_map(|id| {
+            corpus
+                .get(id)
+                .unwrap()
+                .borrow()
+                .metadata::<MapIndexesMetadata>()
+                .map(|indexes| indexes.list.clone())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    edges.sort_unstable();
+    edges.dedup();
+    edges
+}
+
+/// The least a state needs for corpus-level logic: an in-memory corpus, a seeded [`StdRand`],
+/// metadata, an executions counter, and a stop flag.
+#[derive(Debug)]
+pub(crate) struct FakeState {
+    corpus: InMemoryCorpus<BytesInput>,
+    rand: StdRand,
+    metadata: SerdeAnyMap,
+    executions: u64,
+    stop_requested: bool,
+    #[cfg(feature = "scalability_introspection")]
+    scalability_monitor: ScalabilityMonitor,
+}
+
+impl FakeState {
+    /// A state for the `corpus`, rolling the dice from `seed`
+    pub(crate) fn new(seed: u64, corpus: InMemoryCorpus<BytesInput>) -> Self {
+        Self {
+            corpus,
+            rand: StdRand::with_seed(seed),
+            metadata: SerdeAnyMap::new(),
+            executions: 0,
+            stop_requested: false,
+            #[cfg(feature = "scalability_introspection")]
+            scalability_monitor: ScalabilityMonitor::new(),
+        }
+    }
+
+    /// A state for a [`fake_corpus`], from the same `seed`
+    pub(crate) fn generate(seed: u64, entries: usize, map_size: usize) -> Self {
+        Self::new(seed, fake_corpus(seed, entries, map_size))
+    }
+}
+
+impl HasCorpus for FakeState {
+    type Corpus = InMemoryCorpus<BytesInput>;
+
+    fn corpus(&self) -> &InMemoryCorpus<BytesInput> {
+        &self.corpus
+    }
+
+    fn corpus_mut(&mut self) -> &mut InMemoryCorpus<BytesInput> {
+        &mut self.corpus
+    }
+}
+
+impl HasRand for FakeState {
+    type Rand = StdRand;
+
+    fn rand(&self) -> &StdRand {
+        &self.rand
+    }
+
+    fn rand_mut(&mut self) -> &mut StdRand {
+        &mut self.rand
+    }
+}
+
+impl HasMetadata for FakeState {
+    fn metadata_map(&self) -> &SerdeAnyMap {
+        &self.metadata
+    }
+
+    fn metadata_map_mut(&mut self) -> &mut SerdeAnyMap {
+        &mut self.metadata
+    }
+}
+
+impl HasExecutions for FakeState {
+    fn executions(&self) -> &u64 {
+        &self.executions
+    }
+
+    fn executions_mut(&mut self) -> &mut u64 {
+        &mut self.executions
+    }
+}
+
+impl Stoppable for FakeState {
+    fn stop_requested(&self) -> bool {
+        self.stop_requested
+    }
+
+    fn request_stop(&mut self) {
+        self.stop_requested = true;
+    }
+
+    fn discard_stop_request(&mut self) {
+        self.stop_requested = false;
+    }
+}
+
+#[cfg(feature = "scalability_introspection")]
+impl HasScalabilityMonitor for FakeState {
+    fn scalability_monitor(&self) -> &ScalabilityMonitor {
+        &self.scalability_monitor
+    }
+
+    fn scalability_monitor_mut(&mut self) -> &mut ScalabilityMonitor {
+        &mut self.scalability_monitor
+    }
+}
+
+/// The state of the fuzzer tests, with in-memory corpora of [`BytesInput`]s
+pub(crate) type BytesState =
+    StdState<BytesInput, InMemoryCorpus<BytesInput>, StdRand, InMemoryCorpus<BytesInput>>;
+
+/// A [`BytesState`] with an empty corpus, initialized by `feedback` and `objective`, rolling the dice from seed 0
+pub(crate) fn bytes_state<F, O>(feedback: &mut F, objective: &mut O) -> BytesState
+where
+    F: StateInitializer<BytesState>,
+    O: StateInitializer<BytesState>,
+{
+    bytes_state_with_corpus(InMemoryCorpus::new(), feedback, objective)
+}
+
+/// Like [`bytes_state`], but starting with `corpus`
+pub(crate) fn bytes_state_with_corpus<F, O>(
+    corpus: InMemoryCorpus<BytesInput>,
+    feedback: &mut F,
+    objective: &mut O,
+) -> BytesState
+where
+    F: StateInitializer<BytesState>,
+    O: StateInitializer<BytesState>,
+{
+    StdState::new(
+        StdRand::with_seed(0),
+        corpus,
+        InMemoryCorpus::new(),
+        feedback,
+        objective,
+    )
+    .expect("Initializing an in-memory state cannot fail")
+}
+
+/// An event manager remembering the names of the events fired to it, processing none
+#[derive(Debug)]
+pub(crate) struct RecordingEventManager<S> {
+    /// If events are fired at all, see [`EventFirer::should_send`]
+    pub(crate) should_send: bool,
+    /// The names of the fired events, in order
+    pub(crate) fired: Vec<&'static str>,
+    phantom: PhantomData<S>,
+}
+
+impl<S> RecordingEventManager<S> {
+    /// A manager that sends events, and did not record any yet
+    pub(crate) fn new() -> Self {
+        Self {
+            should_send: true,
+            fired: Vec::new(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<S> Default for RecordingEventManager<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> UsesState for RecordingEventManager<S>
+where
+    S: State,
+{
+    type State = S;
+}
+
+impl<S> EventFirer for RecordingEventManager<S>
+where
+    S: State,
+{
+    fn fire(&mut self, _state: &mut S, event: Event<S::Input>) -> Result<(), Error> {
+        self.fired.push(event.name());
+        Ok(())
+    }
+
+    fn should_send(&self) -> bool {
+        self.should_send
+    }
+}
+
+impl<S> EventRestarter for RecordingEventManager<S> where S: State {}
+
+impl<E, S, Z> EventProcessor<E, Z> for RecordingEventManager<S>
+where
+    S: State,
+{
+    fn process(
+        &mut self,
+        _fuzzer: &mut Z,
+        _state: &mut S,
+        _executor: &mut E,
+    ) -> Result<usize, Error> {
+        Ok(0)
+    }
+
+    fn on_shutdown(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<S> HasPendingEvents for RecordingEventManager<S> {
+    fn pending_events(&self) -> bool {
+        false
+    }
+}
+
+impl<S> ProgressReporter for RecordingEventManager<S> where
+    S: State + HasExecutions + HasLastReportTime + HasMetadata
+{
+}
+
+/// Three [`LlmpClient`]s that talk to each other directly, without a broker in between:
+/// clients 1 and 2 listen to the main client 0, which listens to client 2 only.
+#[cfg(feature = "std")]
+pub(crate) fn llmp_ring() -> Vec<LlmpClient<StdShMemProvider>> {
+    let mut shmem_provider = StdShMemProvider::new().unwrap();
+    let mut clients = (0..3)
+        .map(|id| {
+            LlmpClient::new(
+                shmem_provider.clone(),
+                LlmpSharedMap::new(ClientId(id), shmem_provider.new_shmem(1024).unwrap()),
+                ClientId(id),
+            )
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let from_main = clients[0].sender().describe().unwrap();
+    let from_last = clients[2].sender().describe().unwrap();
+    *clients[0].receiver_mut() =
+        LlmpReceiver::on_existing_from_description(shmem_provider.clone(), &from_last).unwrap();
+    for id in [1, 2] {
+        *clients[id].receiver_mut() =
+            LlmpReceiver::on_existing_from_description(shmem_provider.clone(), &from_main).unwrap();
+    }
+    // A little hack for CI. Don't do that in a real-world scenario.
+    for client in &mut clients {
+        unsafe {
+            client.mark_safe_to_unmap();
+        }
+    }
+    clients
+}
@@ -1,5 +1,8 @@
 //! Compression of events passed between a broker and clients.
 //! Currently we use the gzip compression algorithm for its fast decompression performance.
+//!
+//! Events are compressed to raw deflate streams, files meant to be read by other tools
+//! can be wrapped in a gzip container with [`GzipCompressor::compress_gzip`].
 
 use alloc::vec::Vec;
 use core::fmt::Debug;
@@ -72,6 +75,103 @@ impl GzipCompressor {
     }
 }
 
+/// The magic bytes and the compression method (deflate) that start every gzip member
+const GZIP_MAGIC: [u8; 3] = [0x1f, 0x8b, 0x08];
+/// The header flags of gzip, see RFC 1952
+const GZIP_FHCRC: u8 = 1 << 1;
+const GZIP_FEXTRA: u8 = 1 << 2;
+const GZIP_FNAME: u8 = 1 << 3;
+const GZIP_FCOMMENT: u8 = 1 << 4;
+/// The operating system field of a gzip header, unknown
+const GZIP_OS_UNKNOWN: u8 = 0xff;
+
+/// The lookup table of the CRC-32 used by gzip
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < table.len() {
+        #[allow(clippy::cast_possible_truncation)]
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// The CRC-32 of `buf`, as used by gzip
+fn crc32(buf: &[u8]) -> u32 {
+    !buf.iter().fold(!0, |crc, byte| {
+        CRC32_TABLE[((crc ^ u32::from(*byte)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+impl GzipCompressor {
+    /// Compress `buf` into a gzip file (RFC 1952), as read by `gzip -d`.
+    /// Will ignore the preset threshold, and always compress.
+    #[must_use]
+    pub fn compress_gzip(&self, buf: &[u8]) -> Vec<u8> {
+        let deflated = self.compress(buf);
+        let mut gzip = Vec::with_capacity(deflated.len() + 18);
+        gzip.extend_from_slice(&GZIP_MAGIC);
+        // No flags, no modification time, no extra flags
+        gzip.extend_from_slice(&[0; 6]);
+        gzip.push(GZIP_OS_UNKNOWN);
+        gzip.extend_from_slice(&deflated);
+        gzip.extend_from_slice(&crc32(buf).to_le_bytes());
+        #[allow(clippy::cast_possible_truncation)] // The size is stored modulo 2^32
+        gzip.extend_from_slice(&(buf.len() as u32).to_le_bytes());
+        gzip
+    }
+
+    /// Decompress a gzip file (RFC 1952) of a single member, verifying its checksum.
+    #[allow(clippy::unused_self)]
+    pub fn decompress_gzip(&self, buf: &[u8]) -> Result<Vec<u8>, Error> {
+        if buf.len() < 18 || buf[..3] != GZIP_MAGIC {
+            return Err(Error::compression());
+        }
+        let flags = buf[3];
+        let trailer = buf.len() - 8;
+        let mut pos = 10;
+        if flags & GZIP_FEXTRA != 0 {
+            let len = buf.get(pos..pos + 2).ok_or_else(Error::compression)?;
+            pos += 2 + usize::from(u16::from_le_bytes([len[0], len[1]]));
+        }
+        for field in [GZIP_FNAME, GZIP_FCOMMENT] {
+            if flags & field != 0 {
+                let end = buf
+                    .get(pos..)
+                    .and_then(|rest| rest.iter().position(|b| *b == 0))
+                    .ok_or_else(Error::compression)?;
+                pos += end + 1;
+            }
+        }
+        if flags & GZIP_FHCRC != 0 {
+            pos += 2;
+        }
+        if pos > trailer {
+            return Err(Error::compression());
+        }
+
+        let decompressed = self.decompress(&buf[pos..trailer])?;
+        let crc = u32::from_le_bytes(buf[trailer..trailer + 4].try_into().unwrap());
+        let size = u32::from_le_bytes(buf[trailer + 4..].try_into().unwrap());
+        #[allow(clippy::cast_possible_truncation)] // The size is stored modulo 2^32
+        if crc != crc32(&decompressed) || size != decompressed.len() as u32 {
+            return Err(Error::compression());
+        }
+        Ok(decompressed)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::compress::GzipCompressor;
@@ -87,6 +187,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_gzip() {
+        let compressor = GzipCompressor::new();
+        let data = [1u8; 1024];
+        let gzip = compressor.compress_gzip(&data);
+        assert_eq!(gzip[..2], [0x1f, 0x8b]);
+        assert_eq!(compressor.decompress_gzip(&gzip).unwrap(), data);
+
+        // Written by `printf 'hello gzip' | gzip -n`
+        let from_gzip = [
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0xcb, 0x48, 0xcd, 0xc9,
+            0xc9, 0x57, 0x48, 0xaf, 0xca, 0x2c, 0x00, 0x00, 0x19, 0x6a, 0xd2, 0xdf, 0x0a, 0x00,
+            0x00, 0x00,
+        ];
+        assert_eq!(
+            compressor.decompress_gzip(&from_gzip).unwrap(),
+            b"hello gzip"
+        );
+
+        // A corrupted checksum
+        let mut corrupted = gzip.clone();
+        let crc = corrupted.len() - 8;
+        corrupted[crc] ^= 1;
+        assert!(compressor.decompress_gzip(&corrupted).is_err());
+        assert!(compressor.decompress_gzip(&gzip[..10]).is_err());
+    }
+
     #[test]
     fn test_threshold() {
         let compressor = GzipCompressor::with_threshold(1024);
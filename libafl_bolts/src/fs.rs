@@ -61,6 +61,65 @@ where
     inner(path.as_ref(), bytes)
 }
 
+/// Write a file atomically, and make sure it reached the disk before returning
+///
+/// Like [`write_file_atomic`], but the `.{file_name}.tmp` file is synced before it replaces `path`,
+/// and (on unix) the parent directory is synced after the rename.
+/// A leftover tmp-file from an earlier crash is overwritten.
+/// After a crash, `path` either holds the old or the new contents, never a partial file.
+///
+/// # Errors
+/// Can error if the file can't be written, synced or renamed.
+pub fn write_file_durable<P>(path: P, bytes: &[u8]) -> Result<(), Error>
+where
+    P: AsRef<Path>,
+{
+    write_file_durable_with(path, bytes, || Ok(()))
+}
+
+/// Like [`write_file_durable`], but calls `before_replace` once the new contents reached the disk,
+/// right before they replace `path`, e.g., to rotate older copies of `path` out of the way.
+///
+/// If `before_replace` fails, `path` is not replaced.
+///
+/// # Errors
+/// Can error if the file can't be written, synced or renamed, or if `before_replace` fails.
+pub fn write_file_durable_with<P, F>(path: P, bytes: &[u8], before_replace: F) -> Result<(), Error>
+where
+    P: AsRef<Path>,
+    F: FnOnce() -> Result<(), Error>,
+{
+    let path = path.as_ref();
+    let mut tmpfile_name = path.to_path_buf();
+    tmpfile_name.set_file_name(format!(
+        ".{}.tmp",
+        tmpfile_name.file_name().unwrap().to_string_lossy()
+    ));
+
+    let mut tmpfile = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&tmpfile_name)?;
+    tmpfile.write_all(bytes)?;
+    tmpfile.sync_all()?;
+    drop(tmpfile);
+
+    before_replace()?;
+
+    fs::rename(&tmpfile_name, path)?;
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        let parent = if parent.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            parent
+        };
+        File::open(parent)?.sync_all()?;
+    }
+    Ok(())
+}
+
 /// An [`InputFile`] to write fuzzer input to.
 /// The target/forkserver will read from this file.
 #[cfg(feature = "std")]
@@ -139,7 +139,7 @@ pub mod bolts_prelude {
     pub use super::{cpu::*, os::*};
 }
 
-#[cfg(all(unix, feature = "std"))]
+#[cfg(feature = "alloc")]
 use alloc::boxed::Box;
 #[cfg(feature = "alloc")]
 use alloc::{borrow::Cow, vec::Vec};
@@ -315,6 +315,134 @@ pub enum Error {
     InvalidCorpus(String, ErrorBacktrace),
     /// Error specific to a runtime like QEMU or Frida
     Runtime(String, ErrorBacktrace),
+    /// Error in a distributed event flow, telling which client, message, and phase it is about
+    #[cfg(feature = "alloc")]
+    Distributed(Box<DistributedError>, ErrorBacktrace),
+}
+
+/// The phase of a distributed event flow a [`DistributedError`] happened in
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DistributedPhase {
+    /// Sending a message to another node
+    Forwarding,
+    /// Receiving a message, such as an event that should not have arrived
+    Receiving,
+    /// Decompressing or deserializing a received message
+    Deserializing,
+}
+
+#[cfg(feature = "alloc")]
+impl Display for DistributedPhase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Forwarding => write!(f, "forwarding"),
+            Self::Receiving => write!(f, "receiving"),
+            Self::Deserializing => write!(f, "deserializing"),
+        }
+    }
+}
+
+/// The context of an [`Error::Distributed`]: the client a message came from, its llmp tag,
+/// the event it carried, and the phase the error happened in.
+///
+/// Errors about a single received message are recoverable, see [`DistributedError::is_recoverable`],
+/// so the receiver can drop the message and continue.
+#[cfg(feature = "alloc")]
+#[derive(Debug)]
+pub struct DistributedError {
+    /// The phase the error happened in
+    pub phase: DistributedPhase,
+    /// The client the message came from, if known
+    pub client_id: Option<ClientId>,
+    /// The llmp tag of the message, if known
+    pub tag: Option<llmp::Tag>,
+    /// The detailed name of the event, if known
+    pub event: Option<String>,
+    /// What went wrong
+    pub message: String,
+    /// The error that caused this one, if any
+    pub source: Option<Error>,
+}
+
+#[cfg(feature = "alloc")]
+impl DistributedError {
+    /// Create a new [`DistributedError`] without any context yet
+    #[must_use]
+    pub fn new<S>(phase: DistributedPhase, message: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Self {
+            phase,
+            client_id: None,
+            tag: None,
+            event: None,
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// The client the message came from
+    #[must_use]
+    pub fn from_client(mut self, client_id: ClientId) -> Self {
+        self.client_id = Some(client_id);
+        self
+    }
+
+    /// The llmp tag of the message
+    #[must_use]
+    pub fn with_tag(mut self, tag: llmp::Tag) -> Self {
+        self.tag = Some(tag);
+        self
+    }
+
+    /// The detailed name of the event the message carried
+    #[must_use]
+    pub fn with_event<S>(mut self, event: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.event = Some(event.into());
+        self
+    }
+
+    /// The error that caused this one
+    #[must_use]
+    pub fn caused_by(mut self, source: Error) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// If only a single received message is affected, so it can be dropped, and the receiver can continue
+    #[must_use]
+    pub fn is_recoverable(&self) -> bool {
+        matches!(
+            self.phase,
+            DistributedPhase::Receiving | DistributedPhase::Deserializing
+        )
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Display for DistributedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} failed", self.phase)?;
+        if let Some(event) = &self.event {
+            write!(f, " for {event}")?;
+        }
+        if let Some(client_id) = self.client_id {
+            write!(f, " from {client_id:?}")?;
+        }
+        if let Some(tag) = self.tag {
+            write!(f, " with {tag:?}")?;
+        }
+        write!(f, ": {}", self.message)?;
+        if let Some(source) = &self.source {
+            write!(f, "\nCaused by: {source}")?;
+        }
+        Ok(())
+    }
 }
 
 impl Error {
@@ -462,15 +590,38 @@ impl Error {
     {
         Error::Runtime(arg.into(), ErrorBacktrace::new())
     }
+
+    /// Error in a distributed event flow, with its context
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn distributed(err: DistributedError) -> Self {
+        Error::Distributed(Box::new(err), ErrorBacktrace::new())
+    }
+
+    /// Wrap this error into an [`Error::Distributed`] with the given `context`.
+    ///
+    /// [`Error::ShuttingDown`] and errors that already are [`Error::Distributed`] are returned as they are.
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn in_distributed_flow(self, context: DistributedError) -> Self {
+        match self {
+            Error::ShuttingDown | Error::Distributed(..) => self,
+            err => Error::distributed(context.caused_by(err)),
+        }
+    }
 }
 
 impl core::error::Error for Error {
-    #[cfg(feature = "std")]
     fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
-        if let Self::OsError(err, _, _) = self {
-            Some(err)
-        } else {
-            None
+        match self {
+            #[cfg(feature = "std")]
+            Self::OsError(err, _, _) => Some(err),
+            #[cfg(feature = "alloc")]
+            Self::Distributed(err, _) => err
+                .source
+                .as_ref()
+                .map(|source| source as &(dyn core::error::Error + 'static)),
+            _ => None,
         }
     }
 }
@@ -541,6 +692,11 @@ impl Display for Error {
                 write!(f, "Runtime error: {0}", &s)?;
                 display_error_backtrace(f, b)
             }
+            #[cfg(feature = "alloc")]
+            Self::Distributed(err, b) => {
+                write!(f, "Distributed error: {err}")?;
+                display_error_backtrace(f, b)
+            }
         }
     }
 }
@@ -1314,4 +1470,44 @@ mod tests {
         log::set_max_level(log::LevelFilter::Debug);
         log::info!("Test");
     }
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn test_distributed_error() {
+        use alloc::string::ToString;
+
+        use crate::{llmp::Tag, ClientId, DistributedError, DistributedPhase, Error};
+
+        let context = || {
+            DistributedError::new(DistributedPhase::Deserializing, "Could not decode")
+                .from_client(ClientId(3))
+                .with_tag(Tag(0x2C0E))
+                .with_event("Testcase")
+        };
+
+        let err = Error::serialize("bad bytes").in_distributed_flow(context());
+        let Error::Distributed(ref inner, _) = err else {
+            panic!("Expected a distributed error, got {err:?}");
+        };
+        assert!(inner.is_recoverable());
+        assert!(matches!(inner.source, Some(Error::Serialize(..))));
+        assert!(core::error::Error::source(&err).is_some());
+        let message = err.to_string();
+        assert!(
+            message.contains("deserializing failed for Testcase from ClientId(3) with Tag(2C0E)")
+        );
+        assert!(message.contains("bad bytes"));
+
+        // Shutting down and errors that already have a context are kept as they are
+        assert!(matches!(
+            Error::shutting_down().in_distributed_flow(context()),
+            Error::ShuttingDown
+        ));
+        let forwarding =
+            Error::distributed(DistributedError::new(DistributedPhase::Forwarding, "Full"));
+        let Error::Distributed(inner, _) = forwarding.in_distributed_flow(context()) else {
+            panic!("Expected a distributed error");
+        };
+        assert_eq!(inner.phase, DistributedPhase::Forwarding);
+        assert!(!inner.is_recoverable());
+    }
 }
@@ -84,6 +84,12 @@ use std::{
     sync::mpsc::channel,
     thread,
 };
+#[cfg(all(unix, feature = "std"))]
+use std::{
+    fs,
+    os::unix::net::{UnixListener, UnixStream},
+    path::Path,
+};
 
 #[cfg(all(debug_assertions, feature = "llmp_debug", feature = "std"))]
 use backtrace::Backtrace;
@@ -368,6 +374,9 @@ impl TryFrom<Vec<u8>> for TcpResponse {
 pub enum Listener {
     /// Listener listening on `tcp`.
     Tcp(TcpListener),
+    /// Listener listening on a unix domain socket.
+    #[cfg(unix)]
+    Unix(UnixListener),
 }
 
 /// A listener stream abstraction
@@ -376,6 +385,9 @@ pub enum Listener {
 pub enum ListenerStream {
     /// Listener listening on `tcp`.
     Tcp(TcpStream, SocketAddr),
+    /// Listener listening on a unix domain socket.
+    #[cfg(unix)]
+    Unix(UnixStream),
     /// No listener provided.
     Empty(),
 }
@@ -391,6 +403,14 @@ impl Listener {
                     ListenerStream::Empty()
                 }
             },
+            #[cfg(unix)]
+            Listener::Unix(inner) => match inner.accept() {
+                Ok(res) => ListenerStream::Unix(res.0),
+                Err(err) => {
+                    log::warn!("Ignoring failed accept: {err:?}");
+                    ListenerStream::Empty()
+                }
+            },
         }
     }
 }
@@ -460,11 +480,39 @@ fn tcp_bind(port: u16) -> Result<TcpListener, Error> {
     Ok(listener)
 }
 
+/// Bind to a unix domain socket at the given `path`.
+/// A leftover socket of a broker that no longer listens on it is replaced.
+#[cfg(all(unix, feature = "std"))]
+fn uds_bind(path: &Path) -> Result<UnixListener, Error> {
+    match UnixListener::bind(path) {
+        Err(e)
+            if e.kind() == ErrorKind::AddrInUse
+                && UnixStream::connect(path)
+                    .is_err_and(|e| e.kind() == ErrorKind::ConnectionRefused) =>
+        {
+            log::info!("Replacing stale unix socket at {}", path.display());
+            fs::remove_file(path)?;
+            Ok(UnixListener::bind(path)?)
+        }
+        res => Ok(res?),
+    }
+}
+
 /// Send one message as `u32` len and `[u8;len]` bytes
 #[cfg(feature = "std")]
 pub fn send_tcp_msg<T>(stream: &mut TcpStream, msg: &T) -> Result<(), Error>
 where
     T: Serialize,
+{
+    send_stream_msg(stream, msg)
+}
+
+/// Send one message as `u32` len and `[u8;len]` bytes over any stream, see [`send_tcp_msg`]
+#[cfg(feature = "std")]
+fn send_stream_msg<W, T>(stream: &mut W, msg: &T) -> Result<(), Error>
+where
+    W: Write,
+    T: Serialize,
 {
     let msg = postcard::to_allocvec(msg)?;
     if msg.len() > u32::MAX as usize {
@@ -498,6 +546,15 @@ pub fn recv_tcp_msg(stream: &mut TcpStream) -> Result<Vec<u8>, Error> {
         stream.read_timeout().unwrap_or(None)
     );
 
+    recv_stream_msg(stream)
+}
+
+/// Receive one message of `u32` len and `[u8; len]` bytes from any stream, see [`recv_tcp_msg`]
+#[cfg(feature = "std")]
+fn recv_stream_msg<R>(stream: &mut R) -> Result<Vec<u8>, Error>
+where
+    R: Read,
+{
     let mut size_bytes = [0_u8; 4];
     stream.read_exact(&mut size_bytes)?;
     let size = u32::from_be_bytes(size_bytes);
@@ -757,6 +814,41 @@ where
         }
     }
 
+    /// Creates either a broker, if no broker listens on the unix domain socket at `path` yet,
+    /// or a client, connected to the broker listening there.
+    /// A broker creates the socket and accepts clients on it; access is governed by the
+    /// filesystem permissions of the socket file.
+    #[cfg(all(unix, feature = "std"))]
+    pub fn on_uds<P>(shmem_provider: SP, path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        match uds_bind(path) {
+            Ok(listener) => {
+                log::info!("We're the broker");
+
+                let mut broker = LlmpBroker::new(shmem_provider, tuple_list!())?;
+                let _listener_thread = broker
+                    .inner_mut()
+                    .launch_listener(Listener::Unix(listener))?;
+                Ok(LlmpConnection::IsBroker { broker })
+            }
+            Err(Error::OsError(e, ..)) if e.kind() == ErrorKind::AddrInUse => {
+                log::info!(
+                    "We're the client (socket {} already bound by broker)",
+                    path.display()
+                );
+                let client = LlmpClient::create_attach_to_uds(shmem_provider, path)?;
+                Ok(LlmpConnection::IsClient { client })
+            }
+            Err(e) => {
+                log::error!("{e:?}");
+                Err(e)
+            }
+        }
+    }
+
     /// Creates a new broker on the given port
     #[cfg(feature = "std")]
     pub fn broker_on_port(shmem_provider: SP, port: u16) -> Result<Self, Error> {
@@ -959,6 +1051,48 @@ where
         self.id
     }
 
+    /// The bytes used on the current out page, and the bytes it can hold in total.
+    ///
+    /// Once a page is full, the sender moves on to a new one.
+    #[must_use]
+    pub fn page_usage(&self) -> (usize, usize) {
+        // The current out page is always mapped and initialized
+        unsafe {
+            let page = self.out_shmems.last().unwrap().page();
+            ((*page).size_used, (*page).size_total)
+        }
+    }
+
+    /// If sending `buf_len` bytes now needs a new page, while no receiver mapped the older pages yet.
+    ///
+    /// The sender does not wait for its receivers, it gives up once too many pages went unread.
+    /// Callers that must not stall can hold back, or drop, their messages while this is `true`.
+    #[must_use]
+    pub fn would_block(&self, buf_len: usize) -> bool {
+        if self.keep_pages_forever {
+            return false;
+        }
+        // The out pages are always mapped and initialized
+        unsafe {
+            let page = self.out_shmems.last().unwrap().page();
+            if (*page).size_used + llmp_align(size_of::<LlmpMsg>() + buf_len) + EOP_MSG_SIZE
+                <= (*page).size_total
+            {
+                return false;
+            }
+            let oldest_read = self
+                .out_shmems
+                .split_last()
+                .unwrap()
+                .1
+                .first()
+                .is_some_and(|map| {
+                    (*map.page()).receivers_joined_count.load(Ordering::Acquire) > 0
+                });
+            !oldest_read && self.out_shmems.len() >= LLMP_CFG_MAX_PENDING_UNREAD_PAGES
+        }
+    }
+
     /// Completely reset the current sender map.
     /// Afterwards, no receiver should read from it at a different location.
     /// This is only useful if all connected llmp parties start over, for example after a crash.
@@ -1044,6 +1178,23 @@ where
         }
     }
 
+    /// Waits for the sender to be safe to unmap, giving up after `timeout`.
+    ///
+    /// Returns `true` if it is safe to unmap, `false` if the timeout passed first.
+    #[cfg(feature = "std")]
+    pub fn await_safe_to_unmap_timeout(&self, timeout: Duration) -> bool {
+        let start = current_time();
+        loop {
+            if self.safe_to_unmap() {
+                return true;
+            }
+            if current_time().saturating_sub(start) >= timeout {
+                return false;
+            }
+            hint::spin_loop();
+        }
+    }
+
     /// If we are allowed to unmap this client
     pub fn safe_to_unmap(&self) -> bool {
         let current_out_shmem = self.out_shmems.last().unwrap();
@@ -1807,6 +1958,33 @@ where
         }
     }
 
+    /// If a message is waiting to be received, without receiving it.
+    ///
+    /// Never `false` while [`Self::recv_buf`] would return a message, so it can be polled before receiving
+    /// without missing any. It may be `true` for LLMP internal messages, such as the end of a page,
+    /// after which [`Self::recv_buf`] returns `None`.
+    #[must_use]
+    pub fn has_pending(&self) -> bool {
+        // # Safety
+        // Only reads the message ids of the current page, and of the last message received from it.
+        unsafe {
+            let last_msg = self.last_msg_recvd;
+            if !last_msg.is_null() && self.highest_msg_id > (*last_msg).message_id {
+                return true;
+            }
+            let current_msg_id = (*self.current_recv_shmem.page())
+                .current_msg_id
+                .load(Ordering::Relaxed);
+            if current_msg_id == 0 {
+                false
+            } else if last_msg.is_null() {
+                true
+            } else {
+                (*last_msg).message_id.0 != current_msg_id
+            }
+        }
+    }
+
     /// Returns the next message, tag, buf, if available, else None
     #[allow(clippy::type_complexity)]
     #[inline]
@@ -2970,10 +3148,7 @@ where
             }
         };
 
-        let hostname = hostname::get()
-            .unwrap_or_else(|_| "<unknown>".into())
-            .to_string_lossy()
-            .into();
+        let hostname = crate::os::hostname();
 
         send_tcp_msg(&mut stream, &TcpRequest::RemoteBrokerHello { hostname })?;
 
@@ -3083,6 +3258,19 @@ where
         self.launch_listener(Listener::Tcp(listener))
     }
 
+    /// Launches a thread listening on a unix domain socket at the given `path`,
+    /// on which new local clients may connect to this broker.
+    /// Broker to broker connections are not supported over unix domain sockets.
+    #[cfg(all(unix, feature = "std"))]
+    pub fn launch_uds_listener_on<P>(&mut self, path: P) -> Result<thread::JoinHandle<()>, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let listener = uds_bind(path.as_ref())?;
+        log::info!("Server listening on {}", path.as_ref().display());
+        self.launch_listener(Listener::Unix(listener))
+    }
+
     /// Announces a new client on the given shared map.
     /// Called from a background thread, typically.
     /// Upon receiving this message, the broker should map the announced page and start tracking it for new messages.
@@ -3272,6 +3460,37 @@ where
         ret
     }
 
+    /// Sends the broker hello to a new connection and receives its request.
+    /// Returns `None` if the connection broke down.
+    #[cfg(feature = "std")]
+    fn accept_request<S>(stream: &mut S, broker_hello: &TcpResponse) -> Option<TcpRequest>
+    where
+        S: Read + Write,
+    {
+        // Send initial information, without anyone asking.
+        // This makes it a tiny bit easier to map the broker map for new Clients.
+        if let Err(e) = send_stream_msg(stream, broker_hello) {
+            log::error!("Error sending initial hello: {e:?}");
+            return None;
+        }
+
+        let buf = match recv_stream_msg(stream) {
+            Ok(buf) => buf,
+            Err(e) => {
+                log::error!("Error receving from tcp: {e:?}");
+                return None;
+            }
+        };
+
+        match buf.try_into() {
+            Ok(req) => Some(req),
+            Err(e) => {
+                log::error!("Could not deserialize tcp message: {e:?}");
+                None
+            }
+        }
+    }
+
     /// handles a single tcp request in the current context.
     #[cfg(feature = "std")]
     fn handle_tcp_request(
@@ -3282,29 +3501,6 @@ where
         broker_shmem_description: &ShMemDescription,
     ) {
         match request {
-            TcpRequest::ClientQuit { client_id } => {
-                // todo search the ancestor_id and remove it.
-                match Self::announce_client_exit(sender, client_id.0) {
-                    Ok(()) => (),
-                    Err(e) => log::info!("Error announcing client exit: {e:?}"),
-                }
-            }
-            TcpRequest::LocalClientHello { shmem_description } => {
-                match Self::announce_new_client(sender, shmem_description) {
-                    Ok(()) => (),
-                    Err(e) => log::info!("Error forwarding client on map: {e:?}"),
-                };
-
-                if let Err(e) = send_tcp_msg(
-                    &mut stream,
-                    &TcpResponse::LocalClientAccepted {
-                        client_id: *current_client_id,
-                    },
-                ) {
-                    log::info!("An error occurred sending via tcp {e}");
-                };
-                current_client_id.0 += 1;
-            }
             TcpRequest::RemoteBrokerHello { hostname } => {
                 log::info!("B2B new client: {hostname}");
 
@@ -3330,6 +3526,47 @@ where
                     current_client_id.0 += 1;
                 }
             }
+            _ => Self::handle_local_request(&mut stream, request, current_client_id, sender),
+        };
+    }
+
+    /// handles a single request of a local client, on any stream.
+    #[cfg(feature = "std")]
+    fn handle_local_request<S>(
+        stream: &mut S,
+        request: &TcpRequest,
+        current_client_id: &mut ClientId,
+        sender: &mut LlmpSender<SP>,
+    ) where
+        S: Write,
+    {
+        match request {
+            TcpRequest::ClientQuit { client_id } => {
+                // todo search the ancestor_id and remove it.
+                match Self::announce_client_exit(sender, client_id.0) {
+                    Ok(()) => (),
+                    Err(e) => log::info!("Error announcing client exit: {e:?}"),
+                }
+            }
+            TcpRequest::LocalClientHello { shmem_description } => {
+                match Self::announce_new_client(sender, shmem_description) {
+                    Ok(()) => (),
+                    Err(e) => log::info!("Error forwarding client on map: {e:?}"),
+                };
+
+                if let Err(e) = send_stream_msg(
+                    stream,
+                    &TcpResponse::LocalClientAccepted {
+                        client_id: *current_client_id,
+                    },
+                ) {
+                    log::info!("An error occurred sending via tcp {e}");
+                };
+                current_client_id.0 += 1;
+            }
+            TcpRequest::RemoteBrokerHello { hostname } => {
+                log::warn!("Ignoring B2B hello from {hostname}, only supported over tcp");
+            }
         };
     }
 
@@ -3343,10 +3580,7 @@ where
 
         let client_out_shmem_mem = &self.llmp_out.out_shmems.first().unwrap().shmem;
         let broker_shmem_description = client_out_shmem_mem.description();
-        let hostname = hostname::get()
-            .unwrap_or_else(|_| "<unknown>".into())
-            .to_string_lossy()
-            .into();
+        let hostname = crate::os::hostname();
         let broker_hello = TcpResponse::BrokerConnectHello {
             broker_shmem_description,
             hostname,
@@ -3392,31 +3626,8 @@ where
                             stream.peer_addr().unwrap()
                         );
 
-                        // Send initial information, without anyone asking.
-                        // This makes it a tiny bit easier to map the broker map for new Clients.
-                        match send_tcp_msg(&mut stream, &broker_hello) {
-                            Ok(()) => {}
-                            Err(e) => {
-                                log::error!("Error sending initial hello: {e:?}");
-                                continue;
-                            }
-                        }
-
-                        let buf = match recv_tcp_msg(&mut stream) {
-                            Ok(buf) => buf,
-                            Err(e) => {
-                                log::error!("Error receving from tcp: {e:?}");
-                                continue;
-                            }
-                        };
-
-                        // log::info!("{:#?}", buf);
-                        let req = match buf.try_into() {
-                            Ok(req) => req,
-                            Err(e) => {
-                                log::error!("Could not deserialize tcp message: {e:?}");
-                                continue;
-                            }
+                        let Some(req) = Self::accept_request(&mut stream, &broker_hello) else {
+                            continue;
                         };
 
                         Self::handle_tcp_request(
@@ -3427,6 +3638,21 @@ where
                             &broker_shmem_description,
                         );
                     }
+                    #[cfg(unix)]
+                    ListenerStream::Unix(mut stream) => {
+                        log::info!("New connection on unix socket: {stream:?}");
+
+                        let Some(req) = Self::accept_request(&mut stream, &broker_hello) else {
+                            continue;
+                        };
+
+                        Self::handle_local_request(
+                            &mut stream,
+                            &req,
+                            &mut current_client_id,
+                            &mut tcp_incoming_sender,
+                        );
+                    }
                     ListenerStream::Empty() => {
                         continue;
                     }
@@ -3570,6 +3796,14 @@ where
         self.sender.await_safe_to_unmap_blocking();
     }
 
+    /// Waits for the sender to be safe to unmap, giving up after `timeout`.
+    ///
+    /// Returns `true` if it is safe to unmap, `false` if the timeout passed first.
+    #[cfg(feature = "std")]
+    pub fn await_safe_to_unmap_timeout(&self, timeout: Duration) -> bool {
+        self.sender.await_safe_to_unmap_timeout(timeout)
+    }
+
     /// If we are allowed to unmap this client
     pub fn safe_to_unmap(&self) -> bool {
         self.sender.safe_to_unmap()
@@ -3673,6 +3907,13 @@ where
         self.sender.alloc_next(buf_len)
     }
 
+    /// If a message from the broker is waiting, without receiving it, see [`LlmpReceiver::has_pending`]
+    #[must_use]
+    #[inline]
+    pub fn has_pending(&self) -> bool {
+        self.receiver.has_pending()
+    }
+
     /// Returns the next message, tag, buf, if available, else None
     #[allow(clippy::type_complexity)]
     #[inline]
@@ -3709,7 +3950,7 @@ where
     #[cfg(feature = "std")]
     /// Create a [`LlmpClient`], getting the ID from a given port, then also tell the restarter's ID so we ask to be removed later
     /// This is called when, for the first time, the restarter attaches to this process.
-    pub fn create_attach_to_tcp(mut shmem_provider: SP, port: u16) -> Result<Self, Error> {
+    pub fn create_attach_to_tcp(shmem_provider: SP, port: u16) -> Result<Self, Error> {
         let mut stream = match TcpStream::connect((IP_LOCALHOST, port)) {
             Ok(stream) => stream,
             Err(e) => {
@@ -3733,10 +3974,45 @@ where
         };
         log::info!("Connected to port {port}");
 
+        Self::attach_over_stream(shmem_provider, &mut stream)
+    }
+
+    #[cfg(all(unix, feature = "std"))]
+    /// Create a [`LlmpClient`], getting the ID from the broker listening on the unix domain socket at `path`.
+    /// If there is no broker listening there yet, this waits until one shows up.
+    pub fn create_attach_to_uds<P>(shmem_provider: SP, path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let mut stream = loop {
+            match UnixStream::connect(path) {
+                Ok(stream) => break stream,
+                // no socket, or nobody listening on it (yet). loop till the broker is up
+                Err(e)
+                    if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::ConnectionRefused) =>
+                {
+                    log::debug!("No broker at {}. Retrying...", path.display());
+                    thread::sleep(Duration::from_millis(50));
+                }
+                Err(e) => return Err(Error::illegal_state(e.to_string())),
+            }
+        };
+        log::info!("Connected to {}", path.display());
+
+        Self::attach_over_stream(shmem_provider, &mut stream)
+    }
+
+    /// Attach to the broker on the other end of the given `stream`, see [`Self::create_attach_to_tcp`]
+    #[cfg(feature = "std")]
+    fn attach_over_stream<S>(mut shmem_provider: SP, stream: &mut S) -> Result<Self, Error>
+    where
+        S: Read + Write,
+    {
         let TcpResponse::BrokerConnectHello {
             broker_shmem_description,
             hostname: _,
-        } = recv_tcp_msg(&mut stream)?.try_into()?
+        } = recv_stream_msg(stream)?.try_into()?
         else {
             return Err(Error::illegal_state(
                 "Received unexpected Broker Hello".to_string(),
@@ -3754,12 +4030,12 @@ where
         let client_hello_req = TcpRequest::LocalClientHello {
             shmem_description: ret.sender.out_shmems.first().unwrap().shmem.description(),
         };
-        send_tcp_msg(&mut stream, &client_hello_req)?;
+        send_stream_msg(stream, &client_hello_req)?;
 
         // The broker accepted the client, and sent back an ID.
         let TcpResponse::LocalClientAccepted {
             client_id: client_sender_id,
-        } = recv_tcp_msg(&mut stream)?.try_into()?
+        } = recv_stream_msg(stream)?.try_into()?
         else {
             return Err(Error::illegal_state(
                 "Unexpected Response from Broker".to_string(),
@@ -3789,9 +4065,12 @@ mod tests {
     use super::{
         LlmpClient,
         LlmpConnection::{self, IsBroker, IsClient},
-        Tag,
+        LlmpReceiver, LlmpSharedMap, Tag,
+    };
+    use crate::{
+        shmem::{ShMemProvider, StdShMemProvider},
+        ClientId,
     };
-    use crate::shmem::{ShMemProvider, StdShMemProvider};
 
     #[test]
     #[serial]
@@ -3841,4 +4120,38 @@ mod tests {
         // We want at least the tcp and sender clients.
         assert_eq!(broker.inner.llmp_clients.len(), 2);
     }
+
+    #[test]
+    #[serial]
+    #[cfg_attr(miri, ignore)]
+    fn test_has_pending() {
+        let mut shmem_provider = StdShMemProvider::new().unwrap();
+        let mut client = LlmpClient::new(
+            shmem_provider.clone(),
+            LlmpSharedMap::new(ClientId(0), shmem_provider.new_shmem(1024).unwrap()),
+            ClientId(0),
+        )
+        .unwrap();
+        // A little hack for CI. Don't do that in a real-world scenario.
+        unsafe {
+            client.mark_safe_to_unmap();
+        }
+        let mut receiver = LlmpReceiver::on_existing_from_description(
+            shmem_provider.clone(),
+            &client.sender().describe().unwrap(),
+        )
+        .unwrap();
+        assert!(!receiver.has_pending());
+
+        client.send_buf(Tag(0x1337), &[1]).unwrap();
+        client.send_buf(Tag(0x1337), &[2]).unwrap();
+        // Peeking does not receive
+        assert!(receiver.has_pending());
+        assert!(receiver.has_pending());
+        assert_eq!(receiver.recv_buf().unwrap().unwrap().2, [1]);
+        assert!(receiver.has_pending());
+        assert_eq!(receiver.recv_buf().unwrap().unwrap().2, [2]);
+        assert!(!receiver.has_pending());
+        assert!(receiver.recv_buf().unwrap().is_none());
+    }
 }
@@ -17,6 +17,8 @@ pub mod pipes;
 
 #[cfg(all(unix, feature = "std"))]
 use alloc::borrow::Cow;
+#[cfg(feature = "std")]
+use alloc::string::String;
 #[cfg(all(unix, feature = "std"))]
 use core::ffi::CStr;
 #[cfg(feature = "std")]
@@ -103,6 +105,16 @@ pub fn startable_self() -> Result<Command, Error> {
     Ok(startable)
 }
 
+/// The hostname of this machine, or `<unknown>` if it cannot be read
+#[cfg(feature = "std")]
+#[must_use]
+pub fn hostname() -> String {
+    hostname::get()
+        .unwrap_or_else(|_| "<unknown>".into())
+        .to_string_lossy()
+        .into()
+}
+
 /// "Safe" wrapper around `dup`, duplicating the given file descriptor
 ///
 /// # Safety
@@ -53,6 +53,26 @@ fn splitmix64(x: &mut u64) -> u64 {
     z ^ (z >> 31)
 }
 
+/// Derive the seed of a single component of a deterministic campaign from its `master_seed`.
+///
+/// The seed only depends on the `master_seed`, the `component` name (by convention, the name of the type),
+/// and the `index` of the component among those with the same name, so two runs with the same
+/// master seed seed all their components the same way, independent of the order they are built in.
+///
+/// The derivation is: hash the `component` name with 64 bit FNV-1a, xor it with the `master_seed`
+/// and with `index` multiplied by the golden ratio constant `0x9e3779b97f4a7c15`,
+/// then mix the result once with splitmix64.
+#[must_use]
+pub fn derive_seed(master_seed: u64, component: &str, index: usize) -> u64 {
+    let mut name_hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in component.bytes() {
+        name_hash ^= u64::from(byte);
+        name_hash = name_hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    let mut seed = master_seed ^ name_hash ^ (index as u64).wrapping_mul(0x9e3779b97f4a7c15);
+    splitmix64(&mut seed)
+}
+
 /// The standard [`Rand`] implementation for `LibAFL`.
 ///
 /// It is usually the right choice, with very good speed and a reasonable randomness.
@@ -553,7 +573,7 @@ mod tests {
     use crate::{
         nonzero,
         rands::{
-            Rand, RomuDuoJrRand, RomuTrioRand, Sfc64Rand, StdRand, XorShift64Rand,
+            derive_seed, Rand, RomuDuoJrRand, RomuTrioRand, Sfc64Rand, StdRand, XorShift64Rand,
             Xoshiro256PlusPlusRand,
         },
     };
@@ -577,6 +597,15 @@ mod tests {
         test_single_rand(&mut Sfc64Rand::with_seed(0));
     }
 
+    #[test]
+    fn test_derive_seed() {
+        let seed = derive_seed(1337, "StdState", 0);
+        assert_eq!(seed, derive_seed(1337, "StdState", 0));
+        assert_ne!(seed, derive_seed(1337, "StdState", 1));
+        assert_ne!(seed, derive_seed(1337, "CorpusShuffle", 0));
+        assert_ne!(seed, derive_seed(1338, "StdState", 0));
+    }
+
     #[test]
     fn test_romutrio_golden() {
         // https://github.com/ziglang/zig/blob/130fb5cb0fb9039e79450c9db58d6590c5bee3b3/lib/std/Random/RomuTrio.zig#L75-L95
@@ -116,15 +116,21 @@ pub mod serdeany_registry {
 
     use alloc::{
         boxed::Box,
+        format,
         string::{String, ToString},
+        vec::Vec,
     };
+    #[cfg(feature = "std")]
+    use core::cell::{Cell, RefCell};
     use core::{any::TypeId, fmt, hash::BuildHasherDefault};
 
     use hashbrown::{
         hash_map::{Values, ValuesMut},
         HashMap,
     };
-    use serde::{de, Deserialize, Serialize};
+    #[cfg(feature = "std")]
+    use serde::de::DeserializeOwned;
+    use serde::{de, Deserialize, Deserializer, Serialize};
 
     use crate::{
         serdeany::{
@@ -134,9 +140,33 @@ pub mod serdeany_registry {
         Error,
     };
 
-    /// A [`HashMap`] that maps from [`TypeRepr`] to a deserializer and its [`TypeRepr`].
+    /// A registered type: its deserializer, its [`TypeId`], its name,
+    /// and the migrations from older campaign schema versions.
     /// We store the [`TypeId`] to assert we don't have duplicate types in the case of the `stable_anymap` feature.
-    type DeserializeCallbackMap = HashMap<TypeRepr, (DeserializeCallback<dyn SerdeAny>, TypeId)>;
+    struct RegistryEntry {
+        cb: DeserializeCallback<dyn SerdeAny>,
+        #[cfg_attr(not(feature = "stable_anymap"), allow(dead_code))]
+        type_id: TypeId,
+        name: &'static str,
+        migrations: Vec<(u32, DeserializeCallback<dyn SerdeAny>)>,
+    }
+
+    impl RegistryEntry {
+        /// The deserializer to use for a state written with the given schema version
+        fn deserializer(&self, schema_version: Option<u32>) -> DeserializeCallback<dyn SerdeAny> {
+            schema_version
+                .and_then(|version| {
+                    self.migrations
+                        .iter()
+                        .find(|(from, _)| *from == version)
+                        .map(|(_, migrate)| *migrate)
+                })
+                .unwrap_or(self.cb)
+        }
+    }
+
+    /// A [`HashMap`] that maps from [`TypeRepr`] to the registered type.
+    type DeserializeCallbackMap = HashMap<TypeRepr, RegistryEntry>;
 
     /// Visitor object used internally for the [`crate::serdeany::SerdeAny`] registry.
     #[derive(Debug)]
@@ -149,6 +179,26 @@ pub mod serdeany_registry {
             formatter.write_str("Expecting a serialized trait object")
         }
 
+        fn visit_seq<V>(self, visitor: V) -> Result<Self::Value, V::Error>
+        where
+            V: serde::de::SeqAccess<'de>,
+        {
+            MaybeBoxDynVisitor {}
+                .visit_seq(visitor)?
+                .ok_or_else(|| de::Error::custom("The metadata was dropped"))
+        }
+    }
+
+    /// Like the [`BoxDynVisitor`], but yields `None` if the object was dropped, see [`LoadContext`].
+    struct MaybeBoxDynVisitor {}
+    #[allow(unused_qualifications)]
+    impl<'de> serde::de::Visitor<'de> for MaybeBoxDynVisitor {
+        type Value = Option<Box<dyn crate::serdeany::SerdeAny>>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("Expecting a serialized trait object")
+        }
+
         fn visit_seq<V>(self, mut visitor: V) -> Result<Self::Value, V::Error>
         where
             V: serde::de::SeqAccess<'de>,
@@ -156,22 +206,136 @@ pub mod serdeany_registry {
             let id: TypeRepr = visitor.next_element()?.unwrap();
 
             let registry = &raw const REGISTRY;
-            let cb = unsafe {
-                (*registry)
-                    .deserializers
-                    .as_ref()
-                    .ok_or_else(||
-                        de::Error::custom(super::ERR_EMPTY_TYPES_REGISTER))?
-                    .get(&id)
-                    .ok_or_else(|| de::Error::custom(format_args!("Cannot deserialize the unregistered type with id {id}. Enable the `serde_autoreg` feature in libafl_bolts or register all requried types manually.")))?
-                    .0
+            if unsafe { (*registry).deserializers.is_none() } {
+                return Err(de::Error::custom(super::ERR_EMPTY_TYPES_REGISTER));
+            }
+            // Only a versioned state is loaded with a context, and only then the objects are framed.
+            // The context is not borrowed while deserializing the object, which may hold objects itself.
+            let (entry, name, settings) = with_load_context(|ctx| {
+                let ctx = ctx.as_deref();
+                let entry = unsafe { (*registry).entry(&id, ctx) };
+                let name = ctx.and_then(|ctx| ctx.type_names.get(&id)).map_or_else(
+                    || format!("with id {id}"),
+                    |name| format!("{name} (id {id})"),
+                );
+                let settings = ctx.map(|ctx| (ctx.drop_unknown, ctx.schema_version));
+                (entry, name, settings)
+            });
+            let framed = settings.is_some();
+            let (drop_unknown, schema_version) = settings.unwrap_or_default();
+
+            let Some(entry) = entry else {
+                if drop_unknown {
+                    visitor.next_element::<Vec<u8>>()?;
+                    log::warn!("Dropping metadata of the unregistered type {name}");
+                    return Ok(None);
+                }
+                let msg = format!("Cannot deserialize the unregistered type {name}. Enable the `serde_autoreg` feature in libafl_bolts or register all requried types manually.");
+                record_load_failure(&msg);
+                return Err(de::Error::custom(msg));
+            };
+
+            let cb = entry.deserializer(schema_version);
+            let res = if framed {
+                let bytes: Vec<u8> = visitor.next_element()?.unwrap();
+                let mut deserializer = postcard::Deserializer::from_bytes(&bytes);
+                cb(&mut <dyn erased_serde::Deserializer>::erase(
+                    &mut deserializer,
+                ))
+                .map_err(de::Error::custom)
+            } else {
+                let seed = DeserializeCallbackSeed::<dyn crate::serdeany::SerdeAny> { cb };
+                visitor.next_element_seed(seed).map(Option::unwrap)
             };
-            let seed = DeserializeCallbackSeed::<dyn crate::serdeany::SerdeAny> { cb };
-            let obj: Self::Value = visitor.next_element_seed(seed)?.unwrap();
-            Ok(obj)
+
+            match res {
+                Ok(obj) => Ok(Some(obj)),
+                // The framed element was consumed as a whole, we can go on without it
+                Err(err) if drop_unknown => {
+                    log::warn!(
+                        "Dropping metadata of type {} that failed to deserialize: {err}",
+                        entry.name
+                    );
+                    Ok(None)
+                }
+                Err(err) => {
+                    let msg = format!(
+                        "Failed to deserialize metadata of type {}: {err}",
+                        entry.name
+                    );
+                    log::error!("{msg}");
+                    record_load_failure(&msg);
+                    Err(err)
+                }
+            }
+        }
+    }
+
+    /// A [`crate::serdeany::SerdeAny`] that may have been dropped on deserialization, see [`LoadContext`]
+    struct MaybeSerdeAny(Option<Box<dyn SerdeAny>>);
+
+    impl<'de> Deserialize<'de> for MaybeSerdeAny {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer
+                .deserialize_seq(MaybeBoxDynVisitor {})
+                .map(Self)
         }
     }
 
+    /// The [`TypeRepr`] of the concrete type behind a [`SerdeAny`] trait object
+    fn type_repr_of(obj: &dyn SerdeAny) -> TypeRepr {
+        #[cfg(not(feature = "stable_anymap"))]
+        {
+            crate::anymap::unpack_type_id(obj.as_any().type_id())
+        }
+        #[cfg(feature = "stable_anymap")]
+        {
+            alloc::borrow::Cow::Borrowed(obj.type_name())
+        }
+    }
+
+    /// Deserialize the map of a [`SerdeAnyMap`], leaving out dropped elements.
+    /// Elements are keyed by their current type, which may differ from the stored key if the
+    /// type was looked up by name, see [`SchemaHeader`].
+    fn deserialize_map<'de, D>(
+        deserializer: D,
+    ) -> Result<HashMap<TypeRepr, Box<dyn SerdeAny>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let map = HashMap::<TypeRepr, MaybeSerdeAny>::deserialize(deserializer)?;
+        Ok(map
+            .into_values()
+            .filter_map(|obj| obj.0)
+            .map(|obj| (type_repr_of(&*obj), obj))
+            .collect())
+    }
+
+    /// Deserialize the map of a [`NamedSerdeAnyMap`], leaving out dropped elements, see [`deserialize_map`].
+    #[allow(clippy::type_complexity)]
+    fn deserialize_named_map<'de, D>(
+        deserializer: D,
+    ) -> Result<HashMap<TypeRepr, HashMap<String, Box<dyn SerdeAny>>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let map = HashMap::<TypeRepr, HashMap<String, MaybeSerdeAny>>::deserialize(deserializer)?;
+        let mut ret: HashMap<TypeRepr, HashMap<String, Box<dyn SerdeAny>>> = HashMap::default();
+        for (name, obj) in map
+            .into_values()
+            .flatten()
+            .filter_map(|(name, obj)| Some((name, obj.0?)))
+        {
+            ret.entry(type_repr_of(&*obj))
+                .or_default()
+                .insert(name, obj);
+        }
+        Ok(ret)
+    }
+
     #[allow(unused_qualifications)]
     struct Registry {
         deserializers: Option<DeserializeCallbackMap>,
@@ -186,25 +350,72 @@ pub mod serdeany_registry {
         {
             assert!(!self.finalized, "Registry is already finalized!");
 
+            self.entry_mut::<T>();
+        }
+
+        fn entry_mut<T>(&mut self) -> &mut RegistryEntry
+        where
+            T: crate::serdeany::SerdeAny + Serialize + serde::de::DeserializeOwned,
+        {
             let deserializers = self.deserializers.get_or_insert_with(HashMap::default);
-            let _entry = deserializers
+            let entry = deserializers
                 .entry(type_repr_owned::<T>())
-                .or_insert_with(|| {
-                    (
-                        |de| Ok(Box::new(erased_serde::deserialize::<T>(de)?)),
-                        TypeId::of::<T>(),
-                    )
+                .or_insert_with(|| RegistryEntry {
+                    cb: |de| Ok(Box::new(erased_serde::deserialize::<T>(de)?)),
+                    type_id: TypeId::of::<T>(),
+                    name: core::any::type_name::<T>(),
+                    migrations: Vec::new(),
                 });
 
             // We assert that only one element with the given TypeId is in the map.
             // This is only necessary for stable_anymap where we don't directly use the TypeId, but the type_name instead.
             #[cfg(feature = "stable_anymap")]
-            assert_eq!(_entry.1, TypeId::of::<T>(), "Fatal safety error: TypeId of type {} is not equal to the deserializer's TypeId for this type! Two registered types have the same type_name!", type_repr::<T>());
+            assert_eq!(entry.type_id, TypeId::of::<T>(), "Fatal safety error: TypeId of type {} is not equal to the deserializer's TypeId for this type! Two registered types have the same type_name!", type_repr::<T>());
+            entry
+        }
+
+        pub fn register_migration<T>(
+            &mut self,
+            from_schema_version: u32,
+            migrate: DeserializeCallback<dyn SerdeAny>,
+        ) where
+            T: crate::serdeany::SerdeAny + Serialize + serde::de::DeserializeOwned,
+        {
+            assert!(!self.finalized, "Registry is already finalized!");
+
+            let migrations = &mut self.entry_mut::<T>().migrations;
+            migrations.retain(|(from, _)| *from != from_schema_version);
+            migrations.push((from_schema_version, migrate));
         }
 
         pub fn finalize(&mut self) {
             self.finalized = true;
         }
+
+        /// The registered type for `id`, falling back to a lookup by the name the [`LoadContext`] knows for `id`.
+        ///
+        /// Without the `stable_anymap` feature, ids are not stable across builds, but names are.
+        fn entry(&self, id: &TypeRepr, ctx: Option<&LoadContext>) -> Option<&RegistryEntry> {
+            let deserializers = self.deserializers.as_ref()?;
+            deserializers.get(id).or_else(|| {
+                let name = ctx?.type_names.get(id)?;
+                deserializers.values().find(|entry| entry.name == name)
+            })
+        }
+
+        /// The ids and names of all registered types
+        #[cfg(feature = "std")]
+        #[cfg_attr(not(feature = "stable_anymap"), allow(clippy::clone_on_copy))]
+        fn types(&self) -> Vec<(TypeRepr, String)> {
+            let mut types = self
+                .deserializers
+                .iter()
+                .flatten()
+                .map(|(id, entry)| (id.clone(), entry.name.to_string()))
+                .collect::<Vec<_>>();
+            types.sort_by(|a, b| a.1.cmp(&b.1));
+            types
+        }
     }
 
     static mut REGISTRY: Registry = Registry {
@@ -212,6 +423,248 @@ pub mod serdeany_registry {
         finalized: false,
     };
 
+    /// How [`crate::serdeany::SerdeAny`] objects are deserialized while loading a versioned state,
+    /// see [`from_versioned_bytes`].
+    #[derive(Debug, Default)]
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    struct LoadContext {
+        /// The schema version the state was written with, if it differs from the current one
+        schema_version: Option<u32>,
+        /// Drop objects that are unknown or fail to deserialize, instead of failing
+        drop_unknown: bool,
+        /// The names of the types registered when the state was written
+        type_names: HashMap<TypeRepr, String>,
+        /// The first object that failed to deserialize
+        failure: Option<String>,
+    }
+
+    #[cfg(feature = "std")]
+    std::thread_local! {
+        /// The context of the versioned state currently loaded on this thread, see [`from_versioned_bytes`]
+        static LOAD_CONTEXT: RefCell<Option<LoadContext>> = const { RefCell::new(None) };
+        /// If objects are framed while serializing, see [`to_versioned_bytes`]
+        static FRAME_OBJECTS: Cell<bool> = const { Cell::new(false) };
+    }
+
+    /// Run `f` with the context of the versioned state currently loaded on this thread, if any
+    #[cfg(feature = "std")]
+    fn with_load_context<R>(f: impl FnOnce(Option<&mut LoadContext>) -> R) -> R {
+        LOAD_CONTEXT.with(|ctx| f(ctx.borrow_mut().as_mut()))
+    }
+
+    /// Without `std`, versioned states are not supported, and there is never a context
+    #[cfg(not(feature = "std"))]
+    fn with_load_context<R>(f: impl FnOnce(Option<&mut LoadContext>) -> R) -> R {
+        f(None)
+    }
+
+    /// Remember the first object that failed to deserialize, to name it in the error
+    fn record_load_failure(msg: &str) {
+        with_load_context(|ctx| {
+            if let Some(ctx) = ctx {
+                ctx.failure.get_or_insert_with(|| msg.to_string());
+            }
+        });
+    }
+
+    /// If objects are framed as bytes while serializing, so that they can be dropped when loading them fails.
+    ///
+    /// Only versioned states frame their objects, everything else keeps the plain layout.
+    #[must_use]
+    pub(crate) fn frame_objects() -> bool {
+        #[cfg(feature = "std")]
+        {
+            FRAME_OBJECTS.with(Cell::get)
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            false
+        }
+    }
+
+    /// The version of this crate, stored in each [`SchemaHeader`]
+    #[cfg(feature = "std")]
+    const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+    /// A header in front of a serialized state, describing the layout it was written with.
+    ///
+    /// It holds the crate version, a user-supplied campaign schema version, and the names of the
+    /// registered [`crate::serdeany::SerdeAny`] types.
+    /// On load, it is used to pick migrations registered with [`RegistryBuilder::register_migration`],
+    /// and to name the types that fail to deserialize.
+    #[cfg(feature = "std")]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct SchemaHeader {
+        crate_version: String,
+        schema_version: u32,
+        metadata_types: Vec<(TypeRepr, String)>,
+    }
+
+    #[cfg(feature = "std")]
+    impl SchemaHeader {
+        /// Create a header for a state written now, with the given campaign schema version
+        #[must_use]
+        pub fn new(schema_version: u32) -> Self {
+            let registry = &raw const REGISTRY;
+            Self {
+                crate_version: CRATE_VERSION.to_string(),
+                schema_version,
+                metadata_types: unsafe { (*registry).types() },
+            }
+        }
+
+        /// The crate version the state was written with
+        #[must_use]
+        pub fn crate_version(&self) -> &str {
+            &self.crate_version
+        }
+
+        /// The campaign schema version the state was written with
+        #[must_use]
+        pub fn schema_version(&self) -> u32 {
+            self.schema_version
+        }
+
+        /// The names of the metadata types registered when the state was written
+        pub fn metadata_types(&self) -> impl Iterator<Item = &str> {
+            self.metadata_types.iter().map(|(_, name)| name.as_str())
+        }
+
+        /// Get a [`VersionedLoader`] for the records following this header.
+        ///
+        /// Fails if they were written with a newer schema version than the given (current) one.
+        /// With `drop_unknown`, metadata that is unknown or fails to deserialize is dropped.
+        pub fn loader(
+            &self,
+            schema_version: u32,
+            drop_unknown: bool,
+        ) -> Result<VersionedLoader, Error> {
+            Ok(VersionedLoader {
+                ctx: self.load_context(schema_version, drop_unknown)?,
+            })
+        }
+
+        /// Check if a state with this header can be loaded with the given (current) schema version
+        fn load_context(
+            &self,
+            schema_version: u32,
+            drop_unknown: bool,
+        ) -> Result<LoadContext, Error> {
+            if self.schema_version > schema_version {
+                return Err(Error::illegal_state(format!(
+                    "The state was written with schema version {}, newer than the current version {schema_version}",
+                    self.schema_version
+                )));
+            }
+            if self.crate_version != CRATE_VERSION {
+                log::info!(
+                    "Loading a state written by libafl_bolts {} with version {CRATE_VERSION}",
+                    self.crate_version
+                );
+            }
+
+            let ctx = LoadContext {
+                schema_version: (self.schema_version != schema_version)
+                    .then_some(self.schema_version),
+                drop_unknown,
+                type_names: self.metadata_types.iter().cloned().collect(),
+                failure: None,
+            };
+
+            let registry = &raw const REGISTRY;
+            let unknown = self
+                .metadata_types
+                .iter()
+                .filter(|(id, _)| unsafe { (*registry).entry(id, Some(&ctx)).is_none() })
+                .map(|(_, name)| name.as_str())
+                .collect::<Vec<_>>();
+            if !unknown.is_empty() {
+                log::warn!(
+                    "The state was written with metadata types that are not registered anymore: {unknown:?}"
+                );
+            }
+            Ok(ctx)
+        }
+    }
+
+    /// Serialize `val` with `postcard`, prefixed by a [`SchemaHeader`] with the given campaign schema version.
+    ///
+    /// Unlike a plain `postcard` serialization, each [`crate::serdeany::SerdeAny`] object is framed as bytes,
+    /// so that [`from_versioned_bytes`] can drop objects it fails to load.
+    #[cfg(feature = "std")]
+    pub fn to_versioned_bytes<T>(val: &T, schema_version: u32) -> Result<Vec<u8>, Error>
+    where
+        T: Serialize,
+    {
+        let framed = FRAME_OBJECTS.with(|frame| frame.replace(true));
+        let res = postcard::to_allocvec(&(SchemaHeader::new(schema_version), val));
+        FRAME_OBJECTS.with(|frame| frame.set(framed));
+        Ok(res?)
+    }
+
+    /// Deserialize a value written by [`to_versioned_bytes`].
+    ///
+    /// If the stored schema version is older than `schema_version`, the migrations registered for it
+    /// with [`RegistryBuilder::register_migration`] are used.
+    /// If a metadata type is unknown or fails to deserialize, the error names it.
+    /// With `drop_unknown`, such metadata is dropped instead, and loading continues.
+    #[cfg(feature = "std")]
+    pub fn from_versioned_bytes<T>(
+        bytes: &[u8],
+        schema_version: u32,
+        drop_unknown: bool,
+    ) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let (header, bytes) = postcard::take_from_bytes::<SchemaHeader>(bytes)?;
+        header.loader(schema_version, drop_unknown)?.load(bytes)
+    }
+
+    /// Serialize `val` with `postcard` like [`to_versioned_bytes`], but without a [`SchemaHeader`].
+    ///
+    /// Use this for the records of a stream that starts with a single [`SchemaHeader`],
+    /// and read them with the [`VersionedLoader`] of that header.
+    #[cfg(feature = "std")]
+    pub fn to_framed_bytes<T>(val: &T) -> Result<Vec<u8>, Error>
+    where
+        T: Serialize,
+    {
+        let framed = FRAME_OBJECTS.with(|frame| frame.replace(true));
+        let res = postcard::to_allocvec(val);
+        FRAME_OBJECTS.with(|frame| frame.set(framed));
+        Ok(res?)
+    }
+
+    /// Loads the records written after a [`SchemaHeader`], see [`SchemaHeader::loader`]
+    #[cfg(feature = "std")]
+    #[derive(Debug)]
+    pub struct VersionedLoader {
+        ctx: LoadContext,
+    }
+
+    #[cfg(feature = "std")]
+    impl VersionedLoader {
+        /// Deserialize a record written by [`to_framed_bytes`]
+        pub fn load<T>(&mut self, bytes: &[u8]) -> Result<T, Error>
+        where
+            T: DeserializeOwned,
+        {
+            let ctx = core::mem::take(&mut self.ctx);
+            let outer = LOAD_CONTEXT.with(|load_context| load_context.replace(Some(ctx)));
+            let res = postcard::from_bytes::<T>(bytes);
+            let mut ctx = LOAD_CONTEXT
+                .with(|load_context| load_context.replace(outer))
+                .unwrap_or_default();
+            let failure = ctx.failure.take();
+            self.ctx = ctx;
+            res.map_err(|err| match failure {
+                Some(failure) => Error::serialize(failure),
+                None => err.into(),
+            })
+        }
+    }
+
     /// This sugar must be used to register all the structs which
     /// have trait objects that can be serialized and deserialized in the program
     #[derive(Debug)]
@@ -234,6 +687,27 @@ pub mod serdeany_registry {
             }
         }
 
+        /// Register a migration for the type `T`, registering `T` itself if needed.
+        ///
+        /// When loading a state written with the campaign schema version `from_schema_version`,
+        /// see [`from_versioned_bytes`], `migrate` is used instead of the deserializer of `T`.
+        /// It receives the old layout and needs to build the current type from it.
+        ///
+        /// # Safety
+        /// This may never be called concurrently or at the same time as `finalize`.
+        /// It dereferences the `REGISTRY` hashmap and adds the given migration to it.
+        pub unsafe fn register_migration<T>(
+            from_schema_version: u32,
+            migrate: DeserializeCallback<dyn SerdeAny>,
+        ) where
+            T: crate::serdeany::SerdeAny + Serialize + serde::de::DeserializeOwned,
+        {
+            let registry = &raw mut REGISTRY;
+            unsafe {
+                (*registry).register_migration::<T>(from_schema_version, migrate);
+            }
+        }
+
         /// Finalize the registry, no more registrations are allowed after this call
         ///
         /// # Safety
@@ -252,7 +726,12 @@ pub mod serdeany_registry {
     #[allow(clippy::unsafe_derive_deserialize)]
     #[derive(Debug, Serialize, Deserialize)]
     pub struct SerdeAnyMap {
+        #[serde(deserialize_with = "deserialize_map")]
         map: HashMap<TypeRepr, Box<dyn SerdeAny>>,
+        /// Additional elements, stored under a key, so multiple instances of a type can coexist.
+        /// Absent in maps written before keyed elements existed.
+        #[serde(default)]
+        keyed: NamedSerdeAnyMap,
     }
 
     // Cloning by serializing and deserializing. It ain't fast, but it's honest work.
@@ -414,11 +893,83 @@ pub mod serdeany_registry {
             self.map.contains_key(type_repr)
         }
 
+        /// Get an element stored under `key` from the map.
+        #[must_use]
+        #[inline]
+        pub fn get_keyed<T>(&self, key: &str) -> Option<&T>
+        where
+            T: crate::serdeany::SerdeAny,
+        {
+            self.keyed.get::<T>(key)
+        }
+
+        /// Get a mutable borrow for an element stored under `key` in the map.
+        #[must_use]
+        #[inline]
+        pub fn get_keyed_mut<T>(&mut self, key: &str) -> Option<&mut T>
+        where
+            T: crate::serdeany::SerdeAny,
+        {
+            self.keyed.get_mut::<T>(key)
+        }
+
+        /// Insert an element into the map, under `key`.
+        ///
+        /// Elements stored under a key are independent of the element stored for the type itself, see [`Self::insert`].
+        #[inline]
+        pub fn insert_keyed<T>(&mut self, key: &str, t: T)
+        where
+            T: crate::serdeany::SerdeAny,
+        {
+            self.keyed.insert(key, t);
+        }
+
+        /// Remove the element stored under `key` from the map. Returns the removed element.
+        #[must_use]
+        #[inline]
+        pub fn remove_keyed<T>(&mut self, key: &str) -> Option<Box<T>>
+        where
+            T: crate::serdeany::SerdeAny,
+        {
+            self.keyed.remove::<T>(key)
+        }
+
+        /// Returns if the map contains an element of the given type under `key`.
+        #[must_use]
+        #[inline]
+        pub fn contains_keyed<T>(&self, key: &str) -> bool
+        where
+            T: crate::serdeany::SerdeAny,
+        {
+            self.keyed.contains::<T>(key)
+        }
+
+        /// Iterate over all elements in this map, including the ones stored under a key.
+        pub fn values(&self) -> impl Iterator<Item = &dyn SerdeAny> {
+            self.map
+                .values()
+                .chain(self.keyed.map.values().flat_map(HashMap::values))
+                .map(AsRef::as_ref)
+        }
+
+        /// Iterate over the type names and the approximate serialized sizes of all elements in this map.
+        ///
+        /// Each element is serialized when the iterator reaches it, which is slow, but does not clone the map.
+        /// Use this to find out what bloats a state.
+        pub fn sizes(&self) -> impl Iterator<Item = (&'static str, usize)> + '_ {
+            self.values().map(|value| {
+                let size = postcard::to_allocvec(&crate::serdeany::Wrap(value))
+                    .map_or(0, |bytes| bytes.len());
+                (value.type_name(), size)
+            })
+        }
+
         /// Create a new [`SerdeAnyMap`].
         #[must_use]
         pub fn new() -> Self {
             SerdeAnyMap {
                 map: HashMap::default(),
+                keyed: NamedSerdeAnyMap::new(),
             }
         }
     }
@@ -434,6 +985,7 @@ pub mod serdeany_registry {
     #[allow(unused_qualifications)]
     #[derive(Debug, Serialize, Deserialize)]
     pub struct NamedSerdeAnyMap {
+        #[serde(deserialize_with = "deserialize_named_map")]
         map: HashMap<TypeRepr, HashMap<String, Box<dyn crate::serdeany::SerdeAny>>>,
     }
 
@@ -778,7 +1330,15 @@ impl Serialize for dyn crate::serdeany::SerdeAny {
 
         let mut seq = se.serialize_seq(Some(2))?;
         seq.serialize_element(type_id)?;
-        seq.serialize_element(&crate::serdeany::Wrap(self))?;
+        if serdeany_registry::frame_objects() {
+            // A versioned state can not skip over an object it does not know, so we frame it.
+            // This way, a type that fails to deserialize can be dropped, see `from_versioned_bytes`.
+            let bytes = postcard::to_allocvec(&crate::serdeany::Wrap(self))
+                .map_err(serde::ser::Error::custom)?;
+            seq.serialize_element(&bytes)?;
+        } else {
+            seq.serialize_element(&crate::serdeany::Wrap(self))?;
+        }
         seq.end()
     }
 }
@@ -932,9 +1492,14 @@ macro_rules! impl_serdeany {
 
 #[cfg(test)]
 mod tests {
-    use serde::{Deserialize, Serialize};
+    use alloc::{boxed::Box, format, vec::Vec};
+
+    use serde::{ser::SerializeSeq, Deserialize, Serialize};
 
-    use crate::serdeany::RegistryBuilder;
+    use crate::serdeany::{
+        from_versioned_bytes, to_versioned_bytes, NamedSerdeAnyMap, RegistryBuilder, SerdeAnyMap,
+        TypeRepr,
+    };
 
     #[derive(Debug, Serialize, Deserialize)]
     struct MyType(u32);
@@ -964,4 +1529,184 @@ mod tests {
         );
         assert!(postcard::from_bytes::<inner::MyType>(&serialized).is_err());
     }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Migrated(u32);
+    impl_serdeany!(Migrated);
+
+    /// A map in the wire format of [`SerdeAnyMap`], to write an entry for an unregistered type
+    #[derive(Serialize)]
+    struct RawMap<T> {
+        map: hashbrown::HashMap<TypeRepr, RawEntry<T>>,
+        keyed: NamedSerdeAnyMap,
+    }
+
+    /// An object in the wire format of a [`crate::serdeany::SerdeAny`], framed as bytes in a versioned state
+    struct RawEntry<T>(TypeRepr, T);
+
+    impl<T> Serialize for RawEntry<T>
+    where
+        T: Serialize,
+    {
+        fn serialize<S>(&self, se: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            let mut seq = se.serialize_seq(Some(2))?;
+            seq.serialize_element(&self.0)?;
+            seq.serialize_element(&self.1)?;
+            seq.end()
+        }
+    }
+
+    #[test]
+    fn test_plain_layout() {
+        unsafe {
+            RegistryBuilder::register::<MyType>();
+        }
+
+        #[cfg(not(feature = "stable_anymap"))]
+        let id: TypeRepr = crate::anymap::unpack_type_id(core::any::TypeId::of::<MyType>());
+        #[cfg(feature = "stable_anymap")]
+        let id: TypeRepr = core::any::type_name::<MyType>().into();
+
+        let mut map = SerdeAnyMap::new();
+        map.insert(MyType(7));
+        let mut raw = RawMap {
+            map: hashbrown::HashMap::default(),
+            keyed: NamedSerdeAnyMap::new(),
+        };
+        raw.map.insert(id.clone(), RawEntry(id, 7_u32));
+
+        // Outside of versioned states, objects are not framed, and the layout stays as it always was
+        let bytes = postcard::to_allocvec(&map).unwrap();
+        assert_eq!(bytes, postcard::to_allocvec(&raw).unwrap());
+        let loaded: SerdeAnyMap = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.get::<MyType>().unwrap().0, 7);
+    }
+
+    #[test]
+    fn test_versioned_migration() {
+        unsafe {
+            RegistryBuilder::register::<MyType>();
+            RegistryBuilder::register_migration::<Migrated>(1, |de| {
+                let old: u32 = erased_serde::deserialize(de)?;
+                Ok(Box::new(Migrated(old * 2)))
+            });
+        }
+
+        let mut map = SerdeAnyMap::new();
+        map.insert(MyType(3));
+        map.insert(Migrated(21));
+        let bytes = to_versioned_bytes(&map, 1).unwrap();
+
+        // Same schema, no migration
+        let loaded: SerdeAnyMap = from_versioned_bytes(&bytes, 1, false).unwrap();
+        assert_eq!(loaded.get::<Migrated>().unwrap().0, 21);
+
+        // Newer schema, the registered migration runs
+        let loaded: SerdeAnyMap = from_versioned_bytes(&bytes, 2, false).unwrap();
+        assert_eq!(loaded.get::<Migrated>().unwrap().0, 42);
+        assert_eq!(loaded.get::<MyType>().unwrap().0, 3);
+
+        // States from the future are refused
+        let bytes = to_versioned_bytes(&map, 3).unwrap();
+        assert!(from_versioned_bytes::<SerdeAnyMap>(&bytes, 2, false).is_err());
+    }
+
+    #[test]
+    fn test_versioned_drop_unknown() {
+        unsafe {
+            RegistryBuilder::register::<MyType>();
+        }
+
+        #[cfg(not(feature = "stable_anymap"))]
+        let (known, unknown): (TypeRepr, TypeRepr) = (
+            crate::anymap::unpack_type_id(core::any::TypeId::of::<MyType>()),
+            0x1337,
+        );
+        #[cfg(feature = "stable_anymap")]
+        let (known, unknown): (TypeRepr, TypeRepr) = (
+            core::any::type_name::<MyType>().into(),
+            "gone::Metadata".into(),
+        );
+
+        let mut raw = RawMap {
+            map: hashbrown::HashMap::default(),
+            keyed: NamedSerdeAnyMap::new(),
+        };
+        raw.map.insert(
+            known.clone(),
+            RawEntry(known, postcard::to_allocvec(&7_u32).unwrap()),
+        );
+        raw.map.insert(
+            unknown.clone(),
+            RawEntry(unknown, postcard::to_allocvec(&1_u8).unwrap()),
+        );
+        let bytes = to_versioned_bytes(&raw, 0).unwrap();
+
+        let err = from_versioned_bytes::<SerdeAnyMap>(&bytes, 0, false).unwrap_err();
+        assert!(format!("{err:?}").contains("unregistered type"));
+
+        let loaded: SerdeAnyMap = from_versioned_bytes(&bytes, 0, true).unwrap();
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded.get::<MyType>().unwrap().0, 7);
+    }
+
+    #[test]
+    fn test_versioned_names_failing_type() {
+        #[derive(Debug, Serialize, Deserialize)]
+        struct Failing(u32);
+        impl_serdeany!(Failing);
+
+        unsafe {
+            RegistryBuilder::register::<MyType>();
+            RegistryBuilder::register_migration::<Failing>(4, |_de| {
+                Err(serde::de::Error::custom("layout changed"))
+            });
+        }
+
+        let mut map = SerdeAnyMap::new();
+        map.insert(MyType(1));
+        map.insert(Failing(1));
+        let bytes = to_versioned_bytes(&map, 4).unwrap();
+
+        let err = from_versioned_bytes::<SerdeAnyMap>(&bytes, 5, false).unwrap_err();
+        assert!(format!("{err:?}").contains("Failing"));
+
+        let loaded: SerdeAnyMap = from_versioned_bytes(&bytes, 5, true).unwrap();
+        assert!(loaded.get::<Failing>().is_none());
+        assert_eq!(loaded.get::<MyType>().unwrap().0, 1);
+    }
+
+    #[test]
+    fn test_keyed_entries() {
+        unsafe {
+            RegistryBuilder::register::<MyType>();
+        }
+
+        let mut map = SerdeAnyMap::new();
+        map.insert(MyType(1));
+        map.insert_keyed("first", MyType(2));
+        map.insert_keyed("second", MyType(3));
+        assert_eq!(map.get::<MyType>().unwrap().0, 1);
+        assert_eq!(map.get_keyed::<MyType>("first").unwrap().0, 2);
+        map.get_keyed_mut::<MyType>("second").unwrap().0 = 4;
+
+        let loaded: SerdeAnyMap =
+            postcard::from_bytes(&postcard::to_allocvec(&map).unwrap()).unwrap();
+        assert_eq!(loaded.get_keyed::<MyType>("second").unwrap().0, 4);
+
+        let sizes = loaded.sizes().collect::<Vec<_>>();
+        assert_eq!(sizes.len(), 3);
+        assert!(sizes
+            .iter()
+            .all(|(name, size)| name.ends_with("MyType") && *size > 0));
+
+        assert_eq!(map.remove_keyed::<MyType>("first").unwrap().0, 2);
+        assert!(!map.contains_keyed::<MyType>("first"));
+        assert_eq!(map.remove::<MyType>().unwrap().0, 1);
+        assert!(map.contains_keyed::<MyType>("second"));
+        assert_eq!(map.values().count(), 1);
+    }
 }
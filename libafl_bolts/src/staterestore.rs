@@ -1,11 +1,15 @@
 //! Stores and restores state when a client needs to relaunch.
 //! Uses a [`ShMem`] up to a threshold, then write to disk.
-use alloc::string::{String, ToString};
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+};
 use core::{
     hash::{BuildHasher, Hasher},
     marker::PhantomData,
-    mem::size_of,
+    mem::{align_of, size_of},
     ptr, slice,
+    time::Duration,
 };
 use std::{
     env::temp_dir,
@@ -13,12 +17,16 @@ use std::{
     io::{Read, Write},
     path::PathBuf,
     ptr::read_volatile,
+    time::Instant,
 };
 
 use ahash::RandomState;
 use serde::{de::DeserializeOwned, Serialize};
 
+#[cfg(feature = "gzip")]
+use crate::compress::GzipCompressor;
 use crate::{
+    serdeany::{from_versioned_bytes, to_versioned_bytes},
     shmem::{ShMem, ShMemProvider},
     AsSlice, Error,
 };
@@ -30,7 +38,17 @@ const EXITING_MAGIC: &[u8; 16] = b"LIBAFL_EXIT_NOW\0";
 #[repr(C)]
 struct StateShMemContent {
     is_disk: bool,
+    /// If the stored bytes are gzip compressed
+    is_compressed: bool,
     buf_len: usize,
+    /// The length of the serialized state, before compression
+    state_len: usize,
+    /// The length of the stored bytes, in the map or in the tmpfile
+    stored_len: usize,
+    /// The checksum of the stored bytes
+    checksum: u64,
+    /// How long saving took, in microseconds
+    save_micros: u64,
     buf: [u8; 0],
 }
 
@@ -59,10 +77,34 @@ impl StateShMemContent {
     }
 }
 
+/// The checksum over the stored bytes of a state
+fn checksum(bytes: &[u8]) -> u64 {
+    let mut hasher = RandomState::with_seeds(0, 0, 0, 0).build_hasher();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+/// Information about the state snapshot stored in a [`StateRestorer`], see [`StateRestorer::snapshot_stats`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotStats {
+    /// The size of the serialized state
+    pub state_len: usize,
+    /// The size of the stored bytes, after compression
+    pub stored_len: usize,
+    /// If the snapshot was compressed
+    pub compressed: bool,
+    /// If the snapshot was too large for the shared map and spilled to a tmpfile
+    pub on_disk: bool,
+    /// How long serializing and storing the snapshot took
+    pub save_time: Duration,
+}
+
 /// A [`StateRestorer`] saves and restores bytes to a shared map.
 ///
 /// If the state gets larger than the preallocated [`ShMem`] shared map,
 /// it will instead write to disk, and store the file name into the map.
+/// The stored bytes can be compressed, see [`StateRestorer::set_compression`],
+/// and are verified with a checksum on restore.
 /// Writing to [`StateRestorer`] multiple times is not allowed.
 #[derive(Debug, Clone)]
 pub struct StateRestorer<SP>
@@ -70,6 +112,10 @@ where
     SP: ShMemProvider,
 {
     shmem: SP::ShMem,
+    #[cfg(feature = "gzip")]
+    compress: bool,
+    schema_version: u32,
+    drop_unknown_metadata: bool,
     phantom: PhantomData<*const SP>,
 }
 
@@ -91,6 +137,10 @@ where
     pub fn from_env(shmem_provider: &mut SP, env_name: &str) -> Result<Self, Error> {
         Ok(Self {
             shmem: shmem_provider.existing_from_env(env_name)?,
+            #[cfg(feature = "gzip")]
+            compress: false,
+            schema_version: 0,
+            drop_unknown_metadata: false,
             phantom: PhantomData,
         })
     }
@@ -99,13 +149,42 @@ where
     pub fn new(shmem: SP::ShMem) -> Self {
         let mut ret = Self {
             shmem,
+            #[cfg(feature = "gzip")]
+            compress: false,
+            schema_version: 0,
+            drop_unknown_metadata: false,
             phantom: PhantomData,
         };
         ret.reset();
         ret
     }
 
+    /// Gzip-compress the serialized state before storing it, off by default.
+    ///
+    /// Makes large states cheaper to hand over, at the cost of some time to compress.
+    /// A state spilled to a tmpfile is then a gzip file, readable with `gzip -d`.
+    #[cfg(feature = "gzip")]
+    pub fn set_compression(&mut self, compress: bool) {
+        self.compress = compress;
+    }
+
+    /// Set the campaign schema version stored with, and expected from, the state. Defaults to `0`.
+    ///
+    /// Bump it when the layout of some metadata changes, and register migrations from the old version
+    /// with [`crate::serdeany::RegistryBuilder::register_migration`].
+    pub fn set_schema_version(&mut self, schema_version: u32) {
+        self.schema_version = schema_version;
+    }
+
+    /// On restore, drop metadata that is unknown to this binary, or fails to deserialize,
+    /// instead of failing. Off by default.
+    pub fn set_drop_unknown_metadata(&mut self, drop_unknown_metadata: bool) {
+        self.drop_unknown_metadata = drop_unknown_metadata;
+    }
+
     /// Saves a state to the connected [`ShMem`], or a tmpfile, if its serialized size get too large.
+    ///
+    /// The state is prefixed with a [`crate::serdeany::SchemaHeader`], see [`StateRestorer::set_schema_version`].
     pub fn save<S>(&mut self, state: &S) -> Result<(), Error>
     where
         S: Serialize,
@@ -116,7 +195,18 @@ where
             ));
         }
 
-        let serialized = postcard::to_allocvec(state)?;
+        let start = Instant::now();
+        let serialized = to_versioned_bytes(state, self.schema_version)?;
+        let state_len = serialized.len();
+        #[cfg(feature = "gzip")]
+        let (serialized, is_compressed) = if self.compress {
+            (GzipCompressor::new().compress_gzip(&serialized), true)
+        } else {
+            (serialized, false)
+        };
+        #[cfg(not(feature = "gzip"))]
+        let is_compressed = false;
+        let checksum = checksum(&serialized);
 
         if size_of::<StateShMemContent>() + serialized.len() > self.shmem.len() {
             // generate a filename
@@ -169,9 +259,32 @@ where
             shmem_content.buf_len = len;
             shmem_content.is_disk = false;
         };
+
+        let save_micros = start.elapsed().as_micros().try_into().unwrap_or(u64::MAX);
+        let shmem_content = self.content_mut();
+        shmem_content.is_compressed = is_compressed;
+        shmem_content.state_len = state_len;
+        shmem_content.stored_len = serialized.len();
+        shmem_content.checksum = checksum;
+        shmem_content.save_micros = save_micros;
         Ok(())
     }
 
+    /// Information about the stored snapshot, if there is one
+    pub fn snapshot_stats(&self) -> Option<SnapshotStats> {
+        if !self.has_content() || self.wants_to_exit() {
+            return None;
+        }
+        let content = self.content();
+        Some(SnapshotStats {
+            state_len: content.state_len,
+            stored_len: content.stored_len,
+            compressed: content.is_compressed,
+            on_disk: content.is_disk,
+            save_time: Duration::from_micros(content.save_micros),
+        })
+    }
+
     /// Reset this [`StateRestorer`] to an empty state.
     pub fn reset(&mut self) {
         let mapsize = self.mapsize();
@@ -181,6 +294,7 @@ where
             drop(fs::remove_file(tmpfile));
         }
         content_mut.is_disk = false;
+        content_mut.is_compressed = false;
         content_mut.buf_len = 0;
     }
 
@@ -212,7 +326,7 @@ where
     fn content_mut(&mut self) -> &mut StateShMemContent {
         let ptr = self.shmem.as_slice().as_ptr();
         debug_assert_eq!(
-            ptr.align_offset(size_of::<StateShMemContent>()),
+            ptr.align_offset(align_of::<StateShMemContent>()),
             0,
             "Beginning of the page is not aligned at {ptr:?}!"
         );
@@ -235,7 +349,11 @@ where
     }
 
     /// Restores the contents saved in this [`StateRestorer`], if any are available.
-    /// Can only be read once.
+    /// Can only be read once, [`StateRestorer::reset`] removes the tmpfile afterwards.
+    ///
+    /// Fails if the stored bytes do not match their checksum. A corrupted tmpfile is removed right away.
+    /// Fails with an error naming the metadata type if some metadata can not be deserialized,
+    /// unless [`StateRestorer::set_drop_unknown_metadata`] is set.
     pub fn restore<S>(&self) -> Result<Option<S>, Error>
     where
         S: DeserializeOwned,
@@ -259,13 +377,14 @@ where
 
         let mut state = bytes;
         let mut file_content;
+        let mut tmpfile = None;
         if state_shmem_content.buf_len == 0 {
             return Ok(None);
         } else if state_shmem_content.is_disk {
             let filename: String = postcard::from_bytes(bytes)?;
-            let tmpfile = temp_dir().join(&filename);
+            let path = temp_dir().join(&filename);
             file_content = vec![];
-            File::open(tmpfile)?.read_to_end(&mut file_content)?;
+            File::open(&path)?.read_to_end(&mut file_content)?;
             if file_content.is_empty() {
                 return Err(Error::illegal_state(format!(
                     "Colud not restore state from file {}",
@@ -273,8 +392,34 @@ where
                 )));
             }
             state = &file_content;
+            tmpfile = Some(path);
         }
-        let deserialized = postcard::from_bytes(state)?;
+
+        if checksum(state) != state_shmem_content.checksum {
+            if let Some(tmpfile) = tmpfile {
+                drop(fs::remove_file(tmpfile));
+            }
+            return Err(Error::illegal_state(
+                "The checksum of the stored state does not match, state corrupted?",
+            ));
+        }
+
+        let state: Cow<[u8]> = if state_shmem_content.is_compressed {
+            #[cfg(feature = "gzip")]
+            {
+                Cow::Owned(GzipCompressor::new().decompress_gzip(state)?)
+            }
+            #[cfg(not(feature = "gzip"))]
+            {
+                return Err(Error::illegal_state(
+                    "The stored state is compressed, but the gzip feature is disabled",
+                ));
+            }
+        } else {
+            Cow::Borrowed(state)
+        };
+        let deserialized =
+            from_versioned_bytes(&state, self.schema_version, self.drop_unknown_metadata)?;
         Ok(Some(deserialized))
     }
 }
@@ -346,4 +491,101 @@ mod tests {
         assert!(!state_restorer.has_content());
         assert!(!tmpfile.exists());
     }
+
+    #[test]
+    #[serial]
+    #[cfg_attr(miri, ignore)]
+    #[cfg(not(target_os = "haiku"))]
+    fn test_state_restore_checksum() {
+        use alloc::vec::Vec;
+        use std::fs;
+
+        use crate::{
+            shmem::{ShMemProvider, StdShMemProvider},
+            staterestore::StateRestorer,
+        };
+
+        const TESTMAP_SIZE: usize = 1024;
+
+        let mut shmem_provider = StdShMemProvider::new().unwrap();
+        let shmem = shmem_provider.new_shmem(TESTMAP_SIZE).unwrap();
+        let mut state_restorer = StateRestorer::<StdShMemProvider>::new(shmem);
+
+        // Corrupted bytes in the map
+        state_restorer.save(&vec![1u8; 16]).unwrap();
+        let stats = state_restorer.snapshot_stats().unwrap();
+        assert!(!stats.on_disk);
+        assert!(!stats.compressed);
+        unsafe {
+            *state_restorer.content_mut().buf.as_mut_ptr().add(4) = 2;
+        }
+        assert!(state_restorer.restore::<Vec<u8>>().is_err());
+        state_restorer.reset();
+
+        // A corrupted tmpfile is removed
+        let too_large = (0..TESTMAP_SIZE * 2)
+            .map(|i| (i * 7) as u8)
+            .collect::<Vec<_>>();
+        state_restorer.save(&too_large).unwrap();
+        assert!(state_restorer.snapshot_stats().unwrap().on_disk);
+        let tmpfile = state_restorer
+            .content()
+            .tmpfile(state_restorer.mapsize())
+            .unwrap()
+            .unwrap();
+        fs::write(&tmpfile, b"garbage").unwrap();
+        assert!(state_restorer.restore::<Vec<u8>>().is_err());
+        assert!(!tmpfile.exists());
+        state_restorer.reset();
+        assert!(state_restorer.snapshot_stats().is_none());
+    }
+
+    #[test]
+    #[serial]
+    #[cfg_attr(miri, ignore)]
+    #[cfg(all(feature = "gzip", not(target_os = "haiku")))]
+    fn test_state_restore_compressed() {
+        use alloc::vec::Vec;
+
+        use crate::{
+            rands::{Rand, StdRand},
+            shmem::{ShMemProvider, StdShMemProvider},
+            staterestore::StateRestorer,
+        };
+
+        const TESTMAP_SIZE: usize = 1024;
+
+        let mut shmem_provider = StdShMemProvider::new().unwrap();
+        let shmem = shmem_provider.new_shmem(TESTMAP_SIZE).unwrap();
+        let mut state_restorer = StateRestorer::<StdShMemProvider>::new(shmem);
+        state_restorer.set_compression(true);
+
+        // Too large for the map, unless compressed
+        let state = vec![4u8; TESTMAP_SIZE * 4];
+        state_restorer.save(&state).unwrap();
+        let stats = state_restorer.snapshot_stats().unwrap();
+        assert!(stats.compressed);
+        assert!(!stats.on_disk);
+        assert!(stats.stored_len < stats.state_len);
+
+        assert_eq!(state_restorer.restore::<Vec<u8>>().unwrap().unwrap(), state);
+        state_restorer.reset();
+
+        // Too large for the map even when compressed, spilled as a gzip file
+        let mut rand = StdRand::with_seed(0);
+        let state = (0..TESTMAP_SIZE * 4)
+            .map(|_| rand.next() as u8)
+            .collect::<Vec<_>>();
+        state_restorer.save(&state).unwrap();
+        assert!(state_restorer.snapshot_stats().unwrap().on_disk);
+        let tmpfile = state_restorer
+            .content()
+            .tmpfile(state_restorer.mapsize())
+            .unwrap()
+            .unwrap();
+        assert_eq!(std::fs::read(&tmpfile).unwrap()[..2], [0x1f, 0x8b]);
+        assert_eq!(state_restorer.restore::<Vec<u8>>().unwrap().unwrap(), state);
+        state_restorer.reset();
+        assert!(!tmpfile.exists());
+    }
 }
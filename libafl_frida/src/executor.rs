@@ -1,4 +1,7 @@
-use core::fmt::{self, Debug, Formatter};
+use core::{
+    fmt::{self, Debug, Formatter},
+    time::Duration,
+};
 #[cfg(all(windows, not(test)))]
 use std::process::abort;
 use std::{ffi::c_void, marker::PhantomData};
@@ -14,7 +17,7 @@ use libafl::{
     state::{HasCorpus, HasSolutions},
 };
 use libafl::{
-    executors::{Executor, ExitKind, HasObservers, InProcessExecutor},
+    executors::{Executor, ExitKind, HasObservers, HasTimeout, InProcessExecutor},
     inputs::{HasTargetBytes, NopTargetBytesConverter, TargetBytesConverter},
     observers::ObserversTuple,
     state::{HasExecutions, State, UsesState},
@@ -151,6 +154,24 @@ where
     }
 }
 
+impl<H, OT, RT, S, TC> HasTimeout for FridaInProcessExecutor<'_, '_, '_, H, OT, RT, S, TC>
+where
+    H: FnMut(&S::Input) -> ExitKind,
+    TC: TargetBytesConverter<Input = S::Input>,
+    S: State,
+    OT: ObserversTuple<S::Input, S>,
+{
+    #[inline]
+    fn timeout(&self) -> Duration {
+        self.base.timeout()
+    }
+
+    #[inline]
+    fn set_timeout(&mut self, timeout: Duration) {
+        self.base.set_timeout(timeout);
+    }
+}
+
 impl<'a, 'b, 'c, H, OT, RT, S>
     FridaInProcessExecutor<'a, 'b, 'c, H, OT, RT, S, NopTargetBytesConverter<S::Input>>
 where
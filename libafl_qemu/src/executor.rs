@@ -16,7 +16,7 @@ use libafl::{
         hooks::inprocess::InProcessExecutorHandlerData,
         inprocess::{stateful::StatefulInProcessExecutor, HasInProcessHooks},
         inprocess_fork::stateful::StatefulInProcessForkExecutor,
-        Executor, ExitKind, HasObservers,
+        Executor, ExitKind, HasObservers, HasTimeout,
     },
     feedbacks::Feedback,
     fuzzer::HasObjective,
@@ -303,6 +303,25 @@ where
     }
 }
 
+impl<CM, ED, ET, H, OT, S, SM> HasTimeout for QemuExecutor<'_, CM, ED, ET, H, OT, S, SM>
+where
+    CM: CommandManager<ED, ET, S, SM>,
+    ET: EmulatorModuleTuple<S>,
+    H: FnMut(&mut Emulator<CM, ED, ET, S, SM>, &mut S, &S::Input) -> ExitKind,
+    OT: ObserversTuple<S::Input, S>,
+    S: State,
+{
+    #[inline]
+    fn timeout(&self) -> Duration {
+        self.inner.timeout()
+    }
+
+    #[inline]
+    fn set_timeout(&mut self, timeout: Duration) {
+        self.inner.set_timeout(timeout);
+    }
+}
+
 pub type QemuInProcessForkExecutor<'a, CM, ED, EM, ET, H, OT, S, SM, SP, Z> =
     StatefulInProcessForkExecutor<'a, H, OT, S, SP, Emulator<CM, ED, ET, S, SM>, EM, Z>;
 
@@ -477,3 +496,26 @@ where
         self.inner.observers_mut()
     }
 }
+
+#[cfg(feature = "fork")]
+impl<CM, ED, EM, ET, H, OT, S, SM, SP, Z> HasTimeout
+    for QemuForkExecutor<'_, CM, ED, EM, ET, H, OT, S, SM, SP, Z>
+where
+    CM: CommandManager<ED, ET, S, SM>,
+    EM: UsesState<State = S>,
+    ET: EmulatorModuleTuple<S>,
+    H: FnMut(&mut Emulator<CM, ED, ET, S, SM>, &S::Input) -> ExitKind + ?Sized,
+    OT: ObserversTuple<S::Input, S>,
+    S: State,
+    SP: ShMemProvider,
+{
+    #[inline]
+    fn timeout(&self) -> Duration {
+        self.inner.timeout()
+    }
+
+    #[inline]
+    fn set_timeout(&mut self, timeout: Duration) {
+        self.inner.set_timeout(timeout);
+    }
+}
@@ -0,0 +1,62 @@
+//! Compare the full and the lazy deserialization of observers on a main node
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use libafl::{
+    corpus::InMemoryCorpus,
+    feedbacks::{MapFeedbackMetadata, MapObserverFeedbacksMetadata},
+    inputs::BytesInput,
+    observers::{LazyObserversTuple, StdMapObserver},
+    state::StdState,
+    HasNamedMetadata,
+};
+use libafl_bolts::{rands::StdRand, tuples::tuple_list};
+
+const MAP_SIZE: usize = 1 << 16;
+const OTHER_SIZE: usize = 1 << 20;
+
+type Observers = (
+    StdMapObserver<'static, u8, false>,
+    (StdMapObserver<'static, u8, false>, ()),
+);
+type BenchState =
+    StdState<BytesInput, InMemoryCorpus<BytesInput>, StdRand, InMemoryCorpus<BytesInput>>;
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut state: BenchState = StdState::nop().unwrap();
+    // Every entry was seen before by the maximizing feedback of the map, so no testcase has new coverage
+    state.add_named_metadata(
+        "map",
+        MapFeedbackMetadata::with_history_map(vec![u8::MAX; MAP_SIZE], 0),
+    );
+    state.add_named_metadata(
+        "map",
+        MapObserverFeedbacksMetadata {
+            maximizing: vec!["map".into()],
+            other: false,
+        },
+    );
+
+    let observers: Observers = tuple_list!(
+        StdMapObserver::owned("map", vec![1; MAP_SIZE]),
+        StdMapObserver::owned("other", vec![1; OTHER_SIZE])
+    );
+    let bytes = postcard::to_allocvec(&observers).unwrap();
+
+    c.bench_function("eager_observers", |b| {
+        b.iter(|| black_box(postcard::from_bytes::<Observers>(black_box(&bytes)).unwrap()));
+    });
+    c.bench_function("lazy_observers", |b| {
+        b.iter(|| {
+            black_box(
+                <Observers as LazyObserversTuple<BytesInput, BenchState>>::take_lazy(
+                    black_box(&bytes),
+                    &state,
+                )
+                .unwrap(),
+            )
+        });
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);